@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Measure cost of error construction in activation-heavy explorations
+//!
+//! Explores a function which ignores errors of a number of independent
+//! operations, so every combination of failpoints is activated, and
+//! prints wall time for ways to name failpoints and to produce
+//! injected errors:
+//!
+//! ```sh
+//! cargo run --release --example activation_benchmark
+//! ```
+
+use std::io;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use faine::{FailpointId, InjectedError, ResultExt, Runner, inject_return_io_error};
+
+const NAMES: [&str; 14] = [
+    "op 0", "op 1", "op 2", "op 3", "op 4", "op 5", "op 6", "op 7", "op 8", "op 9", "op 10",
+    "op 11", "op 12", "op 13",
+];
+
+static IDS: LazyLock<Vec<FailpointId>> =
+    LazyLock::new(|| NAMES.iter().map(|name| FailpointId::intern(name)).collect());
+
+#[derive(Debug)]
+enum StoreError {
+    Io(#[allow(dead_code)] io::Error),
+    Named(#[allow(dead_code)] &'static str),
+    Injected(#[allow(dead_code)] InjectedError),
+}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+fn io_operation(index: usize) -> io::Result<()> {
+    inject_return_io_error!(NAMES[index]);
+    Ok(())
+}
+
+fn io_operation_by_id(index: usize) -> io::Result<()> {
+    inject_return_io_error!(IDS[index]);
+    Ok(())
+}
+
+fn store_operation(index: usize) -> Result<(), StoreError> {
+    Ok(()).faine(NAMES[index])
+}
+
+type Operation = fn(usize) -> bool;
+type Case = (&'static str, fn() -> Runner, Operation);
+
+fn explore(runner: Runner, operation: Operation) -> (usize, Duration) {
+    let start = Instant::now();
+    let report = runner
+        .run(|| {
+            let failed = (0..NAMES.len()).filter(|index| !operation(*index)).count();
+            assert!(failed <= NAMES.len());
+        })
+        .unwrap();
+    (report.paths().len(), start.elapsed())
+}
+
+fn main() {
+    let io = |index| io_operation(index).is_ok();
+    let io_by_id = |index| io_operation_by_id(index).is_ok();
+    let store = |index| store_operation(index).is_ok();
+
+    let cases: [Case; 7] = [
+        ("io errors", Runner::ignore_env, io),
+        ("io errors, interned ids", Runner::ignore_env, io_by_id),
+        (
+            "io errors, tagged",
+            || Runner::ignore_env().with_error_tagging(true),
+            io,
+        ),
+        (
+            "named factory",
+            || Runner::ignore_env().with_error_factory(StoreError::Named),
+            store,
+        ),
+        (
+            "named factory, tagged",
+            || {
+                Runner::ignore_env()
+                    .with_error_tagging(true)
+                    .with_error_factory(StoreError::Named)
+            },
+            store,
+        ),
+        (
+            "injected error factory",
+            || Runner::ignore_env().with_injected_error_factory(StoreError::Injected),
+            store,
+        ),
+        (
+            "injected error factory, tagged",
+            || {
+                Runner::ignore_env()
+                    .with_error_tagging(true)
+                    .with_injected_error_factory(StoreError::Injected)
+            },
+            store,
+        ),
+    ];
+
+    for (title, runner, operation) in cases {
+        let (paths, time) = explore(runner(), operation);
+        println!("{title}: {paths} paths in {time:.2?}");
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use faine::inject_override_async_io_error;
+
+/// Future which becomes ready after a given number of polls
+pub struct Delay(pub usize);
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 == 0 {
+            Poll::Ready(())
+        } else {
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Future which resolves to the output of whichever of two futures completes first
+pub struct Race<A, B> {
+    a: Pin<Box<A>>,
+    b: Pin<Box<B>>,
+}
+
+impl<T, A: Future<Output = T>, B: Future<Output = T>> Future for Race<A, B> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        if let Poll::Ready(value) = self.a.as_mut().poll(cx) {
+            return Poll::Ready(value);
+        }
+        self.b.as_mut().poll(cx)
+    }
+}
+
+pub fn race<A: Future, B: Future<Output = A::Output>>(a: A, b: B) -> Race<A, B> {
+    Race {
+        a: Box::pin(a),
+        b: Box::pin(b),
+    }
+}
+
+/// Simulated read from a replica, which takes some time
+pub async fn read(replica: &str, latency: usize) -> io::Result<String> {
+    Delay(latency).await;
+    Ok(format!("data from {replica}"))
+}
+
+/// Read from primary replica, falling back to secondary one
+pub async fn read_with_fallback() -> io::Result<String> {
+    match inject_override_async_io_error!(read("primary", 2), "read primary").await {
+        Ok(data) => Ok(data),
+        Err(_) => inject_override_async_io_error!(read("secondary", 1), "read secondary").await,
+    }
+}
+
+/// Read from both replicas, returning whichever responds first
+pub async fn read_fastest() -> io::Result<String> {
+    race(
+        inject_override_async_io_error!(read("primary", 2), "read primary"),
+        inject_override_async_io_error!(read("secondary", 1), "read secondary"),
+    )
+    .await
+}
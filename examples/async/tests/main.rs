@@ -0,0 +1,87 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use faine::{Runner, assert_injected_by};
+use faine_async_example::{read_fastest, read_with_fallback};
+
+/// Run futures to completion on the current thread
+///
+/// Futures passed after the first one are spawned tasks, which are
+/// polled along with it. Output of the first one is returned.
+fn block_on<T>(
+    main: impl Future<Output = T>,
+    mut tasks: Vec<Pin<Box<dyn Future<Output = ()>>>>,
+) -> T {
+    let mut main = Box::pin(main);
+    let mut output = None;
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        tasks.retain_mut(|task| task.as_mut().poll(&mut cx).is_pending());
+        if output.is_none()
+            && let Poll::Ready(value) = main.as_mut().poll(&mut cx)
+        {
+            output = Some(value);
+        }
+        if tasks.is_empty()
+            && let Some(value) = output.take()
+        {
+            return value;
+        }
+    }
+}
+
+#[test]
+fn test_await() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| results.push(block_on(read_with_fallback(), vec![])))
+        .unwrap();
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .map(|path| path.to_string())
+            .collect::<Vec<_>>(),
+        [
+            "[read primary] → [read secondary]",
+            "[read primary] → read secondary",
+            "read primary",
+        ]
+    );
+    assert_injected_by!(results[0], "read secondary");
+    assert_eq!(results[1].as_ref().unwrap(), "data from secondary");
+    assert_eq!(results[2].as_ref().unwrap(), "data from primary");
+}
+
+#[test]
+fn test_select() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| results.push(block_on(read_fastest(), vec![])))
+        .unwrap();
+    assert_eq!(report.num_paths(), 4);
+    // injected errors are ready immediately, and win the race
+    assert_injected_by!(results[0], "read primary");
+    assert_injected_by!(results[1], "read primary");
+    assert_injected_by!(results[2], "read secondary");
+    assert_eq!(results[3].as_ref().unwrap(), "data from secondary");
+}
+
+#[test]
+fn test_spawned_tasks() {
+    let report = Runner::default()
+        .run(|| {
+            let task = Box::pin(async {
+                let _ = read_with_fallback().await;
+            });
+            let _ = block_on(read_fastest(), vec![task]);
+        })
+        .unwrap();
+    // failpoints in the spawned task are visited while it's polled
+    assert!(report.num_paths() > 4);
+    assert!(report.paths().iter().all(|path| path.steps.len() >= 3));
+}
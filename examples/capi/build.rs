@@ -0,0 +1,7 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+fn main() {
+    println!("cargo::rerun-if-changed=csrc/blocks.c");
+    cc::Build::new().file("csrc/blocks.c").compile("blocks");
+}
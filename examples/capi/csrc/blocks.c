@@ -0,0 +1,20 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+#include <errno.h>
+#include <string.h>
+
+int faine_visit(const char* name);
+int faine_enabled(void);
+
+/* I/O shim which may fail reading or verifying a block */
+int read_block(char* buf, int size) {
+	if (faine_visit("c_read_block"))
+		return EIO;
+	memset(buf, 'x', size);
+	if (faine_enabled() && faine_visit("c_verify_block"))
+		return EILSEQ;
+	return 0;
+}
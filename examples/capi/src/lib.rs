@@ -0,0 +1,21 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::ffi::{c_char, c_int};
+
+// keep faine linked, as it provides symbols used by the C code
+use faine as _;
+
+unsafe extern "C" {
+    fn read_block(buf: *mut c_char, size: c_int) -> c_int;
+}
+
+/// Read a block through the C shim, returning errno on failure
+pub fn read(size: usize) -> Result<Vec<u8>, i32> {
+    let mut buf = vec![0u8; size];
+    // SAFETY: buffer is valid for `size` bytes
+    match unsafe { read_block(buf.as_mut_ptr().cast(), size as c_int) } {
+        0 => Ok(buf),
+        errno => Err(errno),
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::thread;
+
+use faine::{Runner, inject_return};
+use faine_capi_example::read;
+
+// same value on Linux, macOS and BSDs
+const EIO: i32 = 5;
+
+fn read_twice() -> Result<(), i32> {
+    read(4)?;
+    inject_return!("rust_check", Err(0));
+    read(4)?;
+    Ok(())
+}
+
+#[test]
+fn test_c_failpoints() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| results.push(read_twice()))
+        .unwrap();
+
+    let mut failpoints: Vec<_> = report.activated_failpoints().into_iter().collect();
+    failpoints.sort();
+    assert_eq!(failpoints, ["c_read_block", "c_verify_block", "rust_check"]);
+    assert!(results.contains(&Err(EIO)));
+    assert!(results.contains(&Err(0)));
+    assert!(results.contains(&Ok(())));
+}
+
+#[test]
+fn test_outside_of_runner() {
+    assert_eq!(read(4), Ok(vec![b'x'; 4]));
+    Runner::default()
+        .run(|| {
+            // failpoints are not processed on other threads
+            let res = thread::scope(|scope| scope.spawn(|| read(4)).join().unwrap());
+            assert!(res.is_ok());
+        })
+        .unwrap();
+}
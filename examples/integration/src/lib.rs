@@ -0,0 +1,52 @@
+use std::fs::{File, rename};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Lets tests check that `faine` feature is enabled
+#[cfg(feature = "faine")]
+faine::declare_instrumented!();
+
+/// Failpoint which is only compiled in with `faine` feature
+macro_rules! failpoint {
+    ($name:literal) => {
+        #[cfg(feature = "faine")]
+        faine::inject_return_io_error!($name);
+    };
+}
+
+/// Atomically replace file contents
+pub fn replace_file(path: &Path, content: &str) -> io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    {
+        failpoint!("create temp file");
+        let mut file = File::create(&temp_path)?;
+        failpoint!("write temp file");
+        file.write_all(content.as_bytes())?;
+    }
+    failpoint!("replace file");
+    rename(&temp_path, path)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "faine"))]
+mod tests {
+    use std::fs::read_to_string;
+
+    use faine::Runner;
+
+    use super::*;
+
+    #[test]
+    fn test_replace_file_unit() {
+        Runner::default()
+            .run(|| {
+                let tempdir = tempfile::tempdir().unwrap();
+                let path = tempdir.path().join("myfile");
+                std::fs::write(&path, "old").unwrap();
+                let res = replace_file(&path, "new");
+                let contents = read_to_string(&path).unwrap();
+                assert!(res.is_ok() && contents == "new" || res.is_err() && contents == "old");
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,34 @@
+use std::fs::read_to_string;
+
+use faine::testsupport::{assert_all_failpoints_activated, assert_explores_at_least};
+use faine::{Runner, assert_injected_by};
+use faine_integration_example::replace_file;
+
+// fail to compile, instead of testing nothing, without `faine` feature
+faine::assert_instrumented!(faine_integration_example);
+
+fn check_replace_file() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("myfile");
+    std::fs::write(&path, "old").unwrap();
+    let res = replace_file(&path, "new");
+    let contents = read_to_string(&path).unwrap();
+    if res.is_err() {
+        assert_injected_by!(res, prefix = "");
+        assert_eq!(contents, "old");
+    } else {
+        assert_eq!(contents, "new");
+    }
+}
+
+#[test]
+fn test_replace_file_is_atomic() {
+    // 3 failpoints, each may fail, plus successful path
+    assert_explores_at_least(4, Runner::default(), check_replace_file);
+}
+
+#[test]
+fn test_replace_file_failpoints_are_reachable() {
+    let report = assert_all_failpoints_activated(Runner::default(), check_replace_file);
+    assert_eq!(report.failpoints().len(), 3);
+}
@@ -0,0 +1,41 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Render exploration results as markdown for a pull request comment
+//!
+//! A CI job may run this and post its output with any bot, or append
+//! it to `$GITHUB_STEP_SUMMARY`:
+//!
+//! ```sh
+//! cargo run --example markdown_summary >> "$GITHUB_STEP_SUMMARY"
+//! ```
+
+use std::io;
+
+use faine::{Error, MarkdownOptions, Runner, inject_return_io_error};
+
+fn save(data: &mut Vec<u8>) -> io::Result<()> {
+    inject_return_io_error!("open");
+    data.clear();
+    inject_return_io_error!("write");
+    data.extend_from_slice(b"saved");
+    Ok(())
+}
+
+fn main() {
+    let report = match Runner::default()
+        .with_catch_panics(true)
+        .run_with_state(b"old".to_vec(), |data| {
+            if save(data).is_err() {
+                // data is lost if write fails after truncation
+                assert!(data == b"old", "data lost");
+            }
+        })
+        .into_result()
+    {
+        Ok(report) => report,
+        Err(Error::UnexpectedPanics { report, .. }) => *report,
+        Err(err) => panic!("{err}"),
+    };
+    print!("{}", report.to_markdown(&MarkdownOptions::default()));
+}
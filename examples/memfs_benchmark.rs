@@ -0,0 +1,78 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Compare exploration over the real filesystem and over `MemFs`
+//!
+//! Explores atomic replacement of a number of files with both backends,
+//! checks that explored paths are identical, and prints wall time:
+//!
+//! ```sh
+//! cargo run --release --features fs --example memfs_benchmark
+//! ```
+
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use faine::Runner;
+use faine::fs::{FsBackend, MemFs, RealFs};
+
+const NUM_FILES: usize = 50;
+
+fn replace_all<F: FsBackend>(fs: &F, dir: &Path) -> io::Result<()> {
+    for i in 0..NUM_FILES {
+        let (tmp, target) = (dir.join(format!("{i}.tmp")), dir.join(i.to_string()));
+        fs.write(&tmp, b"new")?;
+        fs.sync_all(&tmp)?;
+        fs.rename(&tmp, &target)?;
+    }
+    fs.sync_all(dir)
+}
+
+fn explore<F: FsBackend>(dir: &Path, prepare: impl Fn() -> F) -> (Vec<String>, Duration) {
+    let start = Instant::now();
+    let report = Runner::ignore_env()
+        .run(|| {
+            let fs = prepare();
+            let res = replace_all(&fs, dir);
+            let replaced = (0..NUM_FILES)
+                .filter(|i| fs.read(&dir.join(i.to_string())).unwrap() == b"new")
+                .count();
+            assert!(res.is_err() || replaced == NUM_FILES);
+        })
+        .unwrap();
+    let paths = report.paths().iter().map(|path| path.key()).collect();
+    (paths, start.elapsed())
+}
+
+fn main() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path();
+
+    let (real_paths, real_time) = explore(dir, || {
+        for i in 0..NUM_FILES {
+            let _ = std::fs::remove_file(dir.join(format!("{i}.tmp")));
+            std::fs::write(dir.join(i.to_string()), "old").unwrap();
+        }
+        RealFs
+    });
+    let (mem_paths, mem_time) = explore(dir, || {
+        let fs = MemFs::new();
+        for i in 0..NUM_FILES {
+            fs.insert(dir.join(i.to_string()), "old");
+        }
+        fs
+    });
+    assert_eq!(
+        mem_paths, real_paths,
+        "backends must be explored identically"
+    );
+
+    println!("paths explored: {}", real_paths.len());
+    println!("real filesystem: {real_time:.2?}");
+    println!("in-memory filesystem: {mem_time:.2?}");
+    println!(
+        "speedup: {:.1}x",
+        real_time.as_secs_f64() / mem_time.as_secs_f64()
+    );
+}
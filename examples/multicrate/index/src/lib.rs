@@ -0,0 +1,7 @@
+use std::io;
+
+/// Open index file, using the same failpoint name as storage does
+pub fn open() -> io::Result<()> {
+    faine::inject_return_io_error!("open file");
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use std::io;
+
+/// Open database, which consists of data and index files
+pub fn open_database() -> io::Result<()> {
+    faine_multicrate_storage::open()?;
+    faine_multicrate_index::open()?;
+    Ok(())
+}
@@ -0,0 +1,7 @@
+use std::io;
+
+/// Open data file
+pub fn open() -> io::Result<()> {
+    faine::inject_return_io_error!("open file");
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use faine::Runner;
+use faine_multicrate_example::open_database;
+
+#[test]
+fn test_same_name_in_different_crates() {
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = open_database();
+        })
+        .unwrap();
+
+    // failpoints are distinct, and named after their crates
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(
+        report.failpoints().into_iter().collect::<Vec<_>>(),
+        vec![
+            "faine-multicrate-index::open file",
+            "faine-multicrate-storage::open file"
+        ]
+    );
+}
+
+#[test]
+fn test_qualified_and_unqualified_selectors() {
+    // unqualified name refers to failpoints of all crates
+    let err = Runner::ignore_env()
+        .with_forbidden(&["open file"])
+        .run(|| {
+            let _ = open_database();
+        })
+        .unwrap_err();
+    assert!(matches!(err, faine::Error::ForbiddenFailpointReached { .. }));
+
+    // qualified one only to that of the given crate
+    let err = Runner::ignore_env()
+        .with_forbidden(&["faine-multicrate-index::open file"])
+        .run(|| {
+            let _ = open_database();
+        })
+        .unwrap_err();
+    let faine::Error::ForbiddenFailpointReached { name, .. } = err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(name, "faine-multicrate-index::open file");
+}
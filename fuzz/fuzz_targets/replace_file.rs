@@ -0,0 +1,27 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#![no_main]
+
+use std::fs::read_to_string;
+
+use faine_integration_example::replace_file;
+use libfuzzer_sys::fuzz_target;
+
+fn check_replace_file() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("myfile");
+    std::fs::write(&path, "old").unwrap();
+    let res = replace_file(&path, "new");
+    let contents = read_to_string(&path).unwrap();
+    assert!(res.is_ok() && contents == "new" || res.is_err() && contents == "old");
+}
+
+fuzz_target!(|data: &[u8]| {
+    if std::env::var_os("FAINE_REPRO").is_some() {
+        // print a configuration for a regular test instead of crashing
+        println!("{}", faine::fuzz::repro_from_bytes(data, check_replace_file));
+        return;
+    }
+    faine::fuzz::run_from_bytes(data, check_replace_file);
+});
@@ -0,0 +1,514 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Procedural macros for [faine](https://docs.rs/faine)
+//!
+//! Not intended to be used directly, enable `macros` feature of `faine` instead.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    Error, FnArg, GenericArgument, Ident, ItemFn, ItemTrait, PathArguments, ReturnType, Signature,
+    Stmt, TraitItem, Type, parse_macro_input,
+};
+
+/// Generate failpoint-injecting wrapper for a trait
+///
+/// See `faine::wrap_trait` for documentation.
+#[proc_macro_attribute]
+pub fn wrap_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_trait = parse_macro_input!(item as ItemTrait);
+    let res = if attr.is_empty() {
+        expand(&item_trait)
+    } else {
+        Err(Error::new(
+            Span::call_site(),
+            "wrap_trait does not accept arguments",
+        ))
+    };
+    let wrapper = res.unwrap_or_else(Error::into_compile_error);
+    quote! {
+        #item_trait
+        #wrapper
+    }
+    .into()
+}
+
+/// Generate fake implementation of a trait with failpoints in fallible methods
+///
+/// See `faine::fake_impl` for documentation.
+#[proc_macro_attribute]
+pub fn fake_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_trait = parse_macro_input!(item as ItemTrait);
+    let res = if attr.is_empty() {
+        expand_fake(&item_trait)
+    } else {
+        Err(Error::new(
+            Span::call_site(),
+            "fake_impl does not accept arguments",
+        ))
+    };
+    let fake = res.unwrap_or_else(Error::into_compile_error);
+    quote! {
+        #item_trait
+        #fake
+    }
+    .into()
+}
+
+/// Wrap a function in a critical section where faults are not injected
+///
+/// See `faine::no_faults` for documentation.
+#[proc_macro_attribute]
+pub fn no_faults(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut item_fn = parse_macro_input!(item as ItemFn);
+    if !attr.is_empty() {
+        return Error::new(Span::call_site(), "no_faults does not accept arguments")
+            .into_compile_error()
+            .into();
+    }
+    if let Some(asyncness) = &item_fn.sig.asyncness {
+        return Error::new(
+            asyncness.span(),
+            "no_faults: async functions are not supported",
+        )
+        .into_compile_error()
+        .into();
+    }
+    let guard: Stmt = syn::parse_quote! {
+        let __faine_no_faults = ::faine::no_faults();
+    };
+    item_fn.block.stmts.insert(0, guard);
+    quote! { #item_fn }.into()
+}
+
+/// Run a test function under exploration
+///
+/// See `faine::test` for documentation.
+#[proc_macro_attribute]
+pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_fn = parse_macro_input!(item as ItemFn);
+    if !attr.is_empty() {
+        return Error::new(Span::call_site(), "test does not accept arguments")
+            .into_compile_error()
+            .into();
+    }
+    let sig = &item_fn.sig;
+    let unsupported = if let Some(asyncness) = &sig.asyncness {
+        Some((asyncness.span(), "async functions"))
+    } else if !sig.generics.params.is_empty() {
+        Some((sig.generics.span(), "generic functions"))
+    } else if !sig.inputs.is_empty() {
+        Some((sig.inputs.span(), "functions with arguments"))
+    } else {
+        None
+    };
+    if let Some((span, what)) = unsupported {
+        return Error::new(span, format!("test: {what} are not supported"))
+            .into_compile_error()
+            .into();
+    }
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item_fn;
+    let name = &sig.ident;
+    let output = &sig.output;
+    let explore = match output {
+        ReturnType::Type(_, ty) if !is_unit(ty) => quote! { explore_result },
+        _ => quote! { explore },
+    };
+    // full path, as plain `test` may resolve to this attribute if it
+    // is imported with `use faine::test` or `use faine::*`
+    quote! {
+        #[::core::prelude::v1::test]
+        #(#attrs)*
+        #vis fn #name() {
+            fn __faine_test() #output #block
+            ::faine::#explore(__faine_test)
+        }
+    }
+    .into()
+}
+
+/// Check whether a type is `()`
+fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+}
+
+/// Check whether a type is `Result<T, E>` or `io::Result<T>`
+///
+/// Other types named `Result`, such as `fmt::Result` or crate-specific
+/// aliases, may have errors not convertible from `io::Error`, so these
+/// are not considered fallible.
+fn is_result(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    let segments = &path.path.segments;
+    let Some(last) = segments.last() else {
+        return false;
+    };
+    if last.ident != "Result" {
+        return false;
+    }
+    let num_types = match &last.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter(|arg| matches!(arg, GenericArgument::Type(_)))
+            .count(),
+        _ => 0,
+    };
+    let is_io = segments.len() >= 2 && segments[segments.len() - 2].ident == "io";
+    num_types == 2 || num_types == 1 && is_io
+}
+
+fn expand(item_trait: &ItemTrait) -> syn::Result<TokenStream2> {
+    let mut errors: Vec<Error> = vec![];
+    let unsupported =
+        |span: Span, what: &str| Error::new(span, format!("wrap_trait: {what} are not supported"));
+
+    if !item_trait.generics.params.is_empty() || item_trait.generics.where_clause.is_some() {
+        errors.push(unsupported(item_trait.generics.span(), "generic traits"));
+    }
+    if !item_trait.supertraits.is_empty() {
+        errors.push(unsupported(item_trait.supertraits.span(), "supertraits"));
+    }
+
+    let trait_name = &item_trait.ident;
+    let wrapper_name = format_ident!("FaineWrapped{}", trait_name);
+    let vis = &item_trait.vis;
+    let unsafety = &item_trait.unsafety;
+
+    let mut items = vec![];
+    for item in &item_trait.items {
+        match item {
+            TraitItem::Fn(method) => match expand_method(trait_name, &method.sig) {
+                Ok(expanded) => items.push(expanded),
+                Err(err) => errors.push(err),
+            },
+            TraitItem::Type(assoc) => {
+                if !assoc.generics.params.is_empty() {
+                    errors.push(unsupported(assoc.span(), "generic associated types"));
+                    continue;
+                }
+                let name = &assoc.ident;
+                items.push(quote! {
+                    type #name = <T as #trait_name>::#name;
+                });
+            }
+            TraitItem::Const(assoc) => {
+                let name = &assoc.ident;
+                let ty = &assoc.ty;
+                items.push(quote! {
+                    const #name: #ty = <T as #trait_name>::#name;
+                });
+            }
+            other => errors.push(unsupported(
+                other.span(),
+                "trait items other than methods, associated types and constants",
+            )),
+        }
+    }
+
+    combine_errors(errors)?;
+
+    let doc = format!(
+        "Wrapper for [`{trait_name}`] implementor which injects failures into its fallible methods"
+    );
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #wrapper_name<T>(pub T);
+
+        #unsafety impl<T: #trait_name> #trait_name for #wrapper_name<T> {
+            #(#items)*
+        }
+    })
+}
+
+fn expand_method(trait_name: &Ident, sig: &Signature) -> syn::Result<TokenStream2> {
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new(
+            asyncness.span(),
+            "wrap_trait: async methods are not supported",
+        ));
+    }
+    if let Some(variadic) = &sig.variadic {
+        return Err(Error::new(
+            variadic.span(),
+            "wrap_trait: variadic methods are not supported",
+        ));
+    }
+
+    let method_name = &sig.ident;
+    let mut inputs = vec![];
+    let mut call_args = vec![];
+    for (index, input) in sig.inputs.iter().enumerate() {
+        match input {
+            FnArg::Receiver(receiver) => {
+                if receiver.colon_token.is_some() {
+                    return Err(Error::new(
+                        receiver.span(),
+                        "wrap_trait: receivers with explicit types are not supported",
+                    ));
+                }
+                inputs.push(quote! { #receiver });
+                call_args.push(match (&receiver.reference, &receiver.mutability) {
+                    (Some(_), Some(_)) => quote! { &mut self.0 },
+                    (Some(_), None) => quote! { &self.0 },
+                    (None, _) => quote! { self.0 },
+                });
+            }
+            FnArg::Typed(typed) => {
+                let name = format_ident!("arg{}", index);
+                let ty = &typed.ty;
+                inputs.push(quote! { #name: #ty });
+                call_args.push(quote! { #name });
+            }
+        }
+    }
+    if !matches!(sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return Err(Error::new(
+            sig.span(),
+            "wrap_trait: methods without `self` receiver are not supported",
+        ));
+    }
+
+    let generics = &sig.generics;
+    let where_clause = &generics.where_clause;
+    let output = &sig.output;
+    let unsafety = &sig.unsafety;
+    let call = quote! {
+        <T as #trait_name>::#method_name(#(#call_args),*)
+    };
+    let call = if unsafety.is_some() {
+        quote! { unsafe { #call } }
+    } else {
+        call
+    };
+    let body = match output {
+        ReturnType::Type(_, ty) if is_result(ty) => {
+            let failpoint = format!("{trait_name}::{method_name}");
+            quote! {
+                ::faine::inject_override!(
+                    #call,
+                    #failpoint,
+                    Err(::std::convert::From::from(::std::io::Error::other(
+                        ::faine::InjectedError::new(#failpoint)
+                    )))
+                )
+            }
+        }
+        _ => call,
+    };
+
+    Ok(quote! {
+        #unsafety fn #method_name #generics(#(#inputs),*) #output #where_clause {
+            #body
+        }
+    })
+}
+
+/// Combine multiple errors into one, if any
+fn combine_errors(mut errors: Vec<Error>) -> syn::Result<()> {
+    if let Some(mut error) = errors.pop() {
+        for other in errors {
+            error.combine(other);
+        }
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn expand_fake(item_trait: &ItemTrait) -> syn::Result<TokenStream2> {
+    let mut errors: Vec<Error> = vec![];
+    let unsupported =
+        |span: Span, what: &str| Error::new(span, format!("fake_impl: {what} are not supported"));
+
+    if !item_trait.generics.params.is_empty() || item_trait.generics.where_clause.is_some() {
+        errors.push(unsupported(item_trait.generics.span(), "generic traits"));
+    }
+    if !item_trait.supertraits.is_empty() {
+        errors.push(unsupported(item_trait.supertraits.span(), "supertraits"));
+    }
+
+    let trait_name = &item_trait.ident;
+    let fake_name = format_ident!("FaineFake{}", trait_name);
+    let vis = &item_trait.vis;
+    let unsafety = &item_trait.unsafety;
+
+    let mut fields = vec![];
+    let mut defaults = vec![];
+    let mut setters = vec![];
+    let mut items = vec![];
+    for item in &item_trait.items {
+        match item {
+            TraitItem::Fn(method) => {
+                let sig = &method.sig;
+                let stub = match &sig.output {
+                    ReturnType::Type(_, ty) if is_result(ty) => Some(ty),
+                    // methods with default implementations are left as is
+                    _ if method.default.is_some() => continue,
+                    _ => None,
+                };
+                match expand_fake_method(trait_name, &fake_name, sig, stub.is_some()) {
+                    Ok(expanded) => items.push(expanded),
+                    Err(err) => {
+                        errors.push(err);
+                        continue;
+                    }
+                }
+                if let Some(ty) = stub {
+                    let method_name = &sig.ident;
+                    let setter = format_ident!("on_{}", method_name);
+                    let doc = format!(
+                        "Set value returned by [`{trait_name}::{method_name}()`] unless its failpoint is activated"
+                    );
+                    fields.push(quote! {
+                        #method_name: ::std::option::Option<
+                            ::std::boxed::Box<dyn Fn() -> #ty + ::std::marker::Send + ::std::marker::Sync>
+                        >
+                    });
+                    defaults.push(quote! { #method_name: ::std::option::Option::None });
+                    setters.push(quote! {
+                        #[doc = #doc]
+                        pub fn #setter(
+                            mut self,
+                            stub: impl Fn() -> #ty + ::std::marker::Send + ::std::marker::Sync + 'static,
+                        ) -> Self {
+                            self.#method_name = ::std::option::Option::Some(::std::boxed::Box::new(stub));
+                            self
+                        }
+                    });
+                }
+            }
+            other => errors.push(unsupported(other.span(), "trait items other than methods")),
+        }
+    }
+
+    combine_errors(errors)?;
+
+    let doc = format!(
+        "Fake implementation of [`{trait_name}`] which returns configured values from its fallible methods, or injects failures"
+    );
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #fake_name {
+            #(#fields),*
+        }
+
+        impl #fake_name {
+            /// Construct a fake with no values configured
+            pub fn new() -> Self {
+                Self {
+                    #(#defaults),*
+                }
+            }
+
+            #(#setters)*
+        }
+
+        impl ::std::default::Default for #fake_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #unsafety impl #trait_name for #fake_name {
+            #(#items)*
+        }
+    })
+}
+
+fn expand_fake_method(
+    trait_name: &Ident,
+    fake_name: &Ident,
+    sig: &Signature,
+    fallible: bool,
+) -> syn::Result<TokenStream2> {
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new(
+            asyncness.span(),
+            "fake_impl: async methods are not supported",
+        ));
+    }
+    if let Some(variadic) = &sig.variadic {
+        return Err(Error::new(
+            variadic.span(),
+            "fake_impl: variadic methods are not supported",
+        ));
+    }
+    if !matches!(sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return Err(Error::new(
+            sig.span(),
+            "fake_impl: methods without `self` receiver are not supported",
+        ));
+    }
+
+    let method_name = &sig.ident;
+    let mut inputs = vec![];
+    for (index, input) in sig.inputs.iter().enumerate() {
+        match input {
+            FnArg::Receiver(receiver) => {
+                if receiver.colon_token.is_some() {
+                    return Err(Error::new(
+                        receiver.span(),
+                        "fake_impl: receivers with explicit types are not supported",
+                    ));
+                }
+                inputs.push(quote! { #receiver });
+            }
+            FnArg::Typed(typed) => {
+                let name = format_ident!("_arg{}", index);
+                let ty = &typed.ty;
+                inputs.push(quote! { #name: #ty });
+            }
+        }
+    }
+
+    let generics = &sig.generics;
+    let where_clause = &generics.where_clause;
+    let output = &sig.output;
+    let unsafety = &sig.unsafety;
+    let body = if fallible {
+        let failpoint = format!("{trait_name}::{method_name}");
+        let setter = format!("on_{method_name}");
+        quote! {
+            ::faine::inject_override!(
+                match &self.#method_name {
+                    ::std::option::Option::Some(stub) => stub(),
+                    ::std::option::Option::None => ::std::unimplemented!(
+                        "{}::{}() has no value configured, set it with {}()",
+                        ::std::stringify!(#fake_name),
+                        ::std::stringify!(#method_name),
+                        #setter,
+                    ),
+                },
+                #failpoint,
+                Err(::std::convert::From::from(::std::io::Error::other(
+                    ::faine::InjectedError::new(#failpoint)
+                )))
+            )
+        }
+    } else {
+        quote! {
+            ::std::unimplemented!(
+                "{} only fakes methods returning Result, {}::{}() is not implemented",
+                ::std::stringify!(#fake_name),
+                ::std::stringify!(#trait_name),
+                ::std::stringify!(#method_name),
+            )
+        }
+    };
+
+    Ok(quote! {
+        #unsafety fn #method_name #generics(#(#inputs),*) #output #where_clause {
+            #body
+        }
+    })
+}
@@ -1,14 +1,638 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use crate::absorption::{Outcome, OutcomeClassifier};
+use crate::aliases::Aliases;
+use crate::audit::PurityAudit;
+pub use crate::audit::{
+    ClassifyInjected, ClassifyUnknown, DigestDebug, DigestOutcome, DigestOutcomeDebug,
+    DigestUnknown, Injected, audit_injection, audit_purity, inspect,
+};
+use crate::capture::Capture;
+use crate::checkpoint::Checkpoint;
+pub use crate::checks::{intern_check, record_check};
+use crate::common::{After, Branch, Exhaust, Expect, Label, Phase, Weight};
+pub use crate::counters::{add_to_counter, intern_counter};
+use crate::env::EnvAxis;
+use crate::events::{self, SharedEventSink};
+use crate::ext::ErrorFactory;
+use crate::fnv::FnvHasher;
+use crate::functions::{Accumulator, AccumulatorCloner};
+use crate::injected::{ErrorTag, InjectedError};
+use crate::intern::{self, FailpointId};
+pub use crate::intern::{FailpointIdCache, FailpointRef, PerTypeIdCache};
+use crate::panics;
+use crate::params::Parameter;
+use crate::perturb::{Perturb, Perturbable};
+use crate::plan::PlannedPath;
+use crate::recent::EventBuffer;
+use crate::report::{ExecutionPath, InjectionAudit, Step};
+use crate::safety::{SafetyLimits, SafetyValve};
+use crate::selector::Selector;
+use crate::trace::Trace;
 use crate::tree::Tree;
+use crate::watchdog::Watchdog;
+use std::any::TypeId;
 use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::hash::{Hash, Hasher};
+
+pub use crate::common::Site;
 
 pub struct State {
     pub enabled: bool,
+    pub aliases: Aliases,
     pub tree: Tree,
+    pub conditions: HashMap<&'static str, Exhaust>,
+    pub watchdog: Option<Watchdog>,
+    pub capture: Option<Capture>,
+    pub trace: Option<Trace>,
+    pub locations: HashMap<Label, &'static str>,
+    pub outcome: Option<u64>,
+    pub requirements: HashMap<FailpointId, FailpointId>,
+    pub allow_faults_during_unwind: bool,
+    pub suppressed_during_unwind: Vec<&'static str>,
+    pub critical_sections: usize,
+    pub suppressed_by_critical_section: Vec<&'static str>,
+    pub triggers: HashMap<FailpointId, FailpointId>,
+    pub consumed_triggers: HashMap<FailpointId, usize>,
+    pub error_factories: HashMap<TypeId, ErrorFactory>,
+    pub outcome_classifiers: HashMap<TypeId, OutcomeClassifier>,
+    pub result: Option<Outcome>,
+    pub forbidden: Vec<Selector>,
+    pub forbidden_labels: HashMap<Label, bool>,
+    pub forbidden_visits: Vec<(&'static str, Vec<Step>)>,
+    pub batch_cuts: HashMap<FailpointId, Option<usize>>,
+    pub expectations: HashMap<Label, Expect>,
+    pub accumulator: Option<Accumulator>,
+    pub accumulator_cloner: Option<AccumulatorCloner>,
+    pub accumulator_snapshot: Option<Accumulator>,
+    pub axis_values: Vec<(&'static str, &'static str)>,
+    pub env_axes: Vec<EnvAxis>,
+    pub event_sinks: Vec<SharedEventSink>,
+    pub iteration: Option<usize>,
+    pub phase: Phase,
+    pub safety_limits: SafetyLimits,
+    pub activation_limits: HashMap<&'static str, usize>,
+    pub num_visits: usize,
+    pub num_activations: HashMap<&'static str, usize>,
+    pub safety_valve: Vec<SafetyValve>,
+    pub injection_audit: Option<BTreeMap<&'static str, InjectionAudit>>,
+    pub injection_purity: Option<PurityAudit>,
+    pub error_tags: Option<Vec<ErrorTag>>,
+    pub checks: Vec<u64>,
+    pub counters: Vec<u64>,
+    pub parameters: Vec<Parameter>,
+    pub parameter_bindings: HashMap<&'static str, f64>,
+    pub rng_seed: u64,
+    pub rng_draws: u64,
+    pub plan: Option<PlannedPath>,
+    pub recent_events: Option<EventBuffer>,
+    pub checkpoints: Vec<Checkpoint>,
+    pub continue_after_checkpoint_violation: bool,
+    #[cfg(feature = "tempdir")]
+    pub tempdir: Option<&'static std::path::Path>,
+    #[cfg(feature = "fs")]
+    pub metadata_perturbations: HashMap<String, crate::fs::MetadataPerturbation>,
+    #[cfg(feature = "fs")]
+    pub durability: crate::fs::DurabilityTracker,
+    #[cfg(feature = "fs")]
+    pub fixture: Option<crate::fs::FixtureTree>,
+}
+
+impl State {
+    /// Path of the current iteration so far
+    pub fn current_path(&mut self) -> ExecutionPath {
+        let steps = self
+            .tree
+            .current_path()
+            .into_iter()
+            .filter_map(|(label, branch)| self.step(label, branch))
+            .collect();
+        ExecutionPath {
+            steps,
+            axes: self.axis_values.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Convert tree label into a reported step, applying aliases and triggers
+    pub fn step(&mut self, label: Label, branch: Branch) -> Option<Step> {
+        let (label, branch) = self.parameter_fallback(label, branch);
+        let mut step = Step::from_label(label, branch)?;
+        if branch == Branch::Activate
+            && let Label::Failpoint(id) = label
+            && let Some(trigger) = self.triggers.get(&id)
+        {
+            step.trigger = Some(self.aliases.apply(trigger.name()));
+        }
+        step.failpoint = self.aliases.apply(step.failpoint);
+        Some(step)
+    }
+
+    /// Replace skip of the last but one value of a parameter with the
+    /// last value, which is chosen implicitly, see [`param_f64()`](crate::param_f64)
+    fn parameter_fallback(&self, label: Label, branch: Branch) -> (Label, Branch) {
+        if branch == Branch::Skip
+            && let Label::Variant(name, variant) = label
+            && let Some((_, values)) = self
+                .parameters
+                .iter()
+                .find(|(parameter, _)| *parameter == name)
+            && let [.., (_, penultimate), (_, last)] = values.as_slice()
+            && *penultimate == variant
+        {
+            return (Label::Variant(name, last), Branch::Activate);
+        }
+        (label, branch)
+    }
+
+    /// Whether a failpoint is selected by any of forbidden selectors,
+    /// either by its raw name or by its alias
+    fn is_forbidden(&mut self, label: Label) -> bool {
+        if let Some(forbidden) = self.forbidden_labels.get(&label) {
+            return *forbidden;
+        }
+        let forbidden = label.failpoint_name().is_some_and(|name| {
+            let alias = self.aliases.apply(name);
+            self.forbidden
+                .iter()
+                .any(|selector| selector.matches(name) || selector.matches(alias))
+        });
+        self.forbidden_labels.insert(label, forbidden);
+        forbidden
+    }
+
+    /// Activation limit of a failpoint, see [`Runner::with_adaptive_hit_limits()`](crate::Runner::with_adaptive_hit_limits)
+    fn activation_limit(&mut self, name: &'static str) -> usize {
+        if self.activation_limits.is_empty() {
+            return self.safety_limits.max_activations;
+        }
+        // limits are keyed by names as rendered in reports, which may
+        // be qualified differently by now
+        let alias = self.aliases.apply(name);
+        self.activation_limits
+            .get(alias)
+            .or_else(|| {
+                self.activation_limits
+                    .iter()
+                    .find(|(limited, _)| intern::refers_to(limited, name))
+                    .map(|(_, limit)| limit)
+            })
+            .copied()
+            .unwrap_or(self.safety_limits.max_activations)
+    }
+
+    /// Check per-iteration safety limits before visiting a failpoint,
+    /// returning the triggered safety valve if a limit is reached
+    fn check_safety_limits(&mut self, label: Label) -> Option<SafetyValve> {
+        let valve = if self.num_visits >= self.safety_limits.max_visits {
+            SafetyValve::Visits
+        } else if let Some(name) = label.failpoint_name()
+            && self.num_activations.get(name).copied().unwrap_or_default()
+                >= self.activation_limit(name)
+        {
+            SafetyValve::Activations(name)
+        } else {
+            self.num_visits += 1;
+            return None;
+        };
+        if !self.safety_valve.contains(&valve) {
+            self.safety_valve.push(valve);
+        }
+        Some(valve)
+    }
+
+    /// Apply checks which may prevent a failpoint visit from being explored
+    ///
+    /// Returns `false` if the failpoint must be skipped without
+    /// consulting the tree.
+    fn admit(&mut self, label: Label, location: &'static str) -> bool {
+        // forbidden failpoints are never explored, only reported
+        if !self.forbidden.is_empty() && self.is_forbidden(label) {
+            self.record_forbidden_visit(label);
+            return false;
+        }
+        // activating a failpoint in a drop run during unwinding may
+        // lead to a double panic, which aborts the process
+        if !self.allow_faults_during_unwind && panics::is_unwinding() {
+            if let Some(name) = label.failpoint_name()
+                && !self.suppressed_during_unwind.contains(&name)
+            {
+                self.suppressed_during_unwind.push(name);
+            }
+            return false;
+        }
+        // explicitly marked by the tested code, see no_faults()
+        if self.critical_sections > 0 {
+            if let Some(name) = label.failpoint_name()
+                && !self.suppressed_by_critical_section.contains(&name)
+            {
+                self.suppressed_by_critical_section.push(name);
+            }
+            return false;
+        }
+        self.locations.entry(label).or_insert(location);
+        // code which retries indefinitely on injected failures
+        // would never finish an iteration otherwise
+        self.check_safety_limits(label).is_none()
+    }
+
+    /// Record a visit of a forbidden failpoint along with the path leading to it
+    fn record_forbidden_visit(&mut self, label: Label) {
+        let Some(name) = label.failpoint_name() else {
+            return;
+        };
+        let path = self
+            .tree
+            .current_path()
+            .into_iter()
+            .filter_map(|(label, branch)| self.step(label, branch))
+            .collect();
+        let name = self.aliases.apply(name);
+        self.forbidden_visits.push((name, path));
+    }
 }
 
 thread_local! {
     pub static FAILPOINTS: RefCell<Option<Box<State>>> = const { RefCell::new(None) };
 }
+
+pub fn visit(label: Label, location: &'static str) -> Branch {
+    // event sinks must not affect exploration
+    if events::is_dispatching() {
+        return Branch::Skip;
+    }
+    let mut event = None;
+    let branch = FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state
+            && state.enabled
+        {
+            if !state.admit(label, location) {
+                return Branch::Skip;
+            }
+            let branch = state.tree.visit(label);
+            if let Some(buffer) = &mut state.recent_events {
+                buffer.record_visit(label, branch);
+            }
+            if branch == Branch::Activate
+                && let Some(name) = label.failpoint_name()
+            {
+                *state.num_activations.entry(name).or_default() += 1;
+            }
+            if state.watchdog.is_some()
+                && let Some(step) = state.step(label, branch)
+                && let Some(watchdog) = &state.watchdog
+            {
+                watchdog.record(step);
+            }
+            if state.trace.is_some()
+                && let Some(step) = state.step(label, branch)
+                && let Some(trace) = &mut state.trace
+            {
+                trace.record(step);
+            }
+            // sinks are called after the state is released, as they may
+            // visit failpoints too
+            if !state.event_sinks.is_empty()
+                && state.phase == Phase::Exploring
+                && let Some(step) = state.step(label, branch)
+            {
+                event = Some((
+                    state.event_sinks.clone(),
+                    step.failpoint,
+                    state.iteration.unwrap_or_default(),
+                ));
+            }
+            branch
+        } else {
+            Branch::Skip
+        }
+    });
+    if let Some((sinks, failpoint, iteration)) = event {
+        events::dispatch(&sinks, |sink| sink.on_visit(failpoint, branch, iteration));
+    }
+    branch
+}
+
+/// Visit a multi-valued failpoint
+///
+/// Variants are represented in the tree as a chain of binary
+/// decisions, first activated one is chosen. Returns index of the
+/// chosen variant, or `None` if all are skipped.
+pub fn visit_variants(
+    name: &'static str,
+    location: &'static str,
+    variants: &[&'static str],
+) -> Option<usize> {
+    variants
+        .iter()
+        .position(|variant| visit(Label::Variant(name, variant), location) == Branch::Activate)
+}
+
+/// Visit a multi-valued failpoint with variants from a preset
+pub fn perturb_preset<T: Perturbable>(
+    name: &'static str,
+    location: &'static str,
+    preset: Perturb,
+    value: T,
+) -> T {
+    match visit_variants(name, location, T::variants(preset)) {
+        Some(index) => T::perturbation(preset, index, &value),
+        None => value,
+    }
+}
+
+/// Visit a failpoint, recording a weight declared at its site
+pub fn visit_weighted(label: Label, location: &'static str, weight: Weight) -> Branch {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            state.tree.set_weight(label, weight);
+        }
+    });
+    visit(label, location)
+}
+
+/// Visit a failpoint, recording an expectation declared at its site
+pub fn visit_expecting(label: Label, location: &'static str, expect: Expect) -> Branch {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            state.expectations.entry(label).or_insert(expect);
+        }
+    });
+    visit(label, location)
+}
+
+/// Visit a batch cut point, returning whether the batch is cut here
+///
+/// Index of the cut is decided once per batch, on visit of its first
+/// item, and is represented in the tree as a chain of binary decisions
+/// like variants of a multi-valued failpoint, so a batch of N items is
+/// explored in N + 1 paths regardless of whether the code stops at the
+/// cut. Visits of further items only compare their index with the one
+/// decided, and the next visit with index 0 starts a new batch.
+pub fn visit_batch_cut(
+    failpoint: FailpointId,
+    location: &'static str,
+    index: usize,
+    len: usize,
+) -> bool {
+    let decided = FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .and_then(|state| state.batch_cuts.get(&failpoint).copied())
+    });
+    let cut = match decided {
+        Some(cut) if index != 0 => cut,
+        _ => {
+            let cut = (0..len)
+                .position(|item| visit(Label::Cut(failpoint, item), location) == Branch::Activate);
+            FAILPOINTS.with_borrow_mut(|state| {
+                if let Some(state) = state {
+                    state.batch_cuts.insert(failpoint, cut);
+                }
+            });
+            cut
+        }
+    };
+    cut == Some(index)
+}
+
+/// Visit a failpoint which may only be activated after a prerequisite
+///
+/// If the prerequisite was not activated earlier on the current path,
+/// the tree is not visited, so no branches are consumed.
+pub fn visit_requiring(
+    failpoint: FailpointId,
+    location: &'static str,
+    prerequisite: FailpointId,
+) -> Branch {
+    let applicable = FAILPOINTS.with_borrow_mut(|state| {
+        let Some(state) = state else {
+            return false;
+        };
+        if state.requirements.insert(failpoint, prerequisite) != Some(prerequisite) {
+            check_requirement_cycle(&state.requirements, failpoint);
+        }
+        state.tree.is_activated(Label::Failpoint(prerequisite))
+    });
+    if applicable {
+        visit(Label::Failpoint(failpoint), location)
+    } else {
+        Branch::Skip
+    }
+}
+
+/// Visit a failpoint which may only be activated right after another one
+///
+/// Each activation of the trigger failpoint on the current path arms
+/// the failpoint, and the first visit after that consumes it. Visits
+/// of disarmed failpoint do not visit the tree.
+pub fn visit_triggered(
+    failpoint: FailpointId,
+    location: &'static str,
+    trigger: After,
+    cache: &FailpointIdCache,
+) -> Branch {
+    let After::Activation(source) = trigger;
+    let source = source.failpoint_id(cache);
+    let armed = FAILPOINTS.with_borrow_mut(|state| {
+        let Some(state) = state else {
+            return false;
+        };
+        state.triggers.insert(failpoint, source);
+        let activations = state.tree.count_activations(Label::Failpoint(source));
+        let consumed = state.consumed_triggers.entry(failpoint).or_default();
+        if activations > *consumed {
+            *consumed = activations;
+            true
+        } else {
+            false
+        }
+    });
+    if armed {
+        visit(Label::Failpoint(failpoint), location)
+    } else {
+        Branch::Skip
+    }
+}
+
+/// Panic if failpoint requirements form a cycle, as failpoints
+/// involved in it may never be activated
+fn check_requirement_cycle(requirements: &HashMap<FailpointId, FailpointId>, start: FailpointId) {
+    let mut chain = vec![start];
+    let mut current = start;
+    while let Some(&next) = requirements.get(&current) {
+        chain.push(next);
+        if next == start {
+            let chain: Vec<_> = chain.iter().map(|id| id.name()).collect();
+            panic!("failpoint requirements form a cycle: {}", chain.join(" → "));
+        }
+        if chain.len() > requirements.len() + 1 {
+            break;
+        }
+        current = next;
+    }
+}
+
+/// Visit a failpoint which unconditionally fails while a condition holds
+///
+/// The tree is not visited in that case, so such failpoints do not
+/// consume any branches. Checks which apply to regular visits, such as
+/// suppression during unwinding and in critical sections, still apply.
+pub fn visit_conditional(label: Label, location: &'static str, condition: &str) -> Branch {
+    if events::is_dispatching() {
+        return Branch::Skip;
+    }
+    let branch = FAILPOINTS.with_borrow_mut(|state| {
+        let state = state
+            .as_mut()
+            .filter(|state| state.enabled && state.conditions.contains_key(condition))?;
+        if !state.admit(label, location) {
+            return Some(Branch::Skip);
+        }
+        if let Some(name) = label.failpoint_name() {
+            *state.num_activations.entry(name).or_default() += 1;
+        }
+        Some(Branch::Activate)
+    });
+    branch.unwrap_or_else(|| visit(label, location))
+}
+
+/// Set a condition, to be used by `inject_exhaust!`
+pub fn set_condition(condition: &'static str, scope: Exhaust) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            state.conditions.insert(condition, scope);
+        }
+    });
+}
+
+/// Names of failpoints activated so far in the current iteration
+pub fn activated_failpoints() -> Option<Vec<&'static str>> {
+    FAILPOINTS.with_borrow(|state| {
+        state.as_ref().map(|state| {
+            state
+                .tree
+                .current_path()
+                .into_iter()
+                .filter(|(_, branch)| *branch == Branch::Activate)
+                .filter_map(|(label, _)| label.failpoint_name())
+                .collect()
+        })
+    })
+}
+
+/// Intern a failpoint name belonging to a crate, if the crate is known
+pub fn intern_from(krate: Option<&str>, name: &str) -> FailpointId {
+    FailpointId::intern_from(krate, name)
+}
+
+/// Tag of an error injected by the just activated failpoint, if
+/// error tagging is enabled
+///
+/// Records the tag so it can be resolved into the path afterwards.
+pub fn error_tag() -> Option<ErrorTag> {
+    FAILPOINTS.with_borrow_mut(|state| {
+        let state = state.as_mut()?;
+        state.error_tags.as_ref()?;
+        let mut hasher = FnvHasher::new();
+        for (label, branch) in state.tree.current_path() {
+            (label.failpoint_name(), branch == Branch::Activate).hash(&mut hasher);
+        }
+        let tag = ErrorTag::new(state.iteration?, hasher.finish() as u32);
+        state.error_tags.as_mut()?.push(tag);
+        Some(tag)
+    })
+}
+
+/// Error injected by a failpoint, tagged if error tagging is enabled
+pub fn injected_error(failpoint: &'static str) -> InjectedError {
+    let error = InjectedError::new(failpoint);
+    match error_tag() {
+        Some(tag) => error.with_tag(tag),
+        None => error,
+    }
+}
+
+/// Conversion of various error types into `&dyn Error`
+///
+/// Used with method call syntax, so autoderef makes it work
+/// with `Box<dyn Error>` and `anyhow::Error` as well.
+pub trait AsDynError {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static);
+}
+
+impl<E: Error + 'static> AsDynError for E {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+}
+
+impl AsDynError for dyn Error + 'static {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+}
+
+impl AsDynError for dyn Error + Send + Sync + 'static {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+}
+
+fn describe_error(err: &(dyn Error + 'static)) -> String {
+    let mut res = String::from("error chain:");
+    let mut current = Some(err);
+    let mut depth = 0;
+    while let Some(err) = current {
+        res += &format!("\n  {depth}: {err} ({err:?})");
+        current = err.source();
+        depth += 1;
+    }
+    if let Some(activated) = activated_failpoints() {
+        res += &format!("\nactivated failpoints: {activated:?}");
+    }
+    res
+}
+
+pub fn assert_injected_by(err: Option<&(dyn Error + 'static)>, failpoint: &str, prefix: bool) {
+    let matches = |name: &str| {
+        if prefix {
+            name.starts_with(failpoint)
+        } else {
+            name == failpoint
+        }
+    };
+    let what = if prefix { "prefixed with" } else { "named" };
+    let Some(err) = err else {
+        panic!(
+            "assertion failed: expected error injected by failpoint {what} `{failpoint}`, got Ok"
+        );
+    };
+    match InjectedError::find_in(err) {
+        Some(injected) if matches(injected.failpoint()) => {}
+        Some(injected) => panic!(
+            "assertion failed: expected error injected by failpoint {what} `{failpoint}`, got one injected by `{}`\n{}",
+            injected.failpoint(),
+            describe_error(err)
+        ),
+        None => panic!(
+            "assertion failed: expected error injected by failpoint {what} `{failpoint}`, got non-injected error\n{}",
+            describe_error(err)
+        ),
+    }
+}
+
+pub fn assert_not_injected(err: Option<&(dyn Error + 'static)>) {
+    if let Some(err) = err
+        && let Some(injected) = InjectedError::find_in(err)
+    {
+        panic!(
+            "assertion failed: expected genuine error, got one injected by `{}`\n{}",
+            injected.failpoint(),
+            describe_error(err)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use crate::__private::FAILPOINTS;
+
+/// Outcome of an iteration as observed by its caller
+///
+/// Recorded with [`record_result()`], and used to find failpoints whose
+/// activation does not surface as a failure, see [`Report::absorption()`](crate::Report::absorption).
+/// Ordered by severity, so combination of outcomes is the maximal one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Outcome {
+    /// Operation has succeeded
+    Success,
+
+    /// Operation has succeeded, but reported reduced functionality
+    Degraded,
+
+    /// Operation has failed
+    Failure,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Success => write!(f, "success"),
+            Outcome::Degraded => write!(f, "degraded"),
+            Outcome::Failure => write!(f, "failure"),
+        }
+    }
+}
+
+/// Values which may be classified as an [`Outcome`]
+///
+/// Classification may be overridden per type with
+/// [`Runner::with_outcome_classifier()`](crate::Runner::with_outcome_classifier).
+pub trait IntoOutcome {
+    /// Default classification of the value
+    fn outcome(&self) -> Outcome;
+}
+
+impl<T, E> IntoOutcome for Result<T, E> {
+    /// [`Outcome::Failure`] for `Err`
+    fn outcome(&self) -> Outcome {
+        match self {
+            Ok(_) => Outcome::Success,
+            Err(_) => Outcome::Failure,
+        }
+    }
+}
+
+impl<T> IntoOutcome for Option<T> {
+    /// [`Outcome::Failure`] for `None`
+    fn outcome(&self) -> Outcome {
+        match self {
+            Some(_) => Outcome::Success,
+            None => Outcome::Failure,
+        }
+    }
+}
+
+impl IntoOutcome for Outcome {
+    fn outcome(&self) -> Outcome {
+        *self
+    }
+}
+
+/// Type erased outcome classifier registered with [`Runner::with_outcome_classifier()`](crate::Runner::with_outcome_classifier)
+pub type OutcomeClassifier = Arc<dyn Any + Send + Sync>;
+
+type TypedOutcomeClassifier<T> = Box<dyn Fn(&T) -> Outcome + Send + Sync>;
+
+/// Wrap an outcome classifier, keyed by type of classified values
+pub fn outcome_classifier<T: 'static>(
+    classifier: impl Fn(&T) -> Outcome + Send + Sync + 'static,
+) -> (TypeId, OutcomeClassifier) {
+    let classifier: TypedOutcomeClassifier<T> = Box::new(classifier);
+    (TypeId::of::<T>(), Arc::new(classifier))
+}
+
+/// Record the result of the public API called by the current iteration
+///
+/// The result is classified as an [`Outcome`], either by its
+/// [`IntoOutcome`] implementation, or by a classifier registered with
+/// [`Runner::with_outcome_classifier()`] for its type. Combined with
+/// activated failpoints, this allows to find injected failures which
+/// were absorbed, that is, did not surface to the caller, see
+/// [`Report::absorption()`]. If called multiple times per iteration,
+/// the most severe outcome is recorded.
+///
+/// Like `inject_*` macros, it does nothing outside of [`Runner::run()`].
+///
+/// ```
+/// use std::io;
+/// use faine::{Runner, inject_return_io_error, record_result};
+///
+/// fn save() -> io::Result<()> {
+///     // failure to write a backup is (wrongly) ignored
+///     let _ = (|| -> io::Result<()> {
+///         inject_return_io_error!("write backup");
+///         Ok(())
+///     })();
+///     inject_return_io_error!("write");
+///     Ok(())
+/// }
+///
+/// let report = Runner::ignore_env()
+///     .run(|| record_result(&save()))
+///     .unwrap();
+/// assert_eq!(report.absorption()["write backup"].ratio(), 0.5);
+/// assert_eq!(report.absorption()["write"].ratio(), 0.0);
+/// ```
+///
+/// [`Report::absorption()`]: crate::Report::absorption
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_outcome_classifier()`]: crate::Runner::with_outcome_classifier
+pub fn record_result<T: IntoOutcome + 'static>(value: &T) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            let outcome = state
+                .outcome_classifiers
+                .get(&TypeId::of::<T>())
+                .and_then(|classifier| classifier.downcast_ref::<TypedOutcomeClassifier<T>>())
+                .map_or_else(|| value.outcome(), |classifier| classifier(value));
+            state.result = state.result.max(Some(outcome));
+        }
+    });
+}
@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fmt::Debug;
+
+use crate::error::Error;
+use crate::runner::Runner;
+
+#[track_caller]
+fn explore_or_panic(func: impl FnMut()) {
+    let err = match Runner::default()
+        .with_catch_panics(true)
+        .run(func)
+        .into_result()
+    {
+        Ok(_) => return,
+        Err(err) => err,
+    };
+    let mut message = err.to_string();
+    if let Error::UnexpectedPanics { report, .. } = &err
+        && let Some(path) = report.paths().iter().find(|path| path.panic.is_some())
+    {
+        message += &format!("\nfailing path: {path}");
+        if let Some(panic_message) = path.panic.as_ref().and_then(|panic| panic.message.as_ref()) {
+            message += &format!("\npanic message: {panic_message}");
+        }
+    }
+    panic!("{message}");
+}
+
+/// Explore a plain test function
+///
+/// Shortcut for running `test_fn` with a [`Runner::default()`], which
+/// honors [environment variables](crate::Runner#environment), with
+/// [panics caught](crate::Runner::with_catch_panics), so all failing
+/// paths are found. Panics if the exploration fails, with the error
+/// followed by the first failing path and its panic message.
+///
+/// Meant for migrating existing test suites with minimal changes,
+/// as the body of a test just moves into a separate function:
+///
+/// ```
+/// # use std::fs;
+/// # use std::path::Path;
+/// # fn replace_file(path: &Path, content: &str) -> std::io::Result<()> {
+/// #     faine::inject_return_io_error!("write");
+/// #     fs::write(path, content)
+/// # }
+/// #[test]
+/// # fn dummy() {}
+/// fn test_replace_file() {
+///     faine::explore(test_replace_file_impl)
+/// }
+///
+/// fn test_replace_file_impl() {
+///     let tempdir = tempfile::tempdir().unwrap();
+///     let path = tempdir.path().join("myfile");
+///     if replace_file(&path, "new").is_ok() {
+///         assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+///     }
+/// }
+/// # test_replace_file();
+/// ```
+///
+/// See also [`explore_result()`] for tests returning `Result`, and
+/// `#[faine::test]` attribute (with `macros` feature) which does the
+/// same without a separate function.
+#[track_caller]
+pub fn explore(test_fn: fn()) {
+    explore_or_panic(test_fn)
+}
+
+/// Explore a test function returning `Result`
+///
+/// Same as [`explore()`], but an `Err` returned by `test_fn` fails the
+/// path like a panic, with its `Debug` rendering as the message, so
+/// tests using `?` need no restructuring:
+///
+/// ```
+/// # use std::fs;
+/// # use std::io;
+/// # use std::path::Path;
+/// # fn replace_file(path: &Path, content: &str) -> io::Result<()> {
+/// #     let temp_path = path.with_extension("tmp");
+/// #     faine::inject_return_io_error!("write");
+/// #     fs::write(&temp_path, content)?;
+/// #     fs::rename(&temp_path, path)
+/// # }
+/// #[test]
+/// # fn dummy() {}
+/// fn test_replace_file() {
+///     faine::explore_result(test_replace_file_impl)
+/// }
+///
+/// fn test_replace_file_impl() -> io::Result<()> {
+///     let tempdir = tempfile::tempdir()?;
+///     let path = tempdir.path().join("myfile");
+///     fs::write(&path, "old")?;
+///     let res = replace_file(&path, "new");
+///     let expected = if res.is_ok() { "new" } else { "old" };
+///     assert_eq!(fs::read_to_string(&path)?, expected);
+///     Ok(())
+/// }
+/// # test_replace_file();
+/// ```
+#[track_caller]
+pub fn explore_result<E: Debug>(test_fn: fn() -> Result<(), E>) {
+    explore_or_panic(|| {
+        if let Err(err) = test_fn() {
+            panic!("test function returned an error: {err:?}");
+        }
+    })
+}
@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{LazyLock, RwLock};
+
+use crate::intern;
+
+static NAMES: LazyLock<RwLock<HashSet<&'static str>>> = LazyLock::new(Default::default);
+
+/// Make a static copy of an alias, once per distinct alias
+///
+/// Aliases are not failpoint names, so they are kept apart from the
+/// failpoint interner.
+fn static_name(alias: &str) -> &'static str {
+    if let Some(name) = NAMES
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(alias)
+    {
+        return name;
+    }
+    let mut names = NAMES.write().unwrap_or_else(|err| err.into_inner());
+    if let Some(name) = names.get(alias) {
+        return name;
+    }
+    let name: &'static str = Box::leak(alias.into());
+    names.insert(name);
+    name
+}
+
+/// Human readable names for failpoints, applied when rendering reports
+///
+/// See [`Runner::with_aliases()`](crate::Runner::with_aliases).
+#[derive(Default)]
+pub struct Aliases {
+    aliases: HashMap<String, &'static str>,
+    observed: BTreeSet<&'static str>,
+}
+
+impl Aliases {
+    pub fn new(pairs: &[(String, String)]) -> Self {
+        Self {
+            aliases: pairs
+                .iter()
+                .map(|(raw, alias)| (raw.clone(), static_name(alias)))
+                .collect(),
+            observed: Default::default(),
+        }
+    }
+
+    /// Name to render for a failpoint, recording that it was observed
+    pub fn apply(&mut self, name: &'static str) -> &'static str {
+        if self.aliases.is_empty() {
+            return name;
+        }
+        let raw = if self.aliases.contains_key(name) {
+            Some(name)
+        } else {
+            // alias may refer to a failpoint by another form of its name
+            intern::forms_of(name).and_then(|forms| {
+                forms
+                    .into_iter()
+                    .find(|form| self.aliases.contains_key(*form))
+            })
+        };
+        match raw {
+            Some(raw) => {
+                self.observed.insert(raw);
+                self.aliases[raw]
+            }
+            None => name,
+        }
+    }
+
+    /// Raw names of aliased failpoints which were never observed
+    pub fn stale(&self) -> Vec<String> {
+        let mut stale: Vec<_> = self
+            .aliases
+            .keys()
+            .filter(|raw| !self.observed.contains(raw.as_str()))
+            .cloned()
+            .collect();
+        stale.sort();
+        stale
+    }
+
+    /// Mapping from raw names to aliases
+    pub fn mapping(&self) -> &HashMap<String, &'static str> {
+        &self.aliases
+    }
+}
+
+/// Parse alias file contents
+///
+/// Each line contains a raw failpoint name and an alias separated by
+/// `=`, empty lines and lines starting with `#` are ignored.
+pub fn parse(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(number, line)| (number + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| match line.split_once('=') {
+            Some((raw, alias)) if !raw.trim().is_empty() && !alias.trim().is_empty() => {
+                Ok((raw.trim().to_string(), alias.trim().to_string()))
+            }
+            _ => Err(format!(
+                "line {number}: expected `<failpoint> = <alias>`, got `{line}`"
+            )),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("# comment\n\nsrc/wal.rs:412:17 = wal append\n  a=b  \n"),
+            Ok(vec![
+                ("src/wal.rs:412:17".to_string(), "wal append".to_string()),
+                ("a".to_string(), "b".to_string()),
+            ])
+        );
+        assert!(parse("a = b\nc\n").unwrap_err().starts_with("line 2:"));
+        assert!(parse("a =\n").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut aliases = Aliases::new(&[
+            ("a".to_string(), "alpha".to_string()),
+            ("b".to_string(), "beta".to_string()),
+        ]);
+        assert_eq!(aliases.apply("a"), "alpha");
+        assert_eq!(aliases.apply("c"), "c");
+        assert_eq!(aliases.stale(), vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_alias_is_not_failpoint() {
+        let mut aliases = Aliases::new(&[(
+            "aliases test raw".to_string(),
+            "aliases test alias".to_string(),
+        )]);
+        assert_eq!(aliases.apply("aliases test raw"), "aliases test alias");
+        assert!(intern::forms_of("aliases test alias").is_none());
+    }
+}
@@ -0,0 +1,287 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::__private::FAILPOINTS;
+use crate::report::{ExecutionPath, ImpureInjection};
+
+/// Values injected by override failpoints which may be classified
+/// as a success or a failure
+///
+/// Used by `Runner::with_audit_injections()` (with `audit` feature)
+/// to find failpoints which inject a success where a failure was
+/// likely intended. Implement it for your own types to make their
+/// injections auditable; injections of types which don't implement
+/// it are reported as unclassified.
+///
+/// It may also provide a digest of the value, which is used instead
+/// of its [`Debug`] representation by
+/// `Runner::with_audit_injection_purity()` (with `audit` feature).
+///
+/// ```
+/// use faine::InjectedOutcome;
+///
+/// enum Status {
+///     Done,
+///     Rejected,
+/// }
+///
+/// impl InjectedOutcome for Status {
+///     fn is_success(&self) -> bool {
+///         matches!(self, Status::Done)
+///     }
+/// }
+/// ```
+pub trait InjectedOutcome {
+    /// Whether the value represents a success
+    fn is_success(&self) -> bool;
+
+    /// Digest of the value, which must be the same for values which
+    /// behave the same
+    ///
+    /// If `None`, [`Debug`] representation is used if the value
+    /// implements it, otherwise the value is not audited for purity.
+    fn digest(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T, E> InjectedOutcome for Result<T, E> {
+    fn is_success(&self) -> bool {
+        self.is_ok()
+    }
+}
+
+impl<T> InjectedOutcome for Option<T> {
+    fn is_success(&self) -> bool {
+        self.is_some()
+    }
+}
+
+/// Injected value wrapper, for classification with autoref specialization
+///
+/// Not a public API. `(&Injected(&value)).classify()` resolves to
+/// [`ClassifyInjected`] if the value implements [`InjectedOutcome`],
+/// and to [`ClassifyUnknown`] otherwise.
+pub struct Injected<'a, T>(pub &'a T);
+
+pub trait ClassifyInjected {
+    fn classify(&self) -> Option<bool>;
+}
+
+impl<T: InjectedOutcome> ClassifyInjected for Injected<'_, T> {
+    fn classify(&self) -> Option<bool> {
+        Some(self.0.is_success())
+    }
+}
+
+pub trait ClassifyUnknown {
+    fn classify(&self) -> Option<bool>;
+}
+
+impl<T> ClassifyUnknown for &Injected<'_, T> {
+    fn classify(&self) -> Option<bool> {
+        None
+    }
+}
+
+pub trait DigestOutcomeDebug {
+    fn digest(&self) -> Option<String>;
+}
+
+impl<T: InjectedOutcome + Debug> DigestOutcomeDebug for &&&Injected<'_, T> {
+    fn digest(&self) -> Option<String> {
+        self.0.digest().or_else(|| Some(format!("{:?}", self.0)))
+    }
+}
+
+pub trait DigestOutcome {
+    fn digest(&self) -> Option<String>;
+}
+
+impl<T: InjectedOutcome> DigestOutcome for &&Injected<'_, T> {
+    fn digest(&self) -> Option<String> {
+        self.0.digest()
+    }
+}
+
+pub trait DigestDebug {
+    fn digest(&self) -> Option<String>;
+}
+
+impl<T: Debug> DigestDebug for &Injected<'_, T> {
+    fn digest(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+pub trait DigestUnknown {
+    fn digest(&self) -> Option<String>;
+}
+
+impl<T> DigestUnknown for Injected<'_, T> {
+    fn digest(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Digests of values injected by failpoints, compared across activations
+#[derive(Debug, Default)]
+pub struct PurityAudit {
+    first: HashMap<&'static str, (String, ExecutionPath)>,
+    impure: Vec<ImpureInjection>,
+}
+
+impl PurityAudit {
+    /// Whether recording a digest needs the current path, that is, it's
+    /// either the first one seen or the first one which differs
+    fn needs_path(&self, failpoint: &'static str, digest: &str) -> bool {
+        match self.first.get(failpoint) {
+            Some((first, _)) => {
+                first != digest
+                    && !self
+                        .impure
+                        .iter()
+                        .any(|impure| impure.failpoint == failpoint)
+            }
+            None => true,
+        }
+    }
+
+    fn record(&mut self, failpoint: &'static str, digest: String, path: ExecutionPath) {
+        match self.first.get(failpoint) {
+            Some((first, first_path)) => self.impure.push(ImpureInjection {
+                failpoint,
+                first: first.clone(),
+                first_path: first_path.clone(),
+                later: digest,
+                later_path: path,
+            }),
+            None => {
+                self.first.insert(failpoint, (digest, path));
+            }
+        }
+    }
+
+    pub fn into_impure(self) -> Vec<ImpureInjection> {
+        self.impure
+    }
+}
+
+/// Returns its argument, after passing it to a function
+///
+/// Used by `inject_return!` to audit a returned value while keeping
+/// its type inferred from the return type of the enclosing function.
+pub fn inspect<T>(value: T, func: impl FnOnce(&T)) -> T {
+    func(&value);
+    value
+}
+
+/// Compare digest of a value injected by an activated failpoint with
+/// the one seen on its first activation
+///
+/// Does nothing unless injection purity audit is enabled, in which
+/// case the digest is only computed.
+pub fn audit_purity(name: &'static str, digest: impl FnOnce() -> Option<String>) {
+    let enabled = FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .is_some_and(|state| state.injection_purity.is_some())
+    });
+    // computed outside of the borrow, as Debug implementations may
+    // visit failpoints too
+    let Some(digest) = enabled.then(digest).flatten() else {
+        return;
+    };
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state
+            && let Some(purity) = &state.injection_purity
+            && purity.needs_path(name, &digest)
+        {
+            let path = state.current_path();
+            if let Some(purity) = &mut state.injection_purity {
+                purity.record(name, digest, path);
+            }
+        }
+    });
+}
+
+/// Record classification of a value injected by an activated failpoint
+///
+/// Does nothing unless injection audit is enabled.
+pub fn audit_injection(name: &'static str, success: Option<bool>, intentional_success: bool) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state
+            && let Some(audit) = &mut state.injection_audit
+        {
+            let stats = audit.entry(name).or_default();
+            match success {
+                Some(true) => stats.success += 1,
+                Some(false) => stats.failure += 1,
+                None => stats.unclassified += 1,
+            }
+            stats.intentional_success |= intentional_success;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::needless_borrow)] // autoref is what selects the implementation
+    fn test_classify() {
+        struct Opaque;
+        assert_eq!((&Injected(&Ok::<(), ()>(()))).classify(), Some(true));
+        assert_eq!((&Injected(&Err::<(), ()>(()))).classify(), Some(false));
+        assert_eq!((&Injected(&None::<()>)).classify(), Some(false));
+        assert_eq!((&Injected(&Opaque)).classify(), None);
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn test_digest() {
+        struct Opaque;
+        #[derive(Debug)]
+        struct Noisy;
+        impl InjectedOutcome for Noisy {
+            fn is_success(&self) -> bool {
+                false
+            }
+            fn digest(&self) -> Option<String> {
+                Some("noisy".into())
+            }
+        }
+        assert_eq!(
+            (&&&&Injected(&Err::<(), _>(1))).digest().as_deref(),
+            Some("Err(1)")
+        );
+        assert_eq!((&&&&Injected(&Noisy)).digest().as_deref(), Some("noisy"));
+        assert_eq!(
+            (&&&&Injected(&"text")).digest().as_deref(),
+            Some("\"text\"")
+        );
+        assert_eq!((&&&&Injected(&Err::<(), _>(Opaque))).digest(), None);
+        assert_eq!((&&&&Injected(&Opaque)).digest(), None);
+    }
+
+    #[test]
+    fn test_purity_audit() {
+        let mut audit = PurityAudit::default();
+        assert!(audit.needs_path("a", "1"));
+        audit.record("a", "1".into(), Default::default());
+        assert!(!audit.needs_path("a", "1"));
+        assert!(audit.needs_path("a", "2"));
+        audit.record("a", "2".into(), Default::default());
+        assert!(!audit.needs_path("a", "3"));
+        let impure = audit.into_impure();
+        assert_eq!(impure.len(), 1);
+        assert_eq!(
+            (impure[0].first.as_str(), impure[0].later.as_str()),
+            ("1", "2")
+        );
+    }
+}
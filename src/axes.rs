@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::__private::FAILPOINTS;
+
+/// Configuration axis, as name and possible values
+pub type Axis = (&'static str, Vec<&'static str>);
+
+/// Distribution of iteration budget among axis combinations
+///
+/// See [`Runner::with_axis_budget()`](crate::Runner::with_axis_budget).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AxisBudget {
+    /// Each combination of axis values gets an equal share of
+    /// [`Runner::with_max_iterations()`](crate::Runner::with_max_iterations)
+    #[default]
+    Divided,
+
+    /// Combinations share the budget, so when it's insufficient,
+    /// later combinations may not be explored at all
+    Shared,
+}
+
+/// Number of combinations of values of given axes
+pub fn num_combinations(axes: &[Axis]) -> usize {
+    axes.iter().map(|(_, values)| values.len()).product()
+}
+
+/// Values of given axes in a combination with given index
+///
+/// Combination 0 has the first value of each axis.
+pub fn combination(axes: &[Axis], mut index: usize) -> Vec<(&'static str, &'static str)> {
+    axes.iter()
+        .map(|(name, values)| {
+            let value = values[index % values.len()];
+            index /= values.len();
+            (*name, value)
+        })
+        .collect()
+}
+
+/// Value of a configuration axis in the current iteration
+///
+/// Axes are declared with [`Runner::with_axis()`], and each
+/// combination of their values is explored as a separate
+/// configuration of the tested code.
+///
+/// # Panics
+///
+/// If the axis was not declared, or when called outside of [`Runner::run()`].
+///
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_axis()`]: crate::Runner::with_axis
+pub fn axis(name: &str) -> &'static str {
+    FAILPOINTS
+        .with_borrow(|state| {
+            state.as_ref().and_then(|state| {
+                state
+                    .axis_values
+                    .iter()
+                    .find(|(axis, _)| *axis == name)
+                    .map(|(_, value)| *value)
+            })
+        })
+        .unwrap_or_else(|| panic!("axis `{name}` is not declared with Runner::with_axis()"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combination() {
+        let axes = vec![
+            ("mode", vec!["sync", "async"]),
+            ("checksum", vec!["on", "off", "auto"]),
+        ];
+        assert_eq!(num_combinations(&axes), 6);
+        assert_eq!(
+            combination(&axes, 0),
+            [("mode", "sync"), ("checksum", "on")]
+        );
+        assert_eq!(
+            combination(&axes, 3),
+            [("mode", "async"), ("checksum", "off")]
+        );
+        assert_eq!(
+            combination(&axes, 5),
+            [("mode", "async"), ("checksum", "auto")]
+        );
+        assert_eq!(num_combinations(&[]), 1);
+        assert!(combination(&[], 0).is_empty());
+    }
+}
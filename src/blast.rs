@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::collections::BitSet;
+use crate::common::Branch;
+use crate::report::{ExecutionPath, Step};
+
+/// Failpoints which become unreachable when a given failpoint is activated
+///
+/// Computed by comparing failpoints visited after a failpoint was
+/// skipped with these visited after it was activated, for each place
+/// on the explored paths where both branches were taken.
+///
+/// See [`Report::blast_radius()`](crate::Report::blast_radius).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BlastRadius {
+    /// Failpoints which are never reached after activation
+    pub always_skipped: Vec<&'static str>,
+
+    /// Failpoints which are not reached after some of activations
+    pub sometimes_skipped: Vec<&'static str>,
+}
+
+impl BlastRadius {
+    /// Total number of failpoints affected by activation
+    pub fn len(&self) -> usize {
+        self.always_skipped.len() + self.sometimes_skipped.len()
+    }
+
+    /// Whether activation does not affect reachability of any failpoints
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Compute blast radii of all failpoints
+///
+/// Paths are merged into a prefix tree, and sets of failpoints
+/// reachable from each tree node are propagated bottom up as bitsets,
+/// so no per-node sets of names are constructed.
+pub(crate) fn blast_radii(paths: &[ExecutionPath]) -> BTreeMap<&'static str, BlastRadius> {
+    let names: Vec<&'static str> = paths
+        .iter()
+        .flat_map(|path| path.steps.iter().map(|step| step.failpoint))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let indexes: HashMap<&'static str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (*name, index))
+        .collect();
+
+    // node 0 is the root; for other nodes, parent and failpoint of
+    // the incoming step are stored, parents always preceding children
+    let mut edges: Vec<Option<(usize, usize)>> = vec![None];
+    let mut children: HashMap<(usize, &Step), usize> = Default::default();
+    for path in paths {
+        let mut node = 0;
+        for step in &path.steps {
+            node = *children.entry((node, step)).or_insert_with(|| {
+                edges.push(Some((node, indexes[step.failpoint])));
+                edges.len() - 1
+            });
+        }
+    }
+
+    let mut reachable = vec![BitSet::new(names.len()); edges.len()];
+    for node in (1..edges.len()).rev() {
+        if let Some((parent, failpoint)) = edges[node] {
+            let (head, tail) = reachable.split_at_mut(node);
+            head[parent].union_with(&tail[0]);
+            head[parent].insert(failpoint);
+        }
+    }
+
+    let mut aggregated: BTreeMap<usize, (BitSet, BitSet)> = Default::default();
+    for (&(parent, step), &activated) in &children {
+        if step.branch != Branch::Activate || step.variant.is_some() {
+            continue;
+        }
+        let skip_step = Step {
+            branch: Branch::Skip,
+            ..step.clone()
+        };
+        let Some(&skipped) = children.get(&(parent, &skip_step)) else {
+            continue;
+        };
+        let mut suppressed = reachable[skipped].clone();
+        suppressed.difference_with(&reachable[activated]);
+        aggregated
+            .entry(indexes[step.failpoint])
+            .and_modify(|(always, sometimes)| {
+                always.intersect_with(&suppressed);
+                sometimes.union_with(&suppressed);
+            })
+            .or_insert_with(|| (suppressed.clone(), suppressed));
+    }
+
+    aggregated
+        .into_iter()
+        .map(|(failpoint, (always, mut sometimes))| {
+            sometimes.difference_with(&always);
+            (
+                names[failpoint],
+                BlastRadius {
+                    always_skipped: always.iter().map(|index| names[index]).collect(),
+                    sometimes_skipped: sometimes.iter().map(|index| names[index]).collect(),
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(steps: &[(&'static str, Branch)]) -> ExecutionPath {
+        ExecutionPath {
+            steps: steps
+                .iter()
+                .map(|&(failpoint, branch)| Step {
+                    failpoint,
+                    branch,
+                    variant: None,
+                    variant_index: None,
+                    trigger: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_blast_radii() {
+        use Branch::*;
+        // a suppresses b and c; b suppresses c only when it's visited
+        // the second time
+        let paths = [
+            path(&[("a", Activate)]),
+            path(&[("a", Skip), ("b", Activate), ("b", Activate)]),
+            path(&[("a", Skip), ("b", Activate), ("b", Skip), ("c", Skip)]),
+            path(&[("a", Skip), ("b", Skip), ("b", Activate), ("c", Activate)]),
+            path(&[("a", Skip), ("b", Skip), ("b", Skip), ("c", Skip)]),
+        ];
+        let radii = blast_radii(&paths);
+        assert_eq!(
+            radii["a"],
+            BlastRadius {
+                always_skipped: vec!["b", "c"],
+                sometimes_skipped: vec![],
+            }
+        );
+        assert_eq!(
+            radii["b"],
+            BlastRadius {
+                always_skipped: vec![],
+                sometimes_skipped: vec!["c"],
+            }
+        );
+        assert!(!radii.contains_key("c"));
+    }
+}
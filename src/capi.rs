@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! C API for instrumenting C code linked into the tested binary
+//!
+//! Exports the following functions with C linkage:
+//!
+//! ```c
+//! /* Returns 1 if the failpoint should be activated, 0 otherwise */
+//! int faine_visit(const char* name);
+//!
+//! /* Returns 1 if failpoints are processed on the current thread */
+//! int faine_enabled(void);
+//! ```
+//!
+//! These behave the same way as [`inject_return!`](crate::inject_return)
+//! and other macros: failpoints are only processed on the thread running
+//! [`Runner::run()`](crate::Runner::run), and both functions return 0
+//! elsewhere. The only difference is that C code is not part of any
+//! crate, so its failpoints do not belong to one. A failpoint in C code
+//! looks like:
+//!
+//! ```c
+//! if (faine_visit("read_block"))
+//!     return EIO;
+//! ```
+//!
+//! Available with `capi` feature.
+
+use std::ffi::{CStr, c_char, c_int};
+
+use crate::__private::{FAILPOINTS, visit};
+use crate::common::{Branch, Label};
+use crate::intern::FailpointId;
+
+const LOCATION: &str = "(C code)";
+
+/// Visit a named failpoint
+///
+/// Name is interned on first sight, so the pointer does not need to
+/// outlive the call. Returns 0 for a null name.
+///
+/// Unlike names given to `inject_*` macros, names coming from C code
+/// do not belong to any crate (see [`FailpointId`]), so a failpoint
+/// with the same name used by Rust code is the same failpoint.
+///
+/// # Safety
+///
+/// `name` must be null or point to a nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn faine_visit(name: *const c_char) -> c_int {
+    if name.is_null() {
+        return 0;
+    }
+    // SAFETY: guaranteed by the caller
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+    match visit(Label::Failpoint(FailpointId::intern(&name)), LOCATION) {
+        Branch::Activate => 1,
+        Branch::Skip => 0,
+    }
+}
+
+/// Check whether failpoints are processed on the current thread
+///
+/// Allows C code to skip preparing failpoint names when they would
+/// be ignored anyway.
+#[unsafe(no_mangle)]
+pub extern "C" fn faine_enabled() -> c_int {
+    FAILPOINTS.with_borrow(|state| state.as_ref().is_some_and(|state| state.enabled).into())
+}
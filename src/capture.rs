@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Per-iteration capture of stdout and stderr
+//!
+//! Implemented by redirecting file descriptors 1 and 2 into temporary
+//! files, so only output written directly to these descriptors is
+//! captured. Note that under libtest, `print!` family of macros write
+//! into test harness's own capture buffer unless `--nocapture` is used.
+//! Descriptors are shared by the process, so runners capturing output
+//! run exclusively of other runners, but output of other threads
+//! which do not run faine is captured as well.
+//!
+//! On non-unix platforms, capture is a no-op.
+
+/// Maximal amount of output stored per stream per iteration
+const MAX_CAPTURED_BYTES: usize = 64 * 1024;
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_CAPTURED_BYTES {
+        let total = text.len();
+        let mut end = MAX_CAPTURED_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text += &format!(
+            "\n[faine: output truncated, {} byte(s) omitted]",
+            total - end
+        );
+    }
+    text
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::fs::File;
+    use std::io::{self, Write};
+    use std::os::fd::{AsRawFd, RawFd};
+    use std::os::unix::fs::FileExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_file() -> io::Result<File> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "faine-capture-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok(file)
+    }
+
+    /// Convert -1 returned by a libc function into an error
+    fn check(res: libc::c_int) -> io::Result<libc::c_int> {
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(res)
+        }
+    }
+
+    pub struct Redirect {
+        fd: RawFd,
+        saved_fd: RawFd,
+        file: File,
+    }
+
+    impl Redirect {
+        pub fn new(fd: RawFd) -> io::Result<Self> {
+            let file = temp_file()?;
+            flush(fd);
+            // SAFETY: plain file descriptor manipulation, all descriptors are valid
+            unsafe {
+                let saved_fd = check(libc::dup(fd))?;
+                if let Err(err) = check(libc::dup2(file.as_raw_fd(), fd)) {
+                    libc::close(saved_fd);
+                    return Err(err);
+                }
+                Ok(Self { fd, saved_fd, file })
+            }
+        }
+
+        pub fn contents(&self) -> String {
+            flush(self.fd);
+            let len = self.file.metadata().map(|m| m.len()).unwrap_or(0) as usize;
+            let mut buf = vec![0; len];
+            let len = self.file.read_at(&mut buf, 0).unwrap_or(0);
+            buf.truncate(len);
+            String::from_utf8_lossy(&buf).into_owned()
+        }
+    }
+
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            flush(self.fd);
+            // SAFETY: restoring descriptor saved in constructor
+            let res = unsafe {
+                check(libc::dup2(self.saved_fd, self.fd)).and(check(libc::close(self.saved_fd)))
+            };
+            if let Err(err) = res {
+                eprintln!(
+                    "faine: warning: cannot restore captured descriptor {}: {err}",
+                    self.fd
+                );
+            }
+        }
+    }
+
+    fn flush(fd: RawFd) {
+        match fd {
+            1 => {
+                let _ = std::io::stdout().flush();
+            }
+            2 => {
+                let _ = std::io::stderr().flush();
+            }
+            _ => {}
+        }
+    }
+
+    pub const SUPPORTED: bool = true;
+}
+
+#[cfg(not(unix))]
+mod imp {
+    pub struct Redirect;
+
+    impl Redirect {
+        pub fn new(_fd: i32) -> std::io::Result<Self> {
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+
+        pub fn contents(&self) -> String {
+            String::new()
+        }
+    }
+
+    pub const SUPPORTED: bool = false;
+}
+
+pub use imp::SUPPORTED;
+
+/// Output captured during a single iteration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CapturedOutput {
+    /// Text written to stdout
+    pub stdout: String,
+
+    /// Text written to stderr
+    pub stderr: String,
+}
+
+fn redirect(fd: i32) -> Option<imp::Redirect> {
+    imp::Redirect::new(fd)
+        .inspect_err(|err| {
+            if SUPPORTED {
+                eprintln!("faine: warning: cannot capture descriptor {fd}: {err}");
+            }
+        })
+        .ok()
+}
+
+/// Active capture of both output streams
+pub struct Capture {
+    stdout: Option<imp::Redirect>,
+    stderr: Option<imp::Redirect>,
+}
+
+impl Capture {
+    pub fn start() -> Self {
+        Self {
+            stdout: redirect(1),
+            stderr: redirect(2),
+        }
+    }
+
+    pub fn stdout(&self) -> String {
+        self.stdout
+            .as_ref()
+            .map(|redirect| redirect.contents())
+            .unwrap_or_default()
+    }
+
+    pub fn stderr(&self) -> String {
+        self.stderr
+            .as_ref()
+            .map(|redirect| redirect.contents())
+            .unwrap_or_default()
+    }
+
+    pub fn finish(self) -> CapturedOutput {
+        CapturedOutput {
+            stdout: truncate(self.stdout()),
+            stderr: truncate(self.stderr()),
+        }
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            // iteration has failed; restore descriptors first, then
+            // make captured output visible
+            let stdout = truncate(self.stdout());
+            let stderr = truncate(self.stderr());
+            self.stdout.take();
+            self.stderr.take();
+            eprintln!("faine: captured stdout of the failing path:\n{stdout}");
+            eprintln!("faine: captured stderr of the failing path:\n{stderr}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("foo".into()), "foo");
+        let long = "x".repeat(MAX_CAPTURED_BYTES + 10);
+        let truncated = truncate(long);
+        assert!(truncated.starts_with(&"x".repeat(MAX_CAPTURED_BYTES)));
+        assert!(truncated.ends_with("[faine: output truncated, 10 byte(s) omitted]"));
+    }
+}
@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fmt::Display;
+
+use crate::__private::FAILPOINTS;
+use crate::functions::no_faults;
+
+/// Result of a check executed at a [`checkpoint()`]
+pub trait CheckpointResult {
+    /// Description of the violation, if the check has failed
+    fn into_violation(self) -> Option<String>;
+}
+
+impl CheckpointResult for bool {
+    fn into_violation(self) -> Option<String> {
+        (!self).then(|| "check failed".to_string())
+    }
+}
+
+impl<E: Display> CheckpointResult for Result<(), E> {
+    fn into_violation(self) -> Option<String> {
+        self.err().map(|err| err.to_string())
+    }
+}
+
+/// Checkpoint passed on an execution path
+///
+/// See [`checkpoint()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Checkpoint {
+    /// Name of the checkpoint
+    pub name: &'static str,
+
+    /// Number of [steps](crate::ExecutionPath::steps) taken before the checkpoint
+    pub position: usize,
+
+    /// Description of the violation, if the check has failed
+    pub violation: Option<String>,
+}
+
+/// Check an invariant at an intermediate point of the tested flow
+///
+/// Runs `check` immediately, which allows catching violations of
+/// invariants which only hold at some moments, and may be masked by
+/// code which follows, such as recovery. The check returns either
+/// `bool` or `Result<(), E>` with displayable error, and runs in a
+/// [critical section](crate::no_faults()), so failpoints it visits
+/// are not activated.
+///
+/// Checkpoints are recorded in [`ExecutionPath::checkpoints`], and
+/// shown in paths as `⟨name⟩`. By default, a violation panics, failing
+/// the path as usual. With [`Runner::with_continue_after_checkpoint_violation()`]
+/// the iteration continues, so the end state is checked as well, and
+/// [`Runner::run()`] fails with [`Error::CheckpointViolated`] afterwards.
+/// Violating paths are grouped by checkpoint with
+/// [`Report::checkpoint_violations()`].
+///
+/// Like `inject_*` macros, it does nothing outside of [`Runner::run()`].
+///
+/// ```
+/// use faine::{Runner, checkpoint, inject_return};
+///
+/// fn append(journal: &mut Vec<u32>, index: &mut Vec<u32>) -> Result<(), ()> {
+///     journal.push(1);
+///     checkpoint("journal sealed", || journal.len() >= index.len());
+///     inject_return!("update index", Err(()));
+///     index.push(1);
+///     Ok(())
+/// }
+///
+/// let report = Runner::default()
+///     .run(|| {
+///         let (mut journal, mut index) = (vec![], vec![]);
+///         let _ = append(&mut journal, &mut index);
+///     })
+///     .unwrap();
+/// assert_eq!(report.paths()[0].to_string(), "⟨journal sealed⟩ → [update index]");
+/// ```
+///
+/// [`ExecutionPath::checkpoints`]: crate::ExecutionPath::checkpoints
+/// [`Runner::with_continue_after_checkpoint_violation()`]: crate::Runner::with_continue_after_checkpoint_violation
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Error::CheckpointViolated`]: crate::Error::CheckpointViolated
+/// [`Report::checkpoint_violations()`]: crate::Report::checkpoint_violations
+#[track_caller]
+pub fn checkpoint<R: CheckpointResult>(name: &'static str, check: impl FnOnce() -> R) {
+    let active = FAILPOINTS.with_borrow(Option::is_some);
+    if !active || std::thread::panicking() {
+        return;
+    }
+    let violation = {
+        let _cs = no_faults();
+        check().into_violation()
+    };
+    let continue_after_violation = FAILPOINTS.with_borrow_mut(|state| {
+        let state = state.as_mut()?;
+        let position = state
+            .tree
+            .current_path()
+            .into_iter()
+            .filter_map(|(label, branch)| state.step(label, branch))
+            .count();
+        state.checkpoints.push(Checkpoint {
+            name,
+            position,
+            violation: violation.clone(),
+        });
+        Some(state.continue_after_checkpoint_violation)
+    });
+    if let Some(violation) = violation
+        && continue_after_violation == Some(false)
+    {
+        panic!("checkpoint {name} violated: {violation}");
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use crate::__private::FAILPOINTS;
+
+#[derive(Default)]
+struct Table {
+    names: Vec<&'static str>,
+    ids: HashMap<&'static str, usize>,
+}
+
+/// Interned names, numbered densely in order of interning
+#[derive(Default)]
+pub struct Names(RwLock<Table>);
+
+impl Names {
+    /// Intern a name, returning its index
+    pub fn intern(&self, name: &'static str) -> usize {
+        if let Some(id) = self
+            .0
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .ids
+            .get(name)
+        {
+            return *id;
+        }
+        let table = &mut *self.0.write().unwrap_or_else(|err| err.into_inner());
+        *table.ids.entry(name).or_insert_with(|| {
+            table.names.push(name);
+            table.names.len() - 1
+        })
+    }
+
+    /// Name with a given index
+    pub fn name(&self, id: usize) -> &'static str {
+        self.0.read().unwrap_or_else(|err| err.into_inner()).names[id]
+    }
+
+    /// All interned names, by index
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .names
+            .clone()
+    }
+}
+
+static CHECKS: LazyLock<Names> = LazyLock::new(Default::default);
+
+/// Intern a check name, returning its index in per-iteration bitmap
+pub fn intern_check(name: &'static str) -> usize {
+    CHECKS.intern(name)
+}
+
+/// Name of an interned check
+pub fn check_name(id: usize) -> &'static str {
+    CHECKS.name(id)
+}
+
+/// Record execution of a check in the current iteration
+///
+/// Does nothing outside of [`Runner::run()`](crate::Runner::run).
+pub fn record_check(id: usize) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            let (word, bit) = (id / 64, id % 64);
+            if state.checks.len() <= word {
+                state.checks.resize(word + 1, 0);
+            }
+            state.checks[word] |= 1 << bit;
+            if let Some(buffer) = &mut state.recent_events {
+                buffer.record_check(id);
+            }
+        }
+    });
+}
+
+/// Names of checks set in a bitmap, sorted
+pub fn executed_checks(bitmap: &[u64]) -> Vec<&'static str> {
+    let mut names: Vec<_> = CHECKS
+        .names()
+        .into_iter()
+        .enumerate()
+        .filter(|(id, _)| {
+            bitmap
+                .get(id / 64)
+                .is_some_and(|word| word & (1 << (id % 64)) != 0)
+        })
+        .map(|(_, name)| name)
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap() {
+        let first = intern_check("checks test first");
+        let second = intern_check("checks test second");
+        assert_eq!(intern_check("checks test first"), first);
+        let mut bitmap = vec![0; second / 64 + 1];
+        bitmap[second / 64] |= 1 << (second % 64);
+        assert_eq!(executed_checks(&bitmap), ["checks test second"]);
+        assert!(executed_checks(&[]).is_empty());
+    }
+}
@@ -28,6 +28,40 @@ impl<T> IndexMut<Branch> for BranchVec<T> {
     }
 }
 
+/// Fixed size set of small integers
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct BitSet(Vec<u64>);
+
+impl BitSet {
+    pub fn new(size: usize) -> Self {
+        Self(vec![0; size.div_ceil(64)])
+    }
+
+    pub fn insert(&mut self, value: usize) {
+        self.0[value / 64] |= 1 << (value % 64);
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.0[value / 64] & (1 << (value % 64)) != 0
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a |= b);
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= b);
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= !b);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.0.len() * 64).filter(|value| self.contains(*value))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,4 +83,27 @@ mod tests {
         assert_eq!(v[Branch::Skip], 0);
         assert_eq!(v[Branch::Activate], 0);
     }
+
+    #[test]
+    fn test_bitset() {
+        let mut a = BitSet::new(100);
+        a.insert(1);
+        a.insert(70);
+        let mut b = BitSet::new(100);
+        b.insert(70);
+        b.insert(99);
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.iter().collect::<Vec<_>>(), vec![1, 70, 99]);
+
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), vec![70]);
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert_eq!(difference.iter().collect::<Vec<_>>(), vec![1]);
+        assert!(!difference.contains(70));
+    }
 }
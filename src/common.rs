@@ -1,8 +1,12 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use std::hash::{Hash, Hasher};
+
+use crate::intern::FailpointId;
+
 /// Path chosen when execution passes through a failpoint
-#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
 pub enum Branch {
     /// Failpoint is skipped lile it never existed
     Skip,
@@ -12,14 +16,208 @@ pub enum Branch {
     Activate,
 }
 
+/// Lifetime of a condition set by [`inject_exhaust!`]
+///
+/// Conditions are always reset when a new iteration starts.
+///
+/// [`inject_exhaust!`]: crate::inject_exhaust
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Exhaust {
+    /// Condition holds until the end of the iteration, and cannot
+    /// be cleared with [`clear_condition()`](crate::clear_condition)
+    UntilIterationEnd,
+
+    /// Condition holds until cleared with
+    /// [`clear_condition()`](crate::clear_condition), modeling a
+    /// resource which may be released (for instance, disk space
+    /// being freed)
+    UntilCleared,
+}
+
+/// Trigger of a failpoint, relative to other failpoints
+///
+/// See `trigger:` argument of [`inject_return!`](crate::inject_return).
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum After {
+    /// Failpoint may only be activated on the first visit following
+    /// each activation of the named failpoint on the current path
+    Activation(&'static str),
+}
+
+/// Expectation declared at a failpoint site
+///
+/// See `expect:` argument of [`inject_return!`](crate::inject_return).
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum Expect {
+    /// Failpoint is expected to be activated on some explored path
+    Activated,
+}
+
+impl std::fmt::Display for Expect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expect::Activated => f.write_str("activated"),
+        }
+    }
+}
+
+/// Importance of a failpoint declared at its site
+///
+/// See `weight:` argument of [`inject_return!`](crate::inject_return).
+#[derive(Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum Weight {
+    /// Activations are explored last, and are first to be left
+    /// unexplored when the budget is exhausted
+    Low,
+
+    /// Default weight of failpoints
+    #[default]
+    Normal,
+
+    /// Activations are explored first
+    High,
+}
+
+impl std::fmt::Display for Weight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Weight::Low => f.write_str("low"),
+            Weight::Normal => f.write_str("normal"),
+            Weight::High => f.write_str("high"),
+        }
+    }
+}
+
+/// Exploration tier, see [`Runner::smoke()`]
+///
+/// [`Runner::smoke()`]: crate::Runner::smoke
+#[derive(Debug, Default, Eq, PartialEq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum Tier {
+    /// All combinations of failpoints are explored
+    #[default]
+    Full,
+
+    /// Each failpoint is activated once, see [`Runner::smoke()`]
+    ///
+    /// [`Runner::smoke()`]: crate::Runner::smoke
+    Smoke,
+}
+
+/// Kind of the current iteration, see [`current_phase()`]
+///
+/// [`current_phase()`]: crate::current_phase
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum Phase {
+    /// Iteration explores a new path
+    Exploring,
+
+    /// Iteration replays an already explored path, either to rerun a
+    /// failure (see [`Runner::with_failure_reruns()`]) or for self
+    /// verification (see [`Runner::with_self_verification()`])
+    ///
+    /// [`Runner::with_failure_reruns()`]: crate::Runner::with_failure_reruns
+    /// [`Runner::with_self_verification()`]: crate::Runner::with_self_verification
+    Replaying,
+}
+
 /// Label used when describing code execution path
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[doc(hidden)] // not part of public API until introspection API is introduced
+#[non_exhaustive]
 pub enum Label {
     /// Code execution passes through a named failpont
-    Failpoint(&'static str),
+    Failpoint(FailpointId),
+
+    /// Code execution passes through an unnamed failpoint
+    Site(&'static Site),
+
+    /// Code execution passes through a single variant of a multi-valued
+    /// failpoint, such as one defined with `inject_perturb!`
+    Variant(&'static str, &'static str),
+
+    /// Code execution passes through a point where a batch may be cut
+    /// before the item with given index, defined with `batch_cut!`
+    Cut(FailpointId, usize),
+
+    /// Decision selecting a combination of configuration axis values
+    /// with given index, taken before code execution
+    AxisCombination(usize),
 
     /// Code execution has finished
     Finished,
-    // TODO: Panic,
+
+    /// Code execution has finished after exceeding iteration timeout
+    TimedOut,
+
+    /// Code execution has panicked, and the panic was caught
+    Panicked,
+}
+
+impl Label {
+    /// Name of the failpoint, if label describes one
+    pub fn failpoint_name(&self) -> Option<&'static str> {
+        match self {
+            Label::Failpoint(id) => Some(id.name()),
+            Label::Site(site) => Some(site.name()),
+            Label::Variant(name, _) => Some(name),
+            Label::Cut(id, _) => Some(id.name()),
+            Label::AxisCombination(_) | Label::Finished | Label::TimedOut | Label::Panicked => None,
+        }
+    }
+
+    /// Description of the variant, if label describes one
+    pub fn variant(&self) -> Option<&'static str> {
+        match self {
+            Label::Variant(_, variant) => Some(variant),
+            Label::Cut(..) => Some("cut before"),
+            _ => None,
+        }
+    }
+
+    /// Index qualifying the variant, if label has one
+    pub fn variant_index(&self) -> Option<usize> {
+        match self {
+            Label::Cut(_, index) => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+/// Expansion site of an unnamed failpoint
+///
+/// Each unnamed failpoint defines a static of this type, and its
+/// address is used to identify the failpoint, as autogenerated
+/// names are not guaranteed to be unique.
+#[doc(hidden)]
+pub struct Site {
+    name: &'static str,
+}
+
+impl Site {
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl PartialEq for Site {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Site {}
+
+impl Hash for Site {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self, state)
+    }
 }
@@ -0,0 +1,65 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::LazyLock;
+
+use crate::__private::FAILPOINTS;
+use crate::checks::Names;
+
+static COUNTERS: LazyLock<Names> = LazyLock::new(Default::default);
+
+/// Intern a counter name, returning its index in per-iteration array
+pub fn intern_counter(name: &'static str) -> usize {
+    COUNTERS.intern(name)
+}
+
+/// Name of an interned counter
+pub fn counter_name(id: usize) -> &'static str {
+    COUNTERS.name(id)
+}
+
+/// Add to a counter in the current iteration
+///
+/// Does nothing outside of [`Runner::run()`](crate::Runner::run).
+pub fn add_to_counter(id: usize, value: u64) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            if state.counters.len() <= id {
+                state.counters.resize(id + 1, 0);
+            }
+            state.counters[id] = state.counters[id].saturating_add(value);
+            if let Some(buffer) = &mut state.recent_events {
+                buffer.record_counter(id, value);
+            }
+        }
+    });
+}
+
+/// Names and values of nonzero counters in an array, sorted by name
+pub fn counter_values(counters: &[u64]) -> Vec<(&'static str, u64)> {
+    let mut values: Vec<_> = COUNTERS
+        .names()
+        .into_iter()
+        .zip(counters)
+        .filter(|(_, value)| **value > 0)
+        .map(|(name, value)| (name, *value))
+        .collect();
+    values.sort_unstable();
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_values() {
+        let first = intern_counter("counters test first");
+        let second = intern_counter("counters test second");
+        assert_eq!(intern_counter("counters test first"), first);
+        let mut counters = vec![0; first.max(second) + 1];
+        counters[second] = 3;
+        assert_eq!(counter_values(&counters), [("counters test second", 3)]);
+        assert!(counter_values(&[]).is_empty());
+    }
+}
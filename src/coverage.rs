@@ -0,0 +1,434 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Failpoint coverage aggregated across test runs
+//!
+//! When [`Runner::with_coverage_dir()`] is used, each exploration writes
+//! a small record file describing which failpoints were reached and
+//! activated, and by which test. [`heatmap()`] aggregates all records
+//! in a directory, which allows to see which failpoints are poorly
+//! covered throughout the whole test suite.
+//!
+//! [`Runner::with_coverage_dir()`]: crate::Runner::with_coverage_dir
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::report::Report;
+
+const RECORD_HEADER: &str = "faine-coverage 1";
+const RECORD_EXTENSION: &str = "faine-coverage";
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => res.push('\t'),
+                Some('n') => res.push('\n'),
+                Some(c) => res.push(c),
+                None => res.push('\\'),
+            }
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut res = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => res += "\\\"",
+            '\\' => res += "\\\\",
+            '\n' => res += "\\n",
+            '\t' => res += "\\t",
+            c if (c as u32) < 0x20 => res += &format!("\\u{:04x}", c as u32),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Name of the current test binary, without cargo hash suffix
+fn binary_name() -> String {
+    let name = std::env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unknown".into());
+    match name.rsplit_once('-') {
+        Some((base, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            base.into()
+        }
+        _ => name,
+    }
+}
+
+/// Write coverage record of a single exploration
+pub(crate) fn write_record(dir: &Path, test_name: &str, report: &Report) -> io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let binary = binary_name();
+    let mut reached: BTreeMap<&str, usize> = Default::default();
+    let mut activated: BTreeMap<&str, usize> = Default::default();
+    for path in report.paths() {
+        let visited: BTreeSet<_> = path.steps.iter().map(|step| step.failpoint).collect();
+        for failpoint in visited {
+            *reached.entry(failpoint).or_default() += 1;
+        }
+        let path_activated: BTreeSet<_> = path.activated_failpoints().collect();
+        for failpoint in path_activated {
+            *activated.entry(failpoint).or_default() += 1;
+        }
+    }
+
+    let mut contents = format!(
+        "{RECORD_HEADER}\nbinary\t{}\ntest\t{}\n",
+        escape(&binary),
+        escape(test_name)
+    );
+    for (failpoint, reached) in reached {
+        contents += &format!(
+            "failpoint\t{}\t{}\t{}\t{}\n",
+            escape(report.failpoint_location(failpoint).unwrap_or("")),
+            reached,
+            activated.get(failpoint).copied().unwrap_or(0),
+            escape(failpoint),
+        );
+    }
+    for (failpoint, expect) in report.site_expectations() {
+        contents += &format!("expect\t{}\t{}\n", expect, escape(failpoint));
+    }
+
+    std::fs::create_dir_all(dir)?;
+    let file_name = format!(
+        "{}-{}-{}.{RECORD_EXTENSION}",
+        binary,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    std::fs::write(dir.join(file_name), contents)
+}
+
+/// Coverage of a single failpoint across all recorded tests
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FailpointCoverage {
+    /// Name of the failpoint
+    pub name: String,
+
+    /// Location of the failpoint in the source code (`file:line:column`)
+    pub location: String,
+
+    /// Tests which have reached the failpoint, as `binary::test`
+    pub tests: BTreeSet<String>,
+
+    /// Number of explored paths which reached the failpoint
+    pub reached: usize,
+
+    /// Number of explored paths which activated the failpoint
+    pub activated: usize,
+
+    /// Whether the failpoint site declares it's expected to be activated
+    ///
+    /// Only known for failpoints visited by at least one test.
+    pub expect_activated: bool,
+}
+
+impl FailpointCoverage {
+    /// Source file the failpoint is located in
+    pub fn file(&self) -> &str {
+        let mut parts = self.location.rsplitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(_), Some(_), Some(file)) => file,
+            _ => &self.location,
+        }
+    }
+}
+
+/// Coverage of all failpoints in a single source file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileCoverage {
+    /// Source file path
+    pub file: String,
+
+    /// Number of distinct failpoints in this file
+    pub failpoints: usize,
+
+    /// Number of distinct tests reaching failpoints in this file
+    pub tests: usize,
+
+    /// Total number of paths reaching failpoints in this file
+    pub reached: usize,
+
+    /// Total number of paths activating failpoints in this file
+    pub activated: usize,
+}
+
+/// Failpoint coverage aggregated from records, see [`heatmap()`]
+#[derive(Debug, Clone, Default)]
+pub struct Heatmap {
+    failpoints: BTreeMap<String, FailpointCoverage>,
+}
+
+impl Heatmap {
+    /// Coverage of individual failpoints, ordered by name
+    pub fn failpoints(&self) -> impl Iterator<Item = &FailpointCoverage> {
+        self.failpoints.values()
+    }
+
+    /// Coverage grouped by source file, ordered by file
+    pub fn files(&self) -> Vec<FileCoverage> {
+        let mut files: BTreeMap<&str, (FileCoverage, BTreeSet<&str>)> = Default::default();
+        for failpoint in self.failpoints.values() {
+            let (file, tests) = files.entry(failpoint.file()).or_default();
+            file.file = failpoint.file().into();
+            file.failpoints += 1;
+            file.reached += failpoint.reached;
+            file.activated += failpoint.activated;
+            tests.extend(failpoint.tests.iter().map(String::as_str));
+        }
+        files
+            .into_values()
+            .map(|(mut file, tests)| {
+                file.tests = tests.len();
+                file
+            })
+            .collect()
+    }
+
+    /// Failpoints reached by exactly one test
+    ///
+    /// Coverage of these is fragile, as it may be lost with a change
+    /// of a single test.
+    pub fn single_test_failpoints(&self) -> Vec<&FailpointCoverage> {
+        self.failpoints
+            .values()
+            .filter(|failpoint| failpoint.tests.len() == 1)
+            .collect()
+    }
+
+    /// Failpoints expected to be activated at their sites, but never activated
+    ///
+    /// Expectations are only recorded for visited failpoints, so failpoints
+    /// never reached by any test are not listed here.
+    pub fn unmet_expectations(&self) -> Vec<&FailpointCoverage> {
+        self.failpoints
+            .values()
+            .filter(|failpoint| failpoint.expect_activated && failpoint.activated == 0)
+            .collect()
+    }
+
+    /// Render heatmap as JSON, for dashboards
+    pub fn to_json(&self) -> String {
+        let failpoints: Vec<String> = self
+            .failpoints
+            .values()
+            .map(|failpoint| {
+                let tests: Vec<String> = failpoint.tests.iter().map(|t| json_string(t)).collect();
+                format!(
+                    "{{\"name\":{},\"location\":{},\"file\":{},\"tests\":[{}],\"reached\":{},\"activated\":{},\"expect_activated\":{}}}",
+                    json_string(&failpoint.name),
+                    json_string(&failpoint.location),
+                    json_string(failpoint.file()),
+                    tests.join(","),
+                    failpoint.reached,
+                    failpoint.activated,
+                    failpoint.expect_activated
+                )
+            })
+            .collect();
+        let files: Vec<String> = self
+            .files()
+            .iter()
+            .map(|file| {
+                format!(
+                    "{{\"file\":{},\"failpoints\":{},\"tests\":{},\"reached\":{},\"activated\":{}}}",
+                    json_string(&file.file),
+                    file.failpoints,
+                    file.tests,
+                    file.reached,
+                    file.activated
+                )
+            })
+            .collect();
+        format!(
+            "{{\"files\":[{}],\"failpoints\":[{}]}}",
+            files.join(","),
+            failpoints.join(",")
+        )
+    }
+
+    fn add_record(&mut self, contents: &str) -> Option<()> {
+        let mut lines = contents.lines();
+        if lines.next()? != RECORD_HEADER {
+            return None;
+        }
+        let binary = unescape(lines.next()?.strip_prefix("binary\t")?);
+        let test = unescape(lines.next()?.strip_prefix("test\t")?);
+        let test = format!("{binary}::{test}");
+        for line in lines {
+            if let Some(expect) = line.strip_prefix("expect\t") {
+                let (expect, name) = expect.split_once('\t')?;
+                if expect == "activated" {
+                    let failpoint = self.failpoints.entry(unescape(name)).or_default();
+                    failpoint.expect_activated = true;
+                }
+                continue;
+            }
+            let mut fields = line.strip_prefix("failpoint\t")?.splitn(4, '\t');
+            let location = unescape(fields.next()?);
+            let reached: usize = fields.next()?.parse().ok()?;
+            let activated: usize = fields.next()?.parse().ok()?;
+            let name = unescape(fields.next()?);
+            let failpoint = self.failpoints.entry(name.clone()).or_default();
+            failpoint.name = name;
+            if failpoint.location.is_empty() {
+                failpoint.location = location;
+            }
+            failpoint.tests.insert(test.clone());
+            failpoint.reached += reached;
+            failpoint.activated += activated;
+        }
+        Some(())
+    }
+}
+
+impl std::fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut files = self.files();
+        files.sort_by(|a, b| b.reached.cmp(&a.reached).then_with(|| a.file.cmp(&b.file)));
+        writeln!(
+            f,
+            "{:>8} {:>8} {:>10} {:>10}  file",
+            "points", "tests", "reached", "activated"
+        )?;
+        for file in files {
+            writeln!(
+                f,
+                "{:>8} {:>8} {:>10} {:>10}  {}",
+                file.failpoints, file.tests, file.reached, file.activated, file.file
+            )?;
+        }
+        writeln!(f)?;
+        let mut failpoints: Vec<_> = self.failpoints.values().collect();
+        failpoints.sort_by(|a, b| {
+            a.tests
+                .len()
+                .cmp(&b.tests.len())
+                .then_with(|| a.reached.cmp(&b.reached))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        writeln!(
+            f,
+            "{:>8} {:>10} {:>10}  failpoint",
+            "tests", "reached", "activated"
+        )?;
+        for failpoint in failpoints {
+            writeln!(
+                f,
+                "{:>8} {:>10} {:>10}  {} ({})",
+                failpoint.tests.len(),
+                failpoint.reached,
+                failpoint.activated,
+                failpoint.name,
+                failpoint.location
+            )?;
+        }
+        let unmet = self.unmet_expectations();
+        if !unmet.is_empty() {
+            writeln!(f)?;
+            for failpoint in unmet {
+                writeln!(
+                    f,
+                    "expected to be activated, but never was: {} ({})",
+                    failpoint.name, failpoint.location
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Aggregate coverage records from a directory
+///
+/// Files which are not coverage records are ignored.
+pub fn heatmap(records_dir: impl AsRef<Path>) -> io::Result<Heatmap> {
+    let mut heatmap = Heatmap::default();
+    for entry in std::fs::read_dir(records_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == RECORD_EXTENSION) {
+            heatmap.add_record(&std::fs::read_to_string(&path)?);
+        }
+    }
+    Ok(heatmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        let s = "a\tb\nc\\d";
+        assert_eq!(unescape(&escape(s)), s);
+        assert!(!escape(s).contains('\t'));
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\n"), r#""a\"b\\c\n""#);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let mut heatmap = Heatmap::default();
+        heatmap.add_record(
+            "faine-coverage 1\nbinary\tmain\ntest\ttest_a\nfailpoint\tsrc/a.rs:1:1\t2\t1\tfoo\nfailpoint\tsrc/b.rs:1:1\t2\t1\tbar\n",
+        );
+        heatmap.add_record(
+            "faine-coverage 1\nbinary\tmain\ntest\ttest_b\nfailpoint\tsrc/a.rs:1:1\t3\t1\tfoo\n",
+        );
+        heatmap.add_record(
+            "faine-coverage 1\nbinary\tmain\ntest\ttest_c\nfailpoint\tsrc/c.rs:1:1\t1\t0\tbaz\nexpect\tactivated\tbaz\nexpect\tactivated\tfoo\n",
+        );
+
+        let single: Vec<_> = heatmap
+            .single_test_failpoints()
+            .into_iter()
+            .map(|failpoint| failpoint.name.as_str())
+            .collect();
+        assert_eq!(single, vec!["bar", "baz"]);
+
+        let unmet: Vec<_> = heatmap
+            .unmet_expectations()
+            .into_iter()
+            .map(|failpoint| failpoint.name.as_str())
+            .collect();
+        assert_eq!(unmet, vec!["baz"]);
+
+        let files = heatmap.files();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].file, "src/a.rs");
+        assert_eq!(files[0].tests, 2);
+        assert_eq!(files[0].reached, 5);
+
+        assert!(
+            heatmap
+                .to_json()
+                .starts_with(r#"{"files":[{"file":"src/a.rs""#)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::common::{Branch, Label};
+use crate::fnv::FnvHasher;
+
+const RECORD_EXTENSION: &str = "faine-dedup";
+
+/// What to do with subtrees already explored by other tests
+///
+/// See [`Runner::with_dedup_store()`](crate::Runner::with_dedup_store).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DedupMode {
+    /// Do not explore subtrees recorded as passing
+    #[default]
+    Skip,
+
+    /// Explore recorded subtrees anyway, and report ones which
+    /// produce different outcomes
+    CrossCheck,
+}
+
+/// Hash of a tree edge which does not depend on the process,
+/// computed from hash of the path leading to it
+pub fn edge_key(prefix: u64, label: Label, branch: Branch) -> u64 {
+    let mut hasher = FnvHasher::new();
+    prefix.hash(&mut hasher);
+    label.failpoint_name().hash(&mut hasher);
+    label.variant().hash(&mut hasher);
+    if let Some(index) = label.variant_index() {
+        index.hash(&mut hasher);
+    }
+    branch.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Directory with records of completely explored passing subtrees
+///
+/// Each record is a separate file named after the subtree key and
+/// containing digest of its outcomes. Records are written into
+/// temporary files and renamed, so concurrent writers never produce
+/// partial records, and need no locking.
+pub struct DedupStore {
+    dir: PathBuf,
+    pub mode: DedupMode,
+}
+
+impl DedupStore {
+    pub fn new(dir: &Path, mode: DedupMode) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            mode,
+        }
+    }
+
+    fn record_path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{key:016x}.{RECORD_EXTENSION}"))
+    }
+
+    /// Outcome digest of a subtree recorded as passing
+    pub fn lookup(&self, key: u64) -> Option<u64> {
+        let contents = std::fs::read_to_string(self.record_path(key)).ok()?;
+        u64::from_str_radix(contents.trim(), 16).ok()
+    }
+
+    /// Record a passing subtree
+    pub fn record(&self, key: u64, digest: u64) -> io::Result<()> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        std::fs::create_dir_all(&self.dir)?;
+        let temp_path = self.dir.join(format!(
+            "{key:016x}.{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&temp_path, format!("{digest:016x}\n"))?;
+        std::fs::rename(&temp_path, self.record_path(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DedupStore::new(&dir.path().join("store"), DedupMode::Skip);
+        assert_eq!(store.lookup(1), None);
+        store.record(1, 0xdead).unwrap();
+        assert_eq!(store.lookup(1), Some(0xdead));
+        store.record(1, 0xbeef).unwrap();
+        assert_eq!(store.lookup(1), Some(0xbeef));
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("store")).unwrap().count(),
+            1
+        );
+    }
+}
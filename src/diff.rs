@@ -0,0 +1,224 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+
+use crate::common::Branch;
+use crate::coverage::json_string;
+use crate::report::{ExecutionPath, Step};
+
+/// Difference between two execution paths
+///
+/// Produced by [`Report::nearest_passing_path()`]. [`Display`] renders
+/// paths side by side, with diverging decisions marked with `>`, and
+/// [`to_json()`] produces machine readable form.
+///
+/// [`Report::nearest_passing_path()`]: crate::Report::nearest_passing_path
+/// [`Display`]: std::fmt::Display
+/// [`to_json()`]: Self::to_json
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PathDiff {
+    /// Steps of the failing path
+    pub failing: Vec<Step>,
+
+    /// Steps of the passing path
+    pub passing: Vec<Step>,
+
+    /// Number of leading steps shared by both paths
+    pub common_prefix: usize,
+
+    /// Edit distance between the paths
+    pub distance: usize,
+}
+
+impl PathDiff {
+    pub(crate) fn new(failing: &ExecutionPath, passing: &ExecutionPath) -> Self {
+        Self {
+            failing: failing.steps.clone(),
+            passing: passing.steps.clone(),
+            common_prefix: common_prefix(&failing.steps, &passing.steps),
+            distance: edit_distance(&failing.steps, &passing.steps),
+        }
+    }
+
+    /// Render the difference as JSON
+    pub fn to_json(&self) -> String {
+        fn steps_json(steps: &[Step]) -> String {
+            let steps: Vec<String> = steps
+                .iter()
+                .map(|step| {
+                    format!(
+                        "{{\"failpoint\":{},\"activated\":{},\"variant\":{}}}",
+                        json_string(step.failpoint),
+                        step.branch == Branch::Activate,
+                        step.variant_description()
+                            .map_or("null".to_string(), |variant| json_string(&variant))
+                    )
+                })
+                .collect();
+            format!("[{}]", steps.join(","))
+        }
+        format!(
+            "{{\"common_prefix\":{},\"distance\":{},\"failing\":{},\"passing\":{}}}",
+            self.common_prefix,
+            self.distance,
+            steps_json(&self.failing),
+            steps_json(&self.passing)
+        )
+    }
+}
+
+impl std::fmt::Display for PathDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let failing: Vec<String> = self.failing.iter().map(ToString::to_string).collect();
+        let passing: Vec<String> = self.passing.iter().map(ToString::to_string).collect();
+        let width = failing
+            .iter()
+            .map(|step| step.chars().count())
+            .chain(std::iter::once("failing".len()))
+            .max()
+            .unwrap_or_default();
+        write!(f, "  {:width$}   passing", "failing")?;
+        for i in 0..failing.len().max(passing.len()) {
+            let left = failing.get(i).map_or("", String::as_str);
+            let right = passing.get(i).map_or("", String::as_str);
+            let marker = if self.failing.get(i) == self.passing.get(i) {
+                ' '
+            } else {
+                '>'
+            };
+            write!(f, "\n{marker} {left:width$}   {right}")?;
+        }
+        Ok(())
+    }
+}
+
+fn common_prefix(a: &[Step], b: &[Step]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn edit_distance(a: &[Step], b: &[Step]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_step) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_step) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_step != b_step);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Find passing path most similar to a given one
+///
+/// Passing paths are merged into a prefix tree, where each node
+/// remembers the range of paths passing through it. As paths are
+/// explored depth first, paths sharing a prefix are contiguous, so
+/// descending the tree along the failing path yields the candidates
+/// with the longest common prefix, and only these are compared by
+/// edit distance.
+pub(crate) fn nearest_passing_path<'a>(
+    paths: &'a [ExecutionPath],
+    failing: &ExecutionPath,
+) -> Option<&'a ExecutionPath> {
+    let passing: Vec<&ExecutionPath> = paths.iter().filter(|path| !path.is_failed()).collect();
+    if passing.is_empty() {
+        return None;
+    }
+
+    let mut ranges: Vec<(usize, usize)> = vec![(0, passing.len() - 1)];
+    let mut children: HashMap<(usize, &Step), usize> = Default::default();
+    for (index, path) in passing.iter().enumerate() {
+        let mut node = 0;
+        for step in &path.steps {
+            node = *children.entry((node, step)).or_insert_with(|| {
+                ranges.push((index, index));
+                ranges.len() - 1
+            });
+            ranges[node].1 = index;
+        }
+    }
+
+    let mut node = 0;
+    for step in &failing.steps {
+        match children.get(&(node, step)) {
+            Some(&child) => node = child,
+            None => break,
+        }
+    }
+
+    let (first, last) = ranges[node];
+    passing[first..=last]
+        .iter()
+        .min_by_key(|path| {
+            (
+                usize::MAX - common_prefix(&path.steps, &failing.steps),
+                edit_distance(&path.steps, &failing.steps),
+            )
+        })
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(steps: &[(&'static str, Branch)], failed: bool) -> ExecutionPath {
+        ExecutionPath {
+            steps: steps
+                .iter()
+                .map(|&(failpoint, branch)| Step {
+                    failpoint,
+                    branch,
+                    variant: None,
+                    variant_index: None,
+                    trigger: None,
+                })
+                .collect(),
+            timed_out: failed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let a = path(&[("a", Branch::Skip), ("b", Branch::Skip)], false);
+        let b = path(&[("a", Branch::Activate)], false);
+        assert_eq!(edit_distance(&a.steps, &a.steps), 0);
+        assert_eq!(edit_distance(&a.steps, &b.steps), 2);
+        assert_eq!(edit_distance(&b.steps, &[]), 1);
+    }
+
+    #[test]
+    fn test_nearest() {
+        use Branch::*;
+        let paths = [
+            path(&[("a", Activate)], false),
+            path(&[("a", Skip), ("b", Activate)], true),
+            path(&[("a", Skip), ("b", Skip), ("c", Activate)], false),
+            path(&[("a", Skip), ("b", Skip), ("c", Skip)], false),
+        ];
+        let nearest = nearest_passing_path(&paths, &paths[1]).unwrap();
+        assert_eq!(nearest, &paths[2]);
+        assert!(nearest_passing_path(&paths[1..2], &paths[1]).is_none());
+
+        let diff = PathDiff::new(&paths[1], nearest);
+        assert_eq!(diff.common_prefix, 1);
+        assert_eq!(diff.distance, 2);
+        assert_eq!(
+            diff.to_string(),
+            "  failing   passing\n  a         a\n> [b]       b\n>           [c]"
+        );
+        assert_eq!(
+            diff.to_json(),
+            concat!(
+                r#"{"common_prefix":1,"distance":2,"#,
+                r#""failing":[{"failpoint":"a","activated":false,"variant":null},{"failpoint":"b","activated":true,"variant":null}],"#,
+                r#""passing":[{"failpoint":"a","activated":false,"variant":null},{"failpoint":"b","activated":false,"variant":null},{"failpoint":"c","activated":true,"variant":null}]}"#
+            )
+        );
+    }
+}
@@ -0,0 +1,556 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Deterministic fingerprints of outcomes
+//!
+//! Outcome comparison, such as [order sensitivity check](crate::Runner::with_order_sensitivity_check),
+//! needs a digest of what the tested code has produced. For code
+//! working with files, that's usually the state of a directory, and
+//! [`dir_tree()`] fingerprints it deterministically: entries are
+//! hashed in sorted order, with relative paths normalized to `/`
+//! separators, and metadata which differs between runs (such as
+//! modification times) is ignored unless requested.
+//!
+//! ```
+//! use faine::{Runner, digest, inject_return_io_error, record_outcome};
+//!
+//! fn save(dir: &std::path::Path) -> std::io::Result<()> {
+//!     inject_return_io_error!("write data");
+//!     std::fs::write(dir.join("data"), "data")
+//! }
+//!
+//! let tempdir = tempfile::tempdir().unwrap();
+//! Runner::ignore_env()
+//!     .with_order_sensitivity_check(true)
+//!     .run(|| {
+//!         let dir = tempdir.path().join("state");
+//!         let _ = std::fs::remove_dir_all(&dir);
+//!         std::fs::create_dir(&dir).unwrap();
+//!         let _ = save(&dir);
+//!         record_outcome(digest::dir_tree(&dir).unwrap());
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! Digests do not depend on the process or the toolchain, so they
+//! may be compared across runs, as [deduplication](crate::Runner::with_dedup_store)
+//! does, as long as hashed values stay the same.
+
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::fnv::FnvHasher;
+
+/// Fingerprint of an outcome
+///
+/// Produced by [`bytes()`], [`dir_tree()`] and [`DigestBuilder`].
+/// Implements [`Hash`], so it may be passed to
+/// [`record_outcome()`](crate::record_outcome) and anything else
+/// which accepts hashable outcomes. Displayed as 16 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest(u64);
+
+impl Digest {
+    /// Raw value of the digest
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Digest of a byte buffer
+pub fn bytes(buf: impl AsRef<[u8]>) -> Digest {
+    DigestBuilder::new().with_bytes(buf).finish()
+}
+
+/// Digest of a directory tree with default [`DirTree`] options
+///
+/// Hashes relative paths, types and contents of all entries, and
+/// targets of symbolic links.
+pub fn dir_tree(path: impl AsRef<Path>) -> io::Result<Digest> {
+    DirTree::new().digest(path)
+}
+
+/// Composition of multiple digest sources
+///
+/// ```
+/// use faine::digest::{self, DigestBuilder, DirTree};
+///
+/// let tempdir = tempfile::tempdir().unwrap();
+/// let digest = DigestBuilder::new()
+///     .with_bytes(b"returned value")
+///     .with_dir_tree(tempdir.path(), &DirTree::new().with_ignore(&["*.log"]))
+///     .unwrap()
+///     .finish();
+/// assert_ne!(digest, digest::bytes(b"returned value"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DigestBuilder {
+    hasher: FnvHasher,
+}
+
+impl DigestBuilder {
+    /// Construct an empty builder
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a byte buffer
+    pub fn with_bytes(mut self, buf: impl AsRef<[u8]>) -> Self {
+        buf.as_ref().hash(&mut self.hasher);
+        self
+    }
+
+    /// Add any hashable value
+    pub fn with_value(mut self, value: impl Hash) -> Self {
+        value.hash(&mut self.hasher);
+        self
+    }
+
+    /// Add another digest
+    pub fn with_digest(self, digest: Digest) -> Self {
+        self.with_value(digest)
+    }
+
+    /// Add a directory tree, see [`DirTree`]
+    pub fn with_dir_tree(mut self, path: impl AsRef<Path>, tree: &DirTree) -> io::Result<Self> {
+        tree.hash_into(path.as_ref(), &mut self.hasher)?;
+        Ok(self)
+    }
+
+    /// Produce the digest of everything added so far
+    pub fn finish(&self) -> Digest {
+        Digest(self.hasher.finish())
+    }
+}
+
+/// Metadata of directory entries which may be included in a digest
+///
+/// See [`DirTree::with_metadata()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Metadata {
+    /// Permissions: mode bits on Unix, read-only flag elsewhere
+    Permissions,
+
+    /// Modification time
+    Modified,
+}
+
+/// Handling of symbolic links in a directory tree
+///
+/// See [`DirTree::with_symlinks()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Symlinks {
+    /// Hash the link target path, without following it
+    #[default]
+    Target,
+
+    /// Hash the entry the link points to, as if it were in place of the link
+    ///
+    /// Links pointing to their ancestor directories produce an error.
+    Follow,
+
+    /// Leave links out of the digest
+    Skip,
+}
+
+/// Options of a directory tree digest
+///
+/// Entries are hashed in order of their relative paths, which are
+/// normalized to use `/` as a separator on all platforms, so the
+/// digest doesn't depend on the order in which the filesystem lists
+/// entries. For each entry, its relative path, type and, for files,
+/// contents are hashed, plus selected [`Metadata`].
+#[derive(Debug, Clone, Default)]
+pub struct DirTree {
+    metadata: Vec<Metadata>,
+    symlinks: Symlinks,
+    ignore: Vec<String>,
+}
+
+enum Kind {
+    File,
+    Dir,
+    Symlink(PathBuf),
+}
+
+impl DirTree {
+    /// Construct default options
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Include metadata of entries in the digest
+    ///
+    /// By default, no metadata is included, as most of it (such as
+    /// modification time) naturally differs between iterations.
+    pub fn with_metadata(mut self, metadata: &[Metadata]) -> Self {
+        self.metadata = metadata.to_vec();
+        self
+    }
+
+    /// Set handling of symbolic links, see [`Symlinks`]
+    pub fn with_symlinks(mut self, symlinks: Symlinks) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Leave entries matching any of glob patterns out of the digest
+    ///
+    /// Patterns without `/` are matched against entry names, and
+    /// patterns with `/` against paths relative to the root of the
+    /// tree. `*` matches any sequence of characters except `/`, `**`
+    /// matches any sequence including `/`, and `?` matches a single
+    /// character except `/`. Contents of ignored directories are
+    /// ignored too.
+    pub fn with_ignore(mut self, patterns: &[&str]) -> Self {
+        self.ignore = patterns.iter().map(ToString::to_string).collect();
+        self
+    }
+
+    /// Produce a digest of a directory tree
+    pub fn digest(&self, path: impl AsRef<Path>) -> io::Result<Digest> {
+        Ok(DigestBuilder::new().with_dir_tree(path, self)?.finish())
+    }
+
+    fn is_ignored(&self, relative: &[u8]) -> bool {
+        let name = match relative.iter().rposition(|&c| c == b'/') {
+            Some(pos) => &relative[pos + 1..],
+            None => relative,
+        };
+        self.ignore.iter().any(|pattern| {
+            let pattern = pattern.as_bytes();
+            if pattern.contains(&b'/') {
+                glob_match(pattern, relative)
+            } else {
+                glob_match(pattern, name)
+            }
+        })
+    }
+
+    fn hash_into(&self, root: &Path, hasher: &mut FnvHasher) -> io::Result<()> {
+        let mut entries = vec![];
+        let mut ancestors = vec![];
+        self.collect(root, vec![], &mut ancestors, &mut entries)?;
+        entries.sort_unstable_by(|(a, _, _), (b, _, _)| a.cmp(b));
+        for (relative, path, kind) in entries {
+            relative.hash(hasher);
+            match kind {
+                Kind::File => {
+                    0u8.hash(hasher);
+                    let mut writer = HashWriter(hasher);
+                    io::copy(&mut fs::File::open(&path)?, &mut writer)?.hash(hasher);
+                }
+                Kind::Dir => 1u8.hash(hasher),
+                Kind::Symlink(target) => {
+                    2u8.hash(hasher);
+                    normalize(&target).hash(hasher);
+                }
+            }
+            if !self.metadata.is_empty() {
+                let metadata = match self.symlinks {
+                    Symlinks::Follow => fs::metadata(&path)?,
+                    _ => fs::symlink_metadata(&path)?,
+                };
+                for field in &self.metadata {
+                    match field {
+                        Metadata::Permissions => permissions(&metadata).hash(hasher),
+                        Metadata::Modified => metadata.modified()?.hash(hasher),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn collect(
+        &self,
+        path: &Path,
+        relative: Vec<u8>,
+        ancestors: &mut Vec<PathBuf>,
+        entries: &mut Vec<(Vec<u8>, PathBuf, Kind)>,
+    ) -> io::Result<()> {
+        let mut file_type = fs::symlink_metadata(path)?.file_type();
+        if file_type.is_symlink() {
+            match self.symlinks {
+                Symlinks::Target => {
+                    entries.push((
+                        relative,
+                        path.to_path_buf(),
+                        Kind::Symlink(fs::read_link(path)?),
+                    ));
+                    return Ok(());
+                }
+                Symlinks::Follow => file_type = fs::metadata(path)?.file_type(),
+                Symlinks::Skip => return Ok(()),
+            }
+        }
+        if !file_type.is_dir() {
+            entries.push((relative, path.to_path_buf(), Kind::File));
+            return Ok(());
+        }
+
+        let canonical = fs::canonicalize(path)?;
+        if ancestors.contains(&canonical) {
+            return Err(io::Error::other(format!(
+                "symbolic link cycle at {}",
+                path.display()
+            )));
+        }
+        ancestors.push(canonical);
+        entries.push((relative.clone(), path.to_path_buf(), Kind::Dir));
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let mut child = relative.clone();
+            if !child.is_empty() {
+                child.push(b'/');
+            }
+            child.extend_from_slice(entry.file_name().as_encoded_bytes());
+            if !self.is_ignored(&child) {
+                self.collect(&entry.path(), child, ancestors, entries)?;
+            }
+        }
+        ancestors.pop();
+        Ok(())
+    }
+}
+
+/// Feeds everything written into a hasher
+struct HashWriter<'a>(&'a mut FnvHasher);
+
+impl io::Write for HashWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &fs::Metadata) -> u32 {
+    u32::from(metadata.permissions().readonly())
+}
+
+/// Path rendered with `/` separators regardless of the platform
+fn normalize(path: &Path) -> Vec<u8> {
+    let mut res = vec![];
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                res.extend_from_slice(prefix.as_os_str().as_encoded_bytes());
+            }
+            Component::RootDir => res.push(b'/'),
+            component => {
+                if !res.is_empty() && !res.ends_with(b"/") {
+                    res.push(b'/');
+                }
+                res.extend_from_slice(component.as_os_str().as_encoded_bytes());
+            }
+        }
+    }
+    res
+}
+
+/// Match a glob pattern against a path, see [`DirTree::with_ignore()`]
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            let segment = text.iter().position(|&c| c == b'/').unwrap_or(text.len());
+            (0..=segment).any(|i| glob_match(rest, &text[i..]))
+        }
+        [b'?', rest @ ..] => matches!(text, [c, ..] if *c != b'/') && glob_match(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populate(dir: &Path, names: &[&str]) {
+        for name in names {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, path.file_name().unwrap().as_encoded_bytes()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*.log", b"debug.log"));
+        assert!(!glob_match(b"*.log", b"logs/debug.log"));
+        assert!(glob_match(b"**/*.log", b"logs/debug.log"));
+        assert!(glob_match(b"logs/**", b"logs/a/b"));
+        assert!(glob_match(b"?.tmp", b"a.tmp"));
+        assert!(!glob_match(b"?.tmp", b"ab.tmp"));
+        assert!(!glob_match(b"a?b", b"a/b"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Path::new("a/b/c")), b"a/b/c");
+        assert_eq!(normalize(Path::new("../a")), b"../a");
+        assert_eq!(normalize(Path::new("/a/b")), b"/a/b");
+        #[cfg(windows)]
+        assert_eq!(normalize(Path::new(r"a\b")), b"a/b");
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(bytes(b"foo"), bytes(String::from("foo")));
+        assert_ne!(bytes(b"foo"), bytes(b"bar"));
+        assert_eq!(bytes(b"foo").to_string().len(), 16);
+        assert_ne!(
+            DigestBuilder::new()
+                .with_bytes(b"ab")
+                .with_bytes(b"c")
+                .finish(),
+            DigestBuilder::new()
+                .with_bytes(b"a")
+                .with_bytes(b"bc")
+                .finish()
+        );
+    }
+
+    #[test]
+    fn test_dir_tree_order() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        populate(first.path(), &["a", "b/c", "b/d"]);
+        populate(second.path(), &["b/d", "b/c", "a"]);
+        assert_eq!(
+            dir_tree(first.path()).unwrap(),
+            dir_tree(second.path()).unwrap()
+        );
+
+        fs::write(second.path().join("a"), "changed").unwrap();
+        assert_ne!(
+            dir_tree(first.path()).unwrap(),
+            dir_tree(second.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dir_tree_structure() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        populate(first.path(), &["a"]);
+        populate(second.path(), &["a"]);
+        fs::create_dir(second.path().join("empty")).unwrap();
+        assert_ne!(
+            dir_tree(first.path()).unwrap(),
+            dir_tree(second.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dir_tree_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        populate(dir.path(), &["a"]);
+        let with_modified = DirTree::new().with_metadata(&[Metadata::Modified]);
+        let plain = dir_tree(dir.path()).unwrap();
+        let modified = with_modified.digest(dir.path()).unwrap();
+
+        let file = fs::File::options()
+            .write(true)
+            .open(dir.path().join("a"))
+            .unwrap();
+        file.set_modified(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(dir_tree(dir.path()).unwrap(), plain);
+        assert_ne!(with_modified.digest(dir.path()).unwrap(), modified);
+    }
+
+    #[test]
+    fn test_dir_tree_ignore() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        populate(first.path(), &["data"]);
+        fs::create_dir(first.path().join("sub")).unwrap();
+        populate(
+            second.path(),
+            &["data", "debug.log", "cache/blob", "sub/trace.log"],
+        );
+        let tree = DirTree::new().with_ignore(&["*.log", "cache"]);
+        assert_eq!(
+            tree.digest(first.path()).unwrap(),
+            tree.digest(second.path()).unwrap()
+        );
+        let tree = DirTree::new().with_ignore(&["sub/*.log", "cache"]);
+        assert_ne!(
+            tree.digest(first.path()).unwrap(),
+            tree.digest(second.path()).unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dir_tree_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        populate(dir.path(), &["a"]);
+        let tree = DirTree::new().with_metadata(&[Metadata::Permissions]);
+        fs::set_permissions(dir.path().join("a"), fs::Permissions::from_mode(0o600)).unwrap();
+        let plain = dir_tree(dir.path()).unwrap();
+        let with_permissions = tree.digest(dir.path()).unwrap();
+
+        fs::set_permissions(dir.path().join("a"), fs::Permissions::from_mode(0o640)).unwrap();
+        assert_eq!(dir_tree(dir.path()).unwrap(), plain);
+        assert_ne!(tree.digest(dir.path()).unwrap(), with_permissions);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dir_tree_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let linked = tempfile::tempdir().unwrap();
+        let copied = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        populate(target.path(), &["a"]);
+        symlink(target.path(), linked.path().join("link")).unwrap();
+        populate(copied.path(), &["link/a"]);
+
+        let follow = DirTree::new().with_symlinks(Symlinks::Follow);
+        assert_eq!(
+            follow.digest(linked.path()).unwrap(),
+            follow.digest(copied.path()).unwrap()
+        );
+        assert_ne!(
+            dir_tree(linked.path()).unwrap(),
+            dir_tree(copied.path()).unwrap()
+        );
+
+        let skip = DirTree::new().with_symlinks(Symlinks::Skip);
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(
+            skip.digest(linked.path()).unwrap(),
+            skip.digest(empty.path()).unwrap()
+        );
+
+        symlink(linked.path(), linked.path().join("cycle")).unwrap();
+        assert!(follow.digest(linked.path()).is_err());
+        assert!(dir_tree(linked.path()).is_ok());
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::ffi::OsString;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::__private::FAILPOINTS;
+use crate::axes::Axis;
+
+/// Environment variable explored as a configuration axis, as
+/// variable name, axis name, and values along with their labels
+pub type EnvAxis = (
+    &'static str,
+    &'static str,
+    Vec<(&'static str, Option<&'static str>)>,
+);
+
+/// Serializes environment modifications by all runners in the process
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Construct an environment axis and a plain axis it's explored with
+///
+/// Axis is named `$VAR`, unset value is labeled `unset`, and set
+/// ones are quoted.
+pub fn declare(var: &'static str, values: &[Option<&'static str>]) -> (EnvAxis, Axis) {
+    let axis: &'static str = Box::leak(format!("${var}").into_boxed_str());
+    let values: Vec<(&'static str, Option<&'static str>)> = values
+        .iter()
+        .map(|value| match value {
+            Some(value) => (
+                &*Box::leak(format!("{value:?}").into_boxed_str()),
+                Some(*value),
+            ),
+            None => ("unset", None),
+        })
+        .collect();
+    let labels = values.iter().map(|(label, _)| *label).collect();
+    ((var, axis, values), (axis, labels))
+}
+
+/// Environment variables set for an iteration, restored on drop
+///
+/// Holds the process-global lock for its lifetime, so iterations of
+/// runners with environment axes do not overlap.
+pub struct EnvOverride {
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvOverride {
+    /// Set or remove given variables, remembering their original values
+    pub fn apply(vars: &[(&'static str, Option<&'static str>)]) -> Self {
+        // a panic while holding the lock leaves the environment restored
+        // by the guard, so poisoning carries no information
+        let lock = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let mut saved = Vec::with_capacity(vars.len());
+        for (var, value) in vars {
+            saved.push((*var, std::env::var_os(var)));
+            // SAFETY: modifications by runners are serialized by the
+            // lock; readers outside of faine are documented to race
+            unsafe {
+                match value {
+                    Some(value) => std::env::set_var(var, value),
+                    None => std::env::remove_var(var),
+                }
+            }
+        }
+        Self { saved, _lock: lock }
+    }
+
+    /// Apply values of environment axes chosen for the current iteration
+    pub fn for_iteration() -> Option<Self> {
+        let vars: Vec<_> = FAILPOINTS.with_borrow(|state| {
+            let state = state.as_ref()?;
+            Some(
+                state
+                    .env_axes
+                    .iter()
+                    .filter_map(|(var, axis, values)| {
+                        let (_, label) = state.axis_values.iter().find(|(name, _)| name == axis)?;
+                        let (_, value) = values.iter().find(|(value, _)| value == label)?;
+                        Some((*var, *value))
+                    })
+                    .collect(),
+            )
+        })?;
+        (!vars.is_empty()).then(|| Self::apply(&vars))
+    }
+}
+
+impl Drop for EnvOverride {
+    fn drop(&mut self) {
+        for (var, value) in self.saved.drain(..).rev() {
+            // SAFETY: see apply()
+            unsafe {
+                match value {
+                    Some(value) => std::env::set_var(var, value),
+                    None => std::env::remove_var(var),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declare() {
+        let ((var, axis, values), (name, labels)) = declare("FOO", &[None, Some("bar")]);
+        assert_eq!(var, "FOO");
+        assert_eq!(axis, "$FOO");
+        assert_eq!(name, "$FOO");
+        assert_eq!(values, [("unset", None), ("\"bar\"", Some("bar"))]);
+        assert_eq!(labels, ["unset", "\"bar\""]);
+    }
+
+    #[test]
+    fn test_restore() {
+        const VAR: &str = "FAINE_ENV_TEST_RESTORE";
+        let env = EnvOverride::apply(&[(VAR, Some("first")), (VAR, Some("second"))]);
+        assert_eq!(std::env::var(VAR).as_deref(), Ok("second"));
+        drop(env);
+        assert!(std::env::var_os(VAR).is_none());
+
+        let env = EnvOverride::apply(&[(VAR, Some("set")), (VAR, None)]);
+        assert!(std::env::var_os(VAR).is_none());
+        drop(env);
+        assert!(std::env::var_os(VAR).is_none());
+    }
+
+    #[test]
+    fn test_restore_on_panic() {
+        const VAR: &str = "FAINE_ENV_TEST_PANIC";
+        let res = std::panic::catch_unwind(|| {
+            let _env = EnvOverride::apply(&[(VAR, Some("overridden"))]);
+            assert_eq!(std::env::var(VAR).as_deref(), Ok("overridden"));
+            panic!("iteration failed");
+        });
+        assert!(res.is_err());
+        assert!(std::env::var_os(VAR).is_none());
+        // the lock is usable after a panic
+        drop(EnvOverride::apply(&[]));
+    }
+}
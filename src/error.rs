@@ -1,18 +1,380 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use crate::order_fuzz::OrderRepro;
+use crate::report::Report;
+
 /// Error when executing tested code
 #[derive(Debug)]
 #[non_exhaustive]
-pub enum Error {}
+pub enum Error {
+    /// Failed to write coverage record
+    CoverageRecord(std::io::Error),
+
+    /// Failed to update known failures file
+    ///
+    /// See [`Runner::with_update_known_failures()`](crate::Runner::with_update_known_failures).
+    KnownFailures(std::io::Error),
+
+    /// Invalid configuration in environment variables
+    ///
+    /// See [`Runner`](crate::Runner#environment).
+    Configuration(String),
+
+    /// Runner is nested in another one, and either of them uses a
+    /// feature which cannot be shared
+    ///
+    /// See concurrency section of [`Runner::run()`](crate::Runner::run).
+    ConflictingRunner {
+        /// Name of the feature
+        feature: &'static str,
+
+        /// Name of the thread (under libtest, the test) the other runner explores in
+        other_test_thread: String,
+    },
+
+    /// Tested code has behaved non-deterministically
+    ///
+    /// See [`Runner::with_strict_determinism()`](crate::Runner::with_strict_determinism).
+    NonDeterminism {
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Tested code panicked at locations not listed as expected
+    ///
+    /// See [`Runner::with_expected_panics()`](crate::Runner::with_expected_panics).
+    UnexpectedPanics {
+        /// Unexpected panic locations, as `file:line`
+        locations: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Some priority paths could not be followed
+    ///
+    /// See [`Runner::with_require_reachable_priority_paths()`](crate::Runner::with_require_reachable_priority_paths).
+    UnreachablePriorityPaths {
+        /// Unreachable paths, as failpoints to activate
+        paths: Vec<Vec<String>>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Some failpoints were not activated as expected by their sites
+    ///
+    /// See [`Runner::with_enforce_site_expectations()`](crate::Runner::with_enforce_site_expectations).
+    SiteExpectationsNotMet {
+        /// Failpoints with unmet expectations, along with their locations
+        failpoints: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Activations of some failpoints were absorbed too often
+    ///
+    /// See [`Runner::with_max_absorption()`](crate::Runner::with_max_absorption).
+    AbsorptionExceeded {
+        /// Failpoints exceeding the limit, along with numbers of absorbing paths
+        failpoints: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Safety limits were reached on some paths
+    ///
+    /// See [`Runner::with_safety_valve_is_error()`](crate::Runner::with_safety_valve_is_error).
+    SafetyValveTriggered {
+        /// Triggered safety valves
+        valves: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Required checks have not executed on some paths
+    ///
+    /// See [`Runner::with_required_checks()`](crate::Runner::with_required_checks).
+    RequiredChecksNotExecuted {
+        /// Paths missing required checks, along with names of the missing checks
+        paths: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Checkpoints were violated on some paths
+    ///
+    /// See [`Runner::with_continue_after_checkpoint_violation()`](crate::Runner::with_continue_after_checkpoint_violation).
+    CheckpointViolated {
+        /// Paths violating checkpoints, along with violations
+        paths: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Counters have exceeded their limits on some paths
+    ///
+    /// See [`Runner::with_counter_limit()`](crate::Runner::with_counter_limit).
+    CounterLimitExceeded {
+        /// Paths exceeding limits, along with values of exceeding counters
+        paths: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// No failure was found where it was expected
+    ///
+    /// See [`Runner::run_expect_failure()`](crate::Runner::run_expect_failure).
+    NoFailureFound {
+        /// Number of explored paths
+        paths_explored: usize,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Some expected failpoints were not visited on any path
+    ///
+    /// See [`Runner::with_expected_failpoints()`](crate::Runner::with_expected_failpoints).
+    ExpectedFailpointsNotVisited {
+        /// Selectors of failpoints which were not visited
+        failpoints: Vec<String>,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Merged shards do not cover the whole exploration
+    ///
+    /// See [`Report::merge_shards()`](crate::Report::merge_shards).
+    ShardsIncomplete {
+        /// Descriptions of found problems, such as missing shards
+        problems: Vec<String>,
+
+        /// Merged report
+        report: Box<Report>,
+    },
+
+    /// A forbidden failpoint was visited
+    ///
+    /// See [`Runner::with_forbidden()`](crate::Runner::with_forbidden).
+    ForbiddenFailpointReached {
+        /// Name of the first visited forbidden failpoint
+        name: String,
+
+        /// Path which has reached it, ending with the failpoint itself
+        path: String,
+
+        /// Report of the whole exploration
+        report: Box<Report>,
+    },
+
+    /// Exploration of the second stage of a staged run has failed
+    ///
+    /// See [`Runner::run_staged()`](crate::Runner::run_staged).
+    StageFailed {
+        /// Path of the first stage which has produced the state
+        outer_path: String,
+
+        /// Key of the state, formatted with `Debug`
+        state: String,
+
+        /// First failed path of the second stage, if any has failed
+        inner_path: Option<String>,
+
+        /// Error of the second stage exploration
+        error: Box<Error>,
+    },
+
+    /// Exploration with shuffled order has failed
+    ///
+    /// See [`Runner::fuzz_orders()`](crate::Runner::fuzz_orders).
+    OrderFuzzFailure {
+        /// Order seed which has produced the failure
+        seed: u64,
+
+        /// Minimized deterministic reproduction, if the failure could be reproduced
+        repro: Option<OrderRepro>,
+
+        /// Report of the failed exploration
+        report: Box<Report>,
+    },
+}
+
+impl Error {
+    /// Report of the exploration the error has occurred in, if any
+    pub(crate) fn report(&self) -> Option<&Report> {
+        match self {
+            Error::StageFailed { error, .. } => error.report(),
+            Error::CoverageRecord(_)
+            | Error::KnownFailures(_)
+            | Error::Configuration(_)
+            | Error::ConflictingRunner { .. } => None,
+            Error::NonDeterminism { report }
+            | Error::UnexpectedPanics { report, .. }
+            | Error::UnreachablePriorityPaths { report, .. }
+            | Error::SiteExpectationsNotMet { report, .. }
+            | Error::AbsorptionExceeded { report, .. }
+            | Error::SafetyValveTriggered { report, .. }
+            | Error::RequiredChecksNotExecuted { report, .. }
+            | Error::CheckpointViolated { report, .. }
+            | Error::CounterLimitExceeded { report, .. }
+            | Error::NoFailureFound { report, .. }
+            | Error::ExpectedFailpointsNotVisited { report, .. }
+            | Error::ShardsIncomplete { report, .. }
+            | Error::ForbiddenFailpointReached { report, .. }
+            | Error::OrderFuzzFailure { report, .. } => Some(report),
+        }
+    }
+
+    fn write_message(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::CoverageRecord(_) => write!(f, "failed to write coverage record"),
+            Error::KnownFailures(_) => write!(f, "failed to update known failures file"),
+            Error::Configuration(message) => write!(f, "invalid faine configuration: {message}"),
+            Error::ConflictingRunner {
+                feature,
+                other_test_thread,
+            } => write!(
+                f,
+                "{feature} is already used by a runner in thread {other_test_thread}"
+            ),
+            Error::NonDeterminism { .. } => write!(
+                f,
+                "tested code has taken different paths given the same failpoint decisions"
+            ),
+            Error::UnexpectedPanics { locations, .. } => {
+                write!(f, "unexpected panic(s) at {}", locations.join(", "))
+            }
+            Error::UnreachablePriorityPaths { paths, .. } => {
+                let paths: Vec<_> = paths.iter().map(|path| path.join(" → ")).collect();
+                write!(f, "unreachable priority path(s): {}", paths.join(", "))
+            }
+            Error::SiteExpectationsNotMet { failpoints, .. } => {
+                write!(f, "site expectation(s) not met: {}", failpoints.join(", "))
+            }
+            Error::AbsorptionExceeded { failpoints, .. } => {
+                write!(f, "injected failure(s) absorbed: {}", failpoints.join(", "))
+            }
+            Error::SafetyValveTriggered { valves, .. } => {
+                write!(f, "safety valve triggered: {}", valves.join(", "))
+            }
+            Error::RequiredChecksNotExecuted { paths, .. } => {
+                write!(
+                    f,
+                    "required check(s) not executed on path(s): {}",
+                    paths.join(", ")
+                )
+            }
+            Error::CheckpointViolated { paths, .. } => {
+                write!(f, "checkpoint(s) violated on path(s): {}", paths.join(", "))
+            }
+            Error::CounterLimitExceeded { paths, .. } => {
+                write!(
+                    f,
+                    "counter limit(s) exceeded on path(s): {}",
+                    paths.join(", ")
+                )
+            }
+            Error::NoFailureFound { paths_explored, .. } => {
+                write!(
+                    f,
+                    "no failure found where expected, {paths_explored} path(s) explored"
+                )
+            }
+            Error::ExpectedFailpointsNotVisited { failpoints, .. } => {
+                write!(
+                    f,
+                    "expected failpoint(s) not visited: {}",
+                    failpoints.join(", ")
+                )
+            }
+            Error::ShardsIncomplete { problems, .. } => {
+                write!(
+                    f,
+                    "merged shards do not cover the exploration: {}",
+                    problems.join("; ")
+                )
+            }
+            Error::ForbiddenFailpointReached { name, path, .. } => {
+                write!(f, "forbidden failpoint {name} reached via {path}")
+            }
+            Error::StageFailed {
+                outer_path,
+                state,
+                inner_path,
+                error,
+            } => {
+                write!(f, "outer path {outer_path} produced state {state}; ")?;
+                match inner_path {
+                    Some(inner_path) => write!(f, "inner path {inner_path} then failed: ")?,
+                    None => write!(f, "inner exploration then failed: ")?,
+                }
+                error.write_message(f)
+            }
+            Error::OrderFuzzFailure {
+                seed,
+                repro: Some(repro),
+                ..
+            } => {
+                write!(
+                    f,
+                    "failure with order seed {seed}, reproduce with:\n{repro}"
+                )
+            }
+            Error::OrderFuzzFailure {
+                seed, repro: None, ..
+            } => {
+                write!(
+                    f,
+                    "failure with order seed {seed}, not reproducible with forced order, use with_order_seed({seed})"
+                )
+            }
+        }
+    }
+}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "impossible error")
+        self.write_message(f)?;
+        if let Some(summary) = self.report().and_then(Report::failure_summary) {
+            write!(f, "\n{summary}")?;
+        }
+        Ok(())
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::CoverageRecord(err) | Error::KnownFailures(err) => Some(err),
+            Error::Configuration(_)
+            | Error::ConflictingRunner { .. }
+            | Error::NonDeterminism { .. }
+            | Error::UnexpectedPanics { .. }
+            | Error::UnreachablePriorityPaths { .. }
+            | Error::SiteExpectationsNotMet { .. }
+            | Error::AbsorptionExceeded { .. }
+            | Error::SafetyValveTriggered { .. }
+            | Error::RequiredChecksNotExecuted { .. }
+            | Error::CheckpointViolated { .. }
+            | Error::CounterLimitExceeded { .. }
+            | Error::NoFailureFound { .. }
+            | Error::ExpectedFailpointsNotVisited { .. }
+            | Error::ShardsIncomplete { .. }
+            | Error::ForbiddenFailpointReached { .. }
+            | Error::StageFailed { .. }
+            | Error::OrderFuzzFailure { .. } => None,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
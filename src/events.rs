@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::common::Branch;
+use crate::report::{ExecutionPath, Report};
+
+/// Receiver of exploration events, for exporting them into metrics
+/// or logging systems
+///
+/// See [`Runner::with_event_sink()`](crate::Runner::with_event_sink).
+/// All methods do nothing by default, so a sink only implements ones
+/// it's interested in. Methods are called on the thread running the
+/// exploration, outside of any internal state of the runner, so they
+/// may take as long as needed. A sink cannot affect the exploration:
+/// panics are caught and reported as warnings, and failpoints visited
+/// by the sink itself are always skipped and not reported.
+///
+/// ```
+/// use faine::{Branch, EventSink};
+///
+/// #[derive(Default)]
+/// struct ActivationCounter(usize);
+///
+/// impl EventSink for ActivationCounter {
+///     fn on_visit(&mut self, _failpoint: &'static str, branch: Branch, _iteration: usize) {
+///         if branch == Branch::Activate {
+///             self.0 += 1;
+///         }
+///     }
+/// }
+/// ```
+pub trait EventSink {
+    /// Failpoint was visited in an explored iteration, taking a given branch
+    ///
+    /// Multi-valued failpoints report a visit per considered variant.
+    /// Visits in iterations replaying already explored paths are not
+    /// reported.
+    fn on_visit(&mut self, failpoint: &'static str, branch: Branch, iteration: usize) {
+        let _ = (failpoint, branch, iteration);
+    }
+
+    /// Explored iteration has finished, producing a given path
+    ///
+    /// `duration` is the time the tested code has run.
+    fn on_iteration_end(&mut self, path: &ExecutionPath, duration: Duration) {
+        let _ = (path, duration);
+    }
+
+    /// Exploration has finished, producing a given report
+    ///
+    /// Called after every exploration performed by the runner, so
+    /// runners which explore more than once (for instance, with
+    /// [`Runner::with_order_sensitivity_check()`](crate::Runner::with_order_sensitivity_check))
+    /// call it more than once. The sink is released afterwards.
+    fn on_run_end(&mut self, report: &Report) {
+        let _ = report;
+    }
+}
+
+pub type SharedEventSink = Arc<Mutex<dyn EventSink>>;
+
+thread_local! {
+    static DISPATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether a sink is handling an event on the current thread, in
+/// which case failpoints are not explored
+pub fn is_dispatching() -> bool {
+    DISPATCHING.get()
+}
+
+/// Pass an event to all sinks
+///
+/// Events produced while a sink handles another one are dropped.
+pub fn dispatch(sinks: &[SharedEventSink], mut event: impl FnMut(&mut dyn EventSink)) {
+    if sinks.is_empty() || DISPATCHING.replace(true) {
+        return;
+    }
+    for sink in sinks {
+        let mut sink = sink.lock().unwrap_or_else(|err| err.into_inner());
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| event(&mut *sink))) {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            eprintln!("faine: warning: event sink panicked: {message}");
+        }
+    }
+    DISPATCHING.set(false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Vec<usize>);
+
+    impl EventSink for Recorder {
+        fn on_visit(&mut self, _failpoint: &'static str, _branch: Branch, iteration: usize) {
+            self.0.push(iteration);
+            if iteration == 1 {
+                panic!("sink failure");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let sinks: Vec<SharedEventSink> = vec![recorder.clone()];
+        for iteration in 0..3 {
+            dispatch(&sinks, |sink| sink.on_visit("a", Branch::Skip, iteration));
+        }
+        // panicking sink stays attached
+        assert_eq!(recorder.lock().unwrap().0, [0, 1, 2]);
+    }
+}
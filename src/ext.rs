@@ -0,0 +1,230 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::io;
+use std::panic::Location;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::__private::{self, FAILPOINTS};
+use crate::common::{Branch, Label};
+use crate::injected::{ErrorTag, InjectedError};
+use crate::intern::FailpointId;
+
+/// Type erased error factory registered with [`Runner::with_error_factory()`](crate::Runner::with_error_factory)
+pub type ErrorFactory = Arc<dyn Any + Send + Sync>;
+
+type TypedErrorFactory<E> = Box<dyn Fn(InjectedError) -> E + Send + Sync>;
+
+/// Wrap an error factory, keyed by type of produced errors
+pub fn error_factory<E: 'static>(
+    factory: impl Fn(InjectedError) -> E + Send + Sync + 'static,
+) -> (TypeId, ErrorFactory) {
+    let factory: TypedErrorFactory<E> = Box::new(factory);
+    (TypeId::of::<E>(), Arc::new(factory))
+}
+
+/// Wrap an error factory which receives the name of the failpoint
+pub fn named_error_factory<E: 'static>(
+    factory: impl Fn(&'static str) -> E + Send + Sync + 'static,
+) -> (TypeId, ErrorFactory) {
+    error_factory(move |err: InjectedError| {
+        let name = match err.tag() {
+            Some(tag) => tagged_name(err.failpoint(), tag),
+            None => err.failpoint(),
+        };
+        factory(name)
+    })
+}
+
+static TAGGED_NAMES: LazyLock<RwLock<HashMap<(&'static str, ErrorTag), &'static str>>> =
+    LazyLock::new(Default::default);
+
+/// Append a tag to a failpoint name, once per failpoint and tag
+///
+/// Tags are deterministic, so repeated runs produce the same ones and
+/// reuse names produced before.
+fn tagged_name(failpoint: &'static str, tag: ErrorTag) -> &'static str {
+    let key = (failpoint, tag);
+    if let Some(name) = TAGGED_NAMES
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&key)
+    {
+        return name;
+    }
+    TAGGED_NAMES
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .entry(key)
+        .or_insert_with(|| Box::leak(format!("{failpoint} {tag}").into()))
+}
+
+type LocationKey = (&'static str, u32, u32);
+
+static LOCATIONS: LazyLock<RwLock<HashMap<LocationKey, &'static str>>> =
+    LazyLock::new(Default::default);
+
+/// Location of the caller, formatted the same way macros do it, once per call site
+#[track_caller]
+pub(crate) fn caller_location() -> &'static str {
+    let location = Location::caller();
+    let key = (location.file(), location.line(), location.column());
+    if let Some(name) = LOCATIONS
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&key)
+    {
+        return name;
+    }
+    LOCATIONS
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .entry(key)
+        .or_insert_with(|| Box::leak(format!("{}:{}:{}", key.0, key.1, key.2).into()))
+}
+
+#[track_caller]
+fn visit(failpoint: FailpointId) -> Branch {
+    let location = caller_location();
+    let enabled = FAILPOINTS.with_borrow(|state| state.as_ref().is_some_and(|state| state.enabled));
+    if !enabled {
+        return Branch::Skip;
+    }
+    __private::visit(Label::Failpoint(failpoint), location)
+}
+
+/// Produce an error with a factory registered for its type
+fn registered_error<E: 'static>(name: &'static str) -> Option<E> {
+    let factory = FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()?
+            .error_factories
+            .get(&TypeId::of::<E>())
+            .cloned()
+    })?;
+    let factory = factory.downcast_ref::<TypedErrorFactory<E>>()?;
+    Some(factory(__private::injected_error(name)))
+}
+
+/// Failpoints on results of fallible operations
+///
+/// Allows instrumenting code which propagates errors with `?`
+/// without wrapping calls into macros:
+///
+/// ```
+/// # use std::fs::File;
+/// # use std::io;
+/// use faine::ResultExt;
+///
+/// fn create(path: &str) -> io::Result<File> {
+///     let file = File::create(path).faine("create temp file")?;
+///     Ok(file)
+/// }
+/// ```
+///
+/// Failpoints defined this way are the same as ones defined with
+/// [`inject_override_with_side_effect!`](crate::inject_override_with_side_effect):
+/// the operation is always executed, and its result is replaced
+/// with an error when the failpoint is activated. Outside of
+/// [`Runner::run()`](crate::Runner::run), the result is passed
+/// through unchanged.
+///
+/// Methods are not macros, so they cannot tell which crate they are
+/// called from, and a plain string name does not belong to any crate
+/// (see [`FailpointId`]): the same name used by another crate refers
+/// to the same failpoint. To get the same failpoint as `inject_*`
+/// macros would define, pass a name interned with
+/// [`intern!`](crate::intern) or a handle declared with
+/// [`failpoints!`](crate::failpoints), which capture the crate:
+///
+/// ```
+/// # use std::fs::File;
+/// # use std::io;
+/// use faine::{ResultExt, intern};
+///
+/// fn create(path: &str) -> io::Result<File> {
+///     File::create(path).faine(intern!("create temp file"))
+/// }
+/// ```
+pub trait ResultExt<T, E>: Sized {
+    /// Replace the result with an error when the failpoint is activated
+    ///
+    /// The error is produced by a factory registered for `E` with
+    /// [`Runner::with_error_factory()`](crate::Runner::with_error_factory),
+    /// or, if there's none and `E` is [`io::Error`], it carries
+    /// [`InjectedError`](crate::InjectedError), like ones produced by
+    /// `*_io_error` macros.
+    ///
+    /// # Panics
+    ///
+    /// When the failpoint is activated, and there's no factory
+    /// registered for `E` other than [`io::Error`]. Use
+    /// [`faine_io()`](Self::faine_io) for error types which may be
+    /// converted from [`io::Error`].
+    fn faine(self, name: impl Into<FailpointId>) -> Self
+    where
+        E: 'static;
+
+    /// Replace the result with an error when the failpoint is activated,
+    /// converting from [`io::Error`]
+    ///
+    /// Same as [`faine()`](Self::faine), but if there's no factory
+    /// registered for `E`, the error is converted from [`io::Error`]
+    /// carrying [`InjectedError`](crate::InjectedError).
+    fn faine_io(self, name: impl Into<FailpointId>) -> Self
+    where
+        E: From<io::Error> + 'static;
+
+    /// Replace the result with an error produced by `f` when the failpoint is activated
+    fn faine_with(self, name: impl Into<FailpointId>, f: impl FnOnce() -> E) -> Self;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    #[track_caller]
+    fn faine(self, name: impl Into<FailpointId>) -> Self
+    where
+        E: 'static,
+    {
+        let failpoint = name.into();
+        let name = failpoint.unqualified_name();
+        match visit(failpoint) {
+            Branch::Activate => Err(registered_error(name)
+                .or_else(|| {
+                    let err: Box<dyn Any> =
+                        Box::new(io::Error::other(__private::injected_error(name)));
+                    err.downcast().ok().map(|err| *err)
+                })
+                .unwrap_or_else(|| {
+                    panic!(
+                        "no error factory registered for {} injected at failpoint {name}, see Runner::with_error_factory()",
+                        std::any::type_name::<E>()
+                    )
+                })),
+            Branch::Skip => self,
+        }
+    }
+
+    #[track_caller]
+    fn faine_io(self, name: impl Into<FailpointId>) -> Self
+    where
+        E: From<io::Error> + 'static,
+    {
+        let failpoint = name.into();
+        let name = failpoint.unqualified_name();
+        match visit(failpoint) {
+            Branch::Activate => Err(registered_error(name)
+                .unwrap_or_else(|| io::Error::other(__private::injected_error(name)).into())),
+            Branch::Skip => self,
+        }
+    }
+
+    #[track_caller]
+    fn faine_with(self, name: impl Into<FailpointId>, f: impl FnOnce() -> E) -> Self {
+        match visit(name.into()) {
+            Branch::Activate => Err(f()),
+            Branch::Skip => self,
+        }
+    }
+}
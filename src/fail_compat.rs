@@ -0,0 +1,291 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Compatibility layer for code instrumented with the `fail` crate
+//!
+//! Provides [`fail_point!`] with the same syntax as one from
+//! [fail](https://crates.io/crates/fail), so a crate may switch its
+//! `use fail::fail_point;` import to `use faine::fail_compat::fail_point;`
+//! and have its points explored by [`Runner`](crate::Runner):
+//!
+//! ```
+//! use faine::fail_compat::fail_point;
+//!
+//! fn read() -> Result<u32, String> {
+//!     fail_point!("read", |_| Err("injected".to_string()));
+//!     Ok(42)
+//! }
+//! ```
+//!
+//! Instead of being configured to fail, points are activated on some
+//! of explored paths, which performs an action. By default, points
+//! with a closure are activated with `return` action, and points
+//! without one are not explored, as activating them would have no
+//! effect. Actions may be changed with [`cfg()`], which accepts
+//! `fail` configuration strings. As exploration decides when points
+//! are activated, probabilities, counts and action chains have no
+//! meaning here, and some actions can not be expressed; such
+//! configurations are listed in [`migration_issues()`].
+//!
+//! Unlike in `fail`, configuration is local to the thread which makes
+//! it, same as exploration state of [`Runner`](crate::Runner), so
+//! tests which configure the same point differently may run in
+//! parallel. Configure points in the thread which runs the runner.
+//!
+//! Available with `fail-compat` feature.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::__private::visit;
+use crate::common::{Branch, Label};
+use crate::intern::FailpointId;
+
+/// Action performed when a point is activated
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    Off,
+    Return(Option<String>),
+    Sleep(Duration),
+    Panic(Option<String>),
+    Print(Option<String>),
+}
+
+/// Configuration of a point which could not be expressed exactly
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MigrationIssue {
+    /// Name of the point
+    pub name: String,
+
+    /// Configuration string, as passed to [`cfg()`]
+    pub config: String,
+
+    /// Why the configuration could not be expressed
+    pub reason: String,
+}
+
+impl std::fmt::Display for MigrationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}: {}", self.name, self.config, self.reason)
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    actions: HashMap<String, Action>,
+    issues: Vec<MigrationIssue>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = Default::default();
+}
+
+/// Parse a single `fail` action term, recording approximations into `reasons`
+fn parse_action(term: &str, reasons: &mut Vec<String>) -> Result<Action, String> {
+    let mut rest = term.trim();
+    if let Some((probability, tail)) = rest.split_once('%')
+        && probability.trim().parse::<f64>().is_ok()
+    {
+        reasons.push(format!(
+            "probability {}% is replaced by exploration",
+            probability.trim()
+        ));
+        rest = tail.trim();
+    }
+    if let Some((count, tail)) = rest.split_once('*')
+        && count.trim().parse::<usize>().is_ok()
+    {
+        reasons.push(format!("count {} is replaced by exploration", count.trim()));
+        rest = tail.trim();
+    }
+    let (kind, arg) = match rest.split_once('(') {
+        Some((kind, arg)) => {
+            let arg = arg
+                .strip_suffix(')')
+                .ok_or_else(|| format!("unterminated argument in `{term}`"))?;
+            (kind.trim(), Some(arg.to_string()))
+        }
+        None => (rest, None),
+    };
+    match kind {
+        "off" => Ok(Action::Off),
+        "return" => Ok(Action::Return(arg)),
+        "panic" => Ok(Action::Panic(arg)),
+        "print" => Ok(Action::Print(arg)),
+        "sleep" | "delay" => {
+            let millis = arg
+                .as_deref()
+                .unwrap_or_default()
+                .parse()
+                .map_err(|_| format!("invalid duration in `{term}`"))?;
+            if kind == "delay" {
+                reasons.push("delay (busy wait) is replaced by sleep".to_string());
+            }
+            Ok(Action::Sleep(Duration::from_millis(millis)))
+        }
+        "pause" | "yield" => {
+            reasons.push(format!("{kind} is not supported, point is never activated"));
+            Ok(Action::Off)
+        }
+        _ => Err(format!("unknown action `{kind}`")),
+    }
+}
+
+/// Configure action of a point, using `fail` configuration syntax
+///
+/// Mirrors `fail::cfg()`, but only applies to the current thread. Only the first action of a chain (terms
+/// separated by `->`) is used. Returns an error for configurations
+/// which can't be parsed; those which can only be approximated are
+/// accepted and listed in [`migration_issues()`].
+pub fn cfg(name: impl Into<String>, actions: &str) -> Result<(), String> {
+    let name = name.into();
+    let mut reasons = vec![];
+    let mut terms = actions.split("->");
+    let action = parse_action(terms.next().unwrap_or_default(), &mut reasons)?;
+    if terms.next().is_some() {
+        reasons.push("only the first action of a chain is used".to_string());
+    }
+    REGISTRY.with_borrow_mut(|registry| {
+        registry.issues.retain(|issue| issue.name != name);
+        for reason in reasons {
+            registry.issues.push(MigrationIssue {
+                name: name.clone(),
+                config: actions.to_string(),
+                reason,
+            });
+        }
+        registry.actions.insert(name, action);
+    });
+    Ok(())
+}
+
+/// Restore default action of a point
+///
+/// Mirrors `fail::remove()`, but only applies to the current thread.
+pub fn remove(name: &str) {
+    REGISTRY.with_borrow_mut(|registry| {
+        registry.actions.remove(name);
+        registry.issues.retain(|issue| issue.name != name);
+    });
+}
+
+/// Configurations which could not be expressed exactly
+///
+/// Besides approximations recorded by [`cfg()`], this lists points
+/// configured with `return` which were visited without a closure,
+/// and thus can not return, in the current thread.
+pub fn migration_issues() -> Vec<MigrationIssue> {
+    REGISTRY.with_borrow(|registry| registry.issues.clone())
+}
+
+fn action(name: &str) -> Option<Action> {
+    REGISTRY.with_borrow(|registry| registry.actions.get(name).cloned())
+}
+
+/// Perform an action which does not return
+fn perform(action: Action) {
+    match action {
+        Action::Sleep(duration) => std::thread::sleep(duration),
+        Action::Panic(Some(message)) => panic!("{message}"),
+        Action::Panic(None) => panic!("failpoint panic"),
+        Action::Print(message) => {
+            eprintln!("{}", message.as_deref().unwrap_or("failpoint executed"))
+        }
+        Action::Off | Action::Return(_) => {}
+    }
+}
+
+/// Visit a point with a closure, not a public API
+#[doc(hidden)]
+pub fn eval<R>(
+    krate: Option<&'static str>,
+    name: &'static str,
+    location: &'static str,
+    f: impl FnOnce(Option<String>) -> R,
+) -> Option<R> {
+    let action = action(name).unwrap_or(Action::Return(None));
+    if action == Action::Off
+        || visit(
+            Label::Failpoint(FailpointId::intern_from(krate, name)),
+            location,
+        ) == Branch::Skip
+    {
+        return None;
+    }
+    match action {
+        Action::Return(arg) => Some(f(arg)),
+        action => {
+            perform(action);
+            None
+        }
+    }
+}
+
+/// Visit a point without a closure, not a public API
+#[doc(hidden)]
+pub fn eval_plain(krate: Option<&'static str>, name: &'static str, location: &'static str) {
+    let Some(action) = action(name) else {
+        return;
+    };
+    if let Action::Return(_) = action {
+        REGISTRY.with_borrow_mut(|registry| {
+            if !registry.issues.iter().any(|issue| issue.name == name) {
+                registry.issues.push(MigrationIssue {
+                    name: name.to_string(),
+                    config: "return".to_string(),
+                    reason: "point has no closure to return with, never activated".to_string(),
+                });
+            }
+        });
+        return;
+    }
+    if action != Action::Off
+        && visit(
+            Label::Failpoint(FailpointId::intern_from(krate, name)),
+            location,
+        ) == Branch::Activate
+    {
+        perform(action);
+    }
+}
+
+/// Define a fail point, compatible with `fail::fail_point!`
+///
+/// See [module documentation](self).
+pub use crate::__fail_compat_fail_point as fail_point;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action() {
+        let mut reasons = vec![];
+        assert_eq!(
+            parse_action("return(foo)", &mut reasons),
+            Ok(Action::Return(Some("foo".into())))
+        );
+        assert_eq!(parse_action("off", &mut reasons), Ok(Action::Off));
+        assert_eq!(
+            parse_action("sleep(10)", &mut reasons),
+            Ok(Action::Sleep(Duration::from_millis(10)))
+        );
+        assert!(reasons.is_empty());
+
+        assert_eq!(
+            parse_action("20%3*panic(boom)", &mut reasons),
+            Ok(Action::Panic(Some("boom".into())))
+        );
+        assert_eq!(
+            reasons,
+            vec![
+                "probability 20% is replaced by exploration",
+                "count 3 is replaced by exploration"
+            ]
+        );
+        assert!(parse_action("explode", &mut reasons).is_err());
+        assert!(parse_action("return(foo", &mut reasons).is_err());
+    }
+}
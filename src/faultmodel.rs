@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::common::Branch;
+use crate::strategy::Rng;
+
+/// Model of failpoint activations for random strategy
+///
+/// Decides whether a failpoint is activated on each visit, possibly
+/// depending on previous decisions. See
+/// [`Runner::with_fault_model()`](crate::Runner::with_fault_model).
+pub trait FaultModel: Send {
+    /// Decide on a branch for the next visit
+    fn next(&mut self, rng: &mut Rng) -> Branch;
+
+    /// Reset model state at the start of an iteration
+    fn reset(&mut self) {}
+}
+
+fn branch_with_probability(rng: &mut Rng, probability: f64) -> Branch {
+    if rng.next_f64() < probability {
+        Branch::Activate
+    } else {
+        Branch::Skip
+    }
+}
+
+/// Memoryless model: each visit fails with a fixed probability
+#[derive(Debug, Clone)]
+pub struct Bernoulli {
+    p_fail: f64,
+}
+
+impl Bernoulli {
+    /// Construct a model with a given failure probability
+    pub fn new(p_fail: f64) -> Self {
+        Self { p_fail }
+    }
+}
+
+impl FaultModel for Bernoulli {
+    fn next(&mut self, rng: &mut Rng) -> Branch {
+        branch_with_probability(rng, self.p_fail)
+    }
+}
+
+/// Two state model, where failure probability depends on whether
+/// the previous visit has failed
+///
+/// With `p_fail_given_fail` higher than `p_fail_given_ok`, failures
+/// come in runs, like ones of a flaky dependency which keeps failing
+/// for a while before it recovers. Mean run length is
+/// `1 / (1 - p_fail_given_fail)`.
+#[derive(Debug, Clone)]
+pub struct Markov {
+    p_fail_given_ok: f64,
+    p_fail_given_fail: f64,
+    persistent: bool,
+    failing: bool,
+}
+
+impl Markov {
+    /// Construct a model with given transition probabilities
+    ///
+    /// Model starts in the ok state in each iteration.
+    pub fn new(p_fail_given_ok: f64, p_fail_given_fail: f64) -> Self {
+        Self {
+            p_fail_given_ok,
+            p_fail_given_fail,
+            persistent: false,
+            failing: false,
+        }
+    }
+
+    /// Keep model state across iterations
+    ///
+    /// Useful with long running [`Schedule`](crate::Schedule), where
+    /// iterations model consecutive requests to the same dependency.
+    pub fn persistent(mut self) -> Self {
+        self.persistent = true;
+        self
+    }
+}
+
+impl FaultModel for Markov {
+    fn next(&mut self, rng: &mut Rng) -> Branch {
+        let probability = if self.failing {
+            self.p_fail_given_fail
+        } else {
+            self.p_fail_given_ok
+        };
+        let branch = branch_with_probability(rng, probability);
+        self.failing = branch == Branch::Activate;
+        branch
+    }
+
+    fn reset(&mut self) {
+        if !self.persistent {
+            self.failing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_lengths(model: &mut impl FaultModel, visits: usize) -> Vec<usize> {
+        let mut rng = Rng::new(1);
+        let mut runs = vec![0];
+        for _ in 0..visits {
+            match model.next(&mut rng) {
+                Branch::Activate => *runs.last_mut().unwrap() += 1,
+                Branch::Skip => runs.push(0),
+            }
+        }
+        runs.retain(|run| *run > 0);
+        runs
+    }
+
+    #[test]
+    fn test_bernoulli() {
+        assert!(run_lengths(&mut Bernoulli::new(0.0), 100).is_empty());
+        assert_eq!(run_lengths(&mut Bernoulli::new(1.0), 100), vec![100]);
+    }
+
+    #[test]
+    fn test_markov() {
+        let runs = run_lengths(&mut Markov::new(0.05, 0.9), 100000);
+        let mean = runs.iter().sum::<usize>() as f64 / runs.len() as f64;
+        assert!((8.0..12.0).contains(&mean), "mean run length {mean}");
+
+        let mut model = Markov::new(0.0, 1.0);
+        model.failing = true;
+        model.reset();
+        assert!(!model.failing);
+        let mut model = Markov::new(0.0, 1.0).persistent();
+        model.failing = true;
+        model.reset();
+        assert!(model.failing);
+    }
+}
@@ -0,0 +1,193 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Testing build scripts and procedural macros
+//!
+//! Code of build scripts and procedural macros runs in processes
+//! spawned by `cargo`, so exploration has to happen in unit tests of
+//! the crate, and failpoints must only be compiled in for these
+//! tests. The layout which makes that work is:
+//!
+//! - `faine` is only a dev-dependency, never a normal or a build
+//!   one, so neither the build script binary nor the code emitted by
+//!   the procedural macro may reference it;
+//! - failpoints are gated by `cfg(test)`, which unlike features is
+//!   not unified across dependency kinds, and is only set when the
+//!   crate itself is tested:
+//!
+//!   ```ignore
+//!   macro_rules! failpoint {
+//!       ($name:literal) => {
+//!           #[cfg(test)]
+//!           faine::inject_return_io_error!($name);
+//!       };
+//!   }
+//!   ```
+//!
+//! - logic of a build script lives in a module shared between
+//!   `build.rs` (with `#[path = "src/codegen.rs"] mod codegen;`) and
+//!   the library (with `#[cfg(test)] mod codegen;`), so it's tested
+//!   along with the library;
+//! - logic of a procedural macro works with `proc_macro2` or plain
+//!   data, so it may be called from unit tests of the macro crate.
+//!
+//! Build script logic also depends on the environment set by `cargo`,
+//! and talks back to it through stdout. To make it testable, pass
+//! both in explicitly: an environment lookup function, for which
+//! [`BuildEnv`] provides a replacement describing the platform tests
+//! run on, and a writer for directives, which may be parsed back with
+//! [`parse_directives()`]:
+//!
+//! ```
+//! use std::io::{self, Write};
+//! use faine::Runner;
+//! use faine::for_buildscripts::{BuildEnv, parse_directives};
+//!
+//! fn generate(env: impl Fn(&str) -> Option<String>, out: &mut impl Write) -> io::Result<()> {
+//!     let out_dir = env("OUT_DIR").expect("OUT_DIR must be set");
+//!     faine::inject_return_io_error!("write generated code");
+//!     std::fs::write(format!("{out_dir}/generated.rs"), "")?;
+//!     writeln!(out, "cargo::rerun-if-changed=build.rs")
+//! }
+//!
+//! // in build.rs: generate(|name| std::env::var(name).ok(), &mut io::stdout())
+//!
+//! let out_dir = tempfile::tempdir().unwrap();
+//! let env = BuildEnv::new(out_dir.path());
+//! Runner::ignore_env()
+//!     .run(|| {
+//!         let mut out = vec![];
+//!         let res = generate(|name| env.var(name), &mut out);
+//!         let directives = parse_directives(&String::from_utf8(out).unwrap());
+//!         assert_eq!(res.is_ok(), !directives.is_empty());
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! See `tests/buildscript-test-project` and `tests/procmacro-test-project`
+//! in the repository for complete examples.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Environment of a build script, as set by `cargo`
+///
+/// Describes the platform tests are running on, which stands for
+/// both the host and the target, as build script logic under test
+/// is compiled for the former, and has no way to tell them apart.
+/// Sets `OUT_DIR`, `PROFILE`, `DEBUG`, `OPT_LEVEL`, `NUM_JOBS` and
+/// `CARGO_CFG_TARGET_*` variables describing the platform; add
+/// anything else with [`with_var()`](Self::with_var).
+///
+/// This does not modify the process environment, which is shared by
+/// concurrently running tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildEnv {
+    vars: BTreeMap<String, String>,
+}
+
+impl BuildEnv {
+    /// Construct environment with a given output directory
+    pub fn new(out_dir: impl AsRef<Path>) -> Self {
+        let vars = [
+            ("OUT_DIR", out_dir.as_ref().to_string_lossy().as_ref()),
+            ("PROFILE", "debug"),
+            ("DEBUG", "true"),
+            ("OPT_LEVEL", "0"),
+            ("NUM_JOBS", "1"),
+            ("CARGO_CFG_TARGET_ARCH", std::env::consts::ARCH),
+            ("CARGO_CFG_TARGET_OS", std::env::consts::OS),
+            ("CARGO_CFG_TARGET_FAMILY", std::env::consts::FAMILY),
+            (
+                "CARGO_CFG_TARGET_ENDIAN",
+                if cfg!(target_endian = "big") {
+                    "big"
+                } else {
+                    "little"
+                },
+            ),
+            ("CARGO_CFG_TARGET_POINTER_WIDTH", &(usize::BITS).to_string()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        Self { vars }
+    }
+
+    /// Set an additional variable, or override a default one
+    pub fn with_var(mut self, name: &str, value: &str) -> Self {
+        self.vars.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Value of a variable, like [`std::env::var()`] but infallible
+    pub fn var(&self, name: &str) -> Option<String> {
+        self.vars.get(name).cloned()
+    }
+}
+
+/// Directive printed by a build script
+///
+/// Both `cargo::key=value` and legacy `cargo:key=value` forms are
+/// parsed into the same representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    /// Directive name, such as `rerun-if-changed`
+    pub key: String,
+
+    /// Directive argument
+    pub value: String,
+}
+
+/// Parse directives from build script output, ignoring other lines
+pub fn parse_directives(output: &str) -> Vec<Directive> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let directive = line
+                .strip_prefix("cargo::")
+                .or_else(|| line.strip_prefix("cargo:"))?;
+            let (key, value) = directive.split_once('=')?;
+            Some(Directive {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_env() {
+        let env = BuildEnv::new("/out").with_var("CARGO_FEATURE_FOO", "1");
+        assert_eq!(env.var("OUT_DIR").as_deref(), Some("/out"));
+        assert_eq!(env.var("CARGO_FEATURE_FOO").as_deref(), Some("1"));
+        assert_eq!(
+            env.var("CARGO_CFG_TARGET_OS").as_deref(),
+            Some(std::env::consts::OS)
+        );
+        assert_eq!(env.var("TARGET"), None);
+    }
+
+    #[test]
+    fn test_parse_directives() {
+        let directives = parse_directives(
+            "cargo::rerun-if-changed=build.rs\nnoise\ncargo:rustc-cfg=foo\ncargo::warning=a=b\n",
+        );
+        let pairs: Vec<_> = directives
+            .iter()
+            .map(|directive| (directive.key.as_str(), directive.value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("rerun-if-changed", "build.rs"),
+                ("rustc-cfg", "foo"),
+                ("warning", "a=b")
+            ]
+        );
+    }
+}
@@ -0,0 +1,333 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Failpoint-aware filesystem helpers
+//!
+//! [`metadata()`] wraps [`std::fs::metadata()`] with a multi-valued
+//! failpoint which returns inconsistent metadata instead of failing,
+//! with the following variants:
+//!
+//! - `len off`: length is off by a delta.
+//! - `mtime in past`, `mtime in future`: modification time is moved
+//!   by an offset.
+//! - `read-only`: permissions are read-only.
+//!
+//! Delta and offset may be set per failpoint with
+//! [`Runner::with_metadata_perturbation()`](crate::Runner::with_metadata_perturbation).
+//! As [`std::fs::Metadata`] can't be constructed, results are returned
+//! as [`FaineMetadata`], which mirrors its commonly used accessors.
+//!
+//! [`write()`], [`File`], [`sync_all()`], [`rename()`] and
+//! [`remove_file()`] wrap respective [`std::fs`] operations with
+//! failpoints which fail them with an injected error, and additionally
+//! track which of their effects would survive a crash (for instance, one simulated by an
+//! injected failure) under the configured [`DurabilityModel`]. The
+//! tested code may compare the [`surviving_state()`] to what it
+//! expects to find on disk after recovery, catching bugs such as a
+//! missing sync of the parent directory after replacing a file:
+//!
+//! ```
+//! use std::io;
+//! use std::path::Path;
+//! use faine::fs;
+//!
+//! fn save(dir: &Path, data: &str) -> io::Result<()> {
+//!     fs::write(dir.join("data.tmp"), data)?;
+//!     fs::sync_all(dir.join("data.tmp"))?;
+//!     fs::rename(dir.join("data.tmp"), dir.join("data"))?;
+//!     fs::sync_all(dir)
+//! }
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! faine::Runner::default()
+//!     .run(|| {
+//!         std::fs::write(dir.path().join("data"), "old").unwrap();
+//!         let res = save(dir.path(), "new");
+//!         let surviving = fs::surviving_state().get(dir.path().join("data")).map(<[u8]>::to_vec);
+//!         if res.is_ok() {
+//!             assert_eq!(surviving.as_deref(), Some(&b"new"[..]));
+//!         } else {
+//!             // replacement is atomic
+//!             assert!(matches!(surviving.as_deref(), Some(b"old" | b"new")));
+//!         }
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! [`FsBackend`] trait covers these operations, and is implemented
+//! by [`RealFs`] with the wrappers above, and by [`MemFs`], which
+//! keeps files in memory and uses the same failpoints, so the tested
+//! code generic over the backend may be explored without touching
+//! the disk.
+//!
+//! [`FixtureTree`] keeps a directory tree fixture, and cheaply resets
+//! its working copy to pristine state, see also
+//! [`Runner::with_fixture_tree()`](crate::Runner::with_fixture_tree).
+//!
+//! Available with `fs` feature.
+
+mod backend;
+mod durability;
+mod fixture;
+mod memfs;
+
+pub use backend::{FsBackend, FsFile, RealFs};
+
+pub(crate) use durability::DurabilityTracker;
+pub use durability::{
+    DurabilityModel, File, ModelFs, REMOVE_FILE, RENAME, SYNC, WRITE, remove_file, rename,
+    surviving_state, sync_all, write,
+};
+pub use fixture::{FixtureTiming, FixtureTree, fixture_dir};
+pub use memfs::{MemFile, MemFs};
+
+use std::fs::{Metadata, Permissions};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::__private::{FAILPOINTS, visit_variants};
+
+/// Name of the failpoint defined by [`metadata()`]
+pub const METADATA: &str = "fs metadata";
+
+const LEN_OFF: &str = "len off";
+const MTIME_IN_PAST: &str = "mtime in past";
+const MTIME_IN_FUTURE: &str = "mtime in future";
+const READ_ONLY: &str = "read-only";
+
+const VARIANTS: &[&str] = &[LEN_OFF, MTIME_IN_PAST, MTIME_IN_FUTURE, READ_ONLY];
+
+/// Parameters of metadata perturbations
+///
+/// See [`Runner::with_metadata_perturbation()`](crate::Runner::with_metadata_perturbation).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataPerturbation {
+    len_delta: i64,
+    mtime_offset: Duration,
+}
+
+impl Default for MetadataPerturbation {
+    /// Length off by one, modification time moved by an hour
+    fn default() -> Self {
+        Self {
+            len_delta: 1,
+            mtime_offset: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl MetadataPerturbation {
+    /// Set delta added to the length by `len off` variant
+    ///
+    /// The resulting length saturates at zero.
+    pub fn with_len_delta(mut self, len_delta: i64) -> Self {
+        self.len_delta = len_delta;
+        self
+    }
+
+    /// Set offset of modification time for `mtime in past` and `mtime in future` variants
+    pub fn with_mtime_offset(mut self, mtime_offset: Duration) -> Self {
+        self.mtime_offset = mtime_offset;
+        self
+    }
+}
+
+/// Type of a filesystem entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// Regular file
+    File,
+    /// Directory
+    Dir,
+    /// Symbolic link
+    Symlink,
+    /// Any other entry, such as a device or a socket
+    Other,
+}
+
+/// Filesystem metadata, possibly perturbed
+///
+/// Mirrors accessors of [`std::fs::Metadata`], see [module documentation](self).
+/// Also describes entries of [`MemFs`], which have no
+/// [`Permissions`], only the read-only flag.
+#[derive(Debug, Clone)]
+pub struct FaineMetadata {
+    file_type: FileKind,
+    len: u64,
+    readonly: bool,
+    permissions: Option<Permissions>,
+    modified: Result<SystemTime, io::ErrorKind>,
+}
+
+impl From<Metadata> for FaineMetadata {
+    fn from(metadata: Metadata) -> Self {
+        let file_type = metadata.file_type();
+        Self {
+            file_type: if file_type.is_symlink() {
+                FileKind::Symlink
+            } else if file_type.is_dir() {
+                FileKind::Dir
+            } else if file_type.is_file() {
+                FileKind::File
+            } else {
+                FileKind::Other
+            },
+            len: metadata.len(),
+            readonly: metadata.permissions().readonly(),
+            permissions: Some(metadata.permissions()),
+            modified: metadata.modified().map_err(|err| err.kind()),
+        }
+    }
+}
+
+impl FaineMetadata {
+    /// Metadata of an entry of [`MemFs`]
+    fn in_memory(file_type: FileKind, len: u64, modified: SystemTime) -> Self {
+        Self {
+            file_type,
+            len,
+            readonly: false,
+            permissions: None,
+            modified: Ok(modified),
+        }
+    }
+
+    /// Type of the entry, see [`Metadata::file_type()`]
+    pub fn file_type(&self) -> FileKind {
+        self.file_type
+    }
+
+    /// See [`Metadata::is_dir()`]
+    pub fn is_dir(&self) -> bool {
+        self.file_type == FileKind::Dir
+    }
+
+    /// See [`Metadata::is_file()`]
+    pub fn is_file(&self) -> bool {
+        self.file_type == FileKind::File
+    }
+
+    /// See [`Metadata::is_symlink()`]
+    pub fn is_symlink(&self) -> bool {
+        self.file_type == FileKind::Symlink
+    }
+
+    /// See [`Metadata::len()`]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the length is zero
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the entry is read-only, see [`Permissions::readonly()`]
+    pub fn readonly(&self) -> bool {
+        self.readonly
+    }
+
+    /// See [`Metadata::permissions()`]
+    ///
+    /// `None` for entries of [`MemFs`].
+    pub fn permissions(&self) -> Option<Permissions> {
+        self.permissions.clone()
+    }
+
+    /// See [`Metadata::modified()`]
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        self.modified.map_err(io::Error::from)
+    }
+
+    fn perturb(&mut self, variant: &str, perturbation: MetadataPerturbation) {
+        match variant {
+            LEN_OFF => self.len = self.len.saturating_add_signed(perturbation.len_delta),
+            MTIME_IN_PAST => {
+                if let Ok(modified) = &mut self.modified {
+                    *modified = modified
+                        .checked_sub(perturbation.mtime_offset)
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                }
+            }
+            MTIME_IN_FUTURE => {
+                if let Ok(modified) = &mut self.modified {
+                    *modified = modified
+                        .checked_add(perturbation.mtime_offset)
+                        .unwrap_or(*modified);
+                }
+            }
+            READ_ONLY => {
+                self.readonly = true;
+                if let Some(permissions) = &mut self.permissions {
+                    permissions.set_readonly(true);
+                }
+            }
+            _ => unreachable!("unexpected metadata variant {variant}"),
+        }
+    }
+}
+
+/// Query metadata of a path, see [module documentation](self)
+///
+/// Uses failpoint named [`METADATA`].
+#[track_caller]
+pub fn metadata(path: impl AsRef<Path>) -> io::Result<FaineMetadata> {
+    labeled_metadata(METADATA, path)
+}
+
+/// Query metadata of a path, with a failpoint named after the label
+#[track_caller]
+pub fn labeled_metadata(label: &'static str, path: impl AsRef<Path>) -> io::Result<FaineMetadata> {
+    perturbed(label, FaineMetadata::from(std::fs::metadata(path)?))
+}
+
+/// Apply perturbation chosen by a metadata failpoint
+#[track_caller]
+fn perturbed(label: &'static str, mut metadata: FaineMetadata) -> io::Result<FaineMetadata> {
+    let location = crate::ext::caller_location();
+    if let Some(index) = visit_variants(label, location, VARIANTS) {
+        let perturbation = FAILPOINTS.with_borrow(|state| {
+            state
+                .as_ref()
+                .and_then(|state| state.metadata_perturbations.get(label).copied())
+                .unwrap_or_default()
+        });
+        metadata.perturb(VARIANTS[index], perturbation);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_perturb() {
+        let original = FaineMetadata::from(std::fs::metadata("Cargo.toml").unwrap());
+        let perturbation = MetadataPerturbation::default()
+            .with_len_delta(-(original.len() as i64) - 1)
+            .with_mtime_offset(Duration::from_secs(10));
+
+        let mut metadata = original.clone();
+        metadata.perturb(LEN_OFF, perturbation);
+        assert_eq!(metadata.len(), 0);
+
+        let mut metadata = original.clone();
+        metadata.perturb(MTIME_IN_PAST, perturbation);
+        assert_eq!(
+            original
+                .modified()
+                .unwrap()
+                .duration_since(metadata.modified().unwrap())
+                .unwrap(),
+            Duration::from_secs(10)
+        );
+
+        let mut metadata = original.clone();
+        metadata.perturb(READ_ONLY, perturbation);
+        assert!(metadata.readonly());
+        assert!(metadata.permissions().unwrap().readonly());
+        assert!(metadata.is_file());
+    }
+}
@@ -0,0 +1,122 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::FaineMetadata;
+
+/// Filesystem operations with built-in failpoints
+///
+/// Implemented by [`RealFs`], which operates on the real filesystem
+/// with the wrappers of this module, and by [`MemFs`](super::MemFs),
+/// which keeps files in memory. Both use the same failpoints, so the
+/// tested code generic over the backend is explored identically with
+/// either of them.
+pub trait FsBackend {
+    /// File opened for writing by [`create()`](Self::create)
+    type File: FsFile;
+
+    /// Create a directory and all its missing parents, see [`std::fs::create_dir_all()`]
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Write a file, see [`write()`](super::write)
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Create or truncate a file and open it for writing, see [`File::create()`](super::File::create)
+    fn create(&self, path: &Path) -> io::Result<Self::File>;
+
+    /// Read a file, see [`std::fs::read()`]
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Read a file into a string, see [`std::fs::read_to_string()`]
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Sync a file or a directory, see [`sync_all()`](super::sync_all)
+    fn sync_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Rename a file or a directory, see [`rename()`](super::rename)
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Remove a file, see [`remove_file()`](super::remove_file)
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Query metadata of a path, see [`metadata()`](super::metadata)
+    fn metadata(&self, path: &Path) -> io::Result<FaineMetadata>;
+
+    /// Paths of entries in a directory, sorted, see [`std::fs::read_dir()`]
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+}
+
+/// File opened for writing by [`FsBackend::create()`]
+///
+/// Writes either write the whole buffer or fail, and use the same
+/// failpoint as [`FsBackend::write()`].
+pub trait FsFile: io::Write {
+    /// Sync contents of the file, see [`File::sync_all()`](super::File::sync_all)
+    fn sync_all(&self) -> io::Result<()>;
+}
+
+impl FsFile for super::File {
+    #[track_caller]
+    fn sync_all(&self) -> io::Result<()> {
+        super::File::sync_all(self)
+    }
+}
+
+/// Real filesystem, see [`FsBackend`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl FsBackend for RealFs {
+    type File = super::File;
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    #[track_caller]
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        super::write(path, contents)
+    }
+
+    #[track_caller]
+    fn create(&self, path: &Path) -> io::Result<Self::File> {
+        super::File::create(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    #[track_caller]
+    fn sync_all(&self, path: &Path) -> io::Result<()> {
+        super::sync_all(path)
+    }
+
+    #[track_caller]
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        super::rename(from, to)
+    }
+
+    #[track_caller]
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        super::remove_file(path)
+    }
+
+    #[track_caller]
+    fn metadata(&self, path: &Path) -> io::Result<FaineMetadata> {
+        super::metadata(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut paths = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+        Ok(paths)
+    }
+}
@@ -0,0 +1,587 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::__private::{self, FAILPOINTS, visit};
+use crate::common::{Branch, Label};
+use crate::intern::FailpointId;
+
+/// Name of the failpoint defined by [`write()`]
+pub const WRITE: &str = "fs write";
+/// Name of the failpoint defined by [`sync_all()`]
+pub const SYNC: &str = "fs sync";
+/// Name of the failpoint defined by [`rename()`]
+pub const RENAME: &str = "fs rename";
+/// Name of the failpoint defined by [`remove_file()`]
+pub const REMOVE_FILE: &str = "fs remove file";
+
+/// Rules deciding which operations survive a crash
+///
+/// See [`Runner::with_durability_model()`](crate::Runner::with_durability_model).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DurabilityModel {
+    /// Only synced data survives
+    ///
+    /// File contents survive once the file is synced, and creation,
+    /// renaming or removal of a file survives once its parent directory
+    /// is synced. A file whose directory entry survived, but whose
+    /// contents were never synced, survives empty.
+    #[default]
+    Posix,
+    /// Every operation survives as soon as it completes, as with
+    /// filesystems mounted for synchronous writes
+    WriteThrough,
+}
+
+/// Model of file contents, see [`surviving_state()`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelFs {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl ModelFs {
+    /// Read regular files directly in a directory
+    ///
+    /// Allows comparing the actual directory contents to the model.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut files = BTreeMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                let path = entry.path();
+                files.insert(normalize(&path), std::fs::read(&path)?);
+            }
+        }
+        Ok(Self { files })
+    }
+
+    /// Contents of a file, if it exists
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&[u8]> {
+        self.files.get(&normalize(path.as_ref())).map(Vec::as_slice)
+    }
+
+    /// Iterate over files and their contents, ordered by path
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &[u8])> {
+        self.files
+            .iter()
+            .map(|(path, content)| (path.as_path(), content.as_slice()))
+    }
+
+    /// Leave only files directly in a directory
+    pub fn within(mut self, dir: impl AsRef<Path>) -> Self {
+        let dir = normalize(dir.as_ref());
+        self.files.retain(|path, _| path.parent() == Some(&dir));
+        self
+    }
+
+    /// Number of files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether there are no files
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct Inode {
+    content: Vec<u8>,
+    durable: Option<Vec<u8>>,
+    modified: SystemTime,
+}
+
+/// Per-iteration model of files touched by the wrappers
+#[derive(Debug, Default)]
+pub struct DurabilityTracker {
+    model: DurabilityModel,
+    inodes: Vec<Inode>,
+    entries: BTreeMap<PathBuf, usize>,
+    durable_entries: BTreeMap<PathBuf, usize>,
+    loaded: BTreeSet<PathBuf>,
+    loaded_dirs: BTreeSet<PathBuf>,
+    renamed_dirs: Vec<(PathBuf, PathBuf)>,
+}
+
+impl DurabilityTracker {
+    pub fn new(model: DurabilityModel) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.model);
+    }
+
+    /// Take files not seen before as durable, with their current
+    /// contents, loading the whole directory on first access to it
+    fn load(&mut self, path: &Path) {
+        if let Some(dir) = path.parent()
+            && self.loaded_dirs.insert(dir.to_path_buf())
+            && let Ok(entries) = std::fs::read_dir(dir)
+        {
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                    self.load_file(&normalize(&entry.path()));
+                }
+            }
+        }
+        self.load_file(path);
+    }
+
+    fn load_file(&mut self, path: &Path) {
+        if !self.loaded.insert(path.to_path_buf()) {
+            return;
+        }
+        if let Ok(content) = std::fs::read(path) {
+            let inode = self.inodes.len();
+            self.inodes.push(Inode {
+                durable: Some(content.clone()),
+                content,
+                modified: SystemTime::now(),
+            });
+            self.entries.insert(path.to_path_buf(), inode);
+            self.durable_entries.insert(path.to_path_buf(), inode);
+        }
+    }
+
+    pub(super) fn write(&mut self, path: &Path, content: &[u8]) {
+        match self.entries.get(path) {
+            Some(&inode) => {
+                let inode = &mut self.inodes[inode];
+                inode.content = content.to_vec();
+                inode.modified = SystemTime::now();
+            }
+            None => {
+                let inode = self.inodes.len();
+                self.inodes.push(Inode {
+                    content: content.to_vec(),
+                    durable: None,
+                    modified: SystemTime::now(),
+                });
+                self.entries.insert(path.to_path_buf(), inode);
+            }
+        }
+    }
+
+    /// Create or truncate a file, returning its inode
+    pub(super) fn create(&mut self, path: &Path) -> usize {
+        self.write(path, b"");
+        self.entries[path]
+    }
+
+    /// Append to a file by its inode, which is kept by open files
+    pub(super) fn append(&mut self, inode: usize, data: &[u8]) {
+        if let Some(inode) = self.inodes.get_mut(inode) {
+            inode.content.extend_from_slice(data);
+            inode.modified = SystemTime::now();
+        }
+    }
+
+    pub(super) fn sync_file(&mut self, path: &Path) {
+        if let Some(&inode) = self.entries.get(path) {
+            self.sync_inode(inode);
+        }
+    }
+
+    pub(super) fn sync_inode(&mut self, inode: usize) {
+        if let Some(inode) = self.inodes.get_mut(inode) {
+            inode.durable = Some(inode.content.clone());
+        }
+    }
+
+    pub(super) fn sync_dir(&mut self, dir: &Path) {
+        let (synced, pending) = std::mem::take(&mut self.renamed_dirs)
+            .into_iter()
+            .partition(|(_, to)| to.parent() == Some(dir));
+        self.renamed_dirs = pending;
+        for (from, to) in synced {
+            self.durable_entries = std::mem::take(&mut self.durable_entries)
+                .into_iter()
+                .map(|(path, inode)| (moved(&path, &from, &to), inode))
+                .collect();
+        }
+        let children: BTreeSet<PathBuf> = self
+            .entries
+            .keys()
+            .chain(self.durable_entries.keys())
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect();
+        for path in children {
+            match self.entries.get(&path) {
+                Some(&inode) => self.durable_entries.insert(path, inode),
+                None => self.durable_entries.remove(&path),
+            };
+        }
+    }
+
+    pub(super) fn rename(&mut self, from: &Path, to: &Path) {
+        if let Some(inode) = self.entries.remove(from) {
+            self.entries.insert(to.to_path_buf(), inode);
+        }
+    }
+
+    /// Rename a directory, moving all files under it
+    ///
+    /// Like a rename of a file, it is not durable until the new parent
+    /// directory is synced.
+    pub(super) fn rename_dir(&mut self, from: &Path, to: &Path) {
+        self.entries = std::mem::take(&mut self.entries)
+            .into_iter()
+            .map(|(path, inode)| (moved(&path, from, to), inode))
+            .collect();
+        // files under the directory were already loaded under the old path
+        for loaded in [&mut self.loaded, &mut self.loaded_dirs] {
+            let renamed: Vec<PathBuf> = loaded
+                .iter()
+                .filter(|path| path.starts_with(from))
+                .map(|path| moved(path, from, to))
+                .collect();
+            loaded.extend(renamed);
+        }
+        self.renamed_dirs
+            .push((from.to_path_buf(), to.to_path_buf()));
+    }
+
+    pub(super) fn remove_file(&mut self, path: &Path) {
+        self.entries.remove(path);
+    }
+
+    /// Write a file and make it durable, along with its directory entry
+    pub(super) fn write_durable(&mut self, path: &Path, content: &[u8]) {
+        self.write(path, content);
+        self.sync_file(path);
+        let inode = self.entries[path];
+        self.durable_entries.insert(path.to_path_buf(), inode);
+    }
+
+    /// Contents and modification time of a file
+    pub(super) fn file(&self, path: &Path) -> Option<(&[u8], SystemTime)> {
+        let inode = &self.inodes[*self.entries.get(path)?];
+        Some((&inode.content, inode.modified))
+    }
+
+    /// Paths of all files
+    pub(super) fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.entries.keys().map(PathBuf::as_path)
+    }
+
+    /// Replace the current state with the one which survives a crash
+    pub(super) fn crash(&mut self) {
+        self.entries = self.durable_entries.clone();
+        self.renamed_dirs.clear();
+        for inode in &mut self.inodes {
+            inode.content = inode.durable.clone().unwrap_or_default();
+        }
+    }
+
+    /// Apply durability model after an operation
+    pub(super) fn settle(&mut self) {
+        if self.model == DurabilityModel::WriteThrough {
+            self.durable_entries = self.entries.clone();
+            self.renamed_dirs.clear();
+            for inode in &mut self.inodes {
+                inode.durable = Some(inode.content.clone());
+            }
+        }
+    }
+
+    pub(super) fn surviving_state(&self) -> ModelFs {
+        ModelFs {
+            files: self
+                .durable_entries
+                .iter()
+                .map(|(path, &inode)| {
+                    (
+                        path.clone(),
+                        self.inodes[inode].durable.clone().unwrap_or_default(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Path under `from` moved under `to`, other paths are left intact
+pub(super) fn moved(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(rest) => to.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[track_caller]
+pub(super) fn injected(label: &'static str) -> Option<io::Error> {
+    let location = crate::ext::caller_location();
+    let failpoint = FailpointId::intern_for_crate(env!("CARGO_PKG_NAME"), label);
+    (visit(Label::Failpoint(failpoint), location) == Branch::Activate)
+        .then(|| io::Error::other(__private::injected_error(label)))
+}
+
+/// Update the model of the current iteration, if any
+fn track(paths: &[&Path], update: impl FnOnce(&mut DurabilityTracker, &[PathBuf])) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            let paths: Vec<PathBuf> = paths.iter().map(|path| normalize(path)).collect();
+            for path in &paths {
+                state.durability.load(path);
+            }
+            update(&mut state.durability, &paths);
+            state.durability.settle();
+        }
+    })
+}
+
+/// Write a file, see [`std::fs::write()`]
+///
+/// Uses failpoint named [`WRITE`], and is tracked by the durability
+/// model (see [`surviving_state()`]). Written contents are not durable
+/// until the file is synced with [`sync_all()`].
+#[track_caller]
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let (path, contents) = (path.as_ref(), contents.as_ref());
+    // files must be loaded into the model before they are changed
+    track(&[path], |_, _| {});
+    if let Some(err) = injected(WRITE) {
+        return Err(err);
+    }
+    std::fs::write(path, contents)?;
+    track(&[path], |tracker, paths| tracker.write(&paths[0], contents));
+    Ok(())
+}
+
+/// Sync a file or a directory to disk, see [`std::fs::File::sync_all()`]
+///
+/// Uses failpoint named [`SYNC`]. Syncing a file makes its contents
+/// durable, and syncing a directory makes creations, renames and
+/// removals of files in it durable.
+#[track_caller]
+pub fn sync_all(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    track(&[path], |_, _| {});
+    if let Some(err) = injected(SYNC) {
+        return Err(err);
+    }
+    let file = std::fs::File::open(path)?;
+    let is_dir = file.metadata()?.is_dir();
+    file.sync_all()?;
+    track(&[path], |tracker, paths| {
+        if is_dir {
+            tracker.sync_dir(&paths[0]);
+        } else {
+            tracker.sync_file(&paths[0]);
+        }
+    });
+    Ok(())
+}
+
+/// Rename a file or a directory, see [`std::fs::rename()`]
+///
+/// Uses failpoint named [`RENAME`]. The rename is not durable until the
+/// parent directories are synced with [`sync_all()`].
+#[track_caller]
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    track(&[from, to], |_, _| {});
+    if let Some(err) = injected(RENAME) {
+        return Err(err);
+    }
+    std::fs::rename(from, to)?;
+    let is_dir = std::fs::symlink_metadata(to).is_ok_and(|metadata| metadata.is_dir());
+    track(&[from, to], |tracker, paths| {
+        if is_dir {
+            tracker.rename_dir(&paths[0], &paths[1]);
+        } else {
+            tracker.rename(&paths[0], &paths[1]);
+        }
+    });
+    Ok(())
+}
+
+/// Remove a file, see [`std::fs::remove_file()`]
+///
+/// Uses failpoint named [`REMOVE_FILE`]. The removal is not durable
+/// until the parent directory is synced with [`sync_all()`].
+#[track_caller]
+pub fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    track(&[path], |_, _| {});
+    if let Some(err) = injected(REMOVE_FILE) {
+        return Err(err);
+    }
+    std::fs::remove_file(path)?;
+    track(&[path], |tracker, paths| tracker.remove_file(&paths[0]));
+    Ok(())
+}
+
+/// File opened for writing, see [`std::fs::File`]
+///
+/// Creation and writes use failpoint named [`WRITE`], and syncs use
+/// [`SYNC`]. Each write either writes the whole buffer or fails, and
+/// is tracked by the durability model (see [`surviving_state()`]) like
+/// [`write()`]; written contents are not durable until the file is
+/// synced with [`File::sync_all()`].
+#[derive(Debug)]
+pub struct File {
+    file: std::fs::File,
+    inode: Option<usize>,
+}
+
+impl File {
+    /// Create or truncate a file and open it for writing, see [`std::fs::File::create()`]
+    #[track_caller]
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        track(&[path], |_, _| {});
+        if let Some(err) = injected(WRITE) {
+            return Err(err);
+        }
+        let file = std::fs::File::create(path)?;
+        let mut inode = None;
+        track(&[path], |tracker, paths| {
+            inode = Some(tracker.create(&paths[0]));
+        });
+        Ok(Self { file, inode })
+    }
+
+    /// Sync contents of the file to disk, see [`std::fs::File::sync_all()`]
+    #[track_caller]
+    pub fn sync_all(&self) -> io::Result<()> {
+        if let Some(err) = injected(SYNC) {
+            return Err(err);
+        }
+        self.file.sync_all()?;
+        if let Some(inode) = self.inode {
+            track(&[], |tracker, _| tracker.sync_inode(inode));
+        }
+        Ok(())
+    }
+}
+
+impl io::Write for File {
+    #[track_caller]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    #[track_caller]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        if let Some(err) = injected(WRITE) {
+            return Err(err);
+        }
+        self.file.write_all(buf)?;
+        if let Some(inode) = self.inode {
+            track(&[], |tracker, _| tracker.append(inode, buf));
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Files which would survive a crash at this point of the current iteration
+///
+/// Covers files touched by [`write()`], [`File`], [`sync_all()`],
+/// [`rename()`] and [`remove_file()`] in the current iteration, and other files in
+/// their directories, under the configured [`DurabilityModel`]. Files
+/// which existed before their directory was first accessed by these
+/// functions are assumed to be durable. Returns an empty model outside
+/// of exploration.
+pub fn surviving_state() -> ModelFs {
+    FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .map(|state| state.durability.surviving_state())
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(tracker: &DurabilityTracker) -> Vec<(&str, &str)> {
+        tracker
+            .durable_entries
+            .iter()
+            .map(|(path, &inode)| {
+                (
+                    path.to_str().unwrap(),
+                    std::str::from_utf8(tracker.inodes[inode].durable.as_deref().unwrap_or(b""))
+                        .unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_posix() {
+        let mut tracker = DurabilityTracker::new(DurabilityModel::Posix);
+        let (dir, tmp, file) = (Path::new("/d"), Path::new("/d/tmp"), Path::new("/d/f"));
+        tracker.write(tmp, b"new");
+        tracker.sync_dir(dir);
+        assert_eq!(state(&tracker), [("/d/tmp", "")]);
+        tracker.sync_file(tmp);
+        assert_eq!(state(&tracker), [("/d/tmp", "new")]);
+        tracker.rename(tmp, file);
+        assert_eq!(state(&tracker), [("/d/tmp", "new")]);
+        tracker.sync_dir(dir);
+        assert_eq!(state(&tracker), [("/d/f", "new")]);
+        tracker.remove_file(file);
+        tracker.sync_dir(Path::new("/other"));
+        assert_eq!(state(&tracker), [("/d/f", "new")]);
+        tracker.sync_dir(dir);
+        assert_eq!(state(&tracker), []);
+    }
+
+    #[test]
+    fn test_write_through() {
+        let mut tracker = DurabilityTracker::new(DurabilityModel::WriteThrough);
+        tracker.write(Path::new("/d/tmp"), b"new");
+        tracker.settle();
+        tracker.rename(Path::new("/d/tmp"), Path::new("/d/f"));
+        tracker.settle();
+        assert_eq!(state(&tracker), [("/d/f", "new")]);
+    }
+
+    #[test]
+    fn test_open_file() {
+        let mut tracker = DurabilityTracker::new(DurabilityModel::Posix);
+        let (dir, file) = (Path::new("/d"), Path::new("/d/f"));
+        let inode = tracker.create(file);
+        tracker.append(inode, b"ab");
+        tracker.sync_inode(inode);
+        tracker.append(inode, b"c");
+        tracker.sync_dir(dir);
+        assert_eq!(state(&tracker), [("/d/f", "ab")]);
+        assert_eq!(tracker.file(file).unwrap().0, b"abc");
+    }
+
+    #[test]
+    fn test_rename_dir() {
+        let mut tracker = DurabilityTracker::new(DurabilityModel::Posix);
+        tracker.write_durable(Path::new("/d/old/f"), b"data");
+        tracker.rename_dir(Path::new("/d/old"), Path::new("/d/new"));
+        assert!(tracker.file(Path::new("/d/new/f")).is_some());
+        tracker.sync_dir(Path::new("/other"));
+        assert_eq!(state(&tracker), [("/d/old/f", "data")]);
+        tracker.sync_dir(Path::new("/d"));
+        assert_eq!(state(&tracker), [("/d/new/f", "data")]);
+    }
+}
@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::__private::FAILPOINTS;
+
+/// Number of attempts to remove a working copy before giving up
+///
+/// On Windows, files which are still open elsewhere (for instance,
+/// by an antivirus or a search indexer) can't be removed for a while.
+const REMOVE_ATTEMPTS: u32 = 10;
+
+static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// Way of copying files from the pristine copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyMethod {
+    /// Copy-on-write clone, not tried yet
+    Probe,
+    /// Copy-on-write clone, known to work
+    Clone,
+    /// Plain copy
+    Copy,
+}
+
+/// Try to clone a file, returning `false` if cloning is not supported
+#[cfg(target_os = "linux")]
+fn clone_file(src: &Path, dst: &Path) -> io::Result<bool> {
+    use std::os::fd::AsRawFd;
+
+    let src = fs::File::open(src)?;
+    let dst_file = fs::File::create_new(dst)?;
+    // SAFETY: both descriptors are valid for the duration of the call
+    if unsafe { libc::ioctl(dst_file.as_raw_fd(), libc::FICLONE, src.as_raw_fd()) } == 0 {
+        return Ok(true);
+    }
+    let err = io::Error::last_os_error();
+    drop(dst_file);
+    fs::remove_file(dst)?;
+    match err.raw_os_error() {
+        Some(libc::EOPNOTSUPP | libc::EXDEV | libc::EINVAL | libc::ENOTTY | libc::ENOSYS) => {
+            Ok(false)
+        }
+        _ => Err(err),
+    }
+}
+
+/// Try to clone a file, returning `false` if cloning is not supported
+#[cfg(target_vendor = "apple")]
+fn clone_file(src: &Path, dst: &Path) -> io::Result<bool> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let src = CString::new(src.as_os_str().as_bytes())?;
+    let dst = CString::new(dst.as_os_str().as_bytes())?;
+    // SAFETY: both paths are valid NUL terminated strings
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } == 0 {
+        return Ok(true);
+    }
+    let err = io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::ENOTSUP | libc::EXDEV) => Ok(false),
+        _ => Err(err),
+    }
+}
+
+/// Try to clone a file, returning `false` if cloning is not supported
+#[cfg(not(any(target_os = "linux", target_vendor = "apple")))]
+fn clone_file(_src: &Path, _dst: &Path) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Copy a directory tree, cloning files if possible
+fn copy_tree(src: &Path, dst: &Path, method: &mut CopyMethod) -> io::Result<()> {
+    fs::create_dir(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let (src, dst) = (entry.path(), dst.join(entry.file_name()));
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_tree(&src, &dst, method)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&src, &dst)?;
+        } else {
+            if *method != CopyMethod::Copy {
+                let cloned = clone_file(&src, &dst)?;
+                *method = if cloned {
+                    CopyMethod::Clone
+                } else {
+                    CopyMethod::Copy
+                };
+            }
+            if *method == CopyMethod::Copy {
+                fs::copy(&src, &dst)?;
+            } else {
+                fs::set_permissions(&dst, entry.metadata()?.permissions())?;
+            }
+        }
+    }
+    // permissions are restored last, so read-only directories may be filled
+    fs::set_permissions(dst, fs::metadata(src)?.permissions())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+
+#[cfg(windows)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    let target = fs::read_link(src)?;
+    if fs::metadata(src).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, dst)
+    } else {
+        std::os::windows::fs::symlink_file(target, dst)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::copy(src, dst).map(|_| ())
+}
+
+/// Make a directory tree writable, so it can be removed
+///
+/// Read-only files can't be removed on Windows, and entries of
+/// read-only directories can't be removed anywhere.
+#[expect(clippy::permissions_set_readonly_false)]
+fn make_writable(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+    let mut permissions = metadata.permissions();
+    if permissions.readonly() {
+        permissions.set_readonly(false);
+        fs::set_permissions(path, permissions)?;
+    }
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            make_writable(&entry?.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove a directory tree, including read-only entries, retrying on transient failures
+fn remove_tree(path: &Path) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        let res = match make_writable(path) {
+            Ok(()) => fs::remove_dir_all(path),
+            Err(err) => Err(err),
+        };
+        match res {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(_) if attempt < REMOVE_ATTEMPTS => {
+                thread::sleep(Duration::from_millis(10 << attempt.min(5)));
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Time spent on resetting a fixture tree during exploration
+///
+/// See [`Report::fixture_timing()`](crate::Report::fixture_timing).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct FixtureTiming {
+    /// Number of resets
+    pub resets: usize,
+
+    /// Total time spent on resets
+    pub reset_duration: Duration,
+
+    /// Total time of exploration, including resets
+    pub total_duration: Duration,
+}
+
+/// Directory tree fixture, cheaply reset to pristine state
+///
+/// [`prepare()`](Self::prepare) copies a source tree once, and
+/// [`fresh()`](Self::fresh) produces a pristine working copy of it,
+/// removing the previous one, including read-only files. Files are
+/// cloned with copy-on-write (reflinks on Linux, `clonefile` on macOS)
+/// where the filesystem supports it, and copied otherwise. Temporary
+/// data is removed when the fixture is dropped.
+///
+/// See also [`Runner::with_fixture_tree()`](crate::Runner::with_fixture_tree),
+/// which resets the fixture before each iteration.
+///
+/// ```
+/// use faine::fs::FixtureTree;
+///
+/// let source = tempfile::tempdir().unwrap();
+/// std::fs::write(source.path().join("config"), "pristine").unwrap();
+///
+/// let mut fixture = FixtureTree::prepare(source.path()).unwrap();
+/// let dir = fixture.fresh().unwrap();
+/// std::fs::write(dir.join("config"), "modified").unwrap();
+/// let dir = fixture.fresh().unwrap();
+/// assert_eq!(std::fs::read_to_string(dir.join("config")).unwrap(), "pristine");
+/// ```
+#[derive(Debug)]
+pub struct FixtureTree {
+    root: PathBuf,
+    pristine: PathBuf,
+    working: PathBuf,
+    method: CopyMethod,
+    num_resets: usize,
+    reset_duration: Duration,
+}
+
+impl FixtureTree {
+    /// Copy a directory tree into a temporary location
+    pub fn prepare(src: impl AsRef<Path>) -> io::Result<Self> {
+        let name = format!(
+            "faine-fixture-{}-{}",
+            std::process::id(),
+            NEXT_INDEX.fetch_add(1, Ordering::Relaxed)
+        );
+        let root = std::env::temp_dir().join(name);
+        // a leftover of a crashed run with the same process id
+        remove_tree(&root)?;
+        fs::create_dir(&root)?;
+        // on Windows, this produces verbatim paths which are not limited in length
+        let root = fs::canonicalize(&root)?;
+        let mut fixture = Self {
+            pristine: root.join("pristine"),
+            working: root.join("working"),
+            root,
+            method: CopyMethod::Probe,
+            num_resets: 0,
+            reset_duration: Duration::ZERO,
+        };
+        copy_tree(src.as_ref(), &fixture.pristine, &mut fixture.method)?;
+        Ok(fixture)
+    }
+
+    /// Replace the working copy with a pristine one, returning its path
+    ///
+    /// The path is the same for all working copies.
+    pub fn fresh(&mut self) -> io::Result<&Path> {
+        let start = Instant::now();
+        remove_tree(&self.working)?;
+        copy_tree(&self.pristine, &self.working, &mut self.method)?;
+        self.num_resets += 1;
+        self.reset_duration += start.elapsed();
+        Ok(&self.working)
+    }
+
+    /// Path of the working copy
+    pub fn path(&self) -> &Path {
+        &self.working
+    }
+
+    /// Whether files are cloned with copy-on-write
+    ///
+    /// `false` until the first file is copied.
+    pub fn is_copy_on_write(&self) -> bool {
+        self.method == CopyMethod::Clone
+    }
+
+    /// Number of [`fresh()`](Self::fresh) calls
+    pub fn num_resets(&self) -> usize {
+        self.num_resets
+    }
+
+    /// Total time spent in [`fresh()`](Self::fresh)
+    pub fn reset_duration(&self) -> Duration {
+        self.reset_duration
+    }
+}
+
+impl Drop for FixtureTree {
+    fn drop(&mut self) {
+        if let Err(err) = remove_tree(&self.root) {
+            eprintln!(
+                "faine: warning: cannot remove fixture directory {}: {err}",
+                self.root.display()
+            );
+        }
+    }
+}
+
+/// Path of the working copy of the fixture managed by the runner
+///
+/// See [`Runner::with_fixture_tree()`](crate::Runner::with_fixture_tree).
+///
+/// # Panics
+///
+/// If called outside of [`Runner::run()`](crate::Runner::run), or if
+/// the runner does not manage a fixture.
+pub fn fixture_dir() -> PathBuf {
+    FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .and_then(|state| state.fixture.as_ref())
+            .map(|fixture| fixture.path().to_path_buf())
+            .expect("fixture_dir() must be called under a runner with a fixture tree")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh() {
+        let source = tempfile::tempdir().unwrap();
+        fs::create_dir(source.path().join("sub")).unwrap();
+        fs::write(source.path().join("sub/file"), "data").unwrap();
+        let mut permissions = fs::metadata(source.path().join("sub/file"))
+            .unwrap()
+            .permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(source.path().join("sub/file"), permissions).unwrap();
+
+        let mut fixture = FixtureTree::prepare(source.path()).unwrap();
+        let root = fixture.root.clone();
+        for _ in 0..2 {
+            let dir = fixture.fresh().unwrap().to_path_buf();
+            assert_eq!(fs::read_to_string(dir.join("sub/file")).unwrap(), "data");
+            assert!(
+                fs::metadata(dir.join("sub/file"))
+                    .unwrap()
+                    .permissions()
+                    .readonly()
+            );
+            assert!(!dir.join("extra").exists());
+            fs::write(dir.join("extra"), "").unwrap();
+        }
+        assert_eq!(fixture.num_resets(), 2);
+        drop(fixture);
+        assert!(!root.exists());
+    }
+}
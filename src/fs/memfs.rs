@@ -0,0 +1,514 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use super::durability::{DurabilityTracker, injected, moved};
+use super::{
+    DurabilityModel, FaineMetadata, FileKind, FsBackend, FsFile, METADATA, ModelFs, REMOVE_FILE,
+    RENAME, SYNC, WRITE, perturbed,
+};
+
+#[derive(Debug)]
+struct Tree {
+    files: DurabilityTracker,
+    dirs: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl Tree {
+    fn new(model: DurabilityModel) -> Self {
+        Self {
+            files: DurabilityTracker::new(model),
+            dirs: BTreeMap::from([(normalize(Path::new("/")), SystemTime::now())]),
+        }
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.contains_key(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.file(path).is_some()
+    }
+
+    /// Check that a file may be created or replaced at a path
+    fn check_new_file(&self, path: &Path) -> io::Result<()> {
+        if self.is_dir(path) {
+            return Err(error(io::ErrorKind::IsADirectory, path));
+        }
+        match path.parent() {
+            Some(parent) if self.is_dir(parent) => Ok(()),
+            Some(parent) if self.is_file(parent) => {
+                Err(error(io::ErrorKind::NotADirectory, parent))
+            }
+            _ => Err(error(io::ErrorKind::NotFound, path)),
+        }
+    }
+
+    /// Check that a directory may be renamed from one path to another
+    fn check_rename_dir(&self, from: &Path, to: &Path) -> io::Result<()> {
+        if to.starts_with(from) {
+            return Err(error(io::ErrorKind::InvalidInput, to));
+        }
+        if self.is_file(to) {
+            return Err(error(io::ErrorKind::NotADirectory, to));
+        }
+        if self.is_dir(to) {
+            // only an empty directory may be replaced
+            return if self.children(to).next().is_some() {
+                Err(error(io::ErrorKind::DirectoryNotEmpty, to))
+            } else {
+                Ok(())
+            };
+        }
+        self.check_new_file(to)
+    }
+
+    /// Paths of files and directories directly in a directory
+    fn children<'a>(&'a self, dir: &'a Path) -> impl Iterator<Item = &'a Path> {
+        self.dirs
+            .keys()
+            .map(PathBuf::as_path)
+            .chain(self.files.paths())
+            .filter(move |child| child.parent() == Some(dir))
+    }
+
+    /// Check that a file exists at a path
+    fn check_file(&self, path: &Path) -> io::Result<()> {
+        if self.is_file(path) {
+            Ok(())
+        } else if self.is_dir(path) {
+            Err(error(io::ErrorKind::IsADirectory, path))
+        } else {
+            Err(error(io::ErrorKind::NotFound, path))
+        }
+    }
+}
+
+/// Lexically resolve a path against the root, as there's no current directory
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from(std::path::MAIN_SEPARATOR_STR);
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+    normalized
+}
+
+fn error(kind: io::ErrorKind, path: &Path) -> io::Error {
+    io::Error::new(kind, format!("{kind}: {}", path.display()))
+}
+
+/// In-memory filesystem
+///
+/// Implements [`FsBackend`] with the same failpoints as the wrappers
+/// of this module, so the code written against the trait may be
+/// explored without touching the disk, which is faster and not
+/// subject to disk flakiness. Paths are resolved lexically against
+/// the root, which is the only directory initially present.
+///
+/// Files are kept in the same structure which models durability of
+/// the real filesystem, so [`surviving_state()`](Self::surviving_state)
+/// is always available, and [`crash()`](Self::crash) may reduce the
+/// tree to it, for testing recovery code. Clones share the tree;
+/// create a new one in each iteration to start from scratch.
+///
+/// ```
+/// use std::path::Path;
+/// use faine::fs::{FsBackend, MemFs};
+///
+/// let fs = MemFs::new();
+/// fs.insert("/db/data", "old");
+/// fs.write(Path::new("/db/data.tmp"), b"new").unwrap();
+/// fs.rename(Path::new("/db/data.tmp"), Path::new("/db/data")).unwrap();
+/// assert_eq!(fs.read_to_string(Path::new("/db/data")).unwrap(), "new");
+/// // rename is not durable until the directory is synced
+/// fs.crash();
+/// assert_eq!(fs.read_to_string(Path::new("/db/data")).unwrap(), "old");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemFs {
+    tree: Arc<Mutex<Tree>>,
+}
+
+impl Default for MemFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemFs {
+    /// Construct an empty filesystem with [`DurabilityModel::Posix`]
+    pub fn new() -> Self {
+        Self::with_durability_model(DurabilityModel::default())
+    }
+
+    /// Construct an empty filesystem with a given durability model
+    pub fn with_durability_model(model: DurabilityModel) -> Self {
+        Self {
+            tree: Arc::new(Mutex::new(Tree::new(model))),
+        }
+    }
+
+    fn tree(&self) -> MutexGuard<'_, Tree> {
+        self.tree.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Create a durable file along with its parent directories
+    ///
+    /// Meant for preparing the initial state, so does not visit any
+    /// failpoints.
+    ///
+    /// # Panics
+    ///
+    /// If a file is in place of the file's directory, or a directory
+    /// is in place of the file.
+    pub fn insert(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) {
+        let path = normalize(path.as_ref());
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)
+                .expect("file's directory must be creatable");
+        }
+        let mut tree = self.tree();
+        tree.check_new_file(&path).expect("file must be creatable");
+        tree.files.write_durable(&path, contents.as_ref());
+    }
+
+    /// Files which would survive a crash at this point
+    ///
+    /// See [`surviving_state()`](super::surviving_state).
+    pub fn surviving_state(&self) -> ModelFs {
+        self.tree().files.surviving_state()
+    }
+
+    /// Simulate a crash, leaving only files which survive it
+    ///
+    /// Directories are not removed, and missing directories of the
+    /// surviving files (such as ones with a rename which was not
+    /// synced) are recreated.
+    pub fn crash(&self) {
+        let mut tree = self.tree();
+        tree.files.crash();
+        let dirs: Vec<PathBuf> = tree
+            .files
+            .paths()
+            .filter_map(Path::parent)
+            .flat_map(Path::ancestors)
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in dirs {
+            tree.dirs.entry(dir).or_insert_with(SystemTime::now);
+        }
+    }
+}
+
+/// File of [`MemFs`] opened for writing, see [`FsBackend::create()`]
+#[derive(Debug)]
+pub struct MemFile {
+    fs: MemFs,
+    inode: usize,
+}
+
+impl io::Write for MemFile {
+    #[track_caller]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    #[track_caller]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        if let Some(err) = injected(WRITE) {
+            return Err(err);
+        }
+        let mut tree = self.fs.tree();
+        tree.files.append(self.inode, buf);
+        tree.files.settle();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl FsFile for MemFile {
+    #[track_caller]
+    fn sync_all(&self) -> io::Result<()> {
+        if let Some(err) = injected(SYNC) {
+            return Err(err);
+        }
+        self.fs.tree().files.sync_inode(self.inode);
+        Ok(())
+    }
+}
+
+impl FsBackend for MemFs {
+    type File = MemFile;
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut tree = self.tree();
+        for ancestor in path.ancestors().collect::<Vec<_>>().into_iter().rev() {
+            if tree.is_file(ancestor) {
+                return Err(error(io::ErrorKind::NotADirectory, ancestor));
+            }
+            tree.dirs
+                .entry(ancestor.to_path_buf())
+                .or_insert_with(SystemTime::now);
+        }
+        Ok(())
+    }
+
+    #[track_caller]
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        if let Some(err) = injected(WRITE) {
+            return Err(err);
+        }
+        let path = normalize(path);
+        let mut tree = self.tree();
+        tree.check_new_file(&path)?;
+        tree.files.write(&path, contents);
+        tree.files.settle();
+        Ok(())
+    }
+
+    #[track_caller]
+    fn create(&self, path: &Path) -> io::Result<Self::File> {
+        if let Some(err) = injected(WRITE) {
+            return Err(err);
+        }
+        let path = normalize(path);
+        let mut tree = self.tree();
+        tree.check_new_file(&path)?;
+        let inode = tree.files.create(&path);
+        tree.files.settle();
+        Ok(MemFile {
+            fs: self.clone(),
+            inode,
+        })
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+        let tree = self.tree();
+        tree.check_file(&path)?;
+        Ok(tree
+            .files
+            .file(&path)
+            .map(|(content, _)| content.to_vec())
+            .unwrap_or_default())
+    }
+
+    #[track_caller]
+    fn sync_all(&self, path: &Path) -> io::Result<()> {
+        if let Some(err) = injected(SYNC) {
+            return Err(err);
+        }
+        let path = normalize(path);
+        let mut tree = self.tree();
+        if tree.is_dir(&path) {
+            tree.files.sync_dir(&path);
+        } else {
+            tree.check_file(&path)?;
+            tree.files.sync_file(&path);
+        }
+        Ok(())
+    }
+
+    #[track_caller]
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        if let Some(err) = injected(RENAME) {
+            return Err(err);
+        }
+        let (from, to) = (normalize(from), normalize(to));
+        let mut tree = self.tree();
+        if tree.is_dir(&from) {
+            if from == to {
+                return Ok(());
+            }
+            tree.check_rename_dir(&from, &to)?;
+            let dirs: Vec<PathBuf> = tree
+                .dirs
+                .range(from.clone()..)
+                .map(|(dir, _)| dir)
+                .take_while(|dir| dir.starts_with(&from))
+                .cloned()
+                .collect();
+            for dir in dirs {
+                let modified = tree.dirs.remove(&dir).unwrap_or_else(SystemTime::now);
+                tree.dirs.insert(moved(&dir, &from, &to), modified);
+            }
+            tree.files.rename_dir(&from, &to);
+            tree.files.settle();
+            return Ok(());
+        }
+        tree.check_file(&from)?;
+        tree.check_new_file(&to)?;
+        tree.files.rename(&from, &to);
+        tree.files.settle();
+        Ok(())
+    }
+
+    #[track_caller]
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        if let Some(err) = injected(REMOVE_FILE) {
+            return Err(err);
+        }
+        let path = normalize(path);
+        let mut tree = self.tree();
+        tree.check_file(&path)?;
+        tree.files.remove_file(&path);
+        tree.files.settle();
+        Ok(())
+    }
+
+    #[track_caller]
+    fn metadata(&self, path: &Path) -> io::Result<FaineMetadata> {
+        let path = normalize(path);
+        let metadata = {
+            let tree = self.tree();
+            if let Some(&modified) = tree.dirs.get(&path) {
+                FaineMetadata::in_memory(FileKind::Dir, 0, modified)
+            } else if let Some((content, modified)) = tree.files.file(&path) {
+                FaineMetadata::in_memory(FileKind::File, content.len() as u64, modified)
+            } else {
+                return Err(error(io::ErrorKind::NotFound, &path));
+            }
+        };
+        perturbed(METADATA, metadata)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let path = normalize(path);
+        let tree = self.tree();
+        if !tree.is_dir(&path) {
+            return Err(error(
+                if tree.is_file(&path) {
+                    io::ErrorKind::NotADirectory
+                } else {
+                    io::ErrorKind::NotFound
+                },
+                &path,
+            ));
+        }
+        let mut paths: Vec<PathBuf> = tree.children(&path).map(Path::to_path_buf).collect();
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        let fs = MemFs::new();
+        fs.insert("/a/b/file", "data");
+        assert_eq!(fs.read(Path::new("/a/./c/../b/file")).unwrap(), b"data");
+        assert_eq!(
+            fs.read_dir(Path::new("/a")).unwrap(),
+            [normalize(Path::new("/a/b"))]
+        );
+        assert!(fs.metadata(Path::new("/a/b")).unwrap().is_dir());
+        assert_eq!(fs.metadata(Path::new("/a/b/file")).unwrap().len(), 4);
+
+        fn kind<T: std::fmt::Debug>(res: io::Result<T>) -> io::ErrorKind {
+            res.unwrap_err().kind()
+        }
+        assert_eq!(
+            kind(fs.read(Path::new("/a/missing"))),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            kind(fs.write(Path::new("/a/missing/file"), b"")),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            kind(fs.write(Path::new("/a/b/file/nested"), b"")),
+            io::ErrorKind::NotADirectory
+        );
+        assert_eq!(
+            kind(fs.write(Path::new("/a/b"), b"")),
+            io::ErrorKind::IsADirectory
+        );
+        assert_eq!(
+            kind(fs.rename(Path::new("/a/b/file"), Path::new("/a"))),
+            io::ErrorKind::IsADirectory
+        );
+
+        fs.remove_file(Path::new("/a/b/file")).unwrap();
+        assert!(fs.read_dir(Path::new("/a/b")).unwrap().is_empty());
+        // removal was not synced
+        fs.crash();
+        assert_eq!(fs.read(Path::new("/a/b/file")).unwrap(), b"data");
+    }
+
+    #[test]
+    fn test_create() {
+        use std::io::Write;
+
+        let fs = MemFs::new();
+        fs.insert("/d/file", "old");
+        let mut file = fs.create(Path::new("/d/file")).unwrap();
+        assert_eq!(fs.read(Path::new("/d/file")).unwrap(), b"");
+        file.write_all(b"new").unwrap();
+        file.sync_all().unwrap();
+        write!(file, " data").unwrap();
+        // writes go to the renamed file
+        fs.rename(Path::new("/d/file"), Path::new("/d/renamed"))
+            .unwrap();
+        file.write_all(b"!").unwrap();
+        assert_eq!(fs.read(Path::new("/d/renamed")).unwrap(), b"new data!");
+        fs.crash();
+        assert_eq!(fs.read(Path::new("/d/file")).unwrap(), b"new");
+    }
+
+    #[test]
+    fn test_rename_dir() {
+        let fs = MemFs::new();
+        fs.insert("/old/sub/file", "data");
+        fs.create_dir_all(Path::new("/empty")).unwrap();
+        fs.insert("/full/file", "");
+
+        fn kind(res: io::Result<()>) -> io::ErrorKind {
+            res.unwrap_err().kind()
+        }
+        assert_eq!(
+            kind(fs.rename(Path::new("/old"), Path::new("/old/sub/new"))),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            kind(fs.rename(Path::new("/old"), Path::new("/full"))),
+            io::ErrorKind::DirectoryNotEmpty
+        );
+        assert_eq!(
+            kind(fs.rename(Path::new("/old"), Path::new("/full/file"))),
+            io::ErrorKind::NotADirectory
+        );
+
+        fs.rename(Path::new("/old"), Path::new("/empty")).unwrap();
+        assert!(fs.metadata(Path::new("/old")).is_err());
+        assert!(fs.metadata(Path::new("/empty/sub")).unwrap().is_dir());
+        assert_eq!(fs.read(Path::new("/empty/sub/file")).unwrap(), b"data");
+        // rename was not synced
+        fs.crash();
+        assert_eq!(fs.read(Path::new("/old/sub/file")).unwrap(), b"data");
+
+        fs.rename(Path::new("/old"), Path::new("/new")).unwrap();
+        fs.sync_all(Path::new("/")).unwrap();
+        fs.crash();
+        assert_eq!(fs.read(Path::new("/new/sub/file")).unwrap(), b"data");
+        assert!(fs.metadata(Path::new("/old")).is_err());
+    }
+}
@@ -1,7 +1,35 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use std::any::Any;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
 use crate::__private::FAILPOINTS;
+use crate::common::{Branch, Exhaust, Phase};
+use crate::fnv::FnvHasher;
+
+/// Type erased accumulator, see [`accumulate()`]
+pub type Accumulator = Box<dyn Any + Send + Sync>;
+
+/// Constructor of initial accumulator registered with [`Runner::with_accumulator()`](crate::Runner::with_accumulator)
+pub type AccumulatorFactory = Arc<dyn Fn() -> Accumulator + Send + Sync>;
+
+/// Clone of type erased accumulator, used to discard updates made on paths of other shards
+pub type AccumulatorCloner = fn(&Accumulator) -> Accumulator;
+
+/// Clone an accumulator of type `A`
+pub fn clone_accumulator<A: Clone + Send + Sync + 'static>(
+    accumulator: &Accumulator,
+) -> Accumulator {
+    Box::new(
+        accumulator
+            .downcast_ref::<A>()
+            .expect("accumulator must be of declared type")
+            .clone(),
+    )
+}
 
 /// Enable or disable failpoints
 ///
@@ -13,9 +41,14 @@ use crate::__private::FAILPOINTS;
 ///
 /// It can be run from the instrumented code as well, though.
 ///
+/// Failpoints are enabled again at the start of each iteration, so
+/// the setting never leaks into other paths. Paths which end with
+/// failpoints disabled are marked with [`ExecutionPath::left_disabled`].
+///
 /// Like `inject_*` macros, it does nothing outside of [`Runner::run()`].
 ///
 /// [`Runner::run()`]: crate::Runner::run
+/// [`ExecutionPath::left_disabled`]: crate::ExecutionPath::left_disabled
 pub fn enable_failpoints(enable: bool) {
     FAILPOINTS.with_borrow_mut(|state| {
         if let Some(state) = state {
@@ -23,3 +56,321 @@ pub fn enable_failpoints(enable: bool) {
         }
     });
 }
+
+/// Suppress fault injection until the returned guard is dropped
+///
+/// Marks a critical section of the instrumented code, such as one
+/// recording ground truth for later assertions, where injected
+/// faults would violate premises of the test. Failpoints visited in
+/// it are skipped without being explored, and listed in
+/// [`ExecutionPath::suppressed_by_critical_section`]. Unlike
+/// [`enable_failpoints()`], injection is restored on any exit from the
+/// scope, including early returns and panics, and sections nest.
+///
+/// Sections are closed at the start of each iteration, and paths
+/// which end with a section still open, usually because the guard
+/// was leaked, are marked with [`ExecutionPath::critical_section_held`].
+/// With `macros` feature, `#[faine::no_faults]` attribute wraps whole
+/// functions in a section.
+///
+/// Like `inject_*` macros, it does nothing outside of [`Runner::run()`].
+///
+/// ```
+/// use faine::{Runner, inject_return, no_faults};
+///
+/// fn transfer(ledger: &mut Vec<u32>) -> Result<(), ()> {
+///     {
+///         let _cs = no_faults();
+///         inject_return!("append", Err(())); // never activated
+///         ledger.push(1);
+///     }
+///     inject_return!("commit", Err(()));
+///     Ok(())
+/// }
+///
+/// let report = Runner::default()
+///     .run(|| {
+///         let mut ledger = vec![];
+///         let _ = transfer(&mut ledger);
+///         assert_eq!(ledger, [1]);
+///     })
+///     .unwrap();
+/// assert_eq!(report.paths().len(), 2);
+/// ```
+///
+/// [`Runner::run()`]: crate::Runner::run
+/// [`ExecutionPath::suppressed_by_critical_section`]: crate::ExecutionPath::suppressed_by_critical_section
+/// [`ExecutionPath::critical_section_held`]: crate::ExecutionPath::critical_section_held
+#[must_use = "injection is only suppressed while the guard is alive"]
+pub fn no_faults() -> NoFaults {
+    let iteration = FAILPOINTS.with_borrow_mut(|state| {
+        let state = state.as_mut()?;
+        state.critical_sections += 1;
+        state.iteration
+    });
+    NoFaults {
+        iteration,
+        _not_send: PhantomData,
+    }
+}
+
+/// Guard of a critical section, see [`no_faults()`]
+#[derive(Debug)]
+pub struct NoFaults {
+    iteration: Option<usize>,
+    // the section is tracked per thread
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for NoFaults {
+    fn drop(&mut self) {
+        FAILPOINTS.with_borrow_mut(|state| {
+            // sections opened in previous iterations were already closed
+            if let Some(state) = state
+                && self.iteration.is_some()
+                && state.iteration == self.iteration
+            {
+                state.critical_sections = state.critical_sections.saturating_sub(1);
+            }
+        });
+    }
+}
+
+/// Check whether current iteration has exceeded its timeout
+///
+/// As code running under [`Runner::run()`] cannot be interrupted, this
+/// allows cooperative termination of iterations stuck, for instance, in
+/// infinite retry loops. Always returns `false` if timeout was not set
+/// with [`Runner::with_iteration_timeout()`], or outside of [`Runner::run()`].
+///
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_iteration_timeout()`]: crate::Runner::with_iteration_timeout
+pub fn iteration_timed_out() -> bool {
+    FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .and_then(|state| state.watchdog.as_ref())
+            .is_some_and(|watchdog| watchdog.timed_out())
+    })
+}
+
+/// Check whether the code is running under a runner
+///
+/// Returns `true` within [`Runner::run()`] on the thread which runs
+/// the tested code. Along with [`current_iteration()`] and
+/// [`current_phase()`], this is intended for tests and test support
+/// code, for instance to assert that a helper is used under a runner,
+/// or to shrink internal buffers so loops visit fewer failpoints.
+/// Production behavior must never depend on these, as otherwise the
+/// explored code is not the code which runs in production.
+///
+/// [`Runner::run()`]: crate::Runner::run
+pub fn is_active() -> bool {
+    FAILPOINTS.with_borrow(Option::is_some)
+}
+
+/// Index of the current iteration within the run
+///
+/// Iterations are counted from zero, including replays. Returns
+/// `None` outside of [`Runner::run()`]. See [`is_active()`] for
+/// intended use.
+///
+/// [`Runner::run()`]: crate::Runner::run
+pub fn current_iteration() -> Option<usize> {
+    FAILPOINTS.with_borrow(|state| state.as_ref()?.iteration)
+}
+
+/// Kind of the current iteration
+///
+/// Returns `None` outside of [`Runner::run()`]. See [`is_active()`]
+/// for intended use.
+///
+/// [`Runner::run()`]: crate::Runner::run
+pub fn current_phase() -> Option<Phase> {
+    FAILPOINTS.with_borrow(|state| Some(state.as_ref()?.phase))
+}
+
+/// Failpoint decisions made so far in the current iteration
+///
+/// Lists visited failpoints in order of visits, with aliases applied,
+/// along with whether each was activated. This allows model-based
+/// checks, where the expected state is derived from injected faults
+/// instead of being spelled out per scenario:
+///
+/// ```
+/// # use std::io;
+/// use faine::{Branch, Runner, current_path, inject_return_io_error};
+///
+/// fn append(log: &mut Vec<u32>, records: &[u32]) -> io::Result<()> {
+///     for record in records {
+///         inject_return_io_error!("append record");
+///         log.push(*record);
+///     }
+///     Ok(())
+/// }
+///
+/// Runner::ignore_env()
+///     .run(|| {
+///         let mut log = vec![];
+///         let _ = append(&mut log, &[1, 2, 3]);
+///         // if k-th append has failed, exactly k-1 records are written
+///         let written = current_path()
+///             .iter()
+///             .take_while(|(_, branch)| *branch == Branch::Skip)
+///             .count();
+///         assert_eq!(log.len(), written);
+///     })
+///     .unwrap();
+/// ```
+///
+/// Returns an empty list outside of [`Runner::run()`].
+///
+/// [`Runner::run()`]: crate::Runner::run
+pub fn current_path() -> Vec<(String, Branch)> {
+    FAILPOINTS.with_borrow_mut(|state| {
+        let Some(state) = state else {
+            return vec![];
+        };
+        state
+            .tree
+            .current_path()
+            .into_iter()
+            .filter_map(|(label, branch)| state.step(label, branch))
+            .map(|step| (step.failpoint.to_string(), step.branch))
+            .collect()
+    })
+}
+
+/// Names of failpoints activated so far in the current iteration
+///
+/// Same as [`current_path()`] filtered to activations. Returns an
+/// empty list outside of [`Runner::run()`].
+///
+/// [`Runner::run()`]: crate::Runner::run
+pub fn activated_names() -> Vec<String> {
+    current_path()
+        .into_iter()
+        .filter(|(_, branch)| *branch == Branch::Activate)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Clear a condition set by [`inject_exhaust!`]
+///
+/// Models release of an exhausted resource, for instance, disk space
+/// being freed by the tested code. Only conditions set with
+/// [`Exhaust::UntilCleared`] scope are affected.
+///
+/// Like `inject_*` macros, it does nothing outside of [`Runner::run()`].
+///
+/// [`inject_exhaust!`]: crate::inject_exhaust
+/// [`Runner::run()`]: crate::Runner::run
+pub fn clear_condition(condition: &str) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state
+            && state.conditions.get(condition) == Some(&Exhaust::UntilCleared)
+        {
+            state.conditions.remove(condition);
+        }
+    });
+}
+
+/// Output written to stdout so far in the current iteration
+///
+/// Requires output capture to be enabled with
+/// [`Runner::with_capture_output()`], otherwise (and outside of
+/// [`Runner::run()`]) returns an empty string.
+///
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_capture_output()`]: crate::Runner::with_capture_output
+pub fn captured_stdout() -> String {
+    FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .and_then(|state| state.capture.as_ref())
+            .map(|capture| capture.stdout())
+            .unwrap_or_default()
+    })
+}
+
+/// Output written to stderr so far in the current iteration
+///
+/// See [`captured_stdout()`].
+pub fn captured_stderr() -> String {
+    FAILPOINTS.with_borrow(|state| {
+        state
+            .as_ref()
+            .and_then(|state| state.capture.as_ref())
+            .map(|capture| capture.stderr())
+            .unwrap_or_default()
+    })
+}
+
+/// Record an observable outcome of the current iteration
+///
+/// Digest of the outcome is stored in the [`ExecutionPath`], and is
+/// used to compare paths explored in different orders, see
+/// [`Runner::with_order_sensitivity_check()`]. Outcome should describe
+/// what the test observed, for instance a result of the tested function.
+/// If called multiple times per iteration, all outcomes are combined.
+/// For filesystem state, see [`digest::dir_tree()`](crate::digest::dir_tree).
+///
+/// Like `inject_*` macros, it does nothing outside of [`Runner::run()`].
+///
+/// [`ExecutionPath`]: crate::ExecutionPath
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_order_sensitivity_check()`]: crate::Runner::with_order_sensitivity_check
+pub fn record_outcome(outcome: impl Hash) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            let mut hasher = FnvHasher::new();
+            state.outcome.hash(&mut hasher);
+            outcome.hash(&mut hasher);
+            state.outcome = Some(hasher.finish());
+        }
+    });
+}
+
+/// Update the accumulator shared by all iterations
+///
+/// The accumulator is declared with [`Runner::with_accumulator()`],
+/// and is available in the [`Report`] after the exploration via
+/// [`Report::accumulator()`]. Unlike state captured by the tested
+/// closure, which leaks between iterations unnoticed, this makes
+/// collection of data across iterations explicit.
+///
+/// Returns the result of `f`, or `None` outside of [`Runner::run()`]
+/// or when no accumulator was declared. `f` is not called when an
+/// already explored path is replayed (see [`current_phase()`]), so
+/// reruns and self verification do not count paths twice, and
+/// updates made on paths owned by other [shards] are discarded.
+///
+/// # Panics
+///
+/// If the declared accumulator is not of type `A`.
+///
+/// [`Report`]: crate::Report
+/// [`Report::accumulator()`]: crate::Report::accumulator
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_accumulator()`]: crate::Runner::with_accumulator
+/// [shards]: crate::Runner::with_shard
+pub fn accumulate<A: 'static, R>(f: impl FnOnce(&mut A) -> R) -> Option<R> {
+    // taken out of the state, so that `f` may visit failpoints
+    let mut accumulator = FAILPOINTS.with_borrow_mut(|state| {
+        let state = state.as_mut()?;
+        if state.phase == Phase::Replaying {
+            return None;
+        }
+        state.accumulator.take()
+    })?;
+    let downcast = accumulator.downcast_mut::<A>().map(f);
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            state.accumulator = Some(accumulator);
+        }
+    });
+    Some(
+        downcast
+            .unwrap_or_else(|| panic!("accumulator is not of type {}", std::any::type_name::<A>())),
+    )
+}
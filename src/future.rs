@@ -0,0 +1,42 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Future produced by [`inject_override_async!`](crate::inject_override_async)
+///
+/// Either the wrapped future, or, if the failpoint was activated,
+/// a ready value of the same type. The latter makes `.await` on the
+/// macro result infer types the same way as on the wrapped future.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub enum OverrideFuture<F: Future> {
+    /// Wrapped future, when the failpoint is skipped
+    Future(F),
+
+    /// Overriding value, when the failpoint is activated
+    ///
+    /// `None` after the value was returned.
+    Ready(Option<F::Output>),
+}
+
+impl<F: Future> Future for OverrideFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: the wrapped future is never moved out of the enum,
+        // and the enum does not implement Drop or Unpin manually, so
+        // pinning is structural for it; the ready value is not
+        // structurally pinned
+        match unsafe { self.get_unchecked_mut() } {
+            OverrideFuture::Future(future) => unsafe { Pin::new_unchecked(future) }.poll(cx),
+            OverrideFuture::Ready(value) => Poll::Ready(
+                value
+                    .take()
+                    .expect("OverrideFuture polled after completion"),
+            ),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Coverage-guided fuzzing of failpoint decisions
+//!
+//! For spaces of paths too large for exhaustive exploration, a fuzzer
+//! may drive failpoint decisions instead. [`run_from_bytes()`] runs the
+//! tested code once, taking the decision for each visited failpoint
+//! from the next byte of the input (odd bytes activate, even ones and
+//! missing ones skip), so a fuzzer guided by coverage finds
+//! interesting combinations of faults. It is meant to be called from
+//! the body of a `cargo-fuzz` target, where panics of the tested code
+//! signal failures as usual:
+//!
+//! ```ignore
+//! #![no_main]
+//!
+//! libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+//!     faine::fuzz::run_from_bytes(data, || {
+//!         let res = replace_file(&path, "new");
+//!         assert!(res.is_ok() || read_to_string(&path).unwrap() == "old");
+//!     });
+//! });
+//! ```
+//!
+//! A crashing input may then be converted with [`repro_from_bytes()`]
+//! into a deterministic [`Runner`] configuration for a regular test.
+//! See [`fuzz/`](https://github.com/AMDmi3/faine/tree/master/fuzz)
+//! for a complete example.
+
+use crate::report::ExecutionPath;
+use crate::runner::Runner;
+
+fn runner(data: &[u8]) -> Runner {
+    Runner::ignore_env()
+        .with_max_iterations(1)
+        .with_decision_bytes(data)
+}
+
+/// Run the tested code once with failpoint decisions taken from bytes
+///
+/// See [module documentation](self).
+///
+/// # Panics
+///
+/// If the tested code panics, or if the run fails otherwise, for
+/// instance on a failure of a [`checked!`](crate::checked) expectation.
+pub fn run_from_bytes(data: &[u8], func: impl FnMut()) {
+    runner(data).run(func).unwrap();
+}
+
+/// Record the path taken for bytes given to [`run_from_bytes()`]
+///
+/// Runs the tested code once again with the same decisions, catching
+/// panics, and records names of visited failpoints.
+pub fn repro_from_bytes(data: &[u8], func: impl FnMut()) -> FuzzRepro {
+    let report = match runner(data).with_catch_panics(true).run(func).into_result() {
+        Ok(report) => report,
+        Err(err) => err
+            .report()
+            .cloned()
+            .unwrap_or_else(|| panic!("cannot record path: {err}")),
+    };
+    FuzzRepro {
+        path: report
+            .paths()
+            .first()
+            .cloned()
+            .expect("a single path must be explored"),
+    }
+}
+
+/// Path taken for a fuzzer input, see [`repro_from_bytes()`]
+///
+/// Display gives a [`Runner`] configuration which explores the same
+/// path, ready to be pasted into a test. The configuration activates
+/// failpoints by name in order, so if a failpoint was skipped before
+/// being activated on the path, the earlier visit is activated
+/// instead; [`key()`](Self::key) tells the exact path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzRepro {
+    path: ExecutionPath,
+}
+
+impl FuzzRepro {
+    /// The path, as explored by [`Runner`]
+    pub fn path(&self) -> &ExecutionPath {
+        &self.path
+    }
+
+    /// Stable identity of the path, see [`ExecutionPath::key()`]
+    pub fn key(&self) -> String {
+        self.path.key()
+    }
+
+    /// Failpoints activated on the path, in order
+    pub fn activations(&self) -> Vec<&'static str> {
+        self.path.activated_failpoints().collect()
+    }
+}
+
+impl std::fmt::Display for FuzzRepro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self
+            .activations()
+            .iter()
+            .map(|name| format!("{name:?}"))
+            .collect();
+        writeln!(f, "Runner::ignore_env()")?;
+        writeln!(f, "    .with_catch_panics(true)")?;
+        writeln!(f, "    .with_priority_paths(&[&[{}]])", names.join(", "))?;
+        write!(f, "    .with_max_iterations(1)")
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Golden matrices of single fault outcomes
+//!
+//! [`outcome_matrix()`] runs the code in [smoke tier](crate::Runner::smoke),
+//! and records what it returns when each failpoint is activated
+//! alone. The resulting [`OutcomeMatrix`] is a concise contract of
+//! fault behavior which may be committed along with the tests and
+//! reviewed like any other snapshot:
+//!
+//! ```
+//! use faine::{Runner, inject_return};
+//! use faine::golden::{OutcomeMatrix, outcome_matrix};
+//!
+//! fn replace_file() -> Result<(), &'static str> {
+//!     inject_return!("create temp file", Err("cannot create"));
+//!     inject_return!("replace file", Err("cannot replace"));
+//!     Ok(())
+//! }
+//!
+//! let matrix = outcome_matrix(Runner::ignore_env(), replace_file).unwrap();
+//! let recorded: OutcomeMatrix = r#"
+//! create temp file => Err("cannot create")
+//! replace file => Err("cannot replace")
+//! "#.parse().unwrap();
+//! let diff = matrix.diff(&recorded);
+//! assert!(diff.is_empty(), "fault behavior has changed:\n{diff}");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::__private::activated_failpoints;
+use crate::common::Tier;
+use crate::error::Error;
+use crate::runner::Runner;
+
+const SEPARATOR: &str = " => ";
+
+/// Mapping of failpoints to outcomes of code when only they are activated
+///
+/// Outcomes are [`Debug`] representations of values returned by the
+/// code, with newlines escaped, so each mapping fits in a line.
+/// Plain [`Display`](std::fmt::Display) renders one `failpoint => outcome`
+/// line per failpoint, ordered by name, and [`FromStr`](std::str::FromStr)
+/// parses it back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutcomeMatrix {
+    outcomes: BTreeMap<String, String>,
+}
+
+impl OutcomeMatrix {
+    /// Outcomes, keyed by failpoint name
+    pub fn outcomes(&self) -> &BTreeMap<String, String> {
+        &self.outcomes
+    }
+
+    /// Outcome of activating the given failpoint alone
+    pub fn get(&self, failpoint: &str) -> Option<&str> {
+        self.outcomes.get(failpoint).map(String::as_str)
+    }
+
+    /// Compare with a previously recorded matrix
+    pub fn diff(&self, recorded: &OutcomeMatrix) -> OutcomeDiff {
+        let mut changes = vec![];
+        let mut current = self.outcomes.iter().peekable();
+        let mut recorded = recorded.outcomes.iter().peekable();
+        loop {
+            let change = match (current.peek(), recorded.peek()) {
+                (Some((name, _)), Some((recorded_name, _))) if name < recorded_name => {
+                    let (name, outcome) = current.next().unwrap();
+                    OutcomeChange::Added {
+                        failpoint: name.clone(),
+                        outcome: outcome.clone(),
+                    }
+                }
+                (Some((name, _)), Some((recorded_name, _))) if name > recorded_name => {
+                    let (name, outcome) = recorded.next().unwrap();
+                    OutcomeChange::Removed {
+                        failpoint: name.clone(),
+                        outcome: outcome.clone(),
+                    }
+                }
+                (Some(_), Some(_)) => {
+                    let (name, outcome) = current.next().unwrap();
+                    let (_, recorded_outcome) = recorded.next().unwrap();
+                    if outcome == recorded_outcome {
+                        continue;
+                    }
+                    OutcomeChange::Changed {
+                        failpoint: name.clone(),
+                        recorded: recorded_outcome.clone(),
+                        current: outcome.clone(),
+                    }
+                }
+                (Some(_), None) => {
+                    let (name, outcome) = current.next().unwrap();
+                    OutcomeChange::Added {
+                        failpoint: name.clone(),
+                        outcome: outcome.clone(),
+                    }
+                }
+                (None, Some(_)) => {
+                    let (name, outcome) = recorded.next().unwrap();
+                    OutcomeChange::Removed {
+                        failpoint: name.clone(),
+                        outcome: outcome.clone(),
+                    }
+                }
+                (None, None) => break,
+            };
+            changes.push(change);
+        }
+        OutcomeDiff { changes }
+    }
+}
+
+impl std::fmt::Display for OutcomeMatrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (failpoint, outcome) in &self.outcomes {
+            writeln!(f, "{failpoint}{SEPARATOR}{outcome}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for OutcomeMatrix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut outcomes = BTreeMap::new();
+        for (index, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (failpoint, outcome) = line
+                .split_once(SEPARATOR)
+                .ok_or_else(|| format!("line {}: expected `failpoint => outcome`", index + 1))?;
+            if outcomes
+                .insert(failpoint.to_string(), outcome.to_string())
+                .is_some()
+            {
+                return Err(format!(
+                    "line {}: duplicate failpoint {failpoint}",
+                    index + 1
+                ));
+            }
+        }
+        Ok(Self { outcomes })
+    }
+}
+
+/// Single difference between outcome matrices
+///
+/// See [`OutcomeMatrix::diff()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutcomeChange {
+    /// Failpoint which is not present in the recorded matrix
+    Added { failpoint: String, outcome: String },
+
+    /// Failpoint which is no longer activated alone
+    Removed { failpoint: String, outcome: String },
+
+    /// Failpoint which leads to a different outcome
+    Changed {
+        failpoint: String,
+        recorded: String,
+        current: String,
+    },
+}
+
+impl std::fmt::Display for OutcomeChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { failpoint, outcome } => write!(f, "+ {failpoint}{SEPARATOR}{outcome}"),
+            Self::Removed { failpoint, outcome } => write!(f, "- {failpoint}{SEPARATOR}{outcome}"),
+            Self::Changed {
+                failpoint,
+                recorded,
+                current,
+            } => write!(
+                f,
+                "- {failpoint}{SEPARATOR}{recorded}\n+ {failpoint}{SEPARATOR}{current}"
+            ),
+        }
+    }
+}
+
+/// Differences between outcome matrices, ordered by failpoint name
+///
+/// See [`OutcomeMatrix::diff()`]. Plain [`Display`](std::fmt::Display)
+/// renders changes in unified diff style.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutcomeDiff {
+    changes: Vec<OutcomeChange>,
+}
+
+impl OutcomeDiff {
+    /// Whether matrices are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Changed mappings
+    pub fn changes(&self) -> &[OutcomeChange] {
+        &self.changes
+    }
+}
+
+impl std::fmt::Display for OutcomeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Record outcomes of the code for each failpoint activated alone
+///
+/// The runner is switched to [smoke tier](Runner::smoke), otherwise
+/// its settings are retained. Failpoints which are only reachable
+/// when another one is activated are never activated alone, and so
+/// are not included in the matrix. If a failpoint is visited more
+/// than once, its first activation defines the outcome.
+pub fn outcome_matrix<T: Debug>(
+    runner: Runner,
+    mut func: impl FnMut() -> T,
+) -> Result<OutcomeMatrix, Error> {
+    let mut outcomes = BTreeMap::new();
+    runner
+        .with_tier(Tier::Smoke)
+        .run(|| {
+            let outcome = func();
+            if let Some(activated) = activated_failpoints()
+                && let [failpoint] = activated[..]
+            {
+                outcomes
+                    .entry(failpoint.to_string())
+                    .or_insert_with(|| format!("{outcome:?}").replace('\n', "\\n"));
+            }
+        })
+        .into_result()?;
+    Ok(OutcomeMatrix { outcomes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let recorded: OutcomeMatrix = "a => 1\nb => 2\nc => 3\n".parse().unwrap();
+        let current: OutcomeMatrix = "b => 2\nc => 4\nd => 5\n".parse().unwrap();
+        assert_eq!(current.to_string(), "b => 2\nc => 4\nd => 5\n");
+
+        let diff = current.diff(&recorded);
+        assert_eq!(diff.to_string(), "- a => 1\n- c => 3\n+ c => 4\n+ d => 5\n");
+        assert_eq!(diff.changes().len(), 3);
+        assert!(current.diff(&current).is_empty());
+
+        assert!("a => 1\na => 2".parse::<OutcomeMatrix>().is_err());
+        assert!("a = 1".parse::<OutcomeMatrix>().is_err());
+    }
+}
@@ -0,0 +1,240 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::common::Branch;
+use crate::options::Options;
+use crate::report::{ExecutionPath, Observation, Report};
+use crate::runner::explore;
+use crate::safety::SafetyValve;
+
+/// Per-failpoint activation limits found by adaptive discovery
+///
+/// See [`Runner::with_adaptive_hit_limits()`](crate::Runner::with_adaptive_hit_limits).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HitLimits {
+    /// Limit applied to failpoints not listed in [`limits`](Self::limits)
+    pub initial: usize,
+
+    /// Effective limits of failpoints which have reached the initial limit
+    pub limits: BTreeMap<&'static str, usize>,
+
+    /// Whether raising any limit further would not change observed outcomes
+    ///
+    /// `false` if some failpoint has reached the maximal limit while
+    /// its raises still changed outcomes.
+    pub fixed_point: bool,
+
+    /// Number of explorations performed to find the limits
+    pub rounds: usize,
+}
+
+impl HitLimits {
+    /// Effective limit of a given failpoint
+    pub fn limit(&self, failpoint: &str) -> usize {
+        self.limits.get(failpoint).copied().unwrap_or(self.initial)
+    }
+}
+
+impl std::fmt::Display for HitLimits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let limits: Vec<String> = self
+            .limits
+            .iter()
+            .map(|(name, limit)| format!("{name}={limit}"))
+            .collect();
+        write!(f, "hit limits: {}", limits.join(", "))?;
+        if self.limits.is_empty() {
+            write!(f, "none reached")?;
+        }
+        let state = if self.fixed_point {
+            "fixed point reached"
+        } else {
+            "maximum reached"
+        };
+        write!(f, " ({state} after {} round(s))", self.rounds)
+    }
+}
+
+fn num_activations(path: &ExecutionPath, failpoint: &str) -> usize {
+    path.steps
+        .iter()
+        .filter(|step| step.branch == Branch::Activate && step.failpoint == failpoint)
+        .count()
+}
+
+/// Failpoints which have reached their activation limits on some path
+fn limited_failpoints(report: &Report) -> BTreeSet<&'static str> {
+    report
+        .safety_valve_triggered()
+        .into_iter()
+        .filter_map(|valve| match valve {
+            SafetyValve::Activations(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Iteration and duration budget shared by all explorations
+struct Budget {
+    max_iterations: Option<usize>,
+    max_duration: Option<Duration>,
+    start: Instant,
+    used_iterations: usize,
+}
+
+impl Budget {
+    /// Apply a share of the remaining budget to options
+    fn apply(&self, options: &mut Options, share: f64) {
+        options.max_iterations = self
+            .max_iterations
+            .map(|max| ((max.saturating_sub(self.used_iterations) as f64 * share) as usize).max(1));
+        options.max_duration = self
+            .max_duration
+            .map(|max| max.saturating_sub(self.start.elapsed()).mul_f64(share));
+    }
+}
+
+/// Options of an exploration used for discovery of limits only
+///
+/// Such explorations must not record or report anything: their
+/// subtrees would be recorded into the dedup store with smaller
+/// limits, and skipped by the following ones.
+fn discovery_options(options: &Options) -> Options {
+    let mut options = options.clone();
+    options.failure_reruns = 0;
+    #[cfg(feature = "log")]
+    {
+        options.failure_logging = None;
+    }
+    options.self_verification = None;
+    options.dedup_store = None;
+    options.coverage_dir = None;
+    options.trace_dir = None;
+    options.timeline_interval = None;
+    options.diminishing_returns = None;
+    options.event_sinks.clear();
+    options
+}
+
+/// Whether a report of a discovery exploration differs from one
+/// performed with given options
+fn is_discovery_different(options: &Options) -> bool {
+    #[cfg(feature = "log")]
+    if options.failure_logging.is_some() {
+        return true;
+    }
+    options.failure_reruns > 0
+        || options.self_verification.is_some()
+        || options.dedup_store.is_some()
+        || options.trace_dir.is_some()
+        || options.timeline_interval.is_some()
+        || options.diminishing_returns.is_some()
+        || !options.event_sinks.is_empty()
+}
+
+/// Explore repeatedly, raising activation limits of failpoints while
+/// that changes observed outcomes
+///
+/// Limits are never raised above [`SafetyLimits::max_activations`](crate::SafetyLimits::max_activations).
+/// The returned report is of an exploration with the found limits.
+pub fn explore_adaptive(
+    options: Options,
+    func: &mut impl FnMut(),
+    initial: usize,
+    max: usize,
+) -> Report {
+    let max = max.min(options.safety_limits.max_activations);
+    let initial = initial.min(max);
+    let mut budget = Budget {
+        max_iterations: options.max_iterations,
+        max_duration: options.max_duration,
+        start: Instant::now(),
+        used_iterations: 0,
+    };
+    let mut round_options = discovery_options(&options);
+    round_options.safety_limits.max_activations = initial;
+    let mut hit_limits = HitLimits {
+        initial,
+        fixed_point: true,
+        rounds: 1,
+        ..Default::default()
+    };
+    budget.apply(&mut round_options, 0.5);
+    let mut report = explore(round_options.clone(), func);
+    budget.used_iterations += report.num_paths();
+    // limits the report was explored with
+    let mut report_limits: HashMap<&'static str, usize> = Default::default();
+    // failpoints whose last raise has not changed anything
+    let mut settled: HashSet<&'static str> = Default::default();
+    loop {
+        let candidates: Vec<(&'static str, usize)> = limited_failpoints(&report)
+            .into_iter()
+            .filter(|name| !settled.contains(name))
+            .map(|name| (name, hit_limits.limit(name)))
+            .collect();
+        hit_limits.limits.extend(candidates.iter().copied());
+        if candidates.iter().any(|&(_, limit)| limit >= max) {
+            hit_limits.fixed_point = false;
+        }
+        let raised: Vec<(&'static str, usize)> = candidates
+            .into_iter()
+            .filter(|&(_, limit)| limit < max)
+            .collect();
+        // outcomes of an incomplete exploration can't be compared
+        if raised.is_empty() || report.is_incomplete() {
+            break;
+        }
+
+        for &(name, limit) in &raised {
+            hit_limits.limits.insert(name, (limit * 2).min(max));
+        }
+        round_options.activation_limits = hit_limits
+            .limits
+            .iter()
+            .map(|(&name, &limit)| (name, limit))
+            .collect();
+        budget.apply(&mut round_options, 0.5);
+        let raised_report = explore(round_options.clone(), func);
+        budget.used_iterations += raised_report.num_paths();
+        hit_limits.rounds += 1;
+
+        let known: HashSet<Observation> = report
+            .paths()
+            .iter()
+            .map(ExecutionPath::observation)
+            .collect();
+        for (name, limit) in raised {
+            let changed = raised_report.paths().iter().any(|path| {
+                num_activations(path, name) > limit && !known.contains(&path.observation())
+            });
+            if !changed {
+                // the smaller limit was enough
+                hit_limits.limits.insert(name, limit);
+                settled.insert(name);
+            }
+        }
+        report = raised_report;
+        report_limits = round_options.activation_limits.clone();
+    }
+
+    let limits: HashMap<&'static str, usize> = hit_limits
+        .limits
+        .iter()
+        .map(|(&name, &limit)| (name, limit))
+        .collect();
+    let reusable =
+        limits == report_limits && !report.is_incomplete() && !is_discovery_different(&options);
+    if !reusable && !report.is_interrupted() {
+        let mut final_options = options;
+        final_options.safety_limits.max_activations = initial;
+        final_options.activation_limits = limits;
+        budget.apply(&mut final_options, 1.0);
+        report = explore(final_options, func);
+    }
+    report.set_hit_limits(hit_limits);
+    report
+}
@@ -0,0 +1,427 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Misbehaving HTTP server for testing HTTP clients
+//!
+//! [`TestServer`] is a tiny blocking HTTP/1.1 server, which serves
+//! fixed responses over a real socket bound to an ephemeral port on
+//! the loopback interface. Each route defines a multi-valued
+//! failpoint, named after the label given to it, with the following
+//! variants applied to the first response of the route:
+//!
+//! - `drop_after_headers`: the connection is closed right after the
+//!   headers.
+//! - `truncate_body_at_half`: the connection is closed after a half of
+//!   the body.
+//! - `bad_chunk_terminator`: for [chunked](Response::with_chunked)
+//!   responses only, the last data chunk is followed by garbage in
+//!   place of CRLF.
+//! - `stall`: a half of the body is sent, then nothing is sent until
+//!   the [stall timeout](TestServerBuilder::with_stall_timeout) expires,
+//!   the [iteration times out](crate::Runner::with_iteration_timeout),
+//!   the client closes the connection, or the server is shut down,
+//!   after which the connection is closed.
+//!
+//! As failpoint state is per thread, failpoints are visited when the
+//! server is [started](TestServerBuilder::start), on the thread which
+//! runs the tested code, so a server should be started in each
+//! iteration. It is shut down when dropped. Connections are served
+//! one at a time, and are closed after each response.
+//!
+//! ```
+//! use std::io::{Read, Write};
+//! use std::net::TcpStream;
+//!
+//! use faine::Runner;
+//! use faine::http_test::{Response, TestServer};
+//!
+//! fn fetch(stream: &mut TcpStream) -> std::io::Result<String> {
+//!     stream.write_all(b"GET /hello HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+//!     let mut response = String::new();
+//!     stream.read_to_string(&mut response)?;
+//!     Ok(response)
+//! }
+//!
+//! Runner::default()
+//!     .run(|| {
+//!         let server = TestServer::builder()
+//!             .route("/hello", "hello", Response::new(200, "hello"))
+//!             .start()
+//!             .unwrap();
+//!         let mut stream = TcpStream::connect(server.addr()).unwrap();
+//!         stream.set_read_timeout(Some(std::time::Duration::from_millis(100))).unwrap();
+//!         if let Ok(response) = fetch(&mut stream) {
+//!             assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+//!         }
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! Available with `http-test` feature.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::__private::{FAILPOINTS, visit_variants};
+
+const DROP_AFTER_HEADERS: &str = "drop_after_headers";
+const TRUNCATE_BODY_AT_HALF: &str = "truncate_body_at_half";
+const BAD_CHUNK_TERMINATOR: &str = "bad_chunk_terminator";
+const STALL: &str = "stall";
+
+const VARIANTS: &[&str] = &[DROP_AFTER_HEADERS, TRUNCATE_BODY_AT_HALF, STALL];
+const CHUNKED_VARIANTS: &[&str] = &[
+    DROP_AFTER_HEADERS,
+    TRUNCATE_BODY_AT_HALF,
+    BAD_CHUNK_TERMINATOR,
+    STALL,
+];
+
+const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+type TimedOutCheck = Box<dyn Fn() -> bool + Send>;
+
+/// Response served by a route of [`TestServer`]
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    chunk_size: Option<usize>,
+}
+
+impl Response {
+    /// Construct a response with a given status code and body
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.into(),
+            chunk_size: None,
+        }
+    }
+
+    /// Add a header
+    ///
+    /// `Content-Length`, `Transfer-Encoding` and `Connection` headers
+    /// are added by the server.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send the body with chunked transfer encoding, in chunks of a given size
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn with_chunked(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    fn head(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            201 => "Created",
+            204 => "No Content",
+            400 => "Bad Request",
+            404 => "Not Found",
+            500 => "Internal Server Error",
+            503 => "Service Unavailable",
+            _ => "",
+        };
+        let mut head = format!("HTTP/1.1 {} {reason}\r\n", self.status);
+        for (name, value) in &self.headers {
+            head += &format!("{name}: {value}\r\n");
+        }
+        match self.chunk_size {
+            Some(_) => head += "Transfer-Encoding: chunked\r\n",
+            None => head += &format!("Content-Length: {}\r\n", self.body.len()),
+        }
+        head += "Connection: close\r\n\r\n";
+        head
+    }
+}
+
+struct Route {
+    path: String,
+    label: &'static str,
+    response: Response,
+}
+
+/// Builder of [`TestServer`]
+pub struct TestServerBuilder {
+    routes: Vec<Route>,
+    stall_timeout: Duration,
+}
+
+impl TestServerBuilder {
+    /// Serve a response on a given path, with a failpoint named `label`
+    ///
+    /// Requests are matched by path, ignoring method and query.
+    pub fn route(
+        mut self,
+        path: impl Into<String>,
+        label: &'static str,
+        response: Response,
+    ) -> Self {
+        self.routes.push(Route {
+            path: path.into(),
+            label,
+            response,
+        });
+        self
+    }
+
+    /// Set maximal duration of a `stall`, 5 seconds by default
+    pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = timeout;
+        self
+    }
+
+    /// Visit failpoints of all routes, and start serving
+    #[track_caller]
+    pub fn start(self) -> io::Result<TestServer> {
+        let location = crate::ext::caller_location();
+        let routes = self
+            .routes
+            .into_iter()
+            .map(|route| {
+                let variants = match route.response.chunk_size {
+                    Some(_) => CHUNKED_VARIANTS,
+                    None => VARIANTS,
+                };
+                let misbehavior =
+                    visit_variants(route.label, location, variants).map(|index| variants[index]);
+                (route, misbehavior)
+            })
+            .collect();
+        let timed_out = FAILPOINTS.with_borrow(|state| {
+            state
+                .as_ref()
+                .and_then(|state| state.watchdog.as_ref())
+                .map(|watchdog| Box::new(watchdog.timed_out_check()) as TimedOutCheck)
+        });
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let addr = listener.local_addr()?;
+        let (shutdown, shutdown_receiver) = mpsc::channel();
+        let server = Server {
+            routes,
+            stall_timeout: self.stall_timeout,
+            shutdown: shutdown_receiver,
+            timed_out,
+        };
+        let thread = std::thread::spawn(move || server.serve(listener));
+        Ok(TestServer {
+            addr,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Blocking HTTP server with failpoints in responses
+///
+/// See [module documentation](self).
+pub struct TestServer {
+    addr: SocketAddr,
+    // never sent to, dropped to request shutdown
+    shutdown: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    /// Start building a server
+    pub fn builder() -> TestServerBuilder {
+        TestServerBuilder {
+            routes: vec![],
+            stall_timeout: DEFAULT_STALL_TIMEOUT,
+        }
+    }
+
+    /// Address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// URL of a given path on the server
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{path}", self.addr)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.shutdown.take();
+        // wake the server blocked in accept()
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Server {
+    routes: Vec<(Route, Option<&'static str>)>,
+    stall_timeout: Duration,
+    shutdown: Receiver<()>,
+    timed_out: Option<TimedOutCheck>,
+}
+
+impl Server {
+    fn is_shut_down(&self) -> bool {
+        matches!(self.shutdown.try_recv(), Err(TryRecvError::Disconnected))
+    }
+
+    fn serve(mut self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            if self.is_shut_down() {
+                break;
+            }
+            if let Ok(mut stream) = stream {
+                // errors mean the client has gone, which is its business
+                let _ = self.handle(&mut stream);
+            }
+        }
+    }
+
+    /// Read request head and body, returning the path
+    fn read_request(&self, stream: &TcpStream) -> io::Result<String> {
+        stream.set_read_timeout(Some(self.stall_timeout))?;
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let target = line.split_whitespace().nth(1).unwrap_or("/");
+        let path = target.split('?').next().unwrap_or(target).to_string();
+        let mut content_length = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        io::copy(&mut reader.take(content_length), &mut io::sink())?;
+        Ok(path)
+    }
+
+    fn handle(&mut self, stream: &mut TcpStream) -> io::Result<()> {
+        let path = self.read_request(stream)?;
+        let Some((route, misbehavior)) =
+            self.routes.iter_mut().find(|(route, _)| route.path == path)
+        else {
+            let response = Response::new(404, "not found");
+            return write_response(stream, &response, None);
+        };
+        let misbehavior = misbehavior.take();
+        write_response(stream, &route.response, misbehavior)?;
+        if misbehavior == Some(STALL) {
+            self.stall(stream)?;
+        }
+        Ok(())
+    }
+
+    /// Keep the connection open until a timeout, shutdown, or the client going away
+    fn stall(&self, stream: &TcpStream) -> io::Result<()> {
+        stream.set_nonblocking(true)?;
+        let start = Instant::now();
+        while start.elapsed() < self.stall_timeout
+            && !self.timed_out.as_ref().is_some_and(|timed_out| timed_out())
+        {
+            match stream.peek(&mut [0]) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                _ => break,
+            }
+            match self.shutdown.recv_timeout(POLL_INTERVAL) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    response: &Response,
+    misbehavior: Option<&'static str>,
+) -> io::Result<()> {
+    stream.write_all(response.head().as_bytes())?;
+    if misbehavior == Some(DROP_AFTER_HEADERS) {
+        return stream.flush();
+    }
+    let truncated = matches!(misbehavior, Some(TRUNCATE_BODY_AT_HALF | STALL));
+    let body = if truncated {
+        &response.body[..response.body.len() / 2]
+    } else {
+        &response.body[..]
+    };
+    match response.chunk_size {
+        None => stream.write_all(body)?,
+        Some(chunk_size) => {
+            let num_chunks = body.len().div_ceil(chunk_size);
+            for (index, chunk) in body.chunks(chunk_size).enumerate() {
+                write!(stream, "{:x}\r\n", chunk.len())?;
+                stream.write_all(chunk)?;
+                if misbehavior == Some(BAD_CHUNK_TERMINATOR) && index + 1 == num_chunks {
+                    stream.write_all(b"!!")?;
+                } else {
+                    stream.write_all(b"\r\n")?;
+                }
+            }
+            if !truncated {
+                stream.write_all(b"0\r\n\r\n")?;
+            }
+        }
+    }
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(server: &TestServer, path: &str) -> String {
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serve() {
+        let server = TestServer::builder()
+            .route("/plain", "plain", Response::new(200, "hello"))
+            .route(
+                "/chunked",
+                "chunked",
+                Response::new(200, "hello")
+                    .with_header("X-Test", "1")
+                    .with_chunked(2),
+            )
+            .start()
+            .unwrap();
+        assert_eq!(
+            get(&server, "/plain?query"),
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello"
+        );
+        assert_eq!(
+            get(&server, "/chunked"),
+            "HTTP/1.1 200 OK\r\nX-Test: 1\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+             2\r\nhe\r\n2\r\nll\r\n1\r\no\r\n0\r\n\r\n"
+        );
+        assert!(get(&server, "/missing").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::collections::BitSet;
+use crate::common::Branch;
+use crate::report::{ExecutionPath, Observation, Step};
+
+/// Suggested action for a failpoint, see [`Hygiene`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Suggestion {
+    /// Activation changes outcomes
+    Keep,
+    /// Activation never changes outcomes
+    Remove,
+}
+
+impl std::fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keep => write!(f, "keep"),
+            Self::Remove => write!(f, "consider removing"),
+        }
+    }
+}
+
+/// Whether a failpoint contributes to an exploration
+///
+/// Computed by comparing outcomes reached after a failpoint was
+/// activated with these reached after it was skipped, for each place
+/// on the explored paths where both branches were taken (a sibling
+/// pair). Outcomes are [recorded outcomes](crate::record_outcome),
+/// [classified results](crate::Runner::with_outcome_classifier),
+/// panic locations and timeouts.
+///
+/// See [`Runner::with_hygiene_report()`](crate::Runner::with_hygiene_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Hygiene {
+    /// Number of sibling pairs compared
+    pub pairs: usize,
+
+    /// Number of sibling pairs with differing outcomes
+    pub differing: usize,
+
+    /// Suggested action
+    pub suggestion: Suggestion,
+}
+
+impl std::fmt::Display for Hygiene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} sibling pair(s) differ, {}",
+            self.differing, self.pairs, self.suggestion
+        )
+    }
+}
+
+/// Compute instrumentation hygiene of all failpoints
+///
+/// Like with blast radii, paths are merged into a prefix tree, with
+/// sets of outcomes reachable from each node propagated bottom up as
+/// bitsets.
+pub(crate) fn instrumentation_hygiene(paths: &[ExecutionPath]) -> BTreeMap<&'static str, Hygiene> {
+    let mut observations: HashMap<Observation, usize> = Default::default();
+    let mut parents: Vec<usize> = vec![0];
+    let mut children: HashMap<(usize, &Step), usize> = Default::default();
+    let mut leaves: Vec<(usize, usize)> = Default::default();
+    for path in paths {
+        let mut node = 0;
+        for step in &path.steps {
+            node = *children.entry((node, step)).or_insert_with(|| {
+                parents.push(node);
+                parents.len() - 1
+            });
+        }
+        let next = observations.len();
+        leaves.push((
+            node,
+            *observations.entry(path.observation()).or_insert(next),
+        ));
+    }
+
+    let mut reachable = vec![BitSet::new(observations.len()); parents.len()];
+    for (node, observation) in leaves {
+        reachable[node].insert(observation);
+    }
+    for node in (1..parents.len()).rev() {
+        let (head, tail) = reachable.split_at_mut(node);
+        head[parents[node]].union_with(&tail[0]);
+    }
+
+    let mut aggregated: BTreeMap<&'static str, (usize, usize)> = Default::default();
+    for (&(parent, step), &activated) in &children {
+        if step.branch != Branch::Activate || step.variant.is_some() {
+            continue;
+        }
+        let skip_step = Step {
+            branch: Branch::Skip,
+            ..step.clone()
+        };
+        let Some(&skipped) = children.get(&(parent, &skip_step)) else {
+            continue;
+        };
+        let (pairs, differing) = aggregated.entry(step.failpoint).or_default();
+        *pairs += 1;
+        if reachable[activated] != reachable[skipped] {
+            *differing += 1;
+        }
+    }
+
+    aggregated
+        .into_iter()
+        .map(|(failpoint, (pairs, differing))| {
+            let suggestion = if differing == 0 {
+                Suggestion::Remove
+            } else {
+                Suggestion::Keep
+            };
+            (
+                failpoint,
+                Hygiene {
+                    pairs,
+                    differing,
+                    suggestion,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(steps: &[(&'static str, Branch)], outcome: u64) -> ExecutionPath {
+        ExecutionPath {
+            steps: steps
+                .iter()
+                .map(|&(failpoint, branch)| Step {
+                    failpoint,
+                    branch,
+                    variant: None,
+                    variant_index: None,
+                    trigger: None,
+                })
+                .collect(),
+            outcome: Some(outcome),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_instrumentation_hygiene() {
+        use Branch::*;
+        // a and b both lead to outcome 1, which does not make them
+        // mergeable, c never changes anything
+        let paths = [
+            path(&[("a", Activate)], 1),
+            path(&[("a", Skip), ("b", Activate)], 1),
+            path(&[("a", Skip), ("b", Skip), ("c", Activate)], 0),
+            path(&[("a", Skip), ("b", Skip), ("c", Skip)], 0),
+        ];
+        let hygiene = instrumentation_hygiene(&paths);
+        assert_eq!(hygiene["a"].suggestion, Suggestion::Keep);
+        assert_eq!(hygiene["b"].suggestion, Suggestion::Keep);
+        assert_eq!(
+            hygiene["c"],
+            Hygiene {
+                pairs: 1,
+                differing: 0,
+                suggestion: Suggestion::Remove,
+            }
+        );
+        assert_eq!(
+            hygiene["a"].to_string(),
+            "1 of 1 sibling pair(s) differ, keep"
+        );
+    }
+}
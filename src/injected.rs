@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::error::Error;
+
+/// Marker error produced by failpoints
+///
+/// `*_io_error` family of macros wrap this into [`std::io::Error`], so
+/// tests may distinguish injected errors from genuine ones. See
+/// [`assert_injected_by!`] and [`assert_not_injected!`].
+///
+/// The error is `Copy`, `Send`, `Sync` and `'static`, and holds the
+/// failpoint name as `&'static str`, so constructing it never
+/// allocates. It's only boxed when wrapped into [`std::io::Error`],
+/// which happens on activated paths only.
+///
+/// [`assert_injected_by!`]: crate::assert_injected_by
+/// [`assert_not_injected!`]: crate::assert_not_injected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InjectedError {
+    failpoint: &'static str,
+    tag: Option<ErrorTag>,
+}
+
+impl InjectedError {
+    /// Construct an error injected by a given failpoint
+    pub const fn new(failpoint: &'static str) -> Self {
+        Self {
+            failpoint,
+            tag: None,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: ErrorTag) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Name of the failpoint which injected this error
+    pub fn failpoint(&self) -> &'static str {
+        self.failpoint
+    }
+
+    /// Tag of the path on which this error was injected
+    ///
+    /// Only set with [`Runner::with_error_tagging()`](crate::Runner::with_error_tagging).
+    pub fn tag(&self) -> Option<ErrorTag> {
+        self.tag
+    }
+
+    /// Find injected error in the source chain of a given error
+    ///
+    /// Besides plain [`Error::source()`] chain, this looks into
+    /// [`std::io::Error`] payloads, which are not reachable with
+    /// `source()`.
+    pub fn find_in<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a InjectedError> {
+        let mut current = Some(err);
+        while let Some(err) = current {
+            if let Some(injected) = err.downcast_ref::<InjectedError>() {
+                return Some(injected);
+            }
+            if let Some(io_error) = err.downcast_ref::<std::io::Error>()
+                && let Some(inner) = io_error.get_ref()
+                && let Some(injected) = Self::find_in(inner)
+            {
+                return Some(injected);
+            }
+            current = err.source();
+        }
+        None
+    }
+}
+
+impl std::fmt::Display for InjectedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.failpoint)?;
+        if let Some(tag) = &self.tag {
+            write!(f, " {tag}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Tag identifying a path on which an error was injected
+///
+/// Appended to messages of injected errors with
+/// [`Runner::with_error_tagging()`](crate::Runner::with_error_tagging),
+/// formatted as `[faine i=137 p=1f2eab3f]`, where `i` is the iteration
+/// index and `p` is a short hash of the path prefix up to the
+/// activation. Tags found in application logs are resolved into
+/// paths with [`Report::resolve_tag()`](crate::Report::resolve_tag).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorTag {
+    iteration: usize,
+    path: u32,
+}
+
+impl ErrorTag {
+    pub(crate) fn new(iteration: usize, path: u32) -> Self {
+        Self { iteration, path }
+    }
+
+    /// Index of the iteration, counting reruns and replays
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Find a tag in a text, such as an error message or a log line
+    pub fn find_in(text: &str) -> Option<&str> {
+        let start = text.find("[faine ")?;
+        let len = text[start..].find(']')? + 1;
+        Some(&text[start..start + len])
+    }
+}
+
+impl std::fmt::Display for ErrorTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[faine i={} p={:08x}]", self.iteration, self.path)
+    }
+}
+
+impl Error for InjectedError {}
+
+// error wrapping layers may rely on these
+const _: () = {
+    const fn assert_bounds<T: Copy + Send + Sync + 'static>() {}
+    assert_bounds::<InjectedError>();
+    assert_bounds::<ErrorTag>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Wrapper(std::io::Error);
+
+    impl std::fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "wrapper")
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_find_direct() {
+        let err = InjectedError::new("foo");
+        assert_eq!(InjectedError::find_in(&err), Some(&err));
+    }
+
+    #[test]
+    fn test_find_in_io_error() {
+        let err = std::io::Error::other(InjectedError::new("foo"));
+        assert_eq!(InjectedError::find_in(&err).unwrap().failpoint(), "foo");
+    }
+
+    #[test]
+    fn test_find_in_chain() {
+        let err = Wrapper(std::io::Error::other(InjectedError::new("foo")));
+        assert_eq!(InjectedError::find_in(&err).unwrap().failpoint(), "foo");
+    }
+
+    #[test]
+    fn test_tag() {
+        let err = InjectedError::new("foo").with_tag(ErrorTag::new(137, 0x1f2eab3f));
+        assert_eq!(err.to_string(), "foo [faine i=137 p=1f2eab3f]");
+        assert_eq!(
+            ErrorTag::find_in(&format!("error: {err}: retrying")),
+            Some("[faine i=137 p=1f2eab3f]")
+        );
+        assert_eq!(ErrorTag::find_in("error: foo"), None);
+    }
+
+    #[test]
+    fn test_not_found() {
+        let err = Wrapper(std::io::Error::other("foo"));
+        assert_eq!(InjectedError::find_in(&err), None);
+    }
+}
@@ -0,0 +1,660 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, OnceLock, RwLock};
+
+/// Interned failpoint
+///
+/// Everything but the name is filled in once it becomes known, so
+/// entries are read without locking.
+struct Entry {
+    name: &'static str,
+    /// Crate and qualified name, as `crate::name`
+    qualified: OnceLock<(&'static str, &'static str)>,
+    component: OnceLock<&'static str>,
+    base: OnceLock<u32>,
+    ambiguous: AtomicBool,
+}
+
+impl Entry {
+    fn qualified_name(&self) -> &'static str {
+        self.qualified
+            .get()
+            .map_or(self.name, |(_, qualified)| qualified)
+    }
+
+    fn rendered_name(&self) -> &'static str {
+        if self.ambiguous.load(Ordering::Acquire) {
+            self.qualified_name()
+        } else {
+            self.name
+        }
+    }
+}
+
+const BUCKETS: usize = 32;
+
+/// Entries by id, in buckets of doubling sizes, so these never move
+static ENTRIES: [OnceLock<Box<[OnceLock<Entry>]>>; BUCKETS] = [const { OnceLock::new() }; BUCKETS];
+
+/// Bucket and index in it for an id
+fn locate(id: u32) -> (usize, usize) {
+    let position = u64::from(id) + 1;
+    let bucket = position.ilog2() as usize;
+    (bucket, (position - (1 << bucket)) as usize)
+}
+
+fn entry(id: u32) -> &'static Entry {
+    let (bucket, index) = locate(id);
+    ENTRIES[bucket]
+        .get()
+        .and_then(|entries| entries[index].get())
+        .expect("failpoint ids are only created by interning")
+}
+
+#[derive(Default)]
+struct Table {
+    len: u32,
+    ids: HashMap<(Option<&'static str>, &'static str), u32>,
+    unqualified_ids: HashMap<&'static str, Vec<u32>>,
+    qualified_ids: HashMap<&'static str, u32>,
+}
+
+impl Table {
+    fn push(&mut self, krate: Option<&str>, name: &str) -> u32 {
+        let id = self.len;
+        self.len = id
+            .checked_add(1)
+            .expect("too many interned failpoint names");
+        let name: &'static str = Box::leak(name.into());
+        let (bucket, index) = locate(id);
+        let entries =
+            ENTRIES[bucket].get_or_init(|| (0..1 << bucket).map(|_| OnceLock::new()).collect());
+        let _ = entries[index].set(Entry {
+            name,
+            qualified: OnceLock::new(),
+            component: OnceLock::new(),
+            base: OnceLock::new(),
+            ambiguous: AtomicBool::new(false),
+        });
+        self.unqualified_ids.entry(name).or_default().push(id);
+        match krate {
+            Some(krate) => self.assign_crate(id, krate),
+            None => {
+                self.ids.insert((None, name), id);
+            }
+        }
+        id
+    }
+
+    fn assign_crate(&mut self, id: u32, krate: &str) {
+        let entry = entry(id);
+        let qualified: &'static str = Box::leak(format!("{krate}::{}", entry.name).into());
+        let krate = &qualified[..krate.len()];
+        let _ = entry.qualified.set((krate, qualified));
+        self.ids.insert((Some(krate), entry.name), id);
+        self.qualified_ids.insert(qualified, id);
+
+        let siblings = &self.unqualified_ids[entry.name];
+        if siblings.len() > 1 {
+            for &sibling in siblings {
+                self::entry(sibling)
+                    .ambiguous
+                    .store(true, Ordering::Release);
+            }
+        }
+    }
+}
+
+static TABLE: LazyLock<RwLock<Table>> = LazyLock::new(Default::default);
+
+/// Interned failpoint name
+///
+/// Failpoints are identified by these internally, and `inject_*`
+/// macros accept them in place of string names. Interning allows
+/// failpoint names generated at runtime, and makes visiting
+/// failpoints cheap and allocation free. Use [`intern!`] to intern
+/// a literal once per call site, or [`FailpointId::intern()`] for
+/// arbitrary strings.
+///
+/// Names may be qualified with a component, see
+/// [`FailpointId::intern_in()`].
+///
+/// Failpoints defined with `inject_*` macros and [`intern!`] also
+/// belong to the crate they are defined in, so two crates using the
+/// same name do not share a failpoint. Such names are ambiguous, and
+/// are rendered qualified with the crate, as `crate::name`. APIs
+/// which take failpoint names accept both forms: a qualified name
+/// refers to a failpoint of the given crate, and an unqualified one
+/// refers to failpoints of all crates which use it. Note that a name
+/// only becomes ambiguous when the second crate first uses it, so
+/// paths explored before that still show it unqualified.
+///
+/// [`intern!`]: crate::intern
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FailpointId(u32);
+
+impl FailpointId {
+    /// Intern a failpoint name
+    ///
+    /// Names are never freed, so the number of distinct names
+    /// should be bounded. An unqualified name refers to the failpoint
+    /// of the first crate which has used it, and a qualified one, such
+    /// as `mycrate::open file`, to the failpoint of the given crate,
+    /// provided that it was already interned.
+    pub fn intern(name: &str) -> Self {
+        let table = TABLE.read().unwrap_or_else(|err| err.into_inner());
+        if let Some(id) = Self::lookup(&table, name) {
+            return Self(id);
+        }
+        drop(table);
+
+        let mut table = TABLE.write().unwrap_or_else(|err| err.into_inner());
+        if let Some(id) = Self::lookup(&table, name) {
+            return Self(id);
+        }
+        Self(table.push(None, name))
+    }
+
+    fn lookup(table: &Table, name: &str) -> Option<u32> {
+        table
+            .unqualified_ids
+            .get(name)
+            .map(|ids| ids[0])
+            .or_else(|| table.qualified_ids.get(name).copied())
+    }
+
+    /// Intern a failpoint name belonging to the given crate
+    ///
+    /// This is what `inject_*` macros and [`intern!`](crate::intern)
+    /// do, with the name of the crate they are expanded in. If the
+    /// name was interned without a crate, but not yet by any crate,
+    /// the existing failpoint is claimed by the given crate, so in a
+    /// single crate there's no difference between the two.
+    pub fn intern_for_crate(krate: &str, name: &str) -> Self {
+        let table = TABLE.read().unwrap_or_else(|err| err.into_inner());
+        if let Some(id) = table.ids.get(&(Some(krate), name)) {
+            return Self(*id);
+        }
+        drop(table);
+
+        let mut table = TABLE.write().unwrap_or_else(|err| err.into_inner());
+        if let Some(id) = table.ids.get(&(Some(krate), name)) {
+            return Self(*id);
+        }
+        if let Some(&id) = table.ids.get(&(None, name))
+            && entry(id).qualified.get().is_none()
+        {
+            table.assign_crate(id, krate);
+            return Self(id);
+        }
+        Self(table.push(Some(krate), name))
+    }
+
+    pub(crate) fn intern_from(krate: Option<&str>, name: &str) -> Self {
+        match krate {
+            Some(krate) => Self::intern_for_crate(krate, name),
+            None => Self::intern(name),
+        }
+    }
+
+    /// Intern a failpoint name qualified with a component
+    ///
+    /// The full name is `component/point`, and the failpoint belongs
+    /// to the component, so it may be selected with
+    /// [`Component`](crate::Component). A plain name which happens
+    /// to contain `/` is the same failpoint, but it only gets the
+    /// component once interned with this.
+    pub fn intern_in(component: &str, point: &str) -> Self {
+        Self::intern_in_from(None, component, point)
+    }
+
+    fn intern_in_from(krate: Option<&str>, component: &str, point: &str) -> Self {
+        let id = Self::intern_from(krate, &format!("{component}/{point}"));
+        let entry = entry(id.0);
+        let _ = entry.component.set(&entry.name[..component.len()]);
+        id
+    }
+
+    /// Intern a failpoint name specialized for a type
+    ///
+    /// The name is `base<type>`, where `type` is given by
+    /// [`std::any::type_name()`], and the failpoint belongs to the same
+    /// crate and component as the base one. This is what `per_type:`
+    /// argument of `inject_*` macros does, so failpoints in a generic
+    /// function are distinct for each of its instantiations. Names
+    /// given by the user refer to such failpoints by either full name,
+    /// or by the name of the base failpoint, which covers all types.
+    pub fn intern_per_type(base: FailpointId, type_name: &str) -> Self {
+        let name = format!("{}<{type_name}>", base.unqualified_name());
+        let id = Self::intern_from(base.crate_name(), &name);
+        let entry = entry(id.0);
+        if entry.base.set(base.0).is_ok()
+            && let Some(component) = base.component()
+        {
+            let _ = entry.component.set(component);
+        }
+        id
+    }
+
+    /// Base failpoint of a failpoint specialized for a type
+    ///
+    /// See [`intern_per_type()`](Self::intern_per_type).
+    pub fn base(self) -> Option<Self> {
+        entry(self.0).base.get().copied().map(Self)
+    }
+
+    /// Name of the failpoint, qualified with its crate if ambiguous
+    pub fn name(self) -> &'static str {
+        entry(self.0).rendered_name()
+    }
+
+    /// Name of the failpoint, as passed to `inject_*` macros
+    pub fn unqualified_name(self) -> &'static str {
+        entry(self.0).name
+    }
+
+    /// Name of the failpoint qualified with its crate, as `crate::name`
+    ///
+    /// Same as [`unqualified_name()`](Self::unqualified_name) for
+    /// failpoints which do not belong to a crate.
+    pub fn qualified_name(self) -> &'static str {
+        entry(self.0).qualified_name()
+    }
+
+    /// Crate the failpoint is defined in, if known
+    pub fn crate_name(self) -> Option<&'static str> {
+        entry(self.0).qualified.get().map(|(krate, _)| *krate)
+    }
+
+    /// Component of the failpoint, if it was interned with one
+    pub fn component(self) -> Option<&'static str> {
+        entry(self.0).component.get().copied()
+    }
+}
+
+/// Find a failpoint by the name it is rendered with
+fn find(name: &str) -> Option<&'static Entry> {
+    let table = TABLE.read().unwrap_or_else(|err| err.into_inner());
+    table
+        .qualified_ids
+        .get(name)
+        .or_else(|| table.unqualified_ids.get(name).map(|ids| &ids[0]))
+        .map(|id| entry(*id))
+}
+
+/// Component of a failpoint with a given name, if it has one
+pub fn component_of(name: &str) -> Option<&'static str> {
+    find(name)?.component.get().copied()
+}
+
+/// Whether a name given by the user refers to a failpoint rendered as `name`
+///
+/// Besides exact match, an unqualified name refers to failpoints of
+/// all crates which use it, and a qualified one refers to a failpoint
+/// of the given crate even if it's rendered unqualified. Any form of
+/// a base name also refers to all failpoints specialized from it.
+pub fn refers_to(selected: &str, name: &str) -> bool {
+    if selected == name {
+        return true;
+    }
+    let Some(mut entry) = find(name) else {
+        return false;
+    };
+    loop {
+        if entry.qualified_name() == selected || entry.name == selected {
+            return true;
+        }
+        match entry.base.get() {
+            Some(&base) => entry = self::entry(base),
+            None => return false,
+        }
+    }
+}
+
+/// Whether a failpoint rendered as `name` has a name starting with `prefix`
+///
+/// Either its qualified or unqualified form may match, so the result
+/// does not change when the failpoint becomes ambiguous, and a prefix
+/// without a crate refers to failpoints of all crates.
+pub fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.starts_with(prefix)
+        || forms_of(name).is_some_and(|forms| forms.iter().any(|form| form.starts_with(prefix)))
+}
+
+/// Name a failpoint rendered as `name` is rendered with now
+///
+/// Differs from `name` if it has become ambiguous since.
+pub fn current_name(name: &'static str) -> &'static str {
+    find(name).map_or(name, Entry::rendered_name)
+}
+
+/// Qualified and unqualified names of a failpoint rendered as `name`
+pub fn forms_of(name: &str) -> Option<[&'static str; 2]> {
+    let entry = find(name)?;
+    Some([entry.qualified_name(), entry.name])
+}
+
+/// Typed handle of a failpoint, declared with [`failpoints!`]
+///
+/// Handles are constants, so a mistyped handle is a compile error,
+/// unlike a mistyped string name which silently matches nothing.
+/// They may be passed to `inject_*` macros in place of names, and to
+/// runner methods which take a [`Selector`], and sets of them are
+/// passed to [`Runner::with_expected_failpoints()`] to check that
+/// declared failpoints are not dead. Like string names used by macros,
+/// handles belong to the crate they are declared in.
+///
+/// [`failpoints!`]: crate::failpoints
+/// [`Selector`]: crate::Selector
+/// [`Runner::with_expected_failpoints()`]: crate::Runner::with_expected_failpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FailpointHandle {
+    krate: Option<&'static str>,
+    name: &'static str,
+}
+
+impl FailpointHandle {
+    /// Not a public API, used by [`failpoints!`](crate::failpoints)
+    #[doc(hidden)]
+    pub const fn new(krate: Option<&'static str>, name: &'static str) -> Self {
+        Self { krate, name }
+    }
+
+    /// Name of the failpoint, as declared
+    pub const fn name(self) -> &'static str {
+        self.name
+    }
+
+    /// Crate the failpoint belongs to, if known
+    pub const fn crate_name(self) -> Option<&'static str> {
+        self.krate
+    }
+
+    /// Interned failpoint
+    pub fn id(self) -> FailpointId {
+        FailpointId::intern_from(self.krate, self.name)
+    }
+}
+
+impl From<FailpointHandle> for FailpointId {
+    fn from(handle: FailpointHandle) -> Self {
+        handle.id()
+    }
+}
+
+/// Interns the name with [`FailpointId::intern()`], so, unlike names
+/// given to `inject_*` macros, it does not belong to any crate
+impl From<&str> for FailpointId {
+    fn from(name: &str) -> Self {
+        Self::intern(name)
+    }
+}
+
+impl AsRef<str> for FailpointHandle {
+    fn as_ref(&self) -> &str {
+        self.name
+    }
+}
+
+impl std::fmt::Display for FailpointHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl std::fmt::Debug for FailpointId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FailpointId({:?})", self.name())
+    }
+}
+
+impl std::fmt::Display for FailpointId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Per call site cache of interned failpoint name
+///
+/// Not a public API, used by `inject_*` macros. Remembers the first
+/// name interned at the call site, and returns its id without any
+/// locking or hashing as long as the same string is passed. Names
+/// are interned as belonging to the crate the macro is expanded in.
+#[doc(hidden)]
+pub struct FailpointIdCache {
+    krate: Option<&'static str>,
+    cached: OnceLock<(Option<&'static str>, &'static str, FailpointId)>,
+}
+
+impl FailpointIdCache {
+    pub const fn new(krate: Option<&'static str>) -> Self {
+        Self {
+            krate,
+            cached: OnceLock::new(),
+        }
+    }
+
+    fn resolve(&self, name: &'static str) -> FailpointId {
+        self.resolve_in(None, name)
+    }
+
+    /// Resolve a name, optionally qualified with a component
+    fn resolve_in(&self, component: Option<&'static str>, name: &'static str) -> FailpointId {
+        if let Some((cached_component, cached, id)) = self.cached.get()
+            && std::ptr::eq(*cached, name)
+            && match (cached_component, component) {
+                (Some(cached), Some(component)) => std::ptr::eq(*cached, component),
+                (cached, component) => cached.is_none() && component.is_none(),
+            }
+        {
+            return *id;
+        }
+        let id = match component {
+            Some(component) => FailpointId::intern_in_from(self.krate, component, name),
+            None => FailpointId::intern_from(self.krate, name),
+        };
+        let _ = self.cached.set((component, name, id));
+        id
+    }
+}
+
+/// Per call site cache of failpoints specialized for types
+///
+/// Not a public API, used by `inject_*` macros with `per_type:`
+/// argument. Statics are shared by all instantiations of a generic
+/// function, so this remembers all types seen at the call site.
+#[doc(hidden)]
+pub struct PerTypeIdCache {
+    cached: RwLock<Vec<(FailpointId, &'static str, FailpointId)>>,
+}
+
+impl PerTypeIdCache {
+    pub const fn new() -> Self {
+        Self {
+            cached: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn resolve(&self, base: FailpointId, type_name: &'static str) -> FailpointId {
+        let find = |cached: &[(FailpointId, &'static str, FailpointId)]| {
+            cached
+                .iter()
+                .find(|(cached_base, cached_type, _)| {
+                    *cached_base == base && *cached_type == type_name
+                })
+                .map(|(_, _, id)| *id)
+        };
+        if let Some(id) = find(&self.cached.read().unwrap_or_else(|err| err.into_inner())) {
+            return id;
+        }
+        let id = FailpointId::intern_per_type(base, type_name);
+        let mut cached = self.cached.write().unwrap_or_else(|err| err.into_inner());
+        if find(&cached).is_none() {
+            cached.push((base, type_name, id));
+        }
+        id
+    }
+}
+
+impl Default for PerTypeIdCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Failpoint name accepted by `inject_*` macros
+///
+/// Not a public API.
+#[doc(hidden)]
+pub trait FailpointRef: Copy {
+    fn failpoint_id(self, cache: &FailpointIdCache) -> FailpointId;
+    fn failpoint_name(self) -> &'static str;
+}
+
+impl FailpointRef for &'static str {
+    fn failpoint_id(self, cache: &FailpointIdCache) -> FailpointId {
+        cache.resolve(self)
+    }
+
+    fn failpoint_name(self) -> &'static str {
+        self
+    }
+}
+
+/// Component and point, as passed by `component:` and `point:`
+/// arguments of `inject_*` macros
+impl FailpointRef for (&'static str, &'static str) {
+    fn failpoint_id(self, cache: &FailpointIdCache) -> FailpointId {
+        cache.resolve_in(Some(self.0), self.1)
+    }
+
+    fn failpoint_name(self) -> &'static str {
+        FailpointId::intern_in(self.0, self.1).unqualified_name()
+    }
+}
+
+impl FailpointRef for FailpointHandle {
+    fn failpoint_id(self, cache: &FailpointIdCache) -> FailpointId {
+        if cache.krate == self.krate {
+            cache.resolve(self.name)
+        } else {
+            self.id()
+        }
+    }
+
+    fn failpoint_name(self) -> &'static str {
+        self.name
+    }
+}
+
+impl FailpointRef for FailpointId {
+    fn failpoint_id(self, _cache: &FailpointIdCache) -> FailpointId {
+        self
+    }
+
+    fn failpoint_name(self) -> &'static str {
+        self.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let a = FailpointId::intern("intern test a");
+        let b = FailpointId::intern(&String::from("intern test b"));
+        assert_ne!(a, b);
+        assert_eq!(a, FailpointId::intern(&String::from("intern test a")));
+        assert_eq!(a.name(), "intern test a");
+        assert_eq!(b.to_string(), "intern test b");
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = FailpointIdCache::new(None);
+        let a = cache.resolve("cache test a");
+        assert_eq!(cache.resolve("cache test a"), a);
+        // different names passed through the same site are still
+        // resolved correctly
+        let b = cache.resolve("cache test b");
+        assert_ne!(a, b);
+        assert_eq!(b.name(), "cache test b");
+    }
+
+    #[test]
+    fn test_component() {
+        let plain = FailpointId::intern("component test/a");
+        assert_eq!(plain.component(), None);
+        let qualified = FailpointId::intern_in("component test", "a");
+        assert_eq!(qualified, plain);
+        assert_eq!(qualified.component(), Some("component test"));
+        assert_eq!(component_of("component test/a"), Some("component test"));
+
+        let cache = FailpointIdCache::new(None);
+        let b = cache.resolve_in(Some("component test"), "b");
+        assert_eq!(b.name(), "component test/b");
+        assert_eq!(cache.resolve_in(Some("component test"), "b"), b);
+        assert_eq!(cache.resolve("component test/b"), b);
+    }
+
+    #[test]
+    fn test_crates() {
+        let unqualified = FailpointId::intern("crate test");
+        let a = FailpointId::intern_for_crate("a", "crate test");
+        // single crate claims failpoint interned without one
+        assert_eq!(a, unqualified);
+        assert_eq!(a.name(), "crate test");
+        assert_eq!(a.qualified_name(), "a::crate test");
+        assert_eq!(a.crate_name(), Some("a"));
+
+        let b = FailpointId::intern_for_crate("b", "crate test");
+        assert_ne!(a, b);
+        assert_eq!(a.name(), "a::crate test");
+        assert_eq!(b.name(), "b::crate test");
+        assert_eq!(b.unqualified_name(), "crate test");
+        assert_eq!(FailpointId::intern("crate test"), a);
+        assert_eq!(FailpointId::intern("b::crate test"), b);
+
+        assert!(refers_to("crate test", "a::crate test"));
+        assert!(refers_to("crate test", "b::crate test"));
+        assert!(refers_to("b::crate test", "b::crate test"));
+        assert!(!refers_to("a::crate test", "b::crate test"));
+
+        let cache = FailpointIdCache::new(Some("b"));
+        assert_eq!(cache.resolve("crate test"), b);
+
+        // prefixes match either form, so they still apply once qualified
+        assert!(has_prefix("a::crate test", "crate "));
+        assert!(has_prefix("a::crate test", "a::crate "));
+        assert!(!has_prefix("a::crate test", "b::crate "));
+    }
+
+    #[test]
+    fn test_per_type() {
+        let base = FailpointId::intern_for_crate("a", "per type test");
+        let cache = PerTypeIdCache::new();
+        let int = cache.resolve(base, "i32");
+        let string = cache.resolve(base, "alloc::string::String");
+        assert_ne!(int, string);
+        assert_ne!(int, base);
+        assert_eq!(cache.resolve(base, "i32"), int);
+        assert_eq!(int.name(), "per type test<i32>");
+        assert_eq!(int.crate_name(), Some("a"));
+        assert_eq!(int.base(), Some(base));
+        assert_eq!(base.base(), None);
+
+        assert!(refers_to("per type test", "per type test<i32>"));
+        assert!(refers_to("a::per type test", "per type test<i32>"));
+        assert!(refers_to("per type test<i32>", "per type test<i32>"));
+        assert!(!refers_to(
+            "per type test<i32>",
+            "per type test<alloc::string::String>"
+        ));
+        assert!(!refers_to("per type test<i32>", "per type test"));
+    }
+}
@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+struct Handlers {
+    num_guards: usize,
+    #[cfg(unix)]
+    previous: Vec<(libc::c_int, libc::sighandler_t)>,
+}
+
+static HANDLERS: Mutex<Handlers> = Mutex::new(Handlers {
+    num_guards: 0,
+    #[cfg(unix)]
+    previous: Vec::new(),
+});
+
+#[cfg(unix)]
+const SIGNALS: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+#[cfg(unix)]
+extern "C" fn handle_signal(signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    // second signal terminates the process as usual
+    // SAFETY: signal() is async-signal-safe
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+    }
+}
+
+/// Stop explorations as if SIGINT was received
+///
+/// Explorations with [`Runner::with_graceful_interrupt()`](crate::Runner::with_graceful_interrupt)
+/// enabled stop after the current iteration; others are not affected.
+///
+/// Available with `ctrlc` feature.
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Signal handlers installed for the duration of exploration
+///
+/// Handlers are shared between concurrently running explorations, and
+/// previous handlers are restored when the last one finishes.
+pub struct InterruptGuard;
+
+impl InterruptGuard {
+    pub fn install() -> Self {
+        let mut handlers = HANDLERS.lock().unwrap_or_else(|err| err.into_inner());
+        if handlers.num_guards == 0 {
+            INTERRUPTED.store(false, Ordering::SeqCst);
+            #[cfg(unix)]
+            for signal in SIGNALS {
+                let handler = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                // SAFETY: the handler only touches an atomic and calls async-signal-safe functions
+                let previous = unsafe { libc::signal(signal, handler) };
+                handlers.previous.push((signal, previous));
+            }
+        }
+        handlers.num_guards += 1;
+        Self
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        let mut handlers = HANDLERS.lock().unwrap_or_else(|err| err.into_inner());
+        handlers.num_guards -= 1;
+        if handlers.num_guards == 0 {
+            #[cfg(unix)]
+            for (signal, previous) in std::mem::take(&mut handlers.previous) {
+                // SAFETY: restores the handler which was installed before
+                unsafe {
+                    libc::signal(signal, previous);
+                }
+            }
+        }
+    }
+}
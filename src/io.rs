@@ -0,0 +1,261 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Failpoint-aware wrappers for blocking readers and writers
+//!
+//! [`FaineReader::read_exact()`] and [`FaineWriter::write_all()`]
+//! define a multi-valued failpoint, named after the label given to the
+//! constructor, which simulates a timeout after partial progress: a
+//! prefix of the buffer is actually read from or written to the inner
+//! stream, so its position advances, and then [`io::ErrorKind::TimedOut`]
+//! is returned. Length of the prefix is chosen by the following
+//! variants (see [`Progress`]):
+//!
+//! - `timeout after none`: nothing is transferred.
+//! - `timeout after half`: half of the buffer is transferred.
+//! - `timeout after all but one`: all but the last byte are transferred.
+//!
+//! This catches code which assumes that a failed operation had no
+//! effect, and retries it or resumes using the stream, which then
+//! goes out of sync. A set of variants may be chosen with
+//! [`FaineReader::with_progress()`] and [`FaineWriter::with_progress()`].
+//!
+//! Other methods of [`Read`] and [`Write`] are passed through.
+//!
+//! Available with `io` feature.
+
+use std::io::{self, Read, Write};
+
+use crate::__private::{self, visit_variants};
+
+/// Amount of data transferred before an injected timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Progress {
+    /// Nothing is transferred, like with plain error injection
+    None,
+
+    /// Half of the buffer, rounded down, is transferred
+    Half,
+
+    /// All but the last byte of the buffer are transferred
+    AllButOne,
+}
+
+impl Progress {
+    const DEFAULT: &[Progress] = &[Progress::None, Progress::Half, Progress::AllButOne];
+
+    fn variant(self) -> &'static str {
+        match self {
+            Progress::None => "timeout after none",
+            Progress::Half => "timeout after half",
+            Progress::AllButOne => "timeout after all but one",
+        }
+    }
+
+    fn len(self, len: usize) -> usize {
+        match self {
+            Progress::None => 0,
+            Progress::Half => len / 2,
+            Progress::AllButOne => len.saturating_sub(1),
+        }
+    }
+}
+
+/// Visit a partial progress failpoint, returning the chosen progress
+#[track_caller]
+fn visit_progress(
+    label: &'static str,
+    progress: &[Progress],
+    variants: &[&'static str],
+) -> Option<Progress> {
+    let location = crate::ext::caller_location();
+    visit_variants(label, location, variants).map(|index| progress[index])
+}
+
+fn timed_out(label: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, __private::injected_error(label))
+}
+
+/// Wrapper for a reader which injects timeouts after partial reads
+///
+/// ```
+/// use std::io::{self, Read};
+/// use faine::io::FaineReader;
+///
+/// fn read_header(stream: impl Read) -> io::Result<[u8; 4]> {
+///     let mut header = [0; 4];
+///     FaineReader::new("read header", stream).read_exact(&mut header)?;
+///     Ok(header)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FaineReader<R> {
+    label: &'static str,
+    inner: R,
+    progress: Vec<Progress>,
+    variants: Vec<&'static str>,
+}
+
+impl<R: Read> FaineReader<R> {
+    /// Wrap a reader
+    ///
+    /// `label` is used as the failpoint name.
+    pub fn new(label: &'static str, inner: R) -> Self {
+        Self {
+            label,
+            inner,
+            progress: Progress::DEFAULT.to_vec(),
+            variants: Progress::DEFAULT
+                .iter()
+                .map(|progress| progress.variant())
+                .collect(),
+        }
+    }
+
+    /// Set amounts of data read before injected timeouts, explored in given order
+    pub fn with_progress(mut self, progress: &[Progress]) -> Self {
+        self.progress = progress.to_vec();
+        self.variants = progress.iter().map(|progress| progress.variant()).collect();
+        self
+    }
+
+    /// Access the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutably access the wrapped reader
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the exact number of bytes, see [`Read::read_exact()`]
+    ///
+    /// On injected timeout, the beginning of `buf` holds the prefix
+    /// which was read.
+    #[track_caller]
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        match visit_progress(self.label, &self.progress, &self.variants) {
+            Some(progress) => {
+                let len = progress.len(buf.len());
+                self.inner.read_exact(&mut buf[..len])?;
+                Err(timed_out(self.label))
+            }
+            None => self.inner.read_exact(buf),
+        }
+    }
+}
+
+impl<R: Read> Read for FaineReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    #[track_caller]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        FaineReader::read_exact(self, buf)
+    }
+}
+
+/// Wrapper for a writer which injects timeouts after partial writes
+///
+/// ```
+/// use std::io::{self, Write};
+/// use faine::io::FaineWriter;
+///
+/// fn write_frame(stream: impl Write, frame: &[u8]) -> io::Result<()> {
+///     FaineWriter::new("write frame", stream).write_all(frame)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FaineWriter<W> {
+    label: &'static str,
+    inner: W,
+    progress: Vec<Progress>,
+    variants: Vec<&'static str>,
+}
+
+impl<W: Write> FaineWriter<W> {
+    /// Wrap a writer
+    ///
+    /// `label` is used as the failpoint name.
+    pub fn new(label: &'static str, inner: W) -> Self {
+        Self {
+            label,
+            inner,
+            progress: Progress::DEFAULT.to_vec(),
+            variants: Progress::DEFAULT
+                .iter()
+                .map(|progress| progress.variant())
+                .collect(),
+        }
+    }
+
+    /// Set amounts of data written before injected timeouts, explored in given order
+    pub fn with_progress(mut self, progress: &[Progress]) -> Self {
+        self.progress = progress.to_vec();
+        self.variants = progress.iter().map(|progress| progress.variant()).collect();
+        self
+    }
+
+    /// Access the wrapped writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Mutably access the wrapped writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Write the whole buffer, see [`Write::write_all()`]
+    #[track_caller]
+    pub fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match visit_progress(self.label, &self.progress, &self.variants) {
+            Some(progress) => {
+                self.inner.write_all(&buf[..progress.len(buf.len())])?;
+                Err(timed_out(self.label))
+            }
+            None => self.inner.write_all(buf),
+        }
+    }
+}
+
+impl<W: Write> Write for FaineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    #[track_caller]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        FaineWriter::write_all(self, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_len() {
+        assert_eq!(Progress::None.len(5), 0);
+        assert_eq!(Progress::Half.len(5), 2);
+        assert_eq!(Progress::AllButOne.len(5), 4);
+        assert_eq!(Progress::AllButOne.len(0), 0);
+    }
+}
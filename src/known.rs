@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+
+/// Parse known failures file contents
+///
+/// Each line contains a path key (see [`ExecutionPath::key()`](crate::ExecutionPath::key)),
+/// empty lines and lines starting with `#` are ignored.
+pub fn parse(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Render known failures file contents
+pub fn render<'a>(keys: impl IntoIterator<Item = &'a str>) -> String {
+    let keys: BTreeSet<&str> = keys.into_iter().collect();
+    let mut text = String::from("# paths expected to fail, one per line\n");
+    for key in keys {
+        text += key;
+        text += "\n";
+    }
+    text
+}
+
+/// Extract names of activated failpoints from a path key
+///
+/// Inverse of [`ExecutionPath::key()`](crate::ExecutionPath::key) as
+/// far as [priority paths](crate::Runner::with_priority_paths) are
+/// concerned: configuration axes, checkpoints, variants and triggers
+/// are dropped. Returns `None` if the key is malformed.
+pub fn activations(key: &str) -> Option<Vec<String>> {
+    let mut key = key.trim();
+    if key.starts_with('{') {
+        key = key.split_once("} ")?.1;
+    }
+    if key == "(no failpoints)" {
+        return Some(Vec::new());
+    }
+    let mut activations = Vec::new();
+    for step in key.split(" → ") {
+        if step.starts_with('⟨') && step.ends_with('⟩') {
+            continue;
+        }
+        let Some(name) = step.strip_prefix('[') else {
+            if step.is_empty() || step.contains(['[', ']']) {
+                return None;
+            }
+            continue;
+        };
+        let name = name.strip_suffix(']')?;
+        let name = name
+            .split_once('=')
+            .or_else(|| name.split_once(" after "))
+            .map_or(name, |(name, _)| name);
+        if name.is_empty() {
+            return None;
+        }
+        activations.push(name.to_string());
+    }
+    Some(activations)
+}
+
+/// Load known failures, treating missing file as empty
+pub fn load(path: &Path) -> io::Result<BTreeSet<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(parse(&text)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("# comment\n\n[a] → b\n  a → [b]  \n"),
+            BTreeSet::from(["[a] → b".to_string(), "a → [b]".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_activations() {
+        assert_eq!(
+            activations("[open] → write → [fsync]"),
+            Some(vec!["open".into(), "fsync".into()])
+        );
+        assert_eq!(
+            activations("{mode=fast} ⟨ready⟩ → [read=short] → [retry after read] → close"),
+            Some(vec!["read".into(), "retry".into()])
+        );
+        assert_eq!(activations("(no failpoints)"), Some(vec![]));
+        assert_eq!(activations("[open → write"), None);
+        assert_eq!(activations("open →  → write"), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let text = render(["b", "[a]", "b"]);
+        assert_eq!(text, "# paths expected to fail, one per line\n[a]\nb\n");
+        assert_eq!(parse(&text), BTreeSet::from(["[a]".into(), "b".into()]));
+    }
+}
@@ -76,14 +76,18 @@
 //! }
 //! ```
 //!
-//! Implement setup code and check, and you can test it:
+//! Implement setup code and check, and you can test it. A regular test
+//! would use `Runner::run()`, which fails here, as the implementation
+//! is invalid; let's instead assert that the failure is found with
+//! `Runner::run_expect_failure()`, and see on which paths:
 //!
-//! ```should_panic
+//! ```
 //! # use std::path::Path;
-//! # use std::fs::{File,read_to_string};
+//! # use std::fs::File;
 //! # use std::io::{self, Write};
 //! # use faine::inject_return;
 //! use faine::Runner;
+//! use faine::props::atomic_replace;
 //! # fn replace_file(path: &Path, content: &str) -> io::Result<()> {
 //! #     inject_return!("create new file", Err(io::Error::other("injected error")));
 //! #     let mut file = File::create(path)?;
@@ -93,23 +97,21 @@
 //! # }
 //! #[test]
 //! # fn dummy() {}
-//! fn test_replace_file_is_atomic() {
-//!     faine::Runner::default().run(|| {
+//! fn test_replace_file_is_not_atomic() {
+//!     let found = Runner::default().run_expect_failure(|| {
 //!         // prepare filesystem state for testing
 //!         let tempdir = tempfile::tempdir().unwrap();
 //!         let path = tempdir.path().join("myfile");
 //!         File::create(&path).unwrap().write_all(b"old").unwrap();
 //!         // run the tested code
 //!         let res = replace_file(&path, "new");
-//!         // check resulting filesystem state
-//!         let contents = read_to_string(path).unwrap();
-//!         assert!(
-//!            res.is_ok() && contents == "new" ||
-//!            res.is_err() && contents == "old"
-//!         ); // fires!
+//!         // check resulting filesystem state: "new" if succeeded, "old" otherwise
+//!         atomic_replace(&path, b"old", b"new", &res).unwrap(); // fires!
 //!     }).unwrap();
+//!     // file is truncated if writing fails
+//!     assert!(found.paths().iter().any(|path| path.activated("write new file")));
 //! }
-//! # test_replace_file_is_atomic();
+//! # test_replace_file_is_not_atomic();
 //! ```
 //!
 //! <details>
@@ -117,9 +119,10 @@
 //!
 //! ```
 //! # use std::path::{Path, PathBuf};
-//! # use std::fs::{File,read_to_string, rename};
+//! # use std::fs::{File, rename};
 //! # use std::io::{self, Write};
 //! use faine::{Runner, inject_return};
+//! use faine::props::atomic_replace;
 //! # // XXX: hack to provide clean and correct test code, until
 //! # // `path_add_extension` gets into all supported rust versions
 //! # struct MyPath<'a> {
@@ -154,11 +157,7 @@
 //!         let path = tempdir.path().join("myfile");
 //!         File::create(&path).unwrap().write_all(b"old").unwrap();
 //!         let res = replace_file(&path, "new");
-//!         let contents = read_to_string(path).unwrap();
-//!         assert!(
-//!            res.is_ok() && contents == "new" ||
-//!            res.is_err() && contents == "old"
-//!         ); // now OK!
+//!         atomic_replace(&path, b"old", b"new", &res).unwrap(); // now OK!
 //!     }).unwrap();
 //! }
 //! # test_replace_file_is_atomic();
@@ -217,6 +216,58 @@
 //! }
 //! ```
 //!
+//! Results of fallible operations may also be instrumented with
+//! [`ResultExt`] methods, which read naturally along with `?`:
+//!
+//! ```no_run
+//! # use std::io;
+//! # use std::fs::File;
+//! use faine::ResultExt;
+//!
+//! let f = File::open("foo").faine("failpoint name")?;
+//! let f = File::open("foo").faine_with("failpoint name", || io::Error::other("injected error"))?;
+//! # Ok::<(), io::Error>(())
+//! ```
+//!
+//! Failpoint names may be split into a component and a point, which
+//! produces `component/point` name. Runner methods which take failpoint
+//! names also accept [`Component`] selectors, and [`Report::components()`]
+//! rolls up statistics by component:
+//!
+//! ```
+//! # use std::io;
+//! # use faine::{Component, Runner, inject_return_io_error};
+//! fn append() -> io::Result<()> {
+//!     inject_return_io_error!(component: "wal", point: "append");
+//!     inject_return_io_error!(component: "wal", point: "fsync");
+//!     inject_return_io_error!(component: "index", point: "flush");
+//!     Ok(())
+//! }
+//!
+//! let report = Runner::default()
+//!     .with_independent_groups(&[&[Component("wal")], &[Component("index")]])
+//!     .run(|| { let _ = append(); })
+//!     .unwrap();
+//! assert_eq!(report.components()["wal"].failpoints.len(), 2);
+//! ```
+//!
+//! # Checking injected errors
+//!
+//! Errors produced by `*_io_error` macros carry `faine::InjectedError` marker,
+//! so tests may check that an error was actually injected, and not caused by
+//! genuine environmental failure:
+//!
+//! ```
+//! # use std::io;
+//! # use faine::{assert_injected_by, assert_not_injected};
+//! # let res: io::Result<()> = Ok(());
+//! # if false {
+//! assert_injected_by!(res, "create temp file");
+//! assert_injected_by!(res, prefix = "create");
+//! assert_not_injected!(res);
+//! # }
+//! ```
+//!
 //! # Executing the instrumented code
 //!
 //! In the test, just construct a default `Runner` and call its `run()` method
@@ -234,6 +285,192 @@
 //! }
 //! ```
 //!
+//! `run()` returns a [`Report`] describing explored paths, which may be
+//! examined or printed (use alternate form, `{:#}`, to list all paths).
+//!
+//! The tested code is run many times, so state it captures and modifies
+//! leaks between iterations, making them behave differently depending on
+//! the order of exploration. Make such state explicit with `run_with_state()`,
+//! which hands each iteration a fresh clone, and collect results across
+//! iterations with an accumulator:
+//!
+//! ```
+//! # use std::io;
+//! # use faine::{Runner, accumulate, inject_return_io_error};
+//! # fn save(data: &mut Vec<u8>) -> io::Result<()> {
+//! #     inject_return_io_error!("write");
+//! #     data.push(1);
+//! #     Ok(())
+//! # }
+//! let report = Runner::default()
+//!     .with_accumulator(0usize)
+//!     .run_with_state(Vec::new(), |data| {
+//!         if save(data).is_err() {
+//!             assert!(data.is_empty());
+//!             accumulate(|failures: &mut usize| *failures += 1);
+//!         }
+//!     })
+//!     .unwrap();
+//! assert_eq!(report.accumulator::<usize>(), Some(&1));
+//! ```
+//!
+//! Instead of listing expected outcomes per scenario, tests may derive
+//! them from faults injected in the current iteration, which are
+//! available with `current_path()` and `activated_names()`. For the
+//! atomic file replacement above, the file only changes if nothing
+//! has failed:
+//!
+//! ```
+//! # use std::path::Path;
+//! # use std::fs::{File, read_to_string, rename};
+//! # use std::io::{self, Write};
+//! # use faine::{Runner, activated_names, inject_return_io_error};
+//! # fn replace_file(path: &Path, content: &str) -> io::Result<()> {
+//! #     let temp_path = path.with_extension("tmp");
+//! #     {
+//! #         inject_return_io_error!("create temp file");
+//! #         let mut file = File::create(&temp_path)?;
+//! #         inject_return_io_error!("write temp file");
+//! #         file.write_all(content.as_bytes())?;
+//! #     }
+//! #     inject_return_io_error!("replace file");
+//! #     rename(&temp_path, path)?;
+//! #     Ok(())
+//! # }
+//! Runner::default().run(|| {
+//!     let tempdir = tempfile::tempdir().unwrap();
+//!     let path = tempdir.path().join("myfile");
+//!     std::fs::write(&path, "old").unwrap();
+//!     let _ = replace_file(&path, "new");
+//!     let expected = if activated_names().is_empty() { "new" } else { "old" };
+//!     assert_eq!(read_to_string(&path).unwrap(), expected);
+//! }).unwrap();
+//! ```
+//!
+//! `faine::golden` module records which outcome each failpoint leads to
+//! when activated alone, as a reviewable snapshot of fault behavior.
+//!
+//! `faine::recorder` module runs the code once without activating any
+//! failpoints, and checks the order of visited failpoints against a
+//! script, turning instrumentation into an order of operations assertion.
+//!
+//! Tested code which uses randomness should draw it from `faine::rng()`
+//! instead of `rand::rng()`, so it stays deterministic under exploration;
+//! with `rand` feature, the generator implements `rand_core::RngCore`.
+//!
+//! With `rayon` feature, parallel sections of the tested code wrapped
+//! into `deterministic_parallel()` run sequentially under a runner, so
+//! failpoints visited in `rayon` parallel iterators are explored.
+//!
+//! With `testsupport` feature, `faine::testsupport` module provides helpers for
+//! common assertions on exploration results.
+//!
+//! With `macros` feature, `#[faine::wrap_trait]` attribute generates
+//! wrappers which inject failures at trait boundaries, and
+//! `#[faine::fake_impl]` generates fake implementations of traits for
+//! testing code which depends on them.
+//!
+//! With `async` feature, `inject_override_async!` and
+//! `inject_override_async_io_error!` macros wrap futures, so the
+//! `.await` stays outside of them.
+//!
+//! With `audit` feature, values injected by failpoints may be audited
+//! for injecting a success where a failure was likely intended, see
+//! `Runner::with_audit_injections()`, and for being the same on all
+//! activations, see `Runner::with_audit_injection_purity()`.
+//!
+//! With `fail-compat` feature, code instrumented with `fail::fail_point!`
+//! may be explored after switching to `faine::fail_compat::fail_point!`.
+//!
+//! With `log` feature, failing paths may be re-run with raised log
+//! verbosity, see `Runner::with_failure_log_level()`; `tracing` feature
+//! adds the same for `tracing` subscribers.
+//!
+//! With `tempdir` feature, runners may provide each iteration with an
+//! empty temporary directory whose path is the same in all iterations,
+//! see `Runner::with_managed_tempdir()`.
+//!
+//! With `ctrlc` feature, long explorations may be stopped with Ctrl-C
+//! while still producing a report, see `Runner::with_graceful_interrupt()`.
+//!
+//! With `capi` feature, failpoints may also be placed into C code linked
+//! into the tested binary, see `faine::capi` module.
+//!
+//! With `fuzz` feature, failpoint decisions may be driven by a
+//! coverage-guided fuzzer such as `cargo-fuzz`, see `faine::fuzz` module.
+//!
+//! With `http-test` feature, `faine::http_test` module provides a blocking
+//! HTTP test server whose responses are dropped, truncated, malformed or
+//! stalled by failpoints, for testing HTTP clients over a real socket.
+//!
+//! With `modelcheck` feature, `faine::modelcheck` module generates random
+//! programs of interdependent failpoints and checks that exploration reaches
+//! the same states as a brute-force enumeration, which is useful for testing
+//! strategies and other features affecting exploration.
+//!
+//! See [`examples/integration`](https://github.com/AMDmi3/faine/tree/master/examples/integration)
+//! for a complete project layout, which keeps `faine` out of production builds.
+//! Such crates may mark themselves with `declare_instrumented!`, so tests
+//! using `assert_instrumented!` fail to compile if the feature which
+//! compiles failpoints in is not enabled.
+//!
+//! # Migrating existing tests
+//!
+//! An existing test is converted by moving its body into a plain
+//! function explored with [`explore()`], or [`explore_result()`] if it
+//! returns `Result`, where an `Err` fails the path. With `macros`
+//! feature, [`#[faine::test]`](macro@test) does the same in place of
+//! `#[test]`. Assertions which do not hold under some faults fail the
+//! test with the failing path and the panic message.
+//!
+//! ```
+//! # use std::io;
+//! # fn load() -> io::Result<Vec<u8>> {
+//! #     faine::inject_return_io_error!("read");
+//! #     Ok(vec![1])
+//! # }
+//! // was: #[test] fn test_load() { let _ = load(); }
+//! #[test]
+//! # fn dummy1() {}
+//! fn test_load() {
+//!     faine::explore(test_load_impl)
+//! }
+//!
+//! fn test_load_impl() {
+//!     let _ = load();
+//! }
+//!
+//! // was: #[test] fn test_load_result() -> io::Result<()> { ... }
+//! #[test]
+//! # fn dummy2() {}
+//! fn test_load_result() {
+//!     faine::explore_result(test_load_result_impl)
+//! }
+//!
+//! fn test_load_result_impl() -> io::Result<()> {
+//!     if let Ok(data) = load() {
+//!         assert_eq!(data, [1]);
+//!     }
+//!     Ok(())
+//! }
+//!
+//! // was: #[test] fn test_load_unwrap() { assert_eq!(load().unwrap(), [1]); }
+//! // now fails, as `load()` fails on a path where `read` is activated
+//! #[test]
+//! #[should_panic(expected = "failing path: [read]")]
+//! # fn dummy3() {}
+//! fn test_load_unwrap() {
+//!     faine::explore(test_load_unwrap_impl)
+//! }
+//!
+//! fn test_load_unwrap_impl() {
+//!     assert_eq!(load().unwrap(), [1]);
+//! }
+//! # test_load();
+//! # test_load_result();
+//! # assert!(std::panic::catch_unwind(test_load_unwrap).is_err());
+//! ```
+//!
 //! # Controlling behavior
 //!
 //! - You can disable/enable failpoints processing:
@@ -246,6 +483,14 @@
 //!   enable_failpoints(true);
 //!   ```
 //!
+//! - Instrumented code may mark critical sections where faults must
+//!   never be injected, which are closed on any exit from the scope:
+//!
+//!   ```
+//!   let _cs = faine::no_faults();
+//!   // failpoints will be skipped until `_cs` is dropped
+//!   ```
+//!
 //! - `Runner` has some knobs to tune its behavior.
 //!
 //! # Other implementaions of the same concept
@@ -258,19 +503,304 @@
 //! - [failpoints](https://crates.io/crates/failpoints)
 //! - [fault-injection](https://crates.io/crates/fault-injection)
 
+#![allow(clippy::test_attr_in_doctest)] // examples show how tests are written
+
+mod absorption;
+mod adapters;
+mod aliases;
+mod audit;
+mod axes;
+mod blast;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod capture;
+mod checkpoint;
+mod checks;
 mod collections;
 mod common;
+mod counters;
+pub mod coverage;
+mod dedup;
+mod diff;
+pub mod digest;
+mod env;
 mod error;
+mod events;
+mod ext;
+#[cfg(feature = "fail-compat")]
+pub mod fail_compat;
+mod faultmodel;
+mod fnv;
+pub mod for_buildscripts;
+#[cfg(feature = "fs")]
+pub mod fs;
 mod functions;
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod golden;
+mod hitlimits;
+#[cfg(feature = "http-test")]
+pub mod http_test;
+mod hygiene;
+mod injected;
+mod intern;
+#[cfg(feature = "ctrlc")]
+mod interrupt;
+#[cfg(feature = "io")]
+pub mod io;
+mod known;
+#[cfg(feature = "log")]
+mod logging;
 mod macros;
+mod markdown;
+#[cfg(feature = "modelcheck")]
+pub mod modelcheck;
 mod options;
+mod order_fuzz;
+mod outcome;
+mod panics;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod params;
+mod perturb;
+mod plan;
+#[cfg(feature = "process")]
+pub mod process;
+pub mod props;
+mod recent;
+pub mod recorder;
+mod registry;
+mod report;
+mod rng;
 mod runner;
+mod safety;
+mod selector;
+mod shard;
+mod staged;
+mod strategy;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "tempdir")]
+mod tempdir;
+#[cfg(feature = "testsupport")]
+pub mod testsupport;
+mod timeline;
+mod trace;
 mod tree;
+mod verification;
+mod watchdog;
 
 #[doc(hidden)]
 pub mod __private;
 
-pub use common::{Branch, Label};
+pub use absorption::{IntoOutcome, Outcome, record_result};
+pub use adapters::{explore, explore_result};
+pub use audit::InjectedOutcome;
+pub use axes::{AxisBudget, axis};
+pub use blast::BlastRadius;
+pub use capture::CapturedOutput;
+pub use checkpoint::{Checkpoint, CheckpointResult, checkpoint};
+pub use common::{After, Branch, Exhaust, Expect, Label, Phase, Tier, Weight};
+pub use dedup::DedupMode;
+pub use diff::PathDiff;
 pub use error::Error;
-pub use functions::enable_failpoints;
+pub use events::EventSink;
+pub use ext::ResultExt;
+/// Generate a fake implementation of a trait which injects failures
+///
+/// Applied to a trait definition, generates `FaineFake<TraitName>`
+/// struct implementing the trait, for testing code which depends on
+/// it without a real implementor. Each method returning `Result<T, E>`
+/// or `io::Result<T>` gets `on_<method>()` setter for a closure
+/// producing its successful result, and a failpoint named `TraitName::method`, which, when
+/// activated, returns an error converted from [`std::io::Error`]
+/// containing [`InjectedError`] instead, like with
+/// [`wrap_trait`](macro@wrap_trait). Closures are checked against
+/// method return types at compile time, and calling a method with no
+/// value configured panics. Other methods keep their default
+/// implementations if the trait provides them, and panic otherwise.
+///
+/// ```
+/// use std::io;
+///
+/// #[derive(Clone, Debug)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[faine::fake_impl]
+/// trait UserService {
+///     fn fetch_user(&self, id: u64) -> io::Result<User>;
+///     fn ban_user(&self, id: u64) -> io::Result<()>;
+/// }
+///
+/// fn greet(service: &dyn UserService, id: u64) -> String {
+///     match service.fetch_user(id) {
+///         Ok(user) => format!("hello, {}", user.name),
+///         Err(_) => "hello, stranger".to_string(),
+///     }
+/// }
+///
+/// let fake = FaineFakeUserService::new()
+///     .on_fetch_user(|| Ok(User { name: "alice".into() }));
+///
+/// let mut greetings = vec![];
+/// faine::Runner::default()
+///     .run(|| greetings.push(greet(&fake, 1)))
+///     .unwrap();
+/// assert_eq!(greetings, ["hello, stranger", "hello, alice"]);
+/// ```
+///
+/// Closures are not passed method arguments, and are required to be
+/// `Send + Sync`, so fakes may be shared between threads. Associated
+/// types and constants, async methods, methods without `self`
+/// receiver, generic traits and supertraits are not supported and
+/// produce compile errors.
+///
+/// Available with `macros` feature.
+#[cfg(feature = "macros")]
+pub use faine_macros::fake_impl;
+/// Suppress fault injection in a whole function
+///
+/// Applied to a function, opens a critical section with
+/// [`no_faults()`](fn@no_faults) at its entry, which lasts until the
+/// function returns or unwinds.
+///
+/// ```
+/// #[faine::no_faults]
+/// fn record_ground_truth(log: &mut Vec<u32>, value: u32) -> Result<(), ()> {
+///     faine::inject_return!("append", Err(())); // never activated
+///     log.push(value);
+///     Ok(())
+/// }
+/// ```
+///
+/// Async functions are not supported, as the section is tracked per
+/// thread, and produce compile errors.
+///
+/// Available with `macros` feature.
+#[cfg(feature = "macros")]
+pub use faine_macros::no_faults;
+/// Explore a whole test function
+///
+/// Applied to a test function, turns it into a `#[test]` which runs
+/// its body with [`explore()`], or with [`explore_result()`] if it
+/// returns `Result`. Other attributes, such as `#[should_panic]`, are
+/// kept on the test.
+///
+/// ```
+/// # use std::io;
+/// # fn save() -> io::Result<()> {
+/// #     faine::inject_return_io_error!("write");
+/// #     Ok(())
+/// # }
+/// #[faine::test]
+/// fn test_save() -> io::Result<()> {
+///     if let Err(err) = save() {
+///         assert_eq!(err.to_string(), "write");
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// Async and generic functions, and functions with arguments are not
+/// supported and produce compile errors.
+///
+/// Available with `macros` feature.
+#[cfg(feature = "macros")]
+pub use faine_macros::test;
+/// Generate a wrapper which injects failures into trait methods
+///
+/// Applied to a trait definition, generates `FaineWrapped<TraitName>`
+/// newtype, which implements the trait by forwarding all calls to the
+/// wrapped implementor. Methods returning `Result<T, E>` or
+/// `io::Result<T>` are instrumented with a failpoint named `TraitName::method`, which returns an error converted
+/// from [`std::io::Error`] containing [`InjectedError`], so the error type
+/// must implement `From<io::Error>`. Other methods, including ones
+/// returning `fmt::Result` or other aliases named `Result`, are passed
+/// through.
+///
+/// This allows to explore failures at trait boundaries without
+/// instrumenting each implementor.
+///
+/// ```
+/// use std::io;
+///
+/// #[faine::wrap_trait]
+/// trait BlockDevice {
+///     fn read(&self, block: u64) -> io::Result<Vec<u8>>;
+///     fn size(&self) -> u64;
+/// }
+///
+/// struct Disk;
+///
+/// impl BlockDevice for Disk {
+///     fn read(&self, _block: u64) -> io::Result<Vec<u8>> {
+///         Ok(vec![0; 512])
+///     }
+///
+///     fn size(&self) -> u64 {
+///         1
+///     }
+/// }
+///
+/// let device: Box<dyn BlockDevice> = Box::new(FaineWrappedBlockDevice(Disk));
+/// ```
+///
+/// Associated types and constants are forwarded as well. Generic and
+/// async methods, methods without `self` receiver, generic traits and
+/// supertraits are not supported and produce compile errors:
+///
+/// ```compile_fail
+/// #[faine::wrap_trait]
+/// trait Fetcher {
+///     async fn fetch(&self) -> std::io::Result<Vec<u8>>; // async methods are not supported
+/// }
+/// ```
+///
+/// Available with `macros` feature.
+#[cfg(feature = "macros")]
+pub use faine_macros::wrap_trait;
+pub use faultmodel::{Bernoulli, FaultModel, Markov};
+pub use functions::{
+    accumulate, activated_names, captured_stderr, captured_stdout, clear_condition,
+    current_iteration, current_path, current_phase, enable_failpoints, is_active,
+    iteration_timed_out, no_faults, record_outcome,
+};
+#[cfg(feature = "async")]
+pub use future::OverrideFuture;
+pub use hitlimits::HitLimits;
+pub use hygiene::{Hygiene, Suggestion};
+pub use injected::{ErrorTag, InjectedError};
+pub use intern::{FailpointHandle, FailpointId};
+#[cfg(feature = "ctrlc")]
+pub use interrupt::interrupt;
+pub use markdown::MarkdownOptions;
+pub use order_fuzz::OrderRepro;
+pub use outcome::RunOutcome;
+pub use panics::CaughtPanic;
+#[cfg(feature = "rayon")]
+pub use parallel::deterministic_parallel;
+pub use params::param_f64;
+pub use perturb::{Perturb, Perturbable};
+pub use plan::PlannedPath;
+pub use recent::{RecentEvent, RecentEventKind, RecentEvents};
+pub use recorder::{Recorder, Trace};
+pub use registry::{ActiveRunner, active_runners};
+pub use report::{
+    Absorption, ComponentStats, CounterStats, ExecutionPath, FailureClass, FailureCorrelation,
+    FoundFailure, ImpureInjection, InjectionAudit, OrderSensitivity, Report, Reruns, Step,
+};
+pub use rng::{FaineRng, rng};
 pub use runner::Runner;
+pub use safety::{SafetyLimits, SafetyValve};
+pub use selector::{Component, Prefix, Selector};
+pub use shard::Shard;
+pub use staged::{Stage, StagedReport};
+pub use strategy::{FaultRate, PhaseStats, Rng, Schedule, Strategy};
+#[cfg(feature = "tempdir")]
+pub use tempdir::tempdir;
+pub use timeline::{Timeline, TimelineSample};
+pub use verification::{ReplayDivergence, SelfVerification};
@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::{Mutex, MutexGuard};
+
+use log::LevelFilter;
+
+#[cfg(feature = "tracing")]
+use tracing_subscriber::filter::LevelFilter as TracingLevelFilter;
+
+/// Replacement of a `tracing` level filter, returning the previous one
+#[cfg(feature = "tracing")]
+pub type TracingReload =
+    std::sync::Arc<dyn Fn(TracingLevelFilter) -> Option<TracingLevelFilter> + Send + Sync>;
+
+/// Serializes verbose re-runs, as log levels are process-global, and
+/// concurrent runners would otherwise restore each other's levels
+static VERBOSE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Log verbosity for re-runs of failing paths
+///
+/// See [`Runner::with_failure_log_level()`](crate::Runner::with_failure_log_level).
+#[derive(Clone)]
+pub struct FailureLogging {
+    pub level: LevelFilter,
+    #[cfg(feature = "tracing")]
+    pub tracing_reload: Option<TracingReload>,
+}
+
+impl Default for FailureLogging {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Trace,
+            #[cfg(feature = "tracing")]
+            tracing_reload: None,
+        }
+    }
+}
+
+impl FailureLogging {
+    /// Raise log levels to at least the configured one, until the
+    /// returned guard is dropped
+    pub fn raise(&self) -> VerboseLogging {
+        let lock = VERBOSE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let log_level = log::max_level();
+        log::set_max_level(log_level.max(self.level));
+        VerboseLogging {
+            _lock: lock,
+            log_level,
+            #[cfg(feature = "tracing")]
+            tracing_level: self.tracing_reload.as_ref().and_then(|reload| {
+                let level = to_tracing(self.level);
+                let previous = reload(level)?;
+                if previous > level {
+                    reload(previous);
+                }
+                Some((reload.clone(), previous))
+            }),
+        }
+    }
+}
+
+/// Log levels raised for the duration of an iteration, restored on drop
+pub struct VerboseLogging {
+    _lock: MutexGuard<'static, ()>,
+    log_level: LevelFilter,
+    #[cfg(feature = "tracing")]
+    tracing_level: Option<(TracingReload, TracingLevelFilter)>,
+}
+
+impl Drop for VerboseLogging {
+    fn drop(&mut self) {
+        log::set_max_level(self.log_level);
+        #[cfg(feature = "tracing")]
+        if let Some((reload, previous)) = self.tracing_level.take() {
+            reload(previous);
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn to_tracing(level: LevelFilter) -> TracingLevelFilter {
+    match level {
+        LevelFilter::Off => TracingLevelFilter::OFF,
+        LevelFilter::Error => TracingLevelFilter::ERROR,
+        LevelFilter::Warn => TracingLevelFilter::WARN,
+        LevelFilter::Info => TracingLevelFilter::INFO,
+        LevelFilter::Debug => TracingLevelFilter::DEBUG,
+        LevelFilter::Trace => TracingLevelFilter::TRACE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raise() {
+        let logging = FailureLogging {
+            level: LevelFilter::Debug,
+            #[cfg(feature = "tracing")]
+            tracing_reload: None,
+        };
+        let previous = log::max_level();
+        {
+            let _verbose = logging.raise();
+            assert_eq!(log::max_level(), previous.max(LevelFilter::Debug));
+        }
+        assert_eq!(log::max_level(), previous);
+    }
+}
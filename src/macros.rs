@@ -1,36 +1,154 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-/// Define failpoint which returns from an enclosing function
+/// Location of macro invocation in the source code
+#[doc(hidden)]
 #[macro_export]
-macro_rules! inject_return {
-    ($ret:expr) => {{
-        const NAME: &str = concat!(file!(), ":", line!(), ":", column!());
-        let mut branch = $crate::Branch::Skip;
-        $crate::__private::FAILPOINTS.with_borrow_mut(|state| {
-            if let Some(state) = state
-                && state.enabled
-            {
-                branch = state.tree.visit($crate::Label::Failpoint(NAME));
-            }
-        });
-        match branch {
+macro_rules! __location {
+    () => {
+        concat!(file!(), ":", line!(), ":", column!())
+    };
+}
+
+/// Visit a failpoint and return a [`Branch`](crate::Branch) to follow
+///
+/// Not a public API, used by all `inject_*` macros. Unnamed failpoints
+/// are identified by a unique static defined in each expansion, so two
+/// failpoints which share an autogenerated name (which happens when
+/// they come from the same invocation of an user macro) are still
+/// treated as distinct ones.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __visit {
+    () => {{
+        static SITE: $crate::__private::Site = $crate::__private::Site::new($crate::__location!());
+        $crate::__private::visit($crate::Label::Site(&SITE), SITE.name())
+    }};
+    ($name:expr, requires_activated: $prerequisite:expr) => {{
+        $crate::__private::visit_requiring(
+            $crate::__failpoint_id!($name),
+            $crate::__location!(),
+            $crate::__failpoint_id!($prerequisite),
+        )
+    }};
+    ($name:expr, trigger: $trigger:expr) => {{
+        static TRIGGER_CACHE: $crate::__private::FailpointIdCache =
+            $crate::__private::FailpointIdCache::new(::core::option_env!("CARGO_PKG_NAME"));
+        $crate::__private::visit_triggered(
+            $crate::__failpoint_id!($name),
+            $crate::__location!(),
+            $trigger,
+            &TRIGGER_CACHE,
+        )
+    }};
+    ($name:expr, expect: $expect:ident) => {{
+        $crate::__private::visit_expecting(
+            $crate::Label::Failpoint($crate::__failpoint_id!($name)),
+            $crate::__location!(),
+            $crate::Expect::$expect,
+        )
+    }};
+    ($name:expr, weight: $weight:ident) => {{
+        $crate::__private::visit_weighted(
+            $crate::Label::Failpoint($crate::__failpoint_id!($name)),
+            $crate::__location!(),
+            $crate::Weight::$weight,
+        )
+    }};
+    ($name:expr, fails_if: $condition:expr) => {{
+        $crate::__private::visit_conditional(
+            $crate::Label::Failpoint($crate::__failpoint_id!($name)),
+            $crate::__location!(),
+            $condition,
+        )
+    }};
+    ($name:expr) => {{
+        $crate::__private::visit(
+            $crate::Label::Failpoint($crate::__failpoint_id!($name)),
+            $crate::__location!(),
+        )
+    }};
+}
+
+/// Record classification of a value injected by an activated failpoint
+///
+/// Not a public API, used by override macros. Classification uses
+/// [`InjectedOutcome`](crate::InjectedOutcome) if the value implements
+/// it, so the value type must be known at this point. Without `audit`
+/// feature this expands to nothing, so the value type is not
+/// constrained.
+#[cfg(feature = "audit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_injection {
+    ($name:expr, $value:expr, $intentional_success:literal) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{ClassifyInjected as _, ClassifyUnknown as _};
+        $crate::__private::audit_injection(
+            $name,
+            (&$crate::__private::Injected($value)).classify(),
+            $intentional_success,
+        )
+    }};
+}
+
+#[cfg(not(feature = "audit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_injection {
+    ($name:expr, $value:expr, $intentional_success:literal) => {{}};
+}
+
+/// Compare digest of a value injected by an activated failpoint with
+/// the one seen on its first activation
+///
+/// Not a public API, used by `inject_*` macros. Digest uses
+/// [`InjectedOutcome`](crate::InjectedOutcome) or [`Debug`] if the
+/// value implements them, so the value type must be known at this
+/// point. Without `audit` feature this expands to nothing, so the
+/// value type is not constrained.
+#[cfg(feature = "audit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_purity {
+    ($name:expr, $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{
+            DigestDebug as _, DigestOutcome as _, DigestOutcomeDebug as _, DigestUnknown as _,
+        };
+        $crate::__private::audit_purity($name, || {
+            (&&&&$crate::__private::Injected($value)).digest()
+        })
+    }};
+}
+
+#[cfg(not(feature = "audit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_purity {
+    ($name:expr, $value:expr) => {{}};
+}
+
+/// Return a value from an enclosing function if a failpoint is activated
+///
+/// Not a public API, used by `inject_return*` macros. Arguments of
+/// `__visit!` are passed in parentheses; if a failpoint name is given,
+/// the returned value is audited for purity, which is only compiled
+/// in with `audit` feature.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __inject_return {
+    (($($visit:tt)*), $ret:expr, audit: $name:expr) => {{
+        match $crate::__visit!($($visit)*) {
             $crate::Branch::Activate => {
-                return $ret;
+                #[allow(clippy::unused_unit)]
+                return $crate::__audit_return!($name, $ret);
             }
             $crate::Branch::Skip => {}
         }
     }};
-    ($name:expr, $ret:expr) => {{
-        let mut branch = $crate::Branch::Skip;
-        $crate::__private::FAILPOINTS.with_borrow_mut(|state| {
-            if let Some(state) = state
-                && state.enabled
-            {
-                branch = state.tree.visit($crate::Label::Failpoint($name));
-            }
-        });
-        match branch {
+    (($($visit:tt)*), $ret:expr) => {{
+        match $crate::__visit!($($visit)*) {
             $crate::Branch::Activate => {
                 return $ret;
             }
@@ -39,15 +157,459 @@ macro_rules! inject_return {
     }};
 }
 
+/// Audit a value returned by an activated failpoint for purity
+///
+/// Not a public API, used by `__inject_return!`. The value is passed
+/// through [`inspect()`](crate::__private::inspect), so its type is
+/// still inferred from the return type of the enclosing function.
+#[cfg(feature = "audit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_return {
+    ($name:expr, $ret:expr) => {
+        $crate::__private::inspect($ret, |value| $crate::__audit_purity!($name, value))
+    };
+}
+
+#[cfg(not(feature = "audit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_return {
+    ($name:expr, $ret:expr) => {
+        $ret
+    };
+}
+
+/// Resolve failpoint name or [`FailpointId`](crate::FailpointId) into the latter
+///
+/// Not a public API, used by `__visit!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __failpoint_id {
+    ($name:expr) => {{
+        static CACHE: $crate::__private::FailpointIdCache =
+            $crate::__private::FailpointIdCache::new(::core::option_env!("CARGO_PKG_NAME"));
+        $crate::__private::FailpointRef::failpoint_id($name, &CACHE)
+    }};
+}
+
+/// Resolve failpoint name into [`FailpointId`](crate::FailpointId) specialized for a type
+///
+/// Not a public API, used by `inject_*` macros with `per_type:` argument.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __per_type_id {
+    ($name:expr, $ty:ty) => {{
+        static PER_TYPE_CACHE: $crate::__private::PerTypeIdCache =
+            $crate::__private::PerTypeIdCache::new();
+        PER_TYPE_CACHE.resolve(
+            $crate::__failpoint_id!($name),
+            ::core::any::type_name::<$ty>(),
+        )
+    }};
+}
+
+/// Intern a failpoint name once per call site
+///
+/// Produces [`FailpointId`](crate::FailpointId), which may be passed
+/// to `inject_*` macros in place of a string name. The name is
+/// interned on first use only, so this is cheap to use in hot code.
+///
+/// ```
+/// # use faine::{intern, inject_return};
+/// fn foo() -> Result<(), ()> {
+///     inject_return!(intern!("foo"), Err(()));
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! intern {
+    ($name:literal) => {{
+        static ID: ::std::sync::OnceLock<$crate::FailpointId> = ::std::sync::OnceLock::new();
+        *ID.get_or_init(|| {
+            $crate::__private::intern_from(::core::option_env!("CARGO_PKG_NAME"), $name)
+        })
+    }};
+}
+
+/// Declare typed failpoint handles
+///
+/// Each declaration makes a constant [`FailpointHandle`](crate::FailpointHandle)
+/// with given visibility and name, which may be used wherever a
+/// failpoint name is accepted, so typos are caught by the compiler.
+/// Declarations may be grouped into a named set, which also makes a
+/// constant slice of all handles in it, to be passed to
+/// [`Runner::with_expected_failpoints()`](crate::Runner::with_expected_failpoints),
+/// so failpoints declared later are checked without listing them in
+/// each test:
+///
+/// ```
+/// # use std::io;
+/// use faine::{Runner, failpoints, inject_return_io_error};
+///
+/// failpoints! {
+///     /// Failpoints of temporary file handling
+///     pub TEMP_FILE {
+///         /// Creation of a temporary file
+///         pub CREATE_TEMP_FILE = "create temp file";
+///         pub WRITE_TEMP_FILE = "write temp file";
+///     }
+/// }
+///
+/// fn save() -> io::Result<()> {
+///     inject_return_io_error!(CREATE_TEMP_FILE);
+///     inject_return_io_error!(WRITE_TEMP_FILE);
+///     Ok(())
+/// }
+///
+/// Runner::ignore_env()
+///     .with_expected_failpoints(TEMP_FILE)
+///     .with_priority_paths(&[&[WRITE_TEMP_FILE.name()]])
+///     .run(|| {
+///         let _ = save();
+///     })
+///     .unwrap();
+/// ```
+///
+/// Handles may be declared without a set as well:
+///
+/// ```
+/// faine::failpoints! {
+///     pub CREATE_TEMP_FILE = "create temp file";
+/// }
+/// ```
+#[macro_export]
+macro_rules! failpoints {
+    ($(#[$set_attr:meta])* $set_vis:vis $set:ident { $($(#[$attr:meta])* $vis:vis $ident:ident = $name:literal;)* }) => {
+        $crate::failpoints! { $($(#[$attr])* $vis $ident = $name;)* }
+
+        $(#[$set_attr])*
+        $set_vis const $set: &[$crate::FailpointHandle] = &[$($ident),*];
+    };
+    ($($(#[$attr:meta])* $vis:vis $ident:ident = $name:literal;)*) => {
+        $(
+            $(#[$attr])*
+            $vis const $ident: $crate::FailpointHandle =
+                $crate::FailpointHandle::new(::core::option_env!("CARGO_PKG_NAME"), $name);
+        )*
+    };
+}
+
+/// Define failpoint which returns from an enclosing function
+///
+/// With `requires_activated:` argument, the failpoint is only
+/// activated on paths where the named prerequisite failpoint was
+/// activated before, which is useful to model failures in error
+/// handlers (for instance, cleanup failing after the primary
+/// operation has failed).
+///
+/// With `trigger:` argument, the failpoint may only be activated on
+/// its first visit following each activation of the trigger failpoint
+/// on the current path, which models cascading failures (for instance,
+/// a disk gone bad failing fsync which follows a failed write). Both
+/// branches are explored on that visit; other visits always skip the
+/// failpoint and are not recorded in the path. The path shows this as
+/// `[write block] → [fsync after write block]`.
+///
+/// Instead of a name, `component:` and `point:` arguments may be
+/// given, which makes a failpoint named `component/point` belonging
+/// to the component (see [`Component`](crate::Component)). All other
+/// `inject_*` macros accept these in place of a name as well.
+///
+/// ```
+/// # use std::io;
+/// # use faine::{inject_override_io_error, inject_return_io_error};
+/// fn append(record: &[u8]) -> io::Result<usize> {
+///     inject_return_io_error!(component: "wal", point: "append");
+///     inject_override_io_error!(Ok(record.len()), component: "wal", point: "fsync")
+/// }
+/// ```
+///
+/// ```
+/// # use std::io;
+/// # use faine::{After, Runner, inject_return_io_error};
+/// fn write_and_sync() -> io::Result<()> {
+///     // write error is lost, as with buffered writes
+///     let _ = (|| -> io::Result<()> {
+///         inject_return_io_error!("write block");
+///         Ok(())
+///     })();
+///     inject_return_io_error!("fsync", trigger: After::Activation("write block"));
+///     Ok(())
+/// }
+///
+/// let report = Runner::ignore_env()
+///     .run(|| {
+///         let _ = write_and_sync();
+///     })
+///     .unwrap();
+/// let paths: Vec<String> = report.paths().iter().map(|path| path.key()).collect();
+/// assert_eq!(
+///     paths,
+///     ["[write block] → [fsync after write block]", "[write block] → fsync", "write block"]
+/// );
+/// ```
+///
+/// With `expect: Activated` argument, the site declares that the
+/// failpoint is expected to be activated by tests which reach it,
+/// which is verified by runners with
+/// [`with_enforce_site_expectations()`](crate::Runner::with_enforce_site_expectations),
+/// and is recorded into coverage records (see [`coverage`](crate::coverage)).
+///
+/// ```
+/// # use std::io;
+/// # use faine::inject_return_io_error;
+/// fn append(record: &[u8]) -> io::Result<()> {
+///     inject_return_io_error!("wal append", expect: Activated);
+///     Ok(())
+/// }
+/// ```
+///
+/// With `weight:` argument, the site declares importance of the
+/// failpoint, one of [`Weight`](crate::Weight) variants (`Normal` by
+/// default). Weights only matter when exploration is limited with
+/// [`with_max_iterations()`](crate::Runner::with_max_iterations) or
+/// [`with_max_duration()`](crate::Runner::with_max_duration): paths
+/// are then explored in order of the lowest weight of failpoints
+/// activated on them, so activations of `Low` failpoints are the
+/// ones left unexplored when the budget runs out (see
+/// [`Report::unexplored_slots()`](crate::Report::unexplored_slots)).
+///
+/// ```
+/// # use std::io;
+/// # use faine::inject_return_io_error;
+/// fn commit() -> io::Result<()> {
+///     inject_return_io_error!("fsync journal", weight: High);
+///     inject_return_io_error!("update stats", weight: Low);
+///     Ok(())
+/// }
+/// ```
+///
+/// With `per_type:` argument, the failpoint is distinct for each
+/// given type, which is useful in generic functions, as otherwise all
+/// their instantiations share a failpoint. The type, as given by
+/// [`std::any::type_name()`], is appended to the name, as in
+/// `flush<mycrate::Disk>`. Runner methods which take failpoint names
+/// accept either this full name, or the base name `flush`, which
+/// refers to all instantiations. Unnamed failpoints may use this as
+/// well, with their location as a base name.
+///
+/// ```
+/// # use std::io;
+/// # use faine::inject_return_io_error;
+/// trait Backend {}
+///
+/// fn flush<T: Backend>(_backend: &T) -> io::Result<()> {
+///     inject_return_io_error!("flush", per_type: T);
+///     Ok(())
+/// }
+/// ```
+///
+/// The macro evaluates to `()`, so it may be used in expression
+/// position, such as a `match` arm, and names defined by its
+/// expansion do not leak into the surrounding scope:
+///
+/// ```compile_fail
+/// fn load() -> Result<usize, ()> {
+///     faine::inject_return!(Err(()));
+///     // error[E0425]: cannot find value `SITE` in this scope
+///     Ok(SITE.name().len())
+/// }
+/// ```
+///
+/// ```compile_fail
+/// fn load() -> Result<usize, ()> {
+///     // error[E0308]: mismatched types
+///     let len: usize = faine::inject_return!(Err(()));
+///     Ok(len)
+/// }
+/// ```
+#[macro_export]
+macro_rules! inject_return {
+    (component: $component:expr, point: $point:expr, $($rest:tt)*) => {{
+        $crate::inject_return!(($component, $point), $($rest)*)
+    }};
+    ($name:expr, $ret:expr, per_type: $ty:ty $(,)?) => {{
+        let id = $crate::__per_type_id!($name, $ty);
+        $crate::__inject_return!(
+            (id),
+            $ret,
+            audit: $crate::__private::FailpointRef::failpoint_name(id)
+        )
+    }};
+    ($ret:expr, per_type: $ty:ty $(,)?) => {{
+        $crate::inject_return!($crate::__location!(), $ret, per_type: $ty)
+    }};
+    ($name:expr, $ret:expr, requires_activated: $prerequisite:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, requires_activated: $prerequisite),
+            $ret,
+            audit: $crate::__private::FailpointRef::failpoint_name(name)
+        )
+    }};
+    ($name:expr, $ret:expr, trigger: $trigger:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, trigger: $trigger),
+            $ret,
+            audit: $crate::__private::FailpointRef::failpoint_name(name)
+        )
+    }};
+    ($name:expr, $ret:expr, expect: $expect:ident $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, expect: $expect),
+            $ret,
+            audit: $crate::__private::FailpointRef::failpoint_name(name)
+        )
+    }};
+    ($name:expr, $ret:expr, weight: $weight:ident $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, weight: $weight),
+            $ret,
+            audit: $crate::__private::FailpointRef::failpoint_name(name)
+        )
+    }};
+    ($ret:expr) => {{
+        $crate::__inject_return!((), $ret, audit: $crate::__location!())
+    }};
+    ($name:expr, $ret:expr) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name),
+            $ret,
+            audit: $crate::__private::FailpointRef::failpoint_name(name)
+        )
+    }};
+}
+
 /// Define failpoint which returns [`std::io::Error`] from an enclosing function
 #[macro_export]
 macro_rules! inject_return_io_error {
+    (component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_return_io_error!(($component, $point) $(, $($rest)*)?)
+    }};
     () => {{
-        const NAME: &str = concat!(file!(), ":", line!(), ":", column!());
-        $crate::inject_return!(NAME, Err(std::io::Error::other(NAME)));
+        $crate::__inject_return!(
+            (),
+            Err(std::io::Error::other($crate::__private::injected_error($crate::__location!())))
+        )
     }};
-    ($name:literal) => {{
-        $crate::inject_return!($name, Err(std::io::Error::other($name)));
+    (per_type: $ty:ty $(, kind: $kind:expr)? $(,)?) => {{
+        $crate::inject_return_io_error!($crate::__location!(), per_type: $ty $(, kind: $kind)?)
+    }};
+    ($name:expr, per_type: $ty:ty $(,)?) => {{
+        $crate::inject_return_io_error!($name, per_type: $ty, kind: std::io::ErrorKind::Other)
+    }};
+    ($name:expr, per_type: $ty:ty, kind: $kind:expr $(,)?) => {{
+        let id = $crate::__per_type_id!($name, $ty);
+        $crate::__inject_return!(
+            (id),
+            Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(id))))
+        )
+    }};
+    ($name:expr) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name),
+            Err(std::io::Error::other($crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))))
+        )
+    }};
+    ($name:expr, kind: $kind:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name),
+            Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))))
+        )
+    }};
+    ($name:expr, fails_if: $condition:expr $(,)?) => {{
+        $crate::inject_return_io_error!($name, fails_if: $condition, kind: std::io::ErrorKind::Other)
+    }};
+    ($name:expr, requires_activated: $prerequisite:expr $(,)?) => {{
+        $crate::inject_return_io_error!(
+            $name,
+            requires_activated: $prerequisite,
+            kind: std::io::ErrorKind::Other
+        )
+    }};
+    ($name:expr, requires_activated: $prerequisite:expr, kind: $kind:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, requires_activated: $prerequisite),
+            Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))))
+        )
+    }};
+    ($name:expr, trigger: $trigger:expr $(,)?) => {{
+        $crate::inject_return_io_error!($name, trigger: $trigger, kind: std::io::ErrorKind::Other)
+    }};
+    ($name:expr, expect: $expect:ident $(,)?) => {{
+        $crate::inject_return_io_error!($name, expect: $expect, kind: std::io::ErrorKind::Other)
+    }};
+    ($name:expr, expect: $expect:ident, kind: $kind:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, expect: $expect),
+            Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))))
+        )
+    }};
+    ($name:expr, weight: $weight:ident $(,)?) => {{
+        $crate::inject_return_io_error!($name, weight: $weight, kind: std::io::ErrorKind::Other)
+    }};
+    ($name:expr, weight: $weight:ident, kind: $kind:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, weight: $weight),
+            Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))))
+        )
+    }};
+    ($name:expr, trigger: $trigger:expr, kind: $kind:expr $(,)?) => {{
+        let name = $name;
+        $crate::__inject_return!(
+            (name, trigger: $trigger),
+            Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))))
+        )
+    }};
+    ($name:expr, fails_if: $condition:expr, kind: $kind:expr $(,)?) => {{
+        let name = $name;
+        match $crate::__visit!(name, fails_if: $condition) {
+            $crate::Branch::Activate => {
+                return Err(std::io::Error::new($kind, $crate::__private::injected_error($crate::__private::FailpointRef::failpoint_name(name))));
+            }
+            $crate::Branch::Skip => {}
+        }
+    }};
+}
+
+/// Define failpoint which sets a named condition
+///
+/// When activated, sets a condition which makes all failpoints
+/// declared as sensitive to it (with `fails_if:` argument) fail
+/// unconditionally while it holds, without visiting them. This
+/// models sustained, correlated failures (such as disk being full)
+/// with a single decision point in the explored tree.
+///
+/// Scope defaults to [`Exhaust::UntilIterationEnd`](crate::Exhaust).
+///
+/// ```
+/// # use std::io::{self, ErrorKind};
+/// # use faine::{Exhaust, inject_exhaust, inject_return_io_error};
+/// fn write_blocks() -> io::Result<()> {
+///     inject_exhaust!("disk full", scope: Exhaust::UntilCleared);
+///     inject_return_io_error!("write block 1", fails_if: "disk full", kind: ErrorKind::StorageFull);
+///     inject_return_io_error!("write block 2", fails_if: "disk full", kind: ErrorKind::StorageFull);
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! inject_exhaust {
+    ($condition:literal $(,)?) => {{ $crate::inject_exhaust!($condition, scope: $crate::Exhaust::UntilIterationEnd) }};
+    ($condition:literal, scope: $scope:expr $(,)?) => {{
+        match $crate::__visit!($condition) {
+            $crate::Branch::Activate => $crate::__private::set_condition($condition, $scope),
+            $crate::Branch::Skip => {}
+        }
     }};
 }
 
@@ -55,36 +617,68 @@ macro_rules! inject_return_io_error {
 ///
 /// When the failpoint is activated, the expression is not executed. If you
 /// want to execute is never the less, use `inject_override_with_side_effect!`
+///
+/// With `audit` feature, overriding values are checked by runners
+/// with `with_audit_injections()` for injecting a success instead of
+/// a failure. With `intentional_success: true` argument, the site
+/// acknowledges that this is intended, which silences the finding.
+///
+/// ```
+/// # use faine::inject_override;
+/// fn read_config() -> Result<Vec<String>, ()> {
+///     // missing config is fine
+///     inject_override!(Ok(vec!["debug".into()]), "read config", Ok(vec![]), intentional_success: true)
+/// }
+/// ```
 #[macro_export]
 macro_rules! inject_override {
-    ($input:expr, $ret:expr) => {{
-        const NAME: &str = concat!(file!(), ":", line!(), ":", column!());
-        let mut branch = $crate::Branch::Skip;
-        $crate::__private::FAILPOINTS.with_borrow_mut(|state| {
-            if let Some(state) = state
-                && state.enabled
-            {
-                branch = state.tree.visit($crate::Label::Failpoint(NAME));
-            }
-        });
-        match branch {
+    (@audit $audit_purity:literal, $input:expr, $name:expr, $ret:expr, $intentional_success:literal) => {{
+        let name = $name;
+        let branch = $crate::__visit!(name);
+        let res = match branch {
             $crate::Branch::Activate => $ret,
             $crate::Branch::Skip => $input,
+        };
+        if branch == $crate::Branch::Activate {
+            $crate::__audit_injection!(
+                $crate::__private::FailpointRef::failpoint_name(name),
+                &res,
+                $intentional_success
+            );
+            if $audit_purity {
+                $crate::__audit_purity!($crate::__private::FailpointRef::failpoint_name(name), &res);
+            }
         }
+        res
     }};
-    ($input:expr, $name:expr, $ret:expr) => {{
-        let mut branch = $crate::Branch::Skip;
-        $crate::__private::FAILPOINTS.with_borrow_mut(|state| {
-            if let Some(state) = state
-                && state.enabled
-            {
-                branch = state.tree.visit($crate::Label::Failpoint($name));
-            }
-        });
-        match branch {
+    (@audit $audit_purity:literal, $input:expr, $ret:expr, $intentional_success:literal) => {{
+        let branch = $crate::__visit!();
+        let res = match branch {
             $crate::Branch::Activate => $ret,
             $crate::Branch::Skip => $input,
+        };
+        if branch == $crate::Branch::Activate {
+            $crate::__audit_injection!($crate::__location!(), &res, $intentional_success);
+            if $audit_purity {
+                $crate::__audit_purity!($crate::__location!(), &res);
+            }
         }
+        res
+    }};
+    ($input:expr, component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_override!($input, ($component, $point) $(, $($rest)*)?)
+    }};
+    ($input:expr, $name:expr, $ret:expr, intentional_success: $intentional_success:literal $(,)?) => {{
+        $crate::inject_override!(@audit true, $input, $name, $ret, $intentional_success)
+    }};
+    ($input:expr, $ret:expr, intentional_success: $intentional_success:literal $(,)?) => {{
+        $crate::inject_override!(@audit true, $input, $ret, $intentional_success)
+    }};
+    ($input:expr, $ret:expr) => {{
+        $crate::inject_override!($input, $ret, intentional_success: false)
+    }};
+    ($input:expr, $name:expr, $ret:expr) => {{
+        $crate::inject_override!($input, $name, $ret, intentional_success: false)
     }};
 }
 
@@ -94,11 +688,31 @@ macro_rules! inject_override {
 /// want to execute is never the less, use `inject_override_with_side_effect_io_error!`
 #[macro_export]
 macro_rules! inject_override_io_error {
+    ($input:expr, component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_override_io_error!($input, ($component, $point) $(, $($rest)*)?)
+    }};
     ($input:expr) => {{
-        const NAME: &str = concat!(file!(), ":", line!(), ":", column!());
-        $crate::inject_override!($input, NAME, Err(std::io::Error::other(NAME)))
+        $crate::inject_override!(
+            @audit false,
+            $input,
+            Err(std::io::Error::other($crate::__private::injected_error(
+                $crate::__location!()
+            ))),
+            false
+        )
+    }};
+    ($input:expr, $name:expr) => {{
+        let name = $name;
+        $crate::inject_override!(
+            @audit false,
+            $input,
+            name,
+            Err(std::io::Error::other($crate::__private::injected_error(
+                $crate::__private::FailpointRef::failpoint_name(name)
+            ))),
+            false
+        )
     }};
-    ($input:expr, $name:expr) => {{ $crate::inject_override!($input, $name, Err(std::io::Error::other($name))) }};
 }
 
 /// Define failpoint which overrides an expression (which is still executed)
@@ -109,36 +723,55 @@ macro_rules! inject_override_io_error {
 /// Otherwise, use plain [`inject_override!`]
 #[macro_export]
 macro_rules! inject_override_with_side_effect {
-    ($input:expr, $ret:expr) => {{
-        const NAME: &str = concat!(file!(), ":", line!(), ":", column!());
-        let mut branch = $crate::Branch::Skip;
-        $crate::__private::FAILPOINTS.with_borrow_mut(|state| {
-            if let Some(state) = state
-                && state.enabled
-            {
-                branch = state.tree.visit($crate::Label::Failpoint(NAME));
-            }
-        });
+    (@audit $audit_purity:literal, $input:expr, $name:expr, $ret:expr, $intentional_success:literal) => {{
+        let name = $name;
+        let branch = $crate::__visit!(name);
         let res = $input;
-        match branch {
+        let res = match branch {
             $crate::Branch::Activate => $ret,
             $crate::Branch::Skip => res,
+        };
+        if branch == $crate::Branch::Activate {
+            $crate::__audit_injection!(
+                $crate::__private::FailpointRef::failpoint_name(name),
+                &res,
+                $intentional_success
+            );
+            if $audit_purity {
+                $crate::__audit_purity!($crate::__private::FailpointRef::failpoint_name(name), &res);
+            }
         }
+        res
     }};
-    ($input:expr, $name:expr, $ret:expr) => {{
-        let mut branch = $crate::Branch::Skip;
-        $crate::__private::FAILPOINTS.with_borrow_mut(|state| {
-            if let Some(state) = state
-                && state.enabled
-            {
-                branch = state.tree.visit($crate::Label::Failpoint($name));
-            }
-        });
+    (@audit $audit_purity:literal, $input:expr, $ret:expr, $intentional_success:literal) => {{
+        let branch = $crate::__visit!();
         let res = $input;
-        match branch {
+        let res = match branch {
             $crate::Branch::Activate => $ret,
             $crate::Branch::Skip => res,
+        };
+        if branch == $crate::Branch::Activate {
+            $crate::__audit_injection!($crate::__location!(), &res, $intentional_success);
+            if $audit_purity {
+                $crate::__audit_purity!($crate::__location!(), &res);
+            }
         }
+        res
+    }};
+    ($input:expr, component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_override_with_side_effect!($input, ($component, $point) $(, $($rest)*)?)
+    }};
+    ($input:expr, $name:expr, $ret:expr, intentional_success: $intentional_success:literal $(,)?) => {{
+        $crate::inject_override_with_side_effect!(@audit true, $input, $name, $ret, $intentional_success)
+    }};
+    ($input:expr, $ret:expr, intentional_success: $intentional_success:literal $(,)?) => {{
+        $crate::inject_override_with_side_effect!(@audit true, $input, $ret, $intentional_success)
+    }};
+    ($input:expr, $ret:expr) => {{
+        $crate::inject_override_with_side_effect!($input, $ret, intentional_success: false)
+    }};
+    ($input:expr, $name:expr, $ret:expr) => {{
+        $crate::inject_override_with_side_effect!($input, $name, $ret, intentional_success: false)
     }};
 }
 
@@ -150,9 +783,587 @@ macro_rules! inject_override_with_side_effect {
 /// Otherwise, use plain [`inject_override_io_error!`]
 #[macro_export]
 macro_rules! inject_override_with_side_effect_io_error {
+    ($input:expr, component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_override_with_side_effect_io_error!($input, ($component, $point) $(, $($rest)*)?)
+    }};
+    ($input:expr) => {{
+        $crate::inject_override_with_side_effect!(
+            @audit false,
+            $input,
+            Err(std::io::Error::other($crate::__private::injected_error(
+                $crate::__location!()
+            ))),
+            false
+        )
+    }};
+    ($input:expr, $name:expr) => {{
+        let name = $name;
+        $crate::inject_override_with_side_effect!(
+            @audit false,
+            $input,
+            name,
+            Err(std::io::Error::other($crate::__private::injected_error(
+                $crate::__private::FailpointRef::failpoint_name(name)
+            ))),
+            false
+        )
+    }};
+}
+
+/// Define failpoint which overrides a future
+///
+/// Async counterpart of [`inject_override!`]: produces an
+/// [`OverrideFuture`](crate::OverrideFuture) which, when awaited,
+/// either awaits the wrapped future, or, if the failpoint was
+/// activated, returns the overriding value without constructing it.
+/// `.await` stays outside of the macro, so types are inferred the
+/// same way as for the wrapped future.
+///
+/// The failpoint is visited when the macro is evaluated, not when
+/// the future is first polled, so it is placed in the tree at the
+/// point of future construction. As failpoints are processed on the
+/// thread running [`Runner::run()`](crate::Runner::run), the future
+/// has to be constructed there, but it may then be polled anywhere.
+///
+/// Available with `async` feature.
+///
+/// ```
+/// # use std::io;
+/// # use faine::inject_override_async;
+/// async fn send(request: &str) -> io::Result<usize> {
+///     Ok(request.len())
+/// }
+///
+/// async fn request() -> io::Result<usize> {
+///     let sent = inject_override_async!(send("ping"), "send", Err(io::Error::other("injected"))).await?;
+///     Ok(sent)
+/// }
+/// ```
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! inject_override_async {
+    (@audit $audit_purity:literal, $input:expr, $name:expr, $ret:expr, $intentional_success:literal) => {{
+        let name = $name;
+        let res = match $crate::__visit!(name) {
+            $crate::Branch::Activate => $crate::OverrideFuture::Ready(Some($ret)),
+            $crate::Branch::Skip => {
+                $crate::OverrideFuture::Future(::std::future::IntoFuture::into_future($input))
+            }
+        };
+        if let $crate::OverrideFuture::Ready(Some(value)) = &res {
+            $crate::__audit_injection!(
+                $crate::__private::FailpointRef::failpoint_name(name),
+                value,
+                $intentional_success
+            );
+            if $audit_purity {
+                $crate::__audit_purity!($crate::__private::FailpointRef::failpoint_name(name), value);
+            }
+        }
+        res
+    }};
+    (@audit $audit_purity:literal, $input:expr, $ret:expr, $intentional_success:literal) => {{
+        let res = match $crate::__visit!() {
+            $crate::Branch::Activate => $crate::OverrideFuture::Ready(Some($ret)),
+            $crate::Branch::Skip => {
+                $crate::OverrideFuture::Future(::std::future::IntoFuture::into_future($input))
+            }
+        };
+        if let $crate::OverrideFuture::Ready(Some(value)) = &res {
+            $crate::__audit_injection!($crate::__location!(), value, $intentional_success);
+            if $audit_purity {
+                $crate::__audit_purity!($crate::__location!(), value);
+            }
+        }
+        res
+    }};
+    ($input:expr, component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_override_async!($input, ($component, $point) $(, $($rest)*)?)
+    }};
+    ($input:expr, $name:expr, $ret:expr, intentional_success: $intentional_success:literal $(,)?) => {{
+        $crate::inject_override_async!(@audit true, $input, $name, $ret, $intentional_success)
+    }};
+    ($input:expr, $ret:expr, intentional_success: $intentional_success:literal $(,)?) => {{
+        $crate::inject_override_async!(@audit true, $input, $ret, $intentional_success)
+    }};
+    ($input:expr, $ret:expr) => {{
+        $crate::inject_override_async!($input, $ret, intentional_success: false)
+    }};
+    ($input:expr, $name:expr, $ret:expr) => {{
+        $crate::inject_override_async!($input, $name, $ret, intentional_success: false)
+    }};
+}
+
+/// Define failpoint which overrides a future with [`std::io::Error`]
+///
+/// See [`inject_override_async!`]. Available with `async` feature.
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! inject_override_async_io_error {
+    ($input:expr, component: $component:expr, point: $point:expr $(, $($rest:tt)*)?) => {{
+        $crate::inject_override_async_io_error!($input, ($component, $point) $(, $($rest)*)?)
+    }};
     ($input:expr) => {{
-        const NAME: &str = concat!(file!(), ":", line!(), ":", column!());
-        $crate::inject_override_with_side_effect!($input, NAME, Err(std::io::Error::other(NAME)))
+        $crate::inject_override_async!(
+            @audit false,
+            $input,
+            Err(std::io::Error::other($crate::__private::injected_error(
+                $crate::__location!()
+            ))),
+            false
+        )
+    }};
+    ($input:expr, $name:expr) => {{
+        let name = $name;
+        $crate::inject_override_async!(
+            @audit false,
+            $input,
+            name,
+            Err(std::io::Error::other($crate::__private::injected_error(
+                $crate::__private::FailpointRef::failpoint_name(name)
+            ))),
+            false
+        )
+    }};
+}
+
+/// Assert that a `Result` is an error injected by a given failpoint
+///
+/// Passes only if the result is `Err` and its source chain contains
+/// [`InjectedError`](crate::InjectedError) with a given failpoint name.
+/// This protects tests from mistaking genuine environmental failures
+/// for injected ones. Works with plain errors, [`std::io::Error`],
+/// `Box<dyn Error>` and `anyhow::Error`.
+///
+/// Use `prefix = "..."` form to match failpoint names by prefix.
+///
+/// ```
+/// # use faine::{assert_injected_by, inject_return_io_error};
+/// fn foo() -> std::io::Result<()> {
+///     inject_return_io_error!("create temp file");
+///     Ok(())
+/// }
+/// faine::Runner::default().run(|| {
+///     if let Err(err) = foo() {
+///         assert_injected_by!(Err::<(), _>(err), "create temp file");
+///     }
+/// }).unwrap();
+/// ```
+#[macro_export]
+macro_rules! assert_injected_by {
+    ($res:expr, prefix = $failpoint:expr $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::AsDynError as _;
+        match &$res {
+            Ok(_) => $crate::__private::assert_injected_by(None, $failpoint, true),
+            Err(err) => {
+                $crate::__private::assert_injected_by(Some(err.as_dyn_error()), $failpoint, true)
+            }
+        }
+    }};
+    ($res:expr, $failpoint:expr $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::AsDynError as _;
+        match &$res {
+            Ok(_) => $crate::__private::assert_injected_by(None, $failpoint, false),
+            Err(err) => {
+                $crate::__private::assert_injected_by(Some(err.as_dyn_error()), $failpoint, false)
+            }
+        }
+    }};
+}
+
+/// Assert that a `Result` is not an injected error
+///
+/// Passes if the result is `Ok`, or is `Err` which does not contain
+/// [`InjectedError`](crate::InjectedError) in its source chain.
+#[macro_export]
+macro_rules! assert_not_injected {
+    ($res:expr $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::AsDynError as _;
+        match &$res {
+            Ok(_) => {}
+            Err(err) => $crate::__private::assert_not_injected(Some(err.as_dyn_error())),
+        }
+    }};
+}
+
+/// Define multi-valued failpoint which perturbs a value
+///
+/// Each listed perturbation replaces the expression value on a separate
+/// explored path, in addition to a path where the value is left intact.
+/// Perturbations are shown symbolically in the [`Report`](crate::Report).
+///
+/// With explicit list of perturbations, the expression is not evaluated
+/// when one is applied. With a [`Perturb`](crate::Perturb) preset, the
+/// expression is always evaluated first, as some perturbations are
+/// computed from its value.
+///
+/// ```
+/// # use faine::{Perturb, inject_perturb};
+/// # fn read_sensor() -> f64 { 1.0 }
+/// # fn read_counter() -> u32 { 1 }
+/// let value = inject_perturb!(read_sensor(), "sensor read", perturbations: [f64::NAN, f64::INFINITY, -0.0]);
+/// let value = inject_perturb!(read_sensor(), "sensor read", preset: Perturb::FloatEdgeCases);
+/// let count = inject_perturb!(read_counter(), "counter read", preset: Perturb::IntEdgeCases);
+/// ```
+#[macro_export]
+macro_rules! inject_perturb {
+    ($input:expr, $name:expr, perturbations: [$($perturbation:expr),+ $(,)?] $(,)?) => {{
+        const VARIANTS: &[&str] = &[$(stringify!($perturbation)),+];
+        match $crate::__private::visit_variants($name, $crate::__location!(), VARIANTS) {
+            Some(index) => {
+                // only moves out the chosen value, so no `Copy` is required
+                let mut perturbations = [$(::core::option::Option::Some($perturbation)),+];
+                perturbations[index].take().unwrap()
+            }
+            None => $input,
+        }
+    }};
+    ($input:expr, $name:expr, preset: $preset:expr $(,)?) => {
+        $crate::__private::perturb_preset($name, $crate::__location!(), $preset, $input)
+    };
+}
+
+/// Pin output of a random generator to explored values
+///
+/// Evaluates to the given expression, usually drawing from
+/// [`rng()`](crate::rng), or to one of listed values, each of which
+/// is explored as a separate branch, shown in the
+/// [`Report`](crate::Report) as `[name=value]`. Useful when specific
+/// random values, such as bounds of a jitter range, matter to the
+/// tested code.
+///
+/// ```
+/// # use faine::{inject_override_rng, rng};
+/// let delay = inject_override_rng!(rng().range(100..200), "backoff jitter", values: [100, 199]);
+/// ```
+#[macro_export]
+macro_rules! inject_override_rng {
+    ($input:expr, $name:expr, values: [$($value:expr),+ $(,)?] $(,)?) => {
+        $crate::inject_perturb!($input, $name, perturbations: [$($value),+])
+    };
+}
+
+/// Define a point where a batch of operations may be cut short
+///
+/// Call this once per item of a batch, with the index of the item
+/// about to be applied and the number of items in the batch. Evaluates
+/// to `true` if the batch should stop before this item, simulating
+/// partial application. Where the batch is cut is decided once, when
+/// the item with index 0 is visited, so a batch of N items is explored
+/// in exactly N + 1 paths: without a cut, and with a cut before each
+/// item. Further visits evaluate to `true` only for the item decided,
+/// and each batch started with index 0 gets a decision of its own,
+/// even if the same cut point was already visited in the iteration.
+/// The cut is shown in the [`Report`](crate::Report) as
+/// `[name=cut before k]`.
+///
+/// ```
+/// # use std::io;
+/// # use faine::batch_cut;
+/// fn apply(changes: &[&str]) -> io::Result<()> {
+///     for (index, _change) in changes.iter().enumerate() {
+///         if batch_cut!("apply changes", index, changes.len()) {
+///             return Err(io::Error::other("interrupted"));
+///         }
+///         // apply the change
+///     }
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! batch_cut {
+    ($name:expr, $index:expr, $len:expr $(,)?) => {
+        $crate::__private::visit_batch_cut(
+            $crate::__failpoint_id!($name),
+            $crate::__location!(),
+            $index,
+            $len,
+        )
+    };
+}
+
+/// Record that a named check has executed in the current iteration
+///
+/// Place this inside assertion code, so it's possible to tell paths
+/// on which the assertions were skipped (for instance, because of an
+/// early return) from ones where they have passed. Numbers of paths
+/// each check has executed on are available with
+/// [`Report::check_counts()`](crate::Report::check_counts), and
+/// [`Runner::with_required_checks()`](crate::Runner::with_required_checks)
+/// turns paths which miss a check into an error.
+///
+/// Recording is cheap: the name is interned once per call site, and
+/// a bit is set in a per-iteration bitmap. Does nothing outside of
+/// [`Runner::run()`](crate::Runner::run).
+///
+/// ```
+/// # use faine::{Runner, checked, inject_return};
+/// fn read() -> Result<String, ()> {
+///     inject_return!("read", Err(()));
+///     Ok("data".to_string())
+/// }
+///
+/// let report = Runner::ignore_env()
+///     .run(|| {
+///         let Ok(data) = read() else {
+///             return;
+///         };
+///         checked!("contents invariant");
+///         assert_eq!(data, "data");
+///     })
+///     .unwrap();
+/// assert_eq!(report.check_counts()["contents invariant"], 1);
+/// ```
+#[macro_export]
+macro_rules! checked {
+    ($name:literal) => {{
+        static ID: ::std::sync::OnceLock<usize> = ::std::sync::OnceLock::new();
+        $crate::__private::record_check(*ID.get_or_init(|| $crate::__private::intern_check($name)))
+    }};
+}
+
+/// Count an event on the current path
+///
+/// Bumps a per-path counter, for instance, on each retry or rewrite,
+/// to account work caused by injected faults. Values are recorded in
+/// [`ExecutionPath::counters`](crate::ExecutionPath::counters),
+/// summarized by [`Report::counter_stats()`](crate::Report::counter_stats),
+/// and may be bounded with
+/// [`Runner::with_counter_limit()`](crate::Runner::with_counter_limit).
+///
+/// Counting is cheap: the name is interned once per call site, and a
+/// value is added in a per-iteration array. Does nothing outside of
+/// [`Runner::run()`](crate::Runner::run). See also [`count_by!`](crate::count_by).
+///
+/// ```
+/// # use faine::{Runner, count, inject_return};
+/// fn fetch() -> Result<(), ()> {
+///     inject_return!("fetch", Err(()));
+///     Ok(())
+/// }
+///
+/// let report = Runner::ignore_env()
+///     .run(|| {
+///         while fetch().is_err() {
+///             count!("retries");
+///             faine::enable_failpoints(false);
+///         }
+///     })
+///     .unwrap();
+/// assert_eq!(report.counter_stats()["retries"].max, 1);
+/// ```
+#[macro_export]
+macro_rules! count {
+    ($name:literal) => {
+        $crate::count_by!($name, 1)
+    };
+}
+
+/// Add an amount to a counter on the current path
+///
+/// Same as [`count!`](crate::count), but adds a given amount, such as
+/// a number of bytes rewritten.
+///
+/// ```
+/// # use faine::count_by;
+/// let data = b"data";
+/// count_by!("bytes rewritten", data.len() as u64);
+/// ```
+#[macro_export]
+macro_rules! count_by {
+    ($name:literal, $value:expr) => {{
+        static ID: ::std::sync::OnceLock<usize> = ::std::sync::OnceLock::new();
+        $crate::__private::add_to_counter(
+            *ID.get_or_init(|| $crate::__private::intern_counter($name)),
+            $value,
+        )
+    }};
+}
+
+/// Define a fail point, compatible with `fail::fail_point!`
+///
+/// Not a public API, use `faine::fail_compat::fail_point!`.
+#[cfg(feature = "fail-compat")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fail_compat_fail_point {
+    ($name:expr) => {{
+        $crate::fail_compat::eval_plain(
+            ::core::option_env!("CARGO_PKG_NAME"),
+            $name,
+            $crate::__location!(),
+        )
+    }};
+    ($name:expr, $e:expr) => {{
+        if let Some(res) = $crate::fail_compat::eval(
+            ::core::option_env!("CARGO_PKG_NAME"),
+            $name,
+            $crate::__location!(),
+            $e,
+        ) {
+            return res;
+        }
+    }};
+    ($name:expr, $cond:expr, $e:expr) => {{
+        if $cond {
+            $crate::__fail_compat_fail_point!($name, $e);
+        }
     }};
-    ($input:expr, $name:expr) => {{ $crate::inject_override_with_side_effect!($input, $name, Err(std::io::Error::other($name))) }};
+}
+
+/// Mark a crate as compiled with failpoints
+///
+/// Place this at the root of a crate which compiles failpoints in
+/// conditionally, under the same condition, so tests may check
+/// that the condition holds with [`assert_instrumented!`](crate::assert_instrumented):
+///
+/// ```ignore
+/// #[cfg(feature = "faine")]
+/// faine::declare_instrumented!();
+/// ```
+///
+/// Expands to a public marker item.
+#[macro_export]
+macro_rules! declare_instrumented {
+    () => {
+        #[doc(hidden)]
+        pub const FAINE_INSTRUMENTATION_ENABLED: () = ();
+    };
+}
+
+/// Fail compilation unless a crate is compiled with failpoints
+///
+/// If a crate compiles failpoints in under a feature which tests
+/// forget to enable, exploration silently degrades to a single path.
+/// This macro, placed into a test, turns that into a compilation
+/// error, given the crate has [`declare_instrumented!`](crate::declare_instrumented) at its root:
+///
+/// ```
+/// mod storage {
+///     faine::declare_instrumented!();
+/// }
+///
+/// faine::assert_instrumented!(storage);
+/// ```
+///
+/// ```compile_fail
+/// mod storage {}
+///
+/// // error[E0425]: cannot find value `FAINE_INSTRUMENTATION_ENABLED` in module `storage`
+/// faine::assert_instrumented!(storage);
+/// ```
+#[macro_export]
+macro_rules! assert_instrumented {
+    ($($krate:ident)::+) => {
+        const _: () = $($krate)::+::FAINE_INSTRUMENTATION_ENABLED;
+    };
+}
+
+/// Define a function along with its uninstrumented `const` twin
+///
+/// Failpoints cannot be used in `const fn`, as they need access to
+/// the runtime state. This defines two functions with the same body:
+/// a regular one with failpoints, and a `const` one named after `as
+/// const`, where `inject_*` macros compile to a passthrough: `inject_return!`
+/// and `inject_exhaust!` to nothing, and overrides and perturbations
+/// to their input expressions.
+///
+/// ```
+/// # use faine::{const_compatible, inject_override, inject_return};
+/// const_compatible! {
+///     /// Compute buffer size
+///     pub fn buffer_size(blocks: usize) -> Option<usize> as const BUFFER_SIZE_CONST {
+///         inject_return!("size overflow", None);
+///         blocks.checked_mul(inject_override!(4096, "block size", 0))
+///     }
+/// }
+///
+/// const DEFAULT_BUFFER_SIZE: Option<usize> = BUFFER_SIZE_CONST(16);
+/// # assert_eq!(DEFAULT_BUFFER_SIZE, Some(65536));
+/// # assert_eq!(buffer_size(16), Some(65536));
+/// ```
+///
+/// Macros are only replaced when invoked by their unqualified names
+/// (imported with `use`), but not as `faine::inject_return!`. Generic
+/// functions and patterns in arguments are not supported.
+#[macro_export]
+macro_rules! const_compatible {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $type:ty),* $(,)?) -> $ret:ty as const $const_name:ident { $($body:tt)* }
+    ) => {
+        $(#[$attr])*
+        $vis fn $name($($arg: $type),*) -> $ret { $($body)* }
+
+        $crate::__const_passthrough! {
+            ($)
+            $(#[$attr])*
+            #[allow(non_snake_case)]
+            // passthroughs may produce `let x = x;`
+            #[allow(clippy::redundant_locals)]
+            $vis const fn $const_name($($arg: $type),*) -> $ret { $($body)* }
+        }
+    };
+}
+
+/// Define `const fn` with `inject_*` macros shadowed by passthrough ones
+///
+/// Not a public API, used by `const_compatible!`. Takes `$` token as
+/// an argument, as it cannot be otherwise used in nested macro
+/// definitions. Shadowing macros must be defined by the same expansion
+/// as the function body, otherwise they are ambiguous with imported ones.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __const_passthrough {
+    (($d:tt) $(#[$attr:meta])* $vis:vis const fn $name:ident($($args:tt)*) -> $ret:ty { $($body:tt)* }) => {
+        $(#[$attr])*
+        $vis const fn $name($($args)*) -> $ret {
+            #[allow(unused_macros)]
+            macro_rules! inject_return {
+                ($d($d tt:tt)*) => {};
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_return_io_error {
+                ($d($d tt:tt)*) => {};
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_exhaust {
+                ($d($d tt:tt)*) => {};
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_override {
+                ($d input:expr $d(, $d($d tt:tt)*)?) => {
+                    $d input
+                };
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_override_io_error {
+                ($d input:expr $d(, $d($d tt:tt)*)?) => {
+                    $d input
+                };
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_override_with_side_effect {
+                ($d input:expr $d(, $d($d tt:tt)*)?) => {
+                    $d input
+                };
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_override_with_side_effect_io_error {
+                ($d input:expr $d(, $d($d tt:tt)*)?) => {
+                    $d input
+                };
+            }
+            #[allow(unused_macros)]
+            macro_rules! inject_perturb {
+                ($d input:expr $d(, $d($d tt:tt)*)?) => {
+                    $d input
+                };
+            }
+            $($body)*
+        }
+    };
 }
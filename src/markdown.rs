@@ -0,0 +1,195 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::common::Branch;
+use crate::report::{ExecutionPath, Report};
+
+/// Settings of markdown rendering of a [`Report`]
+///
+/// See [`Report::to_markdown()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    failpoints: bool,
+    failing_paths: bool,
+    diffs: bool,
+    max_entries: usize,
+}
+
+impl Default for MarkdownOptions {
+    /// All sections, up to 20 entries per list
+    fn default() -> Self {
+        Self {
+            failpoints: true,
+            failing_paths: true,
+            diffs: true,
+            max_entries: 20,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// Include table of failpoints with visit and activation counts
+    pub fn with_failpoints(mut self, failpoints: bool) -> Self {
+        self.failpoints = failpoints;
+        self
+    }
+
+    /// Include collapsible list of failing paths
+    pub fn with_failing_paths(mut self, failing_paths: bool) -> Self {
+        self.failing_paths = failing_paths;
+        self
+    }
+
+    /// Include differences from nearest passing paths into the list of failing paths
+    pub fn with_diffs(mut self, diffs: bool) -> Self {
+        self.diffs = diffs;
+        self
+    }
+
+    /// Limit number of entries in each table and list
+    ///
+    /// Omitted entries are counted in a note following the table or list.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+/// Inline code span, safe to be placed in a table cell
+fn code(s: &str) -> String {
+    format!("`{}`", s.replace('`', "'").replace('|', "\\|"))
+}
+
+#[derive(Default)]
+struct FailpointStats {
+    visits: usize,
+    activations: usize,
+    failing_paths: usize,
+}
+
+fn failpoint_stats(paths: &[ExecutionPath]) -> BTreeMap<&'static str, FailpointStats> {
+    let mut stats: BTreeMap<&'static str, FailpointStats> = Default::default();
+    for path in paths {
+        for step in &path.steps {
+            let stats = stats.entry(step.failpoint).or_default();
+            stats.visits += 1;
+            if step.branch == Branch::Activate {
+                stats.activations += 1;
+                if path.is_failed() {
+                    stats.failing_paths += 1;
+                }
+            }
+        }
+    }
+    stats
+}
+
+fn write_omitted(out: &mut String, omitted: usize, what: &str) -> std::fmt::Result {
+    if omitted > 0 {
+        writeln!(out, "\n_…and {omitted} more {what}_")?;
+    }
+    Ok(())
+}
+
+pub fn render(report: &Report, options: &MarkdownOptions) -> String {
+    let mut out = String::new();
+    write_markdown(&mut out, report, options).expect("writing to a string must not fail");
+    out
+}
+
+fn write_markdown(
+    out: &mut String,
+    report: &Report,
+    options: &MarkdownOptions,
+) -> std::fmt::Result {
+    let failing: Vec<&ExecutionPath> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .collect();
+
+    let status = if failing.is_empty() {
+        "passed"
+    } else {
+        "failed"
+    };
+    writeln!(out, "### Fault injection {status}\n")?;
+    let mut counts = vec![
+        format!("**paths** {}", code(&report.num_paths().to_string())),
+        format!("**failing** {}", code(&failing.len().to_string())),
+        format!(
+            "**failpoints** {}",
+            code(&report.failpoints().len().to_string())
+        ),
+    ];
+    if report.known_failures_checked() {
+        counts.push(format!(
+            "**new failures** {}",
+            code(&report.new_failing_paths().count().to_string())
+        ));
+    }
+    writeln!(out, "{}\n", counts.join(" · "))?;
+    writeln!(out, "{report}")?;
+
+    if options.failpoints && !report.failpoints().is_empty() {
+        let stats = failpoint_stats(report.paths());
+        writeln!(
+            out,
+            "\n| Failpoint | Component | Visits | Activations | Failing |"
+        )?;
+        writeln!(out, "|---|---|---:|---:|---:|")?;
+        for (failpoint, stats) in stats.iter().take(options.max_entries) {
+            let component = report
+                .failpoint_component(failpoint)
+                .map(code)
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "| {} | {component} | {} | {} | {} |",
+                code(failpoint),
+                stats.visits,
+                stats.activations,
+                stats.failing_paths
+            )?;
+        }
+        write_omitted(
+            out,
+            stats.len().saturating_sub(options.max_entries),
+            "failpoint(s)",
+        )?;
+    }
+
+    if options.failing_paths && !failing.is_empty() {
+        writeln!(
+            out,
+            "\n<details>\n<summary>Failing paths ({})</summary>\n",
+            failing.len()
+        )?;
+        for path in failing.iter().take(options.max_entries) {
+            let reason = match &path.panic {
+                Some(panic) => format!("panicked at {}", code(&panic.location())),
+                None => "timed out".to_string(),
+            };
+            writeln!(out, "- {} {reason}", code(&path.key()))?;
+            if options.diffs
+                && let Some((_, diff)) = report.nearest_passing_path(path)
+            {
+                writeln!(out, "\n  ```text")?;
+                for line in diff.to_string().lines() {
+                    writeln!(out, "  {}", line.trim_end())?;
+                }
+                writeln!(out, "  ```")?;
+            }
+        }
+        write_omitted(
+            out,
+            failing.len().saturating_sub(options.max_entries),
+            "failing path(s)",
+        )?;
+        writeln!(out, "\n</details>")?;
+    }
+    Ok(())
+}
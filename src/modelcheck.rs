@@ -0,0 +1,236 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Model checking of exploration completeness
+//!
+//! Generates random programs made of failpoints whose reachability
+//! depends on outcomes of earlier ones, and compares states reached
+//! by exploring them with a [`Runner`] against states reached by a
+//! brute-force enumeration of all decision vectors. Meant for testing
+//! `faine` itself, and features which restrict or reorder exploration,
+//! such as strategies and pruning, which must not lose reachable
+//! states. Available with `modelcheck` feature.
+//!
+//! ```
+//! use faine::Runner;
+//! use faine::modelcheck::{Program, check};
+//!
+//! for seed in 0..10 {
+//!     check(&Program::generate(seed, 8), Runner::ignore_env());
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::runner::Runner;
+use crate::strategy::Rng;
+
+/// Maximal number of nodes in a program
+///
+/// Bounded because brute-force enumeration is exponential.
+pub const MAX_NODES: usize = 16;
+
+const NAMES: [&str; MAX_NODES] = [
+    "n0", "n1", "n2", "n3", "n4", "n5", "n6", "n7", "n8", "n9", "n10", "n11", "n12", "n13", "n14",
+    "n15",
+];
+
+/// State reached by a program
+///
+/// Holds the outcome of each node, `true` for activated, `false` for
+/// skipped, and `None` for nodes which were not reached.
+pub type Terminal = Vec<Option<bool>>;
+
+#[derive(Debug, Clone)]
+struct Node {
+    name: &'static str,
+    // outcomes of earlier nodes required to reach this one
+    requires: Vec<(usize, bool)>,
+    // whether activation terminates the program, like `inject_return!`
+    returns: bool,
+}
+
+/// Randomly generated program of conditional failpoints
+///
+/// The program is a sequence of nodes, each being a failpoint visited
+/// only if the listed earlier nodes were reached with given outcomes.
+/// Activation of a node may also terminate the program. Some nodes
+/// share failpoint names with earlier ones, like a failpoint visited
+/// from different places of the code.
+#[derive(Debug, Clone)]
+pub struct Program {
+    nodes: Vec<Node>,
+}
+
+impl Program {
+    /// Generate a program with a given number of nodes
+    ///
+    /// The same seed always produces the same program.
+    ///
+    /// # Panics
+    ///
+    /// If `num_nodes` exceeds [`MAX_NODES`].
+    pub fn generate(seed: u64, num_nodes: usize) -> Self {
+        assert!(
+            num_nodes <= MAX_NODES,
+            "programs are limited to {MAX_NODES} nodes"
+        );
+        let mut rng = Rng::new(seed);
+        let mut below = |n: usize| (rng.next_u64() % n as u64) as usize;
+        let nodes = (0..num_nodes)
+            .map(|index| {
+                let name = if index > 0 && below(4) == 0 {
+                    NAMES[below(index)]
+                } else {
+                    NAMES[index]
+                };
+                let mut requires: Vec<_> = (0..if index > 0 { below(3) } else { 0 })
+                    .map(|_| (below(index), below(2) == 0))
+                    .collect();
+                requires.sort();
+                requires.dedup_by_key(|(node, _)| *node);
+                Node {
+                    name,
+                    requires,
+                    returns: below(5) == 0,
+                }
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Number of nodes in the program
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn interpret(&self, mut decide: impl FnMut(usize, &'static str) -> bool) -> Terminal {
+        let mut outcomes: Terminal = vec![None; self.nodes.len()];
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node
+                .requires
+                .iter()
+                .any(|&(required, outcome)| outcomes[required] != Some(outcome))
+            {
+                continue;
+            }
+            let activated = decide(index, node.name);
+            outcomes[index] = Some(activated);
+            if activated && node.returns {
+                break;
+            }
+        }
+        outcomes
+    }
+
+    /// Run the program, visiting failpoints
+    ///
+    /// To be called under [`Runner::run()`].
+    pub fn execute(&self) -> Terminal {
+        self.interpret(|_, name| crate::inject_override!(false, name, true))
+    }
+
+    /// Explore the program with a given runner, collecting reached states
+    ///
+    /// # Panics
+    ///
+    /// If the exploration fails.
+    pub fn explore(&self, runner: Runner) -> BTreeSet<Terminal> {
+        let mut terminals = BTreeSet::new();
+        runner
+            .run(|| {
+                terminals.insert(self.execute());
+            })
+            .expect("exploration failed");
+        terminals
+    }
+
+    /// Collect states reached with all possible decision vectors
+    pub fn enumerate(&self) -> BTreeSet<Terminal> {
+        (0..1u32 << self.nodes.len())
+            .map(|decisions| self.interpret(|index, _| decisions & (1 << index) != 0))
+            .collect()
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, node) in self.nodes.iter().enumerate() {
+            write!(f, "{index}: {}", node.name)?;
+            for (i, &(required, outcome)) in node.requires.iter().enumerate() {
+                let prefix = if i == 0 { " if" } else { " &&" };
+                let negation = if outcome { "" } else { "!" };
+                write!(f, "{prefix} {negation}{required}")?;
+            }
+            if node.returns {
+                write!(f, ", returns")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Assert that exploration reaches exactly the states reachable by brute force
+///
+/// # Panics
+///
+/// If sets of states differ, listing the program along with missing
+/// and unexpected states.
+#[track_caller]
+pub fn check(program: &Program, runner: Runner) {
+    let explored = program.explore(runner);
+    let expected = program.enumerate();
+    if explored != expected {
+        panic!(
+            "exploration does not match enumeration for program:\n{program}missing: {:?}\nunexpected: {:?}",
+            expected.difference(&explored).collect::<Vec<_>>(),
+            explored.difference(&expected).collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_deterministic() {
+        let program = Program::generate(42, 10);
+        assert_eq!(program.num_nodes(), 10);
+        assert_eq!(program.to_string(), Program::generate(42, 10).to_string());
+    }
+
+    #[test]
+    fn test_enumerate() {
+        let program = Program {
+            nodes: vec![
+                Node {
+                    name: "n0",
+                    requires: vec![],
+                    returns: true,
+                },
+                Node {
+                    name: "n1",
+                    requires: vec![],
+                    returns: false,
+                },
+                Node {
+                    name: "n2",
+                    requires: vec![(1, true)],
+                    returns: false,
+                },
+            ],
+        };
+        assert_eq!(
+            program.enumerate(),
+            BTreeSet::from([
+                vec![Some(true), None, None],
+                vec![Some(false), Some(false), None],
+                vec![Some(false), Some(true), Some(false)],
+                vec![Some(false), Some(true), Some(true)],
+            ])
+        );
+    }
+}
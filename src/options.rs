@@ -1,16 +1,195 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use crate::common::Branch;
+use std::any::TypeId;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
+use crate::absorption::OutcomeClassifier;
+use crate::axes::{Axis, AxisBudget};
+use crate::common::{Branch, Tier};
+use crate::dedup::DedupMode;
+use crate::env::EnvAxis;
+use crate::events::SharedEventSink;
+use crate::ext::ErrorFactory;
+use crate::functions::{AccumulatorCloner, AccumulatorFactory};
+use crate::params::Parameter;
+use crate::report::ExecutionPath;
+use crate::safety::SafetyLimits;
+use crate::selector::Selector;
+use crate::strategy::{Cooldown, RateLimit, SharedFaultModel, Strategy};
+
+#[derive(Clone)]
 pub struct Options {
     pub branch_preference: Branch,
+    pub order_seed: Option<u64>,
+    pub iteration_timeout: Option<Duration>,
+    pub enforce_iteration_timeout: bool,
+    pub independent_groups: Vec<Vec<Selector>>,
+    pub audit_independence: bool,
+    pub capture_output: bool,
+    pub test_name: Option<String>,
+    pub coverage_dir: Option<PathBuf>,
+    pub trace_dir: Option<PathBuf>,
+    pub trace_all: bool,
+    pub order_sensitivity_check: bool,
+    pub tier: Tier,
+    pub catch_panics: bool,
+    pub expected_panics: Vec<(String, u32)>,
+    pub allow_faults_during_unwind: bool,
+    pub max_iterations: Option<usize>,
+    pub max_duration: Option<Duration>,
+    pub strict_determinism: bool,
+    pub max_failure_diffs: usize,
+    pub strategy: Strategy,
+    pub aliases: Vec<(String, String)>,
+    pub fault_models: Vec<(String, SharedFaultModel)>,
+    pub rate_limits: Vec<RateLimit>,
+    pub cooldowns: Vec<Cooldown>,
+    pub dedup_store: Option<PathBuf>,
+    pub dedup_mode: DedupMode,
+    pub scenario_key: Option<u64>,
+    pub failure_reruns: usize,
+    pub error_factories: HashMap<TypeId, ErrorFactory>,
+    #[cfg(feature = "fs")]
+    pub metadata_perturbations: HashMap<String, crate::fs::MetadataPerturbation>,
+    #[cfg(feature = "fs")]
+    pub durability_model: crate::fs::DurabilityModel,
+    #[cfg(feature = "fs")]
+    pub fixture_tree: Option<std::path::PathBuf>,
+    pub priority_paths: Vec<Vec<String>>,
+    pub require_reachable_priority_paths: bool,
+    pub known_failures_file: Option<PathBuf>,
+    pub known_failures: BTreeSet<String>,
+    pub update_known_failures: bool,
+    pub timeline_interval: Option<Duration>,
+    pub diminishing_returns: Option<(Duration, usize)>,
+    pub forbidden: Vec<Selector>,
+    pub expected_failpoints: Vec<Selector>,
+    pub enforce_site_expectations: bool,
+    pub self_verification: Option<f32>,
+    pub self_verification_seed: Option<u64>,
+    pub accumulator: Option<(AccumulatorFactory, AccumulatorCloner)>,
+    pub axes: Vec<Axis>,
+    pub env_axes: Vec<EnvAxis>,
+    pub axis_budget: AxisBudget,
+    pub outcome_classifiers: HashMap<TypeId, OutcomeClassifier>,
+    pub max_absorption: Vec<(String, f64)>,
+    pub safety_limits: SafetyLimits,
+    pub activation_limits: HashMap<&'static str, usize>,
+    pub adaptive_hit_limits: Option<(usize, usize)>,
+    pub hygiene_report: bool,
+    pub safety_valve_is_error: bool,
+    pub audit_injections: bool,
+    pub audit_injection_purity: bool,
+    pub error_tagging: bool,
+    pub required_checks: Vec<String>,
+    pub counter_limits: Vec<(String, u64)>,
+    pub parameters: Vec<Parameter>,
+    pub stage_split: f64,
+    pub outer_path: Option<ExecutionPath>,
+    pub rng_seed: u64,
+    pub shard: Option<(usize, usize)>,
+    pub event_sinks: Vec<SharedEventSink>,
+    pub shard_depth: usize,
+    pub plan_paths: bool,
+    pub recent_events: usize,
+    pub continue_after_checkpoint_violation: bool,
+    pub decision_bytes: Option<Arc<[u8]>>,
+    #[cfg(feature = "log")]
+    pub failure_logging: Option<crate::logging::FailureLogging>,
+    #[cfg(feature = "tempdir")]
+    pub managed_tempdir: bool,
+    #[cfg(feature = "ctrlc")]
+    pub graceful_interrupt: bool,
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             branch_preference: Branch::Activate,
+            order_seed: None,
+            iteration_timeout: None,
+            enforce_iteration_timeout: false,
+            independent_groups: Default::default(),
+            audit_independence: false,
+            capture_output: false,
+            test_name: None,
+            coverage_dir: None,
+            trace_dir: None,
+            trace_all: false,
+            order_sensitivity_check: false,
+            tier: Tier::Full,
+            catch_panics: false,
+            expected_panics: Default::default(),
+            allow_faults_during_unwind: false,
+            max_iterations: None,
+            max_duration: None,
+            strict_determinism: false,
+            max_failure_diffs: 3,
+            strategy: Strategy::Exhaustive,
+            aliases: Default::default(),
+            fault_models: Default::default(),
+            rate_limits: Default::default(),
+            cooldowns: Default::default(),
+            dedup_store: None,
+            dedup_mode: DedupMode::Skip,
+            scenario_key: None,
+            failure_reruns: 0,
+            error_factories: Default::default(),
+            #[cfg(feature = "fs")]
+            metadata_perturbations: Default::default(),
+            #[cfg(feature = "fs")]
+            durability_model: Default::default(),
+            #[cfg(feature = "fs")]
+            fixture_tree: None,
+            priority_paths: Default::default(),
+            require_reachable_priority_paths: false,
+            known_failures_file: None,
+            known_failures: Default::default(),
+            update_known_failures: false,
+            timeline_interval: None,
+            diminishing_returns: None,
+            forbidden: Default::default(),
+            expected_failpoints: Default::default(),
+            enforce_site_expectations: false,
+            self_verification: None,
+            self_verification_seed: None,
+            accumulator: None,
+            axes: Default::default(),
+            env_axes: Default::default(),
+            axis_budget: Default::default(),
+            outcome_classifiers: Default::default(),
+            max_absorption: Default::default(),
+            safety_limits: Default::default(),
+            activation_limits: Default::default(),
+            adaptive_hit_limits: None,
+            hygiene_report: false,
+            safety_valve_is_error: false,
+            audit_injections: false,
+            audit_injection_purity: false,
+            error_tagging: false,
+            required_checks: Default::default(),
+            counter_limits: Default::default(),
+            parameters: Default::default(),
+            outer_path: None,
+            stage_split: 0.5,
+            rng_seed: 0,
+            shard: None,
+            event_sinks: Default::default(),
+            shard_depth: crate::shard::DEFAULT_DEPTH,
+            plan_paths: false,
+            recent_events: 256,
+            continue_after_checkpoint_violation: false,
+            decision_bytes: None,
+            #[cfg(feature = "log")]
+            failure_logging: None,
+            #[cfg(feature = "tempdir")]
+            managed_tempdir: false,
+            #[cfg(feature = "ctrlc")]
+            graceful_interrupt: false,
         }
     }
 }
@@ -20,4 +199,178 @@ impl Options {
         self.branch_preference = branch_preference;
         self
     }
+
+    /// Process-global feature which cannot be shared with other runners
+    pub fn exclusive_feature(&self) -> Option<&'static str> {
+        (self.capture_output && crate::capture::SUPPORTED).then_some("output capture")
+    }
+
+    /// Override defaults with values from environment variables
+    ///
+    /// Variables are obtained through `lookup`, unset and empty
+    /// ones are ignored.
+    pub fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        let get = |name: &str| lookup(name).filter(|value| !value.is_empty());
+        let invalid = |name: &str, value: &str, expected: &str| {
+            format!("invalid value {value:?} of {name}, expected {expected}")
+        };
+
+        if let Some(value) = get("FAINE_MAX_ITERATIONS") {
+            self.max_iterations =
+                Some(value.parse().map_err(|_| {
+                    invalid("FAINE_MAX_ITERATIONS", &value, "a non-negative integer")
+                })?);
+        }
+        if let Some(value) = get("FAINE_MAX_DURATION_MS") {
+            self.max_duration = Some(Duration::from_millis(value.parse().map_err(|_| {
+                invalid("FAINE_MAX_DURATION_MS", &value, "a non-negative integer")
+            })?));
+        }
+        if let Some(value) = get("FAINE_BRANCH_PREFERENCE") {
+            self.branch_preference = match value.to_ascii_lowercase().as_str() {
+                "activate" => Branch::Activate,
+                "skip" => Branch::Skip,
+                _ => {
+                    return Err(invalid(
+                        "FAINE_BRANCH_PREFERENCE",
+                        &value,
+                        "`activate` or `skip`",
+                    ));
+                }
+            };
+        }
+        if let Some(value) = get("FAINE_STRATEGY") {
+            self.tier = match value.to_ascii_lowercase().as_str() {
+                "full" => Tier::Full,
+                "smoke" => Tier::Smoke,
+                _ => return Err(invalid("FAINE_STRATEGY", &value, "`full` or `smoke`")),
+            };
+        }
+        if let Some(value) = get("FAINE_STRICT_DETERMINISM") {
+            self.strict_determinism = match value.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" => false,
+                _ => {
+                    return Err(invalid("FAINE_STRICT_DETERMINISM", &value, "a boolean"));
+                }
+            };
+        }
+        if let Some(value) = get("FAINE_DEDUP_STORE") {
+            self.dedup_store = Some(value.into());
+        }
+        if let Some(value) = get("FAINE_SHARD") {
+            self.shard = Some(
+                crate::shard::parse(&value)
+                    .ok_or_else(|| invalid("FAINE_SHARD", &value, "`index/total`"))?,
+            );
+        }
+        if let Some(value) = get("FAINE_REPLAY") {
+            let path = crate::known::activations(&value)
+                .ok_or_else(|| invalid("FAINE_REPLAY", &value, "a path key"))?;
+            self.priority_paths = vec![path];
+            self.require_reachable_priority_paths = true;
+            self.max_iterations.get_or_insert(1);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_env(vars: &[(&str, &str)]) -> Result<Options, String> {
+        let mut options = Options::default();
+        options.apply_env(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })?;
+        Ok(options)
+    }
+
+    #[test]
+    fn test_defaults() {
+        let options = from_env(&[]).unwrap();
+        assert_eq!(options.max_iterations, None);
+        assert_eq!(options.max_duration, None);
+        assert_eq!(options.branch_preference, Branch::Activate);
+        assert_eq!(options.tier, Tier::Full);
+        assert!(!options.strict_determinism);
+
+        let options = from_env(&[("FAINE_MAX_ITERATIONS", "")]).unwrap();
+        assert_eq!(options.max_iterations, None);
+    }
+
+    #[test]
+    fn test_max_iterations() {
+        let options = from_env(&[("FAINE_MAX_ITERATIONS", "10")]).unwrap();
+        assert_eq!(options.max_iterations, Some(10));
+        let err = from_env(&[("FAINE_MAX_ITERATIONS", "ten")]).err().unwrap();
+        assert_eq!(
+            err,
+            "invalid value \"ten\" of FAINE_MAX_ITERATIONS, expected a non-negative integer"
+        );
+    }
+
+    #[test]
+    fn test_max_duration() {
+        let options = from_env(&[("FAINE_MAX_DURATION_MS", "60000")]).unwrap();
+        assert_eq!(options.max_duration, Some(Duration::from_secs(60)));
+        assert!(from_env(&[("FAINE_MAX_DURATION_MS", "-1")]).is_err());
+    }
+
+    #[test]
+    fn test_branch_preference() {
+        let options = from_env(&[("FAINE_BRANCH_PREFERENCE", "Skip")]).unwrap();
+        assert_eq!(options.branch_preference, Branch::Skip);
+        assert!(from_env(&[("FAINE_BRANCH_PREFERENCE", "both")]).is_err());
+    }
+
+    #[test]
+    fn test_strategy() {
+        let options = from_env(&[("FAINE_STRATEGY", "smoke")]).unwrap();
+        assert_eq!(options.tier, Tier::Smoke);
+        assert!(from_env(&[("FAINE_STRATEGY", "random")]).is_err());
+    }
+
+    #[test]
+    fn test_strict_determinism() {
+        let options = from_env(&[("FAINE_STRICT_DETERMINISM", "1")]).unwrap();
+        assert!(options.strict_determinism);
+        let options = from_env(&[("FAINE_STRICT_DETERMINISM", "false")]).unwrap();
+        assert!(!options.strict_determinism);
+        assert!(from_env(&[("FAINE_STRICT_DETERMINISM", "maybe")]).is_err());
+    }
+
+    #[test]
+    fn test_dedup_store() {
+        let options = from_env(&[("FAINE_DEDUP_STORE", "/tmp/store")]).unwrap();
+        assert_eq!(options.dedup_store, Some(PathBuf::from("/tmp/store")));
+    }
+
+    #[test]
+    fn test_shard() {
+        let options = from_env(&[("FAINE_SHARD", "1/3")]).unwrap();
+        assert_eq!(options.shard, Some((1, 3)));
+        assert!(from_env(&[("FAINE_SHARD", "3/3")]).is_err());
+    }
+
+    #[test]
+    fn test_replay() {
+        let options = from_env(&[("FAINE_REPLAY", "[open] → write → [fsync]")]).unwrap();
+        assert_eq!(options.priority_paths, vec![vec!["open", "fsync"]]);
+        assert!(options.require_reachable_priority_paths);
+        assert_eq!(options.max_iterations, Some(1));
+
+        let options =
+            from_env(&[("FAINE_REPLAY", "[open]"), ("FAINE_MAX_ITERATIONS", "10")]).unwrap();
+        assert_eq!(options.max_iterations, Some(10));
+
+        let err = from_env(&[("FAINE_REPLAY", "[open")]).err().unwrap();
+        assert_eq!(
+            err,
+            "invalid value \"[open\" of FAINE_REPLAY, expected a path key"
+        );
+    }
 }
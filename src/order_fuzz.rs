@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::options::Options;
+use crate::report::{ExecutionPath, Report};
+use crate::runner::explore;
+
+/// Deterministic reproduction of a failure found by order fuzzing
+///
+/// Describes a sequence of paths (as failpoints to activate on each)
+/// which, explored in order, fail on the last one. Display gives
+/// a [`Runner`](crate::Runner) configuration which does that, ready to
+/// be pasted into a test.
+///
+/// See [`Runner::fuzz_orders()`](crate::Runner::fuzz_orders).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRepro {
+    prefix: Vec<Vec<String>>,
+    path: Vec<String>,
+}
+
+impl OrderRepro {
+    /// Minimized failing path, as failpoints to activate
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Paths which need to be explored before the failing one to reproduce the failure
+    ///
+    /// Empty unless the failure is order dependent.
+    pub fn prefix(&self) -> &[Vec<String>] {
+        &self.prefix
+    }
+
+    /// Whether the failing path passes when explored in isolation
+    pub fn is_order_dependent(&self) -> bool {
+        !self.prefix.is_empty()
+    }
+
+    /// All paths to explore, in order, for [`Runner::with_priority_paths()`](crate::Runner::with_priority_paths)
+    pub fn priority_paths(&self) -> Vec<Vec<String>> {
+        let mut paths = self.prefix.clone();
+        paths.push(self.path.clone());
+        paths
+    }
+}
+
+impl std::fmt::Display for OrderRepro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paths = self.priority_paths();
+        let rendered: Vec<String> = paths
+            .iter()
+            .map(|path| {
+                let names: Vec<String> = path.iter().map(|name| format!("{name:?}")).collect();
+                format!("&[{}]", names.join(", "))
+            })
+            .collect();
+        writeln!(f, "Runner::ignore_env()")?;
+        writeln!(f, "    .with_catch_panics(true)")?;
+        writeln!(f, "    .with_priority_paths(&[{}])", rendered.join(", "))?;
+        write!(f, "    .with_max_iterations({})", paths.len())
+    }
+}
+
+fn is_unexpected_failure(options: &Options, path: &ExecutionPath) -> bool {
+    match &path.panic {
+        Some(panic) => !options
+            .expected_panics
+            .iter()
+            .any(|(file, line)| panic.matches(file, *line)),
+        None => path.timed_out,
+    }
+}
+
+/// Index of the first path which failed unexpectedly
+pub(crate) fn first_failure(options: &Options, report: &Report) -> Option<usize> {
+    report
+        .paths()
+        .iter()
+        .position(|path| is_unexpected_failure(options, path))
+}
+
+/// Options for exploration of just the given paths, in order
+fn forced_options(options: &Options, paths: &[Vec<String>]) -> Options {
+    let mut options = options.clone();
+    options.order_seed = None;
+    options.priority_paths = paths.to_vec();
+    options.max_iterations = Some(paths.len());
+    options.max_duration = None;
+    options.failure_reruns = 0;
+    #[cfg(feature = "log")]
+    {
+        options.failure_logging = None;
+    }
+    options.self_verification = None;
+    options.dedup_store = None;
+    options.coverage_dir = None;
+    options.trace_dir = None;
+    options.timeline_interval = None;
+    options.diminishing_returns = None;
+    options
+}
+
+/// Explore given paths in order, returning activated failpoints of
+/// the last one if it failed
+fn reproduce<F: FnMut()>(
+    options: &Options,
+    paths: &[Vec<String>],
+    make: &mut impl FnMut() -> F,
+) -> Option<Vec<String>> {
+    let options = forced_options(options, paths);
+    let report = explore(options.clone(), &mut make());
+    let last = report.paths().get(paths.len().checked_sub(1)?)?;
+    is_unexpected_failure(&options, last).then(|| {
+        last.activated_failpoints()
+            .map(ToString::to_string)
+            .collect()
+    })
+}
+
+/// Reduce failure of the path at `index` to the shortest deterministic reproduction
+///
+/// Activations are dropped from the failing path one by one while it
+/// still fails after all preceding paths. Then, the shortest suffix of
+/// preceding paths which is still required is found by bisection,
+/// assuming that leaked state which causes the failure persists once
+/// introduced. Returns `None` if the failure does not reproduce with
+/// forced order at all.
+pub(crate) fn reduce<F: FnMut()>(
+    options: &Options,
+    report: &Report,
+    index: usize,
+    make: &mut impl FnMut() -> F,
+) -> Option<OrderRepro> {
+    let activated = |path: &ExecutionPath| -> Vec<String> {
+        path.activated_failpoints()
+            .map(ToString::to_string)
+            .collect()
+    };
+    let preceding: Vec<Vec<String>> = report.paths()[..index].iter().map(activated).collect();
+    let with_prefix = |prefix: &[Vec<String>], path: &[String]| {
+        let mut paths = prefix.to_vec();
+        paths.push(path.to_vec());
+        paths
+    };
+
+    let mut path = reproduce(
+        options,
+        &with_prefix(&preceding, &activated(&report.paths()[index])),
+        make,
+    )?;
+    let mut position = 0;
+    while position < path.len() {
+        let mut candidate = path.clone();
+        candidate.remove(position);
+        match reproduce(options, &with_prefix(&preceding, &candidate), make) {
+            Some(reproduced) => path = reproduced,
+            None => position += 1,
+        }
+    }
+
+    // number of preceding paths, from known failing to known passing
+    let (mut failing, mut passing) = (preceding.len(), None);
+    if let Some(reproduced) = reproduce(options, &with_prefix(&[], &path), make) {
+        path = reproduced;
+        failing = 0;
+    } else {
+        passing = Some(0);
+    }
+    while let Some(known_passing) = passing
+        && failing - known_passing > 1
+    {
+        let middle = known_passing + (failing - known_passing) / 2;
+        let prefix = &preceding[preceding.len() - middle..];
+        match reproduce(options, &with_prefix(prefix, &path), make) {
+            Some(reproduced) => {
+                path = reproduced;
+                failing = middle;
+            }
+            None => passing = Some(middle),
+        }
+    }
+
+    Some(OrderRepro {
+        prefix: preceding[preceding.len() - failing..].to_vec(),
+        path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let repro = OrderRepro {
+            prefix: vec![vec!["create \"tmp\"".to_string()]],
+            path: vec!["open".to_string(), "read".to_string()],
+        };
+        assert!(repro.is_order_dependent());
+        assert_eq!(
+            repro.to_string(),
+            concat!(
+                "Runner::ignore_env()\n",
+                "    .with_catch_panics(true)\n",
+                "    .with_priority_paths(&[&[\"create \\\"tmp\\\"\"], &[\"open\", \"read\"]])\n",
+                "    .with_max_iterations(2)",
+            )
+        );
+    }
+}
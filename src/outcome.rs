@@ -0,0 +1,134 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::cell::Cell;
+
+use crate::error::Error;
+use crate::report::Report;
+
+/// Outcome of [`Runner::run()`](crate::Runner::run) and other `run_*` methods
+///
+/// Wraps `Result<T, Error>`, where `T` is a [`Report`] for most
+/// methods, mirroring its commonly used methods,
+/// and may be converted into it with [`into_result()`](Self::into_result),
+/// for instance for use with `?`. Failures must be examined: an outcome
+/// holding an [`Error`] which is dropped without calling any of its
+/// methods panics with the error, so a forgotten `.unwrap()` does not
+/// make a test silently pass. Use [`ignore_failures()`](Self::ignore_failures)
+/// to discard failures explicitly.
+///
+/// ```should_panic
+/// use faine::{Runner, inject_return};
+///
+/// fn save() -> Result<(), ()> {
+///     inject_return!("write", Err(()));
+///     Ok(())
+/// }
+///
+/// // panics when dropped, as the failure was not examined
+/// let _ = Runner::ignore_env()
+///     .with_catch_panics(true)
+///     .run(|| save().unwrap());
+/// ```
+#[derive(Debug)]
+#[must_use = "exploration failures are only reported when the outcome is examined"]
+pub struct RunOutcome<T = Report> {
+    result: Option<Result<T, Error>>,
+    examined: Cell<bool>,
+}
+
+impl<T> RunOutcome<T> {
+    pub(crate) fn new(result: Result<T, Error>) -> Self {
+        Self {
+            result: Some(result),
+            examined: Cell::new(false),
+        }
+    }
+
+    fn take(mut self) -> Result<T, Error> {
+        self.result.take().expect("outcome must not be taken twice")
+    }
+
+    fn result(&self) -> &Result<T, Error> {
+        self.examined.set(true);
+        self.result
+            .as_ref()
+            .expect("outcome must not be taken twice")
+    }
+
+    /// Convert into `Result`
+    pub fn into_result(self) -> Result<T, Error> {
+        self.take()
+    }
+
+    /// Discard the outcome, including any failures
+    pub fn ignore_failures(self) {
+        self.take().ok();
+    }
+
+    /// Whether exploration has succeeded, see [`Result::is_ok()`]
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.result().is_ok()
+    }
+
+    /// Whether exploration has failed, see [`Result::is_err()`]
+    #[must_use]
+    pub fn is_err(&self) -> bool {
+        self.result().is_err()
+    }
+
+    /// Report of successful exploration, see [`Result::ok()`]
+    pub fn ok(self) -> Option<T> {
+        self.take().ok()
+    }
+
+    /// Error of failed exploration, see [`Result::err()`]
+    pub fn err(self) -> Option<Error> {
+        self.take().err()
+    }
+
+    /// Report of successful exploration, panicking otherwise, see [`Result::unwrap()`]
+    #[track_caller]
+    pub fn unwrap(self) -> T {
+        self.take().unwrap()
+    }
+
+    /// Report of successful exploration, panicking with a message otherwise, see [`Result::expect()`]
+    #[track_caller]
+    pub fn expect(self, msg: &str) -> T {
+        self.take().expect(msg)
+    }
+}
+
+impl<T: std::fmt::Debug> RunOutcome<T> {
+    /// Error of failed exploration, panicking otherwise, see [`Result::unwrap_err()`]
+    #[track_caller]
+    pub fn unwrap_err(self) -> Error {
+        self.take().unwrap_err()
+    }
+
+    /// Error of failed exploration, panicking with a message otherwise, see [`Result::expect_err()`]
+    #[track_caller]
+    pub fn expect_err(self, msg: &str) -> Error {
+        self.take().expect_err(msg)
+    }
+}
+
+impl<T> From<RunOutcome<T>> for Result<T, Error> {
+    fn from(outcome: RunOutcome<T>) -> Self {
+        outcome.into_result()
+    }
+}
+
+impl<T> Drop for RunOutcome<T> {
+    fn drop(&mut self) {
+        // panicking while unwinding would abort
+        if let Some(Err(err)) = &self.result
+            && !self.examined.get()
+            && !std::thread::panicking()
+        {
+            panic!("faine: exploration has failed, but its outcome was not examined: {err}");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::cell::{Cell, RefCell};
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
+
+/// Panic caught while executing a path
+///
+/// See [`Runner::with_catch_panics()`](crate::Runner::with_catch_panics).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct CaughtPanic {
+    /// Source file where the panic has occurred
+    pub file: String,
+
+    /// Line where the panic has occurred
+    pub line: u32,
+
+    /// Panic message, if it's a string
+    pub message: Option<String>,
+}
+
+impl CaughtPanic {
+    /// Panic location as `file:line`
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file, self.line)
+    }
+
+    fn from_hook_info(info: &PanicHookInfo<'_>) -> Self {
+        let payload = info.payload();
+        Self {
+            file: info
+                .location()
+                .map(|location| location.file().to_string())
+                .unwrap_or_default(),
+            line: info.location().map_or(0, |location| location.line()),
+            message: payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned()),
+        }
+    }
+
+    /// Check whether panic location matches expected `file` and `line`
+    ///
+    /// File is matched by path suffix on component boundary, so
+    /// workspace-relative paths match crate-relative ones and vice versa.
+    pub(crate) fn matches(&self, file: &str, line: u32) -> bool {
+        fn normalize(path: &str) -> String {
+            path.replace('\\', "/").trim_start_matches("./").to_string()
+        }
+        let (actual, expected) = (normalize(&self.file), normalize(file));
+        let is_suffix = |long: &str, short: &str| {
+            long == short
+                || long
+                    .strip_suffix(short)
+                    .is_some_and(|prefix| prefix.ends_with('/'))
+        };
+        self.line == line && (is_suffix(&actual, &expected) || is_suffix(&expected, &actual))
+    }
+}
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static CAUGHT: RefCell<Option<CaughtPanic>> = const { RefCell::new(None) };
+}
+
+/// Install panic hook which records panics on threads which catch them
+///
+/// All panics are then passed to the previously installed hook, so
+/// diagnostics of caught panics are not lost.
+fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.get() {
+                CAUGHT.set(Some(CaughtPanic::from_hook_info(info)));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Whether the current thread is unwinding due to a panic
+///
+/// This holds from the moment the panic hook is called until the
+/// panic is caught, so it covers drops run during unwinding.
+pub(crate) fn is_unwinding() -> bool {
+    std::thread::panicking()
+}
+
+/// Call a function, catching panics it produces
+pub(crate) fn catch(func: &mut impl FnMut()) -> Option<CaughtPanic> {
+    install_hook();
+    CATCHING.set(true);
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(func));
+    CATCHING.set(false);
+    let caught = CAUGHT.take();
+    res.err().map(|_| {
+        caught.unwrap_or(CaughtPanic {
+            file: String::new(),
+            line: 0,
+            message: None,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caught(file: &str, line: u32) -> CaughtPanic {
+        CaughtPanic {
+            file: file.into(),
+            line,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(caught("src/lib.rs", 10).matches("src/lib.rs", 10));
+        assert!(!caught("src/lib.rs", 10).matches("src/lib.rs", 11));
+        assert!(caught("crates/foo/src/lib.rs", 10).matches("src/lib.rs", 10));
+        assert!(caught("src/lib.rs", 10).matches("crates/foo/src/lib.rs", 10));
+        assert!(caught("src\\lib.rs", 10).matches("./src/lib.rs", 10));
+        assert!(!caught("src/mylib.rs", 10).matches("lib.rs", 10));
+    }
+
+    #[test]
+    fn test_catch() {
+        let caught = catch(&mut || panic!("boom")).unwrap();
+        assert_eq!(caught.file, file!());
+        assert_eq!(caught.message.as_deref(), Some("boom"));
+        assert!(catch(&mut || {}).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::functions::is_active;
+
+/// Run a parallel section of the instrumented code deterministically
+///
+/// Failpoint state is per thread, so failpoints visited on `rayon`
+/// worker threads (for instance, in `par_iter()` closures) are not
+/// explored, and if they were, paths would depend on scheduling.
+/// Wrap parallel sections into this function: under [`Runner::run()`]
+/// it installs a single thread pool made of the current thread for
+/// the duration of the section, so all its `rayon` work runs
+/// sequentially in a stable order, and all failpoints are explored
+/// with stable paths. Otherwise, the section runs in the usual pool.
+///
+/// Nested calls reuse the pool installed by the outer one.
+///
+/// ```
+/// # use std::io;
+/// use faine::{Runner, deterministic_parallel, inject_return_io_error};
+/// use rayon::prelude::*;
+///
+/// fn compress(chunk: &[u8]) -> io::Result<usize> {
+///     inject_return_io_error!("compress chunk");
+///     Ok(chunk.len() / 2)
+/// }
+///
+/// fn compress_all(chunks: &[Vec<u8>]) -> io::Result<Vec<usize>> {
+///     deterministic_parallel(|| chunks.par_iter().map(|chunk| compress(chunk)).collect())
+/// }
+///
+/// let report = Runner::ignore_env()
+///     .run(|| {
+///         let _ = compress_all(&[vec![0; 4], vec![0; 8]]);
+///     })
+///     .unwrap();
+/// assert_eq!(report.num_paths(), 3);
+/// ```
+///
+/// Available with `rayon` feature.
+///
+/// [`Runner::run()`]: crate::Runner::run
+pub fn deterministic_parallel<R: Send>(section: impl FnOnce() -> R + Send) -> R {
+    if !is_active() {
+        return section();
+    }
+    if rayon::current_thread_index().is_some() && rayon::current_num_threads() == 1 {
+        return section();
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .use_current_thread()
+        .build()
+        .expect("cannot build single thread pool for deterministic parallel section")
+        .install(section)
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use crate::__private::{FAILPOINTS, visit_variants};
+
+/// Fault parameter, as name and possible values along with their labels
+pub type Parameter = (&'static str, Vec<(f64, &'static str)>);
+
+/// Rendered parameter values, by bit pattern, so each distinct value
+/// is only leaked once
+static LABELS: LazyLock<RwLock<HashMap<u64, &'static str>>> = LazyLock::new(Default::default);
+
+fn label(value: f64) -> &'static str {
+    let bits = value.to_bits();
+    if let Some(label) = LABELS
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&bits)
+    {
+        return label;
+    }
+    LABELS
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .entry(bits)
+        .or_insert_with(|| Box::leak(format!("{value:?}").into_boxed_str()))
+}
+
+/// Construct a parameter
+pub fn declare(name: &'static str, values: &[f64]) -> Parameter {
+    (
+        name,
+        values.iter().map(|value| (*value, label(*value))).collect(),
+    )
+}
+
+/// Value of a fault parameter on the current path
+///
+/// Parameters are declared with [`Runner::with_parameter()`], and
+/// are meant to be consulted in replacement expressions of
+/// `inject_*` macros to tune severity of injected faults. On first
+/// call in an iteration, each value of the parameter becomes a
+/// separate branch of exploration, shown in the [`Report`] like
+/// `[short read fraction=0.5]`. The value is then bound for the rest
+/// of the iteration, so further calls return the same one.
+///
+/// ```
+/// use faine::{Runner, inject_override, param_f64};
+///
+/// fn read(buf: &[u8]) -> &[u8] {
+///     inject_override!(buf, "short read", {
+///         &buf[..(buf.len() as f64 * param_f64("short read fraction")) as usize]
+///     })
+/// }
+///
+/// let mut lengths = vec![];
+/// let report = Runner::ignore_env()
+///     .with_parameter("short read fraction", &[0.75, 0.5, 0.0])
+///     .run(|| lengths.push(read(b"data").len()))
+///     .unwrap();
+/// assert_eq!(lengths, [3, 2, 0, 4]);
+/// assert_eq!(report.paths()[1].to_string(), "[short read] → [short read fraction=0.5]");
+/// ```
+///
+/// # Panics
+///
+/// Outside of [`Runner::run()`], or if the parameter is not declared
+/// for the current runner.
+///
+/// [`Report`]: crate::Report
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_parameter()`]: crate::Runner::with_parameter
+#[track_caller]
+pub fn param_f64(name: &str) -> f64 {
+    let location = crate::ext::caller_location();
+    let parameter = FAILPOINTS.with_borrow(|state| {
+        let state = state.as_ref()?;
+        if let Some(value) = state.parameter_bindings.get(name) {
+            return Some(Err(*value));
+        }
+        state
+            .parameters
+            .iter()
+            .find(|(parameter, _)| *parameter == name)
+            .cloned()
+            .map(Ok)
+    });
+    let (name, values) = match parameter {
+        Some(Ok(parameter)) => parameter,
+        Some(Err(value)) => return value,
+        None => panic!(
+            "fault parameter {name:?} is not declared for the current runner, see Runner::with_parameter()"
+        ),
+    };
+    let labels: Vec<&'static str> = values.iter().map(|(_, label)| *label).collect();
+    // the last value is taken when none of the others were, so there's
+    // exactly one branch per value
+    let index =
+        visit_variants(name, location, &labels[..labels.len() - 1]).unwrap_or(labels.len() - 1);
+    let value = values[index].0;
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            state.parameter_bindings.insert(name, value);
+        }
+    });
+    value
+}
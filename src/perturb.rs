@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Preset set of perturbations for [`inject_perturb!`]
+///
+/// [`inject_perturb!`]: crate::inject_perturb
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Perturb {
+    /// NaN, positive and negative infinities, negative zero, smallest
+    /// positive normal value, maximal and minimal finite values
+    ///
+    /// Applicable to floating point types.
+    FloatEdgeCases,
+
+    /// Zero, maximal and minimal values, and the original value
+    /// plus and minus one (wrapping)
+    ///
+    /// Applicable to integer types.
+    IntEdgeCases,
+}
+
+/// Type which supports [`Perturb`] presets
+pub trait Perturbable: Sized {
+    /// Symbolic descriptions of perturbations of a given preset, used in reports
+    ///
+    /// # Panics
+    ///
+    /// Panics if the preset is not applicable to the type.
+    fn variants(preset: Perturb) -> &'static [&'static str];
+
+    /// Perturbation with a given index for a given original value
+    ///
+    /// Index corresponds to one in [`variants()`](Self::variants).
+    fn perturbation(preset: Perturb, index: usize, value: &Self) -> Self;
+}
+
+macro_rules! impl_perturbable_float {
+    ($($t:ty),*) => {$(
+        impl Perturbable for $t {
+            fn variants(preset: Perturb) -> &'static [&'static str] {
+                match preset {
+                    Perturb::FloatEdgeCases => {
+                        &["NaN", "+inf", "-inf", "-0.0", "MIN_POSITIVE", "MAX", "MIN"]
+                    }
+                    _ => panic!("{preset:?} is not applicable to {}", stringify!($t)),
+                }
+            }
+
+            fn perturbation(_preset: Perturb, index: usize, _value: &Self) -> Self {
+                [
+                    <$t>::NAN,
+                    <$t>::INFINITY,
+                    <$t>::NEG_INFINITY,
+                    -0.0,
+                    <$t>::MIN_POSITIVE,
+                    <$t>::MAX,
+                    <$t>::MIN,
+                ][index]
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_perturbable_int {
+    ($($t:ty),*) => {$(
+        impl Perturbable for $t {
+            fn variants(preset: Perturb) -> &'static [&'static str] {
+                match preset {
+                    Perturb::IntEdgeCases => &["0", "MAX", "MIN", "value+1", "value-1"],
+                    _ => panic!("{preset:?} is not applicable to {}", stringify!($t)),
+                }
+            }
+
+            fn perturbation(_preset: Perturb, index: usize, value: &Self) -> Self {
+                [
+                    0,
+                    <$t>::MAX,
+                    <$t>::MIN,
+                    value.wrapping_add(1),
+                    value.wrapping_sub(1),
+                ][index]
+            }
+        }
+    )*};
+}
+
+impl_perturbable_float!(f32, f64);
+impl_perturbable_int!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perturbations<T: Perturbable>(preset: Perturb, value: T) -> Vec<T> {
+        (0..T::variants(preset).len())
+            .map(|index| T::perturbation(preset, index, &value))
+            .collect()
+    }
+
+    #[test]
+    fn test_int() {
+        assert_eq!(
+            perturbations(Perturb::IntEdgeCases, 255u8),
+            vec![0, 255, 0, 0, 254]
+        );
+    }
+
+    #[test]
+    fn test_float() {
+        let values = perturbations(Perturb::FloatEdgeCases, 1.0f64);
+        assert_eq!(values.len(), f64::variants(Perturb::FloatEdgeCases).len());
+        assert!(values[0].is_nan());
+        assert!(values[3].is_sign_negative() && values[3] == 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "IntEdgeCases is not applicable to f64")]
+    fn test_mismatch() {
+        f64::variants(Perturb::IntEdgeCases);
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::intern;
+
+/// Failpoints an iteration is going to activate, known before it starts
+///
+/// Passed to the setup closure of [`Runner::run_with_fixture()`](crate::Runner::run_with_fixture),
+/// so the environment may be tailored to the injected faults. The
+/// plan is fully known when replaying paths, when following priority
+/// paths, and when newly discovered failpoints are always skipped,
+/// that is in [smoke tier](crate::Runner::smoke) or with
+/// [skip preference](crate::Runner::with_branch_preference), as long
+/// as the tested code is deterministic. In general exploration, the
+/// plan is unknown and empty, and setup must not depend on it.
+///
+/// Failpoints activated by conditions (see `fails_if:` argument of
+/// [`inject_return_io_error!`](crate::inject_return_io_error)) are not
+/// included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlannedPath {
+    activations: Vec<String>,
+    fully_known: bool,
+}
+
+impl PlannedPath {
+    pub(crate) fn known(activations: Vec<String>) -> Self {
+        Self {
+            activations,
+            fully_known: true,
+        }
+    }
+
+    /// Whether the plan is known
+    ///
+    /// If not, [`activations()`](Self::activations) is empty, and
+    /// any failpoint may be activated.
+    pub fn is_fully_known(&self) -> bool {
+        self.fully_known
+    }
+
+    /// Names of failpoints to be activated, in order
+    ///
+    /// Visits of all other failpoints are going to be skipped.
+    pub fn activations(&self) -> &[String] {
+        &self.activations
+    }
+
+    /// Whether a failpoint is going to be activated
+    ///
+    /// Names are matched like in runner methods which take failpoint
+    /// names, see [`Selector`](crate::Selector).
+    pub fn activates(&self, failpoint: &str) -> bool {
+        self.activations
+            .iter()
+            .any(|activation| intern::refers_to(failpoint, activation))
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Failpoint-aware wrapper for [`std::process::Command`]
+//!
+//! [`FaineCommand`] defines a multi-valued failpoint, named after the
+//! label given to its constructor, with the following variants:
+//!
+//! - `spawn NotFound`, `spawn PermissionDenied`: spawning the process
+//!   fails with corresponding [`io::Error`], the process is not run.
+//! - `exit status`: the process is not run, and nonzero exit status
+//!   (see [`FaineCommand::with_injected_exit_code()`]) is returned along
+//!   with (empty by default) injected stdout.
+//! - `truncated stdout`: the process is run, but its captured stdout
+//!   is cut at an offset drawn from [`rng()`](crate::rng), so it's
+//!   the same for replays of a path (see
+//!   [`FaineCommand::with_truncated_stdout_limit()`]).
+//!
+//! Available with `process` feature.
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output};
+
+use crate::__private::{self, visit_variants};
+use crate::rng::rng;
+
+const SPAWN_NOT_FOUND: &str = "spawn NotFound";
+const SPAWN_PERMISSION_DENIED: &str = "spawn PermissionDenied";
+const EXIT_STATUS: &str = "exit status";
+const TRUNCATED_STDOUT: &str = "truncated stdout";
+
+#[cfg(any(unix, windows))]
+const VARIANTS: &[&str] = &[
+    SPAWN_NOT_FOUND,
+    SPAWN_PERMISSION_DENIED,
+    EXIT_STATUS,
+    TRUNCATED_STDOUT,
+];
+
+// constructing arbitrary exit status is not possible on other platforms
+#[cfg(not(any(unix, windows)))]
+const VARIANTS: &[&str] = &[SPAWN_NOT_FOUND, SPAWN_PERMISSION_DENIED, TRUNCATED_STDOUT];
+
+#[cfg(unix)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    // wait status encoding of normal termination
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn exit_status_from_code(_code: i32) -> ExitStatus {
+    unreachable!("exit status injection is not supported on this platform")
+}
+
+/// Wrapper for [`std::process::Command`] which injects subprocess failures
+///
+/// ```no_run
+/// use faine::process::FaineCommand;
+///
+/// fn git_status() -> std::io::Result<String> {
+///     let output = FaineCommand::new("git status", "git")
+///         .args(["status", "--porcelain"])
+///         .output()?;
+///     Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+/// }
+/// ```
+pub struct FaineCommand {
+    label: &'static str,
+    command: Command,
+    injected_exit_code: i32,
+    injected_stdout: Vec<u8>,
+    truncated_stdout_limit: usize,
+}
+
+impl FaineCommand {
+    /// Construct a command for a given program
+    ///
+    /// `label` is used as the failpoint name.
+    pub fn new(label: &'static str, program: impl AsRef<OsStr>) -> Self {
+        Self {
+            label,
+            command: Command::new(program),
+            injected_exit_code: 1,
+            injected_stdout: Vec::new(),
+            truncated_stdout_limit: usize::MAX,
+        }
+    }
+
+    /// Set exit code returned when exit status is injected (default 1)
+    pub fn with_injected_exit_code(mut self, code: i32) -> Self {
+        self.injected_exit_code = code;
+        self
+    }
+
+    /// Set stdout returned when exit status is injected (default empty)
+    pub fn with_injected_stdout(mut self, stdout: impl Into<Vec<u8>>) -> Self {
+        self.injected_stdout = stdout.into();
+        self
+    }
+
+    /// Set maximal number of bytes left when stdout is truncated (unlimited by default)
+    ///
+    /// Truncated stdout is always shorter than the actual one, and
+    /// is cut at an offset drawn from [`rng()`](crate::rng) up to the
+    /// limit, so a limit allows to explore output cut short.
+    pub fn with_truncated_stdout_limit(mut self, limit: usize) -> Self {
+        self.truncated_stdout_limit = limit;
+        self
+    }
+
+    /// See [`Command::arg()`]
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.command.arg(arg);
+        self
+    }
+
+    /// See [`Command::args()`]
+    pub fn args(mut self, args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Self {
+        self.command.args(args);
+        self
+    }
+
+    /// See [`Command::env()`]
+    pub fn env(mut self, key: impl AsRef<OsStr>, val: impl AsRef<OsStr>) -> Self {
+        self.command.env(key, val);
+        self
+    }
+
+    /// See [`Command::current_dir()`]
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.command.current_dir(dir);
+        self
+    }
+
+    /// Access wrapped [`Command`] for further configuration
+    pub fn command_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+
+    fn spawn_error(&self, kind: io::ErrorKind) -> io::Error {
+        io::Error::new(kind, __private::injected_error(self.label))
+    }
+
+    /// Run the command and collect its output, see [`Command::output()`]
+    #[track_caller]
+    pub fn output(&mut self) -> io::Result<Output> {
+        let location = crate::ext::caller_location();
+        match visit_variants(self.label, location, VARIANTS).map(|index| VARIANTS[index]) {
+            Some(SPAWN_NOT_FOUND) => Err(self.spawn_error(io::ErrorKind::NotFound)),
+            Some(SPAWN_PERMISSION_DENIED) => Err(self.spawn_error(io::ErrorKind::PermissionDenied)),
+            Some(EXIT_STATUS) => Ok(Output {
+                status: exit_status_from_code(self.injected_exit_code),
+                stdout: self.injected_stdout.clone(),
+                stderr: Vec::new(),
+            }),
+            Some(TRUNCATED_STDOUT) => {
+                let mut output = self.command.output()?;
+                let end = output
+                    .stdout
+                    .len()
+                    .min(self.truncated_stdout_limit.saturating_add(1));
+                if end > 0 {
+                    output.stdout.truncate(rng().range(0..end as u64) as usize);
+                }
+                Ok(output)
+            }
+            _ => self.command.output(),
+        }
+    }
+
+    /// Run the command and wait for its exit status, see [`Command::status()`]
+    ///
+    /// Stdout truncation does not apply here, and the process is run
+    /// normally in that case.
+    #[track_caller]
+    pub fn status(&mut self) -> io::Result<ExitStatus> {
+        let location = crate::ext::caller_location();
+        match visit_variants(self.label, location, VARIANTS).map(|index| VARIANTS[index]) {
+            Some(SPAWN_NOT_FOUND) => Err(self.spawn_error(io::ErrorKind::NotFound)),
+            Some(SPAWN_PERMISSION_DENIED) => Err(self.spawn_error(io::ErrorKind::PermissionDenied)),
+            Some(EXIT_STATUS) => Ok(exit_status_from_code(self.injected_exit_code)),
+            _ => self.command.status(),
+        }
+    }
+}
+
+#[cfg(all(test, any(unix, windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_from_code() {
+        assert_eq!(exit_status_from_code(0).code(), Some(0));
+        assert_eq!(exit_status_from_code(3).code(), Some(3));
+        assert!(!exit_status_from_code(1).success());
+    }
+}
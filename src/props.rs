@@ -0,0 +1,362 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Ready-made checks of common properties
+//!
+//! Each check returns a [`PropertyViolation`] explaining what exactly
+//! is wrong, and records a [check](crate::checked) named after the
+//! property, so paths on which it was skipped may be told apart.
+//! Unwrapping the result under [`Runner::run()`](crate::Runner::run)
+//! fails the path as usual, with the violation and the activated
+//! failpoints in the panic message.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::functions::activated_names;
+
+const EXCERPT_LEN: usize = 16;
+
+/// Way in which a property was violated
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// Content is missing, for instance, the file was removed
+    Missing,
+    /// Content could not be read
+    Unreadable(io::ErrorKind),
+    /// Content is empty, while neither old nor new content is
+    Empty,
+    /// Content is a truncated new content
+    Partial {
+        /// Number of bytes present
+        written: usize,
+        /// Number of bytes in the new content
+        expected: usize,
+    },
+    /// Content is neither old nor new one
+    Unexpected,
+    /// Operation succeeded, but content is still the old one
+    SucceededWithOld,
+    /// Operation failed, but content is already the new one
+    FailedWithNew,
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "content is missing"),
+            Self::Unreadable(kind) => write!(f, "content cannot be read: {kind}"),
+            Self::Empty => write!(f, "content is empty"),
+            Self::Partial { written, expected } => write!(
+                f,
+                "content is partially written ({written} of {expected} bytes)"
+            ),
+            Self::Unexpected => write!(f, "content is neither old nor new"),
+            Self::SucceededWithOld => write!(f, "operation succeeded, but content is old"),
+            Self::FailedWithNew => write!(f, "operation failed, but content is new"),
+        }
+    }
+}
+
+/// Violation of a property checked by functions of this module
+///
+/// `Debug` is the same as `Display`, so unwrapping produces a
+/// readable panic message.
+pub struct PropertyViolation {
+    property: &'static str,
+    target: String,
+    kind: ViolationKind,
+    content: Option<Vec<u8>>,
+    activated: Vec<String>,
+}
+
+impl PropertyViolation {
+    /// Name of the violated property
+    pub fn property(&self) -> &'static str {
+        self.property
+    }
+
+    /// Way in which the property was violated
+    pub fn kind(&self) -> &ViolationKind {
+        &self.kind
+    }
+
+    /// Content found, if it could be read
+    pub fn content(&self) -> Option<&[u8]> {
+        self.content.as_deref()
+    }
+
+    /// Names of failpoints activated on the path
+    ///
+    /// Empty outside of [`Runner::run()`](crate::Runner::run).
+    pub fn activated(&self) -> &[String] {
+        &self.activated
+    }
+}
+
+fn write_excerpt(f: &mut fmt::Formatter<'_>, content: &[u8]) -> fmt::Result {
+    let excerpt = &content[..content.len().min(EXCERPT_LEN)];
+    for byte in excerpt {
+        write!(f, "{byte:02x} ")?;
+    }
+    write!(f, "|")?;
+    for &byte in excerpt {
+        let c = if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        };
+        write!(f, "{c}")?;
+    }
+    write!(f, "|")?;
+    if content.len() > EXCERPT_LEN {
+        write!(f, " ({} more bytes)", content.len() - EXCERPT_LEN)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for PropertyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {}: {}", self.property, self.target, self.kind)?;
+        if let Some(content) = &self.content
+            && !content.is_empty()
+            && matches!(
+                self.kind,
+                ViolationKind::Partial { .. } | ViolationKind::Unexpected
+            )
+        {
+            write!(f, "\n  content: ")?;
+            write_excerpt(f, content)?;
+        }
+        if !self.activated.is_empty() {
+            write!(f, "\n  activated: {}", self.activated.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PropertyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for PropertyViolation {}
+
+fn check_atomic_replace(
+    target: String,
+    content: io::Result<Option<Vec<u8>>>,
+    old: &[u8],
+    new: &[u8],
+    succeeded: bool,
+) -> Result<(), PropertyViolation> {
+    crate::checked!("atomic replace");
+    let (kind, content) = match content {
+        Err(err) => (ViolationKind::Unreadable(err.kind()), None),
+        Ok(None) => (ViolationKind::Missing, None),
+        Ok(Some(content)) => {
+            if content == new && (succeeded || old == new) || content == old && !succeeded {
+                return Ok(());
+            }
+            let kind = if content == old {
+                ViolationKind::SucceededWithOld
+            } else if content == new {
+                ViolationKind::FailedWithNew
+            } else if content.is_empty() {
+                ViolationKind::Empty
+            } else if new.starts_with(&content) {
+                ViolationKind::Partial {
+                    written: content.len(),
+                    expected: new.len(),
+                }
+            } else {
+                ViolationKind::Unexpected
+            };
+            (kind, Some(content))
+        }
+    };
+    Err(PropertyViolation {
+        property: "atomic replace",
+        target,
+        kind,
+        content,
+        activated: activated_names(),
+    })
+}
+
+/// Check that a file was atomically replaced, or left intact
+///
+/// The file at `path` is expected to have `new` content if `res` is
+/// `Ok`, and `old` content otherwise. Missing, empty, partially written
+/// or otherwise unexpected content is always a violation.
+///
+/// ```
+/// # use std::fs;
+/// # use std::io;
+/// # use std::path::Path;
+/// use faine::props::atomic_replace;
+///
+/// fn replace_file(path: &Path, content: &str) -> io::Result<()> {
+///     let temp_path = path.with_extension("tmp");
+///     fs::write(&temp_path, content)?;
+///     fs::rename(&temp_path, path)
+/// }
+///
+/// let tempdir = tempfile::tempdir().unwrap();
+/// let path = tempdir.path().join("myfile");
+/// fs::write(&path, "old").unwrap();
+/// let res = replace_file(&path, "new");
+/// atomic_replace(&path, b"old", b"new", &res).unwrap();
+/// ```
+pub fn atomic_replace<T, E>(
+    path: impl AsRef<Path>,
+    old: &[u8],
+    new: &[u8],
+    res: &Result<T, E>,
+) -> Result<(), PropertyViolation> {
+    let path = path.as_ref();
+    let content = match std::fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+    check_atomic_replace(path.display().to_string(), content, old, new, res.is_ok())
+}
+
+/// Check that content of a store was atomically replaced, or left intact
+///
+/// Same as [`atomic_replace()`], but reads current content with
+/// `read`, which should return `None` for missing content.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// use faine::props::atomic_replace_with;
+///
+/// let store = HashMap::from([("key", b"new".to_vec())]);
+/// let res: Result<(), ()> = Ok(());
+/// atomic_replace_with(|| Ok(store.get("key").cloned()), b"old", b"new", &res).unwrap();
+/// ```
+pub fn atomic_replace_with<T, E>(
+    read: impl FnOnce() -> io::Result<Option<Vec<u8>>>,
+    old: &[u8],
+    new: &[u8],
+    res: &Result<T, E>,
+) -> Result<(), PropertyViolation> {
+    check_atomic_replace("content".to_string(), read(), old, new, res.is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(
+        content: Option<&[u8]>,
+        old: &[u8],
+        new: &[u8],
+        succeeded: bool,
+    ) -> Option<ViolationKind> {
+        let res = if succeeded { Ok(()) } else { Err(()) };
+        atomic_replace_with(|| Ok(content.map(<[u8]>::to_vec)), old, new, &res)
+            .err()
+            .map(|violation| violation.kind().clone())
+    }
+
+    #[test]
+    fn test_valid() {
+        assert_eq!(check(Some(b"new"), b"old", b"new", true), None);
+        assert_eq!(check(Some(b"old"), b"old", b"new", false), None);
+    }
+
+    #[test]
+    fn test_mismatched_result() {
+        assert_eq!(
+            check(Some(b"old"), b"old", b"new", true),
+            Some(ViolationKind::SucceededWithOld)
+        );
+        assert_eq!(
+            check(Some(b"new"), b"old", b"new", false),
+            Some(ViolationKind::FailedWithNew)
+        );
+    }
+
+    #[test]
+    fn test_broken() {
+        assert_eq!(
+            check(None, b"old", b"new", false),
+            Some(ViolationKind::Missing)
+        );
+        assert_eq!(
+            check(Some(b""), b"old", b"new", false),
+            Some(ViolationKind::Empty)
+        );
+        assert_eq!(
+            check(Some(b"ne"), b"old", b"new", false),
+            Some(ViolationKind::Partial {
+                written: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            check(Some(b"foo"), b"old", b"new", true),
+            Some(ViolationKind::Unexpected)
+        );
+    }
+
+    #[test]
+    fn test_equal_contents() {
+        assert_eq!(check(Some(b"same"), b"same", b"same", true), None);
+        assert_eq!(check(Some(b"same"), b"same", b"same", false), None);
+    }
+
+    #[test]
+    fn test_empty_old() {
+        assert_eq!(check(Some(b""), b"", b"new", false), None);
+        assert_eq!(
+            check(Some(b""), b"", b"new", true),
+            Some(ViolationKind::SucceededWithOld)
+        );
+        assert_eq!(
+            check(Some(b"n"), b"", b"new", false),
+            Some(ViolationKind::Partial {
+                written: 1,
+                expected: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_unreadable() {
+        let res: Result<(), ()> = Ok(());
+        let violation = atomic_replace_with(
+            || Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+            b"old",
+            b"new",
+            &res,
+        )
+        .unwrap_err();
+        assert_eq!(
+            violation.kind(),
+            &ViolationKind::Unreadable(io::ErrorKind::PermissionDenied)
+        );
+        assert_eq!(violation.content(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let res: Result<(), ()> = Err(());
+        let violation = atomic_replace_with(
+            || Ok(Some(b"new content\x00 which is long".to_vec())),
+            b"old",
+            b"new content\x00 which is longer",
+            &res,
+        )
+        .unwrap_err();
+        assert_eq!(
+            violation.to_string(),
+            "atomic replace of content: content is partially written (26 of 28 bytes)\n  \
+             content: 6e 65 77 20 63 6f 6e 74 65 6e 74 00 20 77 68 69 |new content. whi| (10 more bytes)"
+        );
+    }
+}
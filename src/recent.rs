@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::__private::State;
+use crate::checks::check_name;
+use crate::common::{Branch, Label};
+use crate::counters::counter_name;
+use crate::report::Step;
+
+/// Event recorded into [`EventBuffer`], with interned ids which are
+/// resolved only if the buffer is reported
+#[derive(Clone, Copy)]
+enum Entry {
+    Visit(Label, Branch),
+    Counter(usize, u64),
+    Check(usize),
+}
+
+/// Bounded buffer of the latest events of the current iteration
+pub struct EventBuffer {
+    start: Instant,
+    capacity: usize,
+    entries: VecDeque<(Duration, Entry)>,
+    num_dropped: usize,
+}
+
+impl EventBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            start: Instant::now(),
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+            num_dropped: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.start = Instant::now();
+        self.entries.clear();
+        self.num_dropped = 0;
+    }
+
+    fn push(&mut self, entry: Entry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.num_dropped += 1;
+        }
+        self.entries.push_back((self.start.elapsed(), entry));
+    }
+
+    pub fn record_visit(&mut self, label: Label, branch: Branch) {
+        self.push(Entry::Visit(label, branch));
+    }
+
+    pub fn record_counter(&mut self, id: usize, value: u64) {
+        self.push(Entry::Counter(id, value));
+    }
+
+    pub fn record_check(&mut self, id: usize) {
+        self.push(Entry::Check(id));
+    }
+}
+
+/// Take recorded events out of the state, resolving their names
+pub fn take_recent_events(state: &mut State) -> Option<RecentEvents> {
+    let buffer = state.recent_events.as_mut()?;
+    let entries = std::mem::take(&mut buffer.entries);
+    let num_dropped = std::mem::take(&mut buffer.num_dropped);
+    let events = entries
+        .into_iter()
+        .filter_map(|(at, entry)| {
+            let kind = match entry {
+                Entry::Visit(label, branch) => RecentEventKind::Visit(state.step(label, branch)?),
+                Entry::Counter(id, value) => RecentEventKind::Counter {
+                    name: counter_name(id),
+                    value,
+                },
+                Entry::Check(id) => RecentEventKind::Check(check_name(id)),
+            };
+            Some(RecentEvent { at, kind })
+        })
+        .collect();
+    Some(RecentEvents {
+        events,
+        num_dropped,
+    })
+}
+
+/// Latest events which have led to a failure of a path
+///
+/// See [`Runner::with_recent_events()`](crate::Runner::with_recent_events).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RecentEvents {
+    /// Events in order of recording
+    pub events: Vec<RecentEvent>,
+
+    /// Number of earlier events which did not fit into the buffer
+    pub num_dropped: usize,
+}
+
+impl RecentEvents {
+    /// Whether earlier events were dropped
+    pub fn is_truncated(&self) -> bool {
+        self.num_dropped > 0
+    }
+}
+
+impl std::fmt::Display for RecentEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_truncated() {
+            writeln!(f, "({} earlier event(s) dropped)", self.num_dropped)?;
+        }
+        for event in &self.events {
+            writeln!(f, "{event}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Event recorded in [`RecentEvents`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RecentEvent {
+    /// Time since the start of the iteration
+    pub at: Duration,
+
+    /// What has happened
+    pub kind: RecentEventKind,
+}
+
+impl std::fmt::Display for RecentEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+{:.3?} ", self.at)?;
+        match &self.kind {
+            RecentEventKind::Visit(step) => write!(f, "{step}"),
+            RecentEventKind::Counter { name, value } => write!(f, "count {name} += {value}"),
+            RecentEventKind::Check(name) => write!(f, "check {name}"),
+        }
+    }
+}
+
+/// Kind of [`RecentEvent`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RecentEventKind {
+    /// Failpoint visit
+    Visit(Step),
+
+    /// Counter bump, see [`count!`](crate::count)
+    Counter {
+        /// Name of the counter
+        name: &'static str,
+        /// Amount added
+        value: u64,
+    },
+
+    /// Executed check, see [`checked!`](crate::checked)
+    Check(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_buffer() {
+        let mut buffer = EventBuffer::new(2);
+        for id in 0..5 {
+            buffer.record_check(id);
+        }
+        assert_eq!(buffer.num_dropped, 3);
+        let ids: Vec<_> = buffer
+            .entries
+            .iter()
+            .map(|(_, entry)| match entry {
+                Entry::Check(id) => *id,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ids, [3, 4]);
+        buffer.reset();
+        assert!(buffer.entries.is_empty());
+        assert_eq!(buffer.num_dropped, 0);
+    }
+}
@@ -0,0 +1,347 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Recording of failpoint visits, for asserting order of operations
+//!
+//! [`Recorder::record()`] runs the code once with all failpoints
+//! skipped, and returns a [`Trace`] of visited failpoints, which may
+//! be checked against a script of [`Pattern`]s:
+//!
+//! ```
+//! use faine::inject_return;
+//! use faine::recorder::{Recorder, any, repeated, unordered};
+//!
+//! fn step(name: &'static str) -> Result<(), ()> {
+//!     inject_return!(name, Err(()));
+//!     Ok(())
+//! }
+//!
+//! let trace = Recorder::record(|| {
+//!     let _ = step("open");
+//!     let _ = step("read");
+//!     let _ = step("read");
+//!     let _ = step("sync");
+//!     let _ = step("flush");
+//!     let _ = step("close");
+//! });
+//! trace.assert_matches(&[
+//!     "open".into(),
+//!     repeated("read", 1..=3),
+//!     unordered(["flush", "sync"]),
+//!     any(),
+//! ]);
+//! ```
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::common::Branch;
+use crate::options::Options;
+use crate::report::Step;
+use crate::runner::explore;
+
+/// Runner which records failpoint visits instead of exploring them
+///
+/// See [module documentation](self).
+#[derive(Debug)]
+pub struct Recorder;
+
+impl Recorder {
+    /// Run the code once with all failpoints skipped, recording visits
+    ///
+    /// Panics of the code are propagated.
+    pub fn record(func: impl FnOnce()) -> Trace {
+        let mut func = Some(func);
+        let mut options = Options::default().branch_preference(Branch::Skip);
+        options.max_iterations = Some(1);
+        let report = explore(options, &mut || {
+            if let Some(func) = func.take() {
+                func();
+            }
+        });
+        Trace {
+            steps: report
+                .paths()
+                .first()
+                .map(|path| path.steps.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Matcher of failpoint visits in a [`Trace`]
+///
+/// Constructed from a failpoint name (`"open".into()`), or with
+/// [`any()`], [`repeated()`] and [`unordered()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// Single visit of a given failpoint
+    Name(&'static str),
+    /// Single visit of any failpoint
+    Any,
+    /// Given number of consecutive matches of a pattern
+    Repeated(Box<Pattern>, RangeInclusive<usize>),
+    /// Consecutive matches of all patterns, in any order
+    Unordered(Vec<Pattern>),
+}
+
+impl From<&'static str> for Pattern {
+    fn from(name: &'static str) -> Self {
+        Self::Name(name)
+    }
+}
+
+/// Pattern matching a single visit of any failpoint
+pub fn any() -> Pattern {
+    Pattern::Any
+}
+
+/// Pattern matching a given number of consecutive matches of a pattern
+pub fn repeated(pattern: impl Into<Pattern>, times: RangeInclusive<usize>) -> Pattern {
+    Pattern::Repeated(Box::new(pattern.into()), times)
+}
+
+/// Pattern matching consecutive matches of all given patterns, in any order
+pub fn unordered<P: Into<Pattern>>(patterns: impl IntoIterator<Item = P>) -> Pattern {
+    Pattern::Unordered(patterns.into_iter().map(Into::into).collect())
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name:?}"),
+            Self::Any => write!(f, "any()"),
+            Self::Repeated(pattern, times) => {
+                write!(
+                    f,
+                    "repeated({pattern}, {}..={})",
+                    times.start(),
+                    times.end()
+                )
+            }
+            Self::Unordered(patterns) => {
+                let patterns: Vec<_> = patterns.iter().map(ToString::to_string).collect();
+                write!(f, "unordered([{}])", patterns.join(", "))
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// All positions a match starting at a given position may end at
+    fn ends(&self, names: &[&str], start: usize) -> Vec<usize> {
+        match self {
+            Self::Name(name) => names
+                .get(start)
+                .filter(|visited| *visited == name)
+                .map(|_| vec![start + 1])
+                .unwrap_or_default(),
+            Self::Any => {
+                if start < names.len() {
+                    vec![start + 1]
+                } else {
+                    vec![]
+                }
+            }
+            Self::Repeated(pattern, times) => {
+                let mut ends = vec![];
+                let mut current = vec![start];
+                for count in 0..=*times.end() {
+                    if count >= *times.start() {
+                        ends.extend(&current);
+                    }
+                    if current.is_empty() || count == *times.end() {
+                        break;
+                    }
+                    let mut next: Vec<usize> = current
+                        .iter()
+                        .flat_map(|&position| pattern.ends(names, position))
+                        // empty matches cannot make progress
+                        .filter(|end| !current.contains(end))
+                        .collect();
+                    next.sort_unstable();
+                    next.dedup();
+                    current = next;
+                }
+                ends.sort_unstable();
+                ends.dedup();
+                ends
+            }
+            Self::Unordered(patterns) => {
+                let mut ends = vec![];
+                let mut used = vec![false; patterns.len()];
+                unordered_ends(patterns, &mut used, names, start, &mut ends);
+                ends.sort_unstable();
+                ends.dedup();
+                ends
+            }
+        }
+    }
+}
+
+fn unordered_ends(
+    patterns: &[Pattern],
+    used: &mut [bool],
+    names: &[&str],
+    start: usize,
+    ends: &mut Vec<usize>,
+) {
+    if used.iter().all(|used| *used) {
+        ends.push(start);
+        return;
+    }
+    for i in 0..patterns.len() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        for end in patterns[i].ends(names, start) {
+            unordered_ends(patterns, used, names, end, ends);
+        }
+        used[i] = false;
+    }
+}
+
+/// Alignment of script patterns to visits, as ranges of matched visits
+type Alignment = Vec<(usize, usize)>;
+
+/// Find alignment of a script to visits, either complete, or matching
+/// the longest prefix of the script
+fn align(script: &[Pattern], names: &[&str]) -> Result<Alignment, Alignment> {
+    fn search(
+        script: &[Pattern],
+        names: &[&str],
+        start: usize,
+        current: &mut Alignment,
+        best: &mut Alignment,
+    ) -> bool {
+        let Some((pattern, rest)) = script.split_first() else {
+            if start == names.len() {
+                *best = current.clone();
+                return true;
+            }
+            if best.len() < current.len() {
+                *best = current.clone();
+            }
+            return false;
+        };
+        let better = best.len() < current.len()
+            || (best.len() == current.len() && best.last().is_none_or(|last| last.1 < start));
+        if better {
+            *best = current.clone();
+        }
+        // longest matches first, for more meaningful partial alignments
+        for end in pattern.ends(names, start).into_iter().rev() {
+            current.push((start, end));
+            if search(rest, names, end, current, best) {
+                return true;
+            }
+            current.pop();
+        }
+        false
+    }
+
+    let mut best = vec![];
+    if search(script, names, 0, &mut vec![], &mut best) {
+        Ok(best)
+    } else {
+        Err(best)
+    }
+}
+
+/// Failpoint visits recorded by [`Recorder::record()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    steps: Vec<Step>,
+}
+
+impl Trace {
+    /// Visited failpoints, in order
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Names of visited failpoints, in order
+    pub fn failpoints(&self) -> Vec<&'static str> {
+        self.steps.iter().map(|step| step.failpoint).collect()
+    }
+
+    /// Whether the whole trace matches a script
+    pub fn matches(&self, script: &[Pattern]) -> bool {
+        align(script, &self.failpoints()).is_ok()
+    }
+
+    /// Assert that the whole trace matches a script
+    ///
+    /// On mismatch, panics with alignment of the script to visits,
+    /// up to the first pattern which could not be matched.
+    #[track_caller]
+    pub fn assert_matches(&self, script: &[Pattern]) {
+        let names = self.failpoints();
+        let Err(alignment) = align(script, &names) else {
+            return;
+        };
+        let patterns: Vec<String> = script.iter().map(ToString::to_string).collect();
+        let width = patterns.iter().map(|pattern| pattern.chars().count()).max();
+        let width = width.unwrap_or(0).max("<end of script>".len());
+        let visits = |start: usize, end: usize| names[start..end].join(" → ");
+
+        let mut message = String::from("trace does not match the script:");
+        for (i, pattern) in patterns.iter().enumerate() {
+            let (marker, matched) = match alignment.get(i) {
+                Some(&(start, end)) => (' ', visits(start, end)),
+                None if i == alignment.len() => {
+                    let start = alignment.last().map_or(0, |last| last.1);
+                    ('✗', visits(start, names.len()))
+                }
+                None => (' ', String::new()),
+            };
+            message += &format!("\n  {marker} {pattern:width$} | {matched}");
+        }
+        if alignment.len() == script.len() {
+            let start = alignment.last().map_or(0, |last| last.1);
+            message += &format!(
+                "\n  ✗ {:width$} | {}",
+                "<end of script>",
+                visits(start, names.len())
+            );
+        }
+        panic!("{message}");
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<_> = self.steps.iter().map(ToString::to_string).collect();
+        write!(f, "{}", steps.join(" → "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align() {
+        let names = ["open", "read", "read", "sync", "flush", "close"];
+        let script = [
+            "open".into(),
+            repeated("read", 1..=3),
+            unordered(["flush", "sync"]),
+            any(),
+        ];
+        assert_eq!(
+            align(&script, &names),
+            Ok(vec![(0, 1), (1, 3), (3, 5), (5, 6)])
+        );
+
+        let script = [repeated(any(), 0..=usize::MAX), "close".into()];
+        assert!(align(&script, &names).is_ok());
+
+        let script = ["open".into(), repeated("read", 1..=1), "sync".into()];
+        assert_eq!(align(&script, &names), Err(vec![(0, 1), (1, 2)]));
+
+        let script = [repeated("read", 0..=2), "open".into()];
+        assert_eq!(align(&script, &names), Err(vec![(0, 0), (0, 1)]));
+    }
+}
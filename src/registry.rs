@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::ThreadId;
+use std::time::Instant;
+
+use crate::error::Error;
+
+/// Runner exploring in some thread of the process
+///
+/// See [`active_runners()`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ActiveRunner {
+    /// Identifier unique within the process
+    pub id: u64,
+
+    /// Name of the thread the runner explores in, or its id if unnamed
+    ///
+    /// Under libtest, this is the name of the test.
+    pub thread: String,
+
+    /// Id of the thread the runner explores in
+    pub thread_id: ThreadId,
+
+    /// When the exploration has started
+    pub started: Instant,
+
+    /// Process-global feature used exclusively by the runner, if any
+    pub exclusive: Option<&'static str>,
+}
+
+/// Runners active in the process, guarded by the lock for the whole
+/// check-and-insert, so two conflicting runners cannot both register
+static ACTIVE: Mutex<Vec<ActiveRunner>> = Mutex::new(Vec::new());
+
+/// Notified when a runner leaves the registry
+static RELEASED: Condvar = Condvar::new();
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// List runners which are currently exploring in the process
+///
+/// Mostly useful for diagnostics of tests which run concurrently.
+///
+/// ```
+/// use faine::{Runner, active_runners};
+///
+/// Runner::default()
+///     .run(|| {
+///         let runners = active_runners();
+///         assert!(runners.iter().any(|runner| runner.thread_id == std::thread::current().id()));
+///     })
+///     .unwrap();
+/// ```
+pub fn active_runners() -> Vec<ActiveRunner> {
+    ACTIVE.lock().unwrap_or_else(|err| err.into_inner()).clone()
+}
+
+/// Entry of a runner in the registry, removed on drop
+pub struct Registration {
+    id: u64,
+}
+
+impl Registration {
+    /// Register a runner in the current thread
+    ///
+    /// A runner using an `exclusive` feature cannot run along with
+    /// any other runner, as they would be affected by the feature as
+    /// well, so it waits until all other runners finish, and other
+    /// runners wait until it finishes. Fails instead of waiting if
+    /// the conflicting runner explores in the current thread, as it
+    /// would never finish.
+    pub fn register(exclusive: Option<&'static str>) -> Result<Self, Error> {
+        let mut active = ACTIVE.lock().unwrap_or_else(|err| err.into_inner());
+        let current = std::thread::current().id();
+        let conflicts = |other: &&ActiveRunner| exclusive.is_some() || other.exclusive.is_some();
+        while active.iter().any(|other| conflicts(&other)) {
+            if let Some(other) = active
+                .iter()
+                .filter(conflicts)
+                .find(|other| other.thread_id == current)
+            {
+                return Err(Error::ConflictingRunner {
+                    feature: exclusive.or(other.exclusive).unwrap_or_default(),
+                    other_test_thread: other.thread.clone(),
+                });
+            }
+            active = RELEASED.wait(active).unwrap_or_else(|err| err.into_inner());
+        }
+        let thread = std::thread::current();
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        active.push(ActiveRunner {
+            id,
+            thread: thread
+                .name()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| format!("{:?}", thread.id())),
+            thread_id: thread.id(),
+            started: Instant::now(),
+            exclusive,
+        });
+        Ok(Self { id })
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        ACTIVE
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|runner| runner.id != self.id);
+        RELEASED.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclusive() {
+        const FEATURE: &str = "registry test";
+        let first = Registration::register(Some(FEATURE)).unwrap();
+        // runners nested in the exclusive one would wait forever
+        for exclusive in [None, Some(FEATURE)] {
+            assert!(matches!(
+                Registration::register(exclusive),
+                Err(Error::ConflictingRunner {
+                    feature: FEATURE,
+                    ..
+                })
+            ));
+        }
+
+        // runner in another thread waits for the exclusive one to finish
+        let released = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let waiting = scope.spawn(|| {
+                drop(Registration::register(None).unwrap());
+                released.load(Ordering::SeqCst)
+            });
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            released.store(true, Ordering::SeqCst);
+            drop(first);
+            assert!(waiting.join().unwrap());
+        });
+
+        let shared = Registration::register(None).unwrap();
+        drop(shared);
+        assert!(
+            active_runners()
+                .iter()
+                .all(|runner| runner.exclusive != Some(FEATURE))
+        );
+    }
+}
@@ -0,0 +1,2189 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, OnceLock};
+
+use crate::absorption::Outcome;
+use crate::blast::{BlastRadius, blast_radii};
+use crate::capture::CapturedOutput;
+use crate::checkpoint::Checkpoint;
+use crate::common::{Branch, Expect, Label, Tier, Weight};
+use crate::coverage::json_string;
+use crate::diff::{PathDiff, nearest_passing_path};
+use crate::error::Error;
+use crate::functions::Accumulator;
+use crate::hitlimits::HitLimits;
+use crate::hygiene::{Hygiene, instrumentation_hygiene};
+use crate::injected::ErrorTag;
+use crate::intern;
+use crate::markdown::{self, MarkdownOptions};
+use crate::panics::CaughtPanic;
+use crate::recent::RecentEvents;
+use crate::runner::WholeRunChecks;
+use crate::safety::SafetyValve;
+use crate::selector::Selector;
+use crate::shard::Shard;
+use crate::strategy::{FaultRate, PhaseStats};
+use crate::timeline::Timeline;
+use crate::verification::SelfVerification;
+
+/// Single failpoint visit on an execution path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Step {
+    /// Name of the visited failpoint
+    pub failpoint: &'static str,
+
+    /// Branch taken at the failpoint
+    pub branch: Branch,
+
+    /// Variant applied at a multi-valued failpoint, such as one
+    /// defined with [`inject_perturb!`](crate::inject_perturb)
+    pub variant: Option<&'static str>,
+
+    /// Index qualifying the variant, such as one of the item a batch
+    /// is cut before with [`batch_cut!`](crate::batch_cut)
+    pub variant_index: Option<usize>,
+
+    /// Failpoint whose activation has triggered activation of this one
+    ///
+    /// See `trigger:` argument of [`inject_return!`](crate::inject_return).
+    pub trigger: Option<&'static str>,
+}
+
+impl Step {
+    /// Convert tree label into a step, if it should be reported
+    ///
+    /// Skipped variants of multi-valued failpoints are not reported,
+    /// as they do not affect the code.
+    pub(crate) fn from_label(label: Label, branch: Branch) -> Option<Self> {
+        let variant = label.variant();
+        if variant.is_some() && branch == Branch::Skip {
+            return None;
+        }
+        Some(Self {
+            failpoint: label.failpoint_name()?,
+            branch,
+            variant,
+            variant_index: label.variant_index(),
+            trigger: None,
+        })
+    }
+
+    /// Description of the applied variant, including its index
+    pub(crate) fn variant_description(&self) -> Option<String> {
+        let variant = self.variant?;
+        Some(match self.variant_index {
+            Some(index) => format!("{variant} {index}"),
+            None => variant.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.branch, self.variant_description(), self.trigger) {
+            (Branch::Activate, Some(variant), _) => {
+                write!(f, "[{}={}]", self.failpoint, variant)
+            }
+            (Branch::Activate, None, Some(trigger)) => {
+                write!(f, "[{} after {}]", self.failpoint, trigger)
+            }
+            (Branch::Activate, None, None) => write!(f, "[{}]", self.failpoint),
+            (Branch::Skip, _, _) => write!(f, "{}", self.failpoint),
+        }
+    }
+}
+
+/// Statistics of failpoints belonging to a single component
+///
+/// See [`Report::components()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ComponentStats {
+    /// Names of visited failpoints of the component
+    pub failpoints: BTreeSet<&'static str>,
+
+    /// Number of visits of failpoints of the component, on all paths
+    pub visits: usize,
+
+    /// Number of activations of failpoints of the component, on all paths
+    pub activations: usize,
+
+    /// Number of failed paths which have activated any failpoint of the component
+    pub failing_paths: usize,
+}
+
+/// Outcomes of paths which have activated a single failpoint
+///
+/// See [`Report::absorption()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Absorption {
+    /// Number of paths with the failpoint activated and outcome recorded
+    pub paths: usize,
+
+    /// Number of such paths with [`Outcome::Success`]
+    pub success: usize,
+
+    /// Number of such paths with [`Outcome::Degraded`]
+    pub degraded: usize,
+
+    /// Number of such paths with [`Outcome::Failure`], including panicked ones
+    pub failure: usize,
+}
+
+impl Absorption {
+    /// Fraction of paths on which activation of the failpoint has been
+    /// absorbed, that is, produced [`Outcome::Success`]
+    pub fn ratio(&self) -> f64 {
+        if self.paths == 0 {
+            0.0
+        } else {
+            self.success as f64 / self.paths as f64
+        }
+    }
+}
+
+/// Distribution of a counter over explored paths
+///
+/// Paths which have not touched the counter count as zero. See
+/// [`Report::counter_stats()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CounterStats {
+    /// Smallest value
+    pub min: u64,
+
+    /// Median value, the lower one for even number of paths
+    pub median: u64,
+
+    /// Largest value
+    pub max: u64,
+
+    /// Indexes of paths with the largest value
+    pub max_paths: Vec<usize>,
+}
+
+impl std::fmt::Display for CounterStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max_paths: Vec<String> = self.max_paths.iter().map(|i| format!("#{i}")).collect();
+        write!(
+            f,
+            "min {}, median {}, max {} ({})",
+            self.min,
+            self.median,
+            self.max,
+            max_paths.join(", ")
+        )
+    }
+}
+
+/// Presence of a failpoint activation on failing and passing paths
+///
+/// See [`Report::failure_correlations()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FailureCorrelation {
+    /// Number of failing paths which have activated the failpoint
+    pub failing: usize,
+
+    /// Total number of failing paths
+    pub num_failing: usize,
+
+    /// Number of passing paths which have activated the failpoint
+    pub passing: usize,
+
+    /// Total number of passing paths
+    pub num_passing: usize,
+}
+
+impl FailureCorrelation {
+    /// Fraction of failing paths which have activated the failpoint
+    pub fn failing_ratio(&self) -> f64 {
+        if self.num_failing == 0 {
+            0.0
+        } else {
+            self.failing as f64 / self.num_failing as f64
+        }
+    }
+
+    /// Fraction of passing paths which have activated the failpoint
+    pub fn passing_ratio(&self) -> f64 {
+        if self.num_passing == 0 {
+            0.0
+        } else {
+            self.passing as f64 / self.num_passing as f64
+        }
+    }
+
+    /// How much more often the failpoint is activated on failing
+    /// paths than on passing ones, from -1 to 1
+    pub fn score(&self) -> f64 {
+        self.failing_ratio() - self.passing_ratio()
+    }
+}
+
+/// Classification of values injected by a single failpoint
+///
+/// See [`Report::injection_audit()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InjectionAudit {
+    /// Number of activations which have injected a success
+    pub success: usize,
+
+    /// Number of activations which have injected a failure
+    pub failure: usize,
+
+    /// Number of activations which have injected a value not
+    /// implementing [`InjectedOutcome`](crate::InjectedOutcome)
+    pub unclassified: usize,
+
+    /// Whether injection of a success was acknowledged at the site
+    /// with `intentional_success: true`
+    pub intentional_success: bool,
+}
+
+impl InjectionAudit {
+    /// Whether the failpoint has injected a success which was not acknowledged
+    pub fn is_unintended_success(&self) -> bool {
+        self.success > 0 && !self.intentional_success
+    }
+}
+
+/// Failpoint which has injected values differing between activations
+///
+/// See `Runner::with_audit_injection_purity()` (with `audit` feature).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ImpureInjection {
+    /// Name of the failpoint
+    pub failpoint: &'static str,
+
+    /// Digest of the value injected on the first activation
+    pub first: String,
+
+    /// Path of the first activation, up to and including it
+    pub first_path: ExecutionPath,
+
+    /// Digest of the first differing value injected later
+    pub later: String,
+
+    /// Path of the activation which has injected the differing
+    /// value, up to and including it
+    pub later_path: ExecutionPath,
+}
+
+impl std::fmt::Display for ImpureInjection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "impure injection at {}: first saw {}, later saw {}",
+            self.failpoint, self.first, self.later
+        )
+    }
+}
+
+/// Classification of a failure by its reproducibility
+///
+/// See [`Runner::with_failure_reruns()`](crate::Runner::with_failure_reruns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureClass {
+    /// Failure was reproduced on every replay of the path
+    Deterministic,
+    /// Failure was not reproduced on some replays of the path
+    Flaky,
+}
+
+/// Results of replaying a failing path
+///
+/// See [`Runner::with_failure_reruns()`](crate::Runner::with_failure_reruns).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Reruns {
+    /// Number of replays which have failed the same way
+    pub reproduced: usize,
+
+    /// Number of performed replays
+    ///
+    /// May be less than requested if exploration budget was exhausted.
+    pub total: usize,
+
+    /// Whether tested code has taken different paths on replays
+    pub non_determinism_witnessed: bool,
+
+    /// Whether replays were run with raised log verbosity, so their
+    /// logs are the verbose ones for this path
+    ///
+    /// See `Runner::with_failure_log_level()`, available with `log` feature.
+    pub verbose_logs: bool,
+}
+
+impl Reruns {
+    /// Classify the failure by its reproducibility
+    pub fn classification(&self) -> FailureClass {
+        if self.reproduced == self.total {
+            FailureClass::Deterministic
+        } else {
+            FailureClass::Flaky
+        }
+    }
+}
+
+impl std::fmt::Display for Reruns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "reproduced: {}/{}", self.reproduced, self.total)?;
+        match self.classification() {
+            FailureClass::Deterministic => write!(f, ", deterministic")?,
+            FailureClass::Flaky => write!(f, ", flaky")?,
+        }
+        if self.non_determinism_witnessed {
+            write!(f, ", non-deterministic")?;
+        }
+        if self.verbose_logs {
+            write!(f, ", verbose logs in reruns")?;
+        }
+        Ok(())
+    }
+}
+
+/// Single explored execution path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExecutionPath {
+    /// Failpoints visited on this path, in order
+    pub steps: Vec<Step>,
+
+    /// Whether execution of this path exceeded iteration timeout
+    ///
+    /// See [`Runner::with_iteration_timeout()`](crate::Runner::with_iteration_timeout).
+    pub timed_out: bool,
+
+    /// Whether this path combines failpoints declared independent
+    ///
+    /// See [`Runner::with_independence_audit()`](crate::Runner::with_independence_audit).
+    pub independence_audit: bool,
+
+    /// Output captured while executing this path
+    ///
+    /// See [`Runner::with_capture_output()`](crate::Runner::with_capture_output).
+    pub captured_output: Option<CapturedOutput>,
+
+    /// Digest of outcome recorded on this path
+    ///
+    /// See [`record_outcome()`](crate::record_outcome).
+    pub outcome: Option<u64>,
+
+    /// Panic caught while executing this path
+    ///
+    /// See [`Runner::with_catch_panics()`](crate::Runner::with_catch_panics).
+    pub panic: Option<CaughtPanic>,
+
+    /// Failpoints which were skipped because they were visited
+    /// while unwinding from a panic
+    ///
+    /// See [`Runner::with_allow_faults_during_unwind()`](crate::Runner::with_allow_faults_during_unwind).
+    pub suppressed_during_unwind: Vec<&'static str>,
+
+    /// Failpoints which were skipped because they were visited in
+    /// a critical section
+    ///
+    /// See [`no_faults()`](crate::no_faults()).
+    pub suppressed_by_critical_section: Vec<&'static str>,
+
+    /// Whether a critical section was still open at the end of this path
+    ///
+    /// Sections are closed before the next path, but this usually
+    /// means the guard has leaked. See [`no_faults()`](crate::no_faults()).
+    pub critical_section_held: bool,
+
+    /// Latest events which have led to a failure of this path
+    ///
+    /// `None` for paths which have not failed. See
+    /// [`Runner::with_recent_events()`](crate::Runner::with_recent_events).
+    pub recent_events: Option<RecentEvents>,
+
+    /// Results of replaying this path, if it has failed
+    ///
+    /// See [`Runner::with_failure_reruns()`](crate::Runner::with_failure_reruns).
+    pub reruns: Option<Reruns>,
+
+    /// Values of configuration axes this path was explored with
+    ///
+    /// See [`Runner::with_axis()`](crate::Runner::with_axis).
+    pub axes: Vec<(&'static str, &'static str)>,
+
+    /// Outcome of the result recorded on this path
+    ///
+    /// See [`record_result()`](crate::record_result).
+    pub result: Option<Outcome>,
+
+    /// Safety limits reached on this path
+    ///
+    /// See [`Runner::with_safety_limits()`](crate::Runner::with_safety_limits).
+    pub safety_valve: Vec<SafetyValve>,
+
+    /// Names of checks executed on this path, sorted
+    ///
+    /// See [`checked!`](crate::checked).
+    pub checks: Vec<&'static str>,
+
+    /// Whether failpoints were left disabled at the end of this path
+    ///
+    /// Failpoints are enabled again before the next path, but this
+    /// usually means the tested code forgot to re-enable them on some
+    /// early return. See [`enable_failpoints()`](crate::enable_failpoints).
+    pub left_disabled: bool,
+
+    /// Values of counters bumped on this path, sorted by name
+    ///
+    /// See [`count!`](crate::count).
+    pub counters: Vec<(&'static str, u64)>,
+
+    /// Checkpoints passed on this path, in order
+    ///
+    /// See [`checkpoint()`](crate::checkpoint()).
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// What a path has produced, compared between paths and explorations
+pub(crate) type Observation = (Option<u64>, Option<Outcome>, Option<String>, bool);
+
+impl ExecutionPath {
+    /// What this path has produced
+    pub(crate) fn observation(&self) -> Observation {
+        (
+            self.outcome,
+            self.result,
+            self.panic.as_ref().map(|panic| panic.location()),
+            self.timed_out,
+        )
+    }
+
+    /// Whether a failpoint with a given name was activated on this path
+    pub fn activated(&self, failpoint: &str) -> bool {
+        self.activated_failpoints().any(|name| name == failpoint)
+    }
+
+    /// Names of failpoints activated on this path
+    pub fn activated_failpoints(&self) -> impl Iterator<Item = &'static str> {
+        self.steps
+            .iter()
+            .filter(|step| step.branch == Branch::Activate)
+            .map(|step| step.failpoint)
+    }
+
+    /// Stable identity of this path
+    ///
+    /// Failpoint decisions rendered the same way as by
+    /// [`Display`](std::fmt::Display), but without annotations such
+    /// as panic location.
+    pub fn key(&self) -> String {
+        let mut key = String::new();
+        self.write_steps(&mut key, false)
+            .expect("writing to a string must not fail");
+        key
+    }
+
+    /// Value of a configuration axis this path was explored with
+    pub fn axis(&self, name: &str) -> Option<&'static str> {
+        self.axes
+            .iter()
+            .find(|(axis, _)| *axis == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn write_steps(&self, f: &mut dyn std::fmt::Write, checkpoints: bool) -> std::fmt::Result {
+        if !self.axes.is_empty() {
+            let axes: Vec<String> = self
+                .axes
+                .iter()
+                .map(|(axis, value)| format!("{axis}={value}"))
+                .collect();
+            write!(f, "{{{}}} ", axes.join(", "))?;
+        }
+        let checkpoints = if checkpoints {
+            &self.checkpoints[..]
+        } else {
+            &[]
+        };
+        if self.steps.is_empty() && checkpoints.is_empty() {
+            write!(f, "(no failpoints)")?;
+        }
+        let mut checkpoints = checkpoints.iter().peekable();
+        let mut first = true;
+        let mut separate = |f: &mut dyn std::fmt::Write| {
+            let separator = if first { "" } else { " → " };
+            first = false;
+            write!(f, "{separator}")
+        };
+        for (i, step) in self.steps.iter().enumerate() {
+            while let Some(checkpoint) = checkpoints.next_if(|checkpoint| checkpoint.position <= i)
+            {
+                separate(f)?;
+                write!(f, "⟨{}⟩", checkpoint.name)?;
+            }
+            separate(f)?;
+            write!(f, "{step}")?;
+        }
+        for checkpoint in checkpoints {
+            separate(f)?;
+            write!(f, "⟨{}⟩", checkpoint.name)?;
+        }
+        Ok(())
+    }
+
+    /// Whether execution of this path has failed, that is, panicked or timed out
+    pub fn is_failed(&self) -> bool {
+        self.panic.is_some() || self.timed_out
+    }
+
+    /// Whether another execution has failed the same way as this path
+    pub(crate) fn is_same_failure(&self, timed_out: bool, panic: Option<&CaughtPanic>) -> bool {
+        match (&self.panic, panic) {
+            (Some(expected), Some(actual)) => expected.location() == actual.location(),
+            (None, None) => self.timed_out && timed_out,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutionPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_steps(f, true)?;
+        for checkpoint in &self.checkpoints {
+            if let Some(violation) = &checkpoint.violation {
+                write!(f, " (checkpoint {} violated: {violation})", checkpoint.name)?;
+            }
+        }
+        if self.timed_out {
+            write!(f, " (timed out)")?;
+        }
+        if self.independence_audit {
+            write!(f, " (independence audit)")?;
+        }
+        if let Some(panic) = &self.panic {
+            write!(f, " (panicked at {})", panic.location())?;
+        }
+        if let Some(reruns) = &self.reruns {
+            write!(f, " ({reruns})")?;
+        }
+        if !self.suppressed_during_unwind.is_empty() {
+            write!(
+                f,
+                " (suppressed during unwind: {})",
+                self.suppressed_during_unwind.join(", ")
+            )?;
+        }
+        if !self.suppressed_by_critical_section.is_empty() {
+            write!(
+                f,
+                " (suppressed by critical section: {})",
+                self.suppressed_by_critical_section.join(", ")
+            )?;
+        }
+        if self.critical_section_held {
+            write!(f, " (critical section held)")?;
+        }
+        for valve in &self.safety_valve {
+            write!(f, " (safety valve: {valve})")?;
+        }
+        if self.left_disabled {
+            write!(f, " (failpoints left disabled)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Failure found by [`Runner::run_expect_failure()`](crate::Runner::run_expect_failure)
+#[derive(Debug, Clone)]
+pub struct FoundFailure {
+    paths: Vec<ExecutionPath>,
+    report: Report,
+}
+
+impl FoundFailure {
+    /// Collect failed paths of a report, if there are any
+    pub(crate) fn new(report: Report) -> Result<Self, Box<Report>> {
+        let paths: Vec<_> = report
+            .paths()
+            .iter()
+            .filter(|path| path.is_failed())
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            return Err(Box::new(report));
+        }
+        Ok(Self { paths, report })
+    }
+
+    /// Failed paths, at least one
+    pub fn paths(&self) -> &[ExecutionPath] {
+        &self.paths
+    }
+
+    /// Report of the whole exploration
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+}
+
+/// Path which behaves differently depending on exploration order
+///
+/// See [`Runner::with_order_sensitivity_check()`](crate::Runner::with_order_sensitivity_check).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OrderSensitivity {
+    /// Failpoints visited on the path, in order
+    pub steps: Vec<Step>,
+
+    /// Outcome recorded on the path with the requested branch preference
+    ///
+    /// `None` if the path was not explored in this order, or if
+    /// no outcome was recorded.
+    pub outcome: Option<u64>,
+
+    /// Outcome recorded on the path with the opposite branch preference
+    ///
+    /// `None` if the path was not explored in this order, or if
+    /// no outcome was recorded.
+    pub reversed_outcome: Option<u64>,
+}
+
+/// Results of an exploration performed by [`Runner::run()`]
+///
+/// Plain [`Display`](std::fmt::Display) produces short summary, while
+/// alternate form (`{:#}`) lists all explored paths, with activated
+/// failpoints in square brackets.
+///
+/// [`Runner::run()`]: crate::Runner::run
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    paths: Vec<ExecutionPath>,
+    num_pruned: usize,
+    num_dedup_skipped: usize,
+    num_dedup_mismatches: usize,
+    output_captured: bool,
+    locations: BTreeMap<&'static str, &'static str>,
+    order_sensitive_paths: Vec<OrderSensitivity>,
+    tier: Tier,
+    incomplete: bool,
+    unexplored_slots: BTreeMap<Weight, usize>,
+    hit_limits: Option<HitLimits>,
+    hygiene: Option<BTreeMap<&'static str, Hygiene>>,
+    non_determinism_witnessed: bool,
+    phases: Vec<PhaseStats>,
+    aliases: HashMap<String, &'static str>,
+    stale_aliases: Vec<String>,
+    failure_runs: BTreeMap<&'static str, BTreeMap<usize, usize>>,
+    fault_rates: BTreeMap<&'static str, FaultRate>,
+    unreachable_priority_paths: Vec<Vec<String>>,
+    known_failures: Option<BTreeSet<String>>,
+    timeline: Option<Timeline>,
+    stopped_by_diminishing_returns: bool,
+    interrupted: bool,
+    forbidden_checked: bool,
+    self_verification: Option<SelfVerification>,
+    forbidden_visits: Vec<(&'static str, Vec<Step>)>,
+    site_expectations: BTreeMap<&'static str, Expect>,
+    accumulator: Option<Arc<dyn Any + Send + Sync>>,
+    injection_audit: Option<BTreeMap<&'static str, InjectionAudit>>,
+    impure_injections: Option<Vec<ImpureInjection>>,
+    error_tags: HashMap<String, usize>,
+    shard: Option<Shard>,
+    whole_run_checks: Option<WholeRunChecks>,
+    outer_path: Option<ExecutionPath>,
+    blast_radii: OnceLock<BTreeMap<&'static str, BlastRadius>>,
+    #[cfg(feature = "fs")]
+    fixture_timing: Option<crate::fs::FixtureTiming>,
+}
+
+impl Report {
+    pub(crate) fn add_path(&mut self, path: ExecutionPath) {
+        self.paths.push(path);
+        self.blast_radii.take();
+    }
+
+    pub(crate) fn set_num_pruned(&mut self, num_pruned: usize) {
+        self.num_pruned = num_pruned;
+    }
+
+    pub(crate) fn set_dedup_stats(&mut self, num_skipped: usize, num_mismatches: usize) {
+        self.num_dedup_skipped = num_skipped;
+        self.num_dedup_mismatches = num_mismatches;
+    }
+
+    pub(crate) fn set_output_captured(&mut self, output_captured: bool) {
+        self.output_captured = output_captured;
+    }
+
+    pub(crate) fn set_incomplete(&mut self, incomplete: bool) {
+        self.incomplete = incomplete;
+    }
+
+    pub(crate) fn set_hit_limits(&mut self, hit_limits: HitLimits) {
+        self.hit_limits = Some(hit_limits);
+    }
+
+    pub(crate) fn compute_hygiene(&mut self) {
+        self.hygiene = Some(instrumentation_hygiene(&self.paths));
+    }
+
+    pub(crate) fn set_unexplored_slots(&mut self, unexplored_slots: BTreeMap<Weight, usize>) {
+        self.unexplored_slots = unexplored_slots;
+    }
+
+    pub(crate) fn set_stopped_by_diminishing_returns(&mut self) {
+        self.incomplete = true;
+        self.stopped_by_diminishing_returns = true;
+    }
+
+    #[cfg_attr(not(feature = "ctrlc"), expect(dead_code))]
+    pub(crate) fn set_interrupted(&mut self) {
+        self.incomplete = true;
+        self.interrupted = true;
+    }
+
+    #[cfg(feature = "fs")]
+    pub(crate) fn set_fixture_timing(&mut self, fixture_timing: crate::fs::FixtureTiming) {
+        self.fixture_timing = Some(fixture_timing);
+    }
+
+    pub(crate) fn set_timeline(&mut self, timeline: Timeline) {
+        self.timeline = Some(timeline);
+    }
+
+    pub(crate) fn set_non_determinism_witnessed(&mut self, witnessed: bool) {
+        self.non_determinism_witnessed = witnessed;
+    }
+
+    pub(crate) fn set_aliases(
+        &mut self,
+        aliases: HashMap<String, &'static str>,
+        stale_aliases: Vec<String>,
+    ) {
+        self.aliases = aliases;
+        self.stale_aliases = stale_aliases;
+    }
+
+    /// Resolve failpoint name given by user into a name used in the report
+    fn resolve_alias<'a>(&'a self, failpoint: &'a str) -> &'a str {
+        self.aliases.get(failpoint).copied().unwrap_or(failpoint)
+    }
+
+    /// Whether a failpoint shown in the report is selected, either by
+    /// the name it's shown with or by its raw name
+    pub(crate) fn selects(&self, selector: &Selector, failpoint: &str) -> bool {
+        selector.matches(failpoint)
+            || self
+                .aliases
+                .iter()
+                .any(|(raw, alias)| *alias == failpoint && selector.matches(raw))
+    }
+
+    pub(crate) fn set_failure_runs(
+        &mut self,
+        failure_runs: BTreeMap<&'static str, BTreeMap<usize, usize>>,
+    ) {
+        self.failure_runs = failure_runs;
+    }
+
+    pub(crate) fn set_self_verification(&mut self, verification: SelfVerification) {
+        self.self_verification = Some(verification);
+    }
+
+    pub(crate) fn set_forbidden_checked(&mut self, checked: bool) {
+        self.forbidden_checked = checked;
+    }
+
+    /// Qualify names of failpoints which became ambiguous after they were recorded
+    pub(crate) fn requalify_names(&mut self) {
+        let steps = self
+            .paths
+            .iter_mut()
+            .flat_map(|path| path.steps.iter_mut())
+            .chain(
+                self.forbidden_visits
+                    .iter_mut()
+                    .flat_map(|(_, steps)| steps.iter_mut()),
+            );
+        for step in steps {
+            step.failpoint = intern::current_name(step.failpoint);
+        }
+        self.blast_radii.take();
+        for (failpoint, _) in &mut self.forbidden_visits {
+            *failpoint = intern::current_name(failpoint);
+        }
+        for impure in self.impure_injections.iter_mut().flatten() {
+            impure.failpoint = intern::current_name(impure.failpoint);
+            for step in impure
+                .first_path
+                .steps
+                .iter_mut()
+                .chain(impure.later_path.steps.iter_mut())
+            {
+                step.failpoint = intern::current_name(step.failpoint);
+            }
+        }
+        if let Some(shard) = &mut self.shard {
+            shard.discovered = shard
+                .discovered
+                .iter()
+                .map(|failpoint| intern::current_name(failpoint))
+                .collect();
+        }
+    }
+
+    pub(crate) fn set_shard(&mut self, shard: Shard) {
+        self.shard = Some(shard);
+    }
+
+    pub(crate) fn set_outer_path(&mut self, outer_path: ExecutionPath) {
+        self.outer_path = Some(outer_path);
+    }
+
+    pub(crate) fn set_whole_run_checks(&mut self, checks: WholeRunChecks) {
+        self.whole_run_checks = Some(checks);
+    }
+
+    pub(crate) fn set_accumulator(&mut self, accumulator: Accumulator) {
+        self.accumulator = Some(Arc::from(accumulator));
+    }
+
+    pub(crate) fn set_site_expectations(&mut self, expectations: BTreeMap<&'static str, Expect>) {
+        self.site_expectations = expectations;
+    }
+
+    pub(crate) fn set_injection_audit(&mut self, audit: BTreeMap<&'static str, InjectionAudit>) {
+        self.injection_audit = Some(audit);
+    }
+
+    pub(crate) fn set_impure_injections(&mut self, impure: Vec<ImpureInjection>) {
+        self.impure_injections = Some(impure);
+    }
+
+    pub(crate) fn add_error_tag(&mut self, tag: ErrorTag, path: usize) {
+        self.error_tags.insert(tag.to_string(), path);
+    }
+
+    pub(crate) fn add_forbidden_visit(&mut self, failpoint: &'static str, path: Vec<Step>) {
+        self.forbidden_visits.push((failpoint, path));
+    }
+
+    pub(crate) fn set_unreachable_priority_paths(&mut self, paths: Vec<Vec<String>>) {
+        self.unreachable_priority_paths = paths;
+    }
+
+    pub(crate) fn set_known_failures(&mut self, known_failures: BTreeSet<String>) {
+        self.known_failures = Some(known_failures);
+    }
+
+    pub(crate) fn known_failures_checked(&self) -> bool {
+        self.known_failures.is_some()
+    }
+
+    pub(crate) fn set_fault_rates(&mut self, fault_rates: BTreeMap<&'static str, FaultRate>) {
+        self.fault_rates = fault_rates;
+    }
+
+    pub(crate) fn set_phases(&mut self, phases: Vec<PhaseStats>) {
+        self.phases = phases;
+    }
+
+    pub(crate) fn record_phase(&mut self, phase: usize, path: &ExecutionPath) {
+        let stats = &mut self.phases[phase];
+        stats.iterations += 1;
+        stats.activations += path.activated_failpoints().count();
+        if path.is_failed() {
+            stats.failures += 1;
+        }
+    }
+
+    pub(crate) fn set_tier(&mut self, tier: Tier) {
+        self.tier = tier;
+    }
+
+    pub(crate) fn set_locations(&mut self, locations: BTreeMap<&'static str, &'static str>) {
+        self.locations = locations;
+    }
+
+    /// Compare path outcomes with these of exploration done in a different order
+    pub(crate) fn compare_order(&mut self, reversed: &Report) {
+        let mut reversed_outcomes: HashMap<&[Step], Option<u64>> = reversed
+            .paths
+            .iter()
+            .map(|path| (path.steps.as_slice(), path.outcome))
+            .collect();
+        for path in &self.paths {
+            let reversed_outcome = reversed_outcomes.remove(path.steps.as_slice());
+            if reversed_outcome != Some(path.outcome) {
+                self.order_sensitive_paths.push(OrderSensitivity {
+                    steps: path.steps.clone(),
+                    outcome: path.outcome,
+                    reversed_outcome: reversed_outcome.flatten(),
+                });
+            }
+        }
+        // paths only explored in reversed order, in order of exploration
+        for path in &reversed.paths {
+            if reversed_outcomes.contains_key(path.steps.as_slice()) {
+                self.order_sensitive_paths.push(OrderSensitivity {
+                    steps: path.steps.clone(),
+                    outcome: None,
+                    reversed_outcome: path.outcome,
+                });
+            }
+        }
+    }
+
+    /// Raw names of aliased failpoints which were never visited
+    ///
+    /// See [`Runner::with_aliases()`](crate::Runner::with_aliases).
+    pub fn stale_aliases(&self) -> &[String] {
+        &self.stale_aliases
+    }
+
+    /// Distributions of failure run lengths of failpoints decided by
+    /// fault models
+    ///
+    /// For each failpoint, maps length of a run of consecutive
+    /// activations to the number of such runs. Runs are counted within
+    /// iterations. See [`Runner::with_fault_model()`](crate::Runner::with_fault_model).
+    pub fn failure_runs(&self) -> &BTreeMap<&'static str, BTreeMap<usize, usize>> {
+        &self.failure_runs
+    }
+
+    /// Activation statistics of failpoints, for random strategy
+    ///
+    /// Allows to check that intended fault intensity was achieved,
+    /// and was not throttled by rate limits (see
+    /// [`Runner::with_fault_rate_limit()`](crate::Runner::with_fault_rate_limit))
+    /// or cooldowns (see [`Runner::with_cooldown()`](crate::Runner::with_cooldown)).
+    pub fn fault_rates(&self) -> &BTreeMap<&'static str, FaultRate> {
+        &self.fault_rates
+    }
+
+    /// Number of subtrees skipped as already explored by other tests
+    ///
+    /// See [`Runner::with_dedup_store()`](crate::Runner::with_dedup_store).
+    pub fn num_dedup_skipped(&self) -> usize {
+        self.num_dedup_skipped
+    }
+
+    /// Number of subtrees which produced outcomes different from ones
+    /// recorded by other tests
+    ///
+    /// See [`Runner::with_dedup_mode()`](crate::Runner::with_dedup_mode).
+    pub fn num_dedup_mismatches(&self) -> usize {
+        self.num_dedup_mismatches
+    }
+
+    /// Per phase statistics of random strategy
+    ///
+    /// Empty unless [`Strategy::Random`](crate::Strategy::Random) is used.
+    pub fn phases(&self) -> &[PhaseStats] {
+        &self.phases
+    }
+
+    /// Whether exploration was stopped before all paths were explored
+    ///
+    /// See [`Runner::with_max_iterations()`](crate::Runner::with_max_iterations)
+    /// and [`Runner::with_max_duration()`](crate::Runner::with_max_duration).
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    /// Numbers of subtrees left unexplored due to budget, by weight
+    ///
+    /// Weight of a subtree is the lowest weight of failpoints activated
+    /// on the path leading to it, see `weight:` argument of
+    /// [`inject_return!`](crate::inject_return). Only tracked when
+    /// exploration is limited by
+    /// [`Runner::with_max_iterations()`](crate::Runner::with_max_iterations)
+    /// or [`Runner::with_max_duration()`](crate::Runner::with_max_duration).
+    pub fn unexplored_slots(&self) -> &BTreeMap<Weight, usize> {
+        &self.unexplored_slots
+    }
+
+    /// Activation limits found by adaptive discovery
+    ///
+    /// See [`Runner::with_adaptive_hit_limits()`](crate::Runner::with_adaptive_hit_limits).
+    pub fn hit_limits(&self) -> Option<&HitLimits> {
+        self.hit_limits.as_ref()
+    }
+
+    /// Instrumentation hygiene of failpoints, by failpoint name
+    ///
+    /// Lists failpoints which were both activated and skipped at the
+    /// same place of execution. `None` unless enabled with
+    /// [`Runner::with_hygiene_report()`](crate::Runner::with_hygiene_report).
+    pub fn hygiene(&self) -> Option<&BTreeMap<&'static str, Hygiene>> {
+        self.hygiene.as_ref()
+    }
+
+    /// Path of the first stage which has produced the state this exploration started from
+    ///
+    /// `None` unless this is a second stage exploration of
+    /// [`Runner::run_staged()`](crate::Runner::run_staged).
+    pub fn outer_path(&self) -> Option<&ExecutionPath> {
+        self.outer_path.as_ref()
+    }
+
+    /// Shard this exploration was limited to
+    ///
+    /// See [`Runner::with_shard()`](crate::Runner::with_shard).
+    pub fn shard(&self) -> Option<&Shard> {
+        self.shard.as_ref()
+    }
+
+    /// Combine reports of all shards of an exploration
+    ///
+    /// Merged report holds paths of all shards in order of their
+    /// indexes, along with failpoint locations, pruning and
+    /// deduplication statistics, and completeness and non-determinism
+    /// flags. Other data, such as timelines or accumulators, which
+    /// only makes sense for a single run, is not merged.
+    ///
+    /// Fails with [`Error::ShardsIncomplete`] holding the merged
+    /// report if the reports may not cover all paths an unsharded
+    /// run would explore: if any report is not of a shard, shards
+    /// disagree on their total number, any shard is missing, repeated,
+    /// or was stopped before completing its part (see
+    /// [`is_incomplete()`](Self::is_incomplete)), or some failpoint
+    /// discovered by a shard is not visited on any reported path,
+    /// which means that shards disagree on ownership of the paths
+    /// it's on, for instance because of non-determinism. Empty list
+    /// of reports is an error as well.
+    ///
+    /// Checks which need the whole exploration, such as
+    /// [`Runner::with_expected_failpoints()`](crate::Runner::with_expected_failpoints),
+    /// [`Runner::with_required_checks()`](crate::Runner::with_required_checks)
+    /// or [`Runner::with_enforce_site_expectations()`](crate::Runner::with_enforce_site_expectations),
+    /// are not performed by runs of individual shards, as each only
+    /// sees its own paths; these are performed here over the merged
+    /// report, and the coverage record (see
+    /// [`Runner::with_coverage_dir()`](crate::Runner::with_coverage_dir))
+    /// is written for it.
+    ///
+    /// See [`Runner::with_shard()`](crate::Runner::with_shard).
+    pub fn merge_shards(reports: impl IntoIterator<Item = Report>) -> Result<Report, Error> {
+        let mut reports: Vec<Report> = reports.into_iter().collect();
+        let mut problems = vec![];
+        if reports.is_empty() {
+            problems.push("no shard reports".to_string());
+        }
+        let total = reports
+            .iter()
+            .find_map(|report| report.shard.as_ref().map(|shard| shard.total))
+            .unwrap_or(0);
+        let mut seen = vec![0usize; total];
+        let mut discovered: BTreeSet<&'static str> = Default::default();
+        for report in &reports {
+            let Some(shard) = &report.shard else {
+                problems.push("report is not of a shard".to_string());
+                continue;
+            };
+            if shard.total != total {
+                problems.push(format!(
+                    "shard {} is one of {}, not {}",
+                    shard.index, shard.total, total
+                ));
+                continue;
+            }
+            seen[shard.index] += 1;
+            if report.incomplete {
+                problems.push(format!("shard {} is incomplete", shard.index));
+            }
+            discovered.extend(shard.discovered.iter().copied());
+        }
+        for (index, count) in seen.iter().enumerate() {
+            match count {
+                0 => problems.push(format!("shard {index} is missing")),
+                1 => {}
+                count => problems.push(format!("shard {index} is repeated {count} times")),
+            }
+        }
+        reports.sort_by_key(|report| report.shard.as_ref().map(|shard| shard.index));
+
+        let mut merged = Report::default();
+        for (i, report) in reports.into_iter().enumerate() {
+            if i == 0 {
+                merged.tier = report.tier;
+                merged.output_captured = report.output_captured;
+                merged.aliases = report.aliases;
+                merged.whole_run_checks = report.whole_run_checks;
+            }
+            merged.paths.extend(report.paths);
+            merged.num_pruned += report.num_pruned;
+            merged.num_dedup_skipped += report.num_dedup_skipped;
+            merged.num_dedup_mismatches += report.num_dedup_mismatches;
+            merged.output_captured &= report.output_captured;
+            merged.locations.extend(report.locations);
+            merged.site_expectations.extend(report.site_expectations);
+            merged.incomplete |= report.incomplete;
+            merged.interrupted |= report.interrupted;
+            merged.non_determinism_witnessed |= report.non_determinism_witnessed;
+        }
+        let visited = merged.failpoints();
+        for failpoint in discovered.difference(&visited) {
+            problems.push(format!(
+                "failpoint {failpoint} was discovered, but no shard has reported a path visiting it"
+            ));
+        }
+
+        if !problems.is_empty() {
+            return Err(Error::ShardsIncomplete {
+                problems,
+                report: Box::new(merged),
+            });
+        }
+        match merged.whole_run_checks.take() {
+            Some(checks) => checks.check(merged),
+            None => Ok(merged),
+        }
+    }
+
+    /// Whether exploration was stopped as it no longer found new fault combinations
+    ///
+    /// See [`Runner::with_diminishing_returns_stop()`](crate::Runner::with_diminishing_returns_stop).
+    pub fn is_stopped_by_diminishing_returns(&self) -> bool {
+        self.stopped_by_diminishing_returns
+    }
+
+    /// Version of the exploration algorithm the report was produced with
+    ///
+    /// See [`Runner::exploration_version()`](crate::Runner::exploration_version).
+    pub fn exploration_version(&self) -> u32 {
+        crate::runner::EXPLORATION_VERSION
+    }
+
+    /// Whether exploration was stopped by a signal or `interrupt()`
+    ///
+    /// See `Runner::with_graceful_interrupt()` (with `ctrlc` feature).
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted
+    }
+
+    /// Time spent on resetting the fixture tree, compared to the whole exploration
+    ///
+    /// See [`Runner::with_fixture_tree()`](crate::Runner::with_fixture_tree).
+    ///
+    /// Available with `fs` feature.
+    #[cfg(feature = "fs")]
+    pub fn fixture_timing(&self) -> Option<&crate::fs::FixtureTiming> {
+        self.fixture_timing.as_ref()
+    }
+
+    /// Exploration progress over time
+    ///
+    /// See [`Runner::with_timeline()`](crate::Runner::with_timeline).
+    pub fn timeline(&self) -> Option<&Timeline> {
+        self.timeline.as_ref()
+    }
+
+    /// Priority paths which could not be followed
+    ///
+    /// Each path is listed as failpoints to activate, as passed to
+    /// [`Runner::with_priority_paths()`](crate::Runner::with_priority_paths).
+    pub fn unreachable_priority_paths(&self) -> &[Vec<String>] {
+        &self.unreachable_priority_paths
+    }
+
+    /// Results of replaying a sample of explored paths
+    ///
+    /// See [`Runner::with_self_verification()`](crate::Runner::with_self_verification).
+    pub fn self_verification(&self) -> Option<&SelfVerification> {
+        self.self_verification.as_ref()
+    }
+
+    /// Visits of forbidden failpoints, with paths leading to them
+    ///
+    /// See [`Runner::with_forbidden()`](crate::Runner::with_forbidden).
+    pub fn forbidden_visits(&self) -> &[(&'static str, Vec<Step>)] {
+        &self.forbidden_visits
+    }
+
+    /// Final value of the accumulator
+    ///
+    /// Returns `None` if no accumulator was declared with
+    /// [`Runner::with_accumulator()`](crate::Runner::with_accumulator),
+    /// or if it's not of type `A`. See [`accumulate()`](crate::accumulate).
+    pub fn accumulator<A: 'static>(&self) -> Option<&A> {
+        self.accumulator.as_ref()?.downcast_ref()
+    }
+
+    /// Expectations declared at sites of visited failpoints
+    ///
+    /// See `expect:` argument of [`inject_return!`](crate::inject_return).
+    pub fn site_expectations(&self) -> &BTreeMap<&'static str, Expect> {
+        &self.site_expectations
+    }
+
+    /// Visited failpoints whose site expectations were not met
+    ///
+    /// See [`Runner::with_enforce_site_expectations()`](crate::Runner::with_enforce_site_expectations).
+    pub fn unmet_site_expectations(&self) -> Vec<(&'static str, Expect)> {
+        let activated = self.activated_failpoints();
+        self.site_expectations
+            .iter()
+            .filter(|(failpoint, expect)| match expect {
+                Expect::Activated => !activated.contains(*failpoint),
+            })
+            .map(|(failpoint, expect)| (*failpoint, *expect))
+            .collect()
+    }
+
+    /// Classification of values injected by activated override failpoints
+    ///
+    /// `None` unless enabled with `Runner::with_audit_injections()`
+    /// (with `audit` feature).
+    pub fn injection_audit(&self) -> Option<&BTreeMap<&'static str, InjectionAudit>> {
+        self.injection_audit.as_ref()
+    }
+
+    /// Failpoints which have injected a success without acknowledging it
+    ///
+    /// See `Runner::with_audit_injections()` (with `audit` feature).
+    pub fn unintended_success_injections(&self) -> Vec<&'static str> {
+        self.injection_audit
+            .iter()
+            .flatten()
+            .filter(|(_, audit)| audit.is_unintended_success())
+            .map(|(failpoint, _)| *failpoint)
+            .collect()
+    }
+
+    /// Failpoints which have injected values differing between activations
+    ///
+    /// Empty unless enabled with `Runner::with_audit_injection_purity()`
+    /// (with `audit` feature).
+    pub fn impure_injections(&self) -> &[ImpureInjection] {
+        self.impure_injections.as_deref().unwrap_or_default()
+    }
+
+    /// Safety limits reached on any explored path
+    ///
+    /// See [`Runner::with_safety_limits()`](crate::Runner::with_safety_limits).
+    pub fn safety_valve_triggered(&self) -> BTreeSet<SafetyValve> {
+        self.paths
+            .iter()
+            .flat_map(|path| path.safety_valve.iter().copied())
+            .collect()
+    }
+
+    /// Numbers of paths each check has executed on, by check name
+    ///
+    /// A check executed on fewer than [`num_paths()`](Self::num_paths)
+    /// paths is skipped on some of them. Checks which have never
+    /// executed are not listed. See [`checked!`](crate::checked).
+    pub fn check_counts(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for path in &self.paths {
+            for check in &path.checks {
+                *counts.entry(*check).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Distributions of counters over all paths, by counter name
+    ///
+    /// Counters which have never been bumped are not listed. See
+    /// [`count!`](crate::count).
+    pub fn counter_stats(&self) -> BTreeMap<&'static str, CounterStats> {
+        let mut values: BTreeMap<&'static str, Vec<u64>> = BTreeMap::new();
+        for (i, path) in self.paths.iter().enumerate() {
+            for (counter, value) in &path.counters {
+                values
+                    .entry(counter)
+                    .or_insert_with(|| vec![0; self.paths.len()])[i] = *value;
+            }
+        }
+        values
+            .into_iter()
+            .map(|(counter, values)| {
+                let max = values.iter().copied().max().unwrap_or_default();
+                let max_paths = (0..values.len()).filter(|i| values[*i] == max).collect();
+                let mut sorted = values;
+                sorted.sort_unstable();
+                let stats = CounterStats {
+                    min: sorted[0],
+                    median: sorted[(sorted.len() - 1) / 2],
+                    max,
+                    max_paths,
+                };
+                (counter, stats)
+            })
+            .collect()
+    }
+
+    /// Resolve a tag of an injected error into the path it was injected on
+    ///
+    /// Accepts either a bare tag such as `[faine i=137 p=ab3f]`, or
+    /// any text containing it, such as an error message or a log
+    /// line. Tags are only produced with
+    /// [`Runner::with_error_tagging()`](crate::Runner::with_error_tagging).
+    pub fn resolve_tag(&self, text: &str) -> Option<&ExecutionPath> {
+        let tag = ErrorTag::find_in(text)?;
+        self.paths.get(*self.error_tags.get(tag)?)
+    }
+
+    /// Whether tested code was seen taking different paths given the same decisions
+    ///
+    /// See [`Runner::with_strict_determinism()`](crate::Runner::with_strict_determinism).
+    pub fn non_determinism_witnessed(&self) -> bool {
+        self.non_determinism_witnessed
+    }
+
+    /// Exploration tier this report was produced with
+    ///
+    /// See [`Runner::smoke()`](crate::Runner::smoke).
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+
+    /// Paths which behave differently depending on exploration order
+    ///
+    /// Always empty unless enabled with
+    /// [`Runner::with_order_sensitivity_check()`](crate::Runner::with_order_sensitivity_check).
+    pub fn order_sensitive_paths(&self) -> &[OrderSensitivity] {
+        &self.order_sensitive_paths
+    }
+
+    /// Location of a failpoint in the source code (`file:line:column`)
+    ///
+    /// For failpoints sharing a name, location of the first visited one
+    /// is returned.
+    pub fn failpoint_location(&self, failpoint: &str) -> Option<&'static str> {
+        self.locations.get(self.resolve_alias(failpoint)).copied()
+    }
+
+    /// Component of a failpoint, if it belongs to one
+    ///
+    /// Accepts both raw names and aliases. See [`Component`](crate::Component).
+    pub fn failpoint_component(&self, failpoint: &str) -> Option<&'static str> {
+        intern::component_of(failpoint).or_else(|| {
+            self.aliases
+                .iter()
+                .find(|(_, alias)| **alias == failpoint)
+                .and_then(|(raw, _)| intern::component_of(raw))
+        })
+    }
+
+    /// Outcomes of paths activating each failpoint, by failpoint name
+    ///
+    /// Only paths with an outcome recorded with
+    /// [`record_result()`](crate::record_result) or panicked ones
+    /// (which count as [`Outcome::Failure`]) are accounted. High
+    /// absorption ratio means that injected failures are mostly not
+    /// surfaced to the caller, which is correct for retried or
+    /// optional operations, but may also indicate silently skipped
+    /// work. See also [`Runner::with_max_absorption()`](crate::Runner::with_max_absorption).
+    pub fn absorption(&self) -> BTreeMap<&'static str, Absorption> {
+        let mut absorption: BTreeMap<&'static str, Absorption> = Default::default();
+        for path in &self.paths {
+            let outcome = match (path.result, &path.panic) {
+                (_, Some(_)) => Outcome::Failure,
+                (Some(outcome), None) => outcome,
+                (None, None) => continue,
+            };
+            let activated: BTreeSet<&'static str> = path.activated_failpoints().collect();
+            for failpoint in activated {
+                let stats = absorption.entry(failpoint).or_default();
+                stats.paths += 1;
+                match outcome {
+                    Outcome::Success => stats.success += 1,
+                    Outcome::Degraded => stats.degraded += 1,
+                    Outcome::Failure => stats.failure += 1,
+                }
+            }
+        }
+        absorption
+    }
+
+    /// Statistics of visited failpoints, rolled up by component
+    ///
+    /// Failpoints which do not belong to a component are not included.
+    pub fn components(&self) -> BTreeMap<&'static str, ComponentStats> {
+        let mut components: BTreeMap<&'static str, ComponentStats> = Default::default();
+        let mut cache: HashMap<&'static str, Option<&'static str>> = Default::default();
+        for path in &self.paths {
+            let mut activated: BTreeSet<&'static str> = Default::default();
+            for step in &path.steps {
+                let component = *cache
+                    .entry(step.failpoint)
+                    .or_insert_with(|| self.failpoint_component(step.failpoint));
+                let Some(component) = component else {
+                    continue;
+                };
+                let stats = components.entry(component).or_default();
+                stats.failpoints.insert(step.failpoint);
+                stats.visits += 1;
+                if step.branch == Branch::Activate {
+                    stats.activations += 1;
+                    activated.insert(component);
+                }
+            }
+            if path.is_failed() {
+                for component in activated {
+                    components.entry(component).or_default().failing_paths += 1;
+                }
+            }
+        }
+        components
+    }
+
+    /// Render component statistics as JSON
+    ///
+    /// See [`components()`](Self::components).
+    pub fn components_to_json(&self) -> String {
+        let components: Vec<String> = self
+            .components()
+            .into_iter()
+            .map(|(component, stats)| {
+                let failpoints: Vec<String> =
+                    stats.failpoints.iter().map(|name| json_string(name)).collect();
+                format!(
+                    "{{\"component\":{},\"failpoints\":[{}],\"visits\":{},\"activations\":{},\"failing_paths\":{}}}",
+                    json_string(component),
+                    failpoints.join(","),
+                    stats.visits,
+                    stats.activations,
+                    stats.failing_paths
+                )
+            })
+            .collect();
+        format!("{{\"components\":[{}]}}", components.join(","))
+    }
+
+    /// Whether output was captured during exploration
+    ///
+    /// Output capture is not supported on all platforms, in which case
+    /// it silently does nothing, and this returns `false`.
+    pub fn output_captured(&self) -> bool {
+        self.output_captured
+    }
+
+    /// Number of branches not explored due to user-asserted failpoint independence
+    ///
+    /// See [`Runner::with_independent_groups()`](crate::Runner::with_independent_groups).
+    pub fn num_pruned(&self) -> usize {
+        self.num_pruned
+    }
+
+    /// Explored paths, in order of exploration
+    pub fn paths(&self) -> &[ExecutionPath] {
+        &self.paths
+    }
+
+    /// Number of explored paths
+    pub fn num_paths(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Names of all failpoints visited during exploration
+    ///
+    /// For a report of a [shard](Self::shard), these are only
+    /// failpoints visited on paths owned by it, see also
+    /// [`Shard::discovered`].
+    pub fn failpoints(&self) -> BTreeSet<&'static str> {
+        self.paths
+            .iter()
+            .flat_map(|path| path.steps.iter().map(|step| step.failpoint))
+            .collect()
+    }
+
+    /// Paths which exceeded iteration timeout
+    pub fn timed_out_paths(&self) -> impl Iterator<Item = &ExecutionPath> {
+        self.paths.iter().filter(|path| path.timed_out)
+    }
+
+    /// Names of failpoints activated on at least one path
+    pub fn activated_failpoints(&self) -> BTreeSet<&'static str> {
+        self.paths
+            .iter()
+            .flat_map(|path| path.activated_failpoints())
+            .collect()
+    }
+
+    /// Paths which have violated checkpoints, grouped by checkpoint name
+    ///
+    /// See [`checkpoint()`](crate::checkpoint()).
+    pub fn checkpoint_violations(&self) -> BTreeMap<&'static str, Vec<&ExecutionPath>> {
+        let mut res: BTreeMap<&'static str, Vec<&ExecutionPath>> = Default::default();
+        for path in &self.paths {
+            let mut names: Vec<_> = path
+                .checkpoints
+                .iter()
+                .filter(|checkpoint| checkpoint.violation.is_some())
+                .map(|checkpoint| checkpoint.name)
+                .collect();
+            names.dedup();
+            for name in names {
+                res.entry(name).or_default().push(path);
+            }
+        }
+        res
+    }
+
+    /// Paths which have panicked, grouped by panic location (`file:line`)
+    ///
+    /// See [`Runner::with_catch_panics()`](crate::Runner::with_catch_panics).
+    pub fn panicking_paths(&self) -> BTreeMap<String, Vec<&ExecutionPath>> {
+        let mut res: BTreeMap<String, Vec<&ExecutionPath>> = Default::default();
+        for path in &self.paths {
+            if let Some(panic) = &path.panic {
+                res.entry(panic.location()).or_default().push(path);
+            }
+        }
+        res
+    }
+
+    /// Whether a path has panicked and is listed as a known failure
+    ///
+    /// See [`Runner::with_known_failures_file()`](crate::Runner::with_known_failures_file).
+    pub fn is_known_failure(&self, path: &ExecutionPath) -> bool {
+        path.panic.is_some()
+            && self
+                .known_failures
+                .as_ref()
+                .is_some_and(|known_failures| known_failures.contains(&path.key()))
+    }
+
+    /// Panicking paths listed as known failures
+    ///
+    /// See [`Runner::with_known_failures_file()`](crate::Runner::with_known_failures_file).
+    pub fn known_failing_paths(&self) -> impl Iterator<Item = &ExecutionPath> {
+        self.paths.iter().filter(|path| self.is_known_failure(path))
+    }
+
+    /// Panicking paths not listed as known failures
+    ///
+    /// See [`Runner::with_known_failures_file()`](crate::Runner::with_known_failures_file).
+    pub fn new_failing_paths(&self) -> impl Iterator<Item = &ExecutionPath> {
+        self.paths
+            .iter()
+            .filter(|path| path.panic.is_some() && !self.is_known_failure(path))
+    }
+
+    /// Keys of known failures whose paths were explored and passed
+    ///
+    /// These may be removed from the known failures file. Known
+    /// failures whose paths were not explored, for instance, due to
+    /// budget, are not listed. See
+    /// [`Runner::with_known_failures_file()`](crate::Runner::with_known_failures_file).
+    pub fn fixed_known_failures(&self) -> Vec<&str> {
+        let Some(known_failures) = &self.known_failures else {
+            return vec![];
+        };
+        let passed: BTreeSet<String> = self
+            .paths
+            .iter()
+            .filter(|path| !path.is_failed())
+            .map(ExecutionPath::key)
+            .collect();
+        known_failures
+            .iter()
+            .filter(|key| passed.contains(*key))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Passing path most similar to a given failing one
+    ///
+    /// Similarity is measured by the length of common prefix of
+    /// failpoint decisions, and then by edit distance between them.
+    /// Returns the path along with [`PathDiff`] describing how they
+    /// differ, or `None` if there are no passing paths.
+    pub fn nearest_passing_path(
+        &self,
+        failing: &ExecutionPath,
+    ) -> Option<(&ExecutionPath, PathDiff)> {
+        let nearest = nearest_passing_path(&self.paths, failing)?;
+        Some((nearest, PathDiff::new(failing, nearest)))
+    }
+
+    /// Failpoints activated more often on failing paths than on passing ones
+    ///
+    /// Sorted by [`FailureCorrelation::score()`], highest first, so
+    /// the failpoints at the top are the ones most likely responsible
+    /// for failures.
+    pub fn failure_correlations(&self) -> Vec<(&'static str, FailureCorrelation)> {
+        let num_failing = self.paths.iter().filter(|path| path.is_failed()).count();
+        let mut correlations: BTreeMap<&'static str, FailureCorrelation> = BTreeMap::new();
+        for path in &self.paths {
+            let activated: BTreeSet<_> = path.activated_failpoints().collect();
+            for failpoint in activated {
+                let correlation = correlations.entry(failpoint).or_default();
+                if path.is_failed() {
+                    correlation.failing += 1;
+                } else {
+                    correlation.passing += 1;
+                }
+            }
+        }
+        let mut correlations: Vec<_> = correlations
+            .into_iter()
+            .map(|(failpoint, mut correlation)| {
+                correlation.num_failing = num_failing;
+                correlation.num_passing = self.paths.len() - num_failing;
+                (failpoint, correlation)
+            })
+            .filter(|(_, correlation)| correlation.score() > 0.0)
+            .collect();
+        correlations.sort_by(|(_, a), (_, b)| b.score().total_cmp(&a.score()));
+        correlations
+    }
+
+    /// Compact summary of failures, or `None` if no path has failed
+    ///
+    /// Contains numbers of explored and failed paths, up to three
+    /// failpoints most correlated with failures (see
+    /// [`failure_correlations()`](Self::failure_correlations)), and
+    /// the first failing path. Appended to messages of [`Error`](crate::Error)s
+    /// carrying a report with failures, so default failure output is
+    /// diagnostic without extra configuration.
+    pub fn failure_summary(&self) -> Option<String> {
+        let first = self.paths.iter().find(|path| path.is_failed())?;
+        let num_failing = self.paths.iter().filter(|path| path.is_failed()).count();
+        let mut summary = format!("{num_failing} of {} path(s) failed", self.paths.len());
+        let correlations = self.failure_correlations();
+        if !correlations.is_empty() {
+            let correlations: Vec<String> = correlations
+                .iter()
+                .take(3)
+                .map(|(failpoint, correlation)| {
+                    format!(
+                        "{failpoint} ({} of {} failing vs {} of {} passing)",
+                        correlation.failing,
+                        correlation.num_failing,
+                        correlation.passing,
+                        correlation.num_passing
+                    )
+                })
+                .collect();
+            summary += &format!("; correlated with failure: {}", correlations.join(", "));
+        }
+        summary += &format!("; first failing path: {}", first.key());
+        Some(summary)
+    }
+
+    /// Failpoints which become unreachable when a given failpoint is activated
+    ///
+    /// Empty if the failpoint was never both skipped and activated at
+    /// the same place of execution.
+    pub fn blast_radius(&self, failpoint: &str) -> BlastRadius {
+        self.blast_radii()
+            .get(self.resolve_alias(failpoint))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Blast radii of all failpoints, computed on first use
+    fn blast_radii(&self) -> &BTreeMap<&'static str, BlastRadius> {
+        self.blast_radii.get_or_init(|| blast_radii(&self.paths))
+    }
+
+    /// Render as GitHub flavored markdown, for posting to pull requests
+    ///
+    /// Contains a summary with key counts, and, depending on
+    /// [`MarkdownOptions`], a table of failpoints and a collapsible
+    /// list of failing paths, each with a difference from the nearest
+    /// passing path. Failpoints are rendered with their aliases (see
+    /// [`Runner::with_aliases()`](crate::Runner::with_aliases)).
+    ///
+    /// ```
+    /// # use faine::{MarkdownOptions, Runner};
+    /// let report = Runner::default().run(|| {}).unwrap();
+    /// let markdown = report.to_markdown(&MarkdownOptions::default().with_max_entries(10));
+    /// assert!(markdown.starts_with("### Fault injection passed"));
+    /// ```
+    pub fn to_markdown(&self, options: &MarkdownOptions) -> String {
+        markdown::render(self, options)
+    }
+
+    /// Failpoints with non-empty blast radius, largest first
+    ///
+    /// Activation of failpoints at the top of this list hides the most
+    /// of downstream code from exploration, so these may benefit from
+    /// dedicated tests starting past them.
+    pub fn largest_blast_radii(&self) -> Vec<(&'static str, BlastRadius)> {
+        let mut radii: Vec<_> = self
+            .blast_radii()
+            .iter()
+            .filter(|(_, radius)| !radius.is_empty())
+            .map(|(failpoint, radius)| (*failpoint, radius.clone()))
+            .collect();
+        radii.sort_by_key(|(_, radius)| std::cmp::Reverse(radius.len()));
+        radii
+    }
+
+    /// Render non-empty blast radii as JSON, largest first
+    ///
+    /// See [`largest_blast_radii()`](Self::largest_blast_radii).
+    pub fn blast_radii_to_json(&self) -> String {
+        fn names_json(names: &[&str]) -> String {
+            let names: Vec<String> = names.iter().map(|name| json_string(name)).collect();
+            format!("[{}]", names.join(","))
+        }
+        let radii: Vec<String> = self
+            .largest_blast_radii()
+            .into_iter()
+            .map(|(failpoint, radius)| {
+                format!(
+                    "{{\"failpoint\":{},\"always_skipped\":{},\"sometimes_skipped\":{}}}",
+                    json_string(failpoint),
+                    names_json(&radius.always_skipped),
+                    names_json(&radius.sometimes_skipped)
+                )
+            })
+            .collect();
+        format!("{{\"blast_radii\":[{}]}}", radii.join(","))
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "explored {} path(s) through {} failpoint(s)",
+            self.num_paths(),
+            self.failpoints().len()
+        )?;
+        if self.num_pruned > 0 {
+            write!(
+                f,
+                ", {} branch(es) pruned by user-asserted independence",
+                self.num_pruned
+            )?;
+        }
+        if self.num_dedup_skipped > 0 {
+            write!(
+                f,
+                ", {} subtree(s) skipped as explored by other tests",
+                self.num_dedup_skipped
+            )?;
+        }
+        if self.num_dedup_mismatches > 0 {
+            write!(
+                f,
+                ", {} subtree(s) mismatching other tests",
+                self.num_dedup_mismatches
+            )?;
+        }
+        let num_panicked = self
+            .paths
+            .iter()
+            .filter(|path| path.panic.is_some())
+            .count();
+        if num_panicked > 0 {
+            write!(f, ", {num_panicked} panicking path(s)")?;
+        }
+        if self.known_failures.is_some() {
+            write!(
+                f,
+                " ({} known, {} new failure(s)",
+                self.known_failing_paths().count(),
+                self.new_failing_paths().count()
+            )?;
+            let num_fixed = self.fixed_known_failures().len();
+            if num_fixed > 0 {
+                write!(f, ", {num_fixed} known failure(s) fixed")?;
+            }
+            write!(f, ")")?;
+        }
+        if self.tier == Tier::Smoke {
+            write!(f, " (smoke tier)")?;
+        }
+        if let Some(verification) = &self.self_verification {
+            write!(
+                f,
+                ", {} path(s) replayed (seed {}), {} diverged",
+                verification.replayed,
+                verification.seed,
+                verification.divergences.len()
+            )?;
+        }
+        if self.forbidden_checked {
+            write!(f, ", {} forbidden visit(s)", self.forbidden_visits.len())?;
+        }
+        if self.injection_audit.is_some() {
+            write!(
+                f,
+                ", {} unintended success injection(s)",
+                self.unintended_success_injections().len()
+            )?;
+        }
+        if let Some(impure) = &self.impure_injections {
+            write!(f, ", {} impure injection(s)", impure.len())?;
+        }
+        let num_partial_checks = self
+            .check_counts()
+            .values()
+            .filter(|count| **count < self.paths.len())
+            .count();
+        if num_partial_checks > 0 {
+            write!(
+                f,
+                ", {num_partial_checks} check(s) not executed on all paths"
+            )?;
+        }
+        let num_safety_valve = self
+            .paths
+            .iter()
+            .filter(|path| !path.safety_valve.is_empty())
+            .count();
+        if num_safety_valve > 0 {
+            write!(f, ", safety valve triggered on {num_safety_valve} path(s)")?;
+        }
+        let num_left_disabled = self.paths.iter().filter(|path| path.left_disabled).count();
+        if num_left_disabled > 0 {
+            write!(
+                f,
+                ", failpoints left disabled on {num_left_disabled} path(s)"
+            )?;
+        }
+        let num_section_held = self
+            .paths
+            .iter()
+            .filter(|path| path.critical_section_held)
+            .count();
+        if num_section_held > 0 {
+            write!(
+                f,
+                ", critical section held at the end of {num_section_held} path(s)"
+            )?;
+        }
+        let num_rate_limited: usize = self
+            .fault_rates
+            .values()
+            .map(|fault_rate| fault_rate.rate_limited)
+            .sum();
+        if num_rate_limited > 0 {
+            write!(f, ", {num_rate_limited} activation(s) rate-limited")?;
+        }
+        let num_cooled_down: usize = self
+            .fault_rates
+            .values()
+            .map(|fault_rate| fault_rate.cooled_down)
+            .sum();
+        if num_cooled_down > 0 {
+            write!(f, ", {num_cooled_down} activation(s) cooled down")?;
+        }
+        if self.stopped_by_diminishing_returns {
+            write!(f, ", stopped early due to diminishing returns")?;
+        } else if self.interrupted {
+            write!(f, ", stopped early due to interrupt")?;
+        } else if self.incomplete {
+            write!(f, ", stopped early due to budget")?;
+        }
+        if !self.unexplored_slots.is_empty() {
+            let slots: Vec<String> = self
+                .unexplored_slots
+                .iter()
+                .rev()
+                .map(|(weight, count)| format!("{count} {weight}"))
+                .collect();
+            write!(f, " (unexplored slots: {})", slots.join(", "))?;
+        }
+        if let Some(hit_limits) = &self.hit_limits {
+            write!(f, ", {hit_limits}")?;
+        }
+        if !self.order_sensitive_paths.is_empty() {
+            write!(
+                f,
+                ", {} order-sensitive path(s)",
+                self.order_sensitive_paths.len()
+            )?;
+        }
+        if f.alternate() {
+            for (i, path) in self.paths.iter().enumerate() {
+                write!(f, "\n  #{i}: {path}")?;
+            }
+            for key in self.fixed_known_failures() {
+                write!(f, "\n  fixed known failure: {key}")?;
+            }
+            if let Some(verification) = &self.self_verification {
+                for divergence in &verification.divergences {
+                    write!(f, "\n  replay divergence: {divergence}")?;
+                }
+            }
+            for (failpoint, path) in &self.forbidden_visits {
+                write!(f, "\n  forbidden visit of {failpoint}: ")?;
+                for step in path {
+                    write!(f, "{step} → ")?;
+                }
+                write!(f, "{failpoint}")?;
+            }
+            for path in &self.unreachable_priority_paths {
+                write!(f, "\n  unreachable priority path: {}", path.join(" → "))?;
+            }
+            for (failpoint, absorption) in self.absorption() {
+                if absorption.success > 0 {
+                    write!(
+                        f,
+                        "\n  absorbed: {failpoint} ({} of {} path(s))",
+                        absorption.success, absorption.paths
+                    )?;
+                }
+            }
+            for (failpoint, hygiene) in self.hygiene.iter().flatten() {
+                write!(f, "\n  hygiene of {failpoint}: {hygiene}")?;
+            }
+            for (failpoint, audit) in self.injection_audit.iter().flatten() {
+                if audit.is_unintended_success() {
+                    write!(
+                        f,
+                        "\n  success injected: {failpoint} ({} of {} activation(s))",
+                        audit.success,
+                        audit.success + audit.failure + audit.unclassified
+                    )?;
+                }
+            }
+            for impure in self.impure_injections() {
+                write!(
+                    f,
+                    "\n  {impure}\n    first on: {}\n    later on: {}",
+                    impure.first_path, impure.later_path
+                )?;
+            }
+            for valve in self.safety_valve_triggered() {
+                write!(f, "\n  safety valve triggered: {valve}")?;
+            }
+            for (check, count) in self.check_counts() {
+                if count < self.paths.len() {
+                    write!(
+                        f,
+                        "\n  check partially executed: {check} ({count} of {} path(s))",
+                        self.paths.len()
+                    )?;
+                }
+            }
+            for (counter, stats) in self.counter_stats() {
+                write!(f, "\n  counter {counter}: {stats}")?;
+            }
+            for (failpoint, expect) in self.unmet_site_expectations() {
+                write!(f, "\n  unmet site expectation: {failpoint} ({expect})")?;
+            }
+            if let Some(timeline) = &self.timeline {
+                write!(f, "\n  timeline: {timeline}")?;
+            }
+            for (i, phase) in self.phases.iter().enumerate() {
+                write!(
+                    f,
+                    "\n  phase #{i} ({:?}, p={}): {} iteration(s), {} activation(s), {} failure(s)",
+                    phase.duration,
+                    phase.probability,
+                    phase.iterations,
+                    phase.activations,
+                    phase.failures
+                )?;
+            }
+            for (failpoint, runs) in &self.failure_runs {
+                let runs: Vec<_> = runs
+                    .iter()
+                    .map(|(length, count)| format!("{length}×{count}"))
+                    .collect();
+                write!(f, "\n  failure runs of {failpoint}: {}", runs.join(", "))?;
+            }
+            for (failpoint, fault_rate) in &self.fault_rates {
+                write!(
+                    f,
+                    "\n  fault rate of {failpoint}: {} activation(s) of {} visit(s), {:.1}/s",
+                    fault_rate.activations, fault_rate.visits, fault_rate.activations_per_second
+                )?;
+                if fault_rate.rate_limited > 0 {
+                    write!(f, ", {} rate-limited", fault_rate.rate_limited)?;
+                }
+                if fault_rate.cooled_down > 0 {
+                    write!(f, ", {} cooled down", fault_rate.cooled_down)?;
+                }
+            }
+            #[cfg(feature = "fs")]
+            if let Some(timing) = &self.fixture_timing {
+                write!(
+                    f,
+                    "\n  fixture: {} reset(s) took {:.3}s of {:.3}s",
+                    timing.resets,
+                    timing.reset_duration.as_secs_f64(),
+                    timing.total_duration.as_secs_f64()
+                )?;
+            }
+            for (component, stats) in self.components() {
+                write!(
+                    f,
+                    "\n  component {component}: {} failpoint(s), {} activation(s), {} failing path(s)",
+                    stats.failpoints.len(),
+                    stats.activations,
+                    stats.failing_paths
+                )?;
+            }
+            for (location, paths) in self.panicking_paths() {
+                let combinations: BTreeSet<Vec<&str>> = paths
+                    .iter()
+                    .map(|path| path.activated_failpoints().collect())
+                    .collect();
+                write!(
+                    f,
+                    "\n  panic at {location}: {} fault combination(s)",
+                    combinations.len()
+                )?;
+            }
+            let radii = self.largest_blast_radii();
+            if !radii.is_empty() {
+                write!(f, "\n  largest blast radius:")?;
+                for (i, (failpoint, radius)) in radii.iter().take(3).enumerate() {
+                    let separator = if i > 0 { "," } else { "" };
+                    write!(f, "{separator} {failpoint} ({} failpoint(s))", radius.len())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Report {
+        let mut report = Report::default();
+        report.add_path(ExecutionPath {
+            steps: vec![Step {
+                failpoint: "a",
+                branch: Branch::Activate,
+                variant: None,
+                variant_index: None,
+                trigger: None,
+            }],
+            timed_out: false,
+            independence_audit: false,
+            captured_output: None,
+            outcome: None,
+            panic: None,
+            suppressed_during_unwind: vec![],
+            suppressed_by_critical_section: vec![],
+            critical_section_held: false,
+            recent_events: None,
+            reruns: None,
+            axes: vec![],
+            result: None,
+            safety_valve: vec![],
+            checks: vec![],
+            left_disabled: false,
+            counters: vec![],
+            checkpoints: vec![],
+        });
+        report.add_path(ExecutionPath {
+            steps: vec![
+                Step {
+                    failpoint: "a",
+                    branch: Branch::Skip,
+                    variant: None,
+                    variant_index: None,
+                    trigger: None,
+                },
+                Step {
+                    failpoint: "b",
+                    branch: Branch::Skip,
+                    variant: None,
+                    variant_index: None,
+                    trigger: None,
+                },
+            ],
+            timed_out: true,
+            independence_audit: false,
+            captured_output: None,
+            outcome: None,
+            panic: None,
+            suppressed_during_unwind: vec![],
+            suppressed_by_critical_section: vec![],
+            critical_section_held: false,
+            recent_events: None,
+            reruns: None,
+            axes: vec![],
+            result: None,
+            safety_valve: vec![],
+            checks: vec![],
+            left_disabled: false,
+            counters: vec![],
+            checkpoints: vec![],
+        });
+        report
+    }
+
+    #[test]
+    fn test_failpoints() {
+        let report = sample();
+        assert_eq!(report.num_paths(), 2);
+        assert_eq!(report.failpoints(), BTreeSet::from(["a", "b"]));
+        assert_eq!(report.activated_failpoints(), BTreeSet::from(["a"]));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let report = sample();
+        assert_eq!(
+            report.to_markdown(&MarkdownOptions::default()),
+            "### Fault injection failed
+
+**paths** `2` · **failing** `1` · **failpoints** `2`
+
+explored 2 path(s) through 2 failpoint(s)
+
+| Failpoint | Component | Visits | Activations | Failing |
+|---|---|---:|---:|---:|
+| `a` |  | 2 | 1 | 0 |
+| `b` |  | 1 | 0 | 0 |
+
+<details>
+<summary>Failing paths (1)</summary>
+
+- `a → b` timed out
+
+  ```text
+    failing   passing
+  > a         [a]
+  > b
+  ```
+
+</details>
+"
+        );
+        assert_eq!(
+            report.to_markdown(
+                &MarkdownOptions::default()
+                    .with_max_entries(1)
+                    .with_failing_paths(false)
+            ),
+            "### Fault injection failed
+
+**paths** `2` · **failing** `1` · **failpoints** `2`
+
+explored 2 path(s) through 2 failpoint(s)
+
+| Failpoint | Component | Visits | Activations | Failing |
+|---|---|---:|---:|---:|
+| `a` |  | 2 | 1 | 0 |
+
+_…and 1 more failpoint(s)_
+"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let report = sample();
+        assert_eq!(
+            report.to_string(),
+            "explored 2 path(s) through 2 failpoint(s)"
+        );
+        assert_eq!(
+            format!("{report:#}"),
+            "explored 2 path(s) through 2 failpoint(s)\n  #0: [a]\n  #1: a → b (timed out)\n  largest blast radius: a (1 failpoint(s))"
+        );
+    }
+}
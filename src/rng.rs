@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::ops::Range;
+
+use crate::__private::FAILPOINTS;
+use crate::fnv::FnvHasher;
+use crate::strategy::Rng;
+
+/// Pseudorandom generator deterministic under [`Runner::run()`](crate::Runner::run)
+///
+/// See [`rng()`].
+#[derive(Debug)]
+pub struct FaineRng {
+    fallback: Rng,
+}
+
+impl FaineRng {
+    /// Uniformly distributed 64 bit value
+    pub fn next_u64(&mut self) -> u64 {
+        draw().unwrap_or_else(|| self.fallback.next_u64())
+    }
+
+    /// Uniformly distributed value in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniformly distributed value in a given range
+    ///
+    /// # Panics
+    ///
+    /// If the range is empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "cannot sample empty range");
+        let span = range.end - range.start;
+        range.start + ((self.next_u64() as u128 * span as u128) >> 64) as u64
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::RngCore for FaineRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        FaineRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+/// Draw a value derived from the path of the current iteration, if
+/// running under a runner
+fn draw() -> Option<u64> {
+    FAILPOINTS.with_borrow_mut(|state| {
+        let state = state.as_mut()?;
+        let mut hasher = FnvHasher::new();
+        (state.rng_seed, state.rng_draws, state.tree.current_path()).hash(&mut hasher);
+        state.rng_draws += 1;
+        Some(Rng::new(hasher.finish()).next_u64())
+    })
+}
+
+/// Pseudorandom generator for tested code
+///
+/// Code which uses randomness, for instance, for jittered backoff or
+/// random file names, may take different paths given the same
+/// failpoint decisions, which breaks exploration (see
+/// [`Runner::with_strict_determinism()`]). Under [`Runner::run()`],
+/// values produced by this generator are derived from the seed set
+/// with [`Runner::with_rng_seed()`], the failpoint decisions taken so
+/// far in the iteration, and the number of values drawn before, so
+/// identical paths see identical sequences, including replays of a
+/// path. Outside of runners, it's seeded from entropy, so production
+/// behavior is not affected.
+///
+/// To migrate, replace `rand::rng()` (or `thread_rng()`) with
+/// `faine::rng()` in the code you test; with `rand` feature it
+/// implements `rand_core::RngCore`, so `rand` distributions work with
+/// it as before. To explore specific values at a call site, see
+/// [`inject_override_rng!`](crate::inject_override_rng).
+///
+/// ```
+/// use faine::{Runner, inject_return, rng};
+///
+/// fn backoff() -> u64 {
+///     let delay = rng().range(100..200);
+///     inject_return!("long backoff", delay * 10);
+///     delay
+/// }
+///
+/// let mut delays = vec![];
+/// Runner::ignore_env()
+///     .with_strict_determinism(true)
+///     .run(|| delays.push(backoff()))
+///     .unwrap();
+/// // the same value is drawn on both paths, as no decisions were taken before
+/// assert_eq!(delays[0], delays[1] * 10);
+/// ```
+///
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_rng_seed()`]: crate::Runner::with_rng_seed
+/// [`Runner::with_strict_determinism()`]: crate::Runner::with_strict_determinism
+pub fn rng() -> FaineRng {
+    FaineRng {
+        fallback: Rng::new(RandomState::new().build_hasher().finish()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let mut rng = rng();
+        for _ in 0..100 {
+            assert!((10..20).contains(&rng.range(10..20)));
+        }
+        assert_eq!(rng.range(5..6), 5);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_rng_core() {
+        use rand_core::RngCore;
+        let mut bytes = [0; 37];
+        rng().fill_bytes(&mut bytes);
+        assert!(bytes.iter().any(|byte| *byte != 0));
+    }
+}
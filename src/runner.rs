@@ -1,11 +1,60 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use crate::__private::{FAILPOINTS, State};
-use crate::common::{Branch, Label};
+use crate::absorption::{self, Outcome};
+use crate::aliases;
+use crate::aliases::Aliases;
+use crate::axes::AxisBudget;
+use crate::capture::{self, Capture};
+use crate::checks;
+use crate::common::{Branch, Label, Phase, Tier};
+use crate::counters;
+use crate::coverage;
+use crate::dedup::DedupMode;
+use crate::env::{self, EnvOverride};
 use crate::error::Error;
+use crate::events::{self, EventSink};
+use crate::ext;
+use crate::faultmodel::FaultModel;
+use crate::fnv::FnvHasher;
+use crate::functions;
+use crate::hitlimits;
+use crate::injected::InjectedError;
+use crate::intern;
+use crate::known;
 use crate::options::Options;
+use crate::order_fuzz;
+use crate::outcome::RunOutcome;
+use crate::panics::{self, CaughtPanic};
+use crate::params;
+use crate::plan::PlannedPath;
+use crate::recent::{EventBuffer, take_recent_events};
+use crate::registry::Registration;
+use crate::report::{ExecutionPath, FoundFailure, InjectionAudit, Report, Reruns};
+use crate::safety::{SafetyLimits, SafetyValve};
+use crate::selector::Selector;
+use crate::shard::Shard;
+use crate::staged::{self, Stage, StagedReport};
+use crate::strategy::{Cooldown, PhaseStats, RateLimit, Strategy};
+use crate::timeline::{DiminishingReturns, Timeline, TimelineSample};
+use crate::trace::Trace;
 use crate::tree::{ExecutionStatus, Tree};
+use crate::verification::{self, ReplayDivergence, SelfVerification};
+use crate::watchdog::Watchdog;
+
+/// Version of the exploration algorithm
+///
+/// Must be bumped whenever a change may alter the set or the order
+/// of paths explored for unchanged user code. `test_exploration_version`
+/// integration test guards this with a golden listing of paths.
+pub(crate) const EXPLORATION_VERSION: u32 = 1;
 
 /// Runner for code instrumented with failpoints
 ///
@@ -35,12 +84,181 @@ use crate::tree::{ExecutionStatus, Tree};
 /// }
 /// # test_foobar();
 /// ```
-#[derive(Default)]
+///
+/// # Environment
+///
+/// [`Runner::default()`] takes defaults from the following environment
+/// variables, which allows imposing policy on all tests (for instance,
+/// in CI) without changing the code. Explicit `with_` calls take
+/// precedence. Invalid values make [`run()`] fail with
+/// [`Error::Configuration`].
+///
+/// - `FAINE_MAX_ITERATIONS`: see [`with_max_iterations()`](Self::with_max_iterations).
+/// - `FAINE_MAX_DURATION_MS`: see [`with_max_duration()`](Self::with_max_duration),
+///   in milliseconds.
+/// - `FAINE_BRANCH_PREFERENCE`: `activate` or `skip`, see
+///   [`with_branch_preference()`](Self::with_branch_preference).
+/// - `FAINE_STRATEGY`: `full` or `smoke`, see [`smoke()`](Self::smoke).
+/// - `FAINE_STRICT_DETERMINISM`: boolean, see
+///   [`with_strict_determinism()`](Self::with_strict_determinism).
+/// - `FAINE_DEDUP_STORE`: path, see [`with_dedup_store()`](Self::with_dedup_store).
+/// - `FAINE_SHARD`: `index/total`, see [`with_shard()`](Self::with_shard).
+/// - `FAINE_REPLAY`: path key as printed for a failing path (see
+///   [`ExecutionPath::key()`]), which is followed as the only
+///   [priority path](Self::with_priority_paths) which must be
+///   reachable; unless `FAINE_MAX_ITERATIONS` is also set, exploration
+///   stops after it.
+///
+/// Use [`Runner::ignore_env()`] to construct a runner which ignores them.
 pub struct Runner {
     options: Options,
+    config_error: Option<String>,
+}
+
+impl Default for Runner {
+    /// Construct a runner with defaults from environment variables
+    fn default() -> Self {
+        Self::with_env_lookup(|name| match std::env::var(name) {
+            Ok(value) => Some(value),
+            Err(std::env::VarError::NotPresent) => None,
+            Err(std::env::VarError::NotUnicode(value)) => {
+                Some(value.to_string_lossy().into_owned())
+            }
+        })
+    }
 }
 
 impl Runner {
+    fn with_env_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut options = Options::default();
+        let config_error = options.apply_env(lookup).err();
+        Self {
+            options,
+            config_error,
+        }
+    }
+
+    /// Construct a runner which ignores environment variables
+    ///
+    /// Unlike [`Runner::default()`], the behavior of this runner
+    /// only depends on the code.
+    pub fn ignore_env() -> Self {
+        Self {
+            options: Default::default(),
+            config_error: None,
+        }
+    }
+
+    /// Construct a runner for fast smoke tests
+    ///
+    /// Instead of exploring all combinations of failpoints, this runs
+    /// the code once with no failpoints activated, and then once per
+    /// each failpoint with only that failpoint activated. Failpoints
+    /// which only become reachable when another one is activated are
+    /// discovered along the way, and are activated once as well (along
+    /// with the failpoint which exposed them). This gives predictable
+    /// run time proportional to the number of failpoints, suitable for
+    /// per-commit CI checks, while full exploration may be run less
+    /// frequently. [`Report`] states the tier it was produced with.
+    ///
+    /// The runner may be tuned with `with_` methods as usual, except
+    /// that branch preference is ignored.
+    pub fn smoke() -> Self {
+        let mut runner = Self::default();
+        runner.options.tier = Tier::Smoke;
+        runner
+    }
+
+    pub(crate) fn with_tier(mut self, tier: Tier) -> Self {
+        self.options.tier = tier;
+        self
+    }
+
+    /// Limit number of explored paths
+    ///
+    /// When the limit is reached, exploration stops, and the [`Report`]
+    /// is marked as incomplete.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.options.max_iterations = Some(max_iterations);
+        self
+    }
+
+    /// Limit duration of exploration
+    ///
+    /// Checked between iterations. When the limit is exceeded,
+    /// exploration stops, and the [`Report`] is marked as incomplete.
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.options.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Record exploration progress over time
+    ///
+    /// Progress is sampled between iterations, no more often than once
+    /// per `interval`, and at the end of exploration. Samples are
+    /// available through [`Report::timeline()`], and alternate form of
+    /// report display includes a sparkline of them.
+    pub fn with_timeline(mut self, interval: Duration) -> Self {
+        self.options.timeline_interval = Some(interval);
+        self
+    }
+
+    /// Stop exploration when it no longer finds new fault combinations
+    ///
+    /// Checked between iterations. If fewer than `min_new_combinations`
+    /// distinct combinations of activated failpoints were explored in
+    /// the trailing `window`, exploration stops, and the [`Report`] is
+    /// marked as incomplete (see [`Report::is_stopped_by_diminishing_returns()`]).
+    /// Mostly useful for long explorations, and with random strategy
+    /// (see [`with_strategy()`](Self::with_strategy)).
+    pub fn with_diminishing_returns_stop(
+        mut self,
+        window: Duration,
+        min_new_combinations: usize,
+    ) -> Self {
+        self.options.diminishing_returns = Some((window, min_new_combinations));
+        self
+    }
+
+    /// Replay a sample of explored paths to verify their reproducibility
+    ///
+    /// After exploration, a `sample` fraction (from 0 to 1) of explored
+    /// paths is chosen randomly and replayed by forcing the recorded
+    /// failpoint decisions. Sequences of visited failpoints, as well as
+    /// outcomes (finish, panic location, timeout and the digest recorded
+    /// with [`record_outcome()`](crate::record_outcome)) are compared to
+    /// the original ones. Mismatches show that exploration results can't
+    /// be trusted, as tested code is not deterministic, and are listed
+    /// in [`Report::self_verification()`] along with the seed used to
+    /// choose paths. With [`with_strict_determinism()`](Self::with_strict_determinism),
+    /// mismatches make [`run()`](Self::run) fail.
+    ///
+    /// Seed is random, unless set with
+    /// [`with_self_verification_seed()`](Self::with_self_verification_seed).
+    pub fn with_self_verification(mut self, sample: f32) -> Self {
+        self.options.self_verification = Some(sample);
+        self
+    }
+
+    /// Set seed for choosing paths replayed by self-verification
+    ///
+    /// See [`with_self_verification()`](Self::with_self_verification).
+    pub fn with_self_verification_seed(mut self, seed: u64) -> Self {
+        self.options.self_verification_seed = Some(seed);
+        self
+    }
+
+    /// Fail if tested code is not deterministic
+    ///
+    /// Exploration relies on the tested code following the same path
+    /// given the same failpoint decisions. With this enabled, if a
+    /// violation of this is witnessed, [`run()`](Self::run) returns
+    /// [`Error::NonDeterminism`].
+    pub fn with_strict_determinism(mut self, strict: bool) -> Self {
+        self.options.strict_determinism = strict;
+        self
+    }
+
     /// Select execution order preference
     ///
     /// By default, the runner first tries paths passing through an activated
@@ -52,58 +270,3415 @@ impl Runner {
         self
     }
 
-    /// Run the provided code with failpoint handling
+    /// Set seed of the generator returned by [`rng()`](crate::rng)
     ///
-    /// Runs the provided code, being aware of failpoints defined in it.
-    /// The code will be ran multiple times with different failpoint
-    /// combinations activated.
+    /// Default is zero. Changing the seed changes values drawn by the
+    /// tested code on all paths.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.options.rng_seed = seed;
+        self
+    }
+
+    /// Shuffle execution order
     ///
-    /// Currently, returns nothing useful and never returns an error (but this
-    /// will change in the future), but you can run asserts from the code.
+    /// Instead of following a single [branch
+    /// preference](Self::with_branch_preference), choose which branch
+    /// to try first at each failpoint pseudorandomly, based on the
+    /// given seed. The same seed always gives the same order, and the
+    /// set of explored paths is not affected. Paths are still explored
+    /// depth first, so the shuffle is coarse, but sufficient to expose
+    /// state leaking between iterations which depends on their order.
     ///
-    /// You can treat a code you pass to it as a regular test.
-    pub fn run(self, mut func: impl FnMut()) -> Result<(), Error> {
-        FAILPOINTS.with_borrow_mut(|state| {
-            assert!(state.is_none(), "failpoints state double initialization");
-            *state = Some(Box::new(State {
-                enabled: true,
-                tree: Tree::new(self.options),
-            }));
-        });
+    /// See also [`fuzz_orders()`](Self::fuzz_orders).
+    pub fn with_order_seed(mut self, seed: u64) -> Self {
+        self.options.order_seed = Some(seed);
+        self
+    }
 
-        loop {
-            FAILPOINTS.with_borrow_mut(|state| {
-                state
-                    .as_mut()
-                    .expect("failpoints state must be initialized")
-                    .tree
-                    .start()
-            });
+    /// Set iteration timeout
+    ///
+    /// The timeout is not enforced: arbitrary code cannot be
+    /// interrupted, so an iteration which exceeds it keeps running,
+    /// and one which never finishes (for instance, spinning in an
+    /// infinite retry loop) still hangs the whole run. Instead, when
+    /// a single iteration runs longer than the given duration, a
+    /// watchdog thread immediately prints the partial path it has
+    /// taken to stderr, so there's evidence of which failpoints have
+    /// caused the hang even if the test is killed afterwards.
+    ///
+    /// To actually terminate, the tested code has to check
+    /// [`iteration_timed_out()`](crate::iteration_timed_out) (for
+    /// instance, in its retry loops) and return. Paths which finish
+    /// after exceeding the timeout, cooperatively or not, are marked
+    /// as timed out in the [`Report`]. See
+    /// [`with_enforced_iteration_timeout()`](Self::with_enforced_iteration_timeout)
+    /// for a timeout which terminates hung iterations.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use faine::{Runner, inject_return, iteration_timed_out};
+    /// fn connect() -> Result<(), ()> {
+    ///     inject_return!("connect", Err(()));
+    ///     Ok(())
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_iteration_timeout(Duration::from_millis(10))
+    ///     .run(|| {
+    ///         // retries forever on the injected fault, unless bounded by the check
+    ///         if connect().is_err() {
+    ///             while !iteration_timed_out() {
+    ///                 std::thread::sleep(Duration::from_millis(1));
+    ///             }
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.timed_out_paths().count(), 1);
+    /// ```
+    pub fn with_iteration_timeout(mut self, timeout: Duration) -> Self {
+        self.options.iteration_timeout = Some(timeout);
+        self
+    }
 
-            // TODO: catch panics (but not asserts?)
-            func();
+    /// Set iteration timeout which aborts the process when exceeded
+    ///
+    /// Like [`with_iteration_timeout()`](Self::with_iteration_timeout),
+    /// but as a hung iteration cannot be interrupted, the whole process
+    /// is aborted once it exceeds the timeout, after the path it has
+    /// taken, terminated as timed out, is printed to stderr (bypassing
+    /// [output capture](Self::with_capture_output)). The [`Report`]
+    /// is lost this way, so this is intended for tests which may hang
+    /// indefinitely and would otherwise be killed without any clue of
+    /// the failpoints which made them hang. The printed path key can be
+    /// passed to `FAINE_REPLAY` to reproduce the hang.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use faine::{Runner, inject_return};
+    /// fn connect() -> Result<(), ()> {
+    ///     inject_return!("connect", Err(()));
+    ///     Ok(())
+    /// }
+    ///
+    /// Runner::ignore_env()
+    ///     .with_enforced_iteration_timeout(Duration::from_secs(10))
+    ///     .run(|| {
+    ///         // retries forever on the injected fault, aborting with
+    ///         // "faine: iteration timed out after 10s, aborting: [connect] (timed out), key: [connect]"
+    ///         while connect().is_err() {}
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn with_enforced_iteration_timeout(mut self, timeout: Duration) -> Self {
+        self.options.iteration_timeout = Some(timeout);
+        self.options.enforce_iteration_timeout = true;
+        self
+    }
 
-            let mut status = ExecutionStatus::Continue;
-            FAILPOINTS.with_borrow_mut(|state| {
-                status = state
-                    .as_mut()
-                    .expect("failpoints state must be initialized")
-                    .tree
-                    .finalize(Label::Finished);
-            });
+    /// Set per-iteration limits on injected faults
+    ///
+    /// By default, a failpoint is activated at most 1000 times, and
+    /// failpoints are visited at most 1000000 times per iteration, so
+    /// code which retries indefinitely on injected failures cannot
+    /// make exploration endless. Further visits are skipped, and
+    /// paths on which this has happened have
+    /// [`ExecutionPath::safety_valve`] set, and are counted in the
+    /// [`Report`]. Raise the limits if the tested code legitimately
+    /// needs that many visits.
+    ///
+    /// Skipping failpoints does not abort the iteration, so code which
+    /// keeps looping with all failpoints skipped is not stopped by the
+    /// limits, see [`with_iteration_timeout()`](Self::with_iteration_timeout)
+    /// to detect such hangs.
+    ///
+    /// ```
+    /// use faine::{Runner, SafetyLimits, SafetyValve, inject_return};
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_safety_limits(SafetyLimits {
+    ///         max_activations: 3,
+    ///         ..Default::default()
+    ///     })
+    ///     .run(|| {
+    ///         // retries forever
+    ///         while (|| {
+    ///             inject_return!("connect", false);
+    ///             true
+    ///         })() == false {}
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.num_paths(), 4);
+    /// assert!(report.safety_valve_triggered().contains(&SafetyValve::Activations("connect")));
+    /// ```
+    pub fn with_safety_limits(mut self, limits: SafetyLimits) -> Self {
+        self.options.safety_limits = limits;
+        self
+    }
 
-            match status {
-                ExecutionStatus::Continue => {}
-                ExecutionStatus::Stop => {
-                    break;
-                }
+    /// Find per-failpoint activation limits adaptively
+    ///
+    /// Choosing an activation limit for code which retries in loops
+    /// is a tradeoff: too low a limit hides outcomes which only
+    /// appear after many failures, while too high one makes
+    /// exploration slow. In this mode, exploration starts with the
+    /// `initial` limit for every failpoint, in place of
+    /// [`SafetyLimits::max_activations`]. Then, limits of failpoints
+    /// which have reached them are doubled, up to `max`, and the code
+    /// is explored again, for as long as raising a limit of a failpoint
+    /// produces new outcomes, that is, [recorded outcomes](crate::record_outcome),
+    /// [results](crate::record_result), panic locations or timeouts,
+    /// on paths which activate it more than before. A raise which
+    /// produced nothing new is rolled back.
+    ///
+    /// Limits are never raised above [`SafetyLimits::max_activations`],
+    /// which also caps `initial` and `max`.
+    ///
+    /// Effective limits, and whether a fixed point was reached before
+    /// `max`, are available with [`Report::hit_limits()`]. The report
+    /// itself is of an exploration with the effective limits.
+    /// Explorations performed to find the limits do not use the dedup
+    /// store, export traces, report to event sinks or rerun failures,
+    /// so if any of these is enabled, the code is explored once more
+    /// with the found limits. Iteration and duration budgets (see
+    /// [`with_max_iterations()`](Self::with_max_iterations) and
+    /// [`with_max_duration()`](Self::with_max_duration)) are shared by
+    /// all explorations, each of which gets half of what remains, and
+    /// the limits are not raised after an incomplete exploration.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_return, record_outcome};
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_adaptive_hit_limits(1, 64)
+    ///     .run(|| {
+    ///         let mut retries = 0;
+    ///         while (|| {
+    ///             inject_return!("connect", false);
+    ///             true
+    ///         })() == false {
+    ///             retries += 1;
+    ///         }
+    ///         // gives up with a distinct outcome after 3 retries
+    ///         record_outcome(retries.min(3));
+    ///     })
+    ///     .unwrap();
+    /// let hit_limits = report.hit_limits().unwrap();
+    /// assert_eq!(hit_limits.limit("connect"), 4);
+    /// assert!(hit_limits.fixed_point);
+    /// ```
+    pub fn with_adaptive_hit_limits(mut self, initial: usize, max: usize) -> Self {
+        self.options.adaptive_hit_limits = Some((initial, max));
+        self
+    }
+
+    /// Report instrumentation hygiene of failpoints
+    ///
+    /// After exploration, outcomes reached with each failpoint
+    /// activated are compared with these reached with it skipped at
+    /// the same place. Failpoints which never make a difference only
+    /// multiply the number of paths, and are suggested for removal.
+    /// Results are available
+    /// with [`Report::hygiene()`], and listed in the alternate form of
+    /// the report in a stable order, so they may be tracked over time.
+    ///
+    /// Comparison relies on [recorded outcomes](crate::record_outcome)
+    /// and [classified results](Self::with_outcome_classifier), as
+    /// well as panics and timeouts, so code which does not record its
+    /// outcomes would have most failpoints suggested for removal.
+    /// Exploration is not affected.
+    ///
+    /// ```
+    /// use faine::{Runner, Suggestion, inject_return, record_outcome};
+    ///
+    /// fn save() -> Result<(), ()> {
+    ///     inject_return!("write", Err(()));
+    ///     inject_return!("flush", Err(()));
+    ///     // error is ignored
+    ///     inject_return!("write log", Ok(()));
+    ///     Ok(())
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_hygiene_report(true)
+    ///     .run(|| record_outcome(save()))
+    ///     .unwrap();
+    /// let hygiene = report.hygiene().unwrap();
+    /// assert_eq!(hygiene["write"].suggestion, Suggestion::Keep);
+    /// assert_eq!(hygiene["write log"].suggestion, Suggestion::Remove);
+    /// ```
+    pub fn with_hygiene_report(mut self, report: bool) -> Self {
+        self.options.hygiene_report = report;
+        self
+    }
+
+    /// Fail when safety limits are reached
+    ///
+    /// With this enabled, [`run()`](Self::run) fails with
+    /// [`Error::SafetyValveTriggered`] if any path has reached limits
+    /// set with [`with_safety_limits()`](Self::with_safety_limits),
+    /// for tests where unbounded retries are considered a bug.
+    pub fn with_safety_valve_is_error(mut self, is_error: bool) -> Self {
+        self.options.safety_valve_is_error = is_error;
+        self
+    }
+
+    /// Declare groups of mutually independent failpoints
+    ///
+    /// Failpoints from different groups are asserted not to interact,
+    /// so paths activating members of two different groups are not
+    /// explored (paths activating failpoints within a single group,
+    /// and paths skipping all of them still are). This may reduce the
+    /// number of explored paths dramatically, but it's up to the user
+    /// to ensure that the claim holds. Number of pruned branches is
+    /// included in the [`Report`], and
+    /// [`with_independence_audit()`](Self::with_independence_audit)
+    /// may be used to spot-check the claim.
+    ///
+    /// Groups may list full failpoint names, or whole components (see
+    /// [`Selector`]).
+    pub fn with_independent_groups<S: Clone + Into<Selector>>(mut self, groups: &[&[S]]) -> Self {
+        self.options.independent_groups = groups
+            .iter()
+            .map(|group| group.iter().cloned().map(Into::into).collect())
+            .collect();
+        self
+    }
+
+    /// Spot-check independence claims
+    ///
+    /// With this enabled, for each pair of independent groups declared
+    /// with [`with_independent_groups()`](Self::with_independent_groups),
+    /// the first encountered combination which would be pruned is
+    /// explored anyway, so false independence claims may be caught by
+    /// the assertions in the tested code. Such paths are marked in the
+    /// [`Report`].
+    pub fn with_independence_audit(mut self, audit: bool) -> Self {
+        self.options.audit_independence = audit;
+        self
+    }
+
+    /// Capture stdout and stderr of each iteration
+    ///
+    /// Captured output is stored in the [`Report`] (bounded in size),
+    /// and is available to the tested code through
+    /// [`captured_stdout()`](crate::captured_stdout) and
+    /// [`captured_stderr()`](crate::captured_stderr), so it's possible
+    /// to check invariants on what is being logged. If an iteration
+    /// panics, captured output is printed.
+    ///
+    /// This works by redirecting file descriptors, so only output
+    /// written directly to them is captured. Note that when running
+    /// under libtest, `print!` family of macros do not write to these
+    /// descriptors unless `--nocapture` is specified; writing with
+    /// [`std::io::stdout()`] or [`std::io::stderr()`] works in either case.
+    ///
+    /// On platforms where this is not supported (currently, non-unix),
+    /// this is a no-op, and [`Report::output_captured()`] returns `false`.
+    ///
+    /// Descriptors are shared by the whole process, so output of all
+    /// other threads is captured as well, including one of tests which
+    /// run in parallel and do not use faine, and is lost for them. Such
+    /// tests are better placed into a separate test binary, or run
+    /// with `--test-threads=1`. Runners are aware of this, so a runner
+    /// which captures output runs exclusively: it waits for other
+    /// runners in the process to finish, and they wait for it, see
+    /// concurrency section of [`run()`](Self::run).
+    pub fn with_capture_output(mut self, capture_output: bool) -> Self {
+        self.options.capture_output = capture_output;
+        self
+    }
+
+    /// Set test name used in coverage records
+    ///
+    /// By default, name of the current thread is used, which is the
+    /// test name when running under libtest.
+    pub fn with_test_name(mut self, test_name: impl Into<String>) -> Self {
+        self.options.test_name = Some(test_name.into());
+        self
+    }
+
+    /// Write coverage record into the given directory
+    ///
+    /// Records from multiple tests may be aggregated with
+    /// [`coverage::heatmap()`](crate::coverage::heatmap).
+    pub fn with_coverage_dir(mut self, coverage_dir: impl Into<PathBuf>) -> Self {
+        self.options.coverage_dir = Some(coverage_dir.into());
+        self
+    }
+
+    /// Write traces of failing iterations into the given directory
+    ///
+    /// Each trace lists failpoints visited during the iteration, with
+    /// their (aliased) names, taken branches and timestamps, followed by
+    /// the outcome of the iteration. Traces use Chrome trace event format,
+    /// and may be opened in [Perfetto](https://ui.perfetto.dev) or
+    /// `chrome://tracing`. Files are named after the test (see
+    /// [`with_test_name()`](Self::with_test_name)) and the index of
+    /// the path in [`Report::paths()`].
+    ///
+    /// With `all`, traces of all iterations are written, not only of
+    /// failing ones. Failure to write a trace is reported as a warning.
+    pub fn with_trace_export(mut self, trace_dir: impl Into<PathBuf>, all: bool) -> Self {
+        self.options.trace_dir = Some(trace_dir.into());
+        self.options.trace_all = all;
+        self
+    }
+
+    /// Set number of latest events kept for failing paths
+    ///
+    /// Failpoint visits, counter bumps (see [`count!`](crate::count))
+    /// and executed checks (see [`checked!`](crate::checked)) of each
+    /// iteration are appended to a buffer of this size (256 by
+    /// default), which survives panics of the tested code, and is
+    /// included into failing paths as [`ExecutionPath::recent_events`],
+    /// and printed along with failure diffs (see
+    /// [`with_max_failure_diffs()`](Self::with_max_failure_diffs)).
+    /// Zero disables the buffer.
+    pub fn with_recent_events(mut self, capacity: usize) -> Self {
+        self.options.recent_events = capacity;
+        self
+    }
+
+    /// Take failpoint decisions from bytes, see [`crate::fuzz`]
+    #[cfg(feature = "fuzz")]
+    pub(crate) fn with_decision_bytes(mut self, data: &[u8]) -> Self {
+        self.options.decision_bytes = Some(data.into());
+        self
+    }
+
+    /// Check whether outcomes depend on exploration order
+    ///
+    /// With this enabled, after the exploration completes, it is
+    /// repeated with the opposite branch preference, and outcomes
+    /// recorded with [`record_outcome()`](crate::record_outcome) on
+    /// each path are compared between the two runs. Paths which
+    /// produce different outcomes (or which were only explored in one
+    /// of the orders) are listed in
+    /// [`Report::order_sensitive_paths()`]. This usually indicates
+    /// state leaking between iterations, for instance through files
+    /// left by the test fixture.
+    ///
+    /// Doubles the run time.
+    pub fn with_order_sensitivity_check(mut self, check: bool) -> Self {
+        self.options.order_sensitivity_check = check;
+        self
+    }
+
+    /// Catch panics in the tested code
+    ///
+    /// With this enabled, a panic terminates only the current
+    /// iteration, and exploration continues with the next path.
+    /// Panicking paths are recorded in the [`Report`] (see
+    /// [`Report::panicking_paths()`]), and once the exploration
+    /// completes, [`run()`](Self::run) returns
+    /// [`Error::UnexpectedPanics`] if any of the panics were not listed
+    /// in [`with_expected_panics()`](Self::with_expected_panics). This
+    /// allows to find all panics (such as `unwrap()` calls on fallible
+    /// operations) reachable under faults in a single run.
+    ///
+    /// Caught panics are still passed to the panic hook installed
+    /// before the first run, so they are printed by the default hook,
+    /// and reach custom ones.
+    pub fn with_catch_panics(mut self, catch_panics: bool) -> Self {
+        self.options.catch_panics = catch_panics;
+        self
+    }
+
+    /// Give failpoints human readable names
+    ///
+    /// Each pair maps a failpoint name, usually an automatically
+    /// generated `file:line:column` one of an unnamed failpoint, to
+    /// an alias which is used instead of it everywhere in [`Report`].
+    /// Methods which accept failpoint names, such as
+    /// [`Report::blast_radius()`], and [`with_independent_groups()`](Self::with_independent_groups)
+    /// accept both raw names and aliases.
+    ///
+    /// Aliases for failpoints which were never visited are listed in
+    /// [`Report::stale_aliases()`], and a warning is printed for these.
+    pub fn with_aliases(mut self, aliases: &[(&str, &str)]) -> Self {
+        self.options.aliases.extend(
+            aliases
+                .iter()
+                .map(|(raw, alias)| (raw.to_string(), alias.to_string())),
+        );
+        self
+    }
+
+    /// Load failpoint aliases from a file
+    ///
+    /// Each line of the file contains a failpoint name and its alias,
+    /// separated with `=`; empty lines and lines starting with `#` are
+    /// ignored:
+    ///
+    /// ```text
+    /// # storage
+    /// src/storage/wal.rs:412:17 = wal append
+    /// ```
+    ///
+    /// See [`with_aliases()`](Self::with_aliases). If the file cannot be
+    /// read or parsed, [`run()`](Self::run) fails with [`Error::Configuration`].
+    pub fn with_alias_file(mut self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let parsed = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| aliases::parse(&text))
+            .map_err(|err| format!("cannot load aliases from {}: {err}", path.display()));
+        match parsed {
+            Ok(aliases) => self.options.aliases.extend(aliases),
+            Err(err) => {
+                self.config_error.get_or_insert(err);
             }
         }
+        self
+    }
 
-        FAILPOINTS.with_borrow_mut(|state| {
-            let _state = state.take().expect("failpoints state must be initialized");
-        });
+    /// Explore the code under each value of a configuration axis
+    ///
+    /// Instead of duplicating a test for each configuration of the
+    /// tested code, declare axes of configuration, and the code is
+    /// explored under each combination of their values, reading the
+    /// current one with [`axis()`](crate::axis). Each combination is
+    /// explored as if by a separate runner, including
+    /// [`smoke()`](Self::smoke) runs, but results are collected into a
+    /// single [`Report`]. Paths state values of axes they were explored
+    /// with (see [`ExecutionPath::axes`]), and are displayed prefixed
+    /// with them, like `{commit_mode=async} [write] → fsync`.
+    ///
+    /// Iteration budget (see [`with_max_iterations()`](Self::with_max_iterations))
+    /// is distributed among combinations according to
+    /// [`with_axis_budget()`](Self::with_axis_budget). Priority paths
+    /// are explored with the first value of each axis.
+    ///
+    /// ```
+    /// use faine::{Runner, axis, inject_return};
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_axis("commit_mode", &["sync", "async"])
+    ///     .run(|| {
+    ///         inject_return!("write", ());
+    ///         if axis("commit_mode") == "sync" {
+    ///             inject_return!("fsync", ());
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.num_paths(), 5);
+    /// ```
+    ///
+    /// If no values are given, [`run()`](Self::run) fails with
+    /// [`Error::Configuration`].
+    pub fn with_axis(mut self, name: &'static str, values: &[&'static str]) -> Self {
+        if values.is_empty() {
+            self.config_error
+                .get_or_insert(format!("axis {name} has no values"));
+        } else {
+            self.options.axes.push((name, values.to_vec()));
+        }
+        self
+    }
 
-        Ok(())
+    /// Explore values of an environment variable as a configuration axis
+    ///
+    /// Like [`with_axis()`](Self::with_axis), but for code which reads
+    /// an environment variable (for instance, proxy settings or
+    /// feature toggles) at call time: for each iteration, the variable
+    /// is set to the chosen value, or removed if it's `None`, and
+    /// restored after the iteration, even if the code panics. Paths
+    /// show the value like `{$HTTP_PROXY="not a url"}` or
+    /// `{$HTTP_PROXY=unset}`, and [`axis()`](crate::axis) returns
+    /// these labels for the axis named `$HTTP_PROXY`.
+    ///
+    /// The environment is shared by the whole process, so iterations
+    /// of all runners with environment axes are serialized by a
+    /// global lock. Threads which read the environment without going
+    /// through a runner, such as concurrently running tests not using
+    /// faine, may still observe the modified values, so consider using
+    /// variable names which are only read by the tested code.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_return};
+    ///
+    /// fn connect() -> Result<(), String> {
+    ///     inject_return!("connect", Err("timeout".into()));
+    ///     match std::env::var("FAINE_DOC_PROXY") {
+    ///         Ok(proxy) if !proxy.starts_with("http://") => Err("bad proxy".into()),
+    ///         _ => Ok(()),
+    ///     }
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_env_axis("FAINE_DOC_PROXY", &[None, Some("http://127.0.0.1:1"), Some("not a url")])
+    ///     .run(|| {
+    ///         let _ = connect();
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.num_paths(), 6);
+    /// assert!(
+    ///     report
+    ///         .paths()
+    ///         .iter()
+    ///         .any(|path| path.to_string() == r#"{$FAINE_DOC_PROXY="not a url"} connect"#)
+    /// );
+    /// ```
+    ///
+    /// If no values are given, or the variable already has an axis,
+    /// [`run()`](Self::run) fails with [`Error::Configuration`].
+    pub fn with_env_axis(mut self, var: &'static str, values: &[Option<&'static str>]) -> Self {
+        if values.is_empty() {
+            self.config_error
+                .get_or_insert(format!("environment axis {var} has no values"));
+        } else if self
+            .options
+            .env_axes
+            .iter()
+            .any(|(other, _, _)| *other == var)
+        {
+            self.config_error
+                .get_or_insert(format!("environment axis {var} is declared twice"));
+        } else {
+            let (env_axis, axis) = env::declare(var, values);
+            self.options.env_axes.push(env_axis);
+            self.options.axes.push(axis);
+        }
+        self
+    }
+
+    /// Override classification of results of a given type
+    ///
+    /// Results recorded with [`record_result()`](crate::record_result)
+    /// are classified by their [`IntoOutcome`](crate::IntoOutcome)
+    /// implementation by default. The classifier may, for instance,
+    /// distinguish partial successes as [`Outcome::Degraded`](crate::Outcome::Degraded).
+    ///
+    /// ```
+    /// use faine::{Outcome, Runner, inject_return, record_result};
+    ///
+    /// fn load() -> Result<Vec<u32>, ()> {
+    ///     inject_return!("load index", Ok(vec![]));
+    ///     Ok(vec![1, 2, 3])
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_outcome_classifier(|res: &Result<Vec<u32>, ()>| match res {
+    ///         Ok(items) if items.is_empty() => Outcome::Degraded,
+    ///         Ok(_) => Outcome::Success,
+    ///         Err(_) => Outcome::Failure,
+    ///     })
+    ///     .run(|| record_result(&load()))
+    ///     .unwrap();
+    /// assert_eq!(report.absorption()["load index"].degraded, 1);
+    /// ```
+    pub fn with_outcome_classifier<T: 'static>(
+        mut self,
+        classifier: impl Fn(&T) -> Outcome + Send + Sync + 'static,
+    ) -> Self {
+        let (type_id, classifier) = absorption::outcome_classifier(classifier);
+        self.options.outcome_classifiers.insert(type_id, classifier);
+        self
+    }
+
+    /// Limit fraction of absorbed activations of failpoints with a given name prefix
+    ///
+    /// When a failpoint whose name starts with the prefix was
+    /// activated on paths which have mostly succeeded (see
+    /// [`Report::absorption()`]) with ratio above the given one,
+    /// [`run()`](Self::run) fails with [`Error::AbsorptionExceeded`].
+    /// Use this for failpoints which must never be silently ignored,
+    /// with ratio of `0.0`. May be called multiple times, an empty
+    /// prefix matches all failpoints.
+    pub fn with_max_absorption(mut self, prefix: impl Into<String>, ratio: f64) -> Self {
+        self.options.max_absorption.push((prefix.into(), ratio));
+        self
+    }
+
+    /// Select distribution of iteration budget among axis combinations
+    ///
+    /// See [`with_axis()`](Self::with_axis) and [`AxisBudget`].
+    pub fn with_axis_budget(mut self, axis_budget: AxisBudget) -> Self {
+        self.options.axis_budget = axis_budget;
+        self
+    }
+
+    /// Select strategy of choosing branches at failpoints
+    ///
+    /// By default, all paths are explored systematically. With
+    /// [`Strategy::Random`], failpoints are activated randomly, with
+    /// probabilities following the schedule, and the code is run
+    /// repeatedly until the schedule completes. Per phase statistics
+    /// are available through [`Report::phases()`].
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.options.strategy = strategy;
+        self
+    }
+
+    /// Allow failpoints to be activated while unwinding from a panic
+    ///
+    /// By default, failpoints visited during unwinding (for instance,
+    /// in `Drop` implementations run for a panicking path) are always
+    /// skipped, including ones which would fail because of a `fails_if:`
+    /// condition, and listed in [`ExecutionPath::suppressed_during_unwind`],
+    /// because a panic caused by an activated failpoint at that moment
+    /// would be a double panic, which aborts the process and the whole
+    /// exploration with it. Enable this to explore such faults anyway,
+    /// if double panic behavior is what needs to be tested.
+    pub fn with_allow_faults_during_unwind(mut self, allow_faults_during_unwind: bool) -> Self {
+        self.options.allow_faults_during_unwind = allow_faults_during_unwind;
+        self
+    }
+
+    /// Share explored subtrees with other tests through a directory
+    ///
+    /// When many tests exercise the same instrumented code, they often
+    /// explore identical subtrees of paths. With this enabled, the
+    /// runner records each completely explored subtree where all paths
+    /// have passed into the given directory, and skips exploring subtrees
+    /// recorded by other tests (see [`with_dedup_mode()`](Self::with_dedup_mode)).
+    /// Number of skipped subtrees is reported by [`Report::num_dedup_skipped()`].
+    ///
+    /// Subtrees are identified by failpoint decisions leading to them,
+    /// combined with the key set by [`with_scenario_key()`](Self::with_scenario_key),
+    /// which must describe everything else affecting the tested code,
+    /// such as test fixtures. Deduplication is not performed until the
+    /// key is set, and is only supported with [`Strategy::Exhaustive`].
+    ///
+    /// The directory should be specific to a single build, such as
+    /// a single CI run, as records do not account for code changes.
+    /// May also be set with `FAINE_DEDUP_STORE` environment variable,
+    /// see [`without_dedup()`](Self::without_dedup) to opt out.
+    pub fn with_dedup_store(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.dedup_store = Some(path.into());
+        self
+    }
+
+    /// Set what to do with subtrees already explored by other tests
+    ///
+    /// With [`DedupMode::CrossCheck`], such subtrees are explored anyway,
+    /// and ones which produce outcomes different from recorded are counted
+    /// in [`Report::num_dedup_mismatches()`], which is treated as
+    /// non-determinism by [`with_strict_determinism()`](Self::with_strict_determinism).
+    pub fn with_dedup_mode(mut self, mode: DedupMode) -> Self {
+        self.options.dedup_mode = mode;
+        self
+    }
+
+    /// Set key describing the tested scenario for deduplication
+    ///
+    /// See [`with_dedup_store()`](Self::with_dedup_store).
+    pub fn with_scenario_key(mut self, key: impl Hash) -> Self {
+        let mut hasher = FnvHasher::new();
+        key.hash(&mut hasher);
+        self.options.scenario_key = Some(hasher.finish());
+        self
+    }
+
+    /// Disable deduplication with other tests
+    ///
+    /// Overrides [`with_dedup_store()`](Self::with_dedup_store) and
+    /// `FAINE_DEDUP_STORE` environment variable.
+    pub fn without_dedup(mut self) -> Self {
+        self.options.dedup_store = None;
+        self
+    }
+
+    /// Explore only a part of paths, for splitting exploration across processes
+    ///
+    /// Paths are partitioned among `total` shards by a hash of the
+    /// first few failpoint decisions taken on them (see
+    /// [`with_shard_depth()`](Self::with_shard_depth)), so shards
+    /// agree on which one owns each path without communicating, as long
+    /// as they run the same build of the same code. Each shard prunes
+    /// subtrees owned by others, and only reports paths it owns, so
+    /// reports of all shards are disjoint, and together hold the same
+    /// paths as an unsharded run. These may be combined with
+    /// [`Report::merge_shards()`], which also checks that this holds.
+    ///
+    /// Shards are not balanced: paths shorter than the ownership depth
+    /// are executed (but not reported) by every shard, and subtrees
+    /// below ownership prefixes may differ in size by orders of
+    /// magnitude. Iteration and duration budgets apply to each shard
+    /// separately, and a shard which has exhausted its budget leaves
+    /// its subtrees unexplored. With [`Strategy::Random`], shards sample
+    /// paths independently, and only report ones they own. Deduplication
+    /// (see [`with_dedup_store()`](Self::with_dedup_store)) is disabled,
+    /// as partially explored subtrees must not be recorded.
+    ///
+    /// Checks of the whole exploration, such as
+    /// [`with_expected_failpoints()`](Self::with_expected_failpoints),
+    /// and writing of the coverage record are deferred to
+    /// [`Report::merge_shards()`], and the
+    /// [known failures file](Self::with_update_known_failures) is not
+    /// updated by shards.
+    ///
+    /// ```
+    /// use faine::{Report, Runner, inject_return};
+    ///
+    /// fn work() {
+    ///     inject_return!("first", ());
+    ///     inject_return!("second", ());
+    /// }
+    ///
+    /// let reports: Vec<Report> = (0..3)
+    ///     .map(|index| Runner::ignore_env().with_shard(index, 3).run(work).unwrap())
+    ///     .collect();
+    /// let report = Report::merge_shards(reports).unwrap();
+    /// assert_eq!(report.num_paths(), 3);
+    /// ```
+    ///
+    /// May also be set with `FAINE_SHARD` environment variable, as
+    /// `index/total`. If `index` is not less than `total`,
+    /// [`run()`](Self::run) fails with [`Error::Configuration`].
+    pub fn with_shard(mut self, index: usize, total: usize) -> Self {
+        if index < total {
+            self.options.shard = Some((index, total));
+        } else {
+            self.config_error
+                .get_or_insert(format!("shard {index} is out of range of {total} shard(s)"));
+        }
+        self
+    }
+
+    /// Set number of failpoint decisions which decide the shard owning a path
+    ///
+    /// See [`with_shard()`](Self::with_shard). Deeper prefixes make
+    /// for more, smaller units of work, and better balance, but paths
+    /// shorter than the depth are executed by every shard. Default is 8.
+    ///
+    /// If the depth is zero, [`run()`](Self::run) fails with
+    /// [`Error::Configuration`].
+    pub fn with_shard_depth(mut self, depth: usize) -> Self {
+        if depth > 0 {
+            self.options.shard_depth = depth;
+        } else {
+            self.config_error
+                .get_or_insert("shard depth must be positive".to_string());
+        }
+        self
+    }
+
+    /// Decide activations of failpoints with a stateful fault model
+    ///
+    /// Only affects [`Strategy::Random`]. Failpoints with names starting
+    /// with `name_prefix` are activated according to the `model` instead
+    /// of the schedule probability (though never after the schedule
+    /// completes). A single model instance is shared by all matching
+    /// failpoints, so these fail together; register separate models
+    /// for failpoints which should fail independently. When multiple
+    /// models match, the first registered one is used.
+    ///
+    /// Realized failure run lengths are available through
+    /// [`Report::failure_runs()`].
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use faine::{Markov, Runner, Schedule, Strategy};
+    /// let runner = Runner::default()
+    ///     .with_strategy(Strategy::Random {
+    ///         schedule: Schedule::Phases(vec![(Duration::from_secs(60), 0.0)]),
+    ///         seed: 0,
+    ///     })
+    ///     .with_fault_model("backend ", Markov::new(0.01, 0.7));
+    /// ```
+    pub fn with_fault_model(
+        mut self,
+        name_prefix: impl Into<String>,
+        model: impl FaultModel + 'static,
+    ) -> Self {
+        self.options
+            .fault_models
+            .push((name_prefix.into(), Arc::new(Mutex::new(model))));
+        self
+    }
+
+    /// Register a factory of errors of type `E`
+    ///
+    /// Errors injected by [`ResultExt::faine()`](crate::ResultExt::faine)
+    /// and [`ResultExt::faine_io()`](crate::ResultExt::faine_io) into
+    /// results with error type `E` are produced by calling `factory`
+    /// with the failpoint name, instead of being [`std::io::Error`]
+    /// or converted from it. Registering a factory for the same type
+    /// again replaces it.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use faine::{ResultExt, Runner};
+    /// #[derive(Debug)]
+    /// enum StoreError {
+    ///     Io(io::Error),
+    ///     Injected(&'static str),
+    /// }
+    ///
+    /// impl From<io::Error> for StoreError {
+    ///     fn from(err: io::Error) -> Self {
+    ///         Self::Io(err)
+    ///     }
+    /// }
+    ///
+    /// Runner::default()
+    ///     .with_error_factory(StoreError::Injected)
+    ///     .run(|| {
+    ///         let res: Result<(), StoreError> = Ok(()).faine("store");
+    ///         assert!(matches!(res, Ok(()) | Err(StoreError::Injected("store"))));
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn with_error_factory<E: 'static>(
+        mut self,
+        factory: impl Fn(&'static str) -> E + Send + Sync + 'static,
+    ) -> Self {
+        let (type_id, factory) = ext::named_error_factory(factory);
+        self.options.error_factories.insert(type_id, factory);
+        self
+    }
+
+    /// Register a factory of errors produced by [`ResultExt::faine()`](crate::ResultExt::faine) from [`InjectedError`]
+    ///
+    /// Same as [`Runner::with_error_factory()`], but the factory receives
+    /// the [`InjectedError`] itself instead of the failpoint name. It is
+    /// `Copy`, so it may be stored in produced errors as is, and unlike
+    /// names with tags appended, produces no allocations when
+    /// [error tagging](Runner::with_error_tagging) is enabled.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use faine::{InjectedError, ResultExt, Runner};
+    /// #[derive(Debug)]
+    /// enum StoreError {
+    ///     Io(io::Error),
+    ///     Injected(InjectedError),
+    /// }
+    ///
+    /// impl From<io::Error> for StoreError {
+    ///     fn from(err: io::Error) -> Self {
+    ///         Self::Io(err)
+    ///     }
+    /// }
+    ///
+    /// Runner::default()
+    ///     .with_injected_error_factory(StoreError::Injected)
+    ///     .run(|| {
+    ///         let res: Result<(), StoreError> = Ok(()).faine("store");
+    ///         if let Err(StoreError::Injected(err)) = res {
+    ///             assert_eq!(err.failpoint(), "store");
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn with_injected_error_factory<E: 'static>(
+        mut self,
+        factory: impl Fn(InjectedError) -> E + Send + Sync + 'static,
+    ) -> Self {
+        let (type_id, factory) = ext::error_factory(factory);
+        self.options.error_factories.insert(type_id, factory);
+        self
+    }
+
+    /// Set parameters of metadata perturbations for a failpoint
+    ///
+    /// Applies to [`faine::fs::metadata()`](crate::fs::metadata) and
+    /// [`faine::fs::labeled_metadata()`](crate::fs::labeled_metadata)
+    /// failpoints with the given name, others use
+    /// [defaults](crate::fs::MetadataPerturbation::default).
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use faine::Runner;
+    /// use faine::fs::{METADATA, MetadataPerturbation};
+    ///
+    /// let runner = Runner::default().with_metadata_perturbation(
+    ///     METADATA,
+    ///     MetadataPerturbation::default()
+    ///         .with_len_delta(-4096)
+    ///         .with_mtime_offset(Duration::from_secs(1)),
+    /// );
+    /// ```
+    ///
+    /// Available with `fs` feature.
+    #[cfg(feature = "fs")]
+    pub fn with_metadata_perturbation(
+        mut self,
+        name: impl Into<String>,
+        perturbation: crate::fs::MetadataPerturbation,
+    ) -> Self {
+        self.options
+            .metadata_perturbations
+            .insert(name.into(), perturbation);
+        self
+    }
+
+    /// Set rules deciding which file operations survive a crash
+    ///
+    /// Applies to the model returned by
+    /// [`faine::fs::surviving_state()`](crate::fs::surviving_state).
+    /// Default is [`DurabilityModel::Posix`](crate::fs::DurabilityModel::Posix).
+    ///
+    /// Available with `fs` feature.
+    #[cfg(feature = "fs")]
+    pub fn with_durability_model(mut self, model: crate::fs::DurabilityModel) -> Self {
+        self.options.durability_model = model;
+        self
+    }
+
+    /// Provide each iteration with a pristine copy of a directory tree
+    ///
+    /// The tree is copied once, and its working copy is reset before
+    /// each iteration with [`FixtureTree::fresh()`](crate::fs::FixtureTree::fresh),
+    /// using copy-on-write where possible. The working copy path,
+    /// which is the same for all iterations, is returned by
+    /// [`faine::fs::fixture_dir()`](crate::fs::fixture_dir). Time spent
+    /// on resets is reported in [`Report::fixture_timing()`].
+    ///
+    /// ```
+    /// use faine::Runner;
+    /// use faine::fs::fixture_dir;
+    ///
+    /// let source = tempfile::tempdir().unwrap();
+    /// std::fs::write(source.path().join("data"), "pristine").unwrap();
+    ///
+    /// let report = Runner::default()
+    ///     .with_fixture_tree(source.path())
+    ///     .run(|| {
+    ///         let data = fixture_dir().join("data");
+    ///         assert_eq!(std::fs::read_to_string(&data).unwrap(), "pristine");
+    ///         std::fs::write(&data, "modified").unwrap();
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.fixture_timing().unwrap().resets, 1);
+    /// ```
+    ///
+    /// Available with `fs` feature.
+    #[cfg(feature = "fs")]
+    pub fn with_fixture_tree(mut self, src: impl Into<std::path::PathBuf>) -> Self {
+        self.options.fixture_tree = Some(src.into());
+        self
+    }
+
+    /// Explore given paths before any others
+    ///
+    /// Each path is specified as a list of failpoints to activate, in
+    /// order of their visits; all other failpoints visited along it
+    /// are skipped. Paths are followed in the given order at the
+    /// start of exploration, so known dangerous fault combinations
+    /// (such as ones which caused past incidents) are checked first.
+    /// Exploration then continues as usual, without repeating them.
+    ///
+    /// Paths which could not be followed, because listed failpoints
+    /// were not visited, are listed in
+    /// [`Report::unreachable_priority_paths()`] and printed as
+    /// warnings, see also [`with_require_reachable_priority_paths()`](Self::with_require_reachable_priority_paths).
+    /// Handles declared by [`failpoints!`](crate::failpoints) are given
+    /// with [`FailpointHandle::name()`](crate::FailpointHandle::name).
+    ///
+    /// ```
+    /// # use faine::Runner;
+    /// # fn tested_code() {}
+    /// Runner::default()
+    ///     .with_priority_paths(&[&["write block", "fsync"], &["rename"]])
+    ///     .run(|| {
+    ///         tested_code();
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn with_priority_paths(mut self, paths: &[&[&str]]) -> Self {
+        self.options.priority_paths = paths
+            .iter()
+            .map(|path| path.iter().map(|name| name.to_string()).collect())
+            .collect();
+        self
+    }
+
+    /// Make [`run()`](Self::run) fail if some priority paths could not be followed
+    ///
+    /// See [`with_priority_paths()`](Self::with_priority_paths).
+    pub fn with_require_reachable_priority_paths(mut self, require: bool) -> Self {
+        self.options.require_reachable_priority_paths = require;
+        self
+    }
+
+    /// Replay each failing path to check whether its failure is reproducible
+    ///
+    /// Every failing path (see [`ExecutionPath::is_failed()`]) is
+    /// replayed `reruns` more times, forcing the same failpoint
+    /// decisions. Replays run the tested code the same way as
+    /// regular iterations, and count towards
+    /// [`with_max_iterations()`](Self::with_max_iterations) and
+    /// [`with_max_duration()`](Self::with_max_duration) limits. Number
+    /// of replays which failed the same way is reported in
+    /// [`ExecutionPath::reruns`], along with whether the code has
+    /// deviated from the path, which also counts as witnessed
+    /// non-determinism (see [`with_strict_determinism()`](Self::with_strict_determinism)).
+    ///
+    /// Disabled (0) by default. Failures by panic are only replayed
+    /// when panics are caught (see [`with_catch_panics()`](Self::with_catch_panics)).
+    pub fn with_failure_reruns(mut self, reruns: usize) -> Self {
+        self.options.failure_reruns = reruns;
+        self
+    }
+
+    /// Provide each iteration with an empty temporary directory with a stable path
+    ///
+    /// The directory is created once per run under the system
+    /// temporary directory, emptied before each iteration, and
+    /// removed after the run; it's available to the tested code
+    /// through [`tempdir()`](crate::tempdir). As opposed to creating a
+    /// new temporary directory in each iteration, its path is the
+    /// same for all iterations, so code which embeds paths into its
+    /// output, sorts by them or is sensitive to their length does not
+    /// behave differently, which would be reported as
+    /// non-determinism. Removal of files is retried for a while, as
+    /// on Windows they may remain locked after being closed.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_return_io_error, tempdir};
+    ///
+    /// fn save(data: &str) -> std::io::Result<()> {
+    ///     inject_return_io_error!("write");
+    ///     std::fs::write(tempdir().join("data"), data)
+    /// }
+    ///
+    /// Runner::ignore_env()
+    ///     .with_managed_tempdir()
+    ///     .with_strict_determinism(true)
+    ///     .run(|| {
+    ///         assert!(!tempdir().join("data").exists());
+    ///         let _ = save("hello");
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Available with `tempdir` feature.
+    #[cfg(feature = "tempdir")]
+    pub fn with_managed_tempdir(mut self) -> Self {
+        self.options.managed_tempdir = true;
+        self
+    }
+
+    /// Version of the exploration algorithm
+    ///
+    /// The version is bumped whenever a change in this crate may alter
+    /// the set or the order of explored paths for unchanged tested
+    /// code, so golden files of path listings or counts change
+    /// together with it only. See also
+    /// [`with_required_exploration_version()`](Self::with_required_exploration_version).
+    pub fn exploration_version() -> u32 {
+        EXPLORATION_VERSION
+    }
+
+    /// Require a specific version of the exploration algorithm
+    ///
+    /// Meant for tests which compare explored paths with golden files:
+    /// when the algorithm changes, [`run()`](Self::run) fails with
+    /// [`Error::Configuration`] instead of producing a diff, so golden
+    /// files may be reviewed and regenerated deliberately, after
+    /// bumping the required version.
+    ///
+    /// ```
+    /// use faine::Runner;
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_required_exploration_version(Runner::exploration_version())
+    ///     .run(|| {})
+    ///     .unwrap();
+    /// assert_eq!(report.exploration_version(), Runner::exploration_version());
+    /// ```
+    pub fn with_required_exploration_version(mut self, version: u32) -> Self {
+        if version != EXPLORATION_VERSION {
+            self.config_error.get_or_insert(format!(
+                "exploration version {version} is required, but this version of faine implements \
+                 exploration version {EXPLORATION_VERSION}; paths explored, and their order, \
+                 may differ from the ones expected, so review golden files and update the \
+                 required version"
+            ));
+        }
+        self
+    }
+
+    /// Stop exploration gracefully on SIGINT or SIGTERM
+    ///
+    /// While exploring, signal handlers are installed which, instead of
+    /// terminating the process, let the current iteration finish with
+    /// all its cleanup, after which exploration stops, and [`run()`](Self::run)
+    /// returns the [`Report`] marked as incomplete (see
+    /// [`Report::is_interrupted()`]). The second signal terminates the
+    /// process as usual. Previous handlers are restored when exploration
+    /// finishes. [`interrupt()`](crate::interrupt) has the same effect
+    /// as a signal.
+    ///
+    /// Signals are only handled on Unix.
+    ///
+    /// Available with `ctrlc` feature.
+    #[cfg(feature = "ctrlc")]
+    pub fn with_graceful_interrupt(mut self, graceful_interrupt: bool) -> Self {
+        self.options.graceful_interrupt = graceful_interrupt;
+        self
+    }
+
+    /// Raise log verbosity for replays of failing paths
+    ///
+    /// Logging all iterations at debug level is usually too verbose to
+    /// be useful, but failing ones need it. With this set, replays of
+    /// failing paths (see [`with_failure_reruns()`](Self::with_failure_reruns),
+    /// which defaults to a single replay here) are run with
+    /// [`log::max_level()`] raised to at least the given level, which
+    /// is restored after each replay, and [`Reruns::verbose_logs`] is
+    /// set on these paths, so verbose logs are known to come from the
+    /// replays. The logger itself must not filter out records above
+    /// its usual level for this to have effect.
+    ///
+    /// As log level is process-global, verbose replays of all runners
+    /// in the process are serialized, but other tests running in
+    /// parallel may still produce verbose logs during them. Run tests
+    /// which need clean logs with `--test-threads=1` or in a separate
+    /// test binary.
+    ///
+    /// Available with `log` feature.
+    #[cfg(feature = "log")]
+    pub fn with_failure_log_level(mut self, level: log::LevelFilter) -> Self {
+        self.options
+            .failure_logging
+            .get_or_insert_with(Default::default)
+            .level = level;
+        self
+    }
+
+    /// Raise `tracing` verbosity for replays of failing paths
+    ///
+    /// Same as [`with_failure_log_level()`](Self::with_failure_log_level)
+    /// (and uses the level set by it, trace by default), but for a
+    /// `tracing` subscriber whose level filter is wrapped into
+    /// [`tracing_subscriber::reload::Layer`], with its handle given here.
+    ///
+    /// Available with `tracing` feature.
+    #[cfg(feature = "tracing")]
+    pub fn with_tracing_reload_handle<S: 'static>(
+        mut self,
+        handle: tracing_subscriber::reload::Handle<tracing_subscriber::filter::LevelFilter, S>,
+    ) -> Self {
+        self.options
+            .failure_logging
+            .get_or_insert_with(Default::default)
+            .tracing_reload = Some(Arc::new(move |level| {
+            let previous = handle.clone_current()?;
+            handle.reload(level).ok()?;
+            Some(previous)
+        }));
+        self
+    }
+
+    /// Limit failpoint activations under random strategy
+    ///
+    /// At most `max_activations` failpoints are activated within any
+    /// time window of length `per`; further activations are turned
+    /// into skips, and counted as rate-limited in
+    /// [`Report::fault_rates()`]. This keeps fault rate sustained, but
+    /// survivable, regardless of schedule probabilities. The limit
+    /// applies to all failpoints together, see
+    /// [`with_fault_rate_limit_for()`](Self::with_fault_rate_limit_for)
+    /// for limits of specific ones.
+    pub fn with_fault_rate_limit(mut self, max_activations: usize, per: Duration) -> Self {
+        self.options.rate_limits.push(RateLimit {
+            prefix: None,
+            max_activations,
+            per,
+        });
+        self
+    }
+
+    /// Limit activations of failpoints with names starting with a given prefix
+    ///
+    /// Same as [`with_fault_rate_limit()`](Self::with_fault_rate_limit),
+    /// but applies to matching failpoints together. Multiple limits
+    /// may apply to a failpoint, in which case all of them must allow
+    /// an activation.
+    pub fn with_fault_rate_limit_for(
+        mut self,
+        name_prefix: impl Into<String>,
+        max_activations: usize,
+        per: Duration,
+    ) -> Self {
+        self.options.rate_limits.push(RateLimit {
+            prefix: Some(name_prefix.into()),
+            max_activations,
+            per,
+        });
+        self
+    }
+
+    /// Skip failpoints for a number of visits after their activation, under random strategy
+    ///
+    /// After a failpoint with name starting with a given prefix is
+    /// activated, its next `visits` visits within the same iteration
+    /// are skipped regardless of the probability, and counted as cooled
+    /// down in [`Report::fault_rates()`]. This keeps a failpoint in a
+    /// tight loop from dominating activations. As opposed to
+    /// [`with_fault_rate_limit()`](Self::with_fault_rate_limit), the
+    /// cooldown does not depend on time, so runs with the same seed
+    /// activate the same failpoints. If multiple cooldowns apply to a
+    /// failpoint, the longest one is used.
+    pub fn with_cooldown(mut self, name_prefix: impl Into<String>, visits: usize) -> Self {
+        self.options.cooldowns.push(Cooldown {
+            prefix: name_prefix.into(),
+            visits,
+        });
+        self
+    }
+
+    /// Fail if any of the given failpoints is visited
+    ///
+    /// Asserts that certain operations never happen in the tested
+    /// scenario (for instance, that no write path failpoint is visited
+    /// when a database is opened read-only). As faults open code paths
+    /// not taken otherwise, the check covers the whole exploration. A
+    /// visit of a forbidden failpoint is recorded regardless of the
+    /// branch it would take, and forbidden failpoints are never
+    /// activated. Failpoints are selected by name, component or prefix
+    /// (see [`Selector`]), matching either raw names or aliases.
+    ///
+    /// [`run()`](Self::run) fails with [`Error::ForbiddenFailpointReached`]
+    /// if any forbidden failpoint was visited; otherwise the report
+    /// states that there were no forbidden visits. All visits are
+    /// listed in [`Report::forbidden_visits()`].
+    ///
+    /// ```
+    /// # use faine::{Prefix, Runner, inject_return_io_error};
+    /// fn read(read_only: bool) -> std::io::Result<()> {
+    ///     inject_return_io_error!("read block");
+    ///     if !read_only {
+    ///         inject_return_io_error!("write/journal");
+    ///     }
+    ///     Ok(())
+    /// }
+    ///
+    /// let report = Runner::default()
+    ///     .with_forbidden(&[Prefix("write/")])
+    ///     .run(|| { let _ = read(true); })
+    ///     .unwrap();
+    /// assert!(report.forbidden_visits().is_empty());
+    /// ```
+    pub fn with_forbidden<S: Clone + Into<Selector>>(mut self, selectors: &[S]) -> Self {
+        self.options
+            .forbidden
+            .extend(selectors.iter().cloned().map(Into::into));
+        self
+    }
+
+    /// Fail if any of the given failpoints is not visited
+    ///
+    /// Guards against failpoints which were renamed or moved out of
+    /// the tested code, so the test no longer covers them. Meant to be
+    /// used with sets of handles declared by [`failpoints!`](crate::failpoints),
+    /// so newly declared failpoints are checked without listing them
+    /// here, but any [`Selector`] is accepted, and each has to match a
+    /// failpoint visited on at least one path, by raw name or alias.
+    ///
+    /// [`run()`](Self::run) fails with [`Error::ExpectedFailpointsNotVisited`]
+    /// listing selectors which matched nothing.
+    pub fn with_expected_failpoints<S: Clone + Into<Selector>>(mut self, selectors: &[S]) -> Self {
+        self.options
+            .expected_failpoints
+            .extend(selectors.iter().cloned().map(Into::into));
+        self
+    }
+
+    /// Verify expectations declared at failpoint sites
+    ///
+    /// Failpoints defined with `expect: Activated` argument (see
+    /// [`inject_return!`](crate::inject_return)) are expected to be
+    /// activated on some explored path. With this enabled,
+    /// [`run()`](Self::run) fails with [`Error::SiteExpectationsNotMet`]
+    /// if any such failpoint was visited, but never activated, which
+    /// happens when the code path it was placed for is no longer
+    /// exercised in a way which allows its activation. Expectations of
+    /// failpoints which were not visited at all are not known to the
+    /// runner, and can't be checked here; use
+    /// [`Heatmap::unmet_expectations()`](crate::coverage::Heatmap::unmet_expectations)
+    /// to check them across a test suite.
+    ///
+    /// Expectations are recorded in [`Report::site_expectations()`]
+    /// regardless of this setting.
+    pub fn with_enforce_site_expectations(mut self, enforce: bool) -> Self {
+        self.options.enforce_site_expectations = enforce;
+        self
+    }
+
+    /// Audit values injected by override failpoints
+    ///
+    /// With this enabled, values produced by activated
+    /// [`inject_override!`](crate::inject_override) and similar
+    /// macros are classified as a success or a failure with
+    /// [`InjectedOutcome`](crate::InjectedOutcome), and collected in
+    /// [`Report::injection_audit()`]. Failpoints which inject a
+    /// success (such as `Ok` with default data) are likely a mistake
+    /// which silently weakens the test, so they are listed in the
+    /// [`Report`] unless acknowledged at the site with
+    /// `intentional_success: true`. Behavior of the tested code is
+    /// not affected.
+    ///
+    /// Classification needs the type of the injected value to be
+    /// known at the site, which is not always the case, for instance,
+    /// in closures with unannotated arguments, where it then fails to
+    /// compile. So it is only compiled into the macros with `audit`
+    /// feature, which should only be enabled for test builds which
+    /// need the audit.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_override};
+    ///
+    /// fn read_config() -> Result<String, ()> {
+    ///     inject_override!(Ok("debug".to_string()), "read config", Ok(String::new()))
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_audit_injections(true)
+    ///     .run(|| {
+    ///         let _ = read_config();
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.unintended_success_injections(), ["read config"]);
+    /// ```
+    ///
+    /// Available with `audit` feature.
+    #[cfg(feature = "audit")]
+    pub fn with_audit_injections(mut self, audit: bool) -> Self {
+        self.options.audit_injections = audit;
+        self
+    }
+
+    /// Audit values injected by failpoints for purity
+    ///
+    /// An expression producing the injected value which has side
+    /// effects or depends on state (such as
+    /// `Err(make_error(counter.fetch_add(1)))`) makes the same
+    /// activation behave differently on different paths, which
+    /// results in confusing, flickering findings. With this enabled,
+    /// a digest of each value injected by
+    /// [`inject_return!`](crate::inject_return),
+    /// [`inject_override!`](crate::inject_override) and similar
+    /// macros is recorded on the first activation of a failpoint and
+    /// compared with ones produced by later activations. The digest is
+    /// [`InjectedOutcome::digest()`](crate::InjectedOutcome::digest)
+    /// if provided, or [`Debug`] representation of the value; values
+    /// having neither are not audited, and neither are values of
+    /// `_io_error` macros, which are pure by construction. Differences
+    /// are collected in [`Report::impure_injections()`], along with
+    /// paths they were seen on. Behavior of the tested code is not
+    /// affected.
+    ///
+    /// Computing a digest needs the type of the injected value to be
+    /// known at the site, which is not always the case, for instance,
+    /// in closures without annotated return type, where it then fails
+    /// to compile or requires [`Debug`] on the value. So it is only
+    /// compiled into the macros with `audit` feature, which should
+    /// only be enabled for test builds which need the audit.
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use faine::{Runner, inject_return};
+    ///
+    /// static ATTEMPT: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn fetch() -> Result<(), String> {
+    ///     inject_return!("connect", Err(format!("attempt {}", ATTEMPT.fetch_add(1, Ordering::Relaxed))));
+    ///     Ok(())
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_audit_injection_purity(true)
+    ///     .run(|| {
+    ///         let _ = fetch();
+    ///         let _ = fetch();
+    ///     })
+    ///     .unwrap();
+    /// let impure = report.impure_injections();
+    /// assert_eq!(
+    ///     impure[0].to_string(),
+    ///     r#"impure injection at connect: first saw Err("attempt 0"), later saw Err("attempt 1")"#
+    /// );
+    /// ```
+    ///
+    /// Available with `audit` feature.
+    #[cfg(feature = "audit")]
+    pub fn with_audit_injection_purity(mut self, audit: bool) -> Self {
+        self.options.audit_injection_purity = audit;
+        self
+    }
+
+    /// Report exploration events to a sink
+    ///
+    /// The sink receives visits of failpoints and ends of iterations
+    /// and explorations, see [`EventSink`]. This allows, for instance,
+    /// exporting activations into a metrics system when the same
+    /// instrumentation is exercised by a long-running exploration with
+    /// [`Strategy::Random`]. Multiple sinks may be registered, and are
+    /// called in order of registration.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use faine::{Branch, EventSink, Runner, inject_return};
+    ///
+    /// struct Activations(Arc<Mutex<Vec<&'static str>>>);
+    ///
+    /// impl EventSink for Activations {
+    ///     fn on_visit(&mut self, failpoint: &'static str, branch: Branch, _iteration: usize) {
+    ///         if branch == Branch::Activate {
+    ///             self.0.lock().unwrap().push(failpoint);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let activations = Arc::new(Mutex::new(vec![]));
+    /// Runner::ignore_env()
+    ///     .with_event_sink(Activations(activations.clone()))
+    ///     .run(|| {
+    ///         let _ = (|| -> Result<(), ()> {
+    ///             inject_return!("connect", Err(()));
+    ///             Ok(())
+    ///         })();
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(*activations.lock().unwrap(), ["connect"]);
+    /// ```
+    pub fn with_event_sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.options.event_sinks.push(Arc::new(Mutex::new(sink)));
+        self
+    }
+
+    /// Tag injected errors with the path they were injected on
+    ///
+    /// With this enabled, messages of errors produced by `*_io_error`
+    /// macros, [`ResultExt`](crate::ResultExt) and factories registered
+    /// with [`Runner::with_error_factory()`] get a tag such as
+    /// `[faine i=137 p=1f2eab3f]` appended, containing the iteration
+    /// index and a hash of the path up to the activation. When such
+    /// error ends up in application logs, the tag is resolved into
+    /// the path with [`Report::resolve_tag()`]. Factories receive the
+    /// tag appended to the failpoint name; as these names have to be
+    /// `'static`, one is kept for each distinct tag, and tags repeat
+    /// across runs of the same test. Factories registered with
+    /// [`Runner::with_injected_error_factory()`] receive the tag
+    /// within [`InjectedError`](crate::InjectedError) instead, which
+    /// needs no allocations.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_return_io_error};
+    ///
+    /// fn open() -> std::io::Result<()> {
+    ///     inject_return_io_error!("open");
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut logged = String::new();
+    /// let report = Runner::ignore_env()
+    ///     .with_error_tagging(true)
+    ///     .run(|| {
+    ///         if let Err(err) = open() {
+    ///             logged = format!("error: cannot open: {err}");
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// let path = report.resolve_tag(&logged).unwrap();
+    /// assert_eq!(path.activated_failpoints().collect::<Vec<_>>(), ["open"]);
+    /// ```
+    pub fn with_error_tagging(mut self, tagging: bool) -> Self {
+        self.options.error_tagging = tagging;
+        self
+    }
+
+    /// Require checks to execute on every explored path
+    ///
+    /// Checks are marked with [`checked!`](crate::checked) in
+    /// assertion code. If any of the listed checks has not executed
+    /// on some path, for instance because an early return made the
+    /// assertions unreachable, [`run()`](Self::run) fails with
+    /// [`Error::RequiredChecksNotExecuted`] listing such paths.
+    ///
+    /// ```
+    /// use faine::{Error, Runner, checked, inject_return};
+    ///
+    /// fn read() -> Result<String, ()> {
+    ///     inject_return!("read", Err(()));
+    ///     Ok("data".to_string())
+    /// }
+    ///
+    /// let res = Runner::ignore_env()
+    ///     .with_required_checks(&["contents invariant"])
+    ///     .run(|| {
+    ///         let Ok(data) = read() else {
+    ///             return;
+    ///         };
+    ///         checked!("contents invariant");
+    ///         assert_eq!(data, "data");
+    ///     })
+    ///     .into_result();
+    /// assert!(matches!(res, Err(Error::RequiredChecksNotExecuted { .. })));
+    /// ```
+    pub fn with_required_checks(mut self, checks: &[&str]) -> Self {
+        self.options.required_checks = checks.iter().map(ToString::to_string).collect();
+        self
+    }
+
+    /// Continue iterations after checkpoint violations
+    ///
+    /// By default, a violated [`checkpoint()`](crate::checkpoint()) panics.
+    /// With this enabled, the violation is only recorded, and the
+    /// iteration goes on, so its end state is checked as well. After
+    /// exploration, [`run()`](Self::run) fails with
+    /// [`Error::CheckpointViolated`] listing violating paths.
+    ///
+    /// ```
+    /// use faine::{Error, Runner, checkpoint, inject_return};
+    ///
+    /// fn update(value: &mut u32) -> Result<(), ()> {
+    ///     *value += 1;
+    ///     inject_return!("commit", Err(()));
+    ///     Ok(())
+    /// }
+    ///
+    /// let res = Runner::ignore_env()
+    ///     .with_continue_after_checkpoint_violation(true)
+    ///     .run(|| {
+    ///         let mut value = 0;
+    ///         let res = update(&mut value);
+    ///         checkpoint("updated", || res.is_ok());
+    ///         assert_eq!(value, 1);
+    ///     })
+    ///     .into_result();
+    /// let Err(Error::CheckpointViolated { report, .. }) = res else {
+    ///     panic!("checkpoint violation expected");
+    /// };
+    /// assert_eq!(report.checkpoint_violations()["updated"].len(), 1);
+    /// ```
+    pub fn with_continue_after_checkpoint_violation(mut self, continue_iteration: bool) -> Self {
+        self.options.continue_after_checkpoint_violation = continue_iteration;
+        self
+    }
+
+    /// Limit value of a counter on every explored path
+    ///
+    /// Counters are bumped with [`count!`](crate::count) and
+    /// [`count_by!`](crate::count_by), for instance, on each retry, so
+    /// the limit bounds work a single injected fault may cause. If the
+    /// counter exceeds the limit on some path, [`run()`](Self::run)
+    /// fails with [`Error::CounterLimitExceeded`] listing such paths.
+    ///
+    /// ```
+    /// use faine::{Error, Runner, count, inject_return};
+    ///
+    /// fn send() -> Result<(), ()> {
+    ///     inject_return!("send", Err(()));
+    ///     Ok(())
+    /// }
+    ///
+    /// let res = Runner::ignore_env()
+    ///     .with_counter_limit("retries", 1)
+    ///     .run(|| {
+    ///         // retries without a bound on a persistent fault
+    ///         for _ in 0..3 {
+    ///             if send().is_ok() {
+    ///                 break;
+    ///             }
+    ///             count!("retries");
+    ///         }
+    ///     })
+    ///     .into_result();
+    /// assert!(matches!(res, Err(Error::CounterLimitExceeded { .. })));
+    /// ```
+    pub fn with_counter_limit(mut self, counter: &str, limit: u64) -> Self {
+        self.options
+            .counter_limits
+            .push((counter.to_string(), limit));
+        self
+    }
+
+    /// Declare a fault parameter with values to explore
+    ///
+    /// Replacement expressions of `inject_*` macros may read the
+    /// parameter with [`param_f64()`](crate::param_f64) to tune
+    /// severity of injected faults, and each value is explored as a
+    /// separate branch of the failpoint, so a single instrumentation
+    /// site covers a sweep of severities.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_override, param_f64};
+    ///
+    /// fn read_len(len: usize) -> usize {
+    ///     inject_override!(len, "short read", (len as f64 * param_f64("short read fraction")) as usize)
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_parameter("short read fraction", &[0.99, 0.5, 0.0])
+    ///     .run(|| {
+    ///         read_len(100);
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.num_paths(), 4);
+    /// ```
+    ///
+    /// If no values are given, or the parameter is declared more than
+    /// once, [`run()`](Self::run) fails with [`Error::Configuration`].
+    pub fn with_parameter(mut self, name: &'static str, values: &[f64]) -> Self {
+        if values.is_empty() {
+            self.config_error
+                .get_or_insert(format!("parameter {name} has no values"));
+        } else if self
+            .options
+            .parameters
+            .iter()
+            .any(|(declared, _)| *declared == name)
+        {
+            self.config_error
+                .get_or_insert(format!("parameter {name} is declared more than once"));
+        } else {
+            self.options.parameters.push(params::declare(name, values));
+        }
+        self
+    }
+
+    /// Select fraction of the budget given to the first stage of [`run_staged()`](Self::run_staged)
+    ///
+    /// Applies to both [`with_max_iterations()`](Self::with_max_iterations)
+    /// and [`with_max_duration()`](Self::with_max_duration); what's
+    /// left after the first stage is divided equally among
+    /// explorations of the second one. Default is one half.
+    ///
+    /// If the fraction is not within `0.0..=1.0`, [`run()`](Self::run)
+    /// fails with [`Error::Configuration`].
+    pub fn with_stage_split(mut self, outer_fraction: f64) -> Self {
+        if (0.0..=1.0).contains(&outer_fraction) {
+            self.options.stage_split = outer_fraction;
+        } else {
+            self.config_error
+                .get_or_insert(format!("stage split {outer_fraction} is out of range"));
+        }
+        self
+    }
+
+    /// Declare an accumulator shared by all iterations
+    ///
+    /// Tested code may update it with [`accumulate()`](crate::accumulate),
+    /// and its final value is available through
+    /// [`Report::accumulator()`]. Each exploration starts with a clone
+    /// of `initial`.
+    ///
+    /// ```
+    /// use faine::{Runner, accumulate, inject_return};
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .with_accumulator(Vec::<bool>::new())
+    ///     .run(|| {
+    ///         let res = (|| {
+    ///             inject_return!("open", false);
+    ///             true
+    ///         })();
+    ///         accumulate(|results: &mut Vec<bool>| results.push(res));
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(report.accumulator::<Vec<bool>>(), Some(&vec![false, true]));
+    /// ```
+    pub fn with_accumulator<A: Clone + Send + Sync + 'static>(mut self, initial: A) -> Self {
+        self.options.accumulator = Some((
+            Arc::new(move || Box::new(initial.clone())),
+            functions::clone_accumulator::<A>,
+        ));
+        self
+    }
+
+    /// Set number of failing paths to print differences for
+    ///
+    /// When [`run()`](Self::run) fails due to unexpected panics, first
+    /// failing paths (3 by default) are printed to stderr along with
+    /// their differences from the most similar passing paths (see
+    /// [`Report::nearest_passing_path()`]).
+    pub fn with_max_failure_diffs(mut self, max_failure_diffs: usize) -> Self {
+        self.options.max_failure_diffs = max_failure_diffs;
+        self
+    }
+
+    /// Tolerate failures of paths listed in a file, and catch panics
+    ///
+    /// Each line of the file contains a key of a path which is known
+    /// to panic (see [`ExecutionPath::key()`]); empty lines and lines
+    /// starting with `#` are ignored:
+    ///
+    /// ```text
+    /// # fixme: recovery after failed fsync
+    /// write block → [fsync]
+    /// ```
+    ///
+    /// Panics on listed paths do not make [`run()`](Self::run) fail,
+    /// while panics on other paths do, unless their locations are
+    /// declared with [`with_expected_panics()`](Self::with_expected_panics).
+    /// This allows to adopt exploration on code with known issues,
+    /// and to fix them gradually without losing protection against
+    /// new ones. The summary shows numbers of known and new failures,
+    /// and [`Report::fixed_known_failures()`] lists paths which no
+    /// longer fail, so they may be removed from the file, see also
+    /// [`with_update_known_failures()`](Self::with_update_known_failures).
+    ///
+    /// Missing file is treated as empty. If the file cannot be read,
+    /// [`run()`](Self::run) fails with [`Error::Configuration`].
+    pub fn with_known_failures_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.options.catch_panics = true;
+        match known::load(&path) {
+            Ok(known_failures) => self.options.known_failures = known_failures,
+            Err(err) => {
+                self.config_error.get_or_insert(format!(
+                    "cannot load known failures from {}: {err}",
+                    path.display()
+                ));
+            }
+        }
+        self.options.known_failures_file = Some(path);
+        self
+    }
+
+    /// Rewrite known failures file with currently failing paths
+    ///
+    /// Intended for local use: after the run, the file set with
+    /// [`with_known_failures_file()`](Self::with_known_failures_file)
+    /// lists exactly the paths which have panicked or timed out, and
+    /// new failures do not make [`run()`](Self::run) fail.
+    ///
+    /// The file is only rewritten after a complete exploration, as
+    /// otherwise failures of unexplored paths would be lost; it is left
+    /// intact (with a warning) if exploration was stopped by a budget
+    /// or interrupted, and for [sharded](Self::with_shard) runs.
+    pub fn with_update_known_failures(mut self, update: bool) -> Self {
+        self.options.update_known_failures = update;
+        self
+    }
+
+    /// Declare known panic locations, and catch panics
+    ///
+    /// Panics at listed `(file, line)` locations do not make the run
+    /// fail, see [`with_catch_panics()`](Self::with_catch_panics),
+    /// which this implies. File paths are matched by suffix, so both
+    /// crate and workspace relative paths may be used.
+    pub fn with_expected_panics(mut self, locations: &[(&str, u32)]) -> Self {
+        self.options.catch_panics = true;
+        self.options.expected_panics = locations
+            .iter()
+            .map(|(file, line)| (file.to_string(), *line))
+            .collect();
+        self
+    }
+
+    /// Run the provided code with failpoint handling
+    ///
+    /// Runs the provided code, being aware of failpoints defined in it.
+    /// The code will be ran multiple times with different failpoint
+    /// combinations activated.
+    ///
+    /// Returns a [`Report`] describing explored paths, wrapped into
+    /// [`RunOutcome`], which must be examined. Fails when coverage
+    /// record could not be written, or when unexpected panics were
+    /// caught (see [`with_catch_panics()`](Self::with_catch_panics)).
+    /// Otherwise, you can run asserts from the code.
+    ///
+    /// You can treat a code you pass to it as a regular test.
+    ///
+    /// # Concurrency
+    ///
+    /// Runners in different threads, such as tests run in parallel by
+    /// `cargo test`, do not interfere with each other: exploration
+    /// state and configuration of `fail_compat` points are
+    /// thread-local, and [fault parameters](Self::with_parameter)
+    /// belong to the runner which declares them.
+    /// Process-global pieces are either append-only name tables, or
+    /// are serialized behind locks, namely environment modifications
+    /// of [environment axes](Self::with_env_axis) and verbose replays
+    /// of `with_failure_log_level()` (with `log` feature). Interrupts
+    /// (with `ctrlc` feature) are shared and stop all runners which
+    /// handle them.
+    ///
+    /// Active runners are tracked in a process-global registry (see
+    /// [`active_runners()`](crate::active_runners)). A runner using a
+    /// feature which affects the whole process, currently
+    /// [output capture](Self::with_capture_output) which redirects
+    /// process-wide file descriptors, waits there until other runners
+    /// finish, and other runners wait until it finishes. A runner
+    /// which would have to wait for one exploring in the same thread
+    /// (that is, nested in its tested code) fails with
+    /// [`Error::ConflictingRunner`] before exploring anything.
+    pub fn run(self, func: impl FnMut()) -> RunOutcome {
+        RunOutcome::new(self.run_inner(func))
+    }
+
+    fn run_inner(self, mut func: impl FnMut()) -> Result<Report, Error> {
+        if let Some(config_error) = self.config_error {
+            return Err(Error::Configuration(config_error));
+        }
+        let _registration = Registration::register(self.options.exclusive_feature())?;
+        let coverage_dir = self.options.coverage_dir.clone();
+        let test_name = resolve_test_name(&self.options);
+        let reversed_options = self.options.order_sensitivity_check.then(|| {
+            let branch_preference = match self.options.branch_preference {
+                Branch::Activate => Branch::Skip,
+                Branch::Skip => Branch::Activate,
+            };
+            let mut options = self.options.clone().branch_preference(branch_preference);
+            // would overwrite traces of the same paths
+            options.trace_dir = None;
+            options.self_verification = None;
+            options
+        });
+
+        let expected_panics = self.options.expected_panics.clone();
+        let strict_determinism = self.options.strict_determinism;
+        let max_failure_diffs = self.options.max_failure_diffs;
+        let require_reachable_priority_paths = self.options.require_reachable_priority_paths;
+        let known_failures_file = self.options.known_failures_file.clone();
+        let known_failures = self.options.known_failures.clone();
+        let update_known_failures = self.options.update_known_failures;
+        let forbidden_checked = !self.options.forbidden.is_empty();
+        let whole_run_checks = WholeRunChecks {
+            coverage: coverage_dir.map(|dir| (dir, test_name)),
+            expected_failpoints: self.options.expected_failpoints.clone(),
+            enforce_site_expectations: self.options.enforce_site_expectations,
+            max_absorption: self.options.max_absorption.clone(),
+            required_checks: self.options.required_checks.clone(),
+        };
+        let sharded = self.options.shard.is_some();
+        let safety_valve_is_error = self.options.safety_valve_is_error;
+        let continue_after_checkpoint_violation = self.options.continue_after_checkpoint_violation;
+        let counter_limits = self.options.counter_limits.clone();
+        let hygiene_report = self.options.hygiene_report;
+
+        let mut report = match self.options.adaptive_hit_limits {
+            Some((initial, max)) => {
+                hitlimits::explore_adaptive(self.options, &mut func, initial, max)
+            }
+            None => explore(self.options, &mut func),
+        };
+        if hygiene_report {
+            report.compute_hygiene();
+        }
+        for raw in report.stale_aliases() {
+            eprintln!("faine: warning: aliased failpoint `{raw}` was never visited");
+        }
+        if !require_reachable_priority_paths {
+            for path in report.unreachable_priority_paths() {
+                eprintln!(
+                    "faine: warning: priority path {} is unreachable",
+                    path.join(" → ")
+                );
+            }
+        }
+
+        let mut known_failures_updated = false;
+        if let Some(known_failures_file) = &known_failures_file {
+            report.set_known_failures(known_failures);
+            // paths of a partial exploration are not all the failing ones
+            let complete =
+                !report.is_incomplete() && !report.is_interrupted() && report.shard().is_none();
+            if update_known_failures && !complete {
+                eprintln!(
+                    "faine: warning: known failures file {} is not updated, as exploration is partial",
+                    known_failures_file.display()
+                );
+            } else if update_known_failures {
+                let keys: Vec<String> = report
+                    .paths()
+                    .iter()
+                    .filter(|path| path.is_failed())
+                    .map(ExecutionPath::key)
+                    .collect();
+                std::fs::write(
+                    known_failures_file,
+                    known::render(keys.iter().map(String::as_str)),
+                )
+                .map_err(Error::KnownFailures)?;
+                known_failures_updated = true;
+            }
+        }
+
+        if let Some(reversed_options) = reversed_options
+            && !report.is_interrupted()
+        {
+            let reversed_report = explore(reversed_options, &mut func);
+            report.compare_order(&reversed_report);
+        }
+
+        // checked over the whole run in Report::merge_shards()
+        if sharded {
+            report.set_whole_run_checks(whole_run_checks);
+        } else {
+            report = whole_run_checks.check(report)?;
+        }
+
+        if forbidden_checked && let Some((name, path)) = report.forbidden_visits().first() {
+            let mut rendered: Vec<String> = path.iter().map(ToString::to_string).collect();
+            rendered.push(name.to_string());
+            return Err(Error::ForbiddenFailpointReached {
+                name: name.to_string(),
+                path: rendered.join(" → "),
+                report: Box::new(report),
+            });
+        }
+
+        if safety_valve_is_error {
+            let valves: Vec<String> = report
+                .safety_valve_triggered()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            if !valves.is_empty() {
+                return Err(Error::SafetyValveTriggered {
+                    valves,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        if continue_after_checkpoint_violation {
+            let paths: Vec<String> = report
+                .paths()
+                .iter()
+                .enumerate()
+                .filter(|(_, path)| {
+                    path.checkpoints
+                        .iter()
+                        .any(|checkpoint| checkpoint.violation.is_some())
+                })
+                .map(|(i, path)| format!("#{i}: {path}"))
+                .collect();
+            if !paths.is_empty() {
+                return Err(Error::CheckpointViolated {
+                    paths,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        if !counter_limits.is_empty() {
+            let paths: Vec<String> = report
+                .paths()
+                .iter()
+                .enumerate()
+                .filter_map(|(i, path)| {
+                    let exceeded: Vec<String> = path
+                        .counters
+                        .iter()
+                        .filter_map(|(counter, value)| {
+                            let (_, limit) = counter_limits
+                                .iter()
+                                .find(|(limited, _)| limited == counter)?;
+                            (value > limit).then(|| format!("{counter} = {value} > {limit}"))
+                        })
+                        .collect();
+                    (!exceeded.is_empty())
+                        .then(|| format!("#{i}: {path} ({})", exceeded.join(", ")))
+                })
+                .collect();
+            if !paths.is_empty() {
+                return Err(Error::CounterLimitExceeded {
+                    paths,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        if require_reachable_priority_paths && !report.unreachable_priority_paths().is_empty() {
+            return Err(Error::UnreachablePriorityPaths {
+                paths: report.unreachable_priority_paths().to_vec(),
+                report: Box::new(report),
+            });
+        }
+
+        if strict_determinism
+            && (report.non_determinism_witnessed()
+                || report.num_dedup_mismatches() > 0
+                || report
+                    .self_verification()
+                    .is_some_and(|verification| !verification.divergences.is_empty()))
+        {
+            return Err(Error::NonDeterminism {
+                report: Box::new(report),
+            });
+        }
+
+        let unexpected_panics: Vec<String> = report
+            .panicking_paths()
+            .into_iter()
+            .filter(|(_, paths)| {
+                let caught = paths[0].panic.as_ref().expect("path must have panicked");
+                let expected = expected_panics
+                    .iter()
+                    .any(|(file, line)| caught.matches(file, *line));
+                let known = known_failures_updated
+                    || paths.iter().all(|path| report.is_known_failure(path));
+                !expected && !known
+            })
+            .map(|(location, _)| location)
+            .collect();
+        if !unexpected_panics.is_empty() {
+            print_failure_diffs(&report, &unexpected_panics, max_failure_diffs);
+            return Err(Error::UnexpectedPanics {
+                locations: unexpected_panics,
+                report: Box::new(report),
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Run and trace the tested code, with explicit per-iteration state
+    ///
+    /// Same as [`run()`](Self::run), but each iteration is handed a
+    /// fresh clone of `initial`, so state it modifies can't leak into
+    /// subsequent iterations (including after a caught panic), which
+    /// would make them behave differently depending on the order of
+    /// exploration. Use [`with_accumulator()`](Self::with_accumulator)
+    /// to collect data across iterations.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use faine::{Runner, inject_return};
+    ///
+    /// fn cache_get(cache: &mut HashMap<u32, u32>, key: u32) -> Option<u32> {
+    ///     if let Some(value) = cache.get(&key) {
+    ///         return Some(*value);
+    ///     }
+    ///     inject_return!("load", None);
+    ///     Some(*cache.entry(key).or_insert(key * 2))
+    /// }
+    ///
+    /// Runner::ignore_env()
+    ///     .run_with_state(HashMap::new(), |cache| {
+    ///         // cache is always empty here
+    ///         let value = cache_get(cache, 1);
+    ///         assert!(value.is_none() || cache.len() == 1);
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn run_with_state<S: Clone>(self, initial: S, mut func: impl FnMut(&mut S)) -> RunOutcome {
+        self.run(|| {
+            let mut state = initial.clone();
+            func(&mut state);
+        })
+    }
+
+    /// Run the provided code with a fixture prepared for each iteration
+    ///
+    /// `setup` is called before each iteration with the
+    /// [`PlannedPath`] of failpoints the iteration is going to
+    /// activate, so the fixture may be tailored to the injected
+    /// faults, and its result is passed to `func`. The plan is only
+    /// known in some modes, such as [smoke tier](Self::smoke) and
+    /// replays; in general exploration it is unknown, and setup must
+    /// not depend on it, see [`PlannedPath::is_fully_known()`].
+    ///
+    /// ```
+    /// use std::io;
+    /// use faine::{Runner, inject_return_io_error};
+    ///
+    /// fn compact(free_space: u64) -> io::Result<()> {
+    ///     inject_return_io_error!("disk full", kind: io::ErrorKind::StorageFull);
+    ///     assert!(free_space > 0);
+    ///     Ok(())
+    /// }
+    ///
+    /// Runner::smoke()
+    ///     .run_with_fixture(
+    ///         // make the environment coherent with the injected fault
+    ///         |plan| if plan.activates("disk full") { 1 } else { 1024 },
+    ///         |free_space| {
+    ///             let _ = compact(free_space);
+    ///         },
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn run_with_fixture<F>(
+        mut self,
+        mut setup: impl FnMut(&PlannedPath) -> F,
+        mut func: impl FnMut(F),
+    ) -> RunOutcome {
+        self.options.plan_paths = true;
+        self.run(|| {
+            let plan = FAILPOINTS.with_borrow(|state| {
+                state
+                    .as_ref()
+                    .and_then(|state| state.plan.clone())
+                    .unwrap_or_default()
+            });
+            func(setup(&plan));
+        })
+    }
+
+    /// Run the provided code, expecting exploration to find a failure
+    ///
+    /// Inverse of [`run()`](Self::run), for testing that a deliberately
+    /// buggy implementation is caught, and thus the instrumentation
+    /// and checks are effective. Panics are caught (see
+    /// [`with_catch_panics()`](Self::with_catch_panics)), and the run
+    /// succeeds only if at least one path has failed, returning failed
+    /// paths for further assertions. Otherwise, returns
+    /// [`Error::NoFailureFound`]. Unexpected panics do not make it
+    /// fail, but other errors of [`run()`](Self::run) do.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_return};
+    ///
+    /// fn save(storage: &mut Vec<&'static str>) -> Result<(), ()> {
+    ///     storage.clear();
+    ///     inject_return!("write", Err(()));
+    ///     storage.push("data");
+    ///     Ok(())
+    /// }
+    ///
+    /// let found = Runner::ignore_env()
+    ///     .run_expect_failure(|| {
+    ///         let mut storage = vec!["old data"];
+    ///         let res = save(&mut storage);
+    ///         assert!(res.is_ok() || storage == ["old data"], "data lost");
+    ///     })
+    ///     .unwrap();
+    /// assert!(found.paths().iter().all(|path| path.activated("write")));
+    /// ```
+    pub fn run_expect_failure(self, func: impl FnMut()) -> RunOutcome<FoundFailure> {
+        RunOutcome::new(self.run_expect_failure_inner(func))
+    }
+
+    fn run_expect_failure_inner(mut self, func: impl FnMut()) -> Result<FoundFailure, Error> {
+        self.options.catch_panics = true;
+        let report = match self.run_inner(func) {
+            Ok(report) => report,
+            Err(Error::UnexpectedPanics { report, .. }) => *report,
+            Err(err) => return Err(err),
+        };
+        FoundFailure::new(report).map_err(|report| Error::NoFailureFound {
+            paths_explored: report.num_paths(),
+            report,
+        })
+    }
+
+    /// Run a two-stage scenario, exploring the second stage from each state produced by the first one
+    ///
+    /// The first stage, `stage1`, is explored as if by [`run()`](Self::run),
+    /// and returns a state it has produced, for instance, files left
+    /// on disk by an older version of the code. States are
+    /// deduplicated by keys computed by `derive_key`, such as a
+    /// [`digest`](crate::digest) of a directory. Then, for each
+    /// distinct state, the second stage, `stage2`, is explored as a
+    /// separate run, given the state, for instance, to run migration
+    /// by a newer version. Budget is split between stages according to
+    /// [`with_stage_split()`](Self::with_stage_split).
+    ///
+    /// Panics in the second stage are caught (see
+    /// [`with_catch_panics()`](Self::with_catch_panics)), and failure
+    /// of any of its explorations is returned as
+    /// [`Error::StageFailed`], which states the path of the first stage
+    /// which has produced the state, and the failed path of the second
+    /// one. Otherwise, returns a [`StagedReport`](crate::StagedReport)
+    /// with reports of all explorations. Reports of the second stage
+    /// explorations hold the path of the first stage they started from
+    /// (see [`Report::outer_path()`]).
+    ///
+    /// Coverage records, traces, deduplication store and known
+    /// failures file (see [`with_coverage_dir()`](Self::with_coverage_dir),
+    /// [`with_trace_export()`](Self::with_trace_export),
+    /// [`with_dedup_store()`](Self::with_dedup_store) and
+    /// [`with_known_failures_file()`](Self::with_known_failures_file))
+    /// are only used by the first stage, as explorations of the second
+    /// one would overwrite each other's records, and skip subtrees
+    /// recorded by each other.
+    ///
+    /// ```
+    /// use faine::{Runner, inject_return};
+    ///
+    /// fn write_v1(disk: &mut Vec<&'static str>) {
+    ///     disk.push("data");
+    ///     inject_return!("write index", ());
+    ///     disk.push("index");
+    /// }
+    ///
+    /// fn migrate_v2(disk: &[&'static str]) -> Vec<&'static str> {
+    ///     inject_return!("migrate", disk.to_vec());
+    ///     disk.iter().map(|_| "data v2").collect()
+    /// }
+    ///
+    /// let report = Runner::ignore_env()
+    ///     .run_staged(
+    ///         || {
+    ///             let mut disk = vec![];
+    ///             write_v1(&mut disk);
+    ///             disk
+    ///         },
+    ///         |disk| disk.len(),
+    ///         |disk| assert_eq!(migrate_v2(disk).len(), disk.len()),
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(report.stages.len(), 2);
+    /// assert_eq!(report.num_inner_paths(), 4);
+    /// ```
+    pub fn run_staged<S, K: PartialEq + std::fmt::Debug>(
+        self,
+        stage1: impl FnMut() -> S,
+        derive_key: impl FnMut(&S) -> K,
+        stage2: impl FnMut(&S),
+    ) -> RunOutcome<StagedReport<K>> {
+        RunOutcome::new(self.run_staged_inner(stage1, derive_key, stage2))
+    }
+
+    fn run_staged_inner<S, K: PartialEq + std::fmt::Debug>(
+        self,
+        mut stage1: impl FnMut() -> S,
+        mut derive_key: impl FnMut(&S) -> K,
+        mut stage2: impl FnMut(&S),
+    ) -> Result<StagedReport<K>, Error> {
+        if let Some(config_error) = self.config_error {
+            return Err(Error::Configuration(config_error));
+        }
+        let start = Instant::now();
+        let options = self.options;
+        let mut outer_options = options.clone();
+        outer_options.max_iterations =
+            staged::outer_iterations(options.max_iterations, options.stage_split);
+        outer_options.max_duration = options
+            .max_duration
+            .map(|duration| duration.mul_f64(options.stage_split));
+
+        let mut states: Vec<(K, ExecutionPath, S)> = vec![];
+        let outer = Runner {
+            options: outer_options,
+            config_error: None,
+        }
+        .run(|| {
+            let state = stage1();
+            let key = derive_key(&state);
+            if !states.iter().any(|(known, _, _)| *known == key) {
+                states.push((key, staged::current_path(), state));
+            }
+        })
+        .into_result()?;
+
+        let mut inner_options = options.clone();
+        inner_options.catch_panics = true;
+        // all explorations of the second stage would share, and
+        // overwrite, these
+        inner_options.coverage_dir = None;
+        inner_options.trace_dir = None;
+        inner_options.dedup_store = None;
+        inner_options.known_failures_file = None;
+        inner_options.known_failures = Default::default();
+        inner_options.update_known_failures = false;
+        inner_options.max_iterations =
+            staged::inner_iterations(options.max_iterations, outer.num_paths(), states.len());
+        inner_options.max_duration = options
+            .max_duration
+            .map(|duration| duration.saturating_sub(start.elapsed()) / states.len().max(1) as u32);
+        let mut stages = vec![];
+        for (key, outer_path, state) in states {
+            let mut options = inner_options.clone();
+            options.outer_path = Some(outer_path.clone());
+            let result = Runner {
+                options,
+                config_error: None,
+            }
+            .run(|| stage2(&state))
+            .into_result();
+            match result {
+                Ok(report) => stages.push(Stage {
+                    key,
+                    outer_path,
+                    report,
+                }),
+                Err(error) => {
+                    let inner_path = error.report().and_then(|report| {
+                        report
+                            .paths()
+                            .iter()
+                            .find(|path| path.is_failed())
+                            .map(ExecutionPath::key)
+                    });
+                    return Err(Error::StageFailed {
+                        outer_path: outer_path.key(),
+                        state: format!("{key:?}"),
+                        inner_path,
+                        error: Box::new(error),
+                    });
+                }
+            }
+        }
+        Ok(StagedReport { outer, stages })
+    }
+
+    /// Run exploration repeatedly with shuffled orders, looking for order dependent failures
+    ///
+    /// For each seed, the code is explored with the order given by
+    /// [`with_order_seed()`](Self::with_order_seed), with panics
+    /// caught (see [`with_catch_panics()`](Self::with_catch_panics)).
+    /// Once a path fails unexpectedly, the failure is reduced to the
+    /// smallest deterministic reproduction ([`OrderRepro`](crate::OrderRepro)): the
+    /// failing path with as few activated failpoints as possible, and,
+    /// if it passes when explored in isolation, the shortest sequence
+    /// of paths which need to be explored before it. The reproduction
+    /// is returned in [`Error::OrderFuzzFailure`], which displays it as
+    /// a ready to use `Runner` configuration.
+    ///
+    /// As the code is explored many times, it's constructed for each
+    /// exploration by `make`, so that state shared by its iterations
+    /// (which is what may make failures order dependent) starts fresh
+    /// each time. Reduction relies on the code being deterministic
+    /// apart from that state. Failure reruns and self-verification are
+    /// not performed while fuzzing.
+    ///
+    /// ```
+    /// use faine::{Error, Runner, inject_return};
+    ///
+    /// let err = Runner::ignore_env()
+    ///     .fuzz_orders(0..16, || {
+    ///         let mut dirty = false;
+    ///         move || {
+    ///             // failed write is not cleaned up
+    ///             inject_return!("write", dirty = true);
+    ///             inject_return!("read", assert!(!dirty, "stale data"));
+    ///         }
+    ///     })
+    ///     .unwrap_err();
+    /// let Error::OrderFuzzFailure { repro: Some(repro), .. } = err else {
+    ///     panic!("unexpected error {err}");
+    /// };
+    /// assert_eq!(repro.prefix(), [vec!["write".to_string()]]);
+    /// assert_eq!(repro.path(), ["read".to_string()]);
+    /// ```
+    pub fn fuzz_orders<F: FnMut()>(
+        self,
+        seeds: impl IntoIterator<Item = u64>,
+        mut make: impl FnMut() -> F,
+    ) -> Result<(), Error> {
+        if let Some(config_error) = self.config_error {
+            return Err(Error::Configuration(config_error));
+        }
+        let _registration = Registration::register(self.options.exclusive_feature())?;
+        let mut options = self.options;
+        options.catch_panics = true;
+        options.failure_reruns = 0;
+        options.self_verification = None;
+        #[cfg(feature = "log")]
+        {
+            options.failure_logging = None;
+        }
+        for seed in seeds {
+            let mut seeded = options.clone();
+            seeded.order_seed = Some(seed);
+            let report = explore(seeded, &mut make());
+            if let Some(index) = order_fuzz::first_failure(&options, &report) {
+                let repro = order_fuzz::reduce(&options, &report, index, &mut make);
+                return Err(Error::OrderFuzzFailure {
+                    seed,
+                    repro,
+                    report: Box::new(report),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Print failing paths along with their differences from nearest passing paths
+fn print_failure_diffs(report: &Report, locations: &[String], max_failure_diffs: usize) {
+    let failing = report
+        .paths()
+        .iter()
+        .filter(|path| {
+            path.panic
+                .as_ref()
+                .is_some_and(|panic| locations.contains(&panic.location()))
+        })
+        .take(max_failure_diffs);
+    for path in failing {
+        match report.nearest_passing_path(path) {
+            Some((_, diff)) => {
+                eprintln!("faine: failing path: {path}\nfaine: nearest passing path:\n{diff}")
+            }
+            None => eprintln!("faine: failing path: {path}\nfaine: no passing paths"),
+        }
+        if let Some(recent_events) = &path.recent_events {
+            eprint!("faine: recent events:\n{recent_events}");
+        }
+    }
+}
+
+/// Take a sample of exploration progress
+fn sample(
+    state: &State,
+    elapsed: Duration,
+    iterations: usize,
+    combinations: usize,
+) -> TimelineSample {
+    let failpoints: HashSet<_> = state
+        .locations
+        .keys()
+        .filter_map(|label| label.failpoint_name())
+        .collect();
+    TimelineSample {
+        elapsed,
+        iterations,
+        combinations,
+        frontier: state.tree.frontier_size(),
+        failpoints: failpoints.len(),
+    }
+}
+
+/// Checks of the whole exploration, which a shard can't perform alone
+///
+/// Performed by [`Runner::run()`] after exploration, or, for sharded
+/// runs, stored in the report of each shard and performed by
+/// [`Report::merge_shards()`] after merging.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WholeRunChecks {
+    coverage: Option<(PathBuf, String)>,
+    expected_failpoints: Vec<Selector>,
+    enforce_site_expectations: bool,
+    max_absorption: Vec<(String, f64)>,
+    required_checks: Vec<String>,
+}
+
+impl WholeRunChecks {
+    /// Write coverage record and check the report
+    pub(crate) fn check(&self, report: Report) -> Result<Report, Error> {
+        if let Some((coverage_dir, test_name)) = &self.coverage {
+            coverage::write_record(coverage_dir, test_name, &report)
+                .map_err(Error::CoverageRecord)?;
+        }
+
+        if !self.expected_failpoints.is_empty() {
+            let visited = report.failpoints();
+            let failpoints: Vec<String> = self
+                .expected_failpoints
+                .iter()
+                .filter(|selector| !visited.iter().any(|name| report.selects(selector, name)))
+                .map(ToString::to_string)
+                .collect();
+            if !failpoints.is_empty() {
+                return Err(Error::ExpectedFailpointsNotVisited {
+                    failpoints,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        if self.enforce_site_expectations {
+            let failpoints: Vec<String> = report
+                .unmet_site_expectations()
+                .into_iter()
+                .map(
+                    |(failpoint, expect)| match report.failpoint_location(failpoint) {
+                        Some(location) => format!("{failpoint} ({expect}) at {location}"),
+                        None => format!("{failpoint} ({expect})"),
+                    },
+                )
+                .collect();
+            if !failpoints.is_empty() {
+                return Err(Error::SiteExpectationsNotMet {
+                    failpoints,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        if !self.max_absorption.is_empty() {
+            let failpoints: Vec<String> = report
+                .absorption()
+                .into_iter()
+                .filter(|(failpoint, absorption)| {
+                    self.max_absorption.iter().any(|(prefix, ratio)| {
+                        intern::has_prefix(failpoint, prefix) && absorption.ratio() > *ratio
+                    })
+                })
+                .map(|(failpoint, absorption)| {
+                    format!(
+                        "{failpoint} ({} of {} path(s))",
+                        absorption.success, absorption.paths
+                    )
+                })
+                .collect();
+            if !failpoints.is_empty() {
+                return Err(Error::AbsorptionExceeded {
+                    failpoints,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        if !self.required_checks.is_empty() {
+            let paths: Vec<String> = report
+                .paths()
+                .iter()
+                .enumerate()
+                .filter_map(|(i, path)| {
+                    let missing: Vec<&str> = self
+                        .required_checks
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|check| !path.checks.contains(check))
+                        .collect();
+                    (!missing.is_empty())
+                        .then(|| format!("#{i}: {path} (missing {})", missing.join(", ")))
+                })
+                .collect();
+            if !paths.is_empty() {
+                return Err(Error::RequiredChecksNotExecuted {
+                    paths,
+                    report: Box::new(report),
+                });
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Test name from options, or name of the current thread
+fn resolve_test_name(options: &Options) -> String {
+    options.test_name.clone().unwrap_or_else(|| {
+        std::thread::current()
+            .name()
+            .unwrap_or("unknown")
+            .to_string()
+    })
+}
+
+/// Write trace of an iteration, warning on failure
+fn write_trace(
+    trace_dir: &Path,
+    test_name: &str,
+    index: usize,
+    trace: &Trace,
+    path: &ExecutionPath,
+) {
+    let test_name: String = test_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let file = trace_dir.join(format!("{test_name}-{index}.json"));
+    if let Err(err) =
+        std::fs::create_dir_all(trace_dir).and_then(|_| std::fs::write(&file, trace.to_json(path)))
+    {
+        eprintln!(
+            "faine: warning: cannot write trace {}: {err}",
+            file.display()
+        );
+    }
+}
+
+/// Prepare failpoints state for a new iteration
+fn reset_iteration(state: &mut State, phase: Phase, capture_output: bool) {
+    state.iteration = Some(state.iteration.map_or(0, |iteration| iteration + 1));
+    state.phase = phase;
+    state.enabled = true;
+    state.axis_values = state.tree.choose_axes();
+    state.conditions.clear();
+    state.outcome = None;
+    state.result = None;
+    state.suppressed_during_unwind.clear();
+    state.critical_sections = 0;
+    if let Some(buffer) = &mut state.recent_events {
+        buffer.reset();
+    }
+    state.suppressed_by_critical_section.clear();
+    state.checkpoints.clear();
+    state.num_visits = 0;
+    state.num_activations.clear();
+    state.safety_valve.clear();
+    state.checks.fill(0);
+    state.counters.fill(0);
+    state.parameter_bindings.clear();
+    state.rng_draws = 0;
+    state.consumed_triggers.clear();
+    state.forbidden_visits.clear();
+    state.batch_cuts.clear();
+    state.accumulator_snapshot = state
+        .accumulator_cloner
+        .zip(state.accumulator.as_ref())
+        .map(|(clone, accumulator)| clone(accumulator));
+    #[cfg(feature = "fs")]
+    state.durability.clear();
+    #[cfg(feature = "fs")]
+    if let Some(fixture) = &mut state.fixture {
+        fixture
+            .fresh()
+            .unwrap_or_else(|err| panic!("cannot reset fixture tree: {err}"));
+    }
+    #[cfg(feature = "tempdir")]
+    if let Some(tempdir) = state.tempdir {
+        crate::tempdir::clean(tempdir).unwrap_or_else(|err| {
+            panic!(
+                "cannot clean managed temporary directory {}: {err}",
+                tempdir.display()
+            )
+        });
+    }
+    if let Some(watchdog) = &state.watchdog {
+        watchdog.start();
+    }
+    if state.trace.is_some() {
+        state.trace = Some(Trace::start());
+    }
+    if capture_output {
+        state.capture = Some(Capture::start());
+    }
+    if state.plan.is_some() {
+        state.plan = Some(planned_path(state));
+    }
+}
+
+/// Failpoints the current iteration is going to activate, if known
+fn planned_path(state: &mut State) -> PlannedPath {
+    if let Some(path) = state.tree.forced_path() {
+        return PlannedPath::known(path.to_vec());
+    }
+    match state.tree.planned_activations() {
+        Some(labels) => PlannedPath::known(
+            labels
+                .into_iter()
+                .filter_map(|label| label.failpoint_name())
+                .map(|name| state.aliases.apply(name).to_string())
+                .collect(),
+        ),
+        None => PlannedPath::default(),
+    }
+}
+
+/// Move tags of errors injected since the last call into the report,
+/// attributing them to a given path, or drop them if the path is not
+/// reported
+fn take_error_tags(report: &mut Report, path: Option<usize>) {
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state
+            && let Some(tags) = &mut state.error_tags
+        {
+            for tag in tags.drain(..) {
+                if let Some(path) = path {
+                    report.add_error_tag(tag, path);
+                }
+            }
+        }
+    });
+}
+
+/// Run tested code once, returning caught panic
+///
+/// Environment variables of environment axes are set for the duration
+/// of the call, and restored even if the code panics.
+fn run_iteration(func: &mut impl FnMut(), catch_panics: bool) -> Option<CaughtPanic> {
+    let _env = EnvOverride::for_iteration();
+    if catch_panics {
+        panics::catch(func)
+    } else {
+        func();
+        None
+    }
+}
+
+/// Replay a sample of explored paths, comparing visits and outcomes
+fn verify(
+    report: &mut Report,
+    decisions: &[Vec<(Label, Branch)>],
+    sample: f32,
+    seed: u64,
+    func: &mut impl FnMut(),
+    capture_output: bool,
+    catch_panics: bool,
+) -> SelfVerification {
+    let mut verification = SelfVerification {
+        seed,
+        ..Default::default()
+    };
+    for index in verification::sample_paths(decisions.len(), sample, seed) {
+        let path = &report.paths()[index];
+        let replayed = &decisions[index];
+        FAILPOINTS.with_borrow_mut(|state| {
+            let state = state
+                .as_mut()
+                .expect("failpoints state must be initialized");
+            state.tree.start_replay(replayed.clone());
+            reset_iteration(state, Phase::Replaying, capture_output);
+        });
+
+        let panic = run_iteration(func, catch_panics);
+
+        FAILPOINTS.with_borrow_mut(|state| {
+            let state = state
+                .as_mut()
+                .expect("failpoints state must be initialized");
+            let timed_out = state
+                .watchdog
+                .as_ref()
+                .is_some_and(|watchdog| watchdog.finish());
+            let _ = state.capture.take().map(Capture::finish);
+            let mut describe_visit = |visit: Option<Label>| {
+                visit
+                    .and_then(|label| label.failpoint_name())
+                    .map_or("end of iteration".to_string(), |name| {
+                        format!("visit of {}", state.aliases.apply(name))
+                    })
+            };
+            let divergence = match state.tree.finish_replay() {
+                Some((step, observed)) => Some(ReplayDivergence {
+                    path: index,
+                    step,
+                    expected: describe_visit(replayed.get(step).map(|&(label, _)| label)),
+                    observed: describe_visit(observed),
+                }),
+                None if path.timed_out != timed_out
+                    || path.outcome != state.outcome
+                    || path.panic.as_ref().map(CaughtPanic::location)
+                        != panic.as_ref().map(CaughtPanic::location) =>
+                {
+                    Some(ReplayDivergence {
+                        path: index,
+                        step: replayed.len(),
+                        expected: verification::describe_outcome(
+                            path.timed_out,
+                            path.panic.as_ref(),
+                            path.outcome,
+                        ),
+                        observed: verification::describe_outcome(
+                            timed_out,
+                            panic.as_ref(),
+                            state.outcome,
+                        ),
+                    })
+                }
+                None => None,
+            };
+            verification.divergences.extend(divergence);
+        });
+        take_error_tags(report, Some(index));
+        verification.replayed += 1;
+    }
+    verification
+}
+
+/// Perform a single exploration of all paths
+pub(crate) fn explore(options: Options, func: &mut impl FnMut()) -> Report {
+    let mut report = Report::default();
+    let capture_output = options.capture_output;
+    let catch_panics = options.catch_panics;
+    let allow_faults_during_unwind = options.allow_faults_during_unwind;
+    #[cfg(feature = "log")]
+    let failure_logging = options.failure_logging.clone();
+    #[cfg(feature = "log")]
+    let failure_reruns = options
+        .failure_reruns
+        .max(usize::from(failure_logging.is_some()));
+    #[cfg(not(feature = "log"))]
+    let failure_reruns = options.failure_reruns;
+    let self_verification = options.self_verification.map(|sample| {
+        let seed = options.self_verification_seed.unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            (std::time::SystemTime::now(), std::process::id()).hash(&mut hasher);
+            hasher.finish()
+        });
+        (sample, seed)
+    });
+    // decisions taken on each explored path, for self-verification
+    let mut decisions: Vec<Vec<(Label, Branch)>> = Default::default();
+    let error_factories = options.error_factories.clone();
+    let outcome_classifiers = options.outcome_classifiers.clone();
+    let accumulator = options.accumulator.as_ref().map(|(factory, _)| factory());
+    // with sharding, whether the path is owned is only known at its end
+    let accumulator_cloner = options
+        .accumulator
+        .as_ref()
+        .filter(|_| options.shard.is_some())
+        .map(|(_, cloner)| *cloner);
+    #[cfg(feature = "fs")]
+    let metadata_perturbations = options.metadata_perturbations.clone();
+    #[cfg(feature = "fs")]
+    let durability_model = options.durability_model;
+    #[cfg(feature = "fs")]
+    let fixture = options.fixture_tree.as_ref().map(|src| {
+        crate::fs::FixtureTree::prepare(src).unwrap_or_else(|err| {
+            panic!("cannot prepare fixture tree from {}: {err}", src.display())
+        })
+    });
+    let forbidden = options.forbidden.clone();
+    let safety_limits = options.safety_limits;
+    let activation_limits = options.activation_limits.clone();
+    #[cfg(feature = "ctrlc")]
+    let interrupt_guard = options
+        .graceful_interrupt
+        .then(crate::interrupt::InterruptGuard::install);
+    #[cfg(feature = "tempdir")]
+    let tempdir = options.managed_tempdir.then(|| {
+        crate::tempdir::ManagedTempdir::create()
+            .unwrap_or_else(|err| panic!("cannot create managed temporary directory: {err}"))
+    });
+    let audit_injections = options.audit_injections;
+    let audit_injection_purity = options.audit_injection_purity;
+    let error_tagging = options.error_tagging;
+    let plan_paths = options.plan_paths;
+    let recent_events = options.recent_events;
+    let continue_after_checkpoint_violation = options.continue_after_checkpoint_violation;
+    let parameters = options.parameters.clone();
+    let rng_seed = options.rng_seed;
+    let env_axes = options.env_axes.clone();
+    let event_sinks = options.event_sinks.clone();
+    let shard = options.shard;
+    let outer_path = options.outer_path.clone();
+    let test_name = resolve_test_name(&options);
+    let trace_dir = options.trace_dir.clone();
+    let trace_all = options.trace_all;
+    let max_iterations = options.max_iterations;
+    let start = Instant::now();
+    let deadline = options
+        .max_duration
+        .map(|max_duration| start + max_duration);
+    let budget_exhausted = |num_iterations: usize| {
+        max_iterations.is_some_and(|max_iterations| num_iterations >= max_iterations)
+            || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    };
+    let mut num_iterations = 0;
+    let mut sampling = options.timeline_interval.map(|interval| {
+        // sized to avoid reallocations affecting timings
+        let capacity = options.max_duration.map_or(256, |max_duration| {
+            (max_duration.as_nanos() / interval.as_nanos().max(1)).min(4096) as usize + 1
+        });
+        (
+            interval,
+            start + interval,
+            Timeline::with_capacity(capacity),
+        )
+    });
+    let mut diminishing_returns =
+        options
+            .diminishing_returns
+            .map(|(window, min_new_combinations)| {
+                DiminishingReturns::new(window, min_new_combinations)
+            });
+    let mut combinations: HashSet<Vec<&'static str>> = Default::default();
+    report.set_output_captured(capture_output && capture::SUPPORTED);
+    report.set_tier(options.tier);
+    report.set_forbidden_checked(!options.forbidden.is_empty());
+    if let Strategy::Random { schedule, .. } = &options.strategy {
+        report.set_phases(PhaseStats::for_schedule(schedule));
+    }
+
+    FAILPOINTS.with_borrow_mut(|state| {
+        assert!(state.is_none(), "failpoints state double initialization");
+        *state = Some(Box::new(State {
+            enabled: true,
+            aliases: Aliases::new(&options.aliases),
+            watchdog: options
+                .iteration_timeout
+                .map(|timeout| Watchdog::new(timeout, options.enforce_iteration_timeout)),
+            tree: Tree::new(options),
+            conditions: Default::default(),
+            capture: None,
+            trace: trace_dir.is_some().then(Trace::start),
+            locations: Default::default(),
+            outcome: None,
+            requirements: Default::default(),
+            allow_faults_during_unwind,
+            suppressed_during_unwind: Default::default(),
+            critical_sections: 0,
+            suppressed_by_critical_section: Default::default(),
+            triggers: Default::default(),
+            consumed_triggers: Default::default(),
+            error_factories,
+            outcome_classifiers,
+            result: None,
+            forbidden,
+            forbidden_labels: Default::default(),
+            forbidden_visits: Default::default(),
+            batch_cuts: Default::default(),
+            expectations: Default::default(),
+            accumulator,
+            accumulator_cloner,
+            accumulator_snapshot: None,
+            axis_values: Default::default(),
+            env_axes: env_axes.clone(),
+            event_sinks: event_sinks.clone(),
+            iteration: None,
+            phase: Phase::Exploring,
+            safety_limits,
+            activation_limits,
+            num_visits: 0,
+            num_activations: Default::default(),
+            safety_valve: Default::default(),
+            injection_audit: audit_injections.then(Default::default),
+            injection_purity: audit_injection_purity.then(Default::default),
+            error_tags: error_tagging.then(Default::default),
+            checks: Default::default(),
+            counters: Default::default(),
+            parameters,
+            parameter_bindings: Default::default(),
+            rng_seed,
+            rng_draws: 0,
+            plan: plan_paths.then(Default::default),
+            recent_events: (recent_events > 0).then(|| EventBuffer::new(recent_events)),
+            checkpoints: Default::default(),
+            continue_after_checkpoint_violation,
+            #[cfg(feature = "tempdir")]
+            tempdir: tempdir.as_ref().map(crate::tempdir::ManagedTempdir::path),
+            #[cfg(feature = "fs")]
+            metadata_perturbations,
+            #[cfg(feature = "fs")]
+            durability: crate::fs::DurabilityTracker::new(durability_model),
+            #[cfg(feature = "fs")]
+            fixture,
+        }));
+    });
+    let _guard = StateGuard;
+
+    loop {
+        FAILPOINTS.with_borrow_mut(|state| {
+            let state = state
+                .as_mut()
+                .expect("failpoints state must be initialized");
+            state.tree.start();
+            reset_iteration(state, Phase::Exploring, capture_output);
+        });
+
+        let iteration_start = (!event_sinks.is_empty()).then(Instant::now);
+        let panic = run_iteration(func, catch_panics);
+        let iteration_duration = iteration_start.map(|start| start.elapsed());
+        num_iterations += 1;
+
+        let mut status = ExecutionStatus::Continue;
+        let mut replayed = None;
+        let mut foreign = false;
+        let mut path = FAILPOINTS.with_borrow_mut(|state| {
+            let state = state
+                .as_mut()
+                .expect("failpoints state must be initialized");
+            let timed_out = state
+                .watchdog
+                .as_ref()
+                .is_some_and(|watchdog| watchdog.finish());
+            let captured_output = state.capture.take().map(Capture::finish);
+            let panicked = panic.is_some();
+            let path_decisions = state.tree.current_path();
+            let steps = path_decisions
+                .iter()
+                .filter_map(|&(label, branch)| state.step(label, branch))
+                .collect();
+            // paths owned by other shards are only executed to reach owned ones
+            foreign = state.tree.is_foreign();
+            if foreign && let Some(snapshot) = state.accumulator_snapshot.take() {
+                state.accumulator = Some(snapshot);
+            }
+            if self_verification.is_some() && !foreign {
+                decisions.push(path_decisions);
+            }
+            let recent_events = (panic.is_some() || timed_out)
+                .then(|| take_recent_events(state))
+                .flatten();
+            let tree = &mut state.tree;
+            let path = ExecutionPath {
+                steps,
+                timed_out,
+                independence_audit: tree.is_independence_audit(),
+                captured_output,
+                outcome: state.outcome,
+                panic,
+                suppressed_during_unwind: std::mem::take(&mut state.suppressed_during_unwind)
+                    .into_iter()
+                    .map(|name| state.aliases.apply(name))
+                    .collect(),
+                suppressed_by_critical_section: std::mem::take(
+                    &mut state.suppressed_by_critical_section,
+                )
+                .into_iter()
+                .map(|name| state.aliases.apply(name))
+                .collect(),
+                critical_section_held: state.critical_sections > 0,
+                recent_events,
+                reruns: None,
+                axes: state.axis_values.clone(),
+                result: state.result,
+                safety_valve: std::mem::take(&mut state.safety_valve)
+                    .into_iter()
+                    .map(|valve| match valve {
+                        SafetyValve::Activations(name) => {
+                            SafetyValve::Activations(state.aliases.apply(name))
+                        }
+                        valve => valve,
+                    })
+                    .collect(),
+                checks: checks::executed_checks(&state.checks),
+                left_disabled: !state.enabled,
+                counters: counters::counter_values(&state.counters),
+                checkpoints: std::mem::take(&mut state.checkpoints),
+            };
+            let forbidden_visits = std::mem::take(&mut state.forbidden_visits);
+            if !foreign {
+                if let Some(trace_dir) = &trace_dir
+                    && let Some(trace) = &state.trace
+                    && (trace_all || path.is_failed())
+                {
+                    write_trace(trace_dir, &test_name, report.paths().len(), trace, &path);
+                }
+                for (failpoint, path) in forbidden_visits {
+                    report.add_forbidden_visit(failpoint, path);
+                }
+                if let Some(phase) = tree.random_phase() {
+                    report.record_phase(phase, &path);
+                }
+                if failure_reruns > 0 && path.is_failed() {
+                    replayed = Some(tree.current_path());
+                }
+            }
+            let final_label = if panicked {
+                Label::Panicked
+            } else if timed_out {
+                Label::TimedOut
+            } else {
+                Label::Finished
+            };
+            status = tree.finalize(final_label, state.outcome);
+            report.set_num_pruned(tree.num_pruned());
+            if tree.num_truncated_combinations() > 0 {
+                report.set_incomplete(true);
+            }
+            report.set_dedup_stats(tree.num_dedup_skipped(), tree.num_dedup_mismatches());
+            path
+        });
+
+        if let Some(replayed) = replayed {
+            let mut reruns = Reruns::default();
+            while reruns.total < failure_reruns && !budget_exhausted(num_iterations) {
+                FAILPOINTS.with_borrow_mut(|state| {
+                    let state = state
+                        .as_mut()
+                        .expect("failpoints state must be initialized");
+                    state.tree.start_replay(replayed.clone());
+                    reset_iteration(state, Phase::Replaying, capture_output);
+                });
+
+                #[cfg(feature = "log")]
+                let verbose_logging = failure_logging.as_ref().map(|logging| {
+                    reruns.verbose_logs = true;
+                    logging.raise()
+                });
+                let panic = run_iteration(func, catch_panics);
+                #[cfg(feature = "log")]
+                drop(verbose_logging);
+                num_iterations += 1;
+
+                FAILPOINTS.with_borrow_mut(|state| {
+                    let state = state
+                        .as_mut()
+                        .expect("failpoints state must be initialized");
+                    let timed_out = state
+                        .watchdog
+                        .as_ref()
+                        .is_some_and(|watchdog| watchdog.finish());
+                    let _ = state.capture.take().map(Capture::finish);
+                    reruns.non_determinism_witnessed |= state.tree.finish_replay().is_some();
+                    if path.is_same_failure(timed_out, panic.as_ref()) {
+                        reruns.reproduced += 1;
+                    }
+                    reruns.total += 1;
+                });
+            }
+            path.reruns = Some(reruns);
+        }
+
+        let now = (sampling.is_some() || diminishing_returns.is_some()).then(Instant::now);
+        if let Some(now) = now
+            && !foreign
+        {
+            let mut combination: Vec<_> = path.activated_failpoints().collect();
+            combination.sort_unstable();
+            if combinations.insert(combination)
+                && let Some(diminishing_returns) = &mut diminishing_returns
+            {
+                diminishing_returns.record(now);
+            }
+        }
+        if let Some(duration) = iteration_duration {
+            events::dispatch(&event_sinks, |sink| sink.on_iteration_end(&path, duration));
+        }
+        let index = (!foreign).then(|| report.num_paths());
+        if !foreign {
+            report.add_path(path);
+        }
+        take_error_tags(&mut report, index);
+        FAILPOINTS.with_borrow(|state| {
+            if let Some(state) = state {
+                report.set_non_determinism_witnessed(state.tree.non_determinism_witnessed());
+                if let Some((interval, next_sample, timeline)) = &mut sampling
+                    && let Some(now) = now
+                    && now >= *next_sample
+                {
+                    timeline.push(sample(
+                        state,
+                        now - start,
+                        num_iterations,
+                        combinations.len(),
+                    ));
+                    *next_sample = now + *interval;
+                }
+            }
+        });
+
+        match status {
+            ExecutionStatus::Continue => {}
+            ExecutionStatus::Stop => {
+                break;
+            }
+        }
+
+        if budget_exhausted(num_iterations) {
+            report.set_incomplete(true);
+            FAILPOINTS.with_borrow(|state| {
+                if let Some(state) = state {
+                    report.set_unexplored_slots(state.tree.unexplored_slots());
+                }
+            });
+            break;
+        }
+
+        #[cfg(feature = "ctrlc")]
+        if interrupt_guard
+            .as_ref()
+            .is_some_and(crate::interrupt::InterruptGuard::is_interrupted)
+        {
+            report.set_interrupted();
+            break;
+        }
+
+        if let Some(diminishing_returns) = &mut diminishing_returns
+            && let Some(now) = now
+            && diminishing_returns.should_stop(start, now)
+        {
+            report.set_stopped_by_diminishing_returns();
+            break;
+        }
+    }
+
+    if let Some((sample, seed)) = self_verification {
+        let verification = verify(
+            &mut report,
+            &decisions,
+            sample,
+            seed,
+            func,
+            capture_output,
+            catch_panics,
+        );
+        report.set_self_verification(verification);
+        FAILPOINTS.with_borrow(|state| {
+            if let Some(state) = state {
+                report.set_non_determinism_witnessed(state.tree.non_determinism_witnessed());
+            }
+        });
+    }
+
+    FAILPOINTS.with_borrow_mut(|state| {
+        if let Some(state) = state {
+            if let Some((_, _, mut timeline)) = sampling {
+                timeline.push(sample(
+                    state,
+                    start.elapsed(),
+                    num_iterations,
+                    combinations.len(),
+                ));
+                report.set_timeline(timeline);
+            }
+            let mut locations = BTreeMap::new();
+            for (label, location) in &state.locations {
+                if let Some(name) = label.failpoint_name() {
+                    locations
+                        .entry(state.aliases.apply(name))
+                        .or_insert(*location);
+                }
+            }
+            report.set_locations(locations);
+            if let Some(accumulator) = state.accumulator.take() {
+                report.set_accumulator(accumulator);
+            }
+            report.set_site_expectations(
+                state
+                    .expectations
+                    .iter()
+                    .filter_map(|(label, expect)| {
+                        Some((state.aliases.apply(label.failpoint_name()?), *expect))
+                    })
+                    .collect(),
+            );
+            if let Some(audit) = state.injection_audit.take() {
+                let mut aliased: BTreeMap<&'static str, InjectionAudit> = Default::default();
+                for (failpoint, audit) in audit {
+                    let stats = aliased.entry(state.aliases.apply(failpoint)).or_default();
+                    stats.success += audit.success;
+                    stats.failure += audit.failure;
+                    stats.unclassified += audit.unclassified;
+                    stats.intentional_success |= audit.intentional_success;
+                }
+                report.set_injection_audit(aliased);
+            }
+            if let Some(purity) = state.injection_purity.take() {
+                let mut impure = purity.into_impure();
+                for impure in &mut impure {
+                    impure.failpoint = state.aliases.apply(impure.failpoint);
+                }
+                report.set_impure_injections(impure);
+            }
+            report.set_aliases(state.aliases.mapping().clone(), state.aliases.stale());
+            let failure_runs = state
+                .tree
+                .take_failure_runs()
+                .into_iter()
+                .map(|(name, runs)| (state.aliases.apply(name), runs))
+                .collect();
+            report.set_failure_runs(failure_runs);
+            let fault_rates = state
+                .tree
+                .take_fault_rates()
+                .into_iter()
+                .map(|(name, fault_rate)| (state.aliases.apply(name), fault_rate))
+                .collect();
+            report.set_fault_rates(fault_rates);
+            #[cfg(feature = "fs")]
+            if let Some(fixture) = &state.fixture {
+                report.set_fixture_timing(crate::fs::FixtureTiming {
+                    resets: fixture.num_resets(),
+                    reset_duration: fixture.reset_duration(),
+                    total_duration: start.elapsed(),
+                });
+            }
+            report.set_unreachable_priority_paths(state.tree.unreachable_priority_paths().to_vec());
+            if let Some(outer_path) = outer_path {
+                report.set_outer_path(outer_path);
+            }
+            if let Some((index, total)) = shard {
+                report.set_shard(Shard {
+                    index,
+                    total,
+                    discovered: state
+                        .locations
+                        .keys()
+                        .filter_map(|label| Some(state.aliases.apply(label.failpoint_name()?)))
+                        .collect(),
+                });
+            }
+        }
+    });
+
+    report.requalify_names();
+    events::dispatch(&event_sinks, |sink| sink.on_run_end(&report));
+    report
+}
+
+/// Cleans up failpoints state when exploration finishes, or when
+/// the tested code panics
+struct StateGuard;
+
+impl Drop for StateGuard {
+    fn drop(&mut self) {
+        let _ = FAILPOINTS.try_with(|state| {
+            if let Ok(mut state) = state.try_borrow_mut() {
+                state.take();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runner(vars: &'static [(&str, &str)]) -> Runner {
+        Runner::with_env_lookup(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_env_precedence() {
+        let vars = &[
+            ("FAINE_MAX_ITERATIONS", "10"),
+            ("FAINE_MAX_DURATION_MS", "1000"),
+            ("FAINE_BRANCH_PREFERENCE", "skip"),
+            ("FAINE_STRATEGY", "smoke"),
+            ("FAINE_STRICT_DETERMINISM", "true"),
+        ];
+
+        // environment overrides built-in defaults
+        let options = runner(vars).options;
+        assert_eq!(options.max_iterations, Some(10));
+        assert_eq!(options.max_duration, Some(Duration::from_secs(1)));
+        assert_eq!(options.branch_preference, Branch::Skip);
+        assert_eq!(options.tier, Tier::Smoke);
+        assert!(options.strict_determinism);
+
+        // explicit builder calls override environment
+        let options = runner(vars)
+            .with_max_iterations(20)
+            .with_max_duration(Duration::from_secs(2))
+            .with_branch_preference(Branch::Activate)
+            .with_strict_determinism(false)
+            .options;
+        assert_eq!(options.max_iterations, Some(20));
+        assert_eq!(options.max_duration, Some(Duration::from_secs(2)));
+        assert_eq!(options.branch_preference, Branch::Activate);
+        assert!(!options.strict_determinism);
+
+        // unrelated builder calls keep environment defaults
+        let options = runner(vars).with_capture_output(true).options;
+        assert_eq!(options.max_iterations, Some(10));
+    }
+
+    fn replay_code() {
+        (|| crate::inject_return!("a", ()))();
+        (|| crate::inject_return!("b", ()))();
+    }
+
+    #[test]
+    fn test_env_replay() {
+        let vars = &[("FAINE_REPLAY", "a → [b]")];
+
+        // environment replays the single given path
+        let report = runner(vars).run(replay_code).unwrap();
+        assert_eq!(report.num_paths(), 1);
+        assert_eq!(report.paths()[0].key(), "a → [b]");
+
+        // explicit builder calls override environment
+        let report = runner(vars)
+            .with_priority_paths(&[&["a"]])
+            .run(replay_code)
+            .unwrap();
+        assert_eq!(report.paths()[0].key(), "[a] → b");
+        let report = runner(vars)
+            .with_max_iterations(10)
+            .run(replay_code)
+            .unwrap();
+        assert_eq!(report.num_paths(), 4);
+        assert_eq!(report.paths()[0].key(), "a → [b]");
+
+        // filters supplied by the test still apply to the replayed path
+        let err = runner(vars)
+            .with_forbidden(&["b"])
+            .run(replay_code)
+            .unwrap_err();
+        assert!(matches!(err, Error::ForbiddenFailpointReached { .. }));
+        let err = runner(&[("FAINE_REPLAY", "[c]")])
+            .run(replay_code)
+            .unwrap_err();
+        assert!(matches!(err, Error::UnreachablePriorityPaths { .. }));
+    }
+
+    #[test]
+    fn test_env_error() {
+        let err = runner(&[("FAINE_STRATEGY", "exhaustive")])
+            .run(|| {})
+            .unwrap_err();
+        assert!(matches!(err, Error::Configuration(_)));
+        assert_eq!(
+            err.to_string(),
+            "invalid faine configuration: invalid value \"exhaustive\" of FAINE_STRATEGY, expected `full` or `smoke`"
+        );
     }
 }
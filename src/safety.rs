@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Per-iteration limits on injected faults
+///
+/// Code which retries indefinitely on failure would otherwise never
+/// finish an iteration, as each retry visits a failpoint which may
+/// be activated again. When a limit is reached, the safety valve
+/// is triggered: the offending failpoint (or, for the visit limit,
+/// all failpoints) is skipped for the remainder of the iteration,
+/// and this is recorded in [`ExecutionPath::safety_valve`](crate::ExecutionPath::safety_valve).
+///
+/// Limits only stop injection of faults, they do not abort the
+/// iteration. Code which keeps looping when failpoints are skipped,
+/// such as a livelock between two retrying parties, is not stopped
+/// by them; see [`Runner::with_iteration_timeout()`](crate::Runner::with_iteration_timeout)
+/// to detect such hangs.
+///
+/// See [`Runner::with_safety_limits()`](crate::Runner::with_safety_limits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyLimits {
+    /// Maximal number of activations of a single failpoint within an iteration
+    pub max_activations: usize,
+
+    /// Maximal number of failpoint visits within an iteration
+    pub max_visits: usize,
+}
+
+impl Default for SafetyLimits {
+    fn default() -> Self {
+        Self {
+            max_activations: 1000,
+            max_visits: 1_000_000,
+        }
+    }
+}
+
+/// Reason the safety valve was triggered
+///
+/// See [`SafetyLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum SafetyValve {
+    /// Failpoint with a given name has reached activation limit
+    Activations(&'static str),
+
+    /// Failpoint visits have reached the limit
+    Visits,
+}
+
+impl std::fmt::Display for SafetyValve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SafetyValve::Activations(name) => write!(f, "{name} activation limit"),
+            SafetyValve::Visits => write!(f, "visit limit"),
+        }
+    }
+}
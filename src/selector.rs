@@ -0,0 +1,132 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::intern::{self, FailpointHandle};
+
+/// Failpoint selector, either a full name, a component, or a name prefix
+///
+/// Accepted by runner methods which take failpoint names. Plain
+/// strings and [`FailpointHandle`]s convert into [`Selector::Name`],
+/// which may be qualified with a crate (see [`FailpointId`](crate::FailpointId)), [`Component`] converts
+/// into [`Selector::Component`], and [`Prefix`] into [`Selector::Prefix`]:
+///
+/// ```
+/// # use faine::{Component, Runner, Selector};
+/// let runner = Runner::default().with_independent_groups(&[
+///     &[Component("wal").into()],
+///     &[Selector::from("index/flush"), Component("cache").into()],
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Selector {
+    /// Failpoint with the given full name
+    Name(String),
+
+    /// All failpoints of the given component
+    ///
+    /// Only failpoints defined with `component:` and `point:`
+    /// arguments of `inject_*` macros (or interned with
+    /// [`FailpointId::intern_in()`](crate::FailpointId::intern_in))
+    /// belong to components.
+    Component(String),
+
+    /// All failpoints with names starting with the given prefix
+    Prefix(String),
+}
+
+/// Selector of all failpoints of a component
+///
+/// See [`Selector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Component<'a>(pub &'a str);
+
+/// Selector of all failpoints with names starting with a prefix
+///
+/// See [`Selector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Prefix<'a>(pub &'a str);
+
+impl Selector {
+    /// Whether a failpoint with the given name is selected
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Name(selected) => intern::refers_to(selected, name),
+            Self::Component(component) => {
+                intern::component_of(name).is_some_and(|actual| actual == component)
+            }
+            Self::Prefix(prefix) => intern::has_prefix(name, prefix),
+        }
+    }
+}
+
+impl From<&str> for Selector {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for Selector {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+/// Qualified with the crate the handle is declared in, if known
+impl From<FailpointHandle> for Selector {
+    fn from(handle: FailpointHandle) -> Self {
+        match handle.crate_name() {
+            Some(krate) => Self::Name(format!("{krate}::{}", handle.name())),
+            None => Self::Name(handle.name().to_string()),
+        }
+    }
+}
+
+impl From<Component<'_>> for Selector {
+    fn from(component: Component<'_>) -> Self {
+        Self::Component(component.0.to_string())
+    }
+}
+
+impl From<Prefix<'_>> for Selector {
+    fn from(prefix: Prefix<'_>) -> Self {
+        Self::Prefix(prefix.0.to_string())
+    }
+}
+
+impl std::fmt::Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => f.write_str(name),
+            Self::Component(component) => write!(f, "{component}/*"),
+            Self::Prefix(prefix) => write!(f, "{prefix}*"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern::FailpointId;
+
+    #[test]
+    fn test_matches() {
+        FailpointId::intern_in("selector test", "a");
+        FailpointId::intern("selector test/b");
+
+        let component = Selector::from(Component("selector test"));
+        assert!(component.matches("selector test/a"));
+        assert!(!component.matches("selector test/b"));
+        assert!(!component.matches("unknown"));
+        assert_eq!(component.to_string(), "selector test/*");
+
+        let name = Selector::from("selector test/b");
+        assert!(name.matches("selector test/b"));
+        assert!(!name.matches("selector test/a"));
+
+        let prefix = Selector::from(Prefix("selector test/"));
+        assert!(prefix.matches("selector test/a"));
+        assert!(prefix.matches("selector test/b"));
+        assert!(!prefix.matches("selector"));
+        assert_eq!(prefix.to_string(), "selector test/*");
+    }
+}
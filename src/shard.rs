@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+
+use crate::common::{Branch, Label};
+use crate::fnv::FnvHasher;
+
+/// Default number of decisions a shard owner is derived from
+pub const DEFAULT_DEPTH: usize = 8;
+
+/// Part of an exploration split across processes
+///
+/// See [`Runner::with_shard()`](crate::Runner::with_shard).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Shard {
+    /// Index of the shard, from zero
+    pub index: usize,
+
+    /// Total number of shards
+    pub total: usize,
+
+    /// Names of failpoints visited by the shard, including ones only
+    /// visited on paths owned by other shards
+    pub discovered: BTreeSet<&'static str>,
+}
+
+/// Shard which owns paths starting with given decisions
+///
+/// Only names of failpoints are hashed, so the owner is the same in
+/// all processes running the same build.
+pub fn owner(decisions: impl IntoIterator<Item = (Label, Branch)>, total: usize) -> usize {
+    let mut hasher = FnvHasher::new();
+    for (label, branch) in decisions {
+        match label {
+            Label::Failpoint(id) => (0u8, id.qualified_name()).hash(&mut hasher),
+            Label::Site(site) => (1u8, site.name()).hash(&mut hasher),
+            Label::Variant(name, variant) => (2u8, name, variant).hash(&mut hasher),
+            Label::Cut(id, index) => (5u8, id.qualified_name(), index).hash(&mut hasher),
+            Label::AxisCombination(index) => (3u8, index).hash(&mut hasher),
+            Label::Finished | Label::TimedOut | Label::Panicked => 4u8.hash(&mut hasher),
+        }
+        (branch == Branch::Activate).hash(&mut hasher);
+    }
+    (hasher.finish() % total as u64) as usize
+}
+
+/// Parse shard specification in `index/total` form
+pub fn parse(value: &str) -> Option<(usize, usize)> {
+    let (index, total) = value.split_once('/')?;
+    let (index, total) = (index.trim().parse().ok()?, total.trim().parse().ok()?);
+    (index < total).then_some((index, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("0/3"), Some((0, 3)));
+        assert_eq!(parse("2 / 3"), Some((2, 3)));
+        assert_eq!(parse("3/3"), None);
+        assert_eq!(parse("0/0"), None);
+        assert_eq!(parse("1"), None);
+    }
+
+    #[test]
+    fn test_owner() {
+        let decisions = [(Label::Variant("a", "b"), Branch::Activate)];
+        assert_eq!(owner(decisions, 1), 0);
+        assert_eq!(owner(decisions, 7), owner(decisions, 7));
+        assert!(owner(decisions, 7) < 7);
+    }
+}
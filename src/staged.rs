@@ -0,0 +1,109 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::__private::FAILPOINTS;
+use crate::report::{ExecutionPath, Report};
+
+/// Exploration of the second stage from a single state
+///
+/// See [`Runner::run_staged()`](crate::Runner::run_staged).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Stage<K> {
+    /// Key of the state, as derived from it
+    pub key: K,
+
+    /// Path of the first stage which has first produced the state
+    ///
+    /// Only contains steps and values of configuration axes.
+    pub outer_path: ExecutionPath,
+
+    /// Report of the second stage exploration
+    pub report: Report,
+}
+
+/// Report of a staged exploration
+///
+/// See [`Runner::run_staged()`](crate::Runner::run_staged).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct StagedReport<K> {
+    /// Report of the first stage exploration
+    pub outer: Report,
+
+    /// Explorations of the second stage, one per distinct state
+    /// produced by the first one, in order of appearance
+    pub stages: Vec<Stage<K>>,
+}
+
+impl<K> StagedReport<K> {
+    /// Total number of paths explored in the second stage
+    pub fn num_inner_paths(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|stage| stage.report.num_paths())
+            .sum()
+    }
+}
+
+impl<K: std::fmt::Debug> std::fmt::Display for StagedReport<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} outer path(s), {} distinct state(s), {} inner path(s)",
+            self.outer.num_paths(),
+            self.stages.len(),
+            self.num_inner_paths()
+        )?;
+        if f.alternate() {
+            for stage in &self.stages {
+                write!(
+                    f,
+                    "\n  state {:?} from {}: {}",
+                    stage.key, stage.outer_path, stage.report
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Path of the current iteration so far
+pub fn current_path() -> ExecutionPath {
+    FAILPOINTS.with_borrow_mut(|state| {
+        state
+            .as_mut()
+            .expect("failpoints state must be initialized")
+            .current_path()
+    })
+}
+
+/// Share of an iteration budget given to the first stage
+pub fn outer_iterations(max_iterations: Option<usize>, outer_fraction: f64) -> Option<usize> {
+    max_iterations.map(|max| ((max as f64 * outer_fraction) as usize).max(1))
+}
+
+/// Share of an iteration budget given to each exploration of the
+/// second stage, given how much of it the first stage has used
+pub fn inner_iterations(
+    max_iterations: Option<usize>,
+    used: usize,
+    num_stages: usize,
+) -> Option<usize> {
+    max_iterations.map(|max| (max.saturating_sub(used) / num_stages.max(1)).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        assert_eq!(outer_iterations(Some(100), 0.25), Some(25));
+        assert_eq!(outer_iterations(Some(1), 0.25), Some(1));
+        assert_eq!(outer_iterations(None, 0.25), None);
+        assert_eq!(inner_iterations(Some(100), 10, 3), Some(30));
+        assert_eq!(inner_iterations(Some(100), 100, 3), Some(1));
+        assert_eq!(inner_iterations(None, 10, 3), None);
+    }
+}
@@ -0,0 +1,511 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::common::Branch;
+use crate::faultmodel::FaultModel;
+use crate::intern;
+
+pub type SharedFaultModel = Arc<Mutex<dyn FaultModel>>;
+
+fn lock(model: &SharedFaultModel) -> MutexGuard<'_, dyn FaultModel + 'static> {
+    model.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Strategy of choosing branches at failpoints
+///
+/// See [`Runner::with_strategy()`](crate::Runner::with_strategy).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Strategy {
+    /// Systematically explore all paths
+    #[default]
+    Exhaustive,
+
+    /// Activate failpoints randomly, running the code repeatedly
+    /// until the schedule completes
+    ///
+    /// Suitable for soak tests, where a long running workload is
+    /// subjected to faults and the behavior is observed over time.
+    /// Note that each iteration is still recorded into the report as
+    /// an [`ExecutionPath`](crate::ExecutionPath), so memory use grows
+    /// with the number of iterations, which may be limited with
+    /// [`Runner::with_max_iterations()`](crate::Runner::with_max_iterations).
+    /// Paths seen by the runner itself, used to detect non-determinism,
+    /// are only kept up to a fixed limit.
+    Random {
+        /// Schedule of activation probabilities
+        schedule: Schedule,
+
+        /// Seed of the pseudorandom generator
+        seed: u64,
+    },
+}
+
+/// Activation probability schedule for [`Strategy::Random`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Schedule {
+    /// Consecutive phases of given durations, with given failpoint
+    /// activation probabilities (from 0.0 to 1.0)
+    ///
+    /// Phase of an iteration is determined by the time it starts at.
+    Phases(Vec<(Duration, f64)>),
+}
+
+impl Schedule {
+    fn phases(&self) -> &[(Duration, f64)] {
+        match self {
+            Schedule::Phases(phases) => phases,
+        }
+    }
+}
+
+/// Statistics of a single [`Schedule`] phase
+///
+/// See [`Report::phases()`](crate::Report::phases).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct PhaseStats {
+    /// Duration of the phase
+    pub duration: Duration,
+
+    /// Failpoint activation probability during the phase
+    pub probability: f64,
+
+    /// Number of iterations started during the phase
+    pub iterations: usize,
+
+    /// Number of failpoint activations during the phase
+    pub activations: usize,
+
+    /// Number of failed iterations (see [`ExecutionPath::is_failed()`](crate::ExecutionPath::is_failed))
+    pub failures: usize,
+}
+
+impl PhaseStats {
+    pub(crate) fn for_schedule(schedule: &Schedule) -> Vec<Self> {
+        schedule
+            .phases()
+            .iter()
+            .map(|&(duration, probability)| Self {
+                duration,
+                probability,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Limit of failpoint activations in a sliding time window
+///
+/// See [`Runner::with_fault_rate_limit()`](crate::Runner::with_fault_rate_limit).
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    pub prefix: Option<String>,
+    pub max_activations: usize,
+    pub per: Duration,
+}
+
+struct RateLimiter {
+    limit: RateLimit,
+    activations: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    fn applies_to(&self, name: &str) -> bool {
+        self.limit
+            .prefix
+            .as_ref()
+            .is_none_or(|prefix| intern::has_prefix(name, prefix))
+    }
+
+    fn is_exhausted(&mut self, now: Instant) -> bool {
+        while let Some(&oldest) = self.activations.front()
+            && now.duration_since(oldest) >= self.limit.per
+        {
+            self.activations.pop_front();
+        }
+        self.activations.len() >= self.limit.max_activations
+    }
+}
+
+/// Number of visits a failpoint is skipped for after its activation
+///
+/// See [`Runner::with_cooldown()`](crate::Runner::with_cooldown).
+#[derive(Debug, Clone)]
+pub struct Cooldown {
+    pub prefix: String,
+    pub visits: usize,
+}
+
+/// Activation statistics of a single failpoint under random strategy
+///
+/// See [`Report::fault_rates()`](crate::Report::fault_rates).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct FaultRate {
+    /// Number of visits
+    pub visits: usize,
+
+    /// Number of activations
+    pub activations: usize,
+
+    /// Number of activations turned into skips by rate limits
+    ///
+    /// See [`Runner::with_fault_rate_limit()`](crate::Runner::with_fault_rate_limit).
+    pub rate_limited: usize,
+
+    /// Number of activations turned into skips by cooldowns
+    ///
+    /// See [`Runner::with_cooldown()`](crate::Runner::with_cooldown).
+    pub cooled_down: usize,
+
+    /// Achieved activation rate, per second of exploration
+    pub activations_per_second: f64,
+}
+
+/// Pseudorandom generator used by random strategy
+///
+/// Passed to [`FaultModel::next()`](crate::FaultModel::next).
+#[derive(Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Uniformly distributed 64 bit value
+    pub fn next_u64(&mut self) -> u64 {
+        // splitmix64
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed value in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// State of random strategy
+pub struct Random {
+    phases: Vec<(Duration, f64)>,
+    rng: Rng,
+    started: Option<Instant>,
+    phase: Option<usize>,
+    models: Vec<(String, SharedFaultModel)>,
+    model_of: HashMap<&'static str, Option<usize>>,
+    current_runs: HashMap<&'static str, usize>,
+    failure_runs: BTreeMap<&'static str, BTreeMap<usize, usize>>,
+    rate_limiters: Vec<RateLimiter>,
+    cooldowns: Vec<Cooldown>,
+    cooldown_of: HashMap<&'static str, usize>,
+    // remaining visits to skip in the current iteration
+    cooldown_left: HashMap<&'static str, usize>,
+    fault_rates: BTreeMap<&'static str, FaultRate>,
+}
+
+impl Random {
+    pub fn new(
+        schedule: &Schedule,
+        seed: u64,
+        models: &[(String, SharedFaultModel)],
+        rate_limits: &[RateLimit],
+        cooldowns: &[Cooldown],
+    ) -> Self {
+        Self {
+            phases: schedule.phases().to_vec(),
+            rng: Rng::new(seed),
+            started: None,
+            phase: None,
+            models: models.to_vec(),
+            model_of: Default::default(),
+            current_runs: Default::default(),
+            failure_runs: Default::default(),
+            rate_limiters: rate_limits
+                .iter()
+                .map(|limit| RateLimiter {
+                    limit: limit.clone(),
+                    activations: Default::default(),
+                })
+                .collect(),
+            cooldowns: cooldowns.to_vec(),
+            cooldown_of: Default::default(),
+            cooldown_left: Default::default(),
+            fault_rates: Default::default(),
+        }
+    }
+
+    /// Determine phase of a starting iteration
+    pub fn start(&mut self) {
+        self.finish_runs();
+        self.cooldown_left.clear();
+        for (_, model) in &self.models {
+            lock(model).reset();
+        }
+        let elapsed = self.started.get_or_insert_with(Instant::now).elapsed();
+        let mut phase_end = Duration::ZERO;
+        self.phase = self.phases.iter().position(|(duration, _)| {
+            phase_end += *duration;
+            elapsed < phase_end
+        });
+    }
+
+    /// Phase of the current iteration, `None` if the schedule has completed
+    pub fn phase(&self) -> Option<usize> {
+        self.phase
+    }
+
+    /// Check rate limits applying to a failpoint, accounting an activation if none is exhausted
+    fn is_rate_limited(&mut self, name: &str) -> bool {
+        if self.rate_limiters.is_empty() {
+            return false;
+        }
+        let now = Instant::now();
+        let mut exhausted = false;
+        for limiter in &mut self.rate_limiters {
+            exhausted |= limiter.applies_to(name) && limiter.is_exhausted(now);
+        }
+        if !exhausted {
+            for limiter in &mut self.rate_limiters {
+                if limiter.applies_to(name) {
+                    limiter.activations.push_back(now);
+                }
+            }
+        }
+        exhausted
+    }
+
+    /// Check whether a failpoint is cooling down, counting the visit
+    fn is_cooling_down(&mut self, name: &'static str) -> bool {
+        match self.cooldown_left.get_mut(name) {
+            Some(left) if *left > 0 => {
+                *left -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Start cooldown of an activated failpoint, if any applies
+    fn start_cooldown(&mut self, name: &'static str) {
+        if self.cooldowns.is_empty() {
+            return;
+        }
+        let cooldowns = &self.cooldowns;
+        let visits = *self.cooldown_of.entry(name).or_insert_with(|| {
+            cooldowns
+                .iter()
+                .filter(|cooldown| intern::has_prefix(name, &cooldown.prefix))
+                .map(|cooldown| cooldown.visits)
+                .max()
+                .unwrap_or(0)
+        });
+        if visits > 0 {
+            self.cooldown_left.insert(name, visits);
+        }
+    }
+
+    /// Randomly decide whether to activate a failpoint
+    ///
+    /// Failpoints matching registered fault models are decided by these,
+    /// others are activated with probability of the current phase.
+    /// Activations during cooldowns or exceeding rate limits are turned
+    /// into skips. The generator is advanced regardless, so cooldowns do
+    /// not affect decisions at other failpoints.
+    pub fn decide(&mut self, failpoint: Option<&'static str>) -> bool {
+        if self.phase.is_none() {
+            return false;
+        }
+        let model = failpoint.and_then(|name| {
+            let models = &self.models;
+            let model = *self.model_of.entry(name).or_insert_with(|| {
+                models
+                    .iter()
+                    .position(|(prefix, _)| intern::has_prefix(name, prefix))
+            });
+            model.map(|model| (name, model))
+        });
+        let activate = match model {
+            Some((_, model)) => lock(&self.models[model].1).next(&mut self.rng) == Branch::Activate,
+            None => {
+                let probability = self.phase.map_or(0.0, |phase| self.phases[phase].1);
+                self.rng.next_f64() < probability
+            }
+        };
+        let Some(name) = failpoint else {
+            return activate;
+        };
+
+        let cooled_down = self.is_cooling_down(name) && activate;
+        let rate_limited = activate && !cooled_down && self.is_rate_limited(name);
+        let fault_rate = self.fault_rates.entry(name).or_default();
+        fault_rate.visits += 1;
+        if cooled_down {
+            fault_rate.cooled_down += 1;
+        } else if rate_limited {
+            fault_rate.rate_limited += 1;
+        } else if activate {
+            fault_rate.activations += 1;
+        }
+        let activate = activate && !cooled_down && !rate_limited;
+        if activate {
+            self.start_cooldown(name);
+        }
+
+        if model.is_none() {
+            return activate;
+        }
+        let run = self.current_runs.entry(name).or_default();
+        if activate {
+            *run += 1;
+        } else if *run > 0 {
+            *self
+                .failure_runs
+                .entry(name)
+                .or_default()
+                .entry(*run)
+                .or_default() += 1;
+            *run = 0;
+        }
+        activate
+    }
+
+    /// Record failure runs which were interrupted by iteration end
+    fn finish_runs(&mut self) {
+        for (name, run) in &mut self.current_runs {
+            if *run > 0 {
+                *self
+                    .failure_runs
+                    .entry(name)
+                    .or_default()
+                    .entry(*run)
+                    .or_default() += 1;
+                *run = 0;
+            }
+        }
+    }
+
+    /// Failure run length distributions of failpoints decided by fault models
+    pub fn take_failure_runs(&mut self) -> BTreeMap<&'static str, BTreeMap<usize, usize>> {
+        self.finish_runs();
+        // failpoints which became ambiguous were recorded under both names
+        let mut failure_runs: BTreeMap<&'static str, BTreeMap<usize, usize>> = BTreeMap::new();
+        for (name, runs) in std::mem::take(&mut self.failure_runs) {
+            let merged = failure_runs.entry(intern::current_name(name)).or_default();
+            for (run, count) in runs {
+                *merged.entry(run).or_default() += count;
+            }
+        }
+        failure_runs
+    }
+
+    /// Activation statistics of failpoints
+    pub fn take_fault_rates(&mut self) -> BTreeMap<&'static str, FaultRate> {
+        let elapsed = self
+            .started
+            .map_or(0.0, |started| started.elapsed().as_secs_f64());
+        // failpoints which became ambiguous were recorded under both names
+        let mut fault_rates: BTreeMap<&'static str, FaultRate> = BTreeMap::new();
+        for (name, fault_rate) in std::mem::take(&mut self.fault_rates) {
+            let merged = fault_rates.entry(intern::current_name(name)).or_default();
+            merged.visits += fault_rate.visits;
+            merged.activations += fault_rate.activations;
+            merged.rate_limited += fault_rate.rate_limited;
+            merged.cooled_down += fault_rate.cooled_down;
+        }
+        if elapsed > 0.0 {
+            for fault_rate in fault_rates.values_mut() {
+                fault_rate.activations_per_second = fault_rate.activations as f64 / elapsed;
+            }
+        }
+        fault_rates
+    }
+
+    /// Whether the schedule has completed
+    pub fn is_finished(&self) -> bool {
+        self.started.is_some_and(|started| {
+            started.elapsed() >= self.phases.iter().map(|(duration, _)| *duration).sum()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng(1);
+        let mut b = Rng(1);
+        for _ in 0..100 {
+            let value = a.next_f64();
+            assert_eq!(value, b.next_f64());
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_phases() {
+        let schedule = Schedule::Phases(vec![
+            (Duration::from_secs(3600), 0.0),
+            (Duration::from_secs(3600), 1.0),
+        ]);
+        let mut random = Random::new(&schedule, 0, &[], &[], &[]);
+        random.start();
+        assert_eq!(random.phase(), Some(0));
+        assert!(!random.decide(None));
+        assert!(!random.is_finished());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let schedule = Schedule::Phases(vec![(Duration::from_secs(3600), 1.0)]);
+        let limit = RateLimit {
+            prefix: Some("disk ".to_string()),
+            max_activations: 2,
+            per: Duration::from_secs(3600),
+        };
+        let mut random = Random::new(&schedule, 0, &[], &[limit], &[]);
+        random.start();
+        let decisions: Vec<_> = (0..3).map(|_| random.decide(Some("disk write"))).collect();
+        assert_eq!(decisions, [true, true, false]);
+        assert!(random.decide(Some("net send")));
+        let fault_rates = random.take_fault_rates();
+        let disk = &fault_rates["disk write"];
+        assert_eq!(
+            (disk.visits, disk.activations, disk.rate_limited),
+            (3, 2, 1)
+        );
+        assert_eq!(fault_rates["net send"].rate_limited, 0);
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let schedule = Schedule::Phases(vec![(Duration::from_secs(3600), 1.0)]);
+        let cooldown = Cooldown {
+            prefix: "disk ".to_string(),
+            visits: 2,
+        };
+        let mut random = Random::new(&schedule, 0, &[], &[], &[cooldown]);
+        random.start();
+        let decisions: Vec<_> = (0..4).map(|_| random.decide(Some("disk write"))).collect();
+        assert_eq!(decisions, [true, false, false, true]);
+        assert!(random.decide(Some("net send")));
+        assert!(random.decide(Some("net send")));
+        // cooldown does not carry over to the next iteration
+        random.start();
+        assert!(random.decide(Some("disk write")));
+        let fault_rates = random.take_fault_rates();
+        let disk = &fault_rates["disk write"];
+        assert_eq!((disk.visits, disk.activations, disk.cooled_down), (5, 3, 2));
+        assert_eq!(fault_rates["net send"].cooled_down, 0);
+    }
+}
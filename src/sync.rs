@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Failpoint-aware wrappers for [`std::sync`] primitives
+//!
+//! These allow exploring error paths which are otherwise hard to
+//! trigger, such as lock poisoning, channel disconnection and full or
+//! empty bounded queues. Each wrapper defines failpoints named after
+//! the label given to its constructor, and otherwise mirrors API of
+//! the wrapped type.
+//!
+//! Available with `sync` feature.
+
+pub mod mpsc;
+
+use std::fmt;
+use std::sync::{LockResult, Mutex, MutexGuard, PoisonError, TryLockError, TryLockResult};
+
+use crate::__private::visit;
+use crate::common::{Branch, Label};
+use crate::intern::FailpointId;
+
+/// Wrapper for [`std::sync::Mutex`] which injects lock poisoning
+///
+/// When the failpoint in [`lock()`](Self::lock) or
+/// [`try_lock()`](Self::try_lock) is activated, the lock is acquired
+/// normally, but returned wrapped into [`PoisonError`], as if another
+/// thread has panicked while holding it. The mutex itself is not
+/// poisoned, so this only affects a single call.
+///
+/// ```
+/// use faine::sync::FaineMutex;
+///
+/// fn increment(counter: &FaineMutex<u32>) {
+///     let mut guard = match counter.lock() {
+///         Ok(guard) => guard,
+///         Err(poisoned) => poisoned.into_inner(),
+///     };
+///     *guard += 1;
+/// }
+/// ```
+pub struct FaineMutex<T> {
+    label: FailpointId,
+    mutex: Mutex<T>,
+}
+
+impl<T> FaineMutex<T> {
+    /// Construct a mutex
+    ///
+    /// `label` is used as the failpoint name. A plain string does not
+    /// belong to any crate (see [`FailpointId`]); pass a name interned
+    /// with [`intern!`](crate::intern) or a handle declared with
+    /// [`failpoints!`](crate::failpoints) to get a failpoint of the
+    /// calling crate, same as `inject_*` macros define.
+    pub fn new(label: impl Into<FailpointId>, value: T) -> Self {
+        Self {
+            label: label.into(),
+            mutex: Mutex::new(value),
+        }
+    }
+
+    /// See [`Mutex::into_inner()`]
+    pub fn into_inner(self) -> LockResult<T> {
+        self.mutex.into_inner()
+    }
+
+    #[track_caller]
+    fn visit(&self) -> Branch {
+        visit(Label::Failpoint(self.label), crate::ext::caller_location())
+    }
+
+    /// Acquire the mutex, see [`Mutex::lock()`]
+    #[track_caller]
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        match self.visit() {
+            Branch::Activate => Err(PoisonError::new(
+                self.mutex.lock().unwrap_or_else(PoisonError::into_inner),
+            )),
+            Branch::Skip => self.mutex.lock(),
+        }
+    }
+
+    /// Attempt to acquire the mutex, see [`Mutex::try_lock()`]
+    ///
+    /// The failpoint is only visited if the lock was acquired successfully.
+    #[track_caller]
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        match self.mutex.try_lock() {
+            Ok(guard) => match self.visit() {
+                Branch::Activate => Err(TryLockError::Poisoned(PoisonError::new(guard))),
+                Branch::Skip => Ok(guard),
+            },
+            res => res,
+        }
+    }
+
+    /// See [`Mutex::is_poisoned()`]
+    pub fn is_poisoned(&self) -> bool {
+        self.mutex.is_poisoned()
+    }
+
+    /// See [`Mutex::clear_poison()`]
+    pub fn clear_poison(&self) {
+        self.mutex.clear_poison()
+    }
+
+    /// See [`Mutex::get_mut()`]
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.mutex.get_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FaineMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaineMutex")
+            .field("label", &self.label)
+            .field("mutex", &self.mutex)
+            .finish()
+    }
+}
@@ -0,0 +1,317 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Failpoint-aware wrappers for [`std::sync::mpsc`] channels
+//!
+//! Channels created with [`channel()`] define a multi-valued failpoint,
+//! named after the label given to the constructor, with the following
+//! variants:
+//!
+//! - `send disconnected`: [`Sender::send()`] fails as if the receiver
+//!   was dropped, the value is returned back in [`SendError`].
+//! - `recv disconnected`: receiving methods of [`Receiver`] fail as if
+//!   all senders were dropped. Queued values are not lost, and may be
+//!   received later.
+//!
+//! Bounded channels created with [`sync_channel()`] additionally
+//! model backpressure, without changing actual state of the queue:
+//!
+//! - `send full`: [`SyncSender::try_send()`] fails as if the queue was
+//!   full, the value is returned back in [`TrySendError::Full`].
+//! - `recv empty`: [`Receiver::try_recv()`] and
+//!   [`Receiver::recv_timeout()`] fail as if the queue was empty, with
+//!   [`TryRecvError::Empty`] and [`RecvTimeoutError::Timeout`].
+//! - `send would block` and `recv would block`: blocking
+//!   [`SyncSender::send()`] and [`Receiver::recv()`] fail as if they
+//!   had to wait, which is only injected with [`WouldBlock::Error`].
+
+use std::fmt;
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+use crate::__private::visit_variants;
+
+const SEND_DISCONNECTED: &[&str] = &["send disconnected"];
+const SEND_FULL: &[&str] = &["send disconnected", "send full"];
+const SEND_WOULD_BLOCK: &[&str] = &["send disconnected", "send would block"];
+const RECV_DISCONNECTED: &[&str] = &["recv disconnected"];
+const RECV_EMPTY: &[&str] = &["recv disconnected", "recv empty"];
+const RECV_WOULD_BLOCK: &[&str] = &["recv disconnected", "recv would block"];
+
+/// Handling of full or empty queue by blocking operations on bounded channels
+///
+/// Blocking [`SyncSender::send()`] and [`Receiver::recv()`] wait
+/// until the queue has space or a value, which cannot be injected
+/// without actually blocking, so by default they only inject
+/// disconnection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WouldBlock {
+    /// Blocking operations only inject disconnection
+    #[default]
+    Skip,
+
+    /// Blocking operations may also fail as if they had to wait, with
+    /// the only error they can return, that is [`SendError`] and
+    /// [`RecvError`]
+    Error,
+}
+
+/// Create a channel, see [`std::sync::mpsc::channel()`]
+///
+/// `label` is used as the failpoint name.
+///
+/// ```
+/// use faine::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel("results");
+/// tx.send(1).unwrap();
+/// assert_eq!(rx.recv(), Ok(1));
+/// ```
+pub fn channel<T>(label: &'static str) -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    (
+        Sender { label, sender },
+        Receiver {
+            label,
+            receiver,
+            bounded: false,
+            would_block: WouldBlock::Skip,
+        },
+    )
+}
+
+/// Create a bounded channel, see [`std::sync::mpsc::sync_channel()`]
+///
+/// `label` is used as the failpoint name.
+///
+/// ```
+/// use faine::Runner;
+/// use faine::sync::mpsc::{self, TrySendError};
+///
+/// let mut shed = vec![];
+/// Runner::ignore_env()
+///     .run(|| {
+///         let (tx, rx) = mpsc::sync_channel("jobs", 16);
+///         // shed load instead of blocking the producer
+///         shed.push(matches!(tx.try_send(1), Err(TrySendError::Full(_))));
+///         drop(rx);
+///     })
+///     .unwrap();
+/// assert_eq!(shed, [false, true, false]);
+/// ```
+pub fn sync_channel<T>(label: &'static str, bound: usize) -> (SyncSender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::sync_channel(bound);
+    (
+        SyncSender {
+            label,
+            sender,
+            would_block: WouldBlock::Skip,
+        },
+        Receiver {
+            label,
+            receiver,
+            bounded: true,
+            would_block: WouldBlock::Skip,
+        },
+    )
+}
+
+#[track_caller]
+fn visit(label: &'static str, variants: &'static [&'static str]) -> Option<usize> {
+    visit_variants(label, crate::ext::caller_location(), variants)
+}
+
+/// Sending half of a channel, see [`std::sync::mpsc::Sender`]
+pub struct Sender<T> {
+    label: &'static str,
+    sender: mpsc::Sender<T>,
+}
+
+impl<T> Sender<T> {
+    /// Send a value, see [`mpsc::Sender::send()`]
+    #[track_caller]
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if visit(self.label, SEND_DISCONNECTED).is_some() {
+            return Err(SendError(value));
+        }
+        self.sender.send(value)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label,
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sending half of a bounded channel, see [`std::sync::mpsc::SyncSender`]
+pub struct SyncSender<T> {
+    label: &'static str,
+    sender: mpsc::SyncSender<T>,
+    would_block: WouldBlock,
+}
+
+impl<T> SyncSender<T> {
+    /// Set handling of full queue by [`send()`](Self::send)
+    pub fn with_would_block(mut self, would_block: WouldBlock) -> Self {
+        self.would_block = would_block;
+        self
+    }
+
+    /// Send a value, waiting for space in the queue, see [`mpsc::SyncSender::send()`]
+    #[track_caller]
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let variants = match self.would_block {
+            WouldBlock::Skip => SEND_DISCONNECTED,
+            WouldBlock::Error => SEND_WOULD_BLOCK,
+        };
+        if visit(self.label, variants).is_some() {
+            return Err(SendError(value));
+        }
+        self.sender.send(value)
+    }
+
+    /// Attempt to send a value, see [`mpsc::SyncSender::try_send()`]
+    #[track_caller]
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match visit(self.label, SEND_FULL) {
+            Some(0) => Err(TrySendError::Disconnected(value)),
+            Some(_) => Err(TrySendError::Full(value)),
+            None => self.sender.try_send(value),
+        }
+    }
+}
+
+impl<T> Clone for SyncSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label,
+            sender: self.sender.clone(),
+            would_block: self.would_block,
+        }
+    }
+}
+
+impl<T> fmt::Debug for SyncSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncSender")
+            .field("label", &self.label)
+            .field("would_block", &self.would_block)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Receiving half of a channel, see [`std::sync::mpsc::Receiver`]
+pub struct Receiver<T> {
+    label: &'static str,
+    receiver: mpsc::Receiver<T>,
+    bounded: bool,
+    would_block: WouldBlock,
+}
+
+impl<T> Receiver<T> {
+    /// Set handling of empty queue by [`recv()`](Self::recv)
+    ///
+    /// Only has effect for bounded channels.
+    pub fn with_would_block(mut self, would_block: WouldBlock) -> Self {
+        self.would_block = would_block;
+        self
+    }
+
+    /// Receive a value, see [`mpsc::Receiver::recv()`]
+    #[track_caller]
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let variants = match self.would_block {
+            WouldBlock::Error if self.bounded => RECV_WOULD_BLOCK,
+            _ => RECV_DISCONNECTED,
+        };
+        if visit(self.label, variants).is_some() {
+            return Err(RecvError);
+        }
+        self.receiver.recv()
+    }
+
+    /// Attempt to receive a value, see [`mpsc::Receiver::try_recv()`]
+    #[track_caller]
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        match visit(self.label, self.empty_variants()) {
+            Some(0) => Err(TryRecvError::Disconnected),
+            Some(_) => Err(TryRecvError::Empty),
+            None => self.receiver.try_recv(),
+        }
+    }
+
+    /// Receive a value with timeout, see [`mpsc::Receiver::recv_timeout()`]
+    ///
+    /// Injected empty queue is returned immediately, without waiting
+    /// for the timeout.
+    #[track_caller]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match visit(self.label, self.empty_variants()) {
+            Some(0) => Err(RecvTimeoutError::Disconnected),
+            Some(_) => Err(RecvTimeoutError::Timeout),
+            None => self.receiver.recv_timeout(timeout),
+        }
+    }
+
+    fn empty_variants(&self) -> &'static [&'static str] {
+        if self.bounded {
+            RECV_EMPTY
+        } else {
+            RECV_DISCONNECTED
+        }
+    }
+
+    /// Iterate over received values, see [`mpsc::Receiver::iter()`]
+    ///
+    /// Iteration stops when injected disconnection happens.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { receiver: self }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("label", &self.label)
+            .field("would_block", &self.would_block)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Iterator over values received from a [`Receiver`]
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    receiver: &'a Receiver<T>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::__private::FAILPOINTS;
+
+/// Number of attempts to remove a file before giving up
+///
+/// On Windows, files which are still open elsewhere (for instance,
+/// by an antivirus or a search indexer) can't be removed for a while.
+const REMOVE_ATTEMPTS: u32 = 10;
+
+static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// Remove a file or directory, retrying on transient failures
+fn remove_with_retry(path: &Path, remove: impl Fn(&Path) -> io::Result<()>) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match remove(path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(_) if attempt < REMOVE_ATTEMPTS => {
+                thread::sleep(Duration::from_millis(10 << attempt.min(5)));
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Remove all contents of a directory, keeping the directory itself
+pub fn clean(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // symlinks to directories are removed as links, without following
+        if entry.file_type()?.is_dir() {
+            remove_with_retry(&entry.path(), |path| fs::remove_dir_all(path))?;
+        } else {
+            remove_with_retry(&entry.path(), |path| fs::remove_file(path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Temporary directory with a stable path, managed by a runner
+///
+/// See [`Runner::with_managed_tempdir()`](crate::Runner::with_managed_tempdir).
+pub struct ManagedTempdir {
+    path: &'static Path,
+}
+
+impl ManagedTempdir {
+    /// Create a directory with a short name unique within the system temp directory
+    ///
+    /// The path is leaked, so it may be handed out as `&'static Path`
+    /// for the whole run; it is only done once per run.
+    pub fn create() -> io::Result<Self> {
+        let name = format!(
+            "faine-{}-{}",
+            std::process::id(),
+            NEXT_INDEX.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        // a leftover of a crashed run with the same process id
+        remove_with_retry(&path, |path| fs::remove_dir_all(path))?;
+        fs::create_dir(&path)?;
+        Ok(Self {
+            path: Box::leak(PathBuf::into_boxed_path(path)),
+        })
+    }
+
+    pub fn path(&self) -> &'static Path {
+        self.path
+    }
+}
+
+impl Drop for ManagedTempdir {
+    fn drop(&mut self) {
+        if let Err(err) = remove_with_retry(self.path, |path| fs::remove_dir_all(path)) {
+            eprintln!(
+                "faine: warning: cannot remove managed temporary directory {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Temporary directory of the current iteration
+///
+/// Provided by runners with [`Runner::with_managed_tempdir()`]. The
+/// directory is empty at the start of each iteration, but its path
+/// is the same for all iterations of a run, so code which embeds
+/// paths into its output, sorts by them, or is sensitive to their
+/// length behaves the same way in each iteration.
+///
+/// Available with `tempdir` feature.
+///
+/// # Panics
+///
+/// If the runner does not manage a temporary directory, or when
+/// called outside of [`Runner::run()`].
+///
+/// [`Runner::run()`]: crate::Runner::run
+/// [`Runner::with_managed_tempdir()`]: crate::Runner::with_managed_tempdir
+pub fn tempdir() -> &'static Path {
+    FAILPOINTS
+        .with_borrow(|state| state.as_ref().and_then(|state| state.tempdir))
+        .expect("temporary directory is not managed, see Runner::with_managed_tempdir()")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle() {
+        let tempdir = ManagedTempdir::create().unwrap();
+        let path = tempdir.path();
+        assert!(path.is_dir());
+
+        fs::create_dir_all(path.join("a/b")).unwrap();
+        fs::write(path.join("a/b/c"), "").unwrap();
+        fs::write(path.join("d"), "").unwrap();
+        clean(path).unwrap();
+        assert!(path.is_dir());
+        assert_eq!(fs::read_dir(path).unwrap().count(), 0);
+
+        drop(tempdir);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Helpers for writing tests with `faine`
+//!
+//! These encode assertions commonly needed when testing instrumented
+//! code. Available with `testsupport` feature.
+
+use crate::report::Report;
+use crate::runner::Runner;
+
+/// Run the code and print exploration report
+///
+/// The report is printed to stderr in verbose form, listing all
+/// explored paths.
+pub fn run_and_summarize(runner: Runner, func: impl FnMut()) -> Report {
+    let report = runner.run(func).expect("exploration failed");
+    eprintln!("{report:#}");
+    report
+}
+
+/// Run the code and assert that at least `n_paths` paths were explored
+///
+/// Protects against silently broken instrumentation, such as failpoints
+/// compiled out because of a missing feature.
+#[track_caller]
+pub fn assert_explores_at_least(n_paths: usize, runner: Runner, func: impl FnMut()) -> Report {
+    let report = runner.run(func).expect("exploration failed");
+    assert!(
+        report.num_paths() >= n_paths,
+        "expected at least {n_paths} explored path(s), got {}\n{report:#}",
+        report.num_paths()
+    );
+    report
+}
+
+/// Run the code and assert that every visited failpoint was activated at least once
+///
+/// With exhaustive exploration this always holds, but it may be
+/// violated when exploration is restricted.
+#[track_caller]
+pub fn assert_all_failpoints_activated(runner: Runner, func: impl FnMut()) -> Report {
+    let report = runner.run(func).expect("exploration failed");
+    let activated = report.activated_failpoints();
+    let never_activated: Vec<_> = report
+        .failpoints()
+        .into_iter()
+        .filter(|failpoint| !activated.contains(failpoint))
+        .collect();
+    assert!(
+        never_activated.is_empty(),
+        "failpoint(s) never activated: {never_activated:?}\n{report:#}"
+    );
+    report
+}
@@ -0,0 +1,186 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Exploration progress at a point in time
+///
+/// See [`Runner::with_timeline()`](crate::Runner::with_timeline).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TimelineSample {
+    /// Time since the start of exploration
+    pub elapsed: Duration,
+
+    /// Number of iterations performed
+    pub iterations: usize,
+
+    /// Number of distinct fault combinations explored
+    pub combinations: usize,
+
+    /// Number of discovered, but not yet explored branches
+    pub frontier: usize,
+
+    /// Number of distinct failpoints discovered
+    pub failpoints: usize,
+}
+
+/// Exploration progress over time
+///
+/// Samples are taken at the interval set with
+/// [`Runner::with_timeline()`](crate::Runner::with_timeline), and at
+/// the end of exploration. Plain [`Display`](std::fmt::Display)
+/// renders a sparkline of new fault combinations explored per
+/// sample, which shows whether continuing is worth it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    samples: Vec<TimelineSample>,
+}
+
+impl Timeline {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            samples: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn push(&mut self, sample: TimelineSample) {
+        self.samples.push(sample);
+    }
+
+    /// Collected samples, in chronological order
+    pub fn samples(&self) -> &[TimelineSample] {
+        &self.samples
+    }
+
+    /// Render samples as JSON, for plotting
+    pub fn to_json(&self) -> String {
+        let samples: Vec<String> = self
+            .samples
+            .iter()
+            .map(|sample| {
+                format!(
+                    "{{\"elapsed_ms\":{},\"iterations\":{},\"combinations\":{},\"frontier\":{},\"failpoints\":{}}}",
+                    sample.elapsed.as_millis(),
+                    sample.iterations,
+                    sample.combinations,
+                    sample.frontier,
+                    sample.failpoints
+                )
+            })
+            .collect();
+        format!("{{\"samples\":[{}]}}", samples.join(","))
+    }
+}
+
+impl std::fmt::Display for Timeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let mut previous = 0;
+        let increments: Vec<usize> = self
+            .samples
+            .iter()
+            .map(|sample| {
+                let increment = sample.combinations - previous;
+                previous = sample.combinations;
+                increment
+            })
+            .collect();
+        let max = increments.iter().copied().max().unwrap_or(0).max(1);
+        for increment in &increments {
+            write!(f, "{}", BARS[increment * (BARS.len() - 1) / max])?;
+        }
+        if let Some(last) = self.samples.last() {
+            write!(
+                f,
+                " ({} combination(s) in {:?}, frontier {})",
+                last.combinations, last.elapsed, last.frontier
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Detector of exploration no longer finding new fault combinations
+///
+/// See [`Runner::with_diminishing_returns_stop()`](crate::Runner::with_diminishing_returns_stop).
+pub struct DiminishingReturns {
+    window: Duration,
+    min_new_combinations: usize,
+    discoveries: VecDeque<Instant>,
+}
+
+impl DiminishingReturns {
+    pub fn new(window: Duration, min_new_combinations: usize) -> Self {
+        Self {
+            window,
+            min_new_combinations,
+            discoveries: Default::default(),
+        }
+    }
+
+    /// Record exploration of a new fault combination
+    pub fn record(&mut self, now: Instant) {
+        self.discoveries.push_back(now);
+        // older ones are not needed to count discoveries in the window
+        if self.discoveries.len() > self.min_new_combinations {
+            self.discoveries.pop_front();
+        }
+    }
+
+    /// Whether too few new combinations were explored in the trailing window
+    pub fn should_stop(&mut self, start: Instant, now: Instant) -> bool {
+        if now.duration_since(start) < self.window {
+            return false;
+        }
+        while let Some(&oldest) = self.discoveries.front()
+            && now.duration_since(oldest) > self.window
+        {
+            self.discoveries.pop_front();
+        }
+        self.discoveries.len() < self.min_new_combinations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(elapsed_ms: u64, combinations: usize) -> TimelineSample {
+        TimelineSample {
+            elapsed: Duration::from_millis(elapsed_ms),
+            iterations: combinations,
+            combinations,
+            frontier: 0,
+            failpoints: 1,
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let mut timeline = Timeline::default();
+        for (elapsed_ms, combinations) in [(10, 7), (20, 14), (30, 15), (31, 15)] {
+            timeline.push(sample(elapsed_ms, combinations));
+        }
+        assert_eq!(
+            timeline.to_string(),
+            "██▂▁ (15 combination(s) in 31ms, frontier 0)"
+        );
+        assert!(timeline.to_json().starts_with(
+            r#"{"samples":[{"elapsed_ms":10,"iterations":7,"combinations":7,"frontier":0,"failpoints":1},"#
+        ));
+    }
+
+    #[test]
+    fn test_diminishing_returns() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut detector = DiminishingReturns::new(Duration::from_millis(100), 2);
+        assert!(!detector.should_stop(start, at(50)));
+        detector.record(at(60));
+        detector.record(at(90));
+        assert!(!detector.should_stop(start, at(150)));
+        assert!(detector.should_stop(start, at(170)));
+    }
+}
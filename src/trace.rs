@@ -0,0 +1,137 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::common::Branch;
+use crate::coverage::json_string;
+use crate::report::{ExecutionPath, Step};
+
+/// Small sequential id of the current thread, for trace viewer tracks
+fn thread_index() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static INDEX: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
+
+struct Event {
+    at: Duration,
+    tid: u64,
+    step: Step,
+}
+
+/// Failpoint visits of a single iteration, with timestamps
+pub struct Trace {
+    start: Instant,
+    events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Default::default(),
+        }
+    }
+
+    pub fn record(&mut self, step: Step) {
+        self.events.push(Event {
+            at: self.start.elapsed(),
+            tid: thread_index(),
+            step,
+        });
+    }
+
+    /// Render as Chrome trace event format, readable by Perfetto and chrome://tracing
+    pub fn to_json(&self, path: &ExecutionPath) -> String {
+        let pid = std::process::id();
+        let tid = thread_index();
+        let duration = self.start.elapsed();
+        let micros = |duration: Duration| duration.as_secs_f64() * 1e6;
+
+        let mut events = vec![format!(
+            "{{\"name\":\"iteration\",\"cat\":\"faine\",\"ph\":\"X\",\"ts\":0,\"dur\":{:.3},\"pid\":{pid},\"tid\":{tid},\"args\":{{\"path\":{}}}}}",
+            micros(duration),
+            json_string(&path.key())
+        )];
+        for event in &self.events {
+            let branch = match event.step.branch {
+                Branch::Activate => "activate",
+                Branch::Skip => "skip",
+            };
+            let mut args = format!("\"branch\":\"{branch}\"");
+            if let Some(variant) = event.step.variant_description() {
+                args += &format!(",\"variant\":{}", json_string(&variant));
+            }
+            if let Some(trigger) = event.step.trigger {
+                args += &format!(",\"trigger\":{}", json_string(trigger));
+            }
+            events.push(format!(
+                "{{\"name\":{},\"cat\":\"failpoint\",\"ph\":\"i\",\"s\":\"t\",\"ts\":{:.3},\"pid\":{pid},\"tid\":{},\"args\":{{{args}}}}}",
+                json_string(event.step.failpoint),
+                micros(event.at),
+                event.tid
+            ));
+        }
+        let outcome = if let Some(panic) = &path.panic {
+            format!("panicked at {}", panic.location())
+        } else if path.timed_out {
+            "timed out".to_string()
+        } else {
+            "finished".to_string()
+        };
+        events.push(format!(
+            "{{\"name\":{},\"cat\":\"outcome\",\"ph\":\"i\",\"s\":\"t\",\"ts\":{:.3},\"pid\":{pid},\"tid\":{tid},\"args\":{{}}}}",
+            json_string(&outcome),
+            micros(duration)
+        ));
+        format!(
+            "{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ns\"}}\n",
+            events.join(",\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let step = Step {
+            failpoint: "write \"block\"",
+            branch: Branch::Activate,
+            variant: None,
+            variant_index: None,
+            trigger: Some("open"),
+        };
+        let mut trace = Trace::start();
+        trace.record(step.clone());
+        let path = ExecutionPath {
+            steps: vec![step],
+            timed_out: true,
+            ..Default::default()
+        };
+        let json = trace.to_json(&path);
+        assert!(json.starts_with("{\"traceEvents\":[\n"));
+        assert!(json.ends_with("\n],\"displayTimeUnit\":\"ns\"}\n"));
+
+        // one event per line, each with fields required by the format
+        let events: Vec<&str> = json.lines().skip(1).take(3).collect();
+        for event in &events {
+            for field in ["\"name\":", "\"ph\":", "\"ts\":", "\"pid\":", "\"tid\":"] {
+                assert!(event.contains(field), "{event} lacks {field}");
+            }
+        }
+        assert!(events[0].contains("\"ph\":\"X\""));
+        assert!(events[0].contains("\"dur\":"));
+        assert!(events[1].contains(
+            "\"name\":\"write \\\"block\\\"\",\"cat\":\"failpoint\",\"ph\":\"i\",\"s\":\"t\""
+        ));
+        assert!(events[1].contains("\"args\":{\"branch\":\"activate\",\"trigger\":\"open\"}"));
+        assert!(events[2].starts_with("{\"name\":\"timed out\",\"cat\":\"outcome\""));
+    }
+}
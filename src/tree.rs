@@ -1,23 +1,62 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
+use crate::axes::{self, AxisBudget};
 use crate::collections::BranchVec;
-use crate::common::{Branch, Label};
+use crate::common::{Branch, Label, Tier, Weight};
+use crate::dedup::{self, DedupMode, DedupStore};
+use crate::fnv::FnvHasher;
+use crate::intern;
 use crate::options::Options;
+use crate::selector::Selector;
+use crate::shard;
+use crate::strategy::{FaultRate, Random, Strategy};
 
 type NodeId = usize;
 
+/// Number of nodes random strategy keeps before forgetting seen paths
+const MAX_RANDOM_NODES: usize = 1 << 16;
+
+/// Make failpoints referred to by aliases resolvable by raw names
+fn resolve_aliases(
+    mut names: HashMap<String, usize>,
+    aliases: &[(String, String)],
+) -> HashMap<String, usize> {
+    for (raw, alias) in aliases {
+        if let Some(&group) = names.get(alias.as_str()) {
+            names.insert(raw.clone(), group);
+        }
+    }
+    names
+}
+
 #[derive(Default)]
 pub struct ForwardEdges {
     nodes: HashMap<Label, NodeId>,
     num_completely_visited: usize,
+    is_pruned: bool,
+    has_failed_paths: bool,
+    digest: u64,
+    // highest weight of unexplored slots in the subtree, unknown
+    // until any path through it is finished
+    best_slot: Option<Weight>,
 }
 
 impl ForwardEdges {
     fn is_completely_visited(&self) -> bool {
-        self.num_completely_visited == self.nodes.len().max(1)
+        self.is_pruned || self.num_completely_visited == self.nodes.len().max(1)
+    }
+
+    /// Highest weight of unexplored slots in the subtree
+    ///
+    /// Unknown subtrees are assumed to be of default weight, so
+    /// unweighted failpoints are explored in the usual order.
+    fn best_slot(&self) -> Weight {
+        self.best_slot.unwrap_or_default()
     }
 }
 
@@ -25,23 +64,26 @@ impl ForwardEdges {
 pub struct BackwardEdge {
     node_id: NodeId,
     branch: Branch,
-    #[allow(unused)]
     label: Label,
 }
 
 #[derive(Default)]
 pub struct Node {
     parent: Option<BackwardEdge>,
+    prefix: u64,
     nexts: BranchVec<ForwardEdges>,
     is_final: bool,
+    is_independence_audit: bool,
 }
 
 impl Node {
-    pub fn new(parent_edge: Option<BackwardEdge>) -> Self {
+    pub fn new(parent_edge: Option<BackwardEdge>, prefix: u64) -> Self {
         Self {
             parent: parent_edge,
+            prefix,
             nexts: Default::default(),
             is_final: false,
+            is_independence_audit: false,
         }
     }
 
@@ -52,12 +94,93 @@ impl Node {
     }
 }
 
+/// Decisions forced upon a replayed iteration
+struct Replay {
+    path: Vec<(Label, Branch)>,
+    position: usize,
+    divergence: Option<(usize, Option<Label>)>,
+}
+
+/// Priority path being followed by the current iteration
+struct Forced {
+    path: Vec<String>,
+    position: usize,
+    redundant: bool,
+}
+
+impl Forced {
+    /// Activate failpoints listed in the path in order, skipping others
+    fn decide(&mut self, label: Label, aliases: &[(String, String)]) -> Branch {
+        let (Some(expected), Some(name)) = (self.path.get(self.position), label.failpoint_name())
+        else {
+            return Branch::Skip;
+        };
+        if intern::refers_to(expected, name)
+            || aliases
+                .iter()
+                .any(|(raw, alias)| raw == name && alias == expected)
+        {
+            self.position += 1;
+            Branch::Activate
+        } else {
+            Branch::Skip
+        }
+    }
+}
+
+/// Decisions read from externally controlled bytes, see [`crate::fuzz`]
+struct Decisions {
+    bytes: Arc<[u8]>,
+    position: usize,
+}
+
+impl Decisions {
+    /// Activate on odd bytes, in order of visits, skipping once bytes run out
+    fn decide(&mut self) -> Branch {
+        let byte = self.bytes.get(self.position).copied().unwrap_or_default();
+        self.position += 1;
+        if byte & 1 == 1 {
+            Branch::Activate
+        } else {
+            Branch::Skip
+        }
+    }
+}
+
 pub struct Tree {
     options: Options,
     nodes: Vec<Node>,
     roots: ForwardEdges,
     current_edge: Option<BackwardEdge>,
     non_determinism_witnessed: bool,
+    independent_groups: HashMap<String, usize>,
+    independent_components: HashMap<String, usize>,
+    independent_prefixes: Vec<(String, usize)>,
+    activated_groups: Vec<usize>,
+    audited_group_pairs: HashSet<(usize, usize)>,
+    num_pruned: usize,
+    is_independence_audit: bool,
+    smoke_seen: HashSet<(usize, Label)>,
+    random: Option<Random>,
+    dedup: Option<DedupStore>,
+    root_prefix: u64,
+    num_dedup_skipped: usize,
+    num_dedup_mismatches: usize,
+    replay: Option<Replay>,
+    priority_paths: VecDeque<Vec<String>>,
+    forced: Option<Forced>,
+    decisions: Option<Decisions>,
+    unreachable_priority_paths: Vec<Vec<String>>,
+    current_combination: usize,
+    combination_edge: Option<BackwardEdge>,
+    combination_share: Option<usize>,
+    combination_iterations: Vec<usize>,
+    num_truncated_combinations: usize,
+    depth: usize,
+    foreign_descent: bool,
+    // only tracked when exploration is limited by a budget
+    weights: Option<HashMap<Label, Weight>>,
+    path_weight: Weight,
 }
 
 pub enum ExecutionStatus {
@@ -67,17 +190,338 @@ pub enum ExecutionStatus {
 
 impl Tree {
     pub fn new(options: Options) -> Self {
+        let mut independent_groups = HashMap::new();
+        let mut independent_components = HashMap::new();
+        let mut independent_prefixes = Vec::new();
+        for (group, selectors) in options.independent_groups.iter().enumerate() {
+            for selector in selectors {
+                match selector {
+                    Selector::Name(name) => {
+                        independent_groups.insert(name.clone(), group);
+                    }
+                    Selector::Component(component) => {
+                        independent_components.insert(component.clone(), group);
+                    }
+                    Selector::Prefix(prefix) => {
+                        independent_prefixes.push((prefix.clone(), group));
+                    }
+                }
+            }
+        }
+        let independent_groups = resolve_aliases(independent_groups, &options.aliases);
+        let random = match &options.strategy {
+            Strategy::Exhaustive => None,
+            Strategy::Random { schedule, seed } => Some(Random::new(
+                schedule,
+                *seed,
+                &options.fault_models,
+                &options.rate_limits,
+                &options.cooldowns,
+            )),
+        };
+        let dedup = match (
+            &options.dedup_store,
+            options.scenario_key,
+            &options.strategy,
+        ) {
+            (Some(dir), Some(_), Strategy::Exhaustive) if options.shard.is_none() => {
+                Some(DedupStore::new(dir, options.dedup_mode))
+            }
+            _ => None,
+        };
+        // subtrees are only comparable when explored with the same pruning
+        let mut hasher = FnvHasher::new();
+        (
+            options.scenario_key,
+            options.tier,
+            &options.independent_groups,
+            options.audit_independence,
+        )
+            .hash(&mut hasher);
+        let root_prefix = hasher.finish();
+        let priority_paths = options.priority_paths.iter().cloned().collect();
+        let weights = (matches!(options.strategy, Strategy::Exhaustive)
+            && (options.max_iterations.is_some() || options.max_duration.is_some()))
+        .then(HashMap::new);
+        let num_combinations = axes::num_combinations(&options.axes);
+        let combination_share = match (options.axis_budget, options.max_iterations) {
+            (AxisBudget::Divided, Some(max_iterations)) if num_combinations > 1 => {
+                Some((max_iterations / num_combinations).max(1))
+            }
+            _ => None,
+        };
         Self {
             options,
             nodes: Default::default(),
             roots: Default::default(),
             current_edge: None,
             non_determinism_witnessed: false,
+            independent_groups,
+            independent_components,
+            independent_prefixes,
+            activated_groups: Default::default(),
+            audited_group_pairs: Default::default(),
+            num_pruned: 0,
+            is_independence_audit: false,
+            smoke_seen: Default::default(),
+            random,
+            dedup,
+            root_prefix,
+            num_dedup_skipped: 0,
+            num_dedup_mismatches: 0,
+            replay: None,
+            priority_paths,
+            forced: None,
+            decisions: None,
+            unreachable_priority_paths: Default::default(),
+            current_combination: 0,
+            combination_edge: None,
+            combination_share,
+            combination_iterations: vec![0; num_combinations],
+            num_truncated_combinations: 0,
+            depth: 0,
+            foreign_descent: false,
+            weights,
+            path_weight: Weight::High,
         }
     }
 
     pub fn start(&mut self) {
         self.current_edge = None;
+        self.depth = 0;
+        self.foreign_descent = false;
+        self.path_weight = Weight::High;
+        self.activated_groups.clear();
+        self.is_independence_audit = false;
+        if let Some(random) = &mut self.random {
+            random.start();
+            // paths are revisited randomly rather than explored, so seen
+            // ones are only needed to detect non-determinism, and keeping
+            // all of them would grow without bound in long soak tests
+            if self.nodes.len() > MAX_RANDOM_NODES {
+                self.nodes.clear();
+                self.roots = Default::default();
+            }
+        }
+        self.forced = self.priority_paths.pop_front().map(|path| Forced {
+            path,
+            position: 0,
+            redundant: false,
+        });
+        self.decisions = self
+            .options
+            .decision_bytes
+            .clone()
+            .map(|bytes| Decisions { bytes, position: 0 });
+    }
+
+    /// Decide combination of configuration axis values for the current iteration
+    ///
+    /// Combinations are selected by a chain of decisions preceding all
+    /// failpoints, where activation of n-th decision selects combination
+    /// n, and skipping all of them selects combination 0, so subtree of
+    /// each combination is explored like a separate tree.
+    pub fn choose_axes(&mut self) -> Vec<(&'static str, &'static str)> {
+        let num_combinations = self.combination_iterations.len();
+        self.current_combination = (1..num_combinations)
+            .find(|&index| self.visit(Label::AxisCombination(index)) == Branch::Activate)
+            .unwrap_or(0);
+        self.combination_edge = self.current_edge;
+        axes::combination(&self.options.axes, self.current_combination)
+    }
+
+    /// Number of axis combinations not completely explored due to their budget share
+    pub fn num_truncated_combinations(&self) -> usize {
+        self.num_truncated_combinations
+    }
+
+    /// Number of discovered branches which are not yet explored
+    pub fn frontier_size(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| !node.is_final)
+            .flat_map(|node| [&node.nexts[Branch::Activate], &node.nexts[Branch::Skip]])
+            .filter(|nexts| nexts.nodes.is_empty() && !nexts.is_completely_visited())
+            .count()
+    }
+
+    /// Priority paths which could not be followed
+    pub fn unreachable_priority_paths(&self) -> &[Vec<String>] {
+        &self.unreachable_priority_paths
+    }
+
+    /// Start an iteration which follows an already explored path
+    ///
+    /// The path is obtained from [`current_path()`](Self::current_path)
+    /// of an earlier iteration. Replayed iterations do not affect
+    /// exploration, and must be completed with
+    /// [`finish_replay()`](Self::finish_replay) instead of
+    /// [`finalize()`](Self::finalize).
+    pub fn start_replay(&mut self, path: Vec<(Label, Branch)>) {
+        self.current_edge = None;
+        self.activated_groups.clear();
+        self.is_independence_audit = false;
+        self.replay = Some(Replay {
+            path,
+            position: 0,
+            divergence: None,
+        });
+    }
+
+    /// Complete replayed iteration, returning where tested code has
+    /// diverged from the replayed path, if it has
+    ///
+    /// Divergence is described by the position in the path and the
+    /// label visited there instead of the recorded one, which is
+    /// `None` if iteration has ended early.
+    pub fn finish_replay(&mut self) -> Option<(usize, Option<Label>)> {
+        let replay = self.replay.take().expect("replay must be started");
+        let divergence = replay
+            .divergence
+            .or_else(|| (replay.position != replay.path.len()).then_some((replay.position, None)));
+        self.non_determinism_witnessed |= divergence.is_some();
+        divergence
+    }
+
+    /// Follow replayed path, skipping all failpoints once diverged from it
+    fn replay_visit(&mut self, label: Label) -> Branch {
+        let replay = self.replay.as_mut().expect("replay must be started");
+        if replay.divergence.is_none()
+            && let Some(&(expected, branch)) = replay.path.get(replay.position)
+            && expected == label
+        {
+            replay.position += 1;
+            let parent_nexts = if let Some(current_edge) = self.current_edge {
+                &self.nodes[current_edge.node_id].nexts[current_edge.branch]
+            } else {
+                &self.roots
+            };
+            if let Some(&node_id) = parent_nexts.nodes.get(&label) {
+                self.current_edge = Some(BackwardEdge {
+                    node_id,
+                    branch,
+                    label,
+                });
+                return branch;
+            }
+        }
+        if replay.divergence.is_none() {
+            replay.divergence = Some((replay.position, Some(label)));
+        }
+        Branch::Skip
+    }
+
+    /// Schedule phase of the current iteration, for random strategy
+    pub fn random_phase(&self) -> Option<usize> {
+        self.random.as_ref().and_then(|random| random.phase())
+    }
+
+    /// Failure run length distributions of failpoints decided by fault models
+    pub fn take_failure_runs(&mut self) -> BTreeMap<&'static str, BTreeMap<usize, usize>> {
+        self.random
+            .as_mut()
+            .map(Random::take_failure_runs)
+            .unwrap_or_default()
+    }
+
+    /// Activation statistics of failpoints, for random strategy
+    pub fn take_fault_rates(&mut self) -> BTreeMap<&'static str, FaultRate> {
+        self.random
+            .as_mut()
+            .map(Random::take_fault_rates)
+            .unwrap_or_default()
+    }
+
+    /// Number of subtrees skipped as already explored by other tests
+    pub fn num_dedup_skipped(&self) -> usize {
+        self.num_dedup_skipped
+    }
+
+    /// Number of subtrees with outcomes different from ones recorded by other tests
+    pub fn num_dedup_mismatches(&self) -> usize {
+        self.num_dedup_mismatches
+    }
+
+    /// Whether tested code was seen taking different paths given the same decisions
+    pub fn non_determinism_witnessed(&self) -> bool {
+        self.non_determinism_witnessed
+    }
+
+    /// Number of branches pruned due to user-asserted failpoint independence
+    pub fn num_pruned(&self) -> usize {
+        self.num_pruned
+    }
+
+    /// Whether current iteration explores a combination of independent
+    /// failpoints, which would be pruned if not for independence audit
+    pub fn is_independence_audit(&self) -> bool {
+        self.is_independence_audit
+    }
+
+    fn group_of(&self, label: Label) -> Option<usize> {
+        if self.independent_groups.is_empty()
+            && self.independent_components.is_empty()
+            && self.independent_prefixes.is_empty()
+        {
+            return None;
+        }
+        let name = label.failpoint_name()?;
+        // names may refer to failpoints either qualified with a crate or not
+        let group = match label {
+            Label::Failpoint(id) => [name, id.qualified_name(), id.unqualified_name()]
+                .into_iter()
+                .find_map(|name| self.independent_groups.get(name)),
+            _ => self.independent_groups.get(name),
+        };
+        if let Some(group) = group {
+            return Some(*group);
+        }
+        if !self.independent_components.is_empty() {
+            let component = match label {
+                Label::Failpoint(id) => id.component(),
+                _ => intern::component_of(name),
+            };
+            if let Some(group) =
+                component.and_then(|component| self.independent_components.get(component))
+            {
+                return Some(*group);
+            }
+        }
+        self.independent_prefixes
+            .iter()
+            .find(|(prefix, _)| intern::has_prefix(name, prefix))
+            .map(|(_, group)| *group)
+    }
+
+    /// Decide whether activation of a newly discovered failpoint
+    /// should be pruned, based on independent groups activated so far
+    /// and, in smoke tier, on whether it was already seen elsewhere
+    fn prune_new_node(&mut self, node_id: NodeId, label: Label) {
+        if let Label::AxisCombination(_) = label {
+            return;
+        }
+        if self.options.tier == Tier::Smoke
+            && !self.smoke_seen.insert((self.current_combination, label))
+        {
+            self.nodes[node_id].nexts[Branch::Activate].is_pruned = true;
+            return;
+        }
+        let Some(group) = self.group_of(label) else {
+            return;
+        };
+        for &other in &self.activated_groups {
+            if other == group {
+                continue;
+            }
+            let pair = (group.min(other), group.max(other));
+            if self.options.audit_independence && self.audited_group_pairs.insert(pair) {
+                self.nodes[node_id].is_independence_audit = true;
+            } else {
+                self.nodes[node_id].nexts[Branch::Activate].is_pruned = true;
+                self.num_pruned += 1;
+                return;
+            }
+        }
     }
 
     fn advance(&mut self, label: Label) -> NodeId {
@@ -92,27 +536,238 @@ impl Tree {
         if let Some(current_node_id) = parent_nexts.nodes.get(&label) {
             *current_node_id
         } else {
-            if parent_nexts.nodes.len() >= 1 {
+            if !parent_nexts.nodes.is_empty() {
                 self.non_determinism_witnessed = true;
             }
             parent_nexts.nodes.insert(label, new_node_id);
-            self.nodes.push(Node::new(self.current_edge));
+            let prefix = match self.current_edge {
+                Some(edge) if self.dedup.is_some() => {
+                    dedup::edge_key(self.nodes[edge.node_id].prefix, edge.label, edge.branch)
+                }
+                _ => self.root_prefix,
+            };
+            self.nodes.push(Node::new(self.current_edge, prefix));
             new_node_id
         }
     }
 
-    pub fn finalize(&mut self, label: Label) -> ExecutionStatus {
+    /// Prune branches owned by other shards at the decision which
+    /// completes an ownership prefix
+    ///
+    /// If neither branch is owned, one is still returned, as the
+    /// current iteration has to follow one of them, and the path is
+    /// then accounted like a redundant one.
+    fn shard_prune(
+        &mut self,
+        node_id: NodeId,
+        label: Label,
+        branches: &[Branch; 2],
+    ) -> Option<Branch> {
+        let (index, total) = self.options.shard?;
+        if self.depth + 1 != self.options.shard_depth {
+            return None;
+        }
+        let prefix = self.current_path();
+        let node = &self.nodes[node_id];
+        let was_complete = node.is_completely_visited();
+        let fallback = branches
+            .iter()
+            .copied()
+            .find(|branch| !node.nexts[*branch].is_completely_visited());
+        for &branch in branches {
+            let decisions = prefix.iter().copied().chain([(label, branch)]);
+            if shard::owner(decisions, total) != index {
+                self.nodes[node_id].nexts[branch].is_pruned = true;
+            }
+        }
+        if was_complete || !self.nodes[node_id].is_completely_visited() {
+            return None;
+        }
+        self.complete_ancestors(self.nodes[node_id].parent, false);
+        self.foreign_descent = true;
+        fallback
+    }
+
+    /// Whether the current path is owned by another shard, and must not be reported
+    ///
+    /// Paths shorter than ownership prefix are owned by the hash of
+    /// all their decisions.
+    pub fn is_foreign(&self) -> bool {
+        self.options.shard.is_some_and(|(index, total)| {
+            let decisions = self.current_path();
+            shard::owner(decisions.into_iter().take(self.options.shard_depth), total) != index
+        })
+    }
+
+    /// Account a path, or a subtree of paths, in all edges leading to it
+    fn add_to_ancestors(
+        &mut self,
+        mut current_edge: Option<BackwardEdge>,
+        digest: u64,
+        failed: bool,
+    ) {
+        while let Some(edge) = current_edge {
+            let nexts = &mut self.nodes[edge.node_id].nexts[edge.branch];
+            nexts.digest = nexts.digest.wrapping_add(digest);
+            nexts.has_failed_paths |= failed;
+            current_edge = self.nodes[edge.node_id].parent;
+        }
+    }
+
+    /// Skip the preferred branch if another test has explored it
+    ///
+    /// Only done when the other branch is still to be explored, as
+    /// the current iteration has to follow one of them.
+    fn dedup_prune(&mut self, node_id: NodeId, label: Label, preferred: Branch, other: Branch) {
+        let Some(dedup) = &self.dedup else {
+            return;
+        };
+        let node = &self.nodes[node_id];
+        if dedup.mode != DedupMode::Skip
+            || !node.nexts[preferred].nodes.is_empty()
+            || node.nexts[preferred].is_completely_visited()
+            || node.nexts[other].is_completely_visited()
+        {
+            return;
+        }
+        if let Some(digest) = dedup.lookup(dedup::edge_key(node.prefix, label, preferred)) {
+            self.nodes[node_id].nexts[preferred].is_pruned = true;
+            self.num_dedup_skipped += 1;
+            let edge = BackwardEdge {
+                node_id,
+                branch: preferred,
+                label,
+            };
+            self.add_to_ancestors(Some(edge), digest, false);
+        }
+    }
+
+    /// Record a completely explored subtree, or check it against a record
+    fn dedup_complete(&mut self, edge: BackwardEdge) {
+        let Some(dedup) = &self.dedup else {
+            return;
+        };
+        let node = &self.nodes[edge.node_id];
+        let nexts = &node.nexts[edge.branch];
+        let key = dedup::edge_key(node.prefix, edge.label, edge.branch);
+        match dedup.lookup(key) {
+            Some(digest) if digest != nexts.digest || nexts.has_failed_paths => {
+                self.num_dedup_mismatches += 1;
+            }
+            Some(_) => {}
+            None if !nexts.has_failed_paths => {
+                if let Err(err) = dedup.record(key, nexts.digest) {
+                    eprintln!("faine: warning: cannot write deduplication record: {err}");
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub fn finalize(&mut self, label: Label, outcome: Option<u64>) -> ExecutionStatus {
+        let mut status = self.finalize_path(label, outcome);
+        if self.weights.is_some() {
+            self.update_best_slots(self.current_edge);
+        }
+        if let Some(share) = self.combination_share
+            && self.random.is_none()
+        {
+            let iterations = &mut self.combination_iterations[self.current_combination];
+            *iterations += 1;
+            if *iterations == share
+                && let Some(edge) = self.combination_edge
+            {
+                status = self.close_subtree(edge);
+            }
+        }
+        // priority paths are followed even if the tree is already
+        // explored, so they are all reported as either reached or not
+        if self.priority_paths.is_empty() {
+            status
+        } else {
+            ExecutionStatus::Continue
+        }
+    }
+
+    fn finalize_path(&mut self, label: Label, outcome: Option<u64>) -> ExecutionStatus {
         let current_node_id = self.advance(label);
 
+        // a priority path may repeat an already explored one, which
+        // must not be accounted twice
+        let mut redundant = self.nodes[current_node_id].is_final || self.foreign_descent;
+        if let Some(forced) = self.forced.take() {
+            redundant |= forced.redundant;
+            if forced.position < forced.path.len() {
+                self.unreachable_priority_paths.push(forced.path);
+            }
+        }
+
         self.nodes[current_node_id].is_final = true;
 
-        let mut current_edge = self.current_edge;
+        if self.dedup.is_some() && !redundant {
+            let mut hasher = FnvHasher::new();
+            (
+                self.nodes[current_node_id].prefix,
+                label.failpoint_name(),
+                outcome,
+            )
+                .hash(&mut hasher);
+            self.add_to_ancestors(self.current_edge, hasher.finish(), label != Label::Finished);
+        }
+
+        // random strategy revisits paths, so completeness is not tracked
+        if let Some(random) = &self.random {
+            return if random.is_finished() {
+                ExecutionStatus::Stop
+            } else {
+                ExecutionStatus::Continue
+            };
+        }
+
+        if redundant {
+            return if self.roots.is_completely_visited() {
+                ExecutionStatus::Stop
+            } else {
+                ExecutionStatus::Continue
+            };
+        }
+
+        self.complete_ancestors(self.current_edge, true)
+    }
+
+    /// Stop exploring a subtree, as if it was completely visited
+    fn close_subtree(&mut self, edge: BackwardEdge) -> ExecutionStatus {
+        let node = &self.nodes[edge.node_id];
+        let was_complete = node.is_completely_visited();
+        if !node.nexts[edge.branch].is_completely_visited() {
+            self.num_truncated_combinations += 1;
+        }
+        self.nodes[edge.node_id].nexts[edge.branch].is_pruned = true;
+        if !was_complete && self.nodes[edge.node_id].is_completely_visited() {
+            // partially explored subtrees must not be recorded as complete
+            self.complete_ancestors(self.nodes[edge.node_id].parent, false)
+        } else if self.roots.is_completely_visited() {
+            ExecutionStatus::Stop
+        } else {
+            ExecutionStatus::Continue
+        }
+    }
+
+    /// Account completion of a node in all edges leading to it
+    fn complete_ancestors(
+        &mut self,
+        mut current_edge: Option<BackwardEdge>,
+        dedup: bool,
+    ) -> ExecutionStatus {
         loop {
             if let Some(edge) = current_edge {
-                let parent_node = &mut self.nodes[edge.node_id];
-                let parent_nexts = &mut parent_node.nexts[edge.branch];
+                let parent_nexts = &mut self.nodes[edge.node_id].nexts[edge.branch];
                 assert!(parent_nexts.num_completely_visited <= parent_nexts.nodes.len());
                 parent_nexts.num_completely_visited += 1;
+                if dedup && parent_nexts.is_completely_visited() {
+                    self.dedup_complete(edge);
+                }
+                let parent_node = &self.nodes[edge.node_id];
                 if parent_node.is_completely_visited() {
                     current_edge = parent_node.parent;
                 } else {
@@ -130,18 +785,191 @@ impl Tree {
         }
     }
 
+    /// Record a weight declared at a failpoint site
+    pub fn set_weight(&mut self, label: Label, weight: Weight) {
+        if let Some(weights) = &mut self.weights {
+            weights.insert(label, weight);
+        }
+    }
+
+    fn weight(&self, label: Label) -> Weight {
+        self.weights
+            .as_ref()
+            .and_then(|weights| weights.get(&label).copied())
+            .unwrap_or_default()
+    }
+
+    /// Highest weight of unexplored slots under a node, relative to it
+    fn node_best_slot(&self, node_id: NodeId, label: Label) -> Option<Weight> {
+        let node = &self.nodes[node_id];
+        if node.is_completely_visited() {
+            return None;
+        }
+        [Branch::Activate, Branch::Skip]
+            .into_iter()
+            .filter(|branch| !node.nexts[*branch].is_completely_visited())
+            .map(|branch| match branch {
+                Branch::Activate => node.nexts[branch].best_slot().min(self.weight(label)),
+                Branch::Skip => node.nexts[branch].best_slot(),
+            })
+            .max()
+    }
+
+    /// Recalculate highest weights of unexplored slots on the path to an edge
+    fn update_best_slots(&mut self, mut current_edge: Option<BackwardEdge>) {
+        while let Some(edge) = current_edge {
+            let nexts = &self.nodes[edge.node_id].nexts[edge.branch];
+            let best_slot = nexts
+                .nodes
+                .iter()
+                .filter_map(|(&label, &node_id)| self.node_best_slot(node_id, label))
+                .max();
+            self.nodes[edge.node_id].nexts[edge.branch].best_slot = best_slot;
+            current_edge = self.nodes[edge.node_id].parent;
+        }
+    }
+
+    /// Explore activation after skip if its slots are less important
+    ///
+    /// Activation never raises the lowest weight of a path, so only
+    /// the preference to activate may be reversed.
+    fn weighted_order(&self, node_id: NodeId, label: Label, branches: [Branch; 2]) -> [Branch; 2] {
+        let node = &self.nodes[node_id];
+        if branches[0] != Branch::Activate
+            || node.nexts[Branch::Activate].is_completely_visited()
+            || node.nexts[Branch::Skip].is_completely_visited()
+        {
+            return branches;
+        }
+        let activate = node.nexts[Branch::Activate]
+            .best_slot()
+            .min(self.weight(label))
+            .min(self.path_weight);
+        let skip = node.nexts[Branch::Skip].best_slot().min(self.path_weight);
+        if skip > activate {
+            [Branch::Skip, Branch::Activate]
+        } else {
+            branches
+        }
+    }
+
+    /// Numbers of slots left unexplored, by their weights
+    pub fn unexplored_slots(&self) -> BTreeMap<Weight, usize> {
+        let mut slots = BTreeMap::new();
+        if self.weights.is_none() {
+            return slots;
+        }
+        let mut stack = vec![(&self.roots, Weight::High)];
+        while let Some((nexts, path_weight)) = stack.pop() {
+            if nexts.is_completely_visited() {
+                continue;
+            }
+            if nexts.nodes.is_empty() {
+                *slots.entry(path_weight).or_default() += 1;
+                continue;
+            }
+            for (&label, &node_id) in &nexts.nodes {
+                let node = &self.nodes[node_id];
+                if node.is_final {
+                    continue;
+                }
+                stack.push((
+                    &node.nexts[Branch::Activate],
+                    path_weight.min(self.weight(label)),
+                ));
+                stack.push((&node.nexts[Branch::Skip], path_weight));
+            }
+        }
+        slots
+    }
+
     pub fn visit(&mut self, label: Label) -> Branch {
+        if self.replay.is_some() {
+            return self.replay_visit(label);
+        }
+
+        let new_node_id = self.nodes.len();
         let current_node_id = self.advance(label);
+        if current_node_id == new_node_id {
+            self.prune_new_node(current_node_id, label);
+        }
 
-        let branches = match self.options.branch_preference {
+        // in smoke tier, failpoints must be discovered on paths with
+        // least activations first, so skipping is always preferred
+        let branch_preference = match (self.options.tier, self.options.order_seed) {
+            (Tier::Smoke, _) => Branch::Skip,
+            (Tier::Full, None) => self.options.branch_preference,
+            (Tier::Full, Some(seed)) => {
+                let mut hasher = FnvHasher::new();
+                (seed, self.nodes[current_node_id].prefix).hash(&mut hasher);
+                if hasher.finish() & 1 == 0 {
+                    Branch::Activate
+                } else {
+                    Branch::Skip
+                }
+            }
+        };
+        let branches = match branch_preference {
             Branch::Activate => &[Branch::Activate, Branch::Skip],
             Branch::Skip => &[Branch::Skip, Branch::Activate],
         };
+        let forced_branch = self
+            .forced
+            .as_mut()
+            .map(|forced| forced.decide(label, &self.options.aliases));
+        let chosen_branch = forced_branch
+            .or_else(|| self.decisions.as_mut().map(Decisions::decide))
+            .or_else(|| {
+                self.random.as_mut().map(|random| {
+                    if random.decide(label.failpoint_name())
+                        && !self.nodes[current_node_id].nexts[Branch::Activate].is_pruned
+                    {
+                        Branch::Activate
+                    } else {
+                        Branch::Skip
+                    }
+                })
+            });
+
+        let chosen_branch =
+            chosen_branch.or_else(|| self.shard_prune(current_node_id, label, branches));
+        self.depth += 1;
+
+        if chosen_branch.is_none() {
+            self.dedup_prune(current_node_id, label, branches[0], branches[1]);
+        }
+        let branches = match chosen_branch {
+            None if self.weights.is_some() => {
+                self.weighted_order(current_node_id, label, *branches)
+            }
+            _ => *branches,
+        };
 
-        for branch in *branches {
+        for branch in branches {
             let current_node = &mut self.nodes[current_node_id];
             let current_node_next = &mut current_node.nexts[branch];
-            if !current_node_next.is_completely_visited() {
+            let is_eligible = match chosen_branch {
+                Some(chosen_branch) => branch == chosen_branch,
+                None => !current_node_next.is_completely_visited(),
+            };
+            if is_eligible {
+                if let Some(forced) = &mut self.forced
+                    && current_node_next.is_completely_visited()
+                {
+                    forced.redundant = true;
+                }
+                if branch == Branch::Activate {
+                    if let Some(weights) = &self.weights {
+                        let weight = weights.get(&label).copied().unwrap_or_default();
+                        self.path_weight = self.path_weight.min(weight);
+                    }
+                    if current_node.is_independence_audit {
+                        self.is_independence_audit = true;
+                    }
+                    if let Some(group) = self.group_of(label) {
+                        self.activated_groups.push(group);
+                    }
+                }
                 self.current_edge = Some(BackwardEdge {
                     node_id: current_node_id,
                     branch,
@@ -153,4 +981,132 @@ impl Tree {
 
         unreachable!();
     }
+
+    /// Priority path followed by the current iteration, if any
+    pub fn forced_path(&self) -> Option<&[String]> {
+        self.forced.as_ref().map(|forced| forced.path.as_slice())
+    }
+
+    /// Labels the rest of the current iteration is going to activate,
+    /// if known in advance
+    ///
+    /// Known for replayed iterations, and when newly discovered
+    /// failpoints are always skipped (in smoke tier, or with skip
+    /// preference), as decisions at already discovered ones only
+    /// depend on the tree. Assumes deterministic tested code.
+    pub fn planned_activations(&self) -> Option<Vec<Label>> {
+        if let Some(replay) = &self.replay {
+            return Some(
+                replay.path[replay.position..]
+                    .iter()
+                    .filter(|(_, branch)| *branch == Branch::Activate)
+                    .map(|(label, _)| *label)
+                    .collect(),
+            );
+        }
+        let skips_new = self.options.tier == Tier::Smoke
+            || self.options.branch_preference == Branch::Skip && self.options.order_seed.is_none();
+        if !skips_new
+            || self.random.is_some()
+            || self.dedup.is_some()
+            || self.options.shard.is_some()
+        {
+            return None;
+        }
+        let mut activations = vec![];
+        let mut nexts = match self.current_edge {
+            Some(edge) => &self.nodes[edge.node_id].nexts[edge.branch],
+            None => &self.roots,
+        };
+        loop {
+            let mut known = nexts.nodes.iter();
+            let Some((&label, &node_id)) = known.next() else {
+                // only newly discovered failpoints follow
+                return Some(activations);
+            };
+            if known.next().is_some() {
+                // tested code was seen taking different paths here
+                return None;
+            }
+            let node = &self.nodes[node_id];
+            if node.is_final {
+                return Some(activations);
+            }
+            let branch = [Branch::Skip, Branch::Activate]
+                .into_iter()
+                .find(|branch| !node.nexts[*branch].is_completely_visited())?;
+            if branch == Branch::Activate {
+                activations.push(label);
+            }
+            nexts = &node.nexts[branch];
+        }
+    }
+
+    /// Whether a given label was activated earlier on the current path
+    pub fn is_activated(&self, label: Label) -> bool {
+        let mut current_edge = self.current_edge;
+        while let Some(edge) = current_edge {
+            if edge.label == label && edge.branch == Branch::Activate {
+                return true;
+            }
+            current_edge = self.nodes[edge.node_id].parent;
+        }
+        false
+    }
+
+    /// Number of activations of a given label on the current path
+    pub fn count_activations(&self, label: Label) -> usize {
+        let mut count = 0;
+        let mut current_edge = self.current_edge;
+        while let Some(edge) = current_edge {
+            if edge.label == label && edge.branch == Branch::Activate {
+                count += 1;
+            }
+            current_edge = self.nodes[edge.node_id].parent;
+        }
+        count
+    }
+
+    pub fn current_path(&self) -> Vec<(Label, Branch)> {
+        let mut res = vec![];
+        let mut current_edge = self.current_edge;
+        while let Some(edge) = current_edge {
+            res.push((edge.label, edge.branch));
+            current_edge = self.nodes[edge.node_id].parent;
+        }
+        res.reverse();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::intern::FailpointId;
+    use crate::strategy::Schedule;
+
+    #[test]
+    fn test_random_nodes_are_bounded() {
+        let options = Options {
+            strategy: Strategy::Random {
+                schedule: Schedule::Phases(vec![(Duration::from_secs(3600), 0.5)]),
+                seed: 1,
+            },
+            ..Default::default()
+        };
+        let mut tree = Tree::new(options);
+        let label = Label::Failpoint(FailpointId::intern("random nodes test"));
+        const VISITS: usize = 32;
+        for _ in 0..MAX_RANDOM_NODES / VISITS * 3 {
+            tree.start();
+            assert!(tree.nodes.len() <= MAX_RANDOM_NODES + VISITS);
+            for _ in 0..VISITS {
+                tree.visit(label);
+            }
+            tree.finalize(Label::Finished, None);
+        }
+        assert!(!tree.non_determinism_witnessed());
+    }
 }
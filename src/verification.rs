@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::panics::CaughtPanic;
+use crate::strategy::Rng;
+
+/// Mismatch between an explored path and its replay
+///
+/// See [`Runner::with_self_verification()`](crate::Runner::with_self_verification).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReplayDivergence {
+    /// Index of the replayed path in [`Report::paths()`](crate::Report::paths)
+    pub path: usize,
+
+    /// Index of the first mismatching failpoint visit, or number of
+    /// visits on the path if only the outcome has mismatched
+    pub step: usize,
+
+    /// What was recorded on the explored path
+    pub expected: String,
+
+    /// What was observed on replay
+    pub observed: String,
+}
+
+impl std::fmt::Display for ReplayDivergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "path #{}, step {}: expected {}, observed {}",
+            self.path, self.step, self.expected, self.observed
+        )
+    }
+}
+
+/// Results of replaying a sample of explored paths
+///
+/// See [`Runner::with_self_verification()`](crate::Runner::with_self_verification).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SelfVerification {
+    /// Seed used to choose replayed paths
+    pub seed: u64,
+
+    /// Number of replayed paths
+    pub replayed: usize,
+
+    /// Replayed paths which did not reproduce
+    pub divergences: Vec<ReplayDivergence>,
+}
+
+/// Choose indexes of paths to replay, in ascending order
+pub fn sample_paths(num_paths: usize, fraction: f32, seed: u64) -> Vec<usize> {
+    let count = (num_paths as f64 * f64::from(fraction.clamp(0.0, 1.0))).ceil() as usize;
+    let mut rng = Rng::new(seed);
+    let mut indexes: Vec<usize> = (0..num_paths).collect();
+    // partial Fisher-Yates shuffle
+    for i in 0..count {
+        let j = i + (rng.next_u64() % (num_paths - i) as u64) as usize;
+        indexes.swap(i, j);
+    }
+    indexes.truncate(count);
+    indexes.sort_unstable();
+    indexes
+}
+
+/// Describe how an iteration has ended
+pub fn describe_outcome(
+    timed_out: bool,
+    panic: Option<&CaughtPanic>,
+    outcome: Option<u64>,
+) -> String {
+    let mut description = match panic {
+        Some(panic) => format!("panic at {}", panic.location()),
+        None if timed_out => "timeout".to_string(),
+        None => "finish".to_string(),
+    };
+    if let Some(outcome) = outcome {
+        description += &format!(" with outcome {outcome:016x}");
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_paths() {
+        assert_eq!(sample_paths(10, 1.0, 1), (0..10).collect::<Vec<_>>());
+        assert!(sample_paths(10, 0.0, 1).is_empty());
+        assert!(sample_paths(0, 0.5, 1).is_empty());
+
+        let sample = sample_paths(10, 0.25, 1);
+        assert_eq!(sample.len(), 3);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(sample, sample_paths(10, 0.25, 1));
+    }
+}
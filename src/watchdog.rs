@@ -0,0 +1,149 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::report::{ExecutionPath, Step};
+
+enum Message {
+    Start,
+    Finish,
+}
+
+#[derive(Default)]
+struct Shared {
+    path: Mutex<ExecutionPath>,
+    timed_out: AtomicBool,
+}
+
+/// Watchdog for iterations which take too long
+///
+/// As arbitrary code cannot be interrupted, the watchdog thread
+/// just reports partial path of a timed out iteration to stderr,
+/// and sets a flag which can be checked cooperatively. If the timeout
+/// is enforced, the process is aborted instead.
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    sender: Option<Sender<Message>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    pub fn new(timeout: Duration, enforce: bool) -> Self {
+        let shared: Arc<Shared> = Default::default();
+        // output of the iteration may be captured by the time it hangs
+        let mut stderr = OriginalStderr::new();
+        let (sender, receiver) = mpsc::channel();
+        let thread = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                while let Ok(message) = receiver.recv() {
+                    if !matches!(message, Message::Start) {
+                        continue;
+                    }
+                    match receiver.recv_timeout(timeout) {
+                        Ok(_) => {}
+                        Err(RecvTimeoutError::Timeout) => {
+                            shared.timed_out.store(true, Ordering::SeqCst);
+                            let mut path = shared.path.lock().unwrap().clone();
+                            if enforce {
+                                path.timed_out = true;
+                                stderr.print(format_args!(
+                                    "faine: iteration timed out after {timeout:?}, aborting: {path}, key: {}",
+                                    path.key()
+                                ));
+                                std::process::abort();
+                            }
+                            stderr.print(format_args!(
+                                "faine: iteration timed out after {timeout:?} and keeps running, partial path: {path}"
+                            ));
+                        }
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+        };
+        Self {
+            shared,
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    fn send(&self, message: Message) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(message);
+        }
+    }
+
+    pub fn start(&self) {
+        self.shared.path.lock().unwrap().steps.clear();
+        self.shared.timed_out.store(false, Ordering::SeqCst);
+        self.send(Message::Start);
+    }
+
+    pub fn finish(&self) -> bool {
+        self.send(Message::Finish);
+        self.timed_out()
+    }
+
+    pub fn record(&self, step: Step) {
+        self.shared.path.lock().unwrap().steps.push(step);
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.shared.timed_out.load(Ordering::SeqCst)
+    }
+
+    /// Check of the timeout flag usable from other threads
+    #[cfg_attr(not(feature = "http-test"), allow(dead_code))]
+    pub fn timed_out_check(&self) -> impl Fn() -> bool + Send + 'static {
+        let shared = self.shared.clone();
+        move || shared.timed_out.load(Ordering::SeqCst)
+    }
+}
+
+/// Stderr as it was before output capture
+struct OriginalStderr {
+    #[cfg(unix)]
+    file: Option<std::fs::File>,
+}
+
+impl OriginalStderr {
+    fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            file: {
+                use std::os::fd::AsFd;
+                std::io::stderr()
+                    .as_fd()
+                    .try_clone_to_owned()
+                    .ok()
+                    .map(std::fs::File::from)
+            },
+        }
+    }
+
+    fn print(&mut self, message: std::fmt::Arguments<'_>) {
+        #[cfg(unix)]
+        if let Some(file) = &mut self.file {
+            use std::io::Write;
+            let _ = writeln!(file, "{message}");
+            return;
+        }
+        eprintln!("{message}");
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error::Error;
+use std::io;
+
+use faine::{Runner, assert_injected_by, assert_not_injected, inject_return_io_error};
+
+fn foo() -> io::Result<()> {
+    inject_return_io_error!("create temp file");
+    Ok(())
+}
+
+#[derive(Debug)]
+struct CustomError(io::Error);
+
+impl std::fmt::Display for CustomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "custom error")
+    }
+}
+
+impl Error for CustomError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+fn for_each_error(mut func: impl FnMut(io::Error)) {
+    let mut count = 0;
+    Runner::default()
+        .run(|| {
+            if let Err(err) = foo() {
+                count += 1;
+                func(err);
+            }
+        })
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_io_error() {
+    for_each_error(|err| {
+        let res: io::Result<()> = Err(err);
+        assert_injected_by!(res, "create temp file");
+        assert_injected_by!(res, prefix = "create");
+    });
+}
+
+#[test]
+fn test_custom_error() {
+    for_each_error(|err| {
+        let res: Result<(), CustomError> = Err(CustomError(err));
+        assert_injected_by!(res, "create temp file");
+    });
+}
+
+#[test]
+fn test_boxed_error() {
+    for_each_error(|err| {
+        let res: Result<(), Box<dyn Error>> = Err(Box::new(CustomError(err)));
+        assert_injected_by!(res, "create temp file");
+    });
+    for_each_error(|err| {
+        let res: Result<(), Box<dyn Error + Send + Sync>> = Err(Box::new(err));
+        assert_injected_by!(res, "create temp file");
+    });
+}
+
+#[test]
+fn test_anyhow_error() {
+    for_each_error(|err| {
+        let res: anyhow::Result<()> = Err(anyhow::Error::new(err).context("context"));
+        assert_injected_by!(res, "create temp file");
+    });
+}
+
+#[test]
+fn test_not_injected() {
+    assert_not_injected!(Ok::<(), io::Error>(()));
+    assert_not_injected!(Err::<(), _>(io::Error::other("genuine")));
+    assert_not_injected!(Err::<(), anyhow::Error>(anyhow::anyhow!("genuine")));
+}
+
+#[test]
+#[should_panic(expected = "got non-injected error")]
+fn test_genuine_error_is_not_injected() {
+    assert_injected_by!(
+        Err::<(), _>(io::Error::other("genuine")),
+        "create temp file"
+    );
+}
+
+#[test]
+#[should_panic(expected = "got Ok")]
+fn test_ok_is_not_injected() {
+    assert_injected_by!(Ok::<(), io::Error>(()), "create temp file");
+}
+
+#[test]
+#[should_panic(expected = "got one injected by `create temp file`")]
+fn test_wrong_failpoint() {
+    for_each_error(|err| {
+        assert_injected_by!(Err::<(), _>(err), "write temp file");
+    });
+}
+
+#[test]
+#[should_panic(expected = "expected genuine error")]
+fn test_injected_is_not_genuine() {
+    for_each_error(|err| {
+        assert_not_injected!(Err::<(), _>(err));
+    });
+}
@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io;
+
+use faine::{
+    Runner, inject_override, inject_override_io_error, inject_override_with_side_effect,
+    inject_return, inject_return_io_error,
+};
+
+#[test]
+fn test_audit_injection_purity() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn make_error(n: usize) -> String {
+        format!("error {n}")
+    }
+
+    fn impure() -> Result<(), String> {
+        inject_return!(
+            "impure",
+            Err(make_error(COUNTER.fetch_add(1, Ordering::Relaxed)))
+        );
+        Ok(())
+    }
+
+    fn pure() -> Result<(), String> {
+        inject_return!("pure", Err(make_error(0)));
+        Ok(())
+    }
+
+    fn overridden() -> Result<usize, String> {
+        inject_override!(
+            Ok(1),
+            "override",
+            Err(make_error(COUNTER.load(Ordering::Relaxed)))
+        )
+    }
+
+    fn io() -> io::Result<()> {
+        inject_return_io_error!("io");
+        Ok(())
+    }
+
+    let run = |audit| {
+        Runner::ignore_env()
+            .with_audit_injection_purity(audit)
+            .with_error_tagging(true)
+            .run(|| {
+                let _ = pure();
+                let _ = io();
+                let _ = impure();
+                let _ = overridden();
+            })
+            .unwrap()
+    };
+
+    let report = run(false);
+    assert!(report.impure_injections().is_empty());
+    assert!(!report.to_string().contains("impure injection"));
+
+    let report = run(true);
+    let impure = report.impure_injections();
+    assert_eq!(
+        impure
+            .iter()
+            .map(|impure| impure.failpoint)
+            .collect::<Vec<_>>(),
+        ["impure", "override"]
+    );
+    assert!(
+        impure[0]
+            .to_string()
+            .starts_with("impure injection at impure: first saw Err(\"error ")
+    );
+    assert_eq!(
+        impure[0].later_path.steps.last().unwrap().to_string(),
+        "[impure]"
+    );
+    assert!(report.to_string().contains("2 impure injection(s)"));
+    assert!(format!("{report:#}").contains("\n    later on: "));
+}
+
+#[test]
+fn test_audit_injections() {
+    struct Opaque;
+
+    fn load() -> io::Result<Vec<u8>> {
+        inject_override!(
+            Ok::<_, io::Error>(vec![1]),
+            "read cache",
+            Ok(vec![]),
+            intentional_success: true
+        )?;
+        inject_override_io_error!(Ok(vec![2]), "read data")?;
+        let _ = inject_override!(Opaque, "probe", Opaque);
+        inject_override_with_side_effect!(Ok(vec![3]), "read config", Ok(vec![]))
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = load();
+        })
+        .unwrap();
+    assert!(report.injection_audit().is_none());
+
+    let report = Runner::ignore_env()
+        .with_audit_injections(true)
+        .run(|| {
+            let _ = load();
+        })
+        .unwrap();
+    let audit = report.injection_audit().unwrap();
+    assert_eq!(audit["read cache"].success, 5);
+    assert!(!audit["read cache"].is_unintended_success());
+    assert_eq!(audit["read data"].failure, 2);
+    assert_eq!(audit["probe"].unclassified, 4);
+    assert_eq!(audit["read config"].success, 4);
+    assert_eq!(report.unintended_success_injections(), ["read config"]);
+    assert!(
+        report
+            .to_string()
+            .contains(", 1 unintended success injection(s)")
+    );
+    assert!(
+        format!("{report:#}").contains("\n  success injected: read config (4 of 4 activation(s))")
+    );
+}
@@ -0,0 +1,7 @@
+#[path = "src/codegen.rs"]
+mod codegen;
+
+fn main() {
+    codegen::generate(|name| std::env::var(name).ok(), &mut std::io::stdout())
+        .expect("code generation failed");
+}
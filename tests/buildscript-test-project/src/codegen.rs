@@ -0,0 +1,67 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Failpoint which is only compiled in for unit tests, so the build
+/// script itself does not depend on faine
+macro_rules! failpoint {
+    ($name:literal) => {
+        #[cfg(test)]
+        faine::inject_return_io_error!($name);
+    };
+}
+
+/// Fetch data to generate code from
+fn fetch_table() -> io::Result<Vec<(&'static str, u32)>> {
+    // stands for a network request
+    failpoint!("fetch table");
+    Ok(vec![("answer", 42), ("dozen", 12)])
+}
+
+/// Generate code into `OUT_DIR`, atomically
+pub fn generate(env: impl Fn(&str) -> Option<String>, out: &mut impl Write) -> io::Result<()> {
+    let out_dir = env("OUT_DIR").ok_or_else(|| io::Error::other("OUT_DIR is not set"))?;
+    let table = fetch_table()?;
+    let code: String = table
+        .iter()
+        .map(|(name, value)| format!("pub const {}: u32 = {value};\n", name.to_uppercase()))
+        .collect();
+
+    let path = Path::new(&out_dir).join("table.rs");
+    let temp_path = path.with_extension("tmp");
+    failpoint!("write temp file");
+    fs::write(&temp_path, code)?;
+    failpoint!("replace file");
+    fs::rename(&temp_path, &path)?;
+    writeln!(out, "cargo::rerun-if-changed=build.rs")
+}
+
+#[cfg(test)]
+mod tests {
+    use faine::Runner;
+    use faine::for_buildscripts::{BuildEnv, parse_directives};
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let report = Runner::default()
+            .with_managed_tempdir()
+            .run(|| {
+                let env = BuildEnv::new(faine::tempdir());
+                let mut out = vec![];
+                let res = generate(|name| env.var(name), &mut out);
+                let generated = fs::read_to_string(faine::tempdir().join("table.rs")).ok();
+                let directives = parse_directives(str::from_utf8(&out).unwrap());
+                if res.is_ok() {
+                    assert!(generated.unwrap().contains("pub const ANSWER: u32 = 42;"));
+                    assert_eq!(directives[0].key, "rerun-if-changed");
+                } else {
+                    assert_eq!(generated, None);
+                    assert!(directives.is_empty());
+                }
+            })
+            .unwrap();
+        assert_eq!(report.num_paths(), 4);
+    }
+}
@@ -0,0 +1,13 @@
+// Code generation logic is tested along with the library
+#[cfg(test)]
+mod codegen;
+
+include!(concat!(env!("OUT_DIR"), "/table.rs"));
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_generated() {
+        assert_eq!(super::ANSWER, 42);
+    }
+}
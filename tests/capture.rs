@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// Output capture redirects process-wide file descriptors, so it's
+// tested in a separate binary with a single test.
+
+use std::io::{self, Write};
+
+use faine::{Runner, captured_stderr, captured_stdout, inject_return_io_error};
+
+fn foo() -> io::Result<()> {
+    // note that println! would be captured by libtest instead
+    io::stdout().write_all(b"starting\n").unwrap();
+    let res = (|| -> io::Result<()> {
+        inject_return_io_error!("write");
+        Ok(())
+    })();
+    if let Err(err) = &res {
+        io::stderr()
+            .write_all(format!("ERROR: {err}\n").as_bytes())
+            .unwrap();
+    }
+    res
+}
+
+#[test]
+fn test_capture_output() {
+    let report = Runner::default()
+        .with_capture_output(true)
+        .run(|| {
+            let res = foo();
+            if cfg!(unix) {
+                assert_eq!(captured_stdout(), "starting\n");
+                let error_lines = captured_stderr()
+                    .lines()
+                    .filter(|line| line.starts_with("ERROR"))
+                    .count();
+                assert_eq!(error_lines, if res.is_err() { 1 } else { 0 });
+            }
+        })
+        .unwrap();
+
+    assert_eq!(report.output_captured(), cfg!(unix));
+    assert_eq!(captured_stderr(), "");
+    if cfg!(unix) {
+        let outputs: Vec<_> = report
+            .paths()
+            .iter()
+            .map(|path| path.captured_output.clone().unwrap())
+            .collect();
+        assert_eq!(outputs[0].stdout, "starting\n");
+        assert_eq!(outputs[0].stderr, "ERROR: write\n");
+        assert_eq!(outputs[1].stderr, "");
+    }
+}
@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// Claims output capture, so it's tested in a separate binary, along
+// with runners exploring concurrently.
+
+use std::sync::{Barrier, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use faine::{Error, Runner, active_runners, axis, checked, inject_return, record_outcome};
+
+const NUM_THREADS: usize = 16;
+const NUM_RUNS: usize = 20;
+
+fn save() -> Result<(), ()> {
+    inject_return!("open", Err(()));
+    inject_return!("write", Err(()));
+    Ok(())
+}
+
+fn explore(index: usize, global_features: bool) {
+    let var: &'static str = Box::leak(format!("FAINE_CONCURRENCY_TEST_{index}").into_boxed_str());
+    let mut runner = Runner::ignore_env();
+    if global_features {
+        runner = runner
+            .with_env_axis(var, &[None, Some("set")])
+            .with_hygiene_report(true);
+    }
+    let report = runner
+        .run(|| {
+            if global_features {
+                let set = std::env::var(var).ok().as_deref() == Some("set");
+                assert_eq!(set, axis(&format!("${var}")) == "\"set\"");
+            }
+            assert!(
+                active_runners()
+                    .iter()
+                    .any(|runner| runner.thread_id == thread::current().id())
+            );
+            let res = save();
+            checked!("saved");
+            record_outcome(res);
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), if global_features { 6 } else { 3 });
+    assert!(report.paths().iter().all(|path| path.panic.is_none()));
+    assert!(std::env::var_os(var).is_none());
+}
+
+fn stress(global_features: bool) {
+    thread::scope(|scope| {
+        for index in 0..NUM_THREADS {
+            scope.spawn(move || {
+                for _ in 0..NUM_RUNS {
+                    explore(index, global_features);
+                }
+            });
+        }
+    });
+    assert!(
+        active_runners()
+            .iter()
+            .all(|runner| runner.thread_id != thread::current().id())
+    );
+}
+
+#[test]
+fn test_concurrent_runners() {
+    stress(false);
+}
+
+#[test]
+fn test_concurrent_runners_with_global_features() {
+    stress(true);
+}
+
+#[test]
+fn test_exclusive_runner() {
+    let started = Barrier::new(2);
+    let order = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .name("capturing".to_string())
+            .spawn_scoped(scope, || {
+                Runner::ignore_env()
+                    .with_capture_output(true)
+                    .run(|| {
+                        // no failpoints, so there is a single iteration
+                        started.wait();
+                        thread::sleep(Duration::from_millis(50));
+                        order.lock().unwrap().push("capturing");
+                    })
+                    .unwrap();
+            })
+            .unwrap();
+
+        started.wait();
+        // waits for the capturing runner to finish
+        Runner::ignore_env()
+            .run(|| order.lock().unwrap().push("other"))
+            .unwrap();
+    });
+    if cfg!(unix) {
+        assert_eq!(*order.lock().unwrap(), ["capturing", "other"]);
+    }
+
+    // nested runner would wait forever, so it fails instead
+    let res = Runner::ignore_env()
+        .with_capture_output(true)
+        .run(|| {
+            let res = Runner::ignore_env().run(|| {}).into_result();
+            if cfg!(unix) {
+                match res {
+                    Err(Error::ConflictingRunner {
+                        feature,
+                        other_test_thread,
+                    }) => {
+                        assert_eq!(feature, "output capture");
+                        assert_eq!(other_test_thread, "test_exclusive_runner");
+                    }
+                    _ => panic!("conflicting runner was not detected"),
+                }
+            } else {
+                assert!(res.is_ok());
+            }
+        })
+        .into_result();
+    assert!(res.is_ok());
+}
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io;
+
+use faine::{
+    Runner, const_compatible, inject_exhaust, inject_override, inject_override_io_error,
+    inject_perturb, inject_return, inject_return_io_error,
+};
+
+const_compatible! {
+    fn checked_sum(a: u32, b: u32) -> Option<u32> as const checked_sum_const {
+        inject_exhaust!("overflow");
+        inject_return!("sum", None);
+        inject_return!(None);
+        let b = inject_perturb!(b, "b", perturbations: [u32::MAX]);
+        a.checked_add(inject_override!(b, "override", 0))
+    }
+}
+
+const_compatible! {
+    pub(crate) fn open(ok: bool) -> io::Result<bool> as const open_const {
+        inject_return_io_error!("open");
+        inject_override_io_error!(Ok(ok), "read")
+    }
+}
+
+const SUM: Option<u32> = checked_sum_const(1, 2);
+static OPENED: io::Result<bool> = open_const(true);
+
+#[test]
+fn test_const_usage() {
+    assert_eq!(SUM, Some(3));
+    assert!(matches!(OPENED, Ok(true)));
+    const { assert!(checked_sum_const(u32::MAX, 1).is_none()) };
+}
+
+#[test]
+fn test_runtime_usage() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| results.push(checked_sum(1, 2)))
+        .unwrap();
+    let activated = report.activated_failpoints();
+    assert_eq!(activated.len(), 5);
+    assert!(
+        ["b", "overflow", "override", "sum"]
+            .iter()
+            .all(|name| activated.contains(name))
+    );
+    assert!(results.contains(&None));
+    assert!(results.contains(&Some(1)));
+    assert!(results.contains(&Some(3)));
+
+    let mut results = vec![];
+    Runner::default()
+        .run(|| results.push(open(true).is_ok()))
+        .unwrap();
+    assert_eq!(results, vec![false, false, true]);
+}
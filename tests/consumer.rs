@@ -4,10 +4,86 @@
 use std::process::Command;
 
 #[test]
-fn test_consumer() {
+fn test_integration_example() {
     let success = Command::new("cargo")
-        .args(["test"])
-        .current_dir("tests/consumer-test-project")
+        .args(["test", "--features=faine"])
+        .current_dir("examples/integration")
+        .status()
+        .expect("failed to build or run test project")
+        .success();
+    if !success {
+        panic!("failed to build or run test project");
+    }
+}
+
+#[test]
+fn test_integration_example_requires_instrumentation() {
+    let output = Command::new("cargo")
+        .args(["check", "--tests"])
+        .current_dir("examples/integration")
+        .output()
+        .expect("failed to build test project");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("FAINE_INSTRUMENTATION_ENABLED"));
+}
+
+#[test]
+fn test_capi_example() {
+    let success = Command::new("cargo")
+        .arg("test")
+        .current_dir("examples/capi")
+        .status()
+        .expect("failed to build or run test project")
+        .success();
+    if !success {
+        panic!("failed to build or run test project");
+    }
+}
+
+#[test]
+fn test_async_example() {
+    let success = Command::new("cargo")
+        .arg("test")
+        .current_dir("examples/async")
+        .status()
+        .expect("failed to build or run test project")
+        .success();
+    if !success {
+        panic!("failed to build or run test project");
+    }
+}
+
+#[test]
+fn test_multicrate_example() {
+    let success = Command::new("cargo")
+        .arg("test")
+        .current_dir("examples/multicrate")
+        .status()
+        .expect("failed to build or run test project")
+        .success();
+    if !success {
+        panic!("failed to build or run test project");
+    }
+}
+
+#[test]
+fn test_buildscript_test_project() {
+    let success = Command::new("cargo")
+        .arg("test")
+        .current_dir("tests/buildscript-test-project")
+        .status()
+        .expect("failed to build or run test project")
+        .success();
+    if !success {
+        panic!("failed to build or run test project");
+    }
+}
+
+#[test]
+fn test_procmacro_test_project() {
+    let success = Command::new("cargo")
+        .arg("test")
+        .current_dir("tests/procmacro-test-project")
         .status()
         .expect("failed to build or run test project")
         .success();
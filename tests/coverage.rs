@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::{Runner, coverage, inject_return};
+
+fn foo(deep: bool) -> Result<(), usize> {
+    inject_return!("common", Err(1));
+    if deep {
+        inject_return!("deep", Err(2));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_heatmap() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    Runner::default()
+        .with_coverage_dir(tempdir.path())
+        .run(|| {
+            let _ = foo(false);
+        })
+        .unwrap();
+    Runner::default()
+        .with_coverage_dir(tempdir.path())
+        .with_test_name("explicit name")
+        .run(|| {
+            let _ = foo(true);
+        })
+        .unwrap();
+
+    let heatmap = coverage::heatmap(tempdir.path()).unwrap();
+
+    let failpoints: Vec<_> = heatmap.failpoints().collect();
+    assert_eq!(failpoints.len(), 2);
+    assert_eq!(failpoints[0].name, "common");
+    assert_eq!(failpoints[0].file(), "tests/coverage.rs");
+    assert_eq!(failpoints[0].reached, 5);
+    assert_eq!(failpoints[0].activated, 2);
+    assert_eq!(
+        failpoints[0].tests.iter().collect::<Vec<_>>(),
+        vec!["coverage::explicit name", "coverage::test_heatmap"]
+    );
+
+    let single: Vec<_> = heatmap
+        .single_test_failpoints()
+        .into_iter()
+        .map(|failpoint| failpoint.name.as_str())
+        .collect();
+    assert_eq!(single, vec!["deep"]);
+
+    let files = heatmap.files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].failpoints, 2);
+
+    assert!(heatmap.to_string().contains("tests/coverage.rs"));
+    assert!(heatmap.to_json().contains(r#""name":"deep""#));
+}
@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::{Runner, inject_return, interrupt};
+
+fn step(name: &'static str) -> Result<(), usize> {
+    inject_return!(name, Err(1));
+    Ok(())
+}
+
+fn steps() {
+    let _ = step("a");
+    let _ = step("b");
+    let _ = step("c");
+}
+
+// single test, as interruption affects all concurrent explorations
+#[test]
+fn test_graceful_interrupt() {
+    let mut num_iterations = 0;
+    let report = Runner::default()
+        .with_graceful_interrupt(true)
+        .run(|| {
+            steps();
+            num_iterations += 1;
+            if num_iterations == 2 {
+                interrupt();
+            }
+        })
+        .unwrap();
+    assert!(report.is_interrupted());
+    assert!(report.is_incomplete());
+    assert_eq!(report.paths().len(), 2);
+    assert!(
+        report
+            .to_string()
+            .contains("stopped early due to interrupt")
+    );
+
+    // real signal
+    let report = Runner::default()
+        .with_graceful_interrupt(true)
+        .run(|| {
+            steps();
+            // SAFETY: raise() is safe to call
+            unsafe {
+                libc::raise(libc::SIGINT);
+            }
+        })
+        .unwrap();
+    assert!(report.is_interrupted());
+    assert_eq!(report.paths().len(), 1);
+
+    // not enabled
+    let report = Runner::default()
+        .run(|| {
+            steps();
+            interrupt();
+        })
+        .unwrap();
+    assert!(!report.is_interrupted());
+    assert_eq!(report.paths().len(), 8);
+}
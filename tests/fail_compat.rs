@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::fail_compat::{self, fail_point};
+use faine::{FailpointId, Runner};
+
+fn read(name: &str) -> Result<String, String> {
+    fail_point!("compat read", |arg: Option<String>| Ok(
+        arg.unwrap_or_default()
+    ));
+    fail_point!("compat parse", !name.is_empty(), |_| Err(
+        "parse failed".to_string()
+    ));
+    fail_point!("compat plain");
+    Ok(name.to_string())
+}
+
+#[test]
+fn test_return_and_err_points() {
+    let mut results = vec![];
+    let report = Runner::ignore_env()
+        .run(|| {
+            results.push(read("data"));
+        })
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            Ok(String::new()),
+            Err("parse failed".to_string()),
+            Ok("data".to_string())
+        ]
+    );
+    // plain point without configuration is not explored
+    assert_eq!(report.failpoints().len(), 2);
+    // points belong to the crate they are defined in, as with macros
+    assert_eq!(
+        FailpointId::intern("compat read").crate_name(),
+        Some(env!("CARGO_PKG_NAME"))
+    );
+}
+
+#[test]
+fn test_configured_points() {
+    fn write() -> Result<(), String> {
+        fail_point!("compat configured", |arg: Option<String>| Err(arg.unwrap()));
+        fail_point!("compat configured plain");
+        Ok(())
+    }
+
+    fail_compat::cfg("compat configured", "50%return(disk full)").unwrap();
+    fail_compat::cfg("compat configured plain", "return").unwrap();
+    assert!(fail_compat::cfg("compat configured other", "explode").is_err());
+
+    let mut results = vec![];
+    Runner::ignore_env()
+        .run(|| {
+            results.push(write());
+        })
+        .unwrap();
+    assert_eq!(results, vec![Err("disk full".to_string()), Ok(())]);
+
+    let issues: Vec<String> = fail_compat::migration_issues()
+        .iter()
+        .map(ToString::to_string)
+        .filter(|issue| issue.starts_with("compat configured"))
+        .collect();
+    assert_eq!(
+        issues,
+        vec![
+            "compat configured = 50%return(disk full): probability 50% is replaced by exploration",
+            "compat configured plain = return: point has no closure to return with, never activated",
+        ]
+    );
+}
+
+#[test]
+fn test_configuration_is_thread_local() {
+    fn write() -> Result<(), String> {
+        fail_point!("compat threaded", |arg: Option<String>| Err(
+            arg.unwrap_or_default()
+        ));
+        Ok(())
+    }
+
+    let explore = |config: &'static str| {
+        move || {
+            fail_compat::cfg("compat threaded", config).unwrap();
+            let mut results = vec![];
+            Runner::ignore_env()
+                .run(|| {
+                    results.push(write());
+                })
+                .unwrap();
+            results
+        }
+    };
+    let first = std::thread::spawn(explore("return(first)"));
+    let second = std::thread::spawn(explore("return(second)"));
+    assert_eq!(
+        first.join().unwrap(),
+        vec![Err("first".to_string()), Ok(())]
+    );
+    assert_eq!(
+        second.join().unwrap(),
+        vec![Err("second".to_string()), Ok(())]
+    );
+}
@@ -0,0 +1,81 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use faine::{Runner, assert_injected_by, fake_impl};
+
+#[derive(Clone, Debug, PartialEq)]
+struct User {
+    name: String,
+}
+
+impl User {
+    fn test() -> Self {
+        Self {
+            name: "alice".to_string(),
+        }
+    }
+}
+
+#[fake_impl]
+trait UserService {
+    fn fetch_user(&self, id: u64) -> io::Result<User>;
+    fn store_user(&mut self, user: &User) -> io::Result<()>;
+    fn count(&self) -> usize;
+    fn describe(&self) -> String {
+        "user service".to_string()
+    }
+}
+
+fn rename_user(service: &mut dyn UserService, id: u64) -> io::Result<User> {
+    let mut user = service.fetch_user(id)?;
+    user.name = user.name.to_uppercase();
+    service.store_user(&user)?;
+    Ok(user)
+}
+
+#[test]
+fn test_fake_impl() {
+    let stored = AtomicUsize::new(0);
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            let mut service = FaineFakeUserService::new()
+                .on_fetch_user(|| Ok(User::test()))
+                .on_store_user(|| Ok(()));
+            assert_eq!(service.describe(), "user service");
+            let res = rename_user(&mut service, 1);
+            if res.is_err() {
+                assert_injected_by!(res, prefix = "UserService::");
+            } else {
+                stored.fetch_add(1, Ordering::Relaxed);
+            }
+            results.push(res.ok().map(|user| user.name));
+        })
+        .unwrap();
+
+    assert_eq!(
+        report
+            .activated_failpoints()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["UserService::fetch_user", "UserService::store_user"]
+    );
+    assert_eq!(results, vec![None, None, Some("ALICE".to_string())]);
+    assert_eq!(stored.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+#[should_panic(expected = "FaineFakeUserService::fetch_user() has no value configured")]
+fn test_fake_impl_unconfigured() {
+    let service = FaineFakeUserService::default();
+    let _ = service.fetch_user(1);
+}
+
+#[test]
+#[should_panic(expected = "UserService::count() is not implemented")]
+fn test_fake_impl_infallible() {
+    FaineFakeUserService::default().count();
+}
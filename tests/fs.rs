@@ -0,0 +1,368 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use faine::fs::{
+    DurabilityModel, FsBackend, FsFile, METADATA, MemFs, MetadataPerturbation, ModelFs, RealFs,
+};
+use faine::{Error, FailpointId, Runner};
+
+/// File contents cached until the file changes
+struct CachedFile {
+    path: PathBuf,
+    cached: Option<(SystemTime, String)>,
+    // reload only when the file looks newer, which misses files
+    // restored from backups, and clocks going backwards
+    newer_only: bool,
+}
+
+impl CachedFile {
+    fn new(path: &Path, newer_only: bool) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            cached: None,
+            newer_only,
+        }
+    }
+
+    fn get(&mut self) -> io::Result<&str> {
+        let modified = faine::fs::metadata(&self.path)?.modified()?;
+        let stale = match &self.cached {
+            None => true,
+            Some((cached, _)) if self.newer_only => modified > *cached,
+            Some((cached, _)) => modified != *cached,
+        };
+        if stale {
+            self.cached = Some((modified, std::fs::read_to_string(&self.path)?));
+        }
+        Ok(&self.cached.as_ref().unwrap().1)
+    }
+}
+
+fn write(path: &Path, content: &str, modified: SystemTime) {
+    let mut file = File::create(path).unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+    file.set_modified(modified).unwrap();
+}
+
+fn check_cache(newer_only: bool) {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("config");
+    let now = SystemTime::now();
+
+    write(&path, "old", now);
+    let mut cache = CachedFile::new(&path, newer_only);
+    assert_eq!(cache.get().unwrap(), "old");
+
+    write(&path, "new", now + Duration::from_secs(10));
+    assert_eq!(cache.get().unwrap(), "new");
+}
+
+#[test]
+fn test_mtime_cache() {
+    // each of two metadata queries is explored with 4 perturbations
+    let report = Runner::ignore_env().run(|| check_cache(false)).unwrap();
+    assert_eq!(report.num_paths(), 25);
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| check_cache(true))
+        .unwrap_err();
+    let Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err}");
+    };
+    let failing: Vec<String> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .map(|path| path.key())
+        .collect();
+    // cache goes stale whenever the new file does not look newer
+    assert_eq!(failing.len(), 7);
+    assert!(failing.iter().all(|path| path.contains("=mtime in ")));
+    assert!(failing.contains(&"[fs metadata=mtime in past]".to_string()));
+}
+
+#[test]
+fn test_perturbation_parameters() {
+    let report = Runner::ignore_env()
+        .with_metadata_perturbation(
+            METADATA,
+            MetadataPerturbation::default().with_mtime_offset(Duration::from_secs(5)),
+        )
+        .with_catch_panics(true)
+        .run(|| check_cache(true))
+        .unwrap_err();
+    let Error::UnexpectedPanics { report, .. } = report else {
+        panic!("unexpected error");
+    };
+    // with files 10 seconds apart, a single 5 second perturbation
+    // is not enough, only moving them towards each other is
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .filter(|path| path.is_failed())
+            .count(),
+        1
+    );
+}
+
+/// Replace a file atomically, optionally forgetting to sync the directory
+fn atomic_replace(dir: &Path, data: &str, sync_dir: bool) -> io::Result<()> {
+    let (tmp, target) = (dir.join("data.tmp"), dir.join("data"));
+    faine::fs::write(&tmp, data)?;
+    faine::fs::sync_all(&tmp)?;
+    faine::fs::rename(&tmp, &target)?;
+    if sync_dir {
+        faine::fs::sync_all(dir)?;
+    }
+    Ok(())
+}
+
+fn check_atomic_replace(dir: &Path, sync_dir: bool) {
+    let target = dir.join("data");
+    let _ = std::fs::remove_file(dir.join("data.tmp"));
+    std::fs::write(&target, "old").unwrap();
+
+    let res = atomic_replace(dir, "new", sync_dir);
+    let surviving = faine::fs::surviving_state().within(dir);
+    // temporary file may be left over, but the target is always intact
+    assert!(surviving.len() <= 2);
+    match surviving.get(&target) {
+        Some(b"new") => {}
+        Some(b"old") => assert!(res.is_err(), "completed replacement is not durable"),
+        other => panic!("unexpected surviving contents {other:?}"),
+    }
+    if res.is_ok() {
+        let actual = faine::fs::ModelFs::load(dir).unwrap();
+        assert_eq!(actual.get(&target), Some(&b"new"[..]));
+    }
+}
+
+#[test]
+fn test_durability() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let report = Runner::ignore_env()
+        .run(|| check_atomic_replace(dir.path(), true))
+        .unwrap();
+    assert_eq!(report.num_paths(), 5);
+    // wrappers report the location of their caller, as macros do
+    let location = report.failpoint_location(faine::fs::WRITE).unwrap();
+    assert!(location.starts_with("tests/fs.rs:"));
+    assert_eq!(location.matches(':').count(), 2);
+    // wrappers' failpoints belong to faine
+    assert_eq!(
+        FailpointId::intern(faine::fs::WRITE).crate_name(),
+        Some("faine")
+    );
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| check_atomic_replace(dir.path(), false))
+        .unwrap_err();
+    let Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err}");
+    };
+    let failing: Vec<String> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .map(|path| path.key())
+        .collect();
+    assert_eq!(failing, ["fs write → fs sync → fs rename"]);
+
+    // every completed operation survives
+    Runner::ignore_env()
+        .with_durability_model(DurabilityModel::WriteThrough)
+        .run(|| check_atomic_replace(dir.path(), false))
+        .unwrap();
+}
+
+#[test]
+fn test_fixture_tree() {
+    let source = tempfile::tempdir().unwrap();
+    std::fs::create_dir(source.path().join("db")).unwrap();
+    for i in 0..10 {
+        std::fs::write(source.path().join(format!("db/{i}")), i.to_string()).unwrap();
+    }
+
+    let report = Runner::ignore_env()
+        .with_fixture_tree(source.path())
+        .run(|| {
+            let dir = faine::fs::fixture_dir().join("db");
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 10);
+            for i in 0..10 {
+                let path = dir.join(i.to_string());
+                assert_eq!(std::fs::read_to_string(&path).unwrap(), i.to_string());
+                if faine::fs::write(&path, "updated").is_err() {
+                    break;
+                }
+            }
+            std::fs::write(dir.join("extra"), "").unwrap();
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 11);
+    let timing = report.fixture_timing().unwrap();
+    assert_eq!(timing.resets, 11);
+    assert!(timing.reset_duration <= timing.total_duration);
+    assert!(format!("{report:#}").contains("\n  fixture: 11 reset(s) took "));
+    // source is left intact
+    assert_eq!(
+        std::fs::read_to_string(source.path().join("db/0")).unwrap(),
+        "0"
+    );
+}
+
+/// Same as `atomic_replace()`, generic over the backend
+fn atomic_replace_with<F: FsBackend>(
+    fs: &F,
+    dir: &Path,
+    data: &str,
+    sync_dir: bool,
+) -> io::Result<()> {
+    let (tmp, target) = (dir.join("data.tmp"), dir.join("data"));
+    fs.write(&tmp, data.as_bytes())?;
+    fs.sync_all(&tmp)?;
+    fs.rename(&tmp, &target)?;
+    if sync_dir {
+        fs.sync_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Keys of explored paths, and whether they have failed
+fn explore_atomic_replace<F: FsBackend>(
+    dir: &Path,
+    sync_dir: bool,
+    prepare: impl Fn() -> F,
+    surviving: impl Fn(&F) -> ModelFs,
+) -> Vec<(String, bool)> {
+    let report = match Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            let fs = prepare();
+            let res = atomic_replace_with(&fs, dir, "new", sync_dir);
+            let target = dir.join("data");
+            let visible = fs.read_to_string(&target).unwrap();
+            assert!(visible == "new" || res.is_err() && visible == "old");
+            match surviving(&fs).get(&target) {
+                Some(b"new") => {}
+                Some(b"old") => assert!(res.is_err(), "completed replacement is not durable"),
+                other => panic!("unexpected surviving contents {other:?}"),
+            }
+        })
+        .into_result()
+    {
+        Ok(report) => report,
+        Err(Error::UnexpectedPanics { report, .. }) => *report,
+        Err(err) => panic!("unexpected error {err}"),
+    };
+    report
+        .paths()
+        .iter()
+        .map(|path| (path.key(), path.is_failed()))
+        .collect()
+}
+
+#[test]
+fn test_mem_fs() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path();
+    for sync_dir in [true, false] {
+        let real = explore_atomic_replace(
+            dir,
+            sync_dir,
+            || {
+                let _ = std::fs::remove_file(dir.join("data.tmp"));
+                std::fs::write(dir.join("data"), "old").unwrap();
+                RealFs
+            },
+            |_| faine::fs::surviving_state(),
+        );
+        let mem = explore_atomic_replace(
+            dir,
+            sync_dir,
+            || {
+                let fs = MemFs::new();
+                fs.insert(dir.join("data"), "old");
+                fs
+            },
+            MemFs::surviving_state,
+        );
+        assert_eq!(mem, real);
+        assert_eq!(
+            mem.iter().any(|(_, failed)| *failed),
+            !sync_dir,
+            "missing directory sync must be caught"
+        );
+    }
+}
+
+/// Write a file into a staging directory, and move the directory in place
+fn publish_with<F: FsBackend>(fs: &F, dir: &Path) -> io::Result<()> {
+    let (staging, published) = (dir.join("staging"), dir.join("published"));
+    fs.create_dir_all(&staging)?;
+    let mut file = fs.create(&staging.join("data"))?;
+    file.write_all(b"new")?;
+    file.sync_all()?;
+    fs.sync_all(&staging)?;
+    fs.rename(&staging, &published)?;
+    fs.sync_all(dir)
+}
+
+/// Keys of explored paths of `publish_with()`
+fn explore_publish<F: FsBackend>(
+    dir: &Path,
+    prepare: impl Fn() -> F,
+    surviving: impl Fn(&F) -> ModelFs,
+) -> Vec<String> {
+    let report = Runner::ignore_env()
+        .run(|| {
+            let fs = prepare();
+            let res = publish_with(&fs, dir);
+            let data = dir.join("published/data");
+            let surviving = surviving(&fs).get(&data).map(<[u8]>::to_vec);
+            if res.is_ok() {
+                assert_eq!(fs.read(&data).unwrap(), b"new");
+                assert_eq!(surviving.as_deref(), Some(&b"new"[..]));
+            } else {
+                assert!(matches!(surviving.as_deref(), None | Some(b"new")));
+            }
+        })
+        .unwrap();
+    report.paths().iter().map(|path| path.key()).collect()
+}
+
+#[test]
+fn test_mem_fs_file_handles() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path();
+    let real = explore_publish(
+        dir,
+        || {
+            for subdir in ["staging", "published"] {
+                let _ = std::fs::remove_dir_all(dir.join(subdir));
+            }
+            RealFs
+        },
+        |_| faine::fs::surviving_state(),
+    );
+    let mem = explore_publish(
+        dir,
+        || {
+            let fs = MemFs::new();
+            fs.create_dir_all(dir).unwrap();
+            fs
+        },
+        MemFs::surviving_state,
+    );
+    // create, write, two syncs, rename, and final sync may fail
+    assert_eq!(real.len(), 7);
+    assert_eq!(mem, real);
+}
@@ -0,0 +1,67 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::fuzz::{repro_from_bytes, run_from_bytes};
+use faine::{Error, Runner, activated_names, inject_override, inject_return};
+
+fn transfer() -> Result<(), ()> {
+    inject_return!("debit", Err(()));
+    inject_return!("credit", Err(()));
+    Ok(())
+}
+
+fn transfer_with_rollback() {
+    if transfer().is_err() {
+        let rolled_back = inject_override!(true, "rollback", false);
+        // failed rollback after a successful debit loses money
+        assert!(rolled_back || activated_names()[0] == "debit", "money lost");
+    }
+}
+
+#[test]
+fn test_run_from_bytes() {
+    let decide = |data: &[u8]| {
+        let mut activated = vec![];
+        run_from_bytes(data, || {
+            let _ = transfer();
+            activated = activated_names();
+        });
+        activated
+    };
+    assert!(decide(&[]).is_empty());
+    assert_eq!(decide(&[1]), ["debit"]);
+    assert_eq!(decide(&[0, 3]), ["credit"]);
+    assert_eq!(decide(&[2, 4, 5]), Vec::<String>::new());
+
+    let res = std::panic::catch_unwind(|| run_from_bytes(&[0, 1, 1], transfer_with_rollback));
+    assert!(res.is_err());
+    run_from_bytes(&[0, 1, 0], transfer_with_rollback);
+}
+
+#[test]
+fn test_repro_from_bytes() {
+    let repro = repro_from_bytes(&[0, 1, 1], transfer_with_rollback);
+    assert_eq!(repro.key(), "debit → [credit] → [rollback]");
+    assert_eq!(repro.activations(), ["credit", "rollback"]);
+    assert!(repro.path().is_failed());
+    assert_eq!(
+        repro.to_string(),
+        "Runner::ignore_env()\n    .with_catch_panics(true)\n    .with_priority_paths(&[&[\"credit\", \"rollback\"]])\n    .with_max_iterations(1)"
+    );
+
+    // the configuration reproduces the failure deterministically
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_priority_paths(&[&["credit", "rollback"]])
+        .with_max_iterations(1)
+        .run(transfer_with_rollback)
+        .unwrap_err();
+    let Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(report.paths()[0].key(), repro.key());
+
+    let repro = repro_from_bytes(&[], transfer_with_rollback);
+    assert_eq!(repro.key(), "debit → credit");
+    assert!(!repro.path().is_failed());
+}
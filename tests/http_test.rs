@@ -0,0 +1,181 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeSet;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+use faine::Runner;
+use faine::http_test::{Response, TestServer};
+
+const BODY: &str = "the quick brown fox jumps over the lazy dog";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FetchError {
+    Timeout,
+    NoHeaders,
+    NoBody,
+    Truncated,
+    Malformed,
+}
+
+impl From<io::Error> for FetchError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => Self::Timeout,
+            _ => Self::Truncated,
+        }
+    }
+}
+
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, FetchError> {
+    let mut body = vec![];
+    loop {
+        let line_end = data
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or(FetchError::Truncated)?;
+        let size = std::str::from_utf8(&data[..line_end])
+            .ok()
+            .and_then(|size| usize::from_str_radix(size, 16).ok())
+            .ok_or(FetchError::Malformed)?;
+        data = &data[line_end + 2..];
+        if data.len() < size + 2 {
+            return Err(FetchError::Truncated);
+        }
+        if &data[size..size + 2] != b"\r\n" {
+            return Err(FetchError::Malformed);
+        }
+        if size == 0 {
+            return Ok(body);
+        }
+        body.extend_from_slice(&data[..size]);
+        data = &data[size + 2..];
+    }
+}
+
+/// Minimal HTTP client under test
+fn fetch(addr: SocketAddr, path: &str, timeout: Option<Duration>) -> Result<Vec<u8>, FetchError> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(timeout)?;
+    write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+
+    let head_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or(FetchError::NoHeaders)?;
+    let head = String::from_utf8_lossy(&response[..head_end]).to_lowercase();
+    let data = &response[head_end + 4..];
+    if head.contains("transfer-encoding: chunked") {
+        if data.is_empty() {
+            return Err(FetchError::NoBody);
+        }
+        return decode_chunked(data);
+    }
+    let length: usize = head
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length: "))
+        .and_then(|length| length.parse().ok())
+        .ok_or(FetchError::Malformed)?;
+    match data.len() {
+        0 if length > 0 => Err(FetchError::NoBody),
+        len if len < length => Err(FetchError::Truncated),
+        _ => Ok(data.to_vec()),
+    }
+}
+
+fn start_server() -> TestServer {
+    TestServer::builder()
+        .route("/plain", "plain", Response::new(200, BODY))
+        .route(
+            "/chunked",
+            "chunked",
+            Response::new(200, BODY).with_chunked(8),
+        )
+        .start()
+        .unwrap()
+}
+
+#[test]
+fn test_client_error_handling() {
+    let mut results = BTreeSet::new();
+    let report = Runner::ignore_env()
+        .run(|| {
+            let server = start_server();
+            for path in ["/plain", "/chunked"] {
+                let res = fetch(server.addr(), path, Some(Duration::from_millis(50)));
+                if let Ok(body) = &res {
+                    assert_eq!(body, BODY.as_bytes());
+                }
+                results.insert((path, res.map(|_| ())));
+            }
+        })
+        .unwrap();
+    // 4 plain by 5 chunked outcomes
+    assert_eq!(report.num_paths(), 20);
+    assert_eq!(
+        report.paths()[0].to_string(),
+        "[plain=drop_after_headers] → [chunked=drop_after_headers]"
+    );
+    let location = report.failpoint_location("plain").unwrap();
+    assert!(location.starts_with("tests/http_test.rs:"));
+    assert_eq!(location.matches(':').count(), 2);
+
+    use FetchError::*;
+    let expected: BTreeSet<_> = [
+        ("/plain", Ok(())),
+        ("/plain", Err(NoBody)),
+        ("/plain", Err(Truncated)),
+        ("/plain", Err(Timeout)),
+        ("/chunked", Ok(())),
+        ("/chunked", Err(NoBody)),
+        ("/chunked", Err(Truncated)),
+        ("/chunked", Err(Malformed)),
+        ("/chunked", Err(Timeout)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn test_retry_after_misbehavior() {
+    Runner::ignore_env()
+        .run(|| {
+            let server = TestServer::builder()
+                .route("/plain", "plain", Response::new(200, BODY))
+                .start()
+                .unwrap();
+            // misbehavior only applies to the first response of a route
+            let first = fetch(server.addr(), "/plain", Some(Duration::from_millis(50)));
+            let second = fetch(server.addr(), "/plain", Some(Duration::from_millis(50)));
+            assert_eq!(second, Ok(BODY.as_bytes().to_vec()));
+            assert_eq!(first.is_ok(), faine::activated_names().is_empty());
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_stall_ends_with_iteration_timeout() {
+    let start = Instant::now();
+    let report = Runner::ignore_env()
+        .with_iteration_timeout(Duration::from_millis(200))
+        .run(|| {
+            let server = TestServer::builder()
+                .route("/plain", "plain", Response::new(200, BODY))
+                .with_stall_timeout(Duration::from_secs(60))
+                .start()
+                .unwrap();
+            // client without timeout would otherwise hang for a minute
+            let _ = fetch(server.addr(), "/plain", None);
+        })
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(30));
+    assert_eq!(
+        report.paths().iter().filter(|path| path.timed_out).count(),
+        1
+    );
+}
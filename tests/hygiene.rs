@@ -0,0 +1,149 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Tests for failpoint macros used in unusual contexts
+//!
+//! Most of these are compile tests in fact: if the file compiles,
+//! the macros are usable in corresponding positions.
+
+use std::io;
+
+use faine::{
+    Runner, inject_override, inject_override_io_error, inject_return, inject_return_io_error,
+};
+
+fn run_collect<T: Ord>(mut func: impl FnMut() -> T) -> Vec<T> {
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            results.push(func());
+        })
+        .unwrap();
+    results.sort();
+    results
+}
+
+#[test]
+fn test_match_arm_expression() {
+    fn foo(v: usize) -> Result<(), usize> {
+        match v {
+            0 => inject_return!(Err(1)),
+            _ => inject_return!("hygiene match arm", Err(2)),
+        }
+        Ok(())
+    }
+
+    assert_eq!(run_collect(|| foo(0)), vec![Ok(()), Err(1)]);
+    assert_eq!(run_collect(|| foo(1)), vec![Ok(()), Err(2)]);
+}
+
+#[test]
+fn test_io_error_match_arm_expression() {
+    fn foo(v: usize) -> io::Result<()> {
+        match v {
+            0 => inject_return_io_error!(),
+            _ => inject_return_io_error!("hygiene io match arm"),
+        }
+        Ok(())
+    }
+
+    assert_eq!(run_collect(|| foo(0).is_ok()), vec![false, true]);
+    assert_eq!(run_collect(|| foo(1).is_ok()), vec![false, true]);
+}
+
+#[test]
+fn test_inside_matches() {
+    assert_eq!(
+        run_collect(|| matches!(inject_override!(1, 2), 1)),
+        vec![false, true]
+    );
+}
+
+#[test]
+fn test_inside_closure() {
+    let foo = || -> Result<(), usize> {
+        inject_return!(Err(1));
+        Ok(())
+    };
+    assert_eq!(run_collect(foo), vec![Ok(()), Err(1)]);
+}
+
+#[test]
+fn test_inside_user_macro() {
+    macro_rules! two_failpoints {
+        () => {{
+            inject_return!(Err(1));
+            inject_return!(Err(2));
+        }};
+    }
+
+    fn foo() -> Result<(), usize> {
+        two_failpoints!();
+        Ok(())
+    }
+
+    // both failpoints share the same autogenerated name, but must
+    // still be handled as distinct ones
+    assert_eq!(run_collect(foo), vec![Ok(()), Err(1), Err(2)]);
+}
+
+#[test]
+fn test_inside_user_macro_in_loop() {
+    macro_rules! same_line {
+        ($a:expr, $b:expr) => {
+            inject_override!($a, 0) + inject_override!($b, 0)
+        };
+    }
+
+    fn foo() -> usize {
+        let mut res = 0;
+        for _ in 0..2 {
+            res += same_line!(1, 10);
+        }
+        res
+    }
+
+    // 4 independent failpoints visits, so 16 combinations
+    assert_eq!(run_collect(foo).len(), 16);
+}
+
+mod reexport {
+    // user crate re-exporting faine macros under its own path
+    pub use faine::{inject_override_io_error as my_override, inject_return as my_return};
+}
+
+#[test]
+fn test_reexported_macros() {
+    fn foo() -> io::Result<usize> {
+        reexport::my_return!(Err(io::Error::other("injected")));
+        reexport::my_override!(Ok(1))
+    }
+
+    assert_eq!(run_collect(|| foo().is_ok()), vec![false, false, true]);
+}
+
+#[test]
+fn test_attribute_expanded_function() {
+    // attribute macros such as `#[tracing::instrument]` or
+    // `#[tokio::main]` move function body into a nested block or closure
+    #[tracing::instrument]
+    fn foo(v: usize) -> Result<(), usize> {
+        inject_return!(Err(v));
+        match v {
+            0 => inject_return!(Err(1)),
+            _ => inject_return!("hygiene instrumented", Err(2)),
+        }
+        Ok(())
+    }
+
+    assert_eq!(run_collect(|| foo(0)), vec![Ok(()), Err(0), Err(1)]);
+}
+
+#[test]
+fn test_override_io_error_unnamed() {
+    fn foo() -> io::Result<()> {
+        inject_override_io_error!(Ok(()))
+    }
+
+    assert_eq!(run_collect(|| foo().is_ok()), vec![false, true]);
+}
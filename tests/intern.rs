@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use faine::{FailpointId, Runner, inject_return, intern};
+
+#[test]
+fn test_interned_names() {
+    fn foo(names: &[FailpointId]) -> Result<(), usize> {
+        inject_return!(intern!("static"), Err(0));
+        for (i, name) in names.iter().enumerate() {
+            inject_return!(*name, Err(i + 1));
+        }
+        Ok(())
+    }
+
+    let names: Vec<_> = (0..2)
+        .map(|i| FailpointId::intern(&format!("dynamic {i}")))
+        .collect();
+
+    let mut results = vec![];
+    let report = Runner::default().run(|| results.push(foo(&names))).unwrap();
+
+    assert_eq!(results, vec![Err(0), Err(1), Err(2), Ok(())]);
+    assert_eq!(
+        report.failpoints().into_iter().collect::<Vec<_>>(),
+        vec!["dynamic 0", "dynamic 1", "static"]
+    );
+}
+
+fn measure(func: impl Fn() -> Result<(), ()>) -> Duration {
+    // each iteration of exploration replays visits up to the activated
+    // one, so the number of visits is quadratic
+    const ITERATIONS: usize = 1000;
+    let mut best = Duration::MAX;
+    for _ in 0..5 {
+        let start = Instant::now();
+        Runner::default()
+            .run(|| {
+                for _ in 0..ITERATIONS {
+                    if black_box(func()).is_err() {
+                        break;
+                    }
+                }
+            })
+            .unwrap();
+        best = best.min(start.elapsed());
+    }
+    best
+}
+
+#[test]
+#[ignore] // benchmark, run with --release
+fn test_visit_cost() {
+    fn with_str() -> Result<(), ()> {
+        inject_return!("failpoint with a reasonably long descriptive name", Err(()));
+        Ok(())
+    }
+
+    fn with_id() -> Result<(), ()> {
+        inject_return!(
+            intern!("failpoint with a reasonably long descriptive name"),
+            Err(())
+        );
+        Ok(())
+    }
+
+    let str_cost = measure(with_str);
+    let id_cost = measure(with_id);
+    eprintln!("visit cost: str {str_cost:?}, interned id {id_cost:?}");
+    assert!(
+        id_cost.as_secs_f64() <= str_cost.as_secs_f64() * 1.25,
+        "interned id visit is slower than str visit"
+    );
+}
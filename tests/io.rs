@@ -0,0 +1,161 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io::{self, Cursor, ErrorKind, Read, Write};
+
+use faine::Runner;
+use faine::io::{FaineReader, FaineWriter, Progress};
+
+/// Stream of frames, each prefixed with its length
+const STREAM: &[u8] = b"\x03foo\x05hello";
+
+/// Read a frame, retrying reads which have timed out once
+///
+/// Buggy: assumes a timed out read has consumed nothing.
+fn read_frame_retrying<R: Read>(reader: &mut FaineReader<R>) -> io::Result<Vec<u8>> {
+    fn read_exact_retrying<R: Read>(reader: &mut FaineReader<R>, buf: &mut [u8]) -> io::Result<()> {
+        match reader.read_exact(buf) {
+            Err(err) if err.kind() == ErrorKind::TimedOut => reader.read_exact(buf),
+            res => res,
+        }
+    }
+    let mut len = [0];
+    read_exact_retrying(reader, &mut len)?;
+    let mut frame = vec![0; len[0] as usize];
+    read_exact_retrying(reader, &mut frame)?;
+    Ok(frame)
+}
+
+/// Read a frame, treating timeouts as fatal, as stream position is
+/// unknown after them
+fn read_frame<R: Read>(reader: &mut FaineReader<R>) -> io::Result<Vec<u8>> {
+    let fatal = |err: io::Error| match err.kind() {
+        ErrorKind::TimedOut => io::Error::new(ErrorKind::BrokenPipe, "stream desynchronized"),
+        _ => err,
+    };
+    let mut len = [0];
+    reader.read_exact(&mut len).map_err(fatal)?;
+    let mut frame = vec![0; len[0] as usize];
+    reader.read_exact(&mut frame).map_err(fatal)?;
+    Ok(frame)
+}
+
+type Reader = FaineReader<Cursor<&'static [u8]>>;
+
+fn check_frames(read: fn(&mut Reader) -> io::Result<Vec<u8>>, progress: &[Progress]) -> usize {
+    Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            let mut reader = FaineReader::new("read", Cursor::new(STREAM)).with_progress(progress);
+            // each frame is either read intact, or an error is surfaced
+            for expected in [&b"foo"[..], b"hello"] {
+                match read(&mut reader) {
+                    Ok(frame) => assert_eq!(frame, expected, "stream desynchronized"),
+                    Err(_) => break,
+                }
+            }
+        })
+        .into_result()
+        .map(|report| {
+            report
+                .paths()
+                .iter()
+                .filter(|path| path.is_failed())
+                .count()
+        })
+        .unwrap_or(usize::MAX)
+}
+
+#[test]
+fn test_partial_progress_catches_desync() {
+    // plain error injection misses the bug
+    assert_eq!(check_frames(read_frame_retrying, &[Progress::None]), 0);
+    // partial progress injection catches it
+    assert_ne!(
+        check_frames(
+            read_frame_retrying,
+            &[Progress::None, Progress::Half, Progress::AllButOne]
+        ),
+        0
+    );
+    // correct parser passes either way
+    assert_eq!(
+        check_frames(
+            read_frame,
+            &[Progress::None, Progress::Half, Progress::AllButOne]
+        ),
+        0
+    );
+}
+
+#[test]
+fn test_read_exact_progress() {
+    let mut results = vec![];
+    let report = Runner::ignore_env()
+        .run(|| {
+            let mut reader = FaineReader::new("read", Cursor::new(b"hello".as_slice()));
+            let mut buf = [0; 5];
+            let res = reader.read_exact(&mut buf).map_err(|err| err.kind());
+            results.push((res, reader.into_inner().position()));
+        })
+        .unwrap();
+    assert_eq!(
+        results,
+        [
+            (Err(ErrorKind::TimedOut), 0),
+            (Err(ErrorKind::TimedOut), 2),
+            (Err(ErrorKind::TimedOut), 4),
+            (Ok(()), 5)
+        ]
+    );
+    assert_eq!(report.paths()[1].to_string(), "[read=timeout after half]");
+}
+
+#[test]
+fn test_write_all_progress() {
+    let mut results = vec![];
+    Runner::ignore_env()
+        .run(|| {
+            let mut writer = FaineWriter::new("write", vec![]).with_progress(&[Progress::Half]);
+            let res = writer.write_all(b"hello").map_err(|err| err.kind());
+            results.push((res, writer.into_inner()));
+        })
+        .unwrap();
+    assert_eq!(
+        results,
+        [
+            (Err(ErrorKind::TimedOut), b"he".to_vec()),
+            (Ok(()), b"hello".to_vec())
+        ]
+    );
+}
+
+#[test]
+fn test_trait_methods_inject() {
+    // code generic over Read and Write only sees trait methods
+    fn copy_header(mut reader: impl Read, mut writer: impl Write) -> io::Result<()> {
+        let mut header = [0; 4];
+        reader.read_exact(&mut header)?;
+        writer.write_all(&header)
+    }
+
+    let mut results = vec![];
+    Runner::ignore_env()
+        .run(|| {
+            let reader = FaineReader::new("read", Cursor::new(b"head".as_slice()))
+                .with_progress(&[Progress::None]);
+            let mut output = vec![];
+            let writer = FaineWriter::new("write", &mut output).with_progress(&[Progress::Half]);
+            let res = copy_header(reader, writer).map_err(|err| err.kind());
+            results.push((res, output));
+        })
+        .unwrap();
+    assert_eq!(
+        results,
+        [
+            (Err(ErrorKind::TimedOut), vec![]),
+            (Err(ErrorKind::TimedOut), b"he".to_vec()),
+            (Ok(()), b"head".to_vec())
+        ]
+    );
+}
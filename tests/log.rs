@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use faine::{Runner, inject_return};
+
+static RECORDS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        RECORDS
+            .lock()
+            .unwrap()
+            .push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+fn transfer(accounts: &mut [u32; 2], amount: u32) -> Result<(), ()> {
+    log::info!("transfer {amount}");
+    inject_return!("debit", Err(()));
+    accounts[0] -= amount;
+    log::trace!("debited {amount}");
+    // debit is not rolled back on failure
+    inject_return!("credit", Err(()));
+    accounts[1] += amount;
+    log::trace!("credited {amount}");
+    Ok(())
+}
+
+#[test]
+fn test_failure_log_level() {
+    log::set_logger(&Logger).unwrap();
+    log::set_max_level(LevelFilter::Info);
+
+    let report = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_expected_panics(&[("tests/log.rs", line!() + 5)])
+        .with_failure_log_level(LevelFilter::Trace)
+        .run(|| {
+            let mut accounts = [100, 0];
+            let _ = transfer(&mut accounts, 10);
+            assert_eq!(accounts.iter().sum::<u32>(), 100);
+        })
+        .unwrap();
+    assert_eq!(log::max_level(), LevelFilter::Info);
+
+    let failed: Vec<_> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .collect();
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0].reruns.as_ref().unwrap().verbose_logs);
+    assert!(failed[0].to_string().contains(", verbose logs in reruns"));
+
+    // 3 explored paths and a single rerun, only the latter is verbose
+    let records = RECORDS.lock().unwrap();
+    let count = |level| records.iter().filter(|(l, _)| *l == level).count();
+    assert_eq!(count(Level::Info), 4);
+    let trace: Vec<_> = records
+        .iter()
+        .filter(|(level, _)| *level == Level::Trace)
+        .map(|(_, message)| message.as_str())
+        .collect();
+    assert_eq!(trace, ["debited 10"]);
+}
@@ -1,7 +1,21 @@
 // SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use faine::{Branch, Runner, enable_failpoints, inject_return};
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::time::Duration;
+
+use faine::recorder::{any, repeated, unordered};
+use faine::{
+    After, AxisBudget, Branch, Component, DedupMode, Error, EventSink, ExecutionPath, Exhaust,
+    FailpointId, FailureClass, FaultModel, Markov, Outcome, Perturb, Phase, Prefix,
+    RecentEventKind, Recorder, Report, ResultExt, Rng, Runner, SafetyLimits, SafetyValve, Schedule,
+    Selector, Strategy, Suggestion, Tier, Weight, accumulate, activated_names, assert_injected_by,
+    axis, batch_cut, checked, checkpoint, clear_condition, count, count_by, current_iteration,
+    current_path, current_phase, enable_failpoints, failpoints, inject_exhaust, inject_override,
+    inject_override_rng, inject_perturb, inject_return, inject_return_io_error, intern, is_active,
+    iteration_timed_out, no_faults, param_f64, record_outcome, record_result, rng,
+};
 
 #[test]
 fn test_runner_with_no_failpoints() {
@@ -18,15 +32,44 @@ fn test_failpoints_outside_of_runner() {
 }
 
 #[test]
-#[ignore] // TODO: handle panics
 fn test_panic() {
-    // runner should catch this panic (or should it, how do we handle asserts?)
-    Runner::default()
-        .run(|| {
-            panic!("this panic should be caught");
-        })
+    fn foo() -> Result<(), usize> {
+        inject_return!("1", Err(1));
+        Ok(())
+    }
+
+    let panic_line = line!() + 2;
+    let body = || {
+        foo().expect("this panic should be caught");
+    };
+
+    // panic only terminates the iteration, and fails the run after
+    // all paths are explored
+    let err = Runner::default()
+        .with_catch_panics(true)
+        .run(body)
+        .unwrap_err();
+    let Error::UnexpectedPanics { locations, report } = err else {
+        panic!("unexpected error");
+    };
+    assert_eq!(locations, vec![format!("tests/main.rs:{panic_line}")]);
+    assert_eq!(report.num_paths(), 2);
+    let panicking = report.panicking_paths();
+    let paths: Vec<String> = panicking[&locations[0]]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(panicking.len(), 1);
+    assert_eq!(paths, vec![format!("[1] (panicked at {})", locations[0])]);
+
+    // expected panics do not fail the run
+    let report = Runner::default()
+        .with_expected_panics(&[("tests/main.rs", panic_line)])
+        .run(body)
         .unwrap();
-    // repeatable run should pass fine, e.g. there should be no
+    assert_eq!(report.panicking_paths().len(), 1);
+
+    // repeated run should pass fine, e.g. there should be no
     // leftover state from the panicked run
     Runner::default().run(|| {}).unwrap();
 }
@@ -107,3 +150,3891 @@ fn test_branch_preference_custom() {
 
     assert_eq!(results, vec![Ok(()), Err(1)]);
 }
+
+#[test]
+fn test_exhaust() {
+    fn foo() -> io::Result<()> {
+        inject_exhaust!("disk full");
+        inject_return_io_error!("write 1", fails_if: "disk full", kind: ErrorKind::StorageFull);
+        inject_return_io_error!("write 2", fails_if: "disk full", kind: ErrorKind::StorageFull);
+        Ok(())
+    }
+
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            results.push(foo().map_err(|err| err.kind()));
+        })
+        .unwrap();
+
+    // exhaustion makes first dependent failpoint fail without branching
+    assert_eq!(
+        results,
+        vec![
+            Err(ErrorKind::StorageFull),
+            Err(ErrorKind::StorageFull),
+            Err(ErrorKind::StorageFull),
+            Ok(())
+        ]
+    );
+}
+
+#[test]
+fn test_exhaust_clear_condition() {
+    fn write() -> io::Result<()> {
+        inject_return_io_error!("write", fails_if: "disk full");
+        Ok(())
+    }
+
+    fn foo() -> (bool, bool) {
+        inject_exhaust!("disk full", scope: Exhaust::UntilCleared);
+        let first = write().is_ok();
+        clear_condition("disk full");
+        let second = write().is_ok();
+        (first, second)
+    }
+
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            results.push(foo());
+        })
+        .unwrap();
+    results.sort();
+
+    // when the disk is full, the first write fails without branching,
+    // but the second one is explored normally
+    assert_eq!(
+        results,
+        vec![
+            (false, false),
+            (false, false),
+            (false, true),
+            (false, true),
+            (true, false),
+            (true, true)
+        ]
+    );
+}
+
+#[test]
+fn test_exhaust_until_iteration_end_is_not_cleared() {
+    fn foo() -> io::Result<()> {
+        inject_exhaust!("disk full", scope: Exhaust::UntilIterationEnd);
+        clear_condition("disk full");
+        inject_return_io_error!("write", fails_if: "disk full");
+        Ok(())
+    }
+
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            results.push(foo().is_ok());
+        })
+        .unwrap();
+
+    assert_eq!(results, vec![false, false, true]);
+}
+
+#[test]
+fn test_exhaust_in_no_faults() {
+    fn foo() -> io::Result<()> {
+        inject_exhaust!("disk full");
+        let _cs = no_faults();
+        inject_return_io_error!("write", fails_if: "disk full");
+        Ok(())
+    }
+
+    let mut results = vec![];
+    let report = Runner::ignore_env()
+        .run(|| {
+            results.push(foo().is_ok());
+        })
+        .unwrap();
+
+    // critical section suppresses failpoints even while the disk is full
+    assert_eq!(results, vec![true, true]);
+    assert!(
+        report
+            .paths()
+            .iter()
+            .all(|path| path.suppressed_by_critical_section == ["write"])
+    );
+}
+
+#[test]
+fn test_exhaust_during_unwind() {
+    fn write() -> io::Result<()> {
+        inject_return_io_error!("write", fails_if: "disk full");
+        Ok(())
+    }
+
+    fn work() -> Result<(), ()> {
+        inject_return!("work", Err(()));
+        Ok(())
+    }
+
+    struct Handle;
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            let _ = write();
+        }
+    }
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            inject_exhaust!("disk full");
+            let _handle = Handle;
+            work().expect("work failed");
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    let panicked: Vec<_> = report
+        .paths()
+        .iter()
+        .filter(|path| path.panic.is_some())
+        .collect();
+    assert_eq!(panicked.len(), 2);
+    for path in panicked {
+        assert_eq!(
+            path.panic
+                .as_ref()
+                .and_then(|panic| panic.message.as_deref()),
+            Some("work failed: ()")
+        );
+        assert_eq!(path.suppressed_during_unwind, ["write"]);
+    }
+}
+
+#[test]
+fn test_iteration_timeout() {
+    fn foo() -> Result<(), usize> {
+        inject_return!("1", Err(1));
+        // simulates infinite loop caused by an injected fault
+        if (|| -> Result<(), usize> {
+            inject_return!("hang", Err(2));
+            Ok(())
+        })()
+        .is_err()
+        {
+            while !iteration_timed_out() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            return Err(2);
+        }
+        Ok(())
+    }
+
+    let report = Runner::default()
+        .with_iteration_timeout(Duration::from_millis(50))
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+
+    let timed_out: Vec<_> = report.timed_out_paths().collect();
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(timed_out.len(), 1);
+    assert_eq!(
+        timed_out[0].activated_failpoints().collect::<Vec<_>>(),
+        vec!["hang"]
+    );
+    assert!(!iteration_timed_out());
+}
+
+#[test]
+fn test_independent_groups() {
+    fn foo() -> Vec<usize> {
+        let mut errors = vec![];
+        if (|| -> Result<(), usize> {
+            inject_return!("a", Err(1));
+            Ok(())
+        })()
+        .is_err()
+        {
+            errors.push(1);
+        }
+        if (|| -> Result<(), usize> {
+            inject_return!("b", Err(2));
+            Ok(())
+        })()
+        .is_err()
+        {
+            errors.push(2);
+        }
+        errors
+    }
+
+    let mut results = vec![];
+    let report = Runner::default().run(|| results.push(foo())).unwrap();
+    results.sort();
+    assert_eq!(results, vec![vec![], vec![1], vec![1, 2], vec![2]]);
+    assert_eq!(report.num_pruned(), 0);
+
+    let mut results = vec![];
+    let report = Runner::default()
+        .with_independent_groups(&[&["a"], &["b"]])
+        .run(|| results.push(foo()))
+        .unwrap();
+    results.sort();
+    assert_eq!(results, vec![vec![], vec![1], vec![2]]);
+    assert_eq!(report.num_pruned(), 1);
+    assert!(
+        report
+            .to_string()
+            .contains("pruned by user-asserted independence")
+    );
+
+    let mut results = vec![];
+    let report = Runner::default()
+        .with_independent_groups(&[&["a"], &["b"]])
+        .with_independence_audit(true)
+        .run(|| results.push(foo()))
+        .unwrap();
+    results.sort();
+    assert_eq!(results, vec![vec![], vec![1], vec![1, 2], vec![2]]);
+    assert_eq!(report.num_pruned(), 0);
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .filter(|path| path.independence_audit)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_perturb() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            results.push(inject_perturb!(
+                1.0f64,
+                "sensor read",
+                perturbations: [f64::INFINITY, -1.0]
+            ));
+        })
+        .unwrap();
+
+    assert_eq!(results, vec![f64::INFINITY, -1.0, 1.0]);
+    assert_eq!(
+        format!("{report:#}"),
+        "explored 3 path(s) through 1 failpoint(s)\n  #0: [sensor read=f64::INFINITY]\n  #1: [sensor read=-1.0]\n  #2: (no failpoints)"
+    );
+}
+
+#[test]
+fn test_perturb_non_copy() {
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            results.push(inject_perturb!(
+                String::from("name"),
+                "name read",
+                perturbations: [String::new(), "\0".repeat(2)]
+            ));
+        })
+        .unwrap();
+
+    assert_eq!(results, ["", "\0\0", "name"]);
+}
+
+#[test]
+fn test_perturb_preset() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            results.push(inject_perturb!(10i32, "counter", preset: Perturb::IntEdgeCases));
+        })
+        .unwrap();
+
+    assert_eq!(results, vec![0, i32::MAX, i32::MIN, 11, 9, 10]);
+    assert!(format!("{report:#}").contains("[counter=value+1]"));
+}
+
+#[test]
+fn test_order_sensitivity() {
+    fn foo(cache: &mut bool) -> Result<(), usize> {
+        inject_return!("1", Err(1));
+        // fixture state leaking between iterations: result depends
+        // on whether a successful iteration has already run
+        if *cache {
+            return Err(2);
+        }
+        *cache = true;
+        Ok(())
+    }
+
+    let mut cache = false;
+    let report = Runner::default()
+        .with_order_sensitivity_check(true)
+        .run(|| {
+            record_outcome(foo(&mut cache));
+            cache = false;
+        })
+        .unwrap();
+    assert!(report.order_sensitive_paths().is_empty());
+
+    let mut cache = false;
+    let report = Runner::default()
+        .with_order_sensitivity_check(true)
+        .run(|| record_outcome(foo(&mut cache)))
+        .unwrap();
+    let sensitive = report.order_sensitive_paths();
+    assert_eq!(sensitive.len(), 1);
+    assert!(
+        sensitive[0]
+            .steps
+            .iter()
+            .all(|step| step.branch == Branch::Skip)
+    );
+    assert_ne!(sensitive[0].outcome, sensitive[0].reversed_outcome);
+    assert!(report.to_string().contains("1 order-sensitive path(s)"));
+}
+
+#[test]
+fn test_smoke() {
+    fn foo() -> Result<(), usize> {
+        inject_return!("1", Err(1));
+        if (|| -> Result<(), usize> {
+            inject_return!("2", Err(2));
+            Ok(())
+        })()
+        .is_err()
+        {
+            // only reachable when "2" is activated
+            inject_return!("cleanup", Err(3));
+            return Err(2);
+        }
+        for _ in 0..3 {
+            inject_return!("loop", Err(4));
+        }
+        Ok(())
+    }
+
+    let mut results = vec![];
+    let report = Runner::smoke().run(|| results.push(foo())).unwrap();
+    results.sort();
+
+    assert_eq!(results, vec![Ok(()), Err(1), Err(2), Err(3), Err(4)]);
+    assert_eq!(report.tier(), Tier::Smoke);
+    assert!(report.to_string().contains("(smoke tier)"));
+
+    let report = Runner::default().run(|| results.push(foo())).unwrap();
+    assert_eq!(report.tier(), Tier::Full);
+    assert!(report.num_paths() > 5);
+}
+
+#[test]
+fn test_blast_radius() {
+    fn foo() -> Result<(), usize> {
+        inject_return!("open", Err(1));
+        inject_return!("write", Err(2));
+        inject_return!("close", Err(3));
+        Ok(())
+    }
+
+    let report = Runner::default()
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+
+    assert_eq!(
+        report.blast_radius("open").always_skipped,
+        vec!["close", "write"]
+    );
+    assert_eq!(report.blast_radius("write").always_skipped, vec!["close"]);
+    assert!(report.blast_radius("close").is_empty());
+    assert_eq!(report.largest_blast_radii()[0].0, "open");
+    assert_eq!(
+        report.blast_radii_to_json(),
+        r#"{"blast_radii":[{"failpoint":"open","always_skipped":["close","write"],"sometimes_skipped":[]},{"failpoint":"write","always_skipped":["close"],"sometimes_skipped":[]}]}"#
+    );
+    assert!(
+        format!("{report:#}")
+            .contains("largest blast radius: open (2 failpoint(s)), write (1 failpoint(s))")
+    );
+}
+
+#[test]
+fn test_catch_panics() {
+    fn read() -> io::Result<u32> {
+        inject_return_io_error!("read");
+        Ok(1)
+    }
+
+    fn foo() -> u32 {
+        let a = read().unwrap();
+        let b = read().unwrap();
+        a + b
+    }
+    let line = line!() - 4;
+
+    let err = Runner::default()
+        .with_catch_panics(true)
+        .run(|| {
+            foo();
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { locations, report } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(
+        locations,
+        vec![
+            format!("tests/main.rs:{line}"),
+            format!("tests/main.rs:{}", line + 1)
+        ]
+    );
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(report.panicking_paths().len(), 2);
+    assert!(format!("{report:#}").contains(": 1 fault combination(s)"));
+
+    let err = Runner::default()
+        .with_expected_panics(&[("main.rs", line)])
+        .run(|| {
+            foo();
+        })
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "unexpected panic(s) at tests/main.rs:{}\n2 of 3 path(s) failed; correlated with failure: read (2 of 2 failing vs 0 of 1 passing); first failing path: [read]",
+            line + 1
+        )
+    );
+
+    Runner::default()
+        .with_expected_panics(&[("tests/main.rs", line), ("tests/main.rs", line + 1)])
+        .run(|| {
+            foo();
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_requires_activated() {
+    fn foo() -> Result<(), Vec<&'static str>> {
+        let mut errors = vec![];
+        if (|| -> io::Result<()> {
+            inject_return_io_error!("create temp file");
+            Ok(())
+        })()
+        .is_err()
+        {
+            errors.push("create");
+        }
+        // cleanup runs in both cases
+        if (|| -> io::Result<()> {
+            inject_return_io_error!("cleanup temp file", requires_activated: "create temp file");
+            Ok(())
+        })()
+        .is_err()
+        {
+            errors.push("cleanup");
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    let mut results = vec![];
+    Runner::default().run(|| results.push(foo())).unwrap();
+
+    assert_eq!(
+        results,
+        vec![Err(vec!["create", "cleanup"]), Err(vec!["create"]), Ok(())]
+    );
+}
+
+#[test]
+#[should_panic(expected = "failpoint requirements form a cycle: b → a → b")]
+fn test_requires_activated_cycle() {
+    fn foo() -> Result<(), ()> {
+        inject_return!("a", Err(()), requires_activated: "b");
+        inject_return!("b", Err(()), requires_activated: "a");
+        Ok(())
+    }
+
+    Runner::default()
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_budget() {
+    fn foo() -> Result<(), usize> {
+        inject_return!("1", Err(1));
+        inject_return!("2", Err(2));
+        inject_return!("3", Err(3));
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .with_max_iterations(2)
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 2);
+    assert!(report.is_incomplete());
+    assert!(report.to_string().contains("stopped early due to budget"));
+
+    let report = Runner::ignore_env()
+        .with_max_duration(Duration::from_secs(3600))
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 4);
+    assert!(!report.is_incomplete());
+}
+
+#[test]
+fn test_strict_determinism() {
+    let mut counter = 0;
+    let mut foo = || -> Result<(), usize> {
+        counter += 1;
+        if counter % 2 == 0 {
+            inject_return!("even", Err(1));
+        } else {
+            inject_return!("odd", Err(2));
+        }
+        Ok(())
+    };
+
+    let err = Runner::ignore_env()
+        .with_strict_determinism(true)
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap_err();
+    assert!(matches!(err, faine::Error::NonDeterminism { .. }));
+}
+
+#[test]
+fn test_nearest_passing_path() {
+    fn foo() -> Result<(), usize> {
+        inject_return!("open", Err(1));
+        let fallback = (|| -> Result<(), usize> {
+            inject_return!("read", Err(2));
+            Ok(())
+        })()
+        .is_err();
+        inject_return!("close", Err(3));
+        assert!(!fallback, "fallback is broken");
+        Ok(())
+    }
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    let failing = report.paths().iter().find(|path| path.is_failed()).unwrap();
+    assert_eq!(
+        failing.to_string().split(" (").next(),
+        Some("open → [read] → close")
+    );
+    let (passing, diff) = report.nearest_passing_path(failing).unwrap();
+    // activating "close" returns before the broken assertion
+    assert_eq!(passing.to_string(), "open → [read] → [close]");
+    assert_eq!(diff.common_prefix, 2);
+    assert!(diff.to_string().contains("> close     [close]"));
+}
+
+#[test]
+fn test_random_strategy() {
+    fn foo() -> Result<(), usize> {
+        inject_return!("1", Err(1));
+        inject_return!("2", Err(2));
+        Ok(())
+    }
+
+    let schedule = Schedule::Phases(vec![
+        (Duration::from_millis(20), 0.0),
+        (Duration::from_millis(20), 1.0),
+        (Duration::from_millis(20), 0.0),
+    ]);
+    let report = Runner::ignore_env()
+        .with_strategy(Strategy::Random { schedule, seed: 1 })
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+    let phases = report.phases();
+    assert_eq!(phases.len(), 3);
+    assert_eq!(phases[0].activations, 0);
+    assert!(phases[1].activations > 0);
+    assert_eq!(phases[1].activations, phases[1].iterations);
+    assert_eq!(phases[2].activations, 0);
+    assert!(phases.iter().all(|phase| phase.iterations > 0));
+}
+
+#[test]
+fn test_faults_during_unwind() {
+    fn close() -> Result<(), ()> {
+        inject_return!("close", Err(()));
+        Ok(())
+    }
+
+    fn work() -> Result<(), ()> {
+        inject_return!("work", Err(()));
+        Ok(())
+    }
+
+    struct Handle;
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            close().expect("close failed");
+        }
+    }
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            let _handle = Handle;
+            work().expect("work failed");
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    let paths: Vec<_> = report
+        .paths()
+        .iter()
+        .map(|path| {
+            (
+                path.to_string().split(" (").next().unwrap().to_string(),
+                path.panic.as_ref().and_then(|panic| panic.message.clone()),
+                path.suppressed_during_unwind.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            (
+                "[work]".to_string(),
+                Some("work failed: ()".to_string()),
+                vec!["close"]
+            ),
+            (
+                "work → [close]".to_string(),
+                Some("close failed: ()".to_string()),
+                vec![]
+            ),
+            ("work → close".to_string(), None, vec![]),
+        ]
+    );
+}
+
+#[test]
+fn test_aliases() {
+    fn foo() -> Result<(), usize> {
+        inject_return!(Err(1));
+        inject_return!("named", Err(2));
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+    let unnamed = report
+        .failpoints()
+        .into_iter()
+        .find(|name| *name != "named")
+        .unwrap();
+
+    let report = Runner::ignore_env()
+        .with_aliases(&[
+            (unnamed, "first"),
+            ("named", "second"),
+            ("missing", "stale"),
+        ])
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .map(|path| path.to_string())
+            .collect::<Vec<_>>(),
+        vec!["[first]", "first → [second]", "first → second"]
+    );
+    assert_eq!(report.failpoint_location("first"), Some(unnamed));
+    assert_eq!(report.failpoint_location(unnamed), Some(unnamed));
+    assert_eq!(report.blast_radius("named").len(), 0);
+    assert_eq!(report.blast_radius("first").len(), 1);
+    assert_eq!(report.stale_aliases(), ["missing"]);
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, b"# comment\nnamed = second\n").unwrap();
+    let report = Runner::ignore_env()
+        .with_alias_file(file.path())
+        .run(|| {
+            let _ = foo();
+        })
+        .unwrap();
+    assert!(report.failpoints().contains("second"));
+
+    std::io::Write::write_all(&mut file, b"garbage\n").unwrap();
+    let err = Runner::ignore_env()
+        .with_alias_file(file.path())
+        .run(|| {})
+        .unwrap_err();
+    assert!(matches!(err, faine::Error::Configuration(_)));
+}
+
+#[test]
+fn test_fault_models() {
+    struct Always;
+
+    impl FaultModel for Always {
+        fn next(&mut self, _: &mut Rng) -> Branch {
+            Branch::Activate
+        }
+    }
+
+    fn request(name: &'static str) -> bool {
+        inject_override!(true, name, false)
+    }
+
+    let report = Runner::ignore_env()
+        .with_strategy(Strategy::Random {
+            schedule: Schedule::Phases(vec![(Duration::from_millis(20), 0.0)]),
+            seed: 1,
+        })
+        .with_fault_model("flaky", Markov::new(0.2, 0.8))
+        .with_fault_model("down", Always)
+        .run(|| {
+            for _ in 0..20 {
+                request("flaky backend");
+                request("stable backend");
+            }
+            for _ in 0..3 {
+                request("down backend");
+            }
+        })
+        .unwrap();
+    let runs = report.failure_runs();
+    assert!(!runs["flaky backend"].is_empty());
+    assert!(!runs.contains_key("stable backend"));
+    assert_eq!(runs["down backend"].keys().collect::<Vec<_>>(), vec![&3]);
+    assert!(!report.activated_failpoints().contains("stable backend"));
+}
+
+#[test]
+fn test_dedup() {
+    fn foo(fixture: u32) -> Result<(), usize> {
+        inject_return!("a", Err(1));
+        inject_return!("b", Err(2));
+        record_outcome(fixture);
+        Ok(())
+    }
+
+    let store = tempfile::tempdir().unwrap();
+    let runner = || Runner::ignore_env().with_dedup_store(store.path());
+    let run = |runner: Runner, fixture: u32| {
+        runner
+            .run(|| {
+                let _ = foo(fixture);
+            })
+            .unwrap()
+    };
+
+    let report = run(runner().with_scenario_key(1), 1);
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(report.num_dedup_skipped(), 0);
+
+    let report = run(runner().with_scenario_key(1), 1);
+    assert_eq!(report.num_paths(), 1);
+    assert_eq!(report.num_dedup_skipped(), 2);
+
+    let report = run(runner().with_scenario_key(2), 1);
+    assert_eq!(report.num_paths(), 3);
+
+    let report = run(runner().with_scenario_key(1).without_dedup(), 1);
+    assert_eq!(report.num_paths(), 3);
+
+    let report = run(
+        runner()
+            .with_scenario_key(1)
+            .with_dedup_mode(DedupMode::CrossCheck),
+        1,
+    );
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(report.num_dedup_mismatches(), 0);
+
+    // same key, but the fixture has changed
+    let report = run(
+        runner()
+            .with_scenario_key(1)
+            .with_dedup_mode(DedupMode::CrossCheck),
+        2,
+    );
+    assert_eq!(report.num_paths(), 3);
+    assert!(report.num_dedup_mismatches() > 0);
+}
+
+#[test]
+fn test_trigger() {
+    fn write() -> io::Result<()> {
+        inject_return_io_error!("write");
+        Ok(())
+    }
+
+    fn store() -> io::Result<()> {
+        for _ in 0..2 {
+            let _ = write();
+            inject_return_io_error!("fsync", trigger: After::Activation("write"));
+        }
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = store();
+        })
+        .unwrap();
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .map(|path| path.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            "[write] → [fsync after write]",
+            "[write] → fsync → [write] → [fsync after write]",
+            "[write] → fsync → [write] → fsync",
+            "[write] → fsync → write",
+            "write → [write] → [fsync after write]",
+            "write → [write] → fsync",
+            "write → write",
+        ]
+    );
+}
+
+#[test]
+fn test_failure_reruns() {
+    fn read(deterministic: bool) -> io::Result<()> {
+        if deterministic {
+            inject_return_io_error!("deterministic");
+        } else {
+            inject_return_io_error!("flaky");
+        }
+        Ok(())
+    }
+
+    fn cleanup() -> io::Result<()> {
+        inject_return_io_error!("cleanup");
+        Ok(())
+    }
+
+    let mut iteration = 0;
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_failure_reruns(3)
+        .run(|| {
+            iteration += 1;
+            read(true).unwrap();
+            if read(false).is_err() {
+                assert!(iteration % 2 == 0, "flaky failure");
+                let _ = cleanup();
+            }
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+
+    // each failing path is followed by 3 replays
+    assert_eq!(iteration, 9);
+    assert_eq!(report.num_paths(), 3);
+    assert!(report.non_determinism_witnessed());
+
+    let paths = report.paths();
+    let reruns = paths[0].reruns.as_ref().unwrap();
+    assert_eq!((reruns.reproduced, reruns.total), (3, 3));
+    assert_eq!(reruns.classification(), FailureClass::Deterministic);
+    assert!(!reruns.non_determinism_witnessed);
+    assert!(
+        paths[0]
+            .to_string()
+            .ends_with("(reproduced: 3/3, deterministic)")
+    );
+
+    let reruns = paths[1].reruns.as_ref().unwrap();
+    assert_eq!((reruns.reproduced, reruns.total), (1, 3));
+    assert_eq!(reruns.classification(), FailureClass::Flaky);
+    assert!(reruns.non_determinism_witnessed);
+
+    assert_eq!(paths[2].reruns, None);
+
+    // replays count towards iteration limit
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_failure_reruns(3)
+        .with_max_iterations(2)
+        .run(|| {
+            read(true).unwrap();
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert!(report.is_incomplete());
+    assert_eq!(report.paths()[0].reruns.as_ref().unwrap().total, 1);
+}
+
+#[test]
+fn test_result_ext() {
+    #[derive(Debug, PartialEq)]
+    enum StoreError {
+        Io(String),
+        Injected(&'static str),
+    }
+
+    impl From<io::Error> for StoreError {
+        fn from(err: io::Error) -> Self {
+            Self::Io(err.to_string())
+        }
+    }
+
+    fn read() -> io::Result<u32> {
+        inject_return_io_error!("read");
+        Ok(1)
+    }
+
+    fn load() -> io::Result<u32> {
+        let a = read()?;
+        let b = Ok::<_, io::Error>(2).faine("parse")?;
+        let c = Ok(3).faine_with("check", || io::Error::other("checksum mismatch"))?;
+        Ok(a + b + c)
+    }
+
+    fn store() -> Result<(), StoreError> {
+        Ok(()).faine_io("store")
+    }
+
+    assert_eq!(load().unwrap(), 6);
+
+    let report = Runner::ignore_env()
+        .run(|| match load() {
+            Ok(sum) => assert_eq!(sum, 6),
+            Err(err) if err.to_string() == "checksum mismatch" => {}
+            Err(err) => assert!(["read", "parse"].contains(&err.to_string().as_str())),
+        })
+        .unwrap();
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .map(|path| path.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            "[read]",
+            "read → [parse]",
+            "read → parse → [check]",
+            "read → parse → check",
+        ]
+    );
+    assert!(
+        report
+            .failpoint_location("parse")
+            .unwrap()
+            .starts_with("tests/main.rs:")
+    );
+    // same as locations of macros, including column
+    assert_eq!(
+        report
+            .failpoint_location("parse")
+            .unwrap()
+            .matches(':')
+            .count(),
+        2
+    );
+
+    let mut errors = vec![];
+    Runner::ignore_env().run(|| errors.push(store())).unwrap();
+    assert_eq!(
+        errors,
+        vec![Err(StoreError::Io("store".to_string())), Ok(())]
+    );
+
+    let mut errors = vec![];
+    Runner::ignore_env()
+        .with_error_factory(StoreError::Injected)
+        .run(|| errors.push(store()))
+        .unwrap();
+    assert_eq!(errors, vec![Err(StoreError::Injected("store")), Ok(())]);
+
+    // faine() does not need conversion from io::Error, but needs a factory
+    #[derive(Debug, PartialEq)]
+    struct ParseError;
+
+    let res = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            let _ = Ok::<(), ParseError>(()).faine("parse");
+        })
+        .into_result();
+    assert!(matches!(res, Err(Error::UnexpectedPanics { .. })));
+
+    let mut errors = vec![];
+    Runner::ignore_env()
+        .with_error_factory(|_| ParseError)
+        .run(|| errors.push(Ok::<(), ParseError>(()).faine("parse")))
+        .unwrap();
+    assert_eq!(errors, vec![Err(ParseError), Ok(())]);
+}
+
+#[test]
+fn test_result_ext_crate() {
+    // another crate uses the name first
+    let other = FailpointId::intern_for_crate("other crate", "result ext crate test");
+
+    let explore = |store: fn()| {
+        let report = Runner::ignore_env().run(store).unwrap();
+        report.failpoints().into_iter().collect::<Vec<_>>()
+    };
+    let with_macro = explore(|| {
+        let _ = (|| -> io::Result<()> {
+            inject_return_io_error!("result ext crate test");
+            Ok(())
+        })();
+    });
+    let with_interned = explore(|| {
+        let _ = Ok::<(), io::Error>(()).faine(intern!("result ext crate test"));
+    });
+    let with_string = explore(|| {
+        let _ = Ok::<(), io::Error>(()).faine("result ext crate test");
+    });
+
+    // interned name belongs to this crate, same as one used by macros
+    assert_eq!(with_macro, vec!["faine::result ext crate test"]);
+    assert_eq!(with_interned, with_macro);
+    // plain string does not, and refers to the failpoint of another crate
+    assert_eq!(with_string, vec![other.name()]);
+}
+
+#[test]
+fn test_priority_paths() {
+    fn store() {
+        for name in ["a", "b", "c"] {
+            let _ = Ok::<(), io::Error>(()).faine(name);
+        }
+    }
+
+    let report = Runner::ignore_env()
+        .with_priority_paths(&[&["b", "c"], &["c"], &["x"]])
+        .run(store)
+        .unwrap();
+    assert_eq!(
+        report
+            .paths()
+            .iter()
+            .map(|path| path.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            "a → [b] → [c]",
+            "a → b → [c]",
+            "a → b → c",
+            "[a] → [b] → [c]",
+            "[a] → [b] → c",
+            "[a] → b → [c]",
+            "[a] → b → c",
+            "a → [b] → c",
+        ]
+    );
+    assert_eq!(report.unreachable_priority_paths(), [vec!["x".to_string()]]);
+
+    let err = Runner::ignore_env()
+        .with_priority_paths(&[&["c", "b"]])
+        .with_require_reachable_priority_paths(true)
+        .run(store)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "unreachable priority path(s): c → b");
+}
+
+#[test]
+fn test_known_failures() {
+    fn store() {
+        for name in ["a", "b"] {
+            Ok::<(), io::Error>(()).faine(name).unwrap();
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("known-failures");
+    std::fs::write(&path, "# known\n[a]\n[a] → [b]\na → b\n").unwrap();
+
+    let err = Runner::ignore_env()
+        .with_known_failures_file(&path)
+        .run(store)
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(
+        report.to_string(),
+        "explored 3 path(s) through 2 failpoint(s), 2 panicking path(s) (1 known, 1 new failure(s), 1 known failure(s) fixed)"
+    );
+    assert_eq!(
+        report
+            .new_failing_paths()
+            .map(|path| path.key())
+            .collect::<Vec<_>>(),
+        vec!["a → [b]"]
+    );
+    // "[a] → [b]" is not reachable, and is not known to pass
+    assert_eq!(report.fixed_known_failures(), vec!["a → b"]);
+
+    // partial exploration does not update the file
+    Runner::ignore_env()
+        .with_known_failures_file(&path)
+        .with_update_known_failures(true)
+        .with_max_iterations(1)
+        .run(store)
+        .unwrap();
+    assert!(
+        std::fs::read_to_string(&path)
+            .unwrap()
+            .ends_with("[a] → [b]\na → b\n")
+    );
+
+    Runner::ignore_env()
+        .with_known_failures_file(&path)
+        .with_update_known_failures(true)
+        .run(store)
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "# paths expected to fail, one per line\n[a]\na → [b]\n"
+    );
+
+    let report = Runner::ignore_env()
+        .with_known_failures_file(&path)
+        .run(store)
+        .unwrap();
+    assert_eq!(report.known_failing_paths().count(), 2);
+    assert!(report.fixed_known_failures().is_empty());
+
+    Runner::ignore_env()
+        .with_known_failures_file(dir.path().join("missing"))
+        .run(store)
+        .unwrap_err();
+}
+
+#[test]
+fn test_timeline() {
+    fn store() {
+        for name in ["a", "b", "c"] {
+            let _ = Ok::<(), io::Error>(()).faine(name);
+        }
+    }
+
+    let report = Runner::ignore_env()
+        .with_timeline(Duration::ZERO)
+        .run(store)
+        .unwrap();
+    let timeline = report.timeline().unwrap();
+    let samples = timeline.samples();
+    // sample after each iteration, and a final one
+    assert_eq!(samples.len(), 9);
+    assert!(
+        samples
+            .windows(2)
+            .all(|pair| pair[0].combinations <= pair[1].combinations)
+    );
+    let last = samples.last().unwrap();
+    assert_eq!(
+        (
+            last.iterations,
+            last.combinations,
+            last.frontier,
+            last.failpoints
+        ),
+        (8, 8, 0, 3)
+    );
+    assert!(format!("{report:#}").contains("\n  timeline: "));
+    assert!(timeline.to_json().contains(r#""combinations":8"#));
+
+    // random strategy keeps exploring the same 4 combinations until stopped
+    let schedule = Schedule::Phases(vec![(Duration::from_secs(60), 0.5)]);
+    let report = Runner::ignore_env()
+        .with_strategy(Strategy::Random { schedule, seed: 1 })
+        .with_diminishing_returns_stop(Duration::from_millis(50), 1)
+        .run(|| {
+            let _ = Ok::<(), io::Error>(()).faine("a");
+            let _ = Ok::<(), io::Error>(()).faine("b");
+        })
+        .unwrap();
+    assert!(report.is_incomplete());
+    assert!(report.is_stopped_by_diminishing_returns());
+    assert!(
+        report
+            .to_string()
+            .ends_with(", stopped early due to diminishing returns")
+    );
+}
+
+#[test]
+fn test_fault_rate_limit() {
+    let schedule = Schedule::Phases(vec![(Duration::from_millis(20), 1.0)]);
+    let report = Runner::ignore_env()
+        .with_strategy(Strategy::Random { schedule, seed: 1 })
+        .with_fault_rate_limit(3, Duration::from_secs(3600))
+        .with_fault_rate_limit_for("net ", 1, Duration::from_secs(3600))
+        .run(|| {
+            let _ = Ok::<(), io::Error>(()).faine("disk write");
+            let _ = Ok::<(), io::Error>(()).faine("net send");
+        })
+        .unwrap();
+    let fault_rates = report.fault_rates();
+    let disk = &fault_rates["disk write"];
+    let net = &fault_rates["net send"];
+    assert_eq!(disk.activations + net.activations, 3);
+    assert_eq!(net.activations, 1);
+    assert_eq!(disk.visits, disk.activations + disk.rate_limited);
+    assert!(disk.activations_per_second > 0.0);
+    assert!(report.to_string().contains(" activation(s) rate-limited"));
+    assert!(format!("{report:#}").contains("\n  fault rate of net send: 1 activation(s) of "));
+}
+
+#[test]
+fn test_cooldown() {
+    let explore = |seed| {
+        let schedule = Schedule::Phases(vec![(Duration::from_secs(3600), 0.5)]);
+        let mut activations = vec![];
+        let report = Runner::ignore_env()
+            .with_strategy(Strategy::Random { schedule, seed })
+            .with_max_iterations(20)
+            .with_cooldown("poll", 3)
+            .run(|| {
+                let mut iteration = vec![];
+                for _ in 0..10 {
+                    iteration.push(Ok::<(), io::Error>(()).faine("poll").is_err());
+                }
+                iteration.push(Ok::<(), io::Error>(()).faine("commit").is_err());
+                activations.push(iteration);
+            })
+            .unwrap();
+        (report, activations)
+    };
+
+    let (report, activations) = explore(1);
+    for iteration in &activations {
+        // each activation is followed by at least 3 skips
+        let polls = &iteration[..10];
+        for (i, _) in polls
+            .iter()
+            .enumerate()
+            .filter(|(_, activated)| **activated)
+        {
+            assert!(polls[i + 1..].iter().take(3).all(|activated| !activated));
+        }
+    }
+    let poll = &report.fault_rates()["poll"];
+    assert_eq!(poll.visits, 200);
+    assert!(poll.cooled_down > 0);
+    assert_eq!(report.fault_rates()["commit"].cooled_down, 0);
+    assert!(report.to_string().contains(" activation(s) cooled down"));
+    assert!(format!("{report:#}").contains(" cooled down"));
+
+    // same seed leads to the same activations
+    assert_eq!(explore(1).1, activations);
+    assert_ne!(explore(2).1, activations);
+}
+
+#[test]
+fn test_recorder() {
+    let trace = Recorder::record(|| {
+        let _ = Ok::<(), io::Error>(()).faine("connect");
+        for _ in 0..2 {
+            let _ = Ok::<(), io::Error>(()).faine("send");
+        }
+        let _ = Ok::<(), io::Error>(()).faine("ack");
+        let _ = Ok::<(), io::Error>(()).faine("close");
+    });
+    assert_eq!(trace.to_string(), "connect → send → send → ack → close");
+    assert!(trace.steps().iter().all(|step| step.branch == Branch::Skip));
+    trace.assert_matches(&[
+        "connect".into(),
+        repeated("send", 1..=3),
+        unordered(["close", "ack"]),
+    ]);
+    assert!(!trace.matches(&["connect".into(), repeated("send", 1..=1), any(), any()]));
+
+    let message = *std::panic::catch_unwind(|| {
+        trace.assert_matches(&["connect".into(), repeated("send", 1..=3), "close".into()])
+    })
+    .unwrap_err()
+    .downcast::<String>()
+    .unwrap();
+    assert_eq!(
+        message,
+        [
+            "trace does not match the script:",
+            "    \"connect\"               | connect",
+            "    repeated(\"send\", 1..=3) | send → send",
+            "  ✗ \"close\"                 | ack → close",
+        ]
+        .join("\n")
+    );
+
+    let message = *std::panic::catch_unwind(|| trace.assert_matches(&["connect".into()]))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert!(message.ends_with("\n  ✗ <end of script> | send → send → ack → close"));
+}
+
+#[test]
+fn test_unexamined_outcome() {
+    let failing = || {
+        Runner::ignore_env().with_catch_panics(true).run(|| {
+            if Ok::<(), io::Error>(()).faine("write").is_err() {
+                panic!("write failed");
+            }
+        })
+    };
+
+    // forgotten unwrap() panics on drop
+    let message = *std::panic::catch_unwind(|| {
+        let _ = failing();
+    })
+    .unwrap_err()
+    .downcast::<String>()
+    .unwrap();
+    assert!(
+        message.starts_with("faine: exploration has failed, but its outcome was not examined: ")
+    );
+
+    // examined or explicitly ignored failures do not
+    assert!(failing().is_err());
+    assert!(matches!(
+        failing().into_result(),
+        Err(Error::UnexpectedPanics { .. })
+    ));
+    failing().ignore_failures();
+
+    // successful outcomes need no examination
+    let _ = Runner::ignore_env().run(|| {});
+
+    // same for other run methods
+    let message = *std::panic::catch_unwind(|| {
+        let _ = Runner::ignore_env().run_expect_failure(|| {});
+    })
+    .unwrap_err()
+    .downcast::<String>()
+    .unwrap();
+    assert!(message.ends_with("not examined: no failure found where expected, 1 path(s) explored"));
+}
+
+#[test]
+fn test_trace_export() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let report = Runner::ignore_env()
+        .with_trace_export(tempdir.path(), false)
+        .with_test_name("traced")
+        .with_aliases(&[("raw write", "write")])
+        .with_catch_panics(true)
+        .with_expected_panics(&[("tests/main.rs", line!() + 4)])
+        .run(|| {
+            let _ = Ok::<(), io::Error>(()).faine("open");
+            if Ok::<(), io::Error>(()).faine("raw write").is_err() {
+                panic!("write failed");
+            }
+        })
+        .unwrap();
+    assert_eq!(report.paths().len(), 4);
+
+    // only failing paths are traced
+    let mut files: Vec<_> = std::fs::read_dir(tempdir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(files, vec!["traced-0.json", "traced-2.json"]);
+
+    let trace = std::fs::read_to_string(tempdir.path().join("traced-0.json")).unwrap();
+    let pid = format!("\"pid\":{}", std::process::id());
+    let events: Vec<&str> = trace.lines().skip(1).take(3).collect();
+    assert!(events.iter().all(|event| event.contains(&pid)));
+    assert!(events[1].starts_with("{\"name\":\"open\",\"cat\":\"failpoint\""));
+    assert!(events[1].contains("\"branch\":\"activate\""));
+    assert!(events[2].starts_with("{\"name\":\"write\",\"cat\":\"failpoint\""));
+    assert!(trace.contains("\"name\":\"panicked at tests/main.rs:"));
+}
+
+#[test]
+fn test_components() {
+    fn append() -> io::Result<()> {
+        inject_return_io_error!(component: "wal", point: "append");
+        Ok(())
+    }
+
+    fn flush() -> io::Result<()> {
+        inject_return_io_error!("index/flush");
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .with_independent_groups(&[&[Component("wal").into()], &[Selector::from("index/flush")]])
+        .run(|| {
+            if let Err(err) = append() {
+                assert_injected_by!(Err::<(), _>(err), "wal/append");
+            }
+            let _ = flush();
+        })
+        .unwrap();
+    // activation of index/flush after wal/append is pruned
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(report.num_pruned(), 1);
+    assert_eq!(report.failpoint_component("wal/append"), Some("wal"));
+    // plain names are component-less
+    assert_eq!(report.failpoint_component("index/flush"), None);
+
+    let components = report.components();
+    assert_eq!(components.keys().collect::<Vec<_>>(), vec![&"wal"]);
+    let wal = &components["wal"];
+    assert_eq!(
+        wal.failpoints.iter().collect::<Vec<_>>(),
+        vec![&"wal/append"]
+    );
+    assert_eq!((wal.visits, wal.activations, wal.failing_paths), (3, 1, 0));
+    assert_eq!(
+        report.components_to_json(),
+        r#"{"components":[{"component":"wal","failpoints":["wal/append"],"visits":3,"activations":1,"failing_paths":0}]}"#
+    );
+    assert!(
+        format!("{report:#}")
+            .contains("\n  component wal: 1 failpoint(s), 1 activation(s), 0 failing path(s)")
+    );
+}
+
+#[test]
+fn test_forbidden() {
+    fn open() -> io::Result<()> {
+        // recovery from a corrupt header writes, even in read-only mode
+        if inject_override!(Ok(()), "read header", Err(io::Error::other("corrupt"))).is_err() {
+            inject_return_io_error!("write/repair");
+        }
+        inject_return_io_error!("read index");
+        Ok(())
+    }
+
+    fn read() -> io::Result<()> {
+        inject_return_io_error!("read index");
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .with_forbidden(&[Prefix("write/")])
+        .run(|| {
+            let _ = read();
+        })
+        .unwrap();
+    assert!(report.forbidden_visits().is_empty());
+    assert!(report.to_string().ends_with(", 0 forbidden visit(s)"));
+
+    let err = Runner::ignore_env()
+        .with_forbidden(&[Prefix("write/")])
+        .run(|| {
+            let _ = open();
+        })
+        .unwrap_err();
+    let faine::Error::ForbiddenFailpointReached { name, path, report } = err else {
+        panic!("unexpected error");
+    };
+    assert_eq!(name, "write/repair");
+    assert_eq!(path, "[read header] → write/repair");
+    // forbidden failpoint is not explored
+    assert_eq!(report.num_paths(), 4);
+    assert_eq!(report.forbidden_visits().len(), 2);
+    assert!(!report.failpoints().contains("write/repair"));
+    assert!(report.to_string().ends_with(", 2 forbidden visit(s)"));
+}
+
+#[test]
+fn test_batch_cut() {
+    let report = Runner::ignore_env()
+        .run(|| {
+            let mut applied = 0;
+            for index in 0..4 {
+                // cut is not honored on purpose, which must not
+                // multiply explored paths
+                if !batch_cut!("apply changes", index, 4) {
+                    applied += 1;
+                }
+            }
+            assert!(applied >= 3);
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 5);
+    let paths: Vec<String> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "[apply changes=cut before 0]",
+            "[apply changes=cut before 1]",
+            "[apply changes=cut before 2]",
+            "[apply changes=cut before 3]",
+            "(no failpoints)",
+        ]
+    );
+    // index is kept apart from the variant, so no description is
+    // allocated per index
+    let step = &report.paths()[2].steps[0];
+    assert_eq!(step.variant, Some("cut before"));
+    assert_eq!(step.variant_index, Some(2));
+}
+
+#[test]
+fn test_batch_cut_repeated() {
+    let report = Runner::ignore_env()
+        .run(|| {
+            for _batch in 0..2 {
+                for index in 0..2 {
+                    if batch_cut!("apply changes", index, 2) {
+                        break;
+                    }
+                }
+            }
+        })
+        .unwrap();
+    let paths: Vec<String> = report.paths().iter().map(ToString::to_string).collect();
+    // each batch is cut independently, with a single decision per batch
+    assert_eq!(
+        paths,
+        vec![
+            "[apply changes=cut before 0] → [apply changes=cut before 0]",
+            "[apply changes=cut before 0] → [apply changes=cut before 1]",
+            "[apply changes=cut before 0]",
+            "[apply changes=cut before 1] → [apply changes=cut before 0]",
+            "[apply changes=cut before 1] → [apply changes=cut before 1]",
+            "[apply changes=cut before 1]",
+            "[apply changes=cut before 0]",
+            "[apply changes=cut before 1]",
+            "(no failpoints)",
+        ]
+    );
+}
+
+#[test]
+fn test_self_verification() {
+    let report = Runner::ignore_env()
+        .with_self_verification(1.0)
+        .run(|| {
+            let _ = Ok::<(), io::Error>(()).faine("a");
+            let _ = Ok::<(), io::Error>(()).faine("b");
+        })
+        .unwrap();
+    let verification = report.self_verification().unwrap();
+    assert_eq!(verification.replayed, 4);
+    assert!(verification.divergences.is_empty());
+    assert!(report.to_string().contains(&format!(
+        ", 4 path(s) replayed (seed {}), 0 diverged",
+        verification.seed
+    )));
+
+    // code which changes behavior after exploration
+    let mut iterations = 0;
+    let report = Runner::ignore_env()
+        .with_self_verification(0.5)
+        .with_self_verification_seed(1)
+        .run(|| {
+            iterations += 1;
+            let _ = Ok::<(), io::Error>(()).faine("a");
+            if iterations > 2 {
+                let _ = Ok::<(), io::Error>(()).faine("late");
+            }
+        })
+        .unwrap();
+    let verification = report.self_verification().unwrap();
+    assert_eq!(verification.seed, 1);
+    assert_eq!(verification.replayed, 1);
+    assert_eq!(verification.divergences.len(), 1);
+    let divergence = &verification.divergences[0];
+    assert_eq!(divergence.step, 1);
+    assert_eq!(divergence.expected, "end of iteration");
+    assert_eq!(divergence.observed, "visit of late");
+    assert!(report.non_determinism_witnessed());
+
+    let mut iterations = 0;
+    let err = Runner::ignore_env()
+        .with_self_verification(1.0)
+        .with_strict_determinism(true)
+        .run(|| {
+            iterations += 1;
+            let _ = Ok::<(), io::Error>(()).faine("a");
+            record_outcome(iterations > 2);
+        })
+        .unwrap_err();
+    let faine::Error::NonDeterminism { report } = err else {
+        panic!("unexpected error");
+    };
+    let divergences = &report.self_verification().unwrap().divergences;
+    assert_eq!(divergences.len(), 2);
+    assert_eq!(divergences[0].step, 1);
+    assert!(divergences[0].expected.starts_with("finish with outcome "));
+    assert!(
+        format!("{report:#}")
+            .contains("\n  replay divergence: path #0, step 1: expected finish with outcome ")
+    );
+}
+
+#[test]
+fn test_outcome_matrix() {
+    let matrix = faine::golden::outcome_matrix(Runner::ignore_env(), || -> io::Result<usize> {
+        Ok::<(), io::Error>(()).faine("open")?;
+        if Ok::<(), io::Error>(()).faine("read").is_err() {
+            // only reachable with "read" activated, so never alone
+            let _ = Ok::<(), io::Error>(()).faine("log");
+            return Ok(0);
+        }
+        Ok(1)
+    })
+    .unwrap();
+    assert_eq!(matrix.outcomes().len(), 2);
+    assert_eq!(matrix.get("read"), Some("Ok(0)"));
+    assert!(matrix.get("open").unwrap().starts_with("Err("));
+
+    let recorded: faine::golden::OutcomeMatrix =
+        format!("{}gone => Ok(2)\n", matrix).parse().unwrap();
+    assert_eq!(matrix.diff(&recorded).to_string(), "- gone => Ok(2)\n");
+}
+
+#[test]
+fn test_site_expectations() {
+    fn store() -> io::Result<()> {
+        inject_return!("open", Err(io::Error::other("injected")));
+        inject_return_io_error!("append", expect: Activated);
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = store();
+        })
+        .unwrap();
+    assert_eq!(report.site_expectations().len(), 1);
+    assert!(report.unmet_site_expectations().is_empty());
+
+    // only the first path is explored, so "append" is never activated
+    let err = Runner::ignore_env()
+        .with_branch_preference(Branch::Skip)
+        .with_max_iterations(1)
+        .with_enforce_site_expectations(true)
+        .run(|| {
+            let _ = store();
+        })
+        .unwrap_err();
+    let faine::Error::SiteExpectationsNotMet { failpoints, report } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(failpoints.len(), 1);
+    assert!(failpoints[0].starts_with("append (activated) at tests/main.rs:"));
+    assert_eq!(
+        report.unmet_site_expectations(),
+        [("append", faine::Expect::Activated)]
+    );
+    assert!(format!("{report:#}").contains("\n  unmet site expectation: append (activated)"));
+}
+
+#[test]
+fn test_order_seed() {
+    let paths = |runner: Runner| {
+        let mut paths: Vec<String> = runner
+            .run(|| {
+                for name in ["a", "b", "c"] {
+                    let _ = Ok::<(), io::Error>(()).faine(name);
+                }
+            })
+            .unwrap()
+            .paths()
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+        paths.sort();
+        paths
+    };
+    let expected = paths(Runner::ignore_env());
+    for seed in 0..4 {
+        assert_eq!(paths(Runner::ignore_env().with_order_seed(seed)), expected);
+    }
+}
+
+#[test]
+fn test_fuzz_orders() {
+    let make = || {
+        let mut dirty = false;
+        move || {
+            let _ = Ok::<(), io::Error>(()).faine("noise");
+            // failed write is not cleaned up
+            inject_return!("write", dirty = true);
+            let _ = Ok::<(), io::Error>(()).faine("log");
+            inject_return!("read", assert!(!dirty, "stale data"));
+        }
+    };
+
+    let err = Runner::ignore_env().fuzz_orders(0..16, make).unwrap_err();
+    assert!(err.to_string().contains("with_priority_paths(&[&["));
+    let faine::Error::OrderFuzzFailure {
+        repro: Some(repro), ..
+    } = err
+    else {
+        panic!("unexpected error {err:?}");
+    };
+    assert!(repro.is_order_dependent());
+    assert_eq!(repro.path(), ["read"]);
+    assert!(repro.prefix()[0].contains(&"write".to_string()));
+
+    let paths = repro.priority_paths();
+    let paths: Vec<Vec<&str>> = paths
+        .iter()
+        .map(|path| path.iter().map(String::as_str).collect())
+        .collect();
+    let paths: Vec<&[&str]> = paths.iter().map(Vec::as_slice).collect();
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_priority_paths(&paths)
+        .with_max_iterations(paths.len())
+        .run(make())
+        .unwrap_err();
+    assert!(matches!(err, faine::Error::UnexpectedPanics { .. }));
+
+    // order independent failures are reduced to a single path
+    let err = Runner::ignore_env()
+        .fuzz_orders(0..1, || {
+            || {
+                let _ = Ok::<(), io::Error>(()).faine("noise");
+                if Ok::<(), io::Error>(()).faine("read").is_err() {
+                    Ok::<(), io::Error>(()).faine("fallback").unwrap();
+                }
+            }
+        })
+        .unwrap_err();
+    let faine::Error::OrderFuzzFailure {
+        repro: Some(repro), ..
+    } = err
+    else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(repro.priority_paths(), [vec!["read", "fallback"]]);
+}
+
+#[test]
+fn test_run_with_state() {
+    let report = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_expected_panics(&[("tests/main.rs", line!() + 8)])
+        .with_accumulator(Vec::<usize>::new())
+        .run_with_state(Vec::new(), |state: &mut Vec<&str>| {
+            assert!(state.is_empty(), "state leaked");
+            for name in ["a", "b"] {
+                state.push(name);
+                if Ok::<(), io::Error>(()).faine(name).is_err() {
+                    accumulate(|lens: &mut Vec<usize>| lens.push(state.len()));
+                    panic!("injected");
+                }
+            }
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(report.accumulator::<Vec<usize>>(), Some(&vec![1, 2]));
+    assert_eq!(report.accumulator::<usize>(), None);
+
+    assert_eq!(accumulate(|lens: &mut Vec<usize>| lens.len()), None);
+}
+
+#[test]
+fn test_accumulator_counts_each_path_once() {
+    fn count() {
+        let failed = Ok::<(), io::Error>(()).faine("a").is_err();
+        let _ = Ok::<(), io::Error>(()).faine("b");
+        accumulate(|paths: &mut usize| *paths += 1);
+        assert!(!failed, "injected");
+    }
+
+    // failing paths are rerun
+    let res = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_failure_reruns(2)
+        .with_accumulator(0usize)
+        .run(count)
+        .into_result();
+    let Err(Error::UnexpectedPanics { report, .. }) = res else {
+        panic!("unexpected result {res:?}");
+    };
+    assert_eq!(report.num_paths(), 4);
+    assert_eq!(report.accumulator::<usize>(), Some(&4));
+
+    // paths of other shards are executed, but not counted
+    let counts: Vec<_> = (0..2)
+        .map(|index| {
+            let report = Runner::ignore_env()
+                .with_shard(index, 2)
+                .with_shard_depth(1)
+                .with_accumulator(0usize)
+                .run(|| {
+                    let _ = Ok::<(), io::Error>(()).faine("a");
+                    let _ = Ok::<(), io::Error>(()).faine("b");
+                    accumulate(|paths: &mut usize| *paths += 1);
+                })
+                .unwrap();
+            (report.num_paths(), *report.accumulator::<usize>().unwrap())
+        })
+        .collect();
+    assert_eq!(counts.iter().map(|(paths, _)| paths).sum::<usize>(), 4);
+    for (paths, accumulated) in counts {
+        assert_eq!(paths, accumulated);
+    }
+}
+
+#[test]
+fn test_axes() {
+    fn store() {
+        let _ = Ok::<(), io::Error>(()).faine("write");
+        if axis("checksum") == "on" {
+            let _ = Ok::<(), io::Error>(()).faine("verify");
+        }
+    }
+    let runner = || {
+        Runner::ignore_env()
+            .with_axis("mode", &["sync", "async"])
+            .with_axis("checksum", &["on", "off"])
+    };
+    let combinations = |report: &faine::Report| {
+        let mut combinations: Vec<_> = report
+            .paths()
+            .iter()
+            .map(|path| path.axes.clone())
+            .collect();
+        combinations.dedup();
+        combinations.len()
+    };
+
+    let report = runner().run(store).unwrap();
+    assert_eq!(report.num_paths(), 12);
+    assert_eq!(combinations(&report), 4);
+    assert!(!report.is_incomplete());
+    assert!(
+        report
+            .paths()
+            .iter()
+            .any(|path| path.to_string() == "{mode=async, checksum=on} [write] → verify")
+    );
+
+    let report = runner().with_max_iterations(4).run(store).unwrap();
+    assert_eq!(report.num_paths(), 4);
+    assert_eq!(combinations(&report), 4);
+    assert!(report.is_incomplete());
+
+    let report = runner()
+        .with_max_iterations(4)
+        .with_axis_budget(AxisBudget::Shared)
+        .run(store)
+        .unwrap();
+    assert_eq!(report.num_paths(), 4);
+    assert_eq!(combinations(&report), 1);
+
+    // single fault runs per combination
+    let report = Runner::smoke()
+        .with_axis("mode", &["sync", "async"])
+        .with_axis("checksum", &["on", "off"])
+        .run(|| {
+            for name in ["a", "b"] {
+                let _ = Ok::<(), io::Error>(()).faine(name);
+            }
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 12);
+
+    let err = runner()
+        .with_catch_panics(true)
+        .run(|| {
+            let res = Ok::<(), io::Error>(()).faine("write");
+            assert!(res.is_ok() || axis("mode") == "sync");
+        })
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    let failing: Vec<String> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .map(|path| path.key())
+        .collect();
+    assert_eq!(
+        failing,
+        [
+            "{mode=async, checksum=on} [write]",
+            "{mode=async, checksum=off} [write]"
+        ]
+    );
+
+    let err = Runner::ignore_env()
+        .with_axis("mode", &[])
+        .run(|| {})
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid faine configuration: axis mode has no values"
+    );
+}
+
+#[test]
+fn test_absorption() {
+    fn sync() -> io::Result<()> {
+        // retried once, so a single failure is absorbed
+        if Ok::<(), io::Error>(()).faine("send").is_err() {
+            Ok::<(), io::Error>(()).faine("send retry")?;
+        }
+        // failure is wrongly ignored
+        let _ = Ok::<(), io::Error>(()).faine("ack");
+        Ok(())
+    }
+
+    let report = Runner::ignore_env().run(|| record_result(&sync())).unwrap();
+    let absorption = report.absorption();
+    assert_eq!(absorption["send"].paths, 3);
+    assert_eq!(absorption["send"].success, 2);
+    assert_eq!(absorption["send retry"].ratio(), 0.0);
+    assert_eq!(absorption["ack"].ratio(), 1.0);
+    assert!(format!("{report:#}").contains("\n  absorbed: ack (2 of 2 path(s))"));
+
+    let err = Runner::ignore_env()
+        .with_max_absorption("send", 0.7)
+        .with_max_absorption("ack", 0.0)
+        .run(|| record_result(&sync()))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "injected failure(s) absorbed: ack (2 of 2 path(s))"
+    );
+
+    // panics are surfaced failures
+    let report = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_expected_panics(&[("tests/main.rs", line!() + 3)])
+        .run(|| {
+            record_result(&Outcome::Success);
+            sync().unwrap();
+        })
+        .unwrap();
+    assert_eq!(report.absorption()["send retry"].failure, 1);
+}
+
+#[test]
+fn test_options_refer_to_qualified_names() {
+    let storage = FailpointId::intern_for_crate("storage", "qualified ack");
+    FailpointId::intern_for_crate("index", "qualified ack");
+    assert_eq!(storage.name(), "storage::qualified ack");
+
+    fn sync(ack: FailpointId) -> Result<(), ()> {
+        // failure is wrongly ignored
+        let _ = (|| -> Result<(), ()> {
+            inject_return!(ack, Err(()));
+            Ok(())
+        })();
+        Ok(())
+    }
+
+    // unqualified prefixes and names refer to failpoints of all crates
+    let err = Runner::ignore_env()
+        .with_max_absorption("qualified", 0.0)
+        .run(|| record_result(&sync(storage)))
+        .unwrap_err();
+    assert!(matches!(err, Error::AbsorptionExceeded { .. }));
+
+    let mut activated = vec![];
+    Runner::ignore_env()
+        .with_priority_paths(&[&["qualified ack"]])
+        .with_max_iterations(1)
+        .run(|| {
+            let _ = sync(storage);
+            activated.push(activated_names());
+        })
+        .unwrap();
+    assert_eq!(activated, [["storage::qualified ack"]]);
+}
+
+#[test]
+fn test_safety_valve() {
+    fn connect() -> io::Result<()> {
+        inject_return_io_error!("connect");
+        Ok(())
+    }
+    // retries indefinitely
+    let retry = || while connect().is_err() {};
+
+    let report = Runner::ignore_env().run(retry).unwrap();
+    assert_eq!(report.num_paths(), 1001);
+    assert_eq!(
+        report.safety_valve_triggered(),
+        [SafetyValve::Activations("connect")].into()
+    );
+    assert_eq!(
+        report.paths()[0].safety_valve,
+        [SafetyValve::Activations("connect")]
+    );
+    assert!(report.paths()[1].safety_valve.is_empty());
+    assert!(
+        report
+            .to_string()
+            .contains(", safety valve triggered on 1 path(s)")
+    );
+
+    let report = Runner::ignore_env()
+        .with_safety_limits(SafetyLimits {
+            max_activations: 2,
+            max_visits: 2,
+        })
+        .run(|| {
+            let _ = connect();
+            let _ = connect();
+            let _ = connect();
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 4);
+    assert_eq!(
+        report.safety_valve_triggered(),
+        [SafetyValve::Visits].into()
+    );
+    assert!(format!("{report:#}").contains("\n  safety valve triggered: visit limit"));
+
+    let err = Runner::ignore_env()
+        .with_safety_limits(SafetyLimits {
+            max_activations: 3,
+            ..Default::default()
+        })
+        .with_safety_valve_is_error(true)
+        .run(retry)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "safety valve triggered: connect activation limit"
+    );
+}
+
+#[test]
+#[cfg(not(feature = "audit"))]
+fn test_override_inferred_argument() {
+    let handle = |x| inject_override!(x, "override", x);
+    Runner::ignore_env()
+        .run(|| {
+            let _: u8 = handle(1u8);
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_current_phase() {
+    assert!(!is_active());
+    assert_eq!(current_iteration(), None);
+    assert_eq!(current_phase(), None);
+
+    let mut iterations = vec![];
+    Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_expected_panics(&[("tests/main.rs", line!() + 11)])
+        .with_failure_reruns(2)
+        .with_self_verification(1.0)
+        .run(|| {
+            assert!(is_active());
+            iterations.push((current_iteration().unwrap(), current_phase().unwrap()));
+            let failed = (|| -> Result<(), ()> {
+                inject_return!("connect", Err(()));
+                Ok(())
+            })()
+            .is_err();
+            assert!(!failed);
+        })
+        .unwrap();
+    assert_eq!(
+        iterations,
+        [
+            (0, Phase::Exploring),
+            (1, Phase::Replaying),
+            (2, Phase::Replaying),
+            (3, Phase::Exploring),
+            (4, Phase::Replaying),
+            (5, Phase::Replaying),
+        ]
+    );
+    assert!(!is_active());
+}
+
+#[test]
+fn test_error_tagging() {
+    use anyhow::Context;
+
+    #[derive(Debug)]
+    enum StoreError {
+        Io(io::Error),
+        Injected(&'static str),
+    }
+
+    impl std::fmt::Display for StoreError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Io(err) => write!(f, "{err}"),
+                Self::Injected(name) => write!(f, "injected {name}"),
+            }
+        }
+    }
+
+    impl std::error::Error for StoreError {}
+
+    impl From<io::Error> for StoreError {
+        fn from(err: io::Error) -> Self {
+            Self::Io(err)
+        }
+    }
+
+    fn open() -> io::Result<()> {
+        inject_return_io_error!("open");
+        Ok(())
+    }
+
+    fn store() -> Result<(), StoreError> {
+        Ok(()).faine("store")
+    }
+
+    fn process() -> anyhow::Result<()> {
+        open().context("cannot open")?;
+        store().context("cannot store")?;
+        Ok(())
+    }
+
+    let mut logged = vec![];
+    Runner::ignore_env()
+        .with_error_factory(StoreError::Injected)
+        .run(|| {
+            if let Err(err) = process() {
+                logged.push(format!("{err:#}"));
+            }
+        })
+        .unwrap();
+    assert_eq!(
+        logged,
+        ["cannot open: open", "cannot store: injected store"]
+    );
+
+    let mut logged = vec![];
+    let report = Runner::ignore_env()
+        .with_error_tagging(true)
+        .with_error_factory(StoreError::Injected)
+        .run(|| {
+            if let Err(err) = process() {
+                logged.push(format!("error: {err:#}"));
+            }
+        })
+        .unwrap();
+    assert_eq!(logged.len(), 2);
+    assert!(logged[0].starts_with("error: cannot open: open [faine i=0 p="));
+    assert!(logged[1].starts_with("error: cannot store: injected store [faine i=1 p="));
+    let activated = |line: &str| {
+        report
+            .resolve_tag(line)
+            .unwrap()
+            .activated_failpoints()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(activated(&logged[0]), ["open"]);
+    assert_eq!(activated(&logged[1]), ["store"]);
+    assert!(report.resolve_tag("error: cannot open: open").is_none());
+    assert!(report.resolve_tag("[faine i=2 p=00000000]").is_none());
+
+    // names with tags are kept once for each tag, and repeated runs
+    // produce the same tags, so these are reused
+    let tagged_names = || {
+        let mut names = vec![];
+        Runner::ignore_env()
+            .with_error_tagging(true)
+            .with_error_factory(StoreError::Injected)
+            .run(|| {
+                if let Err(StoreError::Injected(name)) = store() {
+                    names.push(name);
+                }
+            })
+            .unwrap();
+        names
+    };
+    let (first, second) = (tagged_names(), tagged_names());
+    assert_eq!(first.len(), 1);
+    assert!(first[0].starts_with("store [faine i=0 p="));
+    assert!(std::ptr::eq(first[0], second[0]));
+}
+
+#[test]
+fn test_injected_error_factory() {
+    #[derive(Debug)]
+    enum StoreError {
+        Io(#[allow(dead_code)] io::Error),
+        Injected(faine::InjectedError),
+    }
+
+    impl From<io::Error> for StoreError {
+        fn from(err: io::Error) -> Self {
+            Self::Io(err)
+        }
+    }
+
+    let mut errors = vec![];
+    let report = Runner::ignore_env()
+        .with_error_tagging(true)
+        .with_injected_error_factory(StoreError::Injected)
+        .run(|| {
+            let res: Result<(), StoreError> = Ok(()).faine("store");
+            if let Err(StoreError::Injected(err)) = res {
+                errors.push(err);
+            }
+        })
+        .unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].failpoint(), "store");
+    assert!(errors[0].tag().is_some());
+    let path = report.resolve_tag(&errors[0].to_string()).unwrap();
+    assert_eq!(path.activated_failpoints().collect::<Vec<_>>(), ["store"]);
+}
+
+#[test]
+fn test_required_checks() {
+    fn read(name: &'static str) -> Result<u32, ()> {
+        inject_return!(name, Err(()));
+        Ok(42)
+    }
+
+    let check = || {
+        let Ok(first) = read("first") else {
+            return;
+        };
+        checked!("always");
+        if read("second").is_ok() {
+            checked!("guarded");
+            assert_eq!(first, 42);
+        }
+    };
+
+    checked!("outside of runner");
+
+    let report = Runner::ignore_env().run(check).unwrap();
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(
+        report.check_counts().into_iter().collect::<Vec<_>>(),
+        [("always", 2), ("guarded", 1)]
+    );
+    let clean = report
+        .paths()
+        .iter()
+        .find(|path| path.activated_failpoints().next().is_none())
+        .unwrap();
+    assert_eq!(clean.checks, ["always", "guarded"]);
+    assert!(
+        report
+            .to_string()
+            .contains(", 2 check(s) not executed on all paths")
+    );
+    assert!(
+        format!("{report:#}").contains("\n  check partially executed: guarded (1 of 3 path(s))")
+    );
+
+    let err = Runner::ignore_env()
+        .with_required_checks(&["always"])
+        .run(check)
+        .unwrap_err();
+    let faine::Error::RequiredChecksNotExecuted { paths, .. } = err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(paths, ["#0: [first] (missing always)"]);
+
+    let err = Runner::ignore_env()
+        .with_required_checks(&["always", "guarded"])
+        .run(check)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "required check(s) not executed on path(s): #0: [first] (missing always, guarded), #1: first → [second] (missing guarded)"
+    );
+}
+
+#[test]
+fn test_current_path() {
+    assert!(current_path().is_empty());
+    assert!(activated_names().is_empty());
+
+    fn process() -> Result<(), ()> {
+        inject_return!("open", Err(()));
+        inject_return!("read", Err(()));
+        Ok(())
+    }
+
+    let mut paths = vec![];
+    Runner::ignore_env()
+        .with_aliases(&[("read", "read data")])
+        .run(|| {
+            assert!(current_path().is_empty());
+            let _ = process();
+            paths.push((current_path(), activated_names()));
+        })
+        .unwrap();
+    assert_eq!(
+        paths,
+        [
+            (
+                vec![("open".to_string(), Branch::Activate)],
+                vec!["open".to_string()]
+            ),
+            (
+                vec![
+                    ("open".to_string(), Branch::Skip),
+                    ("read data".to_string(), Branch::Activate)
+                ],
+                vec!["read data".to_string()]
+            ),
+            (
+                vec![
+                    ("open".to_string(), Branch::Skip),
+                    ("read data".to_string(), Branch::Skip)
+                ],
+                vec![]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_failure_summary() {
+    fn step(name: &'static str) -> Result<(), ()> {
+        inject_return!(name, Err(()));
+        Ok(())
+    }
+
+    fn process() {
+        // cache failure is absorbed, read failure is handled unless
+        // flushing fails too
+        let _ = step("cache");
+        if step("read").is_err() {
+            assert!(step("flush").is_ok(), "flush failed");
+        }
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = step("read");
+        })
+        .unwrap();
+    assert_eq!(report.failure_summary(), None);
+    assert!(report.failure_correlations().is_empty());
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(process)
+        .unwrap_err();
+    let faine::Error::UnexpectedPanics { report, .. } = &err else {
+        panic!("unexpected error {err}");
+    };
+    let correlations = report.failure_correlations();
+    let names: Vec<_> = correlations.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["flush", "read"]);
+    assert_eq!(correlations[0].1.failing, 2);
+    assert_eq!(correlations[0].1.num_passing, 4);
+    assert_eq!(correlations[1].1.score(), 1.0 - 0.5);
+    assert_eq!(
+        report.failure_summary().unwrap(),
+        "2 of 6 path(s) failed; correlated with failure: flush (2 of 2 failing vs 0 of 4 passing), read (2 of 2 failing vs 2 of 4 passing); first failing path: [cache] → [read] → [flush]"
+    );
+    assert!(
+        err.to_string()
+            .ends_with(&format!("\n{}", report.failure_summary().unwrap()))
+    );
+}
+
+#[test]
+fn test_parameters() {
+    fn read(buf: &[u8]) -> io::Result<usize> {
+        inject_override!(Ok(buf.len()), "short read", {
+            let len = buf.len() as f64 * param_f64("short read fraction");
+            Ok(len as usize)
+        })
+    }
+
+    let mut lengths = vec![];
+    let report = Runner::ignore_env()
+        .with_parameter("short read fraction", &[0.75, 0.5, 0.0])
+        .run(|| {
+            let first = read(b"data").unwrap();
+            let second = read(b"data").unwrap();
+            lengths.push((first, second));
+        })
+        .unwrap();
+    assert_eq!(
+        lengths,
+        [
+            (3, 3),
+            (3, 4),
+            (2, 2),
+            (2, 4),
+            (0, 0),
+            (0, 4),
+            (4, 3),
+            (4, 2),
+            (4, 0),
+            (4, 4)
+        ]
+    );
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths[2],
+        "[short read] → [short read fraction=0.5] → [short read]"
+    );
+    assert_eq!(
+        paths[4],
+        "[short read] → [short read fraction=0.0] → [short read]"
+    );
+    assert_eq!(paths[9], "short read → short read");
+
+    let res = Runner::ignore_env()
+        .with_parameter("short read fraction", &[])
+        .run(|| {})
+        .into_result();
+    assert!(matches!(res, Err(Error::Configuration(_))));
+
+    let res = Runner::ignore_env()
+        .with_parameter("short read fraction", &[0.5])
+        .with_parameter("short read fraction", &[0.0])
+        .run(|| {})
+        .into_result();
+    assert!(matches!(res, Err(Error::Configuration(_))));
+}
+
+#[test]
+#[should_panic(expected = "not declared")]
+fn test_undeclared_parameter() {
+    param_f64("undeclared fraction");
+}
+
+#[test]
+fn test_no_faults() {
+    fn record(truth: &mut Vec<&'static str>) -> Result<(), ()> {
+        let _cs = no_faults();
+        inject_return!("record", Err(()));
+        {
+            let _nested = no_faults();
+            inject_return!("record nested", Err(()));
+        }
+        // still in the outer section
+        inject_return!("record", Err(()));
+        truth.push("recorded");
+        Ok(())
+    }
+
+    fn process(truth: &mut Vec<&'static str>) -> Result<(), ()> {
+        inject_return!("prepare", Err(()));
+        record(truth)?;
+        // early return from the section restores injection
+        inject_return!("commit", Err(()));
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let mut truth = vec![];
+            let _ = process(&mut truth);
+            // ground truth is always recorded once reached
+            assert_eq!(truth.is_empty(), activated_names() == ["prepare"]);
+        })
+        .unwrap();
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths,
+        [
+            "[prepare]",
+            "prepare → [commit] (suppressed by critical section: record, record nested)",
+            "prepare → commit (suppressed by critical section: record, record nested)",
+        ]
+    );
+
+    // panic in the section restores injection as well
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = std::panic::catch_unwind(|| {
+                let _cs = no_faults();
+                panic!("recording failed");
+            });
+            inject_return!("commit", ());
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 2);
+
+    // leaked section is reported, but closed for further iterations
+    let report = Runner::ignore_env()
+        .run(|| {
+            if inject_override!(false, "prepare", true) {
+                std::mem::forget(no_faults());
+            }
+            inject_return!("commit", ());
+        })
+        .unwrap();
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths,
+        [
+            "[prepare] (suppressed by critical section: commit) (critical section held)",
+            "prepare → [commit]",
+            "prepare → commit",
+        ]
+    );
+    assert!(
+        report
+            .to_string()
+            .contains("critical section held at the end of 1 path(s)")
+    );
+
+    // guard outliving its iteration does not close a section of the next one
+    let mut leaked = None;
+    let report = Runner::ignore_env()
+        .run(|| {
+            inject_override!((), "prepare", ());
+            let _cs = no_faults();
+            inject_return!("inside", ());
+            drop(leaked.take());
+            inject_return!("inside again", ());
+            leaked = Some(no_faults());
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 2);
+    assert!(report.paths().iter().all(|path| {
+        path.suppressed_by_critical_section == ["inside", "inside again"]
+            && path.critical_section_held
+    }));
+}
+
+#[test]
+fn test_recent_events() {
+    fn describe(path: &ExecutionPath) -> Vec<String> {
+        let recent_events = path.recent_events.as_ref().unwrap();
+        let mut events = vec![];
+        if recent_events.is_truncated() {
+            events.push(format!("dropped {}", recent_events.num_dropped));
+        }
+        events.extend(recent_events.events.iter().map(|event| match &event.kind {
+            RecentEventKind::Visit(step) => step.to_string(),
+            RecentEventKind::Counter { name, value } => format!("{name} += {value}"),
+            RecentEventKind::Check(name) => format!("check {name}"),
+            _ => unreachable!(),
+        }));
+        events
+    }
+
+    fn compact() -> Result<(), ()> {
+        for _ in 0..3 {
+            inject_return!("read segment", Err(()));
+            count_by!("bytes read", 512);
+        }
+        checked!("segments read");
+        inject_return!("write segment", Err(()));
+        panic!("lost segment");
+    }
+
+    let run = |runner: Runner| {
+        let err = runner
+            .with_catch_panics(true)
+            .run(|| {
+                let _ = compact();
+            })
+            .unwrap_err();
+        let faine::Error::UnexpectedPanics { report, .. } = err else {
+            panic!("unexpected error {err:?}");
+        };
+        report
+    };
+
+    let report = run(Runner::ignore_env());
+    let failed: Vec<_> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(
+        describe(failed[0]),
+        [
+            "read segment",
+            "bytes read += 512",
+            "read segment",
+            "bytes read += 512",
+            "read segment",
+            "bytes read += 512",
+            "check segments read",
+            "write segment",
+        ]
+    );
+    // timestamps are relative to the start of the iteration, in order
+    let recent_events = failed[0].recent_events.as_ref().unwrap();
+    assert!(recent_events.events.is_sorted_by_key(|event| event.at));
+    assert!(
+        report
+            .paths()
+            .iter()
+            .filter(|path| !path.is_failed())
+            .all(|path| path.recent_events.is_none())
+    );
+
+    // only the tail is kept
+    let report = run(Runner::ignore_env().with_recent_events(3));
+    let failed: Vec<_> = report
+        .paths()
+        .iter()
+        .filter(|path| path.is_failed())
+        .collect();
+    assert_eq!(
+        describe(failed[0]),
+        [
+            "dropped 5",
+            "bytes read += 512",
+            "check segments read",
+            "write segment"
+        ]
+    );
+
+    let report = run(Runner::ignore_env().with_recent_events(0));
+    assert!(
+        report
+            .paths()
+            .iter()
+            .all(|path| path.recent_events.is_none())
+    );
+}
+
+#[test]
+fn test_weights() {
+    fn update_stats() -> io::Result<()> {
+        inject_return_io_error!("update stats", weight: Low);
+        Ok(())
+    }
+
+    fn write_journal() -> io::Result<()> {
+        inject_return_io_error!("write journal", weight: High);
+        Ok(())
+    }
+
+    fn sync_journal() -> Result<(), ()> {
+        inject_return!("sync journal", Err(()), weight: High);
+        Ok(())
+    }
+
+    let commit = || {
+        let _ = update_stats();
+        let _ = write_journal();
+        let _ = sync_journal();
+    };
+
+    // without budget, weights do not affect exploration
+    let report = Runner::ignore_env().run(commit).unwrap();
+    assert_eq!(report.num_paths(), 8);
+    assert!(report.paths()[0].activated("update stats"));
+    assert!(report.unexplored_slots().is_empty());
+
+    let report = Runner::ignore_env()
+        .with_max_iterations(4)
+        .run(commit)
+        .unwrap();
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths,
+        [
+            "update stats → [write journal] → [sync journal]",
+            "update stats → [write journal] → sync journal",
+            "update stats → write journal → [sync journal]",
+            "update stats → write journal → sync journal",
+        ]
+    );
+    assert_eq!(
+        report.unexplored_slots(),
+        &BTreeMap::from([(Weight::Low, 1)])
+    );
+    assert!(report.to_string().contains("(unexplored slots: 1 low)"));
+
+    // with equal weights, exploration order is the usual one
+    let report = Runner::ignore_env()
+        .with_max_iterations(2)
+        .run(|| {
+            let _ = write_journal();
+            let _ = sync_journal();
+        })
+        .unwrap();
+    assert_eq!(
+        report.paths()[0].to_string(),
+        "[write journal] → [sync journal]"
+    );
+    assert_eq!(
+        report.unexplored_slots(),
+        &BTreeMap::from([(Weight::High, 1)])
+    );
+}
+
+#[test]
+fn test_atomic_replace_property() {
+    fn replace_file(path: &std::path::Path, content: &str) -> io::Result<()> {
+        inject_return_io_error!("create new file");
+        let mut file = std::fs::File::create(path)?;
+        inject_return_io_error!("write new file");
+        std::io::Write::write_all(&mut file, content.as_bytes())
+    }
+
+    let found = Runner::ignore_env()
+        .run_expect_failure(|| {
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = tempdir.path().join("myfile");
+            std::fs::write(&path, "old").unwrap();
+            let res = replace_file(&path, "new");
+            faine::props::atomic_replace(&path, b"old", b"new", &res).unwrap();
+        })
+        .unwrap();
+    assert_eq!(found.paths().len(), 1);
+    let message = found.paths()[0]
+        .panic
+        .as_ref()
+        .unwrap()
+        .message
+        .as_ref()
+        .unwrap();
+    assert!(message.contains("content is empty"));
+    assert!(message.contains("activated: write new file"));
+    assert_eq!(found.report().check_counts()["atomic replace"], 3);
+}
+
+#[test]
+fn test_checkpoint() {
+    fn commit(journal: &mut Vec<u32>, index: &mut Vec<u32>) -> Result<(), ()> {
+        journal.push(1);
+        if inject_override!(true, "update index", false) {
+            index.push(1);
+        }
+        checkpoint("journal sealed", || index.len() == journal.len());
+        inject_return!("flush", Err(()));
+        Ok(())
+    }
+
+    let check = || {
+        let (mut journal, mut index) = (vec![], vec![]);
+        let _ = commit(&mut journal, &mut index);
+        // recovery repairs the index, masking the violation
+        index.clone_from(&journal);
+        assert_eq!(index, journal);
+    };
+
+    let found = Runner::ignore_env().run_expect_failure(check).unwrap();
+    let paths: Vec<_> = found.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(paths.len(), 1);
+    assert!(paths[0].starts_with(
+        "[update index] → ⟨journal sealed⟩ (checkpoint journal sealed violated: check failed) (panicked at tests/main.rs:"
+    ));
+    assert_eq!(found.paths()[0].key(), "[update index]");
+
+    let mut num_finished = 0;
+    let err = Runner::ignore_env()
+        .with_continue_after_checkpoint_violation(true)
+        .run(|| {
+            check();
+            num_finished += 1;
+        })
+        .unwrap_err();
+    assert_eq!(num_finished, 4);
+    let Error::CheckpointViolated { paths, report } = err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(
+        paths,
+        [
+            "#0: [update index] → ⟨journal sealed⟩ → [flush] (checkpoint journal sealed violated: check failed)",
+            "#1: [update index] → ⟨journal sealed⟩ → flush (checkpoint journal sealed violated: check failed)",
+        ]
+    );
+    let violations = report.checkpoint_violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations["journal sealed"].len(), 2);
+    assert_eq!(
+        report.paths()[3].to_string(),
+        "update index → ⟨journal sealed⟩ → flush"
+    );
+    assert_eq!(report.paths()[3].key(), "update index → flush");
+    let checkpoint = &report.paths()[3].checkpoints[0];
+    assert_eq!(
+        (checkpoint.name, checkpoint.position, &checkpoint.violation),
+        ("journal sealed", 1, &None)
+    );
+}
+
+#[test]
+fn test_adaptive_hit_limits() {
+    fn retries_until_success(name: &'static str) -> usize {
+        let mut retries = 0;
+        while !(|| {
+            inject_return!(name, false);
+            true
+        })() {
+            retries += 1;
+        }
+        retries
+    }
+
+    let report = Runner::ignore_env()
+        .with_adaptive_hit_limits(1, 64)
+        .run(|| {
+            let retries = retries_until_success("connect");
+            // gives up with a distinct outcome after 3 retries
+            record_outcome(retries.min(3));
+        })
+        .unwrap();
+    let hit_limits = report.hit_limits().unwrap();
+    assert_eq!(hit_limits.limit("connect"), 4);
+    assert_eq!(hit_limits.limit("other"), 1);
+    assert_eq!(hit_limits.rounds, 4);
+    assert!(hit_limits.fixed_point);
+    assert!(
+        report
+            .to_string()
+            .contains(", hit limits: connect=4 (fixed point reached after 4 round(s))")
+    );
+    // the report is of the exploration with found limits
+    assert_eq!(report.num_paths(), 5);
+
+    // dedup store is not shared between rounds
+    let store = tempfile::tempdir().unwrap();
+    let mut iterations = 0;
+    let report = Runner::ignore_env()
+        .with_adaptive_hit_limits(1, 64)
+        .with_dedup_store(store.path())
+        .with_scenario_key("adaptive")
+        .run(|| {
+            iterations += 1;
+            let retries = retries_until_success("connect");
+            record_outcome(retries.min(3));
+        })
+        .unwrap();
+    assert_eq!(report.hit_limits().unwrap().limit("connect"), 4);
+    assert_eq!(report.num_paths(), 5);
+    assert_eq!(iterations, 2 + 3 + 5 + 9 + 5);
+
+    // budget is shared by all rounds
+    let mut iterations = 0;
+    Runner::ignore_env()
+        .with_adaptive_hit_limits(1, 64)
+        .with_max_iterations(8)
+        .run(|| {
+            iterations += 1;
+            let retries = retries_until_success("connect");
+            record_outcome(retries.min(3));
+        })
+        .unwrap();
+    assert!(iterations <= 8);
+
+    // limits do not exceed the safety limit
+    let report = Runner::ignore_env()
+        .with_adaptive_hit_limits(1, 64)
+        .with_safety_limits(SafetyLimits {
+            max_activations: 2,
+            ..Default::default()
+        })
+        .run(|| {
+            let retries = retries_until_success("connect");
+            record_outcome(retries.min(3));
+        })
+        .unwrap();
+    let hit_limits = report.hit_limits().unwrap();
+    assert_eq!(hit_limits.limit("connect"), 2);
+    assert!(!hit_limits.fixed_point);
+
+    let report = Runner::ignore_env()
+        .with_adaptive_hit_limits(2, 8)
+        .run(|| {
+            let retries = retries_until_success("read");
+            record_outcome(retries);
+        })
+        .unwrap();
+    let hit_limits = report.hit_limits().unwrap();
+    assert_eq!(hit_limits.limit("read"), 8);
+    assert!(!hit_limits.fixed_point);
+    assert_eq!(report.num_paths(), 9);
+
+    let report = Runner::ignore_env()
+        .with_adaptive_hit_limits(2, 8)
+        .run(|| {
+            let _ = retries_until_success("read");
+        })
+        .unwrap();
+    let hit_limits = report.hit_limits().unwrap();
+    assert_eq!(hit_limits.limit("read"), 2);
+    assert!(hit_limits.fixed_point);
+}
+
+#[test]
+fn test_hygiene_report() {
+    fn sync() -> Result<(), &'static str> {
+        inject_return!("open", Err("io"));
+        inject_return!("write", Err("io"));
+        if inject_override!(true, "checksum", false) {
+            return Err("corrupt");
+        }
+        // result is ignored
+        inject_return!("fsync dir", Ok(()));
+        Ok(())
+    }
+
+    let report = Runner::ignore_env().run(|| record_outcome(sync())).unwrap();
+    assert!(report.hygiene().is_none());
+
+    let report = Runner::ignore_env()
+        .with_hygiene_report(true)
+        .run(|| record_outcome(sync()))
+        .unwrap();
+    // exploration is not affected
+    assert_eq!(report.paths().len(), 5);
+    let hygiene = report.hygiene().unwrap();
+    assert_eq!(hygiene["open"].pairs, 1);
+    assert_eq!(hygiene["open"].differing, 1);
+    // independent error sites with the same outcome are not mergeable
+    assert_eq!(hygiene["open"].suggestion, Suggestion::Keep);
+    assert_eq!(hygiene["write"].suggestion, Suggestion::Keep);
+    assert_eq!(hygiene["checksum"].suggestion, Suggestion::Keep);
+    assert_eq!(hygiene["fsync dir"].suggestion, Suggestion::Remove);
+    assert!(format!("{report:#}").contains(
+        "\n  hygiene of checksum: 1 of 1 sibling pair(s) differ, keep\
+         \n  hygiene of fsync dir: 0 of 1 sibling pair(s) differ, consider removing\
+         \n  hygiene of open: 1 of 1 sibling pair(s) differ, keep\
+         \n  hygiene of write: 1 of 1 sibling pair(s) differ, keep"
+    ));
+}
+
+fn load_config() -> io::Result<String> {
+    inject_return_io_error!("read config");
+    Ok("debug".to_string())
+}
+
+fn test_explore_plain_impl() {
+    if let Ok(config) = load_config() {
+        assert_eq!(config, "debug");
+    }
+}
+
+fn test_explore_result_impl() -> io::Result<()> {
+    let config = load_config().or_else(|_| io::Result::Ok("default".to_string()))?;
+    assert!(config == "debug" || config == "default");
+    Ok(())
+}
+
+fn test_explore_result_failing_impl() -> io::Result<()> {
+    load_config()?;
+    Ok(())
+}
+
+fn test_explore_panicking_impl() {
+    assert_eq!(load_config().unwrap(), "debug");
+}
+
+#[test]
+fn test_explore_plain() {
+    faine::explore(test_explore_plain_impl)
+}
+
+#[test]
+fn test_explore_result() {
+    faine::explore_result(test_explore_result_impl)
+}
+
+#[test]
+#[should_panic(expected = "panic message: test function returned an error: Custom { kind: Other")]
+fn test_explore_result_failing() {
+    faine::explore_result(test_explore_result_failing_impl)
+}
+
+#[test]
+#[should_panic(expected = "unexpected panic(s) at tests/main.rs:")]
+fn test_explore_panicking() {
+    faine::explore(test_explore_panicking_impl)
+}
+
+#[test]
+fn test_enable_failpoints_isolated_between_iterations() {
+    fn process() -> Result<(), ()> {
+        enable_failpoints(false);
+        inject_return!("disabled", Err(()));
+        enable_failpoints(true);
+        inject_return!("commit", Err(()));
+        Ok(())
+    }
+
+    fn run() -> Result<(), ()> {
+        inject_return!("prepare", Err(()));
+        process()?;
+        inject_return!("cleanup", Err(()));
+        Ok(())
+    }
+
+    // early return from the caller does not leak disabled failpoints
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = run();
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 4);
+    assert!(report.paths().iter().all(|path| !path.left_disabled));
+
+    // early return while disabled is reported, but does not affect
+    // further iterations
+    fn leaky() -> Result<(), ()> {
+        let fail = inject_override!(false, "prepare", true);
+        enable_failpoints(false);
+        if fail {
+            return Err(());
+        }
+        enable_failpoints(true);
+        inject_return!("commit", Err(()));
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = leaky();
+        })
+        .unwrap();
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths,
+        [
+            "[prepare] (failpoints left disabled)",
+            "prepare → [commit]",
+            "prepare → commit"
+        ]
+    );
+    assert!(
+        report
+            .to_string()
+            .contains("failpoints left disabled on 1 path(s)")
+    );
+}
+
+#[test]
+fn test_run_staged() {
+    fn write_v1(disk: &mut Vec<&'static str>) -> Result<(), ()> {
+        inject_return!("write data", Err(()));
+        disk.push("data");
+        inject_return!("write index", Err(()));
+        disk.push("index");
+        Ok(())
+    }
+
+    fn migrate_v2(disk: &mut Vec<&'static str>) -> Result<(), ()> {
+        if disk.contains(&"index") {
+            inject_return!("rewrite index", Err(()));
+            disk.retain(|file| *file != "index");
+            disk.push("index v2");
+        }
+        Ok(())
+    }
+
+    let mut num_stage1_runs = 0;
+    let report = Runner::ignore_env()
+        .run_staged(
+            || {
+                num_stage1_runs += 1;
+                let mut disk = vec![];
+                let _ = write_v1(&mut disk);
+                disk
+            },
+            |disk| disk.clone(),
+            |disk| {
+                let mut disk = disk.clone();
+                let _ = migrate_v2(&mut disk);
+            },
+        )
+        .unwrap();
+    assert_eq!(num_stage1_runs, 3);
+    assert_eq!(report.outer.num_paths(), 3);
+    let stages: Vec<_> = report
+        .stages
+        .iter()
+        .map(|stage| {
+            (
+                stage.key.clone(),
+                stage.outer_path.to_string(),
+                stage.report.num_paths(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        stages,
+        [
+            (vec![], "[write data]".to_string(), 1),
+            (vec!["data"], "write data → [write index]".to_string(), 1),
+            (
+                vec!["data", "index"],
+                "write data → write index".to_string(),
+                2
+            ),
+        ]
+    );
+    assert_eq!(report.num_inner_paths(), 4);
+    assert_eq!(
+        report.to_string(),
+        "3 outer path(s), 3 distinct state(s), 4 inner path(s)"
+    );
+    for stage in &report.stages {
+        assert_eq!(
+            stage.report.outer_path().map(ExecutionPath::key),
+            Some(stage.outer_path.key())
+        );
+    }
+
+    // explorations of the second stage do not share the dedup store
+    fn check(disk: &[&'static str]) -> Result<usize, ()> {
+        inject_return!("check", Err(()));
+        Ok(disk.len())
+    }
+    let store = tempfile::tempdir().unwrap();
+    let report = Runner::ignore_env()
+        .with_dedup_store(store.path())
+        .with_scenario_key("staged")
+        .run_staged(
+            || {
+                let mut disk = vec![];
+                let _ = write_v1(&mut disk);
+                disk
+            },
+            |disk| disk.clone(),
+            |disk| {
+                let _ = check(disk);
+            },
+        )
+        .unwrap();
+    assert_eq!(report.num_inner_paths(), 6);
+
+    // failure of the second stage states both paths
+    let err = Runner::ignore_env()
+        .with_stage_split(0.25)
+        .run_staged(
+            || {
+                let mut disk = vec![];
+                let _ = write_v1(&mut disk);
+                disk
+            },
+            |disk| disk.len(),
+            |disk| {
+                let mut disk = disk.clone();
+                let res = migrate_v2(&mut disk);
+                assert!(res.is_ok() || disk.contains(&"index v2"));
+            },
+        )
+        .unwrap_err();
+    let Error::StageFailed {
+        outer_path,
+        state,
+        inner_path,
+        error,
+    } = &err
+    else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(outer_path, "write data → write index");
+    assert_eq!(state, "2");
+    assert_eq!(inner_path.as_deref(), Some("[rewrite index]"));
+    let Error::UnexpectedPanics { report, .. } = error.as_ref() else {
+        panic!("unexpected error {error}");
+    };
+    assert_eq!(
+        report.outer_path().map(ExecutionPath::key),
+        Some(outer_path.clone())
+    );
+    assert!(err.to_string().starts_with(
+        "outer path write data → write index produced state 2; inner path [rewrite index] then failed: unexpected panic(s) at tests/main.rs:"
+    ));
+
+    let res = Runner::ignore_env()
+        .with_stage_split(1.5)
+        .run_staged(|| (), |_| (), |_| {})
+        .into_result();
+    assert!(matches!(res, Err(Error::Configuration(_))));
+}
+
+#[test]
+fn test_rng() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn connect_with_backoff(jitter: u64) -> Result<(), ()> {
+        inject_return!("connect", Err(()));
+        if jitter.is_multiple_of(2) {
+            inject_return!("short backoff", Err(()));
+        } else {
+            inject_return!("long backoff", Err(()));
+        }
+        inject_return!("reconnect", Err(()));
+        Ok(())
+    }
+
+    // stands in for a generator seeded from entropy, as its state
+    // differs between iterations
+    static STATE: AtomicU64 = AtomicU64::new(0);
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = connect_with_backoff(STATE.fetch_add(1, Ordering::Relaxed));
+        })
+        .unwrap();
+    assert!(report.non_determinism_witnessed());
+
+    let mut jitters = vec![];
+    let report = Runner::ignore_env()
+        .with_strict_determinism(true)
+        .with_failure_reruns(2)
+        .with_catch_panics(true)
+        .with_expected_panics(&[(file!(), line!() + 6)])
+        .run(|| {
+            let jitter = rng().range(0..1000);
+            jitters.push(jitter);
+            let res = connect_with_backoff(jitter);
+            if activated_names() == ["reconnect"] {
+                assert!(res.is_ok(), "reconnect failed");
+            }
+        })
+        .unwrap();
+    assert!(!report.non_determinism_witnessed());
+    assert_eq!(report.num_paths(), 4);
+    // the value is drawn before any decisions, so it's the same on all paths
+    assert!(jitters.iter().all(|jitter| *jitter == jitters[0]));
+
+    // seed changes drawn values
+    let mut other = vec![];
+    Runner::ignore_env()
+        .with_rng_seed(1)
+        .run(|| other.push(rng().next_u64()))
+        .unwrap();
+    let mut default = vec![];
+    Runner::ignore_env()
+        .run(|| default.push(rng().next_u64()))
+        .unwrap();
+    assert_ne!(other, default);
+
+    // explored values at a call site
+    let mut jitters = vec![];
+    let report = Runner::ignore_env()
+        .run(|| jitters.push(inject_override_rng!(rng().range(10..20), "jitter", values: [10, 19])))
+        .unwrap();
+    assert_eq!(&jitters[..2], [10, 19]);
+    assert_eq!(report.paths()[0].to_string(), "[jitter=10]");
+}
+
+#[test]
+fn test_counters() {
+    fn write_with_retries(attempts: usize) -> Result<(), ()> {
+        for _ in 0..attempts {
+            count_by!("bytes written", 4);
+            let res: Result<(), ()> = inject_override!(Ok(()), "write", Err(()));
+            if res.is_ok() {
+                return Ok(());
+            }
+            count!("retries");
+        }
+        Err(())
+    }
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = write_with_retries(3);
+        })
+        .unwrap();
+    // paths with 3, 2, 1 and 0 retries
+    assert_eq!(report.num_paths(), 4);
+    assert_eq!(
+        report.paths()[0].counters,
+        [("bytes written", 12), ("retries", 3)]
+    );
+    assert_eq!(report.paths()[3].counters, [("bytes written", 4)]);
+    let stats = report.counter_stats();
+    assert_eq!(stats["retries"].min, 0);
+    assert_eq!(stats["retries"].median, 1);
+    assert_eq!(stats["retries"].max, 3);
+    assert_eq!(stats["retries"].max_paths, [0]);
+    assert!(format!("{report:#}").contains("\n  counter retries: min 0, median 1, max 3 (#0)"));
+
+    let err = Runner::ignore_env()
+        .with_counter_limit("retries", 2)
+        .run(|| {
+            let _ = write_with_retries(3);
+        })
+        .unwrap_err();
+    assert!(matches!(err, Error::CounterLimitExceeded { .. }));
+    assert!(err.to_string().starts_with(
+        "counter limit(s) exceeded on path(s): #0: [write] → [write] → [write] (retries = 3 > 2)"
+    ));
+}
+
+#[test]
+fn test_run_expect_failure() {
+    fn process() -> Result<(), ()> {
+        inject_return!("open", Err(()));
+        inject_return!("read", Err(()));
+        Ok(())
+    }
+
+    let found = Runner::ignore_env()
+        .run_expect_failure(|| {
+            if process().is_err() {
+                assert!(!activated_names().contains(&"read".to_string()));
+            }
+        })
+        .unwrap();
+    let paths: Vec<_> = found.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(paths.len(), 1);
+    assert!(paths[0].starts_with("open → [read] (panicked at tests/main.rs:"));
+    assert!(found.paths()[0].activated("read"));
+    assert!(!found.paths()[0].activated("open"));
+    assert_eq!(found.report().num_paths(), 3);
+
+    let err = Runner::ignore_env()
+        .run_expect_failure(|| {
+            let _ = process();
+        })
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::NoFailureFound {
+            paths_explored: 3,
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "no failure found where expected, 3 path(s) explored"
+    );
+}
+
+#[test]
+fn test_shards() {
+    fn process() -> u32 {
+        inject_return!("open", 0);
+        let a = inject_override!(1, "first", 0);
+        let b = inject_override!(2, "second", 0);
+        let c = inject_override!(4, "third", 0);
+        let d = inject_override!(8, "fourth", 0);
+        a + b + c + d
+    }
+    fn sorted_paths(report: &Report) -> Vec<String> {
+        let mut paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+        paths.sort();
+        paths
+    }
+
+    let unsharded = Runner::ignore_env()
+        .run(|| {
+            process();
+        })
+        .unwrap();
+    assert_eq!(unsharded.num_paths(), 17);
+
+    let mut iterations = 0;
+    let reports: Vec<Report> = (0..3)
+        .map(|index| {
+            Runner::ignore_env()
+                .with_shard(index, 3)
+                .with_shard_depth(3)
+                .run(|| {
+                    iterations += 1;
+                    process();
+                })
+                .unwrap()
+        })
+        .collect();
+    assert!(iterations < 3 * unsharded.num_paths());
+    assert_eq!(
+        reports.iter().map(Report::num_paths).sum::<usize>(),
+        unsharded.num_paths()
+    );
+    for (index, report) in reports.iter().enumerate() {
+        assert_eq!(report.shard().unwrap().index, index);
+    }
+
+    let merged = Report::merge_shards(reports.clone()).unwrap();
+    assert_eq!(sorted_paths(&merged), sorted_paths(&unsharded));
+
+    let err = Report::merge_shards(reports[..2].to_vec()).unwrap_err();
+    assert!(matches!(err, Error::ShardsIncomplete { .. }));
+    assert_eq!(
+        err.to_string(),
+        "merged shards do not cover the exploration: shard 2 is missing"
+    );
+    let err = Report::merge_shards([unsharded]).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("merged shards do not cover the exploration: report is not of a shard")
+    );
+    let err = Report::merge_shards([]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "merged shards do not cover the exploration: no shard reports"
+    );
+
+    // whole run checks are performed on merge
+    let reports: Vec<Report> = (0..3)
+        .map(|index| {
+            Runner::ignore_env()
+                .with_shard(index, 3)
+                .with_shard_depth(3)
+                .with_expected_failpoints(&["fourth", "fifth"])
+                .run(|| {
+                    process();
+                })
+                .unwrap()
+        })
+        .collect();
+    let err = Report::merge_shards(reports).unwrap_err();
+    assert_eq!(err.to_string(), "expected failpoint(s) not visited: fifth");
+}
+
+#[test]
+fn test_failpoint_handles() {
+    failpoints! {
+        CREATE_TEMP_FILE = "create temp file";
+        WRITE_TEMP_FILE = "write temp file";
+        REMOVE_TEMP_FILE = "remove temp file";
+    }
+
+    fn save() -> io::Result<()> {
+        inject_return_io_error!(CREATE_TEMP_FILE);
+        inject_return_io_error!("write temp file");
+        Ok(())
+    }
+
+    let report = Runner::ignore_env()
+        .with_expected_failpoints(&[CREATE_TEMP_FILE, WRITE_TEMP_FILE])
+        .with_forbidden(&[REMOVE_TEMP_FILE])
+        .with_priority_paths(&[&[WRITE_TEMP_FILE.name()]])
+        .run(|| {
+            let _ = save();
+        })
+        .unwrap();
+    assert_eq!(
+        report.paths()[0].to_string(),
+        "create temp file → [write temp file]"
+    );
+    assert_eq!(report.num_paths(), 3);
+    assert_eq!(
+        CREATE_TEMP_FILE.id(),
+        faine::FailpointId::intern("create temp file")
+    );
+
+    let err = Runner::ignore_env()
+        .with_expected_failpoints(&[
+            Selector::from("create temp file"),
+            WRITE_TEMP_FILE.into(),
+            REMOVE_TEMP_FILE.into(),
+        ])
+        .run(|| {
+            let _ = save();
+        })
+        .unwrap_err();
+    assert!(matches!(err, Error::ExpectedFailpointsNotVisited { .. }));
+    assert_eq!(
+        err.to_string(),
+        "expected failpoint(s) not visited: faine::remove temp file"
+    );
+
+    // sets of handles are checked as a whole, so a handle added to a
+    // set but never visited is caught without listing it
+    failpoints! {
+        TEMP_FILE {
+            OPEN_TEMP_FILE = "open temp file";
+            SYNC_TEMP_FILE = "sync temp file";
+        }
+    }
+
+    fn open() -> io::Result<()> {
+        inject_return_io_error!(OPEN_TEMP_FILE);
+        Ok(())
+    }
+
+    assert_eq!(TEMP_FILE, [OPEN_TEMP_FILE, SYNC_TEMP_FILE]);
+    let err = Runner::ignore_env()
+        .with_expected_failpoints(TEMP_FILE)
+        .with_priority_paths(&[])
+        .run(|| {
+            let _ = open();
+        })
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected failpoint(s) not visited: faine::sync temp file"
+    );
+}
+
+#[test]
+fn test_env_axis() {
+    const VAR: &str = "FAINE_TEST_ENV_AXIS";
+
+    fn read_var(seen: &mut Vec<(&'static str, Option<String>)>) -> Result<(), usize> {
+        let value = std::env::var(VAR).ok();
+        inject_return!("use value", Err(1));
+        seen.push((axis("$FAINE_TEST_ENV_AXIS"), value));
+        Ok(())
+    }
+
+    let mut seen = vec![];
+    let report = Runner::ignore_env()
+        .with_env_axis(VAR, &[None, Some("valid"), Some("garbage")])
+        .run(|| {
+            let _ = read_var(&mut seen);
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 6);
+    seen.sort();
+    assert_eq!(
+        seen,
+        [
+            ("\"garbage\"", Some("garbage".to_string())),
+            ("\"valid\"", Some("valid".to_string())),
+            ("unset", None),
+        ]
+    );
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert!(paths.contains(&"{$FAINE_TEST_ENV_AXIS=unset} [use value]".to_string()));
+    assert!(paths.contains(&"{$FAINE_TEST_ENV_AXIS=\"garbage\"} use value".to_string()));
+    assert!(std::env::var_os(VAR).is_none());
+
+    let err = Runner::ignore_env()
+        .with_env_axis(VAR, &[None])
+        .with_env_axis(VAR, &[Some("valid")])
+        .run(|| {})
+        .unwrap_err();
+    assert!(matches!(err, Error::Configuration(_)));
+}
+
+#[test]
+fn test_env_axis_restored_on_panic() {
+    const VAR: &str = "FAINE_TEST_ENV_AXIS_PANIC";
+
+    // caught panics
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_env_axis(VAR, &[Some("first"), Some("second")])
+        .run(|| {
+            fn fail() -> Result<(), usize> {
+                inject_return!("fail", Err(1));
+                panic!("{}", std::env::var(VAR).unwrap());
+            }
+            let _ = fail();
+        })
+        .unwrap_err();
+    assert!(matches!(err, Error::UnexpectedPanics { .. }));
+    assert!(std::env::var_os(VAR).is_none());
+
+    // panic escaping the runner
+    let res = std::panic::catch_unwind(|| {
+        Runner::ignore_env()
+            .with_env_axis(VAR, &[Some("first"), Some("second")])
+            .run(|| {
+                assert_eq!(std::env::var(VAR).as_deref(), Ok("never"));
+            })
+    });
+    assert!(res.is_err());
+    assert!(std::env::var_os(VAR).is_none());
+
+    // the lock is not left poisoned
+    Runner::ignore_env()
+        .with_env_axis(VAR, &[Some("first")])
+        .run(|| assert_eq!(std::env::var(VAR).as_deref(), Ok("first")))
+        .unwrap();
+}
+
+#[test]
+#[cfg(not(feature = "audit"))]
+fn test_inject_return_non_debug() {
+    struct NoDebug;
+
+    let func = || {
+        inject_return!("return", Err(5));
+        Ok::<NoDebug, i32>(NoDebug)
+    };
+    Runner::ignore_env()
+        .run(|| {
+            let _ = func();
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_event_sink() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl EventSink for Recorder {
+        fn on_visit(&mut self, failpoint: &'static str, branch: Branch, iteration: usize) {
+            // failpoints visited by the sink are not reported
+            let _ = (|| -> Result<(), ()> {
+                inject_return!("sink", Err(()));
+                Ok(())
+            })();
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("visit {failpoint} {branch:?} #{iteration}"));
+        }
+
+        fn on_iteration_end(&mut self, path: &ExecutionPath, _duration: Duration) {
+            self.0.lock().unwrap().push(format!("end {path}"));
+        }
+
+        fn on_run_end(&mut self, report: &Report) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("run end {}", report.num_paths()));
+        }
+    }
+
+    let events = Arc::new(Mutex::new(vec![]));
+    Runner::ignore_env()
+        .with_event_sink(Recorder(events.clone()))
+        .run(|| {
+            let _ = (|| -> Result<(), ()> {
+                inject_return!("a", Err(()));
+                inject_return!("b", Err(()));
+                Ok(())
+            })();
+        })
+        .unwrap();
+
+    // the sink is released when the run finishes
+    let events = Arc::into_inner(events).unwrap().into_inner().unwrap();
+    assert_eq!(
+        events,
+        [
+            "visit a Activate #0",
+            "end [a]",
+            "visit a Skip #1",
+            "visit b Activate #1",
+            "end a → [b]",
+            "visit a Skip #2",
+            "visit b Skip #2",
+            "end a → b",
+            "run end 3",
+        ]
+    );
+}
+
+#[test]
+fn test_per_type() {
+    trait Backend {}
+    struct Disk;
+    struct Net;
+    impl Backend for Disk {}
+    impl Backend for Net {}
+
+    fn flush<T: Backend>(_backend: &T) -> io::Result<()> {
+        inject_return_io_error!("flush", per_type: T);
+        Ok(())
+    }
+
+    fn sync<T: Backend>(_backend: &T) -> Result<(), usize> {
+        inject_return!(Err(1), per_type: T);
+        Ok(())
+    }
+
+    let disk = std::any::type_name::<Disk>();
+    let net = std::any::type_name::<Net>();
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = flush(&Disk);
+            let _ = flush(&Net);
+        })
+        .unwrap();
+    // each instantiation gets its own decision
+    let paths: Vec<Vec<_>> = report
+        .paths()
+        .iter()
+        .map(|path| path.activated_failpoints().collect())
+        .collect();
+    let (disk, net) = (format!("flush<{disk}>"), format!("flush<{net}>"));
+    assert_eq!(
+        paths,
+        vec![
+            vec![disk.as_str(), net.as_str()],
+            vec![disk.as_str()],
+            vec![net.as_str()],
+            vec![],
+        ]
+    );
+
+    // base name covers all instantiations, full name only one of them
+    let forbidden = |selector: &str| {
+        let err = Runner::ignore_env()
+            .with_forbidden(&[selector])
+            .run(|| {
+                let _ = flush(&Disk);
+                let _ = flush(&Net);
+            })
+            .unwrap_err();
+        let Error::ForbiddenFailpointReached { name, .. } = err else {
+            panic!("unexpected error {err:?}");
+        };
+        name
+    };
+    assert_eq!(forbidden("flush"), disk);
+    assert_eq!(forbidden(&net), net);
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let _ = sync(&Disk);
+            let _ = sync(&Net);
+        })
+        .unwrap();
+    assert_eq!(report.paths().len(), 4);
+    assert_eq!(report.failpoints().len(), 2);
+}
+
+#[test]
+fn test_exploration_version() {
+    fn open() -> Result<(), usize> {
+        inject_return!("open", Err(1));
+        Ok(())
+    }
+
+    fn write(block: usize) -> io::Result<()> {
+        inject_return_io_error!("write", fails_if: "disk full");
+        if block == 1 {
+            inject_exhaust!("disk full");
+        }
+        Ok(())
+    }
+
+    fn rollback() -> Result<(), usize> {
+        inject_return!("rollback", Err(1), requires_activated: "write");
+        Ok(())
+    }
+
+    fn fixture() {
+        if open().is_err() {
+            return;
+        }
+        for block in 0..3 {
+            if write(block).is_err() {
+                let _ = rollback();
+                return;
+            }
+        }
+        if open().is_err() {
+            let _ = rollback();
+        }
+    }
+
+    let list = |runner: Runner| {
+        let report = runner.run(fixture).unwrap();
+        assert_eq!(report.exploration_version(), Runner::exploration_version());
+        report
+            .paths()
+            .iter()
+            .map(|path| format!("{path}\n"))
+            .collect::<String>()
+    };
+    let schedule = Schedule::Phases(vec![(Duration::from_secs(3600), 0.3)]);
+    let listing = format!(
+        "# full\n{}# smoke\n{}# random\n{}",
+        list(Runner::ignore_env()),
+        list(Runner::smoke()),
+        list(
+            Runner::ignore_env()
+                .with_strategy(Strategy::Random { schedule, seed: 1 })
+                .with_max_iterations(8)
+        ),
+    );
+    assert!(
+        listing == include_str!("data/exploration.txt"),
+        "explored paths have changed; if this is intended, bump EXPLORATION_VERSION \
+         and update tests/data/exploration.txt with:\n{listing}"
+    );
+
+    let err = Runner::ignore_env()
+        .with_required_exploration_version(Runner::exploration_version() + 1)
+        .run(fixture)
+        .unwrap_err();
+    assert!(matches!(err, Error::Configuration(_)));
+}
+
+#[test]
+fn test_run_with_fixture() {
+    fn compact(free_space: u64) -> Result<(), usize> {
+        inject_return!("read segment", Err(1));
+        inject_return!("disk full", Err(2));
+        // the environment must agree with the injected fault
+        assert!(free_space >= 10);
+        inject_return!("sync", Err(3));
+        Ok(())
+    }
+
+    let explore = |runner: Runner| {
+        let plans = std::cell::RefCell::new(vec![]);
+        runner
+            .with_self_verification(1.0)
+            .run_with_fixture(
+                |plan| {
+                    plans.borrow_mut().push(plan.clone());
+                    if plan.activates("disk full") { 1 } else { 100 }
+                },
+                |free_space| {
+                    let _ = compact(free_space);
+                    let plan = plans.borrow().last().unwrap().clone();
+                    if plan.is_fully_known() {
+                        let activated: Vec<String> = current_path()
+                            .into_iter()
+                            .filter(|(_, branch)| *branch == Branch::Activate)
+                            .map(|(name, _)| name)
+                            .collect();
+                        assert_eq!(plan.activations(), activated);
+                    } else {
+                        assert!(plan.activations().is_empty());
+                    }
+                },
+            )
+            .unwrap();
+        plans.into_inner()
+    };
+
+    let plans = explore(Runner::smoke());
+    assert!(plans.iter().all(|plan| plan.is_fully_known()));
+    assert!(plans.iter().any(|plan| plan.activations() == ["disk full"]));
+
+    let plans = explore(Runner::ignore_env().with_branch_preference(Branch::Skip));
+    assert!(plans.iter().all(|plan| plan.is_fully_known()));
+
+    let plans = explore(Runner::ignore_env().with_priority_paths(&[&["disk full"]]));
+    assert_eq!(plans[0].activations(), ["disk full"]);
+
+    // in general exploration, only replays are planned
+    let plans = explore(Runner::ignore_env());
+    assert!(plans.iter().any(|plan| !plan.is_fully_known()));
+    assert!(plans.iter().any(|plan| plan.is_fully_known()));
+}
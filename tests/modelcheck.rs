@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::Runner;
+use faine::modelcheck::{MAX_NODES, Program, check};
+
+#[test]
+fn test_random_programs() {
+    for seed in 0..300 {
+        let program = Program::generate(seed, 1 + seed as usize % 12);
+        check(&program, Runner::ignore_env());
+    }
+}
+
+#[test]
+fn test_large_programs() {
+    for seed in 0..5 {
+        check(&Program::generate(seed, MAX_NODES), Runner::ignore_env());
+    }
+}
+
+#[test]
+fn test_explore_counts_paths() {
+    let program = Program::generate(7, 10);
+    let mut num_runs = 0;
+    let report = Runner::ignore_env()
+        .run(|| {
+            program.execute();
+            num_runs += 1;
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), num_runs);
+    assert_eq!(program.explore(Runner::ignore_env()), program.enumerate());
+}
+
+#[test]
+#[should_panic(expected = "exploration does not match enumeration")]
+fn test_detects_lost_states() {
+    check(
+        &Program::generate(7, 10),
+        Runner::ignore_env().with_max_iterations(3),
+    );
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::{Runner, inject_return, no_faults};
+
+#[no_faults]
+fn record(truth: &mut Vec<u32>, value: u32) -> Result<(), ()> {
+    inject_return!("record", Err(()));
+    if value == 0 {
+        return Ok(());
+    }
+    truth.push(value);
+    Ok(())
+}
+
+#[no_faults]
+fn record_panicking(truth: &mut Vec<u32>) {
+    inject_return!("record", ());
+    truth.push(0);
+    panic!("recording failed");
+}
+
+#[test]
+fn test_no_faults_attribute() {
+    let report = Runner::ignore_env()
+        .run(|| {
+            let mut truth = vec![];
+            record(&mut truth, 0).unwrap();
+            record(&mut truth, 1).unwrap();
+            // injection is restored on both early and normal returns
+            inject_return!("commit", ());
+            assert_eq!(truth, [1]);
+        })
+        .unwrap();
+    let paths: Vec<_> = report.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        paths,
+        [
+            "[commit] (suppressed by critical section: record)",
+            "commit (suppressed by critical section: record)",
+        ]
+    );
+
+    let report = Runner::ignore_env()
+        .run(|| {
+            let mut truth = vec![];
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                record_panicking(&mut truth)
+            }));
+            // injection is restored when unwinding
+            inject_return!("commit", ());
+            assert_eq!(truth, [0]);
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 2);
+    assert!(
+        report
+            .paths()
+            .iter()
+            .all(|path| !path.critical_section_held)
+    );
+}
@@ -0,0 +1,38 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Panic hook must be installed before faine installs its own, so
+//! this lives in a separate test binary.
+
+use std::sync::{Arc, Mutex};
+
+use faine::{Error, Runner, inject_return};
+
+#[test]
+fn test_caught_panics_reach_previous_hook() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let hook_messages = Arc::clone(&messages);
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(message) = info.payload_as_str() {
+            hook_messages.lock().unwrap().push(message.to_string());
+        }
+    }));
+
+    let err = Runner::ignore_env()
+        .with_catch_panics(true)
+        .run(|| {
+            let res = (|| -> Result<(), ()> {
+                inject_return!("load", Err(()));
+                Ok(())
+            })();
+            res.expect("load failed");
+        })
+        .unwrap_err();
+    let Error::UnexpectedPanics { report, .. } = err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(report.panicking_paths().len(), 1);
+    // the lock must not be held while the assertion may panic
+    let messages = messages.lock().unwrap().clone();
+    assert_eq!(messages, ["load failed: ()"]);
+}
@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::process::Command;
+
+use faine::Runner;
+use faine::process::FaineCommand;
+
+/// Count files reported by `git status`
+fn count_changed_files(repo: &Path) -> io::Result<usize> {
+    let output = FaineCommand::new("git status", "git")
+        .args(["status", "--porcelain"])
+        .current_dir(repo)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("git status failed"));
+    }
+    if !output.stdout.is_empty() && !output.stdout.ends_with(b"\n") {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "truncated git status output",
+        ));
+    }
+    Ok(output.stdout.split(|c| *c == b'\n').count() - 1)
+}
+
+#[test]
+fn test_git_status() {
+    if Command::new("git").arg("--version").output().is_err() {
+        eprintln!("git is not available, skipping test");
+        return;
+    }
+
+    let tempdir = tempfile::tempdir().unwrap();
+    assert!(
+        Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(tempdir.path())
+            .status()
+            .unwrap()
+            .success()
+    );
+    std::fs::write(tempdir.path().join("abc"), "").unwrap();
+    std::fs::write(tempdir.path().join("d"), "").unwrap();
+
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            results.push(count_changed_files(tempdir.path()).map_err(|err| err.kind()));
+        })
+        .unwrap();
+
+    assert_eq!(results.len(), 5);
+    assert_eq!(
+        results[..3],
+        [
+            Err(ErrorKind::NotFound),
+            Err(ErrorKind::PermissionDenied),
+            Err(ErrorKind::Other),
+        ]
+    );
+    // output may be cut at a line boundary, which is indistinguishable
+    // from a complete one
+    assert!(matches!(
+        results[3],
+        Err(ErrorKind::InvalidData) | Ok(0) | Ok(1)
+    ));
+    assert_eq!(results[4], Ok(2));
+    assert!(format!("{report:#}").contains("[git status=truncated stdout]"));
+}
+
+#[test]
+fn test_truncated_stdout_limit() {
+    if Command::new("git").arg("--version").output().is_err() {
+        eprintln!("git is not available, skipping test");
+        return;
+    }
+
+    let version = || {
+        FaineCommand::new("git version", "git")
+            .arg("--version")
+            .with_truncated_stdout_limit(4)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| output.stdout)
+    };
+    let full = version().unwrap();
+
+    let mut truncated = vec![];
+    Runner::default()
+        .run(|| {
+            if let Some(stdout) = version()
+                && stdout != full
+            {
+                truncated.push(stdout);
+            }
+        })
+        .unwrap();
+    assert_eq!(truncated.len(), 1);
+    assert!(truncated[0].len() <= 4);
+    assert!(full.starts_with(&truncated[0]));
+}
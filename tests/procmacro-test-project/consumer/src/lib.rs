@@ -0,0 +1,12 @@
+/// Greeting expanded at compile time
+pub fn hello() -> &'static str {
+    greeting::greeting!("world")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_hello() {
+        assert_eq!(super::hello(), "Hello, world!");
+    }
+}
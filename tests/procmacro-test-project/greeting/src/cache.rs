@@ -0,0 +1,66 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Failpoint which is only compiled in for unit tests, so the macro
+/// crate itself does not depend on faine
+macro_rules! failpoint {
+    ($name:literal) => {
+        #[cfg(test)]
+        faine::inject_return_io_error!($name);
+    };
+}
+
+/// Render expansion without cache
+pub fn render(name: &str) -> String {
+    format!("\"Hello, {name}!\"")
+}
+
+/// Render expansion, reusing one cached in a directory
+pub fn expand(cache_dir: &Path, name: &str) -> io::Result<String> {
+    let path = cache_dir.join(format!("{name}.rs"));
+    failpoint!("read cache");
+    match fs::read_to_string(&path) {
+        Ok(cached) => return Ok(cached),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    let expansion = render(name);
+    failpoint!("create cache dir");
+    fs::create_dir_all(cache_dir)?;
+    let temp_path = path.with_extension("tmp");
+    failpoint!("write cache");
+    fs::write(&temp_path, &expansion)?;
+    failpoint!("replace cache");
+    fs::rename(&temp_path, &path)?;
+    Ok(expansion)
+}
+
+#[cfg(test)]
+mod tests {
+    use faine::Runner;
+
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let report = Runner::default()
+            .with_managed_tempdir()
+            .run(|| {
+                let cache_dir = faine::tempdir().join("cache");
+                for _ in 0..2 {
+                    let Ok(expansion) = expand(&cache_dir, "world") else {
+                        return;
+                    };
+                    assert_eq!(expansion, render("world"));
+                }
+                // cache is either missing or complete
+                if let Ok(cached) = fs::read_to_string(cache_dir.join("world.rs")) {
+                    assert_eq!(cached, render("world"));
+                }
+            })
+            .unwrap();
+        assert!(report.num_paths() > 5);
+    }
+}
@@ -0,0 +1,15 @@
+use proc_macro::TokenStream;
+
+mod cache;
+
+/// Expand into a greeting string literal, caching expansions on disk
+#[proc_macro]
+pub fn greeting(input: TokenStream) -> TokenStream {
+    let input = input.to_string();
+    let name = input.trim_matches('"');
+    let cache_dir = std::env::temp_dir().join("faine-procmacro-test-project");
+    cache::expand(&cache_dir, name)
+        .unwrap_or_else(|_| cache::render(name))
+        .parse()
+        .expect("expansion must be valid tokens")
+}
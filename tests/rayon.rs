@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::thread::{self, ThreadId};
+
+use faine::{Runner, deterministic_parallel, inject_return};
+use rayon::prelude::*;
+
+fn process(item: u32) -> Result<u32, ()> {
+    inject_return!("process item", Err(()));
+    Ok(item * 2)
+}
+
+fn process_all(items: &[u32]) -> (Vec<ThreadId>, Vec<Result<u32, ()>>) {
+    deterministic_parallel(|| {
+        items
+            .par_iter()
+            .map(|&item| (thread::current().id(), process(item)))
+            .unzip()
+    })
+}
+
+#[test]
+fn test_outside_runner() {
+    let (_, results) = process_all(&[1, 2, 3]);
+    assert_eq!(results, vec![Ok(2), Ok(4), Ok(6)]);
+}
+
+#[test]
+fn test_deterministic_parallel() {
+    let explore = || {
+        let mut num_failed = BTreeMap::new();
+        let report = Runner::ignore_env()
+            .run(|| {
+                let (threads, results) = process_all(&[1, 2, 3]);
+                assert!(threads.iter().all(|id| *id == thread::current().id()));
+                *num_failed
+                    .entry(results.iter().filter(|res| res.is_err()).count())
+                    .or_insert(0) += 1;
+            })
+            .unwrap();
+        let keys: Vec<_> = report.paths().iter().map(|path| path.key()).collect();
+        (keys, num_failed)
+    };
+    let (keys, num_failed) = explore();
+    assert_eq!(keys.len(), 8);
+    assert_eq!(num_failed, BTreeMap::from([(0, 1), (1, 3), (2, 3), (3, 1)]));
+    assert_eq!(explore().0, keys);
+}
+
+#[test]
+fn test_nested() {
+    let report = Runner::ignore_env()
+        .run(|| {
+            let results: Vec<_> = deterministic_parallel(|| {
+                [[1, 2], [3, 4]]
+                    .par_iter()
+                    .map(|items| process_all(items).1)
+                    .collect()
+            });
+            assert_eq!(results.len(), 2);
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 16);
+}
@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::sync::TryLockError;
+use std::thread;
+use std::time::Duration;
+
+use faine::sync::mpsc::{TryRecvError, TrySendError, WouldBlock};
+use faine::sync::{FaineMutex, mpsc};
+use faine::{FailpointId, Runner, intern};
+
+#[test]
+fn test_mutex_poisoned() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            let counter = FaineMutex::new(intern!("counter"), 0);
+            let recovered = match counter.lock() {
+                Ok(mut guard) => {
+                    *guard += 1;
+                    false
+                }
+                Err(poisoned) => {
+                    *poisoned.into_inner() += 1;
+                    true
+                }
+            };
+            results.push((recovered, counter.into_inner().unwrap()));
+        })
+        .unwrap();
+
+    assert_eq!(results, vec![(true, 1), (false, 1)]);
+    assert_eq!(
+        report
+            .activated_failpoints()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["counter"]
+    );
+    assert_eq!(
+        FailpointId::intern("counter").crate_name(),
+        Some(env!("CARGO_PKG_NAME"))
+    );
+}
+
+#[test]
+fn test_mutex_try_lock() {
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            let mutex = FaineMutex::new("mutex", ());
+            let guard = mutex.lock().unwrap_or_else(|err| err.into_inner());
+            // does not visit the failpoint as the lock is held
+            assert!(matches!(mutex.try_lock(), Err(TryLockError::WouldBlock)));
+            drop(guard);
+            results.push(matches!(mutex.try_lock(), Err(TryLockError::Poisoned(_))));
+        })
+        .unwrap();
+
+    assert_eq!(results, vec![true, false, true, false]);
+}
+
+#[test]
+fn test_channel_disconnected() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            let (tx, rx) = mpsc::channel("results");
+            let sent = thread::scope(|scope| scope.spawn(|| tx.send(1).is_ok()).join().unwrap());
+            results.push((sent, rx.try_recv().ok()));
+        })
+        .unwrap();
+
+    // failpoints are not visited from other threads
+    assert_eq!(results, vec![(true, None), (true, Some(1))]);
+    assert!(format!("{report:#}").contains("[results=recv disconnected]"));
+
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            let (tx, rx) = mpsc::channel("results");
+            let sent = tx.send(1).is_ok();
+            drop(tx);
+            results.push((sent, rx.iter().collect::<Vec<_>>()));
+        })
+        .unwrap();
+
+    assert_eq!(
+        results,
+        vec![
+            (false, vec![]),
+            (false, vec![]),
+            (true, vec![]),
+            (true, vec![1]),
+            (true, vec![1]),
+        ]
+    );
+}
+
+#[test]
+fn test_sync_channel_backpressure() {
+    // producer sheds jobs the consumer cannot keep up with
+    fn produce(tx: &mpsc::SyncSender<u32>, jobs: &[u32]) -> usize {
+        let mut shed = 0;
+        for job in jobs {
+            match tx.try_send(*job) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => shed += 1,
+                Err(TrySendError::Disconnected(_)) => break,
+            }
+        }
+        shed
+    }
+
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            let (tx, rx) = mpsc::sync_channel("jobs", 4);
+            let shed = produce(&tx, &[1, 2]);
+            drop(tx);
+            results.push((shed, rx.iter().collect::<Vec<_>>()));
+        })
+        .unwrap();
+
+    assert!(results.contains(&(2, vec![])));
+    assert!(results.contains(&(1, vec![2])));
+    assert!(results.contains(&(0, vec![1, 2])));
+    assert!(format!("{report:#}").contains("[jobs=send full]"));
+}
+
+#[test]
+fn test_sync_channel_empty() {
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            let (tx, rx) = mpsc::sync_channel("queue", 1);
+            if tx.send(1).is_err() {
+                return;
+            }
+            drop(tx);
+            results.push((
+                rx.try_recv(),
+                rx.recv_timeout(Duration::from_secs(60)).is_ok(),
+            ));
+        })
+        .unwrap();
+
+    // injected empty queue does not consume the value
+    assert!(results.contains(&(Err(TryRecvError::Empty), true)));
+    assert!(results.contains(&(Ok(1), false)));
+
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            let (tx, rx) = mpsc::sync_channel("queue", 1);
+            let tx = tx.with_would_block(WouldBlock::Error);
+            let rx = rx.with_would_block(WouldBlock::Error);
+            let sent = tx.send(1).is_ok();
+            drop(tx);
+            results.push((sent, rx.recv().ok()));
+        })
+        .unwrap();
+
+    assert!(results.contains(&(false, None)));
+    assert!(results.contains(&(true, None)));
+    assert_eq!(results.last(), Some(&(true, Some(1))));
+}
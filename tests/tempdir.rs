@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use faine::{Runner, inject_return_io_error, tempdir};
+
+fn save(name: &str, data: &str) -> io::Result<()> {
+    inject_return_io_error!("create dir");
+    fs::create_dir_all(tempdir().join("state"))?;
+    inject_return_io_error!("write");
+    fs::write(tempdir().join("state").join(name), data)
+}
+
+#[test]
+fn test_managed_tempdir() {
+    let mut paths = HashSet::<PathBuf>::new();
+    let report = Runner::ignore_env()
+        .with_managed_tempdir()
+        .with_strict_determinism(true)
+        .run(|| {
+            let tempdir = tempdir();
+            paths.insert(tempdir.to_path_buf());
+            assert!(tempdir.is_dir());
+            assert_eq!(fs::read_dir(tempdir).unwrap().count(), 0);
+            let _ = save("a", "hello");
+            let _ = save("b", "world");
+        })
+        .unwrap();
+    assert_eq!(report.num_paths(), 9);
+    assert_eq!(paths.len(), 1);
+    assert!(!paths.iter().next().unwrap().exists());
+}
+
+#[test]
+fn test_tempdirs_of_runs_differ() {
+    let mut first = None;
+    Runner::ignore_env()
+        .with_managed_tempdir()
+        .run(|| first = Some(tempdir().to_path_buf()))
+        .unwrap();
+    let mut second = None;
+    Runner::ignore_env()
+        .with_managed_tempdir()
+        .run(|| second = Some(tempdir().to_path_buf()))
+        .unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+#[should_panic(expected = "temporary directory is not managed")]
+fn test_tempdir_not_managed() {
+    Runner::ignore_env()
+        .run(|| {
+            tempdir();
+        })
+        .unwrap();
+}
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io;
+
+use faine::inject_return_io_error;
+
+fn load_config() -> io::Result<String> {
+    inject_return_io_error!("read config");
+    Ok("debug".to_string())
+}
+
+#[faine::test]
+fn test_plain() {
+    if let Ok(config) = load_config() {
+        assert_eq!(config, "debug");
+    }
+}
+
+#[faine::test]
+fn test_result() -> io::Result<()> {
+    let config = load_config().or_else(|_| io::Result::Ok("default".to_string()))?;
+    assert!(config == "debug" || config == "default");
+    Ok(())
+}
+
+#[faine::test]
+#[should_panic(expected = "panic message: test function returned an error")]
+fn test_result_failing() -> io::Result<()> {
+    load_config()?;
+    Ok(())
+}
+
+#[faine::test]
+#[should_panic(expected = "failing path: [read config]")]
+fn test_panicking() {
+    assert_eq!(load_config().unwrap(), "debug");
+}
+
+#[faine::test]
+#[allow(clippy::unused_unit)]
+fn test_unit() -> () {
+    let _ = load_config();
+}
+
+mod imported {
+    use faine::test;
+
+    use super::load_config;
+
+    #[test]
+    fn test_explored() {
+        let _ = load_config();
+    }
+
+    #[::core::prelude::v1::test]
+    fn test_builtin() {
+        assert!(!faine::is_active());
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use faine::testsupport::{
+    assert_all_failpoints_activated, assert_explores_at_least, run_and_summarize,
+};
+use faine::{Runner, inject_return};
+
+fn foo() -> Result<(), usize> {
+    inject_return!("1", Err(1));
+    inject_return!("2", Err(2));
+    Ok(())
+}
+
+#[test]
+fn test_run_and_summarize() {
+    let report = run_and_summarize(Runner::default(), || {
+        let _ = foo();
+    });
+    assert_eq!(report.num_paths(), 3);
+}
+
+#[test]
+fn test_explores_at_least() {
+    assert_explores_at_least(3, Runner::default(), || {
+        let _ = foo();
+    });
+}
+
+#[test]
+#[should_panic(expected = "expected at least 4 explored path(s), got 3")]
+fn test_explores_at_least_fails() {
+    assert_explores_at_least(4, Runner::default(), || {
+        let _ = foo();
+    });
+}
+
+#[test]
+fn test_all_failpoints_activated() {
+    assert_all_failpoints_activated(Runner::default(), || {
+        let _ = foo();
+    });
+}
@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::process::Command;
+use std::time::Duration;
+
+use faine::{Runner, inject_return};
+
+fn connect() -> Result<(), ()> {
+    inject_return!("connect", Err(()));
+    Ok(())
+}
+
+// enforced timeout aborts the process, so the hanging runner is
+// executed by a child process running this same test
+#[test]
+fn test_enforced_iteration_timeout() {
+    if std::env::var_os("FAINE_TEST_HANG").is_some() {
+        let _ = Runner::ignore_env()
+            .with_enforced_iteration_timeout(Duration::from_millis(50))
+            .with_capture_output(true)
+            .run(|| {
+                let _ = connect();
+                while connect().is_err() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            });
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "test_enforced_iteration_timeout", "--nocapture"])
+        .env("FAINE_TEST_HANG", "1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "faine: iteration timed out after 50ms, aborting: [connect] → [connect] → [connect]"
+        ),
+        "{stderr}"
+    );
+    assert!(stderr.contains(" (timed out), key: [connect]"), "{stderr}");
+}
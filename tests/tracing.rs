@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
+
+use faine::{Runner, inject_return};
+
+static EVENTS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+struct Collector;
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        EVENTS
+            .lock()
+            .unwrap()
+            .push((*event.metadata().level(), visitor.0));
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn transfer(accounts: &mut [u32; 2], amount: u32) -> Result<(), ()> {
+    tracing::info!("transfer {amount}");
+    inject_return!("debit", Err(()));
+    accounts[0] -= amount;
+    tracing::trace!("debited {amount}");
+    // debit is not rolled back on failure
+    inject_return!("credit", Err(()));
+    accounts[1] += amount;
+    tracing::trace!("credited {amount}");
+    Ok(())
+}
+
+#[test]
+fn test_tracing_reload_handle() {
+    let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    tracing::subscriber::set_global_default(Collector.with(filter)).unwrap();
+
+    Runner::ignore_env()
+        .with_catch_panics(true)
+        .with_expected_panics(&[("tests/tracing.rs", line!() + 5)])
+        .with_tracing_reload_handle(handle.clone())
+        .run(|| {
+            let mut accounts = [100, 0];
+            let _ = transfer(&mut accounts, 10);
+            assert_eq!(accounts.iter().sum::<u32>(), 100);
+        })
+        .unwrap();
+    assert_eq!(handle.clone_current(), Some(LevelFilter::INFO));
+
+    // 3 explored paths and a single rerun, only the latter is verbose
+    let events = EVENTS.lock().unwrap();
+    let count = |level| events.iter().filter(|(l, _)| *l == level).count();
+    assert_eq!(count(Level::INFO), 4);
+    let trace: Vec<_> = events
+        .iter()
+        .filter(|(level, _)| *level == Level::TRACE)
+        .map(|(_, message)| message.as_str())
+        .collect();
+    assert_eq!(trace, ["debited 10"]);
+}
@@ -0,0 +1,13 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// diagnostics are compiler version specific; regenerate expected
+// outputs with TRYBUILD=overwrite after checking them
+#[test]
+fn test_ui() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass/*.rs");
+    cases.compile_fail("tests/ui/*.rs");
+    #[cfg(feature = "macros")]
+    cases.compile_fail("tests/ui/macros/*.rs");
+}
@@ -0,0 +1,14 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+fn apply(changes: &[&str]) {
+    for (index, _change) in changes.iter().enumerate() {
+        if faine::batch_cut!("apply changes", index) {
+            return;
+        }
+    }
+}
+
+fn main() {
+    apply(&["a", "b"]);
+}
@@ -0,0 +1,10 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+fn delay() -> u64 {
+    faine::inject_override_rng!(100, "backoff jitter", values: [])
+}
+
+fn main() {
+    let _ = delay();
+}
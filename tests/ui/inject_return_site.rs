@@ -0,0 +1,12 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// names defined by the expansion do not leak
+fn load() -> Result<usize, ()> {
+    faine::inject_return!(Err(()));
+    Ok(SITE.name().len())
+}
+
+fn main() {
+    let _ = load();
+}
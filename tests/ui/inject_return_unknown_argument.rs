@@ -0,0 +1,11 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+fn load() -> Result<(), ()> {
+    faine::inject_return!("load", Err(()), retries: 3);
+    Ok(())
+}
+
+fn main() {
+    let _ = load();
+}
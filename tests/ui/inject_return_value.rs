@@ -0,0 +1,12 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// evaluates to (), not to the injected value
+fn load() -> Result<usize, ()> {
+    let len: usize = faine::inject_return!(Err(()));
+    Ok(len)
+}
+
+fn main() {
+    let _ = load();
+}
@@ -0,0 +1,9 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#[faine::no_faults]
+async fn commit() {}
+
+fn main() {
+    let _ = commit();
+}
@@ -0,0 +1,7 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#[faine::test]
+fn explore(_iterations: usize) {}
+
+fn main() {}
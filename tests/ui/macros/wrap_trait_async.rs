@@ -0,0 +1,9 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#[faine::wrap_trait]
+trait Fetcher {
+    async fn fetch(&self) -> std::io::Result<Vec<u8>>;
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// crate compiled with failpoints
+mod storage {
+    faine::declare_instrumented!();
+}
+
+faine::assert_instrumented!(storage);
+
+fn main() {}
@@ -0,0 +1,178 @@
+// SPDX-FileCopyrightText: Copyright 2025 Dmitry Marakasov <amdmi3@amdmi3.ru>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Display;
+use std::io;
+
+use faine::{Runner, assert_injected_by, enable_failpoints, wrap_trait};
+
+#[derive(Debug)]
+struct DeviceError(#[allow(dead_code)] io::Error);
+
+impl From<io::Error> for DeviceError {
+    fn from(err: io::Error) -> Self {
+        Self(err)
+    }
+}
+
+impl std::fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "device error")
+    }
+}
+
+impl std::error::Error for DeviceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[wrap_trait]
+trait BlockDevice {
+    fn read(&self, block: u64) -> io::Result<Vec<u8>>;
+    fn write(&mut self, block: u64, data: &[u8]) -> Result<(), DeviceError>;
+    fn size(&self) -> u64;
+    fn describe(&self) -> String {
+        format!("{} blocks", self.size())
+    }
+}
+
+struct MemoryDevice {
+    blocks: Vec<Vec<u8>>,
+}
+
+impl BlockDevice for MemoryDevice {
+    fn read(&self, block: u64) -> io::Result<Vec<u8>> {
+        Ok(self.blocks[block as usize].clone())
+    }
+
+    fn write(&mut self, block: u64, data: &[u8]) -> Result<(), DeviceError> {
+        self.blocks[block as usize] = data.to_vec();
+        Ok(())
+    }
+
+    fn size(&self) -> u64 {
+        self.blocks.len() as u64
+    }
+
+    fn describe(&self) -> String {
+        "memory".to_string()
+    }
+}
+
+fn copy_block(device: &mut dyn BlockDevice) -> Result<(), DeviceError> {
+    let data = device.read(0)?;
+    device.write(1, &data)
+}
+
+#[test]
+fn test_wrap_trait() {
+    let mut results = vec![];
+    let report = Runner::default()
+        .run(|| {
+            let mut device: Box<dyn BlockDevice> =
+                Box::new(FaineWrappedBlockDevice(MemoryDevice {
+                    blocks: vec![vec![1; 4], vec![0; 4]],
+                }));
+            assert_eq!(device.size(), 2);
+            assert_eq!(device.describe(), "memory");
+            let res = copy_block(device.as_mut());
+            if res.is_err() {
+                assert_injected_by!(res, prefix = "BlockDevice::");
+            }
+            enable_failpoints(false);
+            results.push((res.is_ok(), device.read(1).unwrap()[0]));
+            enable_failpoints(true);
+        })
+        .unwrap();
+
+    assert_eq!(
+        report
+            .activated_failpoints()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["BlockDevice::read", "BlockDevice::write"]
+    );
+    assert_eq!(results, vec![(false, 0), (false, 0), (true, 1)]);
+}
+
+#[wrap_trait]
+trait Codec {
+    type Item;
+    const WIDTH: usize;
+
+    fn decode<D: Display>(&self, input: D) -> Result<Self::Item, DeviceError>;
+    fn into_inner(self) -> Vec<u8>;
+}
+
+struct Bytes(Vec<u8>);
+
+impl Codec for Bytes {
+    type Item = String;
+    const WIDTH: usize = 2;
+
+    fn decode<D: Display>(&self, input: D) -> Result<String, DeviceError> {
+        Ok(format!("{input}{:?}", self.0))
+    }
+
+    fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+#[test]
+fn test_wrap_trait_items() {
+    let mut results = vec![];
+    Runner::default()
+        .run(|| {
+            let codec = FaineWrappedCodec(Bytes(vec![1]));
+            assert_eq!(FaineWrappedCodec::<Bytes>::WIDTH, 2);
+            let item: Result<<FaineWrappedCodec<Bytes> as Codec>::Item, _> = codec.decode('x');
+            results.push(item.ok());
+            assert_eq!(codec.into_inner(), vec![1]);
+        })
+        .unwrap();
+
+    assert_eq!(results, vec![None, Some("x[1]".to_string())]);
+}
+
+type RenderResult<T> = Result<T, std::fmt::Error>;
+
+#[wrap_trait]
+trait Render {
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+    fn width(&self) -> RenderResult<usize>;
+}
+
+struct Label;
+
+impl Render for Label {
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "label")
+    }
+
+    fn width(&self) -> RenderResult<usize> {
+        Ok(5)
+    }
+}
+
+impl Display for FaineWrappedRender<Label> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.render(f)
+    }
+}
+
+#[test]
+fn test_wrap_trait_other_results() {
+    // errors of these cannot be converted from io::Error, so methods
+    // are passed through instead of failing to compile
+    let report = Runner::default()
+        .run(|| {
+            let label = FaineWrappedRender(Label);
+            assert_eq!(label.to_string(), "label");
+            assert_eq!(label.width(), Ok(5));
+        })
+        .unwrap();
+
+    assert_eq!(report.activated_failpoints().len(), 0);
+}